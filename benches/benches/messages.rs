@@ -0,0 +1,50 @@
+//! Parsing, framing, and partial stream assembly.
+//!
+//! Set `CLAUDE_BENCH_CORPUS` to a JSONL file of recorded CLI output, such as
+//! a wire log, to measure parsing on it instead of the built-in sessions.
+
+use claude_agents_sdk::bench::{self, Corpus};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn corpus() -> Corpus {
+    match std::env::var("CLAUDE_BENCH_CORPUS") {
+        Ok(path) => {
+            let text = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("reading {}: {}", path, e));
+            Corpus::from_jsonl(&text)
+        }
+        Err(_) => Corpus::builtin().repeat(50),
+    }
+}
+
+fn parse(c: &mut Criterion) {
+    let corpus = corpus();
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(corpus.bytes() as u64));
+    group.bench_function("messages", |b| {
+        b.iter(|| bench::parse_messages(black_box(&corpus)).unwrap())
+    });
+    for split in [64, 1024] {
+        group.bench_with_input(
+            BenchmarkId::new("split_lines", split),
+            &split,
+            |b, &split| b.iter(|| bench::frame_split_lines(black_box(&corpus), split).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn partial_stream(c: &mut Criterion) {
+    let mut group = c.benchmark_group("partial_stream");
+    for deltas in [100, 1000] {
+        let corpus = Corpus::partial_stream(deltas, 16);
+        group.throughput(Throughput::Elements(deltas as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(deltas), &corpus, |b, corpus| {
+            b.iter(|| bench::assemble_partial_stream(black_box(corpus)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, partial_stream);
+criterion_main!(benches);
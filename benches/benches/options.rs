@@ -0,0 +1,21 @@
+//! Turning options into the CLI's command line and environment.
+
+use claude_agents_sdk::bench;
+use claude_agents_sdk::ClaudeAgentOptions;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize_options");
+    let default = ClaudeAgentOptions::new();
+    group.bench_function("default", |b| {
+        b.iter(|| bench::serialize_options(black_box(&default)).unwrap())
+    });
+    let sample = bench::sample_options();
+    group.bench_function("sample", |b| {
+        b.iter(|| bench::serialize_options(black_box(&sample)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serialize);
+criterion_main!(benches);
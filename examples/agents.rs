@@ -6,7 +6,7 @@
 //! Run with: cargo run --example agents
 
 use claude_agents_sdk::{
-    query, AgentDefinition, AgentModel, ClaudeAgentOptions, ContentBlock, Message,
+    query, AgentDefinition, ClaudeAgentOptions, ContentBlock, Message, ModelAlias,
 };
 use std::collections::HashMap;
 use tokio_stream::StreamExt;
@@ -25,7 +25,10 @@ async fn code_reviewer_example() -> Result<(), Box<dyn std::error::Error>> {
                      Provide constructive feedback."
                 .to_string(),
             tools: Some(vec!["Read".to_string(), "Grep".to_string()]),
-            model: Some(AgentModel::Sonnet),
+            model: Some(ModelAlias::Sonnet.into()),
+            // No MCP servers: the reviewer only reads the code.
+            mcp_servers: Some(Vec::new()),
+            permission_mode: None,
         },
     );
 
@@ -79,7 +82,9 @@ async fn documentation_writer_example() -> Result<(), Box<dyn std::error::Error>
                 "Write".to_string(),
                 "Edit".to_string(),
             ]),
-            model: Some(AgentModel::Sonnet),
+            model: Some(ModelAlias::Sonnet.into()),
+            mcp_servers: None,
+            permission_mode: None,
         },
     );
 
@@ -133,6 +138,8 @@ async fn multiple_agents_example() -> Result<(), Box<dyn std::error::Error>> {
                 "Glob".to_string(),
             ]),
             model: None, // Use default model
+            mcp_servers: None,
+            permission_mode: None,
         },
     );
     agents.insert(
@@ -146,7 +153,9 @@ async fn multiple_agents_example() -> Result<(), Box<dyn std::error::Error>> {
                 "Write".to_string(),
                 "Bash".to_string(),
             ]),
-            model: Some(AgentModel::Sonnet),
+            model: Some(ModelAlias::Sonnet.into()),
+            mcp_servers: None,
+            permission_mode: None,
         },
     );
 
@@ -0,0 +1,13 @@
+//! Interactive chat example.
+//!
+//! Starts the ready-made chat loop from `repl`. Type `/help` for commands.
+//!
+//! Run with: cargo run --example chat
+
+use claude_agents_sdk::{repl, ClaudeAgentOptions};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    repl::run(ClaudeAgentOptions::new()).await?;
+    Ok(())
+}
@@ -58,6 +58,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Message::System(sys) => {
                 println!("SystemMessage: subtype={:?}", sys.subtype);
             }
+            Message::ParseWarning(warning) => {
+                println!("ParseWarning: {}", warning.message);
+            }
+            Message::Lazy(lazy) => {
+                println!("LazyMessage: type={:?}", lazy.msg_type());
+            }
             Message::Result(result) => {
                 println!(
                     "ResultMessage: subtype={:?}, cost={:?}",
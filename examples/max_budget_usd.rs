@@ -92,7 +92,7 @@ async fn with_tight_budget() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Status: {:?}", result.subtype);
 
                 // Check if budget was exceeded
-                if result.subtype == "error_max_budget_usd" {
+                if result.hit_max_budget() {
                     println!("⚠️  Budget limit exceeded!");
                     println!("Note: The cost may exceed the budget by up to one API call's worth");
                 }
@@ -11,7 +11,7 @@
 
 #[cfg(feature = "mcp")]
 use claude_agents_sdk::{
-    mcp::{create_sdk_mcp_server, SdkMcpTool, ToolInputSchema, ToolResult},
+    mcp::{SdkMcpServer, SdkMcpTool, ToolInputSchema, ToolResult},
     ClaudeAgentOptions, ClaudeClient, ContentBlock, Message, UserMessageContent,
 };
 #[cfg(feature = "mcp")]
@@ -82,7 +82,7 @@ fn display_message(msg: &Message) {
                 println!("Cost: ${:.6}", cost);
             }
         }
-        Message::StreamEvent(_) => {}
+        Message::StreamEvent(_) | Message::ParseWarning(_) | Message::Lazy(_) => {}
     }
 }
 
@@ -102,7 +102,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let result = a + b;
-            ToolResult::text(format!("{} + {} = {}", a, b, result))
+            Ok(ToolResult::text(format!("{} + {} = {}", a, b, result)))
         },
     );
 
@@ -118,7 +118,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let result = a - b;
-            ToolResult::text(format!("{} - {} = {}", a, b, result))
+            Ok(ToolResult::text(format!("{} - {} = {}", a, b, result)))
         },
     );
 
@@ -134,7 +134,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let result = a * b;
-            ToolResult::text(format!("{} × {} = {}", a, b, result))
+            Ok(ToolResult::text(format!("{} × {} = {}", a, b, result)))
         },
     );
 
@@ -150,10 +150,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
             if b == 0.0 {
-                return ToolResult::error("Division by zero is not allowed");
+                return Ok(ToolResult::error("Division by zero is not allowed"));
             }
             let result = a / b;
-            ToolResult::text(format!("{} ÷ {} = {}", a, b, result))
+            Ok(ToolResult::text(format!("{} ÷ {} = {}", a, b, result)))
         },
     );
 
@@ -166,13 +166,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         |input| async move {
             let n = input.get("n").and_then(|v| v.as_f64()).unwrap_or(0.0);
             if n < 0.0 {
-                return ToolResult::error(format!(
+                return Ok(ToolResult::error(format!(
                     "Cannot calculate square root of negative number {}",
                     n
-                ));
+                )));
             }
             let result = n.sqrt();
-            ToolResult::text(format!("√{} = {}", n, result))
+            Ok(ToolResult::text(format!("√{} = {}", n, result)))
         },
     );
 
@@ -191,13 +191,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .and_then(|v| v.as_f64())
                 .unwrap_or(0.0);
             let result = base.powf(exponent);
-            ToolResult::text(format!("{}^{} = {}", base, exponent, result))
+            Ok(ToolResult::text(format!(
+                "{}^{} = {}",
+                base, exponent, result
+            )))
         },
     );
 
     // Create the calculator server with all tools
-    let (_config, _tools) = create_sdk_mcp_server(
-        "calculator",
+    let calculator = SdkMcpServer::new(
+        "calc",
         "2.0.0",
         vec![
             add_tool,
@@ -209,17 +212,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ],
     );
 
-    // Configure Claude to use the calculator server with allowed tools
-    // Note: SDK MCP servers are not yet fully implemented in the control protocol.
-    // This example demonstrates the API but may not work end-to-end.
-    let options = ClaudeAgentOptions::new().with_allowed_tools(vec![
-        "mcp__calc__add".to_string(),
-        "mcp__calc__subtract".to_string(),
-        "mcp__calc__multiply".to_string(),
-        "mcp__calc__divide".to_string(),
-        "mcp__calc__sqrt".to_string(),
-        "mcp__calc__power".to_string(),
-    ]);
+    // Register the server and allow its tools
+    let options = ClaudeAgentOptions::new()
+        .with_sdk_mcp_server(calculator)
+        .allow_mcp_server("calc");
 
     // Example prompts to demonstrate calculator usage
     let prompts = [
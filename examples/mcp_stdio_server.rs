@@ -0,0 +1,64 @@
+//! Example: Standalone MCP server over stdio.
+//!
+//! This example exposes `SdkMcpTool` definitions as an external MCP server
+//! binary. Any MCP client can launch it, including the Claude CLI via a
+//! `stdio` entry in `mcp_servers`:
+//!
+//! ```json
+//! {"utils": {"type": "stdio", "command": "target/debug/examples/mcp_stdio_server"}}
+//! ```
+//!
+//! Run with: cargo run --example mcp_stdio_server --features mcp
+
+#[cfg(feature = "mcp")]
+use claude_agents_sdk::mcp::{SdkMcpServer, SdkMcpTool, ToolInputSchema, ToolResult};
+
+#[cfg(not(feature = "mcp"))]
+fn main() {
+    eprintln!("This example requires the 'mcp' feature.");
+    eprintln!("Run with: cargo run --example mcp_stdio_server --features mcp");
+    std::process::exit(1);
+}
+
+#[cfg(feature = "mcp")]
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let reverse = SdkMcpTool::new(
+        "reverse",
+        "Reverse a string",
+        ToolInputSchema::object()
+            .string_property("text", "Text to reverse")
+            .required_property("text"),
+        |input| async move {
+            let text = input["text"].as_str().unwrap_or_default();
+            Ok(ToolResult::text(text.chars().rev().collect::<String>()))
+        },
+    )
+    .read_only();
+
+    let word_count = SdkMcpTool::new(
+        "word_count",
+        "Count the words in a string",
+        ToolInputSchema::object()
+            .string_property("text", "Text to count")
+            .required_property("text"),
+        |input| async move {
+            let count = input["text"]
+                .as_str()
+                .unwrap_or_default()
+                .split_whitespace()
+                .count();
+            Ok(ToolResult::structured(
+                serde_json::json!({ "words": count }),
+            ))
+        },
+    )
+    .read_only();
+
+    // stdout carries the protocol, so diagnostics go to stderr.
+    eprintln!("utils MCP server listening on stdio");
+
+    SdkMcpServer::new("utils", "1.0.0", vec![reverse, word_count])
+        .serve_stdio()
+        .await
+}
@@ -58,6 +58,13 @@ fn display_message(msg: &Message) {
         Message::StreamEvent(_) => {
             // Streaming events handled separately
         }
+        Message::ParseWarning(warning) => {
+            eprintln!("Skipped unparsable output: {}", warning.message);
+        }
+        Message::Lazy(lazy) => match lazy.get() {
+            Ok(parsed) => display_message(parsed),
+            Err(e) => eprintln!("Unparsable message: {}", e),
+        },
     }
 }
 
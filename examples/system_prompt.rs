@@ -8,9 +8,7 @@
 //!
 //! Run with: cargo run --example system_prompt
 
-use claude_agents_sdk::{
-    query, ClaudeAgentOptions, ContentBlock, Message, SystemPromptConfig, SystemPromptPreset,
-};
+use claude_agents_sdk::{query, ClaudeAgentOptions, ContentBlock, Message, SystemPromptConfig};
 use tokio_stream::StreamExt;
 
 /// Example with no system_prompt (vanilla Claude).
@@ -61,11 +59,7 @@ async fn preset_system_prompt() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== Preset System Prompt (Default) ===");
 
     let mut options = ClaudeAgentOptions::new();
-    options.system_prompt = Some(SystemPromptConfig::Preset(SystemPromptPreset {
-        preset_type: "preset".to_string(),
-        preset: "claude_code".to_string(),
-        append: None,
-    }));
+    options.system_prompt = Some(SystemPromptConfig::claude_code());
 
     let mut stream = query("What is 2 + 2?", Some(options)).await?;
 
@@ -88,11 +82,9 @@ async fn preset_with_append() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== Preset System Prompt with Append ===");
 
     let mut options = ClaudeAgentOptions::new();
-    options.system_prompt = Some(SystemPromptConfig::Preset(SystemPromptPreset {
-        preset_type: "preset".to_string(),
-        preset: "claude_code".to_string(),
-        append: Some("Always end your response with a fun fact.".to_string()),
-    }));
+    options.system_prompt = Some(SystemPromptConfig::claude_code_with_append(
+        "Always end your response with a fun fact.",
+    ));
 
     let mut stream = query("What is 2 + 2?", Some(options)).await?;
 
@@ -3,9 +3,7 @@
 //!
 //! Run with: cargo run --example tools_option
 
-use claude_agents_sdk::{
-    query, ClaudeAgentOptions, ContentBlock, Message, ToolsConfig, ToolsPreset,
-};
+use claude_agents_sdk::{query, ClaudeAgentOptions, ContentBlock, Message, ToolsConfig};
 use tokio_stream::StreamExt;
 
 /// Example with tools as array of specific tool names.
@@ -30,14 +28,11 @@ async fn tools_array_example() -> Result<(), Box<dyn std::error::Error>> {
 
     while let Some(message) = stream.next().await {
         match message? {
-            Message::System(sys) => {
-                if sys.subtype == "init" {
-                    if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
-                        let tool_names: Vec<&str> =
-                            tools.iter().filter_map(|t| t.as_str()).collect();
-                        println!("Tools from system message: {:?}", tool_names);
-                        println!();
-                    }
+            Message::System(sys) if sys.subtype == "init" => {
+                if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
+                    let tool_names: Vec<&str> = tools.iter().filter_map(|t| t.as_str()).collect();
+                    println!("Tools from system message: {:?}", tool_names);
+                    println!();
                 }
             }
             Message::Assistant(msg) => {
@@ -78,14 +73,11 @@ async fn tools_empty_array_example() -> Result<(), Box<dyn std::error::Error>> {
 
     while let Some(message) = stream.next().await {
         match message? {
-            Message::System(sys) => {
-                if sys.subtype == "init" {
-                    if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
-                        let tool_names: Vec<&str> =
-                            tools.iter().filter_map(|t| t.as_str()).collect();
-                        println!("Tools from system message: {:?}", tool_names);
-                        println!();
-                    }
+            Message::System(sys) if sys.subtype == "init" => {
+                if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
+                    let tool_names: Vec<&str> = tools.iter().filter_map(|t| t.as_str()).collect();
+                    println!("Tools from system message: {:?}", tool_names);
+                    println!();
                 }
             }
             Message::Assistant(msg) => {
@@ -115,10 +107,7 @@ async fn tools_preset_example() -> Result<(), Box<dyn std::error::Error>> {
     println!();
 
     let mut options = ClaudeAgentOptions::new();
-    options.tools = Some(ToolsConfig::Preset(ToolsPreset {
-        preset_type: "preset".to_string(),
-        preset: "claude_code".to_string(),
-    }));
+    options.tools = Some(ToolsConfig::claude_code());
     options.max_turns = Some(1);
 
     let mut stream = query(
@@ -129,18 +118,16 @@ async fn tools_preset_example() -> Result<(), Box<dyn std::error::Error>> {
 
     while let Some(message) = stream.next().await {
         match message? {
-            Message::System(sys) => {
-                if sys.subtype == "init" {
-                    if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
-                        let tool_names: Vec<&str> =
-                            tools.iter().filter_map(|t| t.as_str()).take(5).collect();
-                        println!(
-                            "Tools from system message ({} tools): {:?}...",
-                            tools.len(),
-                            tool_names
-                        );
-                        println!();
-                    }
+            Message::System(sys) if sys.subtype == "init" => {
+                if let Some(tools) = sys.data.get("tools").and_then(|v| v.as_array()) {
+                    let tool_names: Vec<&str> =
+                        tools.iter().filter_map(|t| t.as_str()).take(5).collect();
+                    println!(
+                        "Tools from system message ({} tools): {:?}...",
+                        tools.len(),
+                        tool_names
+                    );
+                    println!();
                 }
             }
             Message::Assistant(msg) => {
@@ -0,0 +1,10 @@
+//! Raw bytes as one line of CLI output.
+
+#![no_main]
+
+use claude_agents_sdk::_internal::message_parser::fuzz_parse;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = fuzz_parse(data);
+});
@@ -0,0 +1,16 @@
+//! CLI-shaped JSON with missing and mistyped fields.
+
+#![no_main]
+
+use claude_agents_sdk::_internal::fuzz::{cli_message, FuzzInput};
+use claude_agents_sdk::_internal::message_parser::parse_message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let raw = cli_message(&mut FuzzInput::new(data), false);
+    if let Ok(Some(message)) = parse_message(raw) {
+        let canonical = message.to_canonical_json();
+        let back = claude_agents_sdk::Message::from_canonical_json(&canonical).unwrap();
+        assert_eq!(back.to_canonical_json(), canonical);
+    }
+});
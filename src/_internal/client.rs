@@ -4,14 +4,22 @@
 //! the one-shot `query()` function and the streaming `ClaudeClient`.
 
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::sync::mpsc;
 use tokio_stream::Stream;
-use tracing::{debug, info};
-
-use super::query::Query;
-use super::transport::{SubprocessTransport, Transport};
+use tracing::{debug, info, Instrument};
+
+use super::query::{ControlHandle, Query};
+use super::telemetry;
+#[cfg(feature = "subprocess")]
+use super::transport::SubprocessTransport;
+use super::transport::Transport;
+use crate::control::ControlRequestPayload;
 use crate::errors::{ClaudeSDKError, Result};
+use crate::health::{HealthReport, HEALTH_CHECK_TIMEOUT};
+use crate::mcp_status::{self, McpServerStatus, ToolDescriptor};
+use crate::options_delta::{AppliedDelta, LiveChange, OptionsDelta};
 use crate::types::*;
 
 /// A stream that keeps the InternalClient alive while consuming messages.
@@ -21,11 +29,11 @@ use crate::types::*;
 pub struct ClientStream {
     #[allow(dead_code)]
     client: InternalClient,
-    receiver: tokio_stream::wrappers::ReceiverStream<Result<Message>>,
+    receiver: tokio_stream::wrappers::ReceiverStream<Result<Arc<Message>>>,
 }
 
 impl ClientStream {
-    fn new(client: InternalClient, rx: mpsc::Receiver<Result<Message>>) -> Self {
+    fn new(client: InternalClient, rx: mpsc::Receiver<Result<Arc<Message>>>) -> Self {
         Self {
             client,
             receiver: tokio_stream::wrappers::ReceiverStream::new(rx),
@@ -34,13 +42,18 @@ impl ClientStream {
 }
 
 impl Stream for ClientStream {
-    type Item = Result<Message>;
+    type Item = Result<Arc<Message>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         Pin::new(&mut self.receiver).poll_next(cx)
     }
 }
 
+/// Take a message out of its `Arc`, cloning only if it is still shared.
+pub(crate) fn unwrap_shared(message: Arc<Message>) -> Message {
+    Arc::try_unwrap(message).unwrap_or_else(|shared| (*shared).clone())
+}
+
 /// Internal client for processing Claude queries.
 ///
 /// This is the core implementation that handles communication with the CLI.
@@ -50,11 +63,13 @@ pub struct InternalClient {
     /// The query handler.
     query: Option<Query>,
     /// Message receiver from the query handler.
-    message_rx: Option<mpsc::Receiver<Result<Message>>>,
+    message_rx: Option<mpsc::Receiver<Result<Arc<Message>>>>,
     /// Options used for this client.
     options: ClaudeAgentOptions,
     /// Whether the client is connected.
     connected: bool,
+    /// Whether a CLI process has been spawned before, to count restarts.
+    spawned: bool,
 }
 
 impl InternalClient {
@@ -65,20 +80,13 @@ impl InternalClient {
             message_rx: None,
             options,
             connected: false,
+            spawned: false,
         }
     }
 
     /// Validate options before connecting.
     fn validate_options(&self) -> Result<()> {
-        // Check for mutually exclusive options
-        if self.options.can_use_tool.is_some() && self.options.permission_prompt_tool_name.is_some()
-        {
-            return Err(ClaudeSDKError::configuration(
-                "Cannot specify both 'can_use_tool' and 'permission_prompt_tool_name'",
-            ));
-        }
-
-        Ok(())
+        self.options.check()
     }
 
     /// Convert agent definitions to serializable format for the initialize request.
@@ -102,11 +110,23 @@ impl InternalClient {
             return Ok(());
         }
 
+        let metadata = telemetry::metadata_field(&self.options.metadata);
+        let span = telemetry::connect_span(self.options.model.as_deref(), metadata.as_deref());
+        let result = self.connect_inner().instrument(span.clone()).await;
+        telemetry::record_result(&span, &result);
+        result
+    }
+
+    async fn connect_inner(&mut self) -> Result<()> {
         self.validate_options()?;
 
         let agents_dict = Self::build_agents_dict(&self.options);
 
-        let mut transport = SubprocessTransport::new(&self.options)?;
+        let mut transport = match (&self.options.transport, &self.options.backend) {
+            (Some(factory), _) => factory(&self.options)?,
+            (None, Backend::Cli) => Self::subprocess_transport(&self.options)?,
+            (None, Backend::Api(api_key)) => Self::api_transport(&self.options, api_key)?,
+        };
         transport.connect().await?;
 
         // Create query handler with agents
@@ -123,11 +143,45 @@ impl InternalClient {
             debug!("CLI initialized: {:?}", response);
         }
 
+        if self.spawned {
+            crate::metrics::SessionMetrics::new(self.options.metrics.clone())
+                .subprocess_restarted();
+        }
+        self.spawned = true;
         self.connected = true;
         info!("Connected to Claude CLI");
         Ok(())
     }
 
+    /// The default transport: the CLI as a subprocess.
+    #[cfg(feature = "subprocess")]
+    fn subprocess_transport(options: &ClaudeAgentOptions) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(SubprocessTransport::new(options)?))
+    }
+
+    #[cfg(not(feature = "subprocess"))]
+    fn subprocess_transport(_options: &ClaudeAgentOptions) -> Result<Box<dyn Transport>> {
+        Err(ClaudeSDKError::configuration(
+            "No transport configured: enable the `subprocess` feature or set \
+             ClaudeAgentOptions::with_transport()",
+        ))
+    }
+
+    /// The transport running the agent loop against the API.
+    #[cfg(feature = "api")]
+    fn api_transport(options: &ClaudeAgentOptions, api_key: &str) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(super::transport::ApiTransport::new(
+            options, api_key,
+        )?))
+    }
+
+    #[cfg(not(feature = "api"))]
+    fn api_transport(_options: &ClaudeAgentOptions, _api_key: &str) -> Result<Box<dyn Transport>> {
+        Err(ClaudeSDKError::configuration(
+            "Backend::Api requires the `api` feature",
+        ))
+    }
+
     /// Process a one-shot query.
     ///
     /// Always uses streaming mode. Returns a stream of messages from the CLI.
@@ -135,18 +189,41 @@ impl InternalClient {
         options: ClaudeAgentOptions,
         prompt: &str,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Message>> + Send>>> {
-        // Validate options
-        if options.can_use_tool.is_some() && options.permission_prompt_tool_name.is_some() {
-            return Err(ClaudeSDKError::configuration(
-                "Cannot specify both 'can_use_tool' and 'permission_prompt_tool_name'",
-            ));
-        }
+        use tokio_stream::StreamExt;
+
+        let stream = Self::process_query_shared(options, prompt).await?;
+        Ok(Box::pin(stream.map(|message| message.map(unwrap_shared))))
+    }
+
+    /// Process a one-shot query, yielding messages as they were parsed.
+    pub async fn process_query_shared(
+        options: ClaudeAgentOptions,
+        prompt: &str,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Arc<Message>>> + Send>>> {
+        Self::process_query_content(options, serde_json::Value::String(prompt.to_string())).await
+    }
+
+    /// Process a one-shot query whose prompt is user message content, a
+    /// string or an array of content blocks.
+    pub async fn process_query_content(
+        options: ClaudeAgentOptions,
+        content: serde_json::Value,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Arc<Message>>> + Send>>> {
+        options.check()?;
 
-        let has_hooks_or_callbacks = options.can_use_tool.is_some() || options.hooks.is_some();
+        #[cfg(feature = "mcp")]
+        let has_sdk_mcp_servers = !options.sdk_mcp_servers.is_empty();
+        #[cfg(not(feature = "mcp"))]
+        let has_sdk_mcp_servers = false;
+
+        let has_hooks_or_callbacks = options.can_use_tool.is_some()
+            || options.hooks.is_some()
+            || options.file_diffs
+            || has_sdk_mcp_servers;
 
         let mut client = InternalClient::new(options);
         client.connect().await?;
-        client.send_message(prompt).await?;
+        client.send_content(content).await?;
 
         if has_hooks_or_callbacks {
             // For queries with hooks/callbacks, stdin must stay open for
@@ -176,6 +253,31 @@ impl InternalClient {
         query.send_message(message).await
     }
 
+    /// Send a user message with the given content.
+    pub async fn send_content(&mut self, content: serde_json::Value) -> Result<()> {
+        let query = self
+            .query
+            .as_ref()
+            .ok_or_else(|| ClaudeSDKError::cli_connection("Client not connected"))?;
+
+        query.send_content(content).await
+    }
+
+    /// Send the result of a tool call the host executed itself.
+    pub async fn send_tool_result(
+        &mut self,
+        tool_use_id: &str,
+        content: serde_json::Value,
+        is_error: bool,
+    ) -> Result<()> {
+        let query = self
+            .query
+            .as_ref()
+            .ok_or_else(|| ClaudeSDKError::cli_connection("Client not connected"))?;
+
+        query.send_tool_result(tool_use_id, content, is_error).await
+    }
+
     /// Enable closing stdin when a Result message is received.
     fn set_close_stdin_on_result(&self, value: bool) {
         if let Some(ref q) = self.query {
@@ -194,7 +296,7 @@ impl InternalClient {
     }
 
     /// Get the message receiver.
-    pub fn take_message_rx(&mut self) -> Option<mpsc::Receiver<Result<Message>>> {
+    pub fn take_message_rx(&mut self) -> Option<mpsc::Receiver<Result<Arc<Message>>>> {
         self.message_rx.take()
     }
 
@@ -228,6 +330,60 @@ impl InternalClient {
         query.set_model(model).await
     }
 
+    /// Apply the changes the CLI accepts while running, undoing them all if
+    /// one fails, and save every change in the options for the next
+    /// connect.
+    pub async fn apply_options_delta(&mut self, delta: OptionsDelta) -> Result<AppliedDelta> {
+        let query = self
+            .query
+            .as_ref()
+            .ok_or_else(|| ClaudeSDKError::cli_connection("Client not connected"))?;
+
+        let plan = delta.plan(&self.options, query.launch_allowed_tools());
+        let mut applied: Vec<&LiveChange> = Vec::new();
+        for change in &plan.live {
+            if let Err(e) = Self::apply_live(query, change, false).await {
+                for done in applied.into_iter().rev() {
+                    if let Err(undo) = Self::apply_live(query, done, true).await {
+                        debug!("Failed to undo {} change: {}", done.field(), undo);
+                    }
+                }
+                return Err(e);
+            }
+            applied.push(change);
+        }
+
+        delta.write_to(&mut self.options);
+        Ok(AppliedDelta {
+            applied: plan.live.iter().map(LiveChange::field).collect(),
+            requires_reconnect: plan.reconnect,
+        })
+    }
+
+    /// Apply a live change, or restore the value it replaced.
+    async fn apply_live(query: &Query, change: &LiveChange, undo: bool) -> Result<()> {
+        match change {
+            LiveChange::Model { to, from } => {
+                let model = if undo { from.clone() } else { Some(to.clone()) };
+                query.set_model_or_default(model).await
+            }
+            LiveChange::PermissionMode { to, from } => {
+                query
+                    .set_permission_mode(if undo { *from } else { *to })
+                    .await
+            }
+            LiveChange::MaxThinkingTokens { to, from } => {
+                query
+                    .set_max_thinking_tokens(if undo { *from } else { *to })
+                    .await
+            }
+            LiveChange::AllowedTools { to, from } => {
+                query.set_granted_tools(if undo { from.clone() } else { to.clone() });
+                Ok(())
+            }
+        }
+    }
+
     /// Rewind files to a specific user message.
     pub async fn rewind_files(&self, user_message_id: impl Into<String>) -> Result<()> {
         let query = self
@@ -247,6 +403,14 @@ impl InternalClient {
         query.get_server_info().await
     }
 
+    /// A handle for sending control requests from other tasks.
+    pub fn control_handle(&self) -> Result<ControlHandle> {
+        self.query
+            .as_ref()
+            .map(Query::control_handle)
+            .ok_or_else(|| ClaudeSDKError::cli_connection("Client not connected"))
+    }
+
     /// Get current MCP server connection status.
     pub async fn get_mcp_status(&self) -> Result<serde_json::Value> {
         let query = self
@@ -257,6 +421,16 @@ impl InternalClient {
         query.get_mcp_status().await
     }
 
+    /// Get the status of each MCP server.
+    pub async fn mcp_servers(&self) -> Result<Vec<McpServerStatus>> {
+        self.control_handle()?.mcp_servers().await
+    }
+
+    /// Get the tools an MCP server advertises.
+    pub async fn mcp_tools(&self, server: &str) -> Result<Vec<ToolDescriptor>> {
+        self.control_handle()?.mcp_tools(server).await
+    }
+
     /// Disconnect from the CLI.
     pub async fn disconnect(&mut self) -> Result<()> {
         if !self.connected {
@@ -275,10 +449,130 @@ impl InternalClient {
         Ok(())
     }
 
+    /// Latency and throughput statistics for the current connection.
+    pub fn stats(&self) -> crate::stats::SessionStats {
+        self.query.as_ref().map(Query::stats).unwrap_or_default()
+    }
+
+    /// File checkpoints recorded so far, oldest first.
+    pub fn checkpoints(&self) -> Vec<crate::checkpoints::Checkpoint> {
+        self.query
+            .as_ref()
+            .map(Query::checkpoints)
+            .unwrap_or_default()
+    }
+
+    /// The agent's latest task list.
+    pub fn plan(&self) -> Vec<crate::plan::TodoItem> {
+        self.query.as_ref().map(Query::plan).unwrap_or_default()
+    }
+
+    /// Subagent runs seen so far, in the order they started.
+    pub fn subagents(&self) -> Vec<crate::subagents::Subagent> {
+        self.query
+            .as_ref()
+            .map(Query::subagents)
+            .unwrap_or_default()
+    }
+
+    /// The CLI's session ID, once it has reported one.
+    pub fn session_id(&self) -> Option<String> {
+        self.query.as_ref().and_then(Query::session_id)
+    }
+
+    /// Find the session last run in the options' working directory and
+    /// resume it on the next connect.
+    pub fn resume_most_recent(&mut self) -> Result<crate::sessions::SessionInfo> {
+        use crate::sessions::{Session, CLAUDE_CONFIG_DIR_ENV};
+
+        if self.connected {
+            return Err(ClaudeSDKError::cli_connection("Client already connected"));
+        }
+        let cwd = match &self.options.cwd {
+            Some(cwd) => cwd.clone(),
+            None => std::env::current_dir()?,
+        };
+        let config_dir = self
+            .options
+            .env
+            .get(CLAUDE_CONFIG_DIR_ENV)
+            .map(std::path::PathBuf::from)
+            .or_else(Session::config_dir)
+            .ok_or_else(|| {
+                ClaudeSDKError::configuration("No home directory to find sessions in")
+            })?;
+        let session = Session::most_recent_in(&config_dir, &cwd).ok_or_else(|| {
+            ClaudeSDKError::configuration(format!(
+                "No saved session for {} in {}",
+                cwd.display(),
+                config_dir.display()
+            ))
+        })?;
+        self.options.resume = Some(session.session_id.clone());
+        self.options.continue_conversation = false;
+        Ok(session)
+    }
+
+    /// Options for a new client that continues this session as a fork.
+    pub fn fork_options(&self) -> Result<ClaudeAgentOptions> {
+        if !self.connected {
+            return Err(ClaudeSDKError::cli_connection("Client not connected"));
+        }
+        let session_id = self.session_id().ok_or_else(|| {
+            ClaudeSDKError::control_protocol("No session to fork yet; send a query first")
+        })?;
+        let mut options = self.options.clone();
+        options.resume = Some(session_id);
+        options.continue_conversation = false;
+        options.fork_session = true;
+        Ok(options)
+    }
+
+    /// Check the CLI version, the control protocol, MCP servers, and the
+    /// CLI process.
+    pub async fn health(&self) -> HealthReport {
+        let version = check_cli_version(self.options.cli_path.as_deref());
+        let Some(query) = self.query.as_ref().filter(|_| self.connected) else {
+            return HealthReport::disconnected(version.await.ok());
+        };
+
+        let handle = query.control_handle();
+        let status = async {
+            let start = handle.clock().now();
+            let status = handle
+                .request_with_timeout(ControlRequestPayload::McpStatus, Some(HEALTH_CHECK_TIMEOUT))
+                .await?;
+            Ok::<_, ClaudeSDKError>((handle.clock().now() - start, status))
+        };
+        let (version, status, subprocess_alive) =
+            tokio::join!(version, status, query.is_process_alive());
+
+        let (protocol_rtt, mcp_servers) = match status {
+            Ok((rtt, status)) => (
+                Some(rtt),
+                mcp_status::parse_servers(status).unwrap_or_default(),
+            ),
+            Err(_) => (None, Vec::new()),
+        };
+        HealthReport {
+            cli_version: version.ok(),
+            protocol_ok: protocol_rtt.is_some(),
+            protocol_rtt,
+            mcp_servers,
+            last_activity: query.last_activity(),
+            subprocess_alive,
+        }
+    }
+
     /// Check if connected.
     pub fn is_connected(&self) -> bool {
         self.connected
     }
+
+    /// Whether connected and the CLI is still talking to us.
+    pub fn is_alive(&self) -> bool {
+        self.connected && self.query.as_ref().is_some_and(Query::is_reading)
+    }
 }
 
 impl Drop for InternalClient {
@@ -289,6 +583,7 @@ impl Drop for InternalClient {
 }
 
 /// Check CLI version and warn if outdated.
+#[cfg(feature = "subprocess")]
 pub async fn check_cli_version(cli_path: Option<&std::path::Path>) -> Result<String> {
     use std::process::Stdio;
     use tokio::process::Command;
@@ -316,10 +611,13 @@ pub async fn check_cli_version(cli_path: Option<&std::path::Path>) -> Result<Str
     })?;
 
     let version_str = String::from_utf8_lossy(&output.stdout);
-    let version = version_str
-        .lines()
-        .next()
-        .and_then(|line| line.split_whitespace().last())
+    // The CLI prints e.g. "2.0.14 (Claude Code)"; take the token that looks
+    // like a version.
+    let first_line = version_str.lines().next().unwrap_or("");
+    let version = first_line
+        .split_whitespace()
+        .find(|token| semver::Version::parse(token).is_ok())
+        .or_else(|| first_line.split_whitespace().last())
         .unwrap_or("unknown")
         .to_string();
 
@@ -340,6 +638,16 @@ pub async fn check_cli_version(cli_path: Option<&std::path::Path>) -> Result<Str
     Ok(version)
 }
 
+/// Check CLI version and warn if outdated.
+///
+/// Needs the `subprocess` feature to run the CLI; fails without it.
+#[cfg(not(feature = "subprocess"))]
+pub async fn check_cli_version(_cli_path: Option<&std::path::Path>) -> Result<String> {
+    Err(ClaudeSDKError::configuration(
+        "Checking the CLI version needs the `subprocess` feature",
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
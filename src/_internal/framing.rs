@@ -0,0 +1,288 @@
+//! Splitting CLI output into JSON documents.
+//!
+//! The CLI prints one JSON document per line, but some versions and wrappers
+//! print several documents on one line or break one document across lines.
+//! [`JsonFramer`] takes the output a line at a time and yields whole
+//! documents in either case.
+
+use serde_json::Value;
+
+use super::message_parser::decode_line;
+use crate::errors::{ClaudeSDKError, Result};
+
+/// Reassembles JSON documents from lines of output.
+///
+/// A line holding several documents yields each of them. A line ending
+/// inside a document is held back and joined with the lines after it,
+/// without a separator, until the document is complete. If joining a line
+/// gives an invalid document, or one still unfinished while the line holds
+/// whole documents by itself, the held-back text was a line cut short: it
+/// is yielded as an error and the line is framed on its own. An invalid
+/// document is yielded as an error, and the rest of its line is skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::_internal::framing::JsonFramer;
+///
+/// let mut framer = JsonFramer::new(1024);
+/// assert_eq!(framer.push_line(r#"{"a": 1}{"b": 2}"#).len(), 2);
+/// assert!(framer.push_line(r#"{"c": "#).is_empty());
+/// let values = framer.push_line("3}");
+/// assert_eq!(values[0].as_ref().unwrap()["c"], 3);
+/// assert!(framer.finish().is_none());
+/// ```
+#[derive(Debug)]
+pub struct JsonFramer {
+    /// The start of an unfinished document.
+    pending: String,
+    /// Longest unfinished document held back, in bytes.
+    max_len: usize,
+}
+
+impl JsonFramer {
+    /// Create a framer holding back at most `max_len` bytes of an
+    /// unfinished document.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            pending: String::new(),
+            max_len,
+        }
+    }
+
+    /// Take one line of output, without its line terminator, and return
+    /// the documents it completes, in order.
+    pub fn push_line(&mut self, line: &str) -> Vec<Result<Value>> {
+        let mut out = Vec::new();
+        if self.pending.is_empty() {
+            self.frame(line, &mut out);
+            return out;
+        }
+
+        let mut joined = std::mem::take(&mut self.pending);
+        joined.push_str(line);
+        let mut attempt = Vec::new();
+        self.frame(&joined, &mut attempt);
+        let cut_short = match attempt.first() {
+            Some(Ok(_)) => false,
+            Some(Err(_)) => true,
+            // Inside an array or object, a whole document on the next line
+            // still parses as a continuation.
+            None => is_whole(line),
+        };
+        if cut_short && !line.trim().is_empty() {
+            // The held-back text was not the start of this line's document
+            // after all, such as a line cut short: report it on its own.
+            let held = &joined[..joined.len() - line.len()];
+            self.pending.clear();
+            out.push(Err(decode_line(held.trim()).err().unwrap_or_else(|| {
+                ClaudeSDKError::json_decode("Incomplete JSON document from CLI")
+            })));
+            self.frame(line, &mut out);
+        } else {
+            out.extend(attempt);
+        }
+        out
+    }
+
+    /// Signal the end of output, returning an error if a document was left
+    /// unfinished.
+    pub fn finish(&mut self) -> Option<Result<Value>> {
+        if self.pending.trim().is_empty() {
+            self.pending.clear();
+            return None;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        Some(Err(decode_line(pending.trim()).err().unwrap_or_else(
+            || ClaudeSDKError::json_decode("Incomplete JSON document from CLI"),
+        )))
+    }
+
+    /// Whether part of a document is held back.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Decode the documents in `text`, holding back an unfinished last one.
+    fn frame(&mut self, text: &str, out: &mut Vec<Result<Value>>) {
+        let mut documents = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+        loop {
+            let start = documents.byte_offset();
+            match documents.next() {
+                None => return,
+                Some(Ok(value)) => out.push(Ok(value)),
+                Some(Err(e)) if e.is_eof() => {
+                    let rest = &text[start..];
+                    if rest.len() > self.max_len {
+                        out.push(Err(ClaudeSDKError::json_decode(format!(
+                            "JSON document from CLI exceeds {} bytes",
+                            self.max_len
+                        ))));
+                    } else {
+                        self.pending.push_str(rest);
+                    }
+                    return;
+                }
+                Some(Err(_)) => {
+                    // Re-decode just this document for the error's position.
+                    let rest = text[start..].trim_start();
+                    let end = rest.find(['\r', '\n']).unwrap_or(rest.len());
+                    out.push(Err(decode_line(&rest[..end]).err().unwrap_or_else(|| {
+                        ClaudeSDKError::json_decode("Invalid JSON document from CLI")
+                    })));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Whether `line` holds nothing but whole objects, as the CLI's messages
+/// are. A bare number or string could just as well continue a document.
+fn is_whole(line: &str) -> bool {
+    serde_json::Deserializer::from_str(line)
+        .into_iter::<Value>()
+        .all(|document| matches!(document, Ok(Value::Object(_))))
+}
+
+/// Split `text` into documents with a [`JsonFramer`], a line at a time.
+///
+/// Convenient for recorded output; trailing unfinished input is an error.
+pub fn frame_all(text: &str, max_len: usize) -> Vec<Result<Value>> {
+    let mut framer = JsonFramer::new(max_len);
+    let mut out = Vec::new();
+    for line in text.split('\n') {
+        out.extend(framer.push_line(line.strip_suffix('\r').unwrap_or(line)));
+    }
+    out.extend(framer.finish());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(results: Vec<Result<Value>>) -> Vec<Value> {
+        results.into_iter().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_one_document_per_line() {
+        let mut framer = JsonFramer::new(1024);
+        assert_eq!(
+            values(framer.push_line(r#"{"a":1}"#)),
+            vec![json!({"a": 1})]
+        );
+        assert!(!framer.has_pending());
+    }
+
+    #[test]
+    fn test_several_documents_on_one_line() {
+        let mut framer = JsonFramer::new(1024);
+        assert_eq!(
+            values(framer.push_line(r#"{"a":1} {"b":2}{"c":3}"#)),
+            vec![json!({"a": 1}), json!({"b": 2}), json!({"c": 3})]
+        );
+    }
+
+    #[test]
+    fn test_document_split_across_lines() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"text":"hel"#).is_empty());
+        assert!(framer.has_pending());
+        assert!(framer.push_line(r#"lo","n":"#).is_empty());
+        assert_eq!(
+            values(framer.push_line(r#"1}{"next":true}"#)),
+            vec![json!({"text": "hello", "n": 1}), json!({"next": true})]
+        );
+        assert!(framer.finish().is_none());
+    }
+
+    #[test]
+    fn test_blank_lines_are_skipped() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line("").is_empty());
+        assert!(framer.push_line("   ").is_empty());
+        assert!(framer.finish().is_none());
+    }
+
+    #[test]
+    fn test_invalid_document_is_an_error() {
+        let mut framer = JsonFramer::new(1024);
+        let results = framer.push_line(r#"{"a":1} not json"#);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(ClaudeSDKError::JSONDecode { .. })));
+        assert!(!framer.has_pending());
+    }
+
+    #[test]
+    fn test_truncated_line_before_a_valid_one() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"type":"assis"#).is_empty());
+        let results = framer.push_line(r#"{"type":"result"}"#);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().unwrap(), &json!({"type": "result"}));
+    }
+
+    #[test]
+    fn test_line_cut_short_inside_an_array() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"content":["#).is_empty());
+        let results = framer.push_line(r#"{"type":"result"}"#);
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], Err(ClaudeSDKError::JSONDecode { .. })));
+        assert_eq!(results[1].as_ref().unwrap(), &json!({"type": "result"}));
+        assert!(!framer.has_pending());
+    }
+
+    #[test]
+    fn test_line_cut_short_inside_an_object() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"message":{"usage":"#).is_empty());
+        let results = framer.push_line(r#"{"a":1} {"b":2}"#);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_err());
+        assert_eq!(
+            values(results.into_iter().skip(1).collect()),
+            vec![json!({"a": 1}), json!({"b": 2})]
+        );
+        assert!(framer.finish().is_none());
+    }
+
+    #[test]
+    fn test_array_continued_on_next_lines() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"content":["#).is_empty());
+        assert!(framer.push_line(r#"{"n":1},"#).is_empty());
+        assert!(framer.push_line("2,").is_empty());
+        assert_eq!(
+            values(framer.push_line(r#"{"n":2}]}"#)),
+            vec![json!({"content": [{"n": 1}, 2, {"n": 2}]})]
+        );
+    }
+
+    #[test]
+    fn test_unfinished_document_at_end() {
+        let mut framer = JsonFramer::new(1024);
+        assert!(framer.push_line(r#"{"a":"#).is_empty());
+        assert!(matches!(
+            framer.finish(),
+            Some(Err(ClaudeSDKError::JSONDecode { .. }))
+        ));
+        assert!(!framer.has_pending());
+    }
+
+    #[test]
+    fn test_unfinished_document_over_limit() {
+        let mut framer = JsonFramer::new(8);
+        let results = framer.push_line(r#"{"text":"0123456789"#);
+        assert!(matches!(
+            results[..],
+            [Err(ClaudeSDKError::JSONDecode { .. })]
+        ));
+        assert!(!framer.has_pending());
+    }
+}
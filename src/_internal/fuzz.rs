@@ -0,0 +1,339 @@
+//! Structured input generation for fuzz targets.
+//!
+//! Raw bytes mostly exercise the JSON decoder. [`cli_message`] turns fuzzer
+//! bytes into JSON shaped like CLI output, with fields that are sometimes
+//! missing or of the wrong type, so the message parser's own paths get
+//! covered. [`message`] turns bytes into a typed [`Message`] for fuzzing code
+//! that consumes messages.
+//!
+//! A `cargo fuzz` target is a few lines:
+//!
+//! ```rust,ignore
+//! #![no_main]
+//! use claude_agents_sdk::_internal::fuzz::{cli_message, FuzzInput};
+//! use claude_agents_sdk::_internal::message_parser::parse_message;
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|data: &[u8]| {
+//!     let raw = cli_message(&mut FuzzInput::new(data), false);
+//!     let _ = parse_message(raw);
+//! });
+//! ```
+//!
+//! The `fuzz/` directory of the repository has ready-made targets.
+
+use serde_json::{json, Map, Value};
+
+use super::message_parser::parse_message;
+use crate::types::Message;
+
+/// Deepest nesting of generated JSON values.
+const MAX_DEPTH: usize = 4;
+
+/// Longest generated string, in bytes before UTF-8 replacement.
+const MAX_STRING: usize = 64;
+
+/// A cursor over fuzzer bytes.
+///
+/// Once the bytes run out, every read returns zero, so generation always
+/// finishes.
+#[derive(Debug, Clone)]
+pub struct FuzzInput<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> FuzzInput<'a> {
+    /// Read from `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Whether all bytes have been read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The next byte.
+    pub fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((&byte, rest)) => {
+                self.data = rest;
+                byte
+            }
+            None => 0,
+        }
+    }
+
+    /// A number in `0..n`, or zero if `n` is zero.
+    pub fn choose(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            usize::from(self.byte()) % n
+        }
+    }
+
+    /// A coin flip.
+    pub fn bool(&mut self) -> bool {
+        self.byte() & 1 == 1
+    }
+
+    /// A number from the next eight bytes.
+    pub fn u64(&mut self) -> u64 {
+        (0..8).fold(0, |n, _| (n << 8) | u64::from(self.byte()))
+    }
+
+    /// A string of up to `max` bytes, with invalid UTF-8 replaced.
+    pub fn string(&mut self, max: usize) -> String {
+        let len = self.choose(max + 1).min(self.data.len());
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    /// An arbitrary JSON value nested at most `depth` levels.
+    pub fn json(&mut self, depth: usize) -> Value {
+        let kinds = if depth == 0 { 4 } else { 6 };
+        match self.choose(kinds) {
+            0 => Value::Null,
+            1 => Value::Bool(self.bool()),
+            2 => json!(self.u64() as i64),
+            3 => Value::String(self.string(MAX_STRING)),
+            4 => Value::Array((0..self.choose(4)).map(|_| self.json(depth - 1)).collect()),
+            _ => Value::Object(
+                (0..self.choose(4))
+                    .map(|_| (self.string(8), self.json(depth - 1)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// `value`, or when not `well_formed`, sometimes a missing or arbitrary
+    /// value instead.
+    fn field(&mut self, well_formed: bool, value: Value) -> Option<Value> {
+        if well_formed {
+            return Some(value);
+        }
+        match self.choose(8) {
+            0 => None,
+            1 => Some(self.json(MAX_DEPTH)),
+            _ => Some(value),
+        }
+    }
+}
+
+/// Insert the fields that are present.
+fn object(fields: Vec<(&str, Option<Value>)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn content_block(input: &mut FuzzInput<'_>, well_formed: bool) -> Value {
+    let ok = well_formed;
+    match input.choose(5) {
+        0 => {
+            let text = input.string(MAX_STRING);
+            object(vec![
+                ("type", Some(json!("text"))),
+                ("text", input.field(ok, json!(text))),
+            ])
+        }
+        1 => {
+            let (thinking, signature) = (input.string(MAX_STRING), input.string(16));
+            object(vec![
+                ("type", Some(json!("thinking"))),
+                ("thinking", input.field(ok, json!(thinking))),
+                ("signature", input.field(ok, json!(signature))),
+            ])
+        }
+        2 => {
+            let (id, name) = (input.string(16), input.string(16));
+            let tool_input = input.json(MAX_DEPTH);
+            object(vec![
+                ("type", Some(json!("tool_use"))),
+                ("id", input.field(ok, json!(id))),
+                ("name", input.field(ok, json!(name))),
+                ("input", input.field(ok, tool_input)),
+            ])
+        }
+        3 => {
+            let id = input.string(16);
+            let content = input.json(MAX_DEPTH);
+            let is_error = input.bool();
+            object(vec![
+                ("type", Some(json!("tool_result"))),
+                ("tool_use_id", input.field(ok, json!(id))),
+                ("content", input.field(ok, content)),
+                ("is_error", input.field(ok, json!(is_error))),
+            ])
+        }
+        _ if !well_formed => input.json(MAX_DEPTH),
+        _ => json!({"type": "text", "text": ""}),
+    }
+}
+
+fn content_blocks(input: &mut FuzzInput<'_>, well_formed: bool) -> Value {
+    Value::Array(
+        (0..input.choose(4))
+            .map(|_| content_block(input, well_formed))
+            .collect(),
+    )
+}
+
+/// JSON shaped like one line of CLI output.
+///
+/// With `well_formed`, the result always parses as a [`Message`];
+/// otherwise fields may be missing or of the wrong type, and the line may be
+/// a control message or of an unknown type.
+pub fn cli_message(input: &mut FuzzInput<'_>, well_formed: bool) -> Value {
+    let ok = well_formed;
+    let session_id = input.string(36);
+    let kinds = if well_formed { 5 } else { 8 };
+    match input.choose(kinds) {
+        0 => {
+            let content = if input.bool() {
+                json!(input.string(MAX_STRING))
+            } else {
+                content_blocks(input, well_formed)
+            };
+            let message = object(vec![("content", input.field(ok, content))]);
+            object(vec![
+                ("type", Some(json!("user"))),
+                ("message", input.field(ok, message)),
+                ("session_id", Some(json!(session_id))),
+            ])
+        }
+        1 => {
+            let content = content_blocks(input, well_formed);
+            let model = input.string(32);
+            let usage = input.json(2);
+            let message = object(vec![
+                ("content", input.field(ok, content)),
+                ("model", input.field(ok, json!(model))),
+                ("usage", input.field(ok, usage)),
+            ]);
+            object(vec![
+                ("type", Some(json!("assistant"))),
+                ("message", input.field(ok, message)),
+                ("session_id", Some(json!(session_id))),
+            ])
+        }
+        2 => {
+            let subtype = input.string(16);
+            let data = input.json(MAX_DEPTH);
+            object(vec![
+                ("type", Some(json!("system"))),
+                ("subtype", input.field(ok, json!(subtype))),
+                ("data", input.field(ok, data)),
+                ("session_id", Some(json!(session_id))),
+            ])
+        }
+        3 => {
+            let (duration_ms, num_turns) = (input.u64(), input.choose(256));
+            let result = input.string(MAX_STRING);
+            let is_error = input.bool();
+            object(vec![
+                ("type", Some(json!("result"))),
+                ("subtype", input.field(ok, json!("success"))),
+                ("duration_ms", input.field(ok, json!(duration_ms))),
+                ("duration_api_ms", input.field(ok, json!(duration_ms / 2))),
+                ("is_error", input.field(ok, json!(is_error))),
+                ("num_turns", input.field(ok, json!(num_turns))),
+                ("session_id", Some(json!(session_id))),
+                ("result", input.field(ok, json!(result))),
+            ])
+        }
+        4 => {
+            let uuid = input.string(36);
+            let event = input.json(MAX_DEPTH);
+            object(vec![
+                ("type", Some(json!("stream_event"))),
+                ("uuid", input.field(ok, json!(uuid))),
+                ("session_id", Some(json!(session_id))),
+                ("event", input.field(ok, event)),
+            ])
+        }
+        5 => {
+            let request_id = input.string(16);
+            let request = input.json(MAX_DEPTH);
+            json!({"type": "control_request", "request_id": request_id, "request": request})
+        }
+        6 => {
+            let response = input.json(MAX_DEPTH);
+            json!({"type": "control_response", "response": response})
+        }
+        _ => {
+            let mut value = input.json(MAX_DEPTH);
+            if let Value::Object(fields) = &mut value {
+                fields.insert("type".into(), json!(input.string(16)));
+            }
+            value
+        }
+    }
+}
+
+/// A typed message built from fuzzer bytes.
+pub fn message(data: &[u8]) -> Message {
+    let raw = cli_message(&mut FuzzInput::new(data), true);
+    parse_message(raw)
+        .ok()
+        .flatten()
+        .expect("well-formed generated messages parse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::fuzz_parse;
+
+    /// Deterministic pseudo-random bytes.
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_well_formed_messages_always_parse() {
+        for seed in 0..2000 {
+            let data = bytes(seed, (seed % 512) as usize);
+            let raw = cli_message(&mut FuzzInput::new(&data), true);
+            assert!(
+                matches!(parse_message(raw.clone()), Ok(Some(_))),
+                "seed {}: {}",
+                seed,
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_messages_do_not_panic() {
+        for seed in 0..2000 {
+            let data = bytes(seed, (seed % 512) as usize);
+            let raw = cli_message(&mut FuzzInput::new(&data), false);
+            if let Ok(Some(message)) = fuzz_parse(raw.to_string().as_bytes()) {
+                let canonical = message.to_canonical_json();
+                let back = Message::from_canonical_json(&canonical).unwrap();
+                assert_eq!(back.to_canonical_json(), canonical, "seed {}", seed);
+            }
+            let _ = fuzz_parse(&data);
+        }
+    }
+
+    #[test]
+    fn test_empty_input_generates_a_message() {
+        assert!(matches!(message(&[]), Message::User(_)));
+        assert!(FuzzInput::new(&[]).is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+//! Concurrent execution of hook callbacks.
+//!
+//! With [`ClaudeAgentOptions::hook_parallelism`] set, each hook event is
+//! registered with the CLI as a single callback. That callback matches the
+//! event against every [`HookMatcher`] itself and runs the matching callbacks
+//! concurrently, so slow hooks add their latency once instead of in sequence.
+//!
+//! [`ClaudeAgentOptions::hook_parallelism`]: crate::ClaudeAgentOptions::hook_parallelism
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use regex_automata::meta::Regex;
+use tracing::warn;
+
+use crate::types::{HookCallback, HookMatcher, HookOutput};
+
+/// Whether a matcher pattern applies to an event about `tool_name`.
+///
+/// An absent, empty, or `*` pattern matches everything, as does any pattern
+/// for events without a tool. Patterns of names separated by `|` match those
+/// names exactly; other patterns are regular expressions.
+pub(crate) fn matches(pattern: Option<&str>, tool_name: Option<&str>) -> bool {
+    let (Some(pattern), Some(tool_name)) = (pattern, tool_name) else {
+        return true;
+    };
+    if pattern.is_empty() || pattern == "*" {
+        return true;
+    }
+    let is_name_list = pattern
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '|');
+    if is_name_list {
+        return pattern.split('|').any(|name| name == tool_name);
+    }
+    match Regex::new(pattern) {
+        Ok(regex) => regex.is_match(tool_name),
+        Err(e) => {
+            warn!("Invalid hook matcher pattern {:?}: {}", pattern, e);
+            false
+        }
+    }
+}
+
+/// Timeout to register with the CLI for the combined callback: the longest
+/// matcher timeout, or none if any matcher uses the CLI's default.
+pub(crate) fn combined_timeout(matchers: &[HookMatcher]) -> Option<f64> {
+    matchers
+        .iter()
+        .map(|matcher| matcher.timeout)
+        .collect::<Option<Vec<f64>>>()?
+        .into_iter()
+        .reduce(f64::max)
+}
+
+/// One callback running the callbacks of all `matchers` that match an event,
+/// at most `parallelism` at a time, and merging their outputs in
+/// registration order.
+pub(crate) fn parallel_callback(matchers: Vec<HookMatcher>, parallelism: usize) -> HookCallback {
+    let matchers = Arc::new(matchers);
+    let parallelism = parallelism.max(1);
+    Arc::new(move |input, tool_use_id, context| {
+        let matchers = Arc::clone(&matchers);
+        Box::pin(async move {
+            let tool_name = input.tool_name().map(str::to_string);
+            let calls: Vec<_> = matchers
+                .iter()
+                .filter(|matcher| matches(matcher.matcher.as_deref(), tool_name.as_deref()))
+                .flat_map(|matcher| {
+                    let timeout = matcher
+                        .timeout
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+                    matcher
+                        .hooks
+                        .iter()
+                        .map(move |callback| (callback, timeout))
+                })
+                .map(|(callback, timeout)| {
+                    let call = callback(input.clone(), tool_use_id.clone(), context.clone());
+                    async move {
+                        match timeout {
+                            Some(timeout) => {
+                                let output = tokio::time::timeout(timeout, call).await;
+                                if output.is_err() {
+                                    warn!("Hook callback timed out after {:?}", timeout);
+                                }
+                                output.ok()
+                            }
+                            None => Some(call.await),
+                        }
+                    }
+                })
+                .collect();
+
+            let outputs: Vec<Option<HookOutput>> = futures::stream::iter(calls)
+                .buffered(parallelism)
+                .collect()
+                .await;
+            HookOutput::merge(outputs.into_iter().flatten())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        HookContext, HookInput, HookSpecificOutput, PreToolUseHookSpecificOutput, SyncHookOutput,
+    };
+    use tokio::sync::Barrier;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches(None, Some("Bash")));
+        assert!(matches(Some("*"), Some("Bash")));
+        assert!(matches(Some("Bash"), None));
+        assert!(matches(Some("Write|Edit"), Some("Edit")));
+        assert!(!matches(Some("Write|Edit"), Some("MultiEdit")));
+        assert!(matches(Some("mcp__.*"), Some("mcp__db__query")));
+        assert!(!matches(Some("mcp__.*"), Some("Bash")));
+        assert!(!matches(Some("("), Some("Bash")));
+    }
+
+    fn pre_tool_use(tool_name: &str) -> HookInput {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "s",
+            "transcript_path": "/tmp/t",
+            "cwd": "/",
+            "tool_name": tool_name,
+            "tool_input": {},
+            "tool_use_id": "tu_1"
+        }))
+        .unwrap()
+    }
+
+    fn context_hook(barrier: Arc<Barrier>, context: &'static str) -> HookMatcher {
+        HookMatcher::all().with_callback(move |_, _, _| {
+            let barrier = Arc::clone(&barrier);
+            async move {
+                barrier.wait().await;
+                HookOutput::Sync(SyncHookOutput {
+                    hook_specific_output: Some(HookSpecificOutput::PreToolUse(
+                        PreToolUseHookSpecificOutput {
+                            hook_event_name: "PreToolUse".to_string(),
+                            additional_context: Some(context.to_string()),
+                            ..Default::default()
+                        },
+                    )),
+                    ..Default::default()
+                })
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_parallel_callback_runs_matching_hooks_concurrently() {
+        // Both callbacks wait for each other, so they only finish if they
+        // run at the same time.
+        let barrier = Arc::new(Barrier::new(2));
+        let skipped = HookMatcher::new("Write")
+            .with_callback(|_, _, _| async { panic!("matcher for another tool was run") });
+        let callback = parallel_callback(
+            vec![
+                context_hook(Arc::clone(&barrier), "audit"),
+                skipped,
+                context_hook(barrier, "policy"),
+            ],
+            2,
+        );
+
+        let output = tokio::time::timeout(
+            Duration::from_secs(5),
+            callback(pre_tool_use("Bash"), None, HookContext::default()),
+        )
+        .await
+        .expect("callbacks ran one after another");
+        match output {
+            HookOutput::Sync(SyncHookOutput {
+                hook_specific_output: Some(HookSpecificOutput::PreToolUse(output)),
+                ..
+            }) => assert_eq!(output.additional_context.as_deref(), Some("audit\npolicy")),
+            other => panic!("unexpected output {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_callback_skips_timed_out_hooks() {
+        let slow = HookMatcher::all()
+            .with_timeout(0.01)
+            .with_callback(|_, _, _| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                HookOutput::Sync(SyncHookOutput {
+                    decision: Some("block".to_string()),
+                    ..Default::default()
+                })
+            });
+        let callback = parallel_callback(vec![slow], 1);
+        match callback(pre_tool_use("Bash"), None, HookContext::default()).await {
+            HookOutput::Sync(output) => assert!(output.decision.is_none()),
+            other => panic!("unexpected output {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_combined_timeout() {
+        let with = |secs| HookMatcher::all().with_timeout(secs);
+        assert_eq!(combined_timeout(&[with(5.0), with(30.0)]), Some(30.0));
+        assert_eq!(combined_timeout(&[with(5.0), HookMatcher::all()]), None);
+    }
+}
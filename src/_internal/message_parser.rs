@@ -3,17 +3,182 @@
 //! This module handles conversion of raw JSON messages from the CLI
 //! into strongly-typed Message objects.
 
-use crate::errors::{ClaudeSDKError, Result};
+use crate::control::*;
+use crate::errors::{classify_reported_error, ClaudeSDKError, ErrorKind, RateLimitInfo, Result};
 use crate::types::*;
 use tracing::debug;
 
+/// Longest excerpt of an unparsable line kept in a decode error.
+const MAX_EXCERPT_BYTES: usize = 1024;
+
+/// Decode one line of CLI output as JSON.
+///
+/// On failure the error carries the byte offset of the problem, a JSON
+/// pointer to the value being parsed there, and the line, cut down to
+/// [`MAX_EXCERPT_BYTES`] around the offset when it is long.
+pub fn decode_line(line: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(line).map_err(|e| {
+        let offset = error_offset(line, e.line(), e.column());
+        let path = json_pointer_at(&line[..offset]);
+        let location = if path.is_empty() {
+            format!("at byte {}", offset)
+        } else {
+            format!("at byte {} in {}", offset, path)
+        };
+        ClaudeSDKError::json_decode_at(
+            format!("Failed to parse JSON from CLI {}: {}", location, e),
+            excerpt(line, offset),
+            offset,
+            path,
+            e,
+        )
+    })
+}
+
+/// Byte offset of a serde_json error position (1-based line and column).
+fn error_offset(text: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let mut offset = (line_start + column.saturating_sub(1)).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// The part of `line` around `offset`, marking cuts with `...`.
+fn excerpt(line: &str, offset: usize) -> String {
+    if line.len() <= MAX_EXCERPT_BYTES {
+        return line.to_string();
+    }
+    let mut start = offset.saturating_sub(MAX_EXCERPT_BYTES / 2);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + MAX_EXCERPT_BYTES).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        &line[start..end],
+        if end < line.len() { "..." } else { "" }
+    )
+}
+
+/// JSON pointer to the innermost value open at the end of a JSON prefix.
+fn json_pointer_at(prefix: &str) -> String {
+    enum Frame {
+        Object {
+            key: Option<String>,
+            expecting_key: bool,
+        },
+        Array {
+            index: usize,
+        },
+    }
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut chars = prefix.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                text.push(escaped);
+                            }
+                        }
+                        c => text.push(c),
+                    }
+                }
+                if let Some(Frame::Object {
+                    key,
+                    expecting_key: true,
+                }) = stack.last_mut()
+                {
+                    *key = Some(text);
+                }
+            }
+            '{' => stack.push(Frame::Object {
+                key: None,
+                expecting_key: true,
+            }),
+            '[' => stack.push(Frame::Array { index: 0 }),
+            '}' | ']' => {
+                stack.pop();
+            }
+            ':' => {
+                if let Some(Frame::Object { expecting_key, .. }) = stack.last_mut() {
+                    *expecting_key = false;
+                }
+            }
+            ',' => match stack.last_mut() {
+                Some(Frame::Object { key, expecting_key }) => {
+                    *key = None;
+                    *expecting_key = true;
+                }
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    let mut pointer = String::new();
+    for frame in &stack {
+        match frame {
+            Frame::Object {
+                key: Some(key),
+                expecting_key: false,
+            } => {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+            Frame::Object { .. } => break,
+            Frame::Array { index } => {
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+            }
+        }
+    }
+    pointer
+}
+
+/// Decode and parse one line of CLI output from arbitrary bytes.
+///
+/// The entry point for fuzz targets: invalid UTF-8 is replaced, control
+/// messages go through the control protocol parsers and yield `Ok(None)`,
+/// and anything else through [`parse_message`]. It returns an error for bad
+/// input and must never panic.
+pub fn fuzz_parse(bytes: &[u8]) -> Result<Option<Message>> {
+    let line = String::from_utf8_lossy(bytes);
+    let raw = decode_line(line.trim_end_matches(['\r', '\n']))?;
+    if is_control_request(&raw) {
+        parse_control_request(raw)?;
+        Ok(None)
+    } else if is_control_response(&raw) {
+        parse_control_response(raw)?;
+        Ok(None)
+    } else {
+        parse_message(raw)
+    }
+}
+
 /// Parse a raw JSON value into a typed Message.
 ///
 /// This function handles the discriminated union parsing for all message types,
 /// including nested content blocks.
 pub fn parse_message(raw: serde_json::Value) -> Result<Option<Message>> {
     let msg_type = raw.get("type").and_then(|v| v.as_str()).ok_or_else(|| {
-        ClaudeSDKError::message_parse_with_raw("Message missing 'type' field", raw.clone())
+        ClaudeSDKError::message_parse_at("Message missing 'type' field", "/type", raw.clone())
     })?;
 
     match msg_type {
@@ -29,16 +194,29 @@ pub fn parse_message(raw: serde_json::Value) -> Result<Option<Message>> {
     }
 }
 
+/// Whether `parse_message` parses messages of this type instead of skipping them.
+pub(crate) fn is_message_type(msg_type: &str) -> bool {
+    matches!(
+        msg_type,
+        "user" | "assistant" | "system" | "result" | "stream_event"
+    )
+}
+
 /// Parse a user message.
 fn parse_user_message(raw: serde_json::Value) -> Result<Message> {
     // CLI sends user messages with content nested under "message" field
     let message_obj = raw.get("message").ok_or_else(|| {
-        ClaudeSDKError::message_parse_with_raw("User message missing 'message' field", raw.clone())
+        ClaudeSDKError::message_parse_at(
+            "User message missing 'message' field",
+            "/message",
+            raw.clone(),
+        )
     })?;
 
     let content = message_obj.get("content").ok_or_else(|| {
-        ClaudeSDKError::message_parse_with_raw(
+        ClaudeSDKError::message_parse_at(
             "User message missing 'message.content' field",
+            "/message/content",
             raw.clone(),
         )
     })?;
@@ -47,11 +225,12 @@ fn parse_user_message(raw: serde_json::Value) -> Result<Message> {
     let content = if let Some(text) = content.as_str() {
         UserMessageContent::Text(text.to_string())
     } else if let Some(blocks_arr) = content.as_array() {
-        let blocks = parse_content_blocks(blocks_arr)?;
+        let blocks = parse_content_blocks(blocks_arr, &raw)?;
         UserMessageContent::Blocks(blocks)
     } else {
-        return Err(ClaudeSDKError::message_parse_with_raw(
+        return Err(ClaudeSDKError::message_parse_at(
             "User message content must be string or array",
+            "/message/content",
             raw,
         ));
     };
@@ -70,8 +249,9 @@ fn parse_user_message(raw: serde_json::Value) -> Result<Message> {
 fn parse_assistant_message(raw: serde_json::Value) -> Result<Message> {
     // CLI sends assistant messages with content nested under "message" field
     let message_obj = raw.get("message").ok_or_else(|| {
-        ClaudeSDKError::message_parse_with_raw(
+        ClaudeSDKError::message_parse_at(
             "Assistant message missing 'message' field",
+            "/message",
             raw.clone(),
         )
     })?;
@@ -80,13 +260,14 @@ fn parse_assistant_message(raw: serde_json::Value) -> Result<Message> {
         .get("content")
         .and_then(|v| v.as_array())
         .ok_or_else(|| {
-            ClaudeSDKError::message_parse_with_raw(
+            ClaudeSDKError::message_parse_at(
                 "Assistant message missing or invalid 'message.content' array",
+                "/message/content",
                 raw.clone(),
             )
         })?;
 
-    let content = parse_content_blocks(content_arr)?;
+    let content = parse_content_blocks(content_arr, &raw)?;
 
     let model = message_obj
         .get("model")
@@ -102,6 +283,10 @@ fn parse_assistant_message(raw: serde_json::Value) -> Result<Message> {
         "server_error" => AssistantMessageError::ServerError,
         _ => AssistantMessageError::Unknown,
     });
+    let rate_limit = (error == Some(AssistantMessageError::RateLimit)).then(|| {
+        let text: String = content.iter().filter_map(|block| block.as_text()).collect();
+        Box::new(RateLimitInfo::parse(&raw, &text))
+    });
 
     Ok(Message::Assistant(AssistantMessage {
         content,
@@ -111,6 +296,8 @@ fn parse_assistant_message(raw: serde_json::Value) -> Result<Message> {
             .and_then(|v| v.as_str())
             .map(String::from),
         error,
+        usage: message_obj.get("usage").cloned(),
+        rate_limit,
     }))
 }
 
@@ -157,7 +344,7 @@ fn parse_result_message(raw: serde_json::Value) -> Result<Message> {
         .get("num_turns")
         .or_else(|| raw.get("numTurns"))
         .and_then(|v| v.as_u64())
-        .unwrap_or(0) as u32;
+        .map_or(0, |n| u32::try_from(n).unwrap_or(u32::MAX));
 
     let session_id = raw
         .get("session_id")
@@ -177,6 +364,14 @@ fn parse_result_message(raw: serde_json::Value) -> Result<Message> {
         .get("structured_output")
         .or_else(|| raw.get("structuredOutput"))
         .cloned();
+    let permission_denials = raw
+        .get("permission_denials")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let rate_limit = result
+        .as_deref()
+        .filter(|text| is_error && classify_reported_error(text) == Some(ErrorKind::RateLimited))
+        .map(|text| Box::new(RateLimitInfo::parse(&raw, text)));
 
     Ok(Message::Result(ResultMessage {
         subtype,
@@ -189,6 +384,9 @@ fn parse_result_message(raw: serde_json::Value) -> Result<Message> {
         usage,
         result,
         structured_output,
+        permission_denials,
+        stats: None,
+        rate_limit,
     }))
 }
 
@@ -222,18 +420,40 @@ fn parse_stream_event(raw: serde_json::Value) -> Result<Message> {
     }))
 }
 
-/// Parse content blocks from a JSON array, skipping unknown block types.
-fn parse_content_blocks(blocks: &[serde_json::Value]) -> Result<Vec<ContentBlock>> {
+/// Parse the content blocks of `message.content`, skipping unknown block types.
+///
+/// `message` is the whole message, attached to errors along with the path of
+/// the offending block.
+fn parse_content_blocks(
+    blocks: &[serde_json::Value],
+    message: &serde_json::Value,
+) -> Result<Vec<ContentBlock>> {
     blocks
         .iter()
-        .filter_map(|block| parse_content_block(block).transpose())
+        .enumerate()
+        .filter_map(|(index, block)| {
+            parse_content_block(block)
+                .map_err(|e| match e {
+                    ClaudeSDKError::MessageParse {
+                        message: text,
+                        path: Some(path),
+                        ..
+                    } => ClaudeSDKError::message_parse_at(
+                        text,
+                        format!("/message/content/{}{}", index, path),
+                        message.clone(),
+                    ),
+                    other => other,
+                })
+                .transpose()
+        })
         .collect()
 }
 
 /// Parse a single content block. Returns `Ok(None)` for unknown block types.
-fn parse_content_block(raw: &serde_json::Value) -> Result<Option<ContentBlock>> {
+pub(crate) fn parse_content_block(raw: &serde_json::Value) -> Result<Option<ContentBlock>> {
     let block_type = raw.get("type").and_then(|v| v.as_str()).ok_or_else(|| {
-        ClaudeSDKError::message_parse_with_raw("Content block missing 'type' field", raw.clone())
+        ClaudeSDKError::message_parse_at("Content block missing 'type' field", "/type", raw.clone())
     })?;
 
     match block_type {
@@ -293,6 +513,18 @@ fn parse_content_block(raw: &serde_json::Value) -> Result<Option<ContentBlock>>
                 is_error,
             })))
         }
+        "large_payload" => serde_json::from_value(raw.clone())
+            .map(|mut block: LargePayloadBlock| {
+                block.file = crate::_internal::spill::SpillHold::claim(&block.path);
+                Some(ContentBlock::LargePayload(block))
+            })
+            .map_err(|e| {
+                ClaudeSDKError::message_parse_at(
+                    format!("Invalid large payload block: {}", e),
+                    "",
+                    raw.clone(),
+                )
+            }),
         other => {
             debug!("Skipping unknown content block type: {}", other);
             Ok(None)
@@ -340,6 +572,49 @@ pub fn parse_control_response(raw: serde_json::Value) -> Result<ControlResponse>
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fuzz_parse_hostile_input() {
+        // Nesting past serde_json's recursion limit is an error, not a stack overflow.
+        let deep = "[".repeat(100_000);
+        assert!(fuzz_parse(deep.as_bytes()).is_err());
+        let deep_object = format!(
+            "{{\"type\":\"system\",\"data\":{}{}}}",
+            "{\"a\":".repeat(10_000),
+            "}".repeat(10_000)
+        );
+        assert!(fuzz_parse(deep_object.as_bytes()).is_err());
+
+        // A huge string parses, and a huge broken line keeps a bounded excerpt.
+        let huge = format!(
+            "{{\"type\":\"user\",\"message\":{{\"content\":\"{}\"}}}}",
+            "x".repeat(4 << 20)
+        );
+        assert!(matches!(
+            fuzz_parse(huge.as_bytes()),
+            Ok(Some(Message::User(_)))
+        ));
+        match fuzz_parse(&huge.as_bytes()[..huge.len() - 3]) {
+            Err(ClaudeSDKError::JSONDecode { raw_data, .. }) => {
+                assert!(raw_data.unwrap().len() <= MAX_EXCERPT_BYTES + 6)
+            }
+            other => panic!("Expected JSONDecode, got {:?}", other.map(|_| ())),
+        }
+
+        // Invalid UTF-8 is replaced.
+        let bytes = b"{\"type\":\"user\",\"message\":{\"content\":\"\xff\xfe\"}}\r\n";
+        match fuzz_parse(bytes).unwrap() {
+            Some(Message::User(user)) => assert_eq!(user.text(), Some("\u{fffd}\u{fffd}")),
+            other => panic!("Expected User, got {:?}", other),
+        }
+
+        // Control messages go through the control parsers.
+        assert!(fuzz_parse(br#"{"type":"control_response"}"#).is_err());
+        assert!(matches!(
+            fuzz_parse(br#"{"type":"result","num_turns":99999999999}"#),
+            Ok(Some(Message::Result(r))) if r.num_turns == u32::MAX
+        ));
+    }
+
     #[test]
     fn test_parse_user_message_text() {
         let raw = serde_json::json!({
@@ -771,6 +1046,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_rate_limit_info() {
+        let raw = serde_json::json!({
+            "type": "assistant",
+            "error": "rate_limit",
+            "message": {
+                "content": [{"type": "text", "text": "API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\",\"message\":\"Slow down\"}} Retry after 30 seconds."}],
+                "model": "claude-3"
+            }
+        });
+        let Message::Assistant(asst) = parse_message(raw).unwrap().unwrap() else {
+            panic!("Expected assistant message");
+        };
+        let info = asst.rate_limit.unwrap();
+        assert_eq!(info.limit_type.as_deref(), Some("rate_limit_error"));
+        assert_eq!(info.retry_after, Some(std::time::Duration::from_secs(30)));
+
+        // Fields of the payload win over the text.
+        let raw = serde_json::json!({
+            "type": "result", "subtype": "success", "is_error": true,
+            "result": "Rate limit exceeded, retry after 30 seconds",
+            "rate_limit_info": {"rateLimitType": "five_hour", "retryAfter": 120}
+        });
+        let Message::Result(result) = parse_message(raw).unwrap().unwrap() else {
+            panic!("Expected result message");
+        };
+        let info = result.rate_limit.unwrap();
+        assert_eq!(info.limit_type.as_deref(), Some("five_hour"));
+        assert_eq!(info.retry_after, Some(std::time::Duration::from_secs(120)));
+
+        // A reset time is kept as a time, not a wait.
+        let raw = serde_json::json!({
+            "type": "result", "subtype": "success", "is_error": true,
+            "result": "Rate limit exceeded, retry after 30 seconds",
+            "rate_limit_info": {"rateLimitType": "five_hour", "resetsAt": 1760000000}
+        });
+        let Message::Result(result) = parse_message(raw).unwrap().unwrap() else {
+            panic!("Expected result message");
+        };
+        let info = result.rate_limit.unwrap();
+        assert!(info.retry_after.is_none());
+        assert_eq!(
+            info.resets_at,
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_760_000_000))
+        );
+
+        // Other failures carry no rate limit.
+        let raw = serde_json::json!({
+            "type": "result", "subtype": "error_during_execution", "is_error": true,
+            "result": "Tool failed"
+        });
+        let Message::Result(result) = parse_message(raw).unwrap().unwrap() else {
+            panic!("Expected result message");
+        };
+        assert!(result.rate_limit.is_none());
+    }
+
     #[test]
     fn test_parse_thinking_block() {
         let raw = serde_json::json!({
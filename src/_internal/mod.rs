@@ -4,11 +4,19 @@
 //! While exposed for advanced use cases, the API here may change between versions.
 
 pub mod client;
+pub mod framing;
+pub mod fuzz;
+pub(crate) mod hooks;
 pub mod message_parser;
+pub(crate) mod outbox;
 pub mod query;
+pub(crate) mod spill;
+mod telemetry;
 pub mod transport;
 
 pub use client::InternalClient;
 pub use message_parser::parse_message;
 pub use query::Query;
-pub use transport::{SubprocessTransport, Transport};
+#[cfg(feature = "subprocess")]
+pub use transport::SubprocessTransport;
+pub use transport::{RemoteTransport, Transport};
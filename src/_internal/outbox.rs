@@ -0,0 +1,247 @@
+//! Delivery of parsed messages from the reader task to the consumer.
+//!
+//! The reader task also answers control requests, so blocking it on a slow
+//! consumer holds up permission checks and hooks. The [`Outbox`] applies the
+//! session's [`ChannelOverflowPolicy`] when the message channel is full.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::debug;
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ChannelOverflowPolicy, ClaudeAgentOptions, Message};
+
+/// Channel capacity when none is configured.
+pub(crate) const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
+/// The configured channel capacity, at least one.
+pub(crate) fn channel_capacity(options: &ClaudeAgentOptions) -> usize {
+    options
+        .channel_capacity
+        .unwrap_or(DEFAULT_CHANNEL_CAPACITY)
+        .max(1)
+}
+
+/// Sending half of the message channel.
+pub(crate) struct Outbox {
+    tx: mpsc::Sender<Result<Arc<Message>>>,
+    policy: ChannelOverflowPolicy,
+    /// Partial message events waiting for room in the channel.
+    backlog: VecDeque<Arc<Message>>,
+    max_backlog: usize,
+    /// Partial message events dropped so far.
+    dropped: u64,
+    /// The error ending the stream after the channel overflowed, waiting
+    /// for room in the channel.
+    overflow: Option<ClaudeSDKError>,
+}
+
+impl Outbox {
+    /// Create the message channel for `options`.
+    pub(crate) fn channel(
+        options: &ClaudeAgentOptions,
+    ) -> (Self, mpsc::Receiver<Result<Arc<Message>>>) {
+        let capacity = channel_capacity(options);
+        let (tx, rx) = mpsc::channel(capacity);
+        let outbox = Self {
+            tx,
+            policy: options.overflow_policy,
+            backlog: VecDeque::new(),
+            max_backlog: capacity,
+            dropped: 0,
+            overflow: None,
+        };
+        (outbox, rx)
+    }
+
+    /// Send an item to the consumer. Returns `false` once the stream is
+    /// over, because the consumer is gone or the channel overflowed.
+    pub(crate) async fn send(&mut self, item: Result<Arc<Message>>) -> bool {
+        match self.policy {
+            ChannelOverflowPolicy::Block => self.tx.send(item).await.is_ok(),
+            ChannelOverflowPolicy::Error => match self.tx.try_send(item) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    // Waiting for room would block the reader on the
+                    // consumer; the error is delivered by `close`.
+                    self.overflow = Some(ClaudeSDKError::channel(format!(
+                        "Message channel full: the consumer fell {} messages behind",
+                        self.tx.max_capacity()
+                    )));
+                    false
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+            ChannelOverflowPolicy::DropOldestPartialEvents => match item {
+                Ok(message) if message.is_stream_event() => {
+                    self.backlog.push_back(message);
+                    if !self.flush_ready() {
+                        return false;
+                    }
+                    if self.backlog.len() > self.max_backlog {
+                        self.backlog.pop_front();
+                        self.dropped += 1;
+                        debug!("Dropped partial message event, {} so far", self.dropped);
+                    }
+                    true
+                }
+                item => {
+                    while let Some(message) = self.backlog.pop_front() {
+                        if self.tx.send(Ok(message)).await.is_err() {
+                            return false;
+                        }
+                    }
+                    self.tx.send(item).await.is_ok()
+                }
+            },
+        }
+    }
+
+    /// Whether partial message events are waiting for room in the channel.
+    pub(crate) fn has_backlog(&self) -> bool {
+        !self.backlog.is_empty()
+    }
+
+    /// Wait for room in the channel and move one waiting event into it.
+    ///
+    /// Cancel safe: nothing is taken from the backlog until there is room.
+    pub(crate) async fn drain_one(&mut self) -> bool {
+        let Ok(permit) = self.tx.reserve().await else {
+            return false;
+        };
+        if let Some(message) = self.backlog.pop_front() {
+            permit.send(Ok(message));
+        }
+        true
+    }
+
+    /// End the stream, handing any overflow error to the consumer once it
+    /// makes room, without waiting for it.
+    pub(crate) fn close(self) {
+        if let Some(error) = self.overflow {
+            let tx = self.tx;
+            tokio::spawn(async move {
+                let _ = tx.send(Err(error)).await;
+            });
+        }
+    }
+
+    /// Move waiting events into the channel while it has room.
+    fn flush_ready(&mut self) -> bool {
+        while let Some(message) = self.backlog.pop_front() {
+            match self.tx.try_send(Ok(message)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(item)) => {
+                    if let Ok(message) = item {
+                        self.backlog.push_front(message);
+                    }
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ResultMessage, StreamEvent};
+    use std::time::Duration;
+
+    fn event(index: usize) -> Result<Arc<Message>> {
+        Ok(Arc::new(Message::StreamEvent(StreamEvent {
+            uuid: index.to_string(),
+            session_id: "s".to_string(),
+            event: serde_json::json!({"type": "content_block_delta"}),
+            parent_tool_use_id: None,
+        })))
+    }
+
+    fn result() -> Result<Arc<Message>> {
+        let result: ResultMessage = serde_json::from_value(serde_json::json!({
+            "subtype": "success",
+            "duration_ms": 1,
+            "duration_api_ms": 1,
+            "is_error": false,
+            "num_turns": 1,
+            "session_id": "s"
+        }))
+        .unwrap();
+        Ok(Arc::new(Message::Result(result)))
+    }
+
+    fn uuid(item: Result<Arc<Message>>) -> String {
+        match &*item.unwrap() {
+            Message::StreamEvent(event) => event.uuid.clone(),
+            other => panic!("expected stream event, got {:?}", other),
+        }
+    }
+
+    fn options(capacity: usize, policy: ChannelOverflowPolicy) -> ClaudeAgentOptions {
+        ClaudeAgentOptions::new()
+            .with_channel_capacity(capacity)
+            .with_overflow_policy(policy)
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_partial_events() {
+        let options = options(2, ChannelOverflowPolicy::DropOldestPartialEvents);
+        let (mut outbox, mut rx) = Outbox::channel(&options);
+        for i in 0..6 {
+            assert!(outbox.send(event(i)).await);
+        }
+        // Two in the channel, the newest two of the rest waiting.
+        assert!(outbox.has_backlog());
+        assert_eq!(outbox.dropped, 2);
+
+        let (sent, seen) = tokio::join!(outbox.send(result()), async {
+            let mut seen = Vec::new();
+            for _ in 0..4 {
+                seen.push(uuid(rx.recv().await.unwrap()));
+            }
+            seen
+        });
+        assert!(sent);
+        assert_eq!(seen, ["0", "1", "4", "5"]);
+        assert!(rx.recv().await.unwrap().unwrap().is_result());
+    }
+
+    #[tokio::test]
+    async fn test_drain_one_moves_backlog_when_room() {
+        let options = options(1, ChannelOverflowPolicy::DropOldestPartialEvents);
+        let (mut outbox, mut rx) = Outbox::channel(&options);
+        assert!(outbox.send(event(0)).await);
+        assert!(outbox.send(event(1)).await);
+        assert!(outbox.has_backlog());
+
+        assert_eq!(uuid(rx.recv().await.unwrap()), "0");
+        assert!(outbox.drain_one().await);
+        assert!(!outbox.has_backlog());
+        assert_eq!(uuid(rx.recv().await.unwrap()), "1");
+    }
+
+    #[tokio::test]
+    async fn test_error_policy_ends_stream_when_full() {
+        let options = options(1, ChannelOverflowPolicy::Error);
+        let (mut outbox, mut rx) = Outbox::channel(&options);
+        assert!(outbox.send(event(0)).await);
+
+        // The consumer never reads; the send must not wait for it.
+        let sent = tokio::time::timeout(Duration::from_secs(1), outbox.send(event(1)))
+            .await
+            .expect("send blocked on a consumer that never reads");
+        assert!(!sent);
+        outbox.close();
+
+        assert_eq!(uuid(rx.recv().await.unwrap()), "0");
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            Err(ClaudeSDKError::Channel { .. })
+        ));
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -11,16 +11,33 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
-use tracing::{debug, error, trace, warn};
+use tokio_stream::StreamExt;
+use tracing::{debug, error, trace, warn, Instrument};
 use uuid::Uuid;
 
+use super::hooks;
 use super::message_parser::{
-    is_control_request, is_control_response, parse_control_request, parse_control_response,
-    parse_message,
+    is_control_request, is_control_response, is_message_type, parse_control_request,
+    parse_control_response, parse_message,
 };
-use super::transport::{SubprocessTransport, Transport};
+use super::outbox::Outbox;
+use super::telemetry::{self, TurnTracker};
+use super::transport::{MessageStream, Transport};
+use crate::checkpoints::{Checkpoint, CheckpointTracker};
+use crate::clock::{self, Clock};
+use crate::compaction::AutoCompactTracker;
+use crate::control::*;
+use crate::diffs;
 use crate::errors::{ClaudeSDKError, Result};
+use crate::events::EventTracker;
+use crate::keepalive::Pinger;
+use crate::mcp_status::{self, McpServerStatus, ToolDescriptor};
+use crate::metrics::SessionMetrics;
+use crate::plan::{PlanTracker, TodoItem};
+use crate::stats::{SessionStats, StatsTracker};
+use crate::subagents::{Subagent, SubagentTracker};
 use crate::types::*;
 
 /// Counter for generating unique request IDs.
@@ -34,11 +51,250 @@ fn generate_request_id() -> String {
     format!("req_{}_{}", count, &uuid_str[..8])
 }
 
+/// Whether an allowed tool entry, a tool name or an MCP server prefix like
+/// `mcp__github`, covers `tool_name`.
+fn grants(allowed: &str, tool_name: &str) -> bool {
+    allowed == tool_name
+        || tool_name
+            .strip_prefix(allowed)
+            .is_some_and(|rest| rest.starts_with("__"))
+}
+
+/// User message content carrying the result of one tool call.
+fn tool_result_content(
+    tool_use_id: &str,
+    content: serde_json::Value,
+    is_error: bool,
+) -> serde_json::Value {
+    serde_json::json!([{
+        "type": "tool_result",
+        "tool_use_id": tool_use_id,
+        "content": content,
+        "is_error": is_error
+    }])
+}
+
 /// Pending control request waiting for response.
 struct PendingRequest {
     sender: oneshot::Sender<Result<serde_json::Value>>,
 }
 
+/// Removes a pending request when its caller stops waiting, whether it timed
+/// out, failed to write, or was cancelled.
+struct PendingGuard<'a> {
+    pending_requests: &'a Arc<RwLock<HashMap<String, PendingRequest>>>,
+    request_id: Option<String>,
+}
+
+impl<'a> PendingGuard<'a> {
+    fn new(
+        pending_requests: &'a Arc<RwLock<HashMap<String, PendingRequest>>>,
+        request_id: &str,
+    ) -> Self {
+        Self {
+            pending_requests,
+            request_id: Some(request_id.to_string()),
+        }
+    }
+
+    /// The response arrived, so the reader already removed the entry.
+    fn disarm(&mut self) {
+        self.request_id = None;
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        let Some(request_id) = self.request_id.take() else {
+            return;
+        };
+        if let Ok(mut pending) = self.pending_requests.try_write() {
+            pending.remove(&request_id);
+        } else if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let pending_requests = Arc::clone(self.pending_requests);
+            handle.spawn(async move {
+                pending_requests.write().await.remove(&request_id);
+            });
+        }
+    }
+}
+
+/// Handlers for control requests initiated by the CLI.
+///
+/// Cloned into a task per incoming request so that a slow callback or tool
+/// call does not block the reader loop.
+#[derive(Clone)]
+struct ControlHandlers {
+    /// Tool permission callback.
+    can_use_tool: Option<CanUseTool>,
+    /// Tools allowed since connecting, on top of those the CLI started with.
+    granted_tools: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Hook callback registry (callback_id -> callback function).
+    hook_callbacks: Arc<RwLock<HashMap<String, HookCallback>>>,
+    /// In-process SDK MCP servers by name.
+    #[cfg(feature = "mcp")]
+    sdk_mcp_servers: Arc<HashMap<String, Arc<crate::mcp::SdkMcpServer>>>,
+    /// Turn spans, used as the parent of incoming control request spans.
+    turns: TurnTracker,
+    /// Metrics for hook callback outcomes and CLI messages.
+    metrics: SessionMetrics,
+    /// Event bus publisher.
+    events: EventTracker,
+    /// Per-turn latency statistics.
+    stats: StatsTracker,
+    /// File checkpoints seen so far.
+    checkpoints: CheckpointTracker,
+    /// The agent's latest task list.
+    plan: PlanTracker,
+    /// Subagent runs seen so far.
+    subagents: SubagentTracker,
+    /// Automatic compaction.
+    compaction: AutoCompactTracker,
+    /// What to do with unparsable CLI output.
+    parse_errors: ParseErrorPolicy,
+    /// Deliver messages other than results unparsed.
+    lazy_parse: bool,
+    /// The CLI's session ID, once it reports one.
+    session_id: Arc<std::sync::RwLock<Option<String>>>,
+    /// When the last line arrived from the CLI.
+    last_activity: Arc<std::sync::Mutex<Option<SystemTime>>>,
+    /// Time source.
+    clock: Arc<dyn Clock>,
+}
+
+impl ControlHandlers {
+    /// Remember the session ID from a message that carries one.
+    fn observe_session_id(&self, raw: &serde_json::Value) {
+        let Some(id) = raw.get("session_id").and_then(|v| v.as_str()) else {
+            return;
+        };
+        let mut session_id = self.session_id.write().unwrap_or_else(|e| e.into_inner());
+        if session_id.as_deref() != Some(id) {
+            *session_id = Some(id.to_string());
+        }
+    }
+}
+
+/// A cloneable handle for sending control requests to a connected CLI.
+///
+/// Requests are correlated with their responses by request ID, so any number
+/// may be in flight at once from different tasks: for example, interrupting
+/// while a `set_model` is pending or while another task consumes the
+/// client's message stream.
+#[derive(Clone)]
+pub struct ControlHandle {
+    transport: Arc<Mutex<Box<dyn Transport>>>,
+    pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
+    timeout: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "mcp")]
+    sdk_mcp_servers: Arc<HashMap<String, Arc<crate::mcp::SdkMcpServer>>>,
+}
+
+impl ControlHandle {
+    /// Send a control request and wait for its response.
+    pub async fn request(&self, payload: ControlRequestPayload) -> Result<serde_json::Value> {
+        self.request_with_timeout(payload, self.timeout).await
+    }
+
+    /// Send a control request with its own timeout (None = no timeout).
+    pub async fn request_with_timeout(
+        &self,
+        payload: ControlRequestPayload,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value> {
+        Query::request(
+            &self.transport,
+            &self.pending_requests,
+            &*self.clock,
+            timeout,
+            payload,
+        )
+        .await
+    }
+
+    /// Interrupt the current turn.
+    ///
+    /// Also cancels any in-flight SDK MCP tool calls.
+    pub async fn interrupt(&self) -> Result<()> {
+        #[cfg(feature = "mcp")]
+        for server in self.sdk_mcp_servers.values() {
+            server.cancel_all();
+        }
+        self.request(ControlRequestPayload::Interrupt).await?;
+        Ok(())
+    }
+
+    /// Set the permission mode.
+    pub async fn set_permission_mode(&self, mode: PermissionMode) -> Result<()> {
+        self.request(ControlRequestPayload::SetPermissionMode { mode })
+            .await?;
+        Ok(())
+    }
+
+    /// Set the model.
+    pub async fn set_model(&self, model: impl Into<String>) -> Result<()> {
+        self.set_model_or_default(Some(model.into())).await
+    }
+
+    /// Set the model, or `None` for the default.
+    pub(crate) async fn set_model_or_default(&self, model: Option<String>) -> Result<()> {
+        self.request(ControlRequestPayload::SetModel { model })
+            .await?;
+        Ok(())
+    }
+
+    /// Set the thinking budget, or `None` for the default.
+    pub async fn set_max_thinking_tokens(&self, max_thinking_tokens: Option<u32>) -> Result<()> {
+        self.request(ControlRequestPayload::SetMaxThinkingTokens {
+            max_thinking_tokens,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Get current MCP server connection status.
+    pub async fn get_mcp_status(&self) -> Result<serde_json::Value> {
+        self.request(ControlRequestPayload::McpStatus).await
+    }
+
+    /// Get the status of each MCP server.
+    pub async fn mcp_servers(&self) -> Result<Vec<McpServerStatus>> {
+        mcp_status::parse_servers(self.get_mcp_status().await?)
+    }
+
+    /// Get the tools an MCP server advertises.
+    pub async fn mcp_tools(&self, server: &str) -> Result<Vec<ToolDescriptor>> {
+        mcp_status::parse_tools(self.get_mcp_status().await?, server)
+    }
+
+    /// Time one control round trip to the CLI.
+    ///
+    /// The protocol has no dedicated ping, so this sends `mcp_status`, which
+    /// the CLI answers from memory without involving the model.
+    pub async fn ping(&self) -> Result<Duration> {
+        self.ping_with_timeout(self.timeout).await
+    }
+
+    /// Time one control round trip with its own timeout (None = no timeout).
+    pub async fn ping_with_timeout(&self, timeout: Option<Duration>) -> Result<Duration> {
+        let start = self.clock.now();
+        self.request_with_timeout(ControlRequestPayload::McpStatus, timeout)
+            .await?;
+        Ok(self.clock.now() - start)
+    }
+
+    /// The session's time source.
+    pub(crate) fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Ping the CLI every `interval` in the background, keeping the samples.
+    pub fn start_pinger(&self, interval: Duration) -> Pinger {
+        Pinger::spawn(self.clone(), Arc::clone(&self.clock), interval)
+    }
+}
+
 /// Default timeout for CLI operations in seconds (5 minutes).
 const DEFAULT_TIMEOUT_SECS: u64 = 300;
 
@@ -49,15 +305,22 @@ const DEFAULT_TIMEOUT_SECS: u64 = 300;
 /// of control requests.
 pub struct Query {
     /// Transport for CLI communication.
-    transport: Arc<Mutex<SubprocessTransport>>,
+    transport: Arc<Mutex<Box<dyn Transport>>>,
     /// Channel for sending messages to the user (taken when start() is called).
-    message_tx: Option<mpsc::Sender<Result<Message>>>,
+    outbox: Option<Outbox>,
     /// Pending control requests awaiting responses.
     pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
     /// Tool permission callback.
     can_use_tool: Option<CanUseTool>,
+    /// Tools the CLI was started with permission to use.
+    launch_allowed_tools: Vec<String>,
+    /// Tools allowed since connecting, on top of those the CLI started with.
+    granted_tools: Arc<std::sync::RwLock<Vec<String>>>,
     /// Hook configurations.
     hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
+    /// Run the callbacks for each hook event concurrently, at most this many
+    /// at a time.
+    hook_parallelism: Option<usize>,
     /// Hook callback registry (callback_id -> callback function).
     hook_callbacks: Arc<RwLock<HashMap<String, HookCallback>>>,
     /// Whether the query has been started.
@@ -68,43 +331,118 @@ pub struct Query {
     shutdown_tx: Option<mpsc::Sender<()>>,
     /// Server initialization result (stored after initialize()).
     initialization_result: Arc<RwLock<Option<serde_json::Value>>>,
-    /// Timeout for CLI operations in seconds (0 = no timeout).
-    timeout_secs: u64,
+    /// Timeout for each control request (None = no timeout).
+    control_timeout: Option<Duration>,
     /// Agent definitions to send via initialize request.
     agents: Option<HashMap<String, serde_json::Value>>,
     /// Whether to close stdin when a Result message is received.
     /// Used for one-shot queries with hooks/callbacks.
     close_stdin_on_result: Arc<AtomicBool>,
+    /// Turn and tool call spans.
+    turns: TurnTracker,
+    /// Session metrics.
+    metrics: SessionMetrics,
+    /// Event bus publisher.
+    events: EventTracker,
+    /// Per-turn latency statistics.
+    stats: StatsTracker,
+    /// File checkpoints seen so far.
+    checkpoints: CheckpointTracker,
+    /// The agent's latest task list.
+    plan: PlanTracker,
+    /// Subagent runs seen so far.
+    subagents: SubagentTracker,
+    /// Automatic compaction.
+    compaction: AutoCompactTracker,
+    /// What to do with unparsable CLI output.
+    parse_errors: ParseErrorPolicy,
+    /// Deliver messages other than results unparsed.
+    lazy_parse: bool,
+    /// The CLI's session ID, once it reports one.
+    session_id: Arc<std::sync::RwLock<Option<String>>>,
+    /// When the last line arrived from the CLI.
+    last_activity: Arc<std::sync::Mutex<Option<SystemTime>>>,
+    /// Time source.
+    clock: Arc<dyn Clock>,
+    /// In-process SDK MCP servers by name.
+    #[cfg(feature = "mcp")]
+    sdk_mcp_servers: Arc<HashMap<String, Arc<crate::mcp::SdkMcpServer>>>,
+    /// Tasks forwarding SDK MCP server notifications to the CLI.
+    #[cfg(feature = "mcp")]
+    notification_tasks: Vec<tokio::task::JoinHandle<()>>,
 }
 
 impl Query {
     /// Create a new Query handler.
     pub fn new(
-        transport: SubprocessTransport,
+        transport: Box<dyn Transport>,
         options: &ClaudeAgentOptions,
         agents: Option<HashMap<String, serde_json::Value>>,
-    ) -> (Self, mpsc::Receiver<Result<Message>>) {
-        let (message_tx, message_rx) = mpsc::channel(256);
+    ) -> (Self, mpsc::Receiver<Result<Arc<Message>>>) {
+        let (outbox, message_rx) = Outbox::channel(options);
+        let events = EventTracker::new(options.event_bus.clone());
+        let clock = options.clock.clone().unwrap_or_else(clock::default_clock);
+        let mut hooks = options.hooks.clone();
+        if options.file_diffs {
+            hooks
+                .get_or_insert_with(HashMap::new)
+                .entry(HookEvent::PostToolUse)
+                .or_default()
+                .push(diffs::hook(events.clone()));
+        }
 
         let query = Self {
             transport: Arc::new(Mutex::new(transport)),
-            message_tx: Some(message_tx),
+            outbox: Some(outbox),
             pending_requests: Arc::new(RwLock::new(HashMap::new())),
             can_use_tool: options.can_use_tool.clone(),
-            hooks: options.hooks.clone(),
+            launch_allowed_tools: options.allowed_tools.clone(),
+            granted_tools: Arc::default(),
+            hooks,
+            hook_parallelism: options.hook_parallelism,
             hook_callbacks: Arc::new(RwLock::new(HashMap::new())),
             started: false,
             reader_task: None,
             shutdown_tx: None,
             initialization_result: Arc::new(RwLock::new(None)),
-            timeout_secs: options.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            control_timeout: Self::control_timeout(options),
             agents,
             close_stdin_on_result: Arc::new(AtomicBool::new(false)),
+            turns: TurnTracker::new(
+                options.model.clone(),
+                options.redactor.clone(),
+                telemetry::metadata_field(&options.metadata),
+            ),
+            metrics: SessionMetrics::new(options.metrics.clone()),
+            compaction: AutoCompactTracker::new(options.auto_compact.clone(), events.clone()),
+            subagents: SubagentTracker::new(events.clone()),
+            events,
+            stats: StatsTracker::new(Arc::clone(&clock)),
+            checkpoints: CheckpointTracker::default(),
+            plan: PlanTracker::default(),
+            parse_errors: options.parse_error_policy,
+            lazy_parse: options.lazy_parse,
+            session_id: Arc::new(std::sync::RwLock::new(None)),
+            last_activity: Arc::default(),
+            clock,
+            #[cfg(feature = "mcp")]
+            sdk_mcp_servers: Arc::new(options.sdk_mcp_servers.clone()),
+            #[cfg(feature = "mcp")]
+            notification_tasks: Vec::new(),
         };
 
         (query, message_rx)
     }
 
+    /// Resolve the control request timeout: `control_timeout`, else
+    /// `timeout_secs`, else the default. Zero disables the timeout.
+    fn control_timeout(options: &ClaudeAgentOptions) -> Option<Duration> {
+        let timeout = options.control_timeout.unwrap_or_else(|| {
+            Duration::from_secs(options.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+        });
+        (!timeout.is_zero()).then_some(timeout)
+    }
+
     /// Start the query handler.
     ///
     /// This spawns a background task that reads messages from the transport
@@ -114,11 +452,11 @@ impl Query {
             return Ok(());
         }
 
-        // Get the stdout receiver from transport
-        let stdout_rx = {
+        // Take the message stream from the transport
+        let messages = {
             let mut transport = self.transport.lock().await;
-            transport.take_stdout_rx().ok_or_else(|| {
-                ClaudeSDKError::internal("Transport stdout receiver already taken")
+            transport.take_messages().ok_or_else(|| {
+                ClaudeSDKError::internal("Transport message stream unavailable or already taken")
             })?
         };
 
@@ -127,25 +465,42 @@ impl Query {
 
         // Clone references for the background task
         let transport = Arc::clone(&self.transport);
-        // Take ownership of message_tx so that when the reader task finishes,
+        // Take ownership of the outbox so that when the reader task finishes,
         // the channel closes and the consumer stream ends
-        let message_tx = self.message_tx.take().ok_or_else(|| {
-            ClaudeSDKError::internal("Query already started (message_tx already taken)")
+        let outbox = self.outbox.take().ok_or_else(|| {
+            ClaudeSDKError::internal("Query already started (outbox already taken)")
         })?;
         let pending_requests = Arc::clone(&self.pending_requests);
-        let can_use_tool = self.can_use_tool.clone();
-        let hook_callbacks = Arc::clone(&self.hook_callbacks);
+        let handlers = ControlHandlers {
+            can_use_tool: self.can_use_tool.clone(),
+            granted_tools: Arc::clone(&self.granted_tools),
+            hook_callbacks: Arc::clone(&self.hook_callbacks),
+            #[cfg(feature = "mcp")]
+            sdk_mcp_servers: Arc::clone(&self.sdk_mcp_servers),
+            turns: self.turns.clone(),
+            metrics: self.metrics.clone(),
+            events: self.events.clone(),
+            stats: self.stats.clone(),
+            checkpoints: self.checkpoints.clone(),
+            plan: self.plan.clone(),
+            subagents: self.subagents.clone(),
+            compaction: self.compaction.clone(),
+            parse_errors: self.parse_errors,
+            lazy_parse: self.lazy_parse,
+            session_id: Arc::clone(&self.session_id),
+            last_activity: Arc::clone(&self.last_activity),
+            clock: Arc::clone(&self.clock),
+        };
         let close_stdin_on_result = Arc::clone(&self.close_stdin_on_result);
 
         // Spawn background reader task
         let reader_task = tokio::spawn(async move {
             Self::read_messages(
-                stdout_rx,
+                messages,
                 transport,
-                message_tx,
+                outbox,
                 pending_requests,
-                can_use_tool,
-                hook_callbacks,
+                handlers,
                 &mut shutdown_rx,
                 close_stdin_on_result,
             )
@@ -153,21 +508,22 @@ impl Query {
         });
 
         self.reader_task = Some(reader_task);
+        #[cfg(feature = "mcp")]
+        self.forward_mcp_notifications();
         self.started = true;
+        self.metrics.session_started();
 
         debug!("Query handler started");
         Ok(())
     }
 
     /// Background task that reads and routes messages.
-    #[allow(clippy::too_many_arguments)]
     async fn read_messages(
-        mut stdout_rx: mpsc::Receiver<Result<serde_json::Value>>,
-        transport: Arc<Mutex<SubprocessTransport>>,
-        message_tx: mpsc::Sender<Result<Message>>,
+        mut messages: MessageStream,
+        transport: Arc<Mutex<Box<dyn Transport>>>,
+        mut outbox: Outbox,
         pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
-        can_use_tool: Option<CanUseTool>,
-        hook_callbacks: Arc<RwLock<HashMap<String, HookCallback>>>,
+        handlers: ControlHandlers,
         shutdown_rx: &mut mpsc::Receiver<()>,
         close_stdin_on_result: Arc<AtomicBool>,
     ) {
@@ -180,9 +536,18 @@ impl Query {
                     break;
                 }
 
-                msg = stdout_rx.recv() => {
+                // Hand held-back partial events over once the consumer catches up.
+                alive = outbox.drain_one(), if outbox.has_backlog() => {
+                    if !alive {
+                        debug!("Message receiver dropped");
+                        break;
+                    }
+                }
+
+                msg = messages.next() => {
                     match msg {
                         Some(Ok(raw)) => {
+                            *handlers.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = Some(handlers.clock.system_time());
                             let msg_type = raw.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
                             trace!("Query received raw message of type: {}", msg_type);
 
@@ -192,20 +557,39 @@ impl Query {
                                 Self::handle_control_response(raw, &pending_requests).await;
                             } else if is_control_request(&raw) {
                                 debug!("Routing control request");
-                                Self::handle_control_request(
-                                    raw,
-                                    &transport,
-                                    &can_use_tool,
-                                    &hook_callbacks,
-                                ).await;
+                                // Handle concurrently so long-running callbacks and
+                                // tool calls can be cancelled by later requests.
+                                let transport = Arc::clone(&transport);
+                                let handlers = handlers.clone();
+                                tokio::spawn(async move {
+                                    Self::handle_control_request(raw, &transport, &handlers).await;
+                                });
                             } else {
                                 // Regular message — check if it's a result
                                 let is_result = msg_type == "result";
+                                let lazy = handlers.lazy_parse && !is_result && is_message_type(msg_type);
+                                handlers.observe_session_id(&raw);
+                                if handlers.compaction.absorb(&raw) {
+                                    debug!("Absorbed automatic compaction message");
+                                    continue;
+                                }
+                                handlers.turns.observe(&raw);
+                                handlers.metrics.observe(&raw);
 
                                 debug!("Routing regular message of type: {}", msg_type);
-                                match parse_message(raw) {
-                                    Ok(Some(msg)) => {
-                                        if message_tx.send(Ok(msg)).await.is_err() {
+                                let parsed = if lazy {
+                                    Ok(Some(Message::Lazy(LazyMessage::new(raw))))
+                                } else {
+                                    parse_message(raw)
+                                };
+                                match parsed {
+                                    Ok(Some(mut msg)) => {
+                                        handlers.stats.observe(&mut msg);
+                                        handlers.checkpoints.observe(&msg);
+                                        handlers.plan.observe(&msg);
+                                        handlers.subagents.observe(&msg);
+                                        handlers.events.observe(&msg);
+                                        if !outbox.send(Ok(Arc::new(msg))).await {
                                             debug!("Message receiver dropped");
                                             break;
                                         }
@@ -215,7 +599,13 @@ impl Query {
                                     }
                                     Err(e) => {
                                         warn!("Failed to parse message: {}", e);
-                                        if message_tx.send(Err(e)).await.is_err() {
+                                        let item = match handlers.parse_errors {
+                                            ParseErrorPolicy::Fail => Err(e),
+                                            ParseErrorPolicy::SkipAndReport => Ok(Arc::new(
+                                                Message::ParseWarning(ParseWarning::from_error(&e)),
+                                            )),
+                                        };
+                                        if !outbox.send(item).await {
                                             break;
                                         }
                                     }
@@ -231,9 +621,19 @@ impl Query {
                                 }
                             }
                         }
+                        Some(Err(e @ ClaudeSDKError::JSONDecode { .. }))
+                            if handlers.parse_errors == ParseErrorPolicy::SkipAndReport =>
+                        {
+                            warn!("Skipping unparsable CLI output: {}", e);
+                            let warning = Arc::new(Message::ParseWarning(ParseWarning::from_error(&e)));
+                            if !outbox.send(Ok(warning)).await {
+                                break;
+                            }
+                        }
                         Some(Err(e)) => {
                             error!("Error reading from transport: {}", e);
-                            let _ = message_tx.send(Err(e)).await;
+                            Self::fail_pending_requests(&pending_requests, &e).await;
+                            outbox.send(Err(e)).await;
                             break;
                         }
                         None => {
@@ -245,9 +645,35 @@ impl Query {
             }
         }
 
+        outbox.close();
         debug!("Query reader task finished");
     }
 
+    /// Fail in-flight control requests after the transport failed, so callers
+    /// such as `initialize` see why instead of waiting for their timeout.
+    async fn fail_pending_requests(
+        pending_requests: &RwLock<HashMap<String, PendingRequest>>,
+        error: &ClaudeSDKError,
+    ) {
+        for (_, request) in pending_requests.write().await.drain() {
+            let error = match error {
+                ClaudeSDKError::ProcessFailed {
+                    exit_code,
+                    stderr_tail,
+                    argv,
+                } => ClaudeSDKError::process_failed(*exit_code, stderr_tail.clone(), argv.clone()),
+                ClaudeSDKError::ProcessExited {
+                    code,
+                    signal,
+                    argv,
+                    stderr,
+                } => ClaudeSDKError::process_exited(*code, *signal, argv.clone(), stderr.clone()),
+                other => ClaudeSDKError::cli_connection(format!("CLI output failed: {}", other)),
+            };
+            let _ = request.sender.send(Err(error));
+        }
+    }
+
     /// Handle a control response from the CLI.
     async fn handle_control_response(
         raw: serde_json::Value,
@@ -282,9 +708,8 @@ impl Query {
     /// Handle a control request from the CLI.
     async fn handle_control_request(
         raw: serde_json::Value,
-        transport: &Arc<Mutex<SubprocessTransport>>,
-        can_use_tool: &Option<CanUseTool>,
-        hook_callbacks: &RwLock<HashMap<String, HookCallback>>,
+        transport: &Arc<Mutex<Box<dyn Transport>>>,
+        handlers: &ControlHandlers,
     ) {
         let request = match parse_control_request(raw.clone()) {
             Ok(r) => r,
@@ -295,30 +720,36 @@ impl Query {
         };
 
         let request_id = request.request_id.clone();
-        let response = Self::process_control_request(request, can_use_tool, hook_callbacks).await;
+        let subtype = raw
+            .pointer("/request/subtype")
+            .and_then(|v| v.as_str())
+            .unwrap_or_else(|| request.request.subtype());
+        let span = telemetry::control_request_span(
+            handlers.turns.current().as_ref(),
+            subtype,
+            &request_id,
+            "incoming",
+        );
+        let response = Self::process_control_request(request, handlers)
+            .instrument(span.clone())
+            .await;
+        telemetry::record_result(&span, &response);
 
         // Send response back to CLI
         let response_msg = match response {
-            Ok(data) => serde_json::json!({
-                "type": "control_response",
-                "response": {
-                    "subtype": "success",
-                    "request_id": request_id,
-                    "response": data
-                }
-            }),
-            Err(e) => serde_json::json!({
-                "type": "control_response",
-                "response": {
-                    "subtype": "error",
-                    "request_id": request_id,
-                    "error": e.to_string()
-                }
-            }),
+            Ok(data) => ControlResponse::success(request_id, data),
+            Err(e) => ControlResponse::failure(request_id, e.to_string()),
+        };
+        let line = match serde_json::to_string(&response_msg) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to serialize control response: {}", e);
+                return;
+            }
         };
 
         let transport = transport.lock().await;
-        if let Err(e) = transport.write(&response_msg.to_string()).await {
+        if let Err(e) = transport.write(&line).await {
             error!("Failed to send control response: {}", e);
         }
     }
@@ -326,8 +757,7 @@ impl Query {
     /// Process a control request and return the response.
     async fn process_control_request(
         request: ControlRequest,
-        can_use_tool: &Option<CanUseTool>,
-        hook_callbacks: &RwLock<HashMap<String, HookCallback>>,
+        handlers: &ControlHandlers,
     ) -> Result<serde_json::Value> {
         match request.request {
             ControlRequestPayload::CanUseTool {
@@ -336,7 +766,19 @@ impl Query {
                 permission_suggestions,
                 ..
             } => {
-                if let Some(callback) = can_use_tool {
+                let granted = handlers
+                    .granted_tools
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .iter()
+                    .any(|tool| grants(tool, &tool_name));
+                if granted {
+                    let result = PermissionResult::allow();
+                    handlers
+                        .events
+                        .permission_decision(&tool_name, &input, &result);
+                    Ok(serde_json::json!({"behavior": "allow"}))
+                } else if let Some(callback) = &handlers.can_use_tool {
                     let context = ToolPermissionContext {
                         suggestions: permission_suggestions
                             .map(|s| {
@@ -347,7 +789,10 @@ impl Query {
                             .unwrap_or_default(),
                     };
 
-                    let result = callback(tool_name, input, context).await;
+                    let result = callback(tool_name.clone(), input.clone(), context).await;
+                    handlers
+                        .events
+                        .permission_decision(&tool_name, &input, &result);
                     serde_json::to_value(result).map_err(|e| {
                         ClaudeSDKError::internal(format!(
                             "Failed to serialize PermissionResult: {}",
@@ -365,15 +810,23 @@ impl Query {
                 input,
                 tool_use_id,
             } => {
-                let callbacks = hook_callbacks.read().await;
+                let callbacks = handlers.hook_callbacks.read().await;
                 if let Some(callback) = callbacks.get(&callback_id) {
+                    let event = input
+                        .get("hook_event_name")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown")
+                        .to_string();
+
                     // Parse the hook input
                     let hook_input: HookInput = serde_json::from_value(input).map_err(|e| {
                         ClaudeSDKError::message_parse(format!("Failed to parse hook input: {}", e))
                     })?;
 
                     let context = HookContext::default();
-                    let output = callback(hook_input, tool_use_id, context).await;
+                    let started = handlers.clock.now();
+                    let output = callback(hook_input, tool_use_id.clone(), context).await;
+                    let duration = handlers.clock.now() - started;
 
                     // Convert output for CLI (handle field renaming)
                     let mut output_value = serde_json::to_value(&output).map_err(|e| {
@@ -389,6 +842,14 @@ impl Query {
                             map.insert("continue".to_string(), v);
                         }
                     }
+                    handlers.metrics.hook_output(&event, &output_value);
+                    handlers.events.hook_invoked(
+                        &event,
+                        &callback_id,
+                        tool_use_id.as_deref(),
+                        &output_value,
+                        duration,
+                    );
 
                     Ok(output_value)
                 } else {
@@ -405,16 +866,16 @@ impl Query {
 
             ControlRequestPayload::McpMessage {
                 server_name,
-                message: _,
+                message,
             } => {
-                // MCP message routing - this would be implemented with full MCP support
                 debug!("Received MCP message for server: {}", server_name);
-                Err(ClaudeSDKError::internal(format!(
-                    "MCP server '{}' not found (SDK MCP not yet implemented)",
-                    server_name
-                )))
+                Self::route_mcp_message(handlers, server_name, message).await
             }
 
+            ControlRequestPayload::Unknown => Err(ClaudeSDKError::control_protocol(
+                "Unsupported control request subtype",
+            )),
+
             _ => {
                 warn!("Unhandled control request type");
                 Ok(serde_json::Value::Null)
@@ -422,54 +883,205 @@ impl Query {
         }
     }
 
+    /// Route a JSON-RPC message to the named in-process SDK MCP server.
+    #[cfg(feature = "mcp")]
+    async fn route_mcp_message(
+        handlers: &ControlHandlers,
+        server_name: String,
+        message: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let server = handlers.sdk_mcp_servers.get(&server_name).ok_or_else(|| {
+            ClaudeSDKError::internal(format!("MCP server '{}' not found", server_name))
+        })?;
+
+        let response = server
+            .handle_message(message)
+            .await
+            .unwrap_or_else(|| serde_json::json!({"jsonrpc": "2.0", "result": {}}));
+
+        Ok(serde_json::json!({ "mcp_response": response }))
+    }
+
+    /// Route a JSON-RPC message to the named in-process SDK MCP server.
+    #[cfg(not(feature = "mcp"))]
+    async fn route_mcp_message(
+        _handlers: &ControlHandlers,
+        server_name: String,
+        _message: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        Err(ClaudeSDKError::internal(format!(
+            "MCP server '{}' not found (SDK MCP requires the 'mcp' feature)",
+            server_name
+        )))
+    }
+
+    /// Spawn a task per SDK MCP server that forwards its notifications
+    /// (e.g. `notifications/tools/list_changed`) to the CLI as `mcp_message`
+    /// control requests.
+    #[cfg(feature = "mcp")]
+    fn forward_mcp_notifications(&mut self) {
+        for (name, server) in self.sdk_mcp_servers.iter() {
+            let mut notifications = server.subscribe();
+            let server_name = name.clone();
+            let transport = Arc::clone(&self.transport);
+            let pending_requests = Arc::clone(&self.pending_requests);
+            let timeout = self.control_timeout;
+            let clock = Arc::clone(&self.clock);
+
+            self.notification_tasks.push(tokio::spawn(async move {
+                loop {
+                    let message = match notifications.recv().await {
+                        Ok(message) => message,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(
+                                "Dropped {} notifications from MCP server '{}'",
+                                skipped, server_name
+                            );
+                            continue;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    };
+
+                    debug!("Forwarding notification from MCP server '{}'", server_name);
+                    let payload = ControlRequestPayload::McpMessage {
+                        server_name: server_name.clone(),
+                        message,
+                    };
+                    if let Err(e) =
+                        Self::request(&transport, &pending_requests, &*clock, timeout, payload)
+                            .await
+                    {
+                        warn!(
+                            "Failed to forward notification from MCP server '{}': {}",
+                            server_name, e
+                        );
+                    }
+                }
+            }));
+        }
+    }
+
+    /// Cancel in-flight tool calls on all SDK MCP servers.
+    fn cancel_sdk_tool_calls(&self) {
+        #[cfg(feature = "mcp")]
+        for server in self.sdk_mcp_servers.values() {
+            server.cancel_all();
+        }
+    }
+
+    /// Stop forwarding SDK MCP server notifications.
+    fn stop_mcp_notifications(&mut self) {
+        #[cfg(feature = "mcp")]
+        for task in self.notification_tasks.drain(..) {
+            task.abort();
+        }
+    }
+
     /// Send a control request to the CLI and wait for response.
     pub async fn send_control_request(
         &self,
         payload: ControlRequestPayload,
+    ) -> Result<serde_json::Value> {
+        self.send_control_request_with_timeout(payload, self.control_timeout)
+            .await
+    }
+
+    /// Send a control request with its own timeout (None = no timeout).
+    ///
+    /// Fails with [`ClaudeSDKError::ControlRequestTimedOut`] if the CLI does
+    /// not answer in time.
+    pub async fn send_control_request_with_timeout(
+        &self,
+        payload: ControlRequestPayload,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value> {
+        self.control_handle()
+            .request_with_timeout(payload, timeout)
+            .await
+    }
+
+    /// A handle for sending control requests from other tasks.
+    pub fn control_handle(&self) -> ControlHandle {
+        ControlHandle {
+            transport: Arc::clone(&self.transport),
+            pending_requests: Arc::clone(&self.pending_requests),
+            timeout: self.control_timeout,
+            clock: Arc::clone(&self.clock),
+            #[cfg(feature = "mcp")]
+            sdk_mcp_servers: Arc::clone(&self.sdk_mcp_servers),
+        }
+    }
+
+    /// Send a control request without borrowing the query, for background tasks.
+    async fn request(
+        transport: &Mutex<Box<dyn Transport>>,
+        pending_requests: &Arc<RwLock<HashMap<String, PendingRequest>>>,
+        clock: &dyn Clock,
+        timeout: Option<Duration>,
+        payload: ControlRequestPayload,
     ) -> Result<serde_json::Value> {
         let request_id = generate_request_id();
+        let subtype = payload.subtype();
+        let request = ControlRequest::new(request_id.clone(), payload);
+        let span = telemetry::control_request_span(None, subtype, &request_id, "outgoing");
 
-        let request = serde_json::json!({
-            "type": "control_request",
-            "request_id": request_id,
-            "request": payload
-        });
+        let result = Self::exchange(transport, pending_requests, clock, timeout, request)
+            .instrument(span.clone())
+            .await;
+        telemetry::record_result(&span, &result);
+        result
+    }
+
+    /// Write a control request and wait for its response.
+    async fn exchange(
+        transport: &Mutex<Box<dyn Transport>>,
+        pending_requests: &Arc<RwLock<HashMap<String, PendingRequest>>>,
+        clock: &dyn Clock,
+        timeout: Option<Duration>,
+        request: ControlRequest,
+    ) -> Result<serde_json::Value> {
+        let line = serde_json::to_string(&request).map_err(|e| {
+            ClaudeSDKError::internal(format!("Failed to serialize control request: {}", e))
+        })?;
 
         // Register pending request
         let (tx, rx) = oneshot::channel();
         {
-            let mut pending = self.pending_requests.write().await;
-            pending.insert(request_id.clone(), PendingRequest { sender: tx });
+            let mut pending = pending_requests.write().await;
+            pending.insert(request.request_id.clone(), PendingRequest { sender: tx });
         }
+        let mut guard = PendingGuard::new(pending_requests, &request.request_id);
 
         // Send request
         {
-            let transport = self.transport.lock().await;
-            transport.write(&request.to_string()).await?;
+            let transport = transport.lock().await;
+            transport.write(&line).await?;
         }
 
-        // Wait for response with timeout (use configured timeout, or no timeout if 0)
-        let timeout_duration = if self.timeout_secs == 0 {
-            // Use a very long timeout (effectively no timeout)
-            std::time::Duration::from_secs(86400 * 365) // 1 year
-        } else {
-            std::time::Duration::from_secs(self.timeout_secs)
+        let received = match timeout {
+            Some(timeout) => clock::timeout(clock, timeout, rx).await.ok_or_else(|| {
+                ClaudeSDKError::control_request_timed_out(
+                    request.request.subtype(),
+                    &request.request_id,
+                    timeout.as_millis() as u64,
+                )
+            })?,
+            None => rx.await,
         };
-
-        match tokio::time::timeout(timeout_duration, rx).await {
-            Ok(Ok(result)) => result,
-            Ok(Err(_)) => Err(ClaudeSDKError::internal("Control request receiver dropped")),
-            Err(_) => {
-                // Remove from pending
-                let mut pending = self.pending_requests.write().await;
-                pending.remove(&request_id);
-                Err(ClaudeSDKError::timeout(self.timeout_secs * 1000))
-            }
-        }
+        guard.disarm();
+        received
+            .unwrap_or_else(|_| Err(ClaudeSDKError::internal("Control request receiver dropped")))
     }
 
     /// Initialize the streaming session with the CLI.
     pub async fn initialize(&self) -> Result<serde_json::Value> {
+        let span = telemetry::initialize_span();
+        let result = self.send_initialize().instrument(span.clone()).await;
+        telemetry::record_result(&span, &result);
+        result
+    }
+
+    async fn send_initialize(&self) -> Result<serde_json::Value> {
         let hooks_config = self.build_hooks_config().await;
         let agents_config = self
             .agents
@@ -509,39 +1121,61 @@ impl Query {
         for (event, matchers) in hooks {
             let mut event_config = Vec::new();
 
-            for (matcher_idx, matcher) in matchers.iter().enumerate() {
+            if let Some(parallelism) = self.hook_parallelism {
+                // One callback for the whole event, which matches and runs
+                // the matchers' callbacks itself.
+                let callback_id = format!(
+                    "{}_parallel",
+                    serde_json::to_string(event).unwrap_or_default()
+                );
                 let mut matcher_config = serde_json::Map::new();
-
-                if let Some(ref pattern) = matcher.matcher {
-                    matcher_config.insert("matcher".to_string(), serde_json::json!(pattern));
-                }
-
-                if let Some(timeout) = matcher.timeout {
+                if let Some(timeout) = hooks::combined_timeout(matchers) {
                     matcher_config.insert("timeout".to_string(), serde_json::json!(timeout));
                 }
+                matcher_config.insert(
+                    "callbackIds".to_string(),
+                    serde_json::json!([callback_id.clone()]),
+                );
+                event_config.push(serde_json::Value::Object(matcher_config));
 
-                // Register callbacks with unique IDs across all matchers for this event
-                let mut callback_ids = Vec::new();
-                for (callback_idx, callback) in matcher.hooks.iter().enumerate() {
-                    // Include matcher index to ensure uniqueness across matchers
-                    let callback_id = format!(
-                        "{}_{}_{}",
-                        serde_json::to_string(event).unwrap_or_default(),
-                        matcher_idx,
-                        callback_idx
-                    );
-                    callback_ids.push(callback_id.clone());
+                let callback = hooks::parallel_callback(matchers.clone(), parallelism);
+                let mut callbacks = self.hook_callbacks.write().await;
+                callbacks.insert(callback_id, callback);
+            } else {
+                for (matcher_idx, matcher) in matchers.iter().enumerate() {
+                    let mut matcher_config = serde_json::Map::new();
 
-                    let mut callbacks = self.hook_callbacks.write().await;
-                    callbacks.insert(callback_id, callback.clone());
-                }
+                    if let Some(ref pattern) = matcher.matcher {
+                        matcher_config.insert("matcher".to_string(), serde_json::json!(pattern));
+                    }
 
-                if !callback_ids.is_empty() {
-                    matcher_config
-                        .insert("callbackIds".to_string(), serde_json::json!(callback_ids));
-                }
+                    if let Some(timeout) = matcher.timeout {
+                        matcher_config.insert("timeout".to_string(), serde_json::json!(timeout));
+                    }
 
-                event_config.push(serde_json::Value::Object(matcher_config));
+                    // Register callbacks with unique IDs across all matchers for this event
+                    let mut callback_ids = Vec::new();
+                    for (callback_idx, callback) in matcher.hooks.iter().enumerate() {
+                        // Include matcher index to ensure uniqueness across matchers
+                        let callback_id = format!(
+                            "{}_{}_{}",
+                            serde_json::to_string(event).unwrap_or_default(),
+                            matcher_idx,
+                            callback_idx
+                        );
+                        callback_ids.push(callback_id.clone());
+
+                        let mut callbacks = self.hook_callbacks.write().await;
+                        callbacks.insert(callback_id, callback.clone());
+                    }
+
+                    if !callback_ids.is_empty() {
+                        matcher_config
+                            .insert("callbackIds".to_string(), serde_json::json!(callback_ids));
+                    }
+
+                    event_config.push(serde_json::Value::Object(matcher_config));
+                }
             }
 
             let event_name = match event {
@@ -567,35 +1201,46 @@ impl Query {
     }
 
     /// Send an interrupt request.
+    ///
+    /// Also cancels any in-flight SDK MCP tool calls.
     pub async fn interrupt(&self) -> Result<()> {
-        self.send_control_request(ControlRequestPayload::Interrupt)
-            .await?;
-        Ok(())
+        self.control_handle().interrupt().await
     }
 
     /// Set the permission mode.
     pub async fn set_permission_mode(&self, mode: PermissionMode) -> Result<()> {
-        let mode_str = match mode {
-            PermissionMode::Default => "default",
-            PermissionMode::AcceptEdits => "acceptEdits",
-            PermissionMode::Plan => "plan",
-            PermissionMode::BypassPermissions => "bypassPermissions",
-        };
-
-        self.send_control_request(ControlRequestPayload::SetPermissionMode {
-            mode: mode_str.to_string(),
-        })
-        .await?;
-        Ok(())
+        self.control_handle().set_permission_mode(mode).await
     }
 
     /// Set the model.
     pub async fn set_model(&self, model: impl Into<String>) -> Result<()> {
-        self.send_control_request(ControlRequestPayload::SetModel {
-            model: model.into(),
-        })
-        .await?;
-        Ok(())
+        self.control_handle().set_model(model).await
+    }
+
+    /// Set the model, or `None` for the default.
+    pub(crate) async fn set_model_or_default(&self, model: Option<String>) -> Result<()> {
+        self.control_handle().set_model_or_default(model).await
+    }
+
+    /// Set the thinking budget, or `None` for the default.
+    pub async fn set_max_thinking_tokens(&self, max_thinking_tokens: Option<u32>) -> Result<()> {
+        self.control_handle()
+            .set_max_thinking_tokens(max_thinking_tokens)
+            .await
+    }
+
+    /// Tools the CLI was started with permission to use.
+    pub fn launch_allowed_tools(&self) -> &[String] {
+        &self.launch_allowed_tools
+    }
+
+    /// Allow tools the CLI asks permission for, without asking the
+    /// `can_use_tool` callback.
+    pub fn set_granted_tools(&self, tools: Vec<String>) {
+        *self
+            .granted_tools
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = tools;
     }
 
     /// Rewind files to a specific user message.
@@ -612,22 +1257,64 @@ impl Query {
     /// Returns a JSON object (typically containing a `mcpServers` array) with status
     /// for all configured MCP servers.
     pub async fn get_mcp_status(&self) -> Result<serde_json::Value> {
-        self.send_control_request(ControlRequestPayload::McpStatus)
-            .await
+        self.control_handle().get_mcp_status().await
     }
 
     /// Send a user message to the CLI.
     pub async fn send_message(&self, message: &str) -> Result<()> {
+        self.send_user_content(serde_json::Value::String(message.to_string()))
+            .await
+    }
+
+    /// Send a user message with the given content, a string or an array of
+    /// content blocks.
+    pub async fn send_content(&self, content: serde_json::Value) -> Result<()> {
+        self.send_user_content(content).await
+    }
+
+    /// Send the result of a tool call the host executed itself.
+    ///
+    /// `content` is either a string or an array of content blocks.
+    pub async fn send_tool_result(
+        &self,
+        tool_use_id: &str,
+        content: serde_json::Value,
+        is_error: bool,
+    ) -> Result<()> {
+        self.send_user_content(tool_result_content(tool_use_id, content, is_error))
+            .await
+    }
+
+    async fn send_user_content(&self, content: serde_json::Value) -> Result<()> {
+        if let Some(command) = self.compaction.take_due() {
+            debug!("Context over threshold, compacting before the next message");
+            self.write_user_content(serde_json::Value::String(command))
+                .await?;
+        }
+
+        self.turns.begin_turn();
+        self.stats.begin_turn();
+        self.metrics.query_started();
+        let result = self.write_user_content(content).await;
+        if let Some(turn) = self.turns.current() {
+            telemetry::record_result(&turn, &result);
+        }
+        if result.is_err() {
+            self.turns.finish();
+        }
+        result
+    }
+
+    async fn write_user_content(&self, content: serde_json::Value) -> Result<()> {
         let msg = serde_json::json!({
             "type": "user",
             "message": {
                 "role": "user",
-                "content": message
+                "content": content
             },
             "parent_tool_use_id": serde_json::Value::Null,
             "session_id": "default"
         });
-
         let transport = self.transport.lock().await;
         transport.write(&msg.to_string()).await
     }
@@ -652,6 +1339,10 @@ impl Query {
 
     /// Stop the query handler.
     pub async fn stop(&mut self) -> Result<()> {
+        self.cancel_sdk_tool_calls();
+        self.stop_mcp_notifications();
+        self.turns.finish();
+
         // Send shutdown signal
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(()).await;
@@ -662,22 +1353,79 @@ impl Query {
             let _ = tokio::time::timeout(std::time::Duration::from_secs(2), task).await;
         }
 
+        if self.started {
+            self.metrics.session_ended();
+        }
+        self.started = false;
+
         // Close transport
         let mut transport = self.transport.lock().await;
         transport.close().await?;
 
-        self.started = false;
         Ok(())
     }
 
+    /// Latency and throughput statistics for the session so far.
+    pub fn stats(&self) -> SessionStats {
+        self.stats.snapshot()
+    }
+
+    /// File checkpoints recorded so far, oldest first.
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        self.checkpoints.snapshot()
+    }
+
+    /// The agent's latest task list.
+    pub fn plan(&self) -> Vec<TodoItem> {
+        self.plan.snapshot()
+    }
+
+    /// Subagent runs seen so far, in the order they started.
+    pub fn subagents(&self) -> Vec<Subagent> {
+        self.subagents.snapshot()
+    }
+
+    /// The CLI's session ID, once it has reported one.
+    pub fn session_id(&self) -> Option<String> {
+        self.session_id
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// When the last line arrived from the CLI.
+    pub fn last_activity(&self) -> Option<SystemTime> {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether the CLI process is running.
+    pub async fn is_process_alive(&self) -> bool {
+        self.transport.lock().await.is_alive().await
+    }
+
     /// Check if the query is running.
     pub fn is_started(&self) -> bool {
         self.started
     }
+
+    /// Whether the reader task is still reading from the CLI. It stops
+    /// when the CLI's output ends, such as when the CLI exits.
+    pub fn is_reading(&self) -> bool {
+        self.reader_task
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
 }
 
 impl Drop for Query {
     fn drop(&mut self) {
+        self.cancel_sdk_tool_calls();
+        self.stop_mcp_notifications();
+        self.turns.finish();
+        if self.started {
+            self.metrics.session_ended();
+        }
+
         // Cancel reader task if still running
         if let Some(task) = self.reader_task.take() {
             task.abort();
@@ -698,4 +1446,18 @@ mod tests {
         assert!(id2.starts_with("req_"));
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn test_tool_result_content_parses_as_tool_result_block() {
+        let content = tool_result_content("toolu_1", serde_json::json!("42"), true);
+        let blocks: Vec<ContentBlock> = serde_json::from_value(content).unwrap();
+        match &blocks[..] {
+            [ContentBlock::ToolResult(block)] => {
+                assert_eq!(block.tool_use_id, "toolu_1");
+                assert_eq!(block.content, Some(serde_json::json!("42")));
+                assert_eq!(block.is_error, Some(true));
+            }
+            other => panic!("unexpected blocks: {:?}", other),
+        }
+    }
 }
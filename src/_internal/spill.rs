@@ -0,0 +1,499 @@
+//! Spilling oversized lines of CLI output to disk.
+//!
+//! With a spill directory configured, a line longer than the buffer limit is
+//! streamed to a file instead of memory. The message is then loaded with its
+//! largest content blocks replaced by `large_payload` blocks pointing into the
+//! file, so only the parts that fit stay in memory. Loading parses the whole
+//! file once, so memory is only bounded while the line is read and once the
+//! message is built, not during the load itself.
+//!
+//! A file no block points into is deleted once loaded. Otherwise the
+//! registry here holds it until its message is parsed; the blocks parsed
+//! then share the [`SpillFile`], which deletes it when the last of them is
+//! dropped. Files of messages never parsed are deleted when their
+//! transport is dropped.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+#[cfg(feature = "subprocess")]
+use serde_json::json;
+use serde_json::Value;
+#[cfg(feature = "subprocess")]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt};
+
+#[cfg(feature = "subprocess")]
+use crate::_internal::transport::buffer_pool::BufferPool;
+use crate::errors::{ClaudeSDKError, Result};
+
+/// A line of CLI output.
+#[cfg(feature = "subprocess")]
+pub(crate) enum Line {
+    /// A line held in memory, without its line terminator.
+    Text(String),
+    /// A line written to a file, deleted when the path is dropped.
+    Spilled { path: tempfile::TempPath, len: u64 },
+}
+
+/// A spill file, deleted when dropped.
+#[derive(Debug)]
+pub(crate) struct SpillFile {
+    // Only written by the subprocess transport.
+    #[cfg_attr(not(feature = "subprocess"), allow(dead_code))]
+    path: tempfile::TempPath,
+}
+
+/// A registered spill file.
+struct Entry {
+    /// The file until a block parsed from its message takes it.
+    unclaimed: Option<Arc<SpillFile>>,
+    file: Weak<SpillFile>,
+}
+
+/// Spill files by path, for blocks parsed from their messages to hold.
+fn registry() -> &'static Mutex<HashMap<PathBuf, Entry>> {
+    static FILES: OnceLock<Mutex<HashMap<PathBuf, Entry>>> = OnceLock::new();
+    FILES.get_or_init(Mutex::default)
+}
+
+/// Hold a spill file until its message is parsed.
+#[cfg(feature = "subprocess")]
+fn register(path: tempfile::TempPath) -> PathBuf {
+    let file = Arc::new(SpillFile { path });
+    let key = file.path.to_path_buf();
+    let mut files = registry().lock().unwrap_or_else(|e| e.into_inner());
+    files.retain(|_, entry| entry.unclaimed.is_some() || entry.file.strong_count() > 0);
+    files.insert(
+        key.clone(),
+        Entry {
+            file: Arc::downgrade(&file),
+            unclaimed: Some(file),
+        },
+    );
+    key
+}
+
+/// The spill file at `path`, if the SDK wrote it and it still exists.
+pub(crate) fn claim(path: &Path) -> Option<Arc<SpillFile>> {
+    let mut files = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let entry = files.get_mut(path)?;
+    entry.unclaimed.take().or_else(|| entry.file.upgrade())
+}
+
+/// Delete the files at `paths` that no parsed block holds.
+#[cfg(feature = "subprocess")]
+pub(crate) fn release(paths: &[PathBuf]) {
+    let released: Vec<Arc<SpillFile>> = {
+        let mut files = registry().lock().unwrap_or_else(|e| e.into_inner());
+        paths
+            .iter()
+            .filter_map(|path| files.get_mut(path)?.unclaimed.take())
+            .collect()
+    };
+    drop(released);
+}
+
+/// The spill files a transport wrote, released when it is dropped.
+#[cfg(feature = "subprocess")]
+#[derive(Debug, Default)]
+pub(crate) struct SpillFiles(Mutex<Vec<PathBuf>>);
+
+#[cfg(feature = "subprocess")]
+impl SpillFiles {
+    pub(crate) fn add(&self, path: PathBuf) {
+        let mut paths = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let files = registry().lock().unwrap_or_else(|e| e.into_inner());
+        paths.retain(|path| files.get(path).is_some_and(|e| e.unclaimed.is_some()));
+        paths.push(path);
+    }
+}
+
+#[cfg(feature = "subprocess")]
+impl Drop for SpillFiles {
+    fn drop(&mut self) {
+        release(&self.0.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+/// A content block's hold on its spill file. Holds are equal whatever file
+/// they hold, so blocks compare by content.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpillHold {
+    _file: Option<Arc<SpillFile>>,
+}
+
+impl SpillHold {
+    /// Hold the spill file at `path`, if the SDK wrote it.
+    pub(crate) fn claim(path: &Path) -> Self {
+        Self { _file: claim(path) }
+    }
+}
+
+impl PartialEq for SpillHold {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Reads newline-delimited output, spilling lines longer than `max_line`
+/// bytes when a spill directory is set.
+///
+/// Line buffers come from a [`BufferPool`]; hand lines back with
+/// [`recycle`](Self::recycle) once they are decoded.
+#[cfg(feature = "subprocess")]
+pub(crate) struct LineReader<R> {
+    reader: R,
+    max_line: usize,
+    spill_dir: Option<PathBuf>,
+    pool: BufferPool,
+}
+
+#[cfg(feature = "subprocess")]
+impl<R: AsyncBufRead + Unpin> LineReader<R> {
+    pub(crate) fn new(reader: R, max_line: usize, spill_dir: Option<PathBuf>) -> Self {
+        Self {
+            reader,
+            max_line,
+            spill_dir,
+            pool: BufferPool::default(),
+        }
+    }
+
+    /// Return a line's buffer for reuse by later lines.
+    pub(crate) fn recycle(&mut self, line: String) {
+        self.pool.put(line.into_bytes());
+    }
+
+    /// Read the next line, or `None` at end of input.
+    pub(crate) async fn next_line(&mut self) -> io::Result<Option<Line>> {
+        let mut buf = self.pool.get();
+        let mut spill: Option<(tokio::fs::File, tempfile::TempPath)> = None;
+        let mut len = 0u64;
+
+        loop {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                if spill.is_none() && buf.is_empty() {
+                    self.pool.put(buf);
+                    return Ok(None);
+                }
+                break;
+            }
+            let newline = available.iter().position(|b| *b == b'\n');
+            let chunk = &available[..newline.unwrap_or(available.len())];
+
+            match spill.as_mut() {
+                Some((file, _)) => {
+                    file.write_all(chunk).await?;
+                    len += chunk.len() as u64;
+                }
+                None => {
+                    buf.extend_from_slice(chunk);
+                    if let Some(dir) = self
+                        .spill_dir
+                        .as_deref()
+                        .filter(|_| buf.len() > self.max_line)
+                    {
+                        let (file, path) = tempfile::Builder::new()
+                            .prefix("claude-spill-")
+                            .suffix(".json")
+                            .tempfile_in(dir)?
+                            .into_parts();
+                        let mut file = tokio::fs::File::from_std(file);
+                        file.write_all(&buf).await?;
+                        len = buf.len() as u64;
+                        self.pool.put(std::mem::take(&mut buf));
+                        spill = Some((file, path));
+                    }
+                }
+            }
+
+            let consumed = chunk.len() + usize::from(newline.is_some());
+            self.reader.consume(consumed);
+            if newline.is_some() {
+                break;
+            }
+        }
+
+        match spill {
+            Some((mut file, path)) => {
+                file.flush().await?;
+                Ok(Some(Line::Spilled { path, len }))
+            }
+            None => {
+                if buf.last() == Some(&b'\r') {
+                    buf.pop();
+                }
+                // Replace invalid UTF-8 rather than ending the session.
+                let line = match String::from_utf8(buf) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        let line = String::from_utf8_lossy(e.as_bytes()).into_owned();
+                        self.pool.put(e.into_bytes());
+                        line
+                    }
+                };
+                Ok(Some(Line::Text(line)))
+            }
+        }
+    }
+}
+
+/// Load a spilled message, replacing its largest content blocks with
+/// `large_payload` blocks until the rest fits in `max_line` bytes.
+///
+/// Returns the file's path if a block points into it; otherwise it is
+/// deleted.
+#[cfg(feature = "subprocess")]
+pub(crate) fn load_spilled(
+    path: tempfile::TempPath,
+    max_line: usize,
+) -> Result<(Value, Option<PathBuf>)> {
+    let mut value = read_json(&path)?;
+    if shrink(&mut value, &path, max_line) {
+        Ok((value, Some(register(path))))
+    } else {
+        Ok((value, None))
+    }
+}
+
+/// Replace the largest content blocks, returning whether any was.
+#[cfg(feature = "subprocess")]
+fn shrink(value: &mut Value, path: &Path, max_line: usize) -> bool {
+    let Some(content) = value.pointer_mut("/message/content") else {
+        return false;
+    };
+    let mut replaced = false;
+    match content {
+        Value::String(_) => {
+            let size = serialized_len(content);
+            if size > max_line {
+                *content = json!([large_payload(path, "/message/content", size)]);
+                replaced = true;
+            }
+        }
+        Value::Array(blocks) => {
+            let mut sizes: Vec<(usize, usize)> =
+                blocks.iter().map(serialized_len).enumerate().collect();
+            let mut total: usize = sizes.iter().map(|(_, size)| size).sum();
+            sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+            for (index, size) in sizes {
+                if total <= max_line {
+                    break;
+                }
+                let pointer = format!("/message/content/{}", index);
+                blocks[index] = large_payload(path, &pointer, size);
+                total -= size;
+                replaced = true;
+            }
+        }
+        _ => {}
+    }
+    replaced
+}
+
+/// Read the value at `pointer` from a spill file.
+pub(crate) fn load_payload(path: &Path, pointer: &str) -> Result<Value> {
+    read_json(path)?
+        .pointer_mut(pointer)
+        .map(Value::take)
+        .ok_or_else(|| {
+            ClaudeSDKError::message_parse(format!(
+                "Spilled payload {} has no value at {}",
+                path.display(),
+                pointer
+            ))
+        })
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let file = std::fs::File::open(path)?;
+    serde_json::from_reader(io::BufReader::new(file)).map_err(|e| {
+        ClaudeSDKError::json_decode_with_context(
+            format!(
+                "Failed to parse spilled CLI output {}: {}",
+                path.display(),
+                e
+            ),
+            None,
+            None,
+            e,
+        )
+    })
+}
+
+#[cfg(feature = "subprocess")]
+fn large_payload(path: &Path, pointer: &str, len: usize) -> Value {
+    json!({
+        "type": "large_payload",
+        "path": path,
+        "pointer": pointer,
+        "len": len,
+    })
+}
+
+/// Size of a value serialized as JSON, without allocating it.
+#[cfg(feature = "subprocess")]
+fn serialized_len(value: &Value) -> usize {
+    struct Counter(usize);
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+#[cfg(all(test, feature = "subprocess"))]
+mod tests {
+    use super::*;
+
+    async fn read_all(input: &str, max_line: usize, dir: &Path) -> Vec<Line> {
+        let mut reader = LineReader::new(
+            tokio::io::BufReader::with_capacity(4, input.as_bytes()),
+            max_line,
+            Some(dir.to_path_buf()),
+        );
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line().await.unwrap() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    #[tokio::test]
+    async fn test_short_lines_stay_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = read_all("one\r\ntwo\n\nthree", 8, dir.path()).await;
+        let texts: Vec<_> = lines
+            .iter()
+            .map(|line| match line {
+                Line::Text(text) => text.as_str(),
+                Line::Spilled { .. } => panic!("unexpected spill"),
+            })
+            .collect();
+        assert_eq!(texts, ["one", "two", "", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_is_replaced() {
+        let input: &[u8] = b"{\"text\":\"a\xffb\"}\nnext\n";
+        let mut reader = LineReader::new(tokio::io::BufReader::new(input), 1024, None);
+        match reader.next_line().await.unwrap() {
+            Some(Line::Text(text)) => assert_eq!(text, "{\"text\":\"a\u{fffd}b\"}"),
+            other => panic!("expected text, got {:?}", other.is_some()),
+        }
+        assert!(matches!(reader.next_line().await.unwrap(), Some(Line::Text(t)) if t == "next"));
+    }
+
+    #[tokio::test]
+    async fn test_recycled_buffer_is_reused() {
+        let input: &[u8] = b"first line\nsecond\n";
+        let mut reader = LineReader::new(tokio::io::BufReader::new(input), 1024, None);
+        let first = match reader.next_line().await.unwrap() {
+            Some(Line::Text(text)) => text,
+            other => panic!("expected text, got {:?}", other.is_some()),
+        };
+        let ptr = first.as_ptr();
+        reader.recycle(first);
+
+        match reader.next_line().await.unwrap() {
+            Some(Line::Text(text)) => {
+                assert_eq!(text, "second");
+                assert_eq!(text.as_ptr(), ptr);
+            }
+            other => panic!("expected text, got {:?}", other.is_some()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_line_is_spilled() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = read_all("short\n0123456789abcdef\nafter\n", 8, dir.path()).await;
+        assert_eq!(lines.len(), 3);
+        match &lines[1] {
+            Line::Spilled { path, len } => {
+                assert_eq!(*len, 16);
+                assert_eq!(std::fs::read_to_string(path).unwrap(), "0123456789abcdef");
+            }
+            Line::Text(text) => panic!("expected spill, got {}", text),
+        }
+        assert!(matches!(&lines[2], Line::Text(text) if text == "after"));
+    }
+
+    #[test]
+    fn test_load_spilled_replaces_largest_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.json");
+        let big = "x".repeat(500);
+        let message = json!({
+            "type": "user",
+            "message": {"content": [
+                {"type": "text", "text": "small"},
+                {"type": "tool_result", "tool_use_id": "t1", "content": big},
+            ]}
+        });
+        std::fs::write(&path, message.to_string()).unwrap();
+
+        let (value, file) = load_spilled(tempfile::TempPath::from_path(&path), 100).unwrap();
+        assert_eq!(value["message"]["content"][0]["text"], "small");
+        let payload = &value["message"]["content"][1];
+        assert_eq!(payload["type"], "large_payload");
+        assert_eq!(payload["pointer"], "/message/content/1");
+
+        let original = load_payload(&path, "/message/content/1").unwrap();
+        assert_eq!(original["content"], big.as_str());
+        assert!(load_payload(&path, "/message/content/5").is_err());
+
+        // Blocks parsed from the message share the file, deleted with the
+        // last of them.
+        assert_eq!(file.as_deref(), Some(path.as_path()));
+        let hold = SpillHold::claim(&path);
+        let copy = hold.clone();
+        let other = SpillHold::claim(&path);
+        drop((hold, copy));
+        assert!(path.exists());
+        drop(other);
+        assert!(!path.exists());
+        assert!(claim(&path).is_none());
+    }
+
+    #[test]
+    fn test_unparsed_files_are_deleted_with_their_transport() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.json");
+        let message = json!({"type": "user", "message": {"content": "x".repeat(500)}});
+        std::fs::write(&path, message.to_string()).unwrap();
+
+        let (_, file) = load_spilled(tempfile::TempPath::from_path(&path), 100).unwrap();
+        let files = SpillFiles::default();
+        files.add(file.unwrap());
+        assert!(path.exists());
+        drop(files);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_load_spilled_deletes_file_nothing_points_into() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.json");
+        let message = json!({"type": "user", "message": {"content": "short"}});
+        std::fs::write(&path, message.to_string()).unwrap();
+
+        let (value, file) = load_spilled(tempfile::TempPath::from_path(&path), 100).unwrap();
+        assert_eq!(value, message);
+        assert!(file.is_none());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,494 @@
+//! Tracing spans for the query lifecycle (`otel` feature).
+//!
+//! Spans use the field conventions understood by `tracing-opentelemetry`
+//! (`otel.kind`, `otel.status_code`, ...) and the OpenTelemetry GenAI
+//! semantic conventions where one exists, so an agent run exported through an
+//! OpenTelemetry layer shows up as one coherent trace:
+//!
+//! - `claude.connect` — spawning the CLI, with `claude.initialize` nested inside
+//! - `claude.turn` — one prompt through to its result message
+//! - `claude.tool` — a tool call within a turn, from `tool_use` to `tool_result`
+//! - `claude.control_request` — each control request, in either direction
+//!
+//! Without the feature every helper returns [`Span::none()`] and the tracker
+//! does nothing, so call sites don't need their own `cfg` attributes.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::redact::Redactor;
+
+/// Whether spans are emitted.
+const ENABLED: bool = cfg!(feature = "otel");
+
+/// Session metadata as the value of a `claude.metadata` span field: a JSON
+/// object with sorted keys, or `None` when there is none.
+pub(crate) fn metadata_field(metadata: &HashMap<String, String>) -> Option<String> {
+    if metadata.is_empty() {
+        return None;
+    }
+    let sorted: std::collections::BTreeMap<_, _> = metadata.iter().collect();
+    serde_json::to_string(&sorted).ok()
+}
+
+/// Span for connecting to the CLI.
+pub(crate) fn connect_span(model: Option<&str>, metadata: Option<&str>) -> Span {
+    if !ENABLED {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "claude.connect",
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        gen_ai.system = "anthropic",
+        gen_ai.request.model = model,
+        claude.metadata = metadata,
+    )
+}
+
+/// Span for the initialize handshake.
+pub(crate) fn initialize_span() -> Span {
+    if !ENABLED {
+        return Span::none();
+    }
+    tracing::info_span!(
+        "claude.initialize",
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+    )
+}
+
+/// Span for a control request. `direction` is `"outgoing"` for requests the
+/// SDK sends and `"incoming"` for requests from the CLI.
+pub(crate) fn control_request_span(
+    parent: Option<&Span>,
+    subtype: &str,
+    request_id: &str,
+    direction: &str,
+) -> Span {
+    if !ENABLED {
+        return Span::none();
+    }
+    let kind = if direction == "incoming" {
+        "server"
+    } else {
+        "client"
+    };
+    match parent {
+        Some(parent) => tracing::info_span!(
+            parent: parent,
+            "claude.control_request",
+            otel.kind = kind,
+            otel.status_code = Empty,
+            otel.status_message = Empty,
+            claude.control.subtype = subtype,
+            claude.control.request_id = request_id,
+            claude.control.direction = direction,
+        ),
+        None => tracing::info_span!(
+            "claude.control_request",
+            otel.kind = kind,
+            otel.status_code = Empty,
+            otel.status_message = Empty,
+            claude.control.subtype = subtype,
+            claude.control.request_id = request_id,
+            claude.control.direction = direction,
+        ),
+    }
+}
+
+/// Mark a span as failed when `result` is an error.
+pub(crate) fn record_result<T, E: std::fmt::Display>(span: &Span, result: &Result<T, E>) {
+    if let Err(e) = result {
+        span.record("otel.status_code", "error");
+        span.record("otel.status_message", e.to_string().as_str());
+    }
+}
+
+/// Open turn and tool spans.
+#[derive(Default)]
+struct TurnState {
+    turn: Option<Span>,
+    tools: HashMap<String, Span>,
+    session_id: Option<String>,
+}
+
+/// Tracks the turn and tool-call spans of a session.
+///
+/// Turns start when a user message is sent and end at the next result
+/// message. Messages read from the CLI are fed to [`observe`](Self::observe)
+/// to open and close tool spans and record session, model, cost, and token
+/// usage attributes.
+#[derive(Clone, Default)]
+pub(crate) struct TurnTracker {
+    model: Option<String>,
+    redactor: Option<Redactor>,
+    /// Session metadata, from [`metadata_field`].
+    metadata: Option<String>,
+    state: Arc<Mutex<TurnState>>,
+}
+
+impl TurnTracker {
+    /// Create a tracker for a session using `model`, redacting recorded
+    /// text with `redactor` and tagging turns with `metadata`.
+    pub(crate) fn new(
+        model: Option<String>,
+        redactor: Option<Redactor>,
+        metadata: Option<String>,
+    ) -> Self {
+        Self {
+            model,
+            redactor,
+            metadata,
+            state: Arc::default(),
+        }
+    }
+
+    /// Start a new turn, ending any turn that never received a result.
+    pub(crate) fn begin_turn(&self) {
+        if !ENABLED {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tools.clear();
+        let span = tracing::info_span!(
+            "claude.turn",
+            otel.kind = "client",
+            otel.status_code = Empty,
+            otel.status_message = Empty,
+            gen_ai.system = "anthropic",
+            gen_ai.operation.name = "chat",
+            gen_ai.request.model = self.model.as_deref(),
+            gen_ai.response.model = Empty,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            claude.session_id = state.session_id.as_deref(),
+            claude.metadata = self.metadata.as_deref(),
+            claude.cost_usd = Empty,
+            claude.num_turns = Empty,
+            claude.duration_ms = Empty,
+            claude.result_subtype = Empty,
+            claude.usage.cache_read_input_tokens = Empty,
+            claude.usage.cache_creation_input_tokens = Empty,
+        );
+        state.turn = Some(span);
+    }
+
+    /// The span of the turn in progress, if any.
+    pub(crate) fn current(&self) -> Option<Span> {
+        if !ENABLED {
+            return None;
+        }
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .turn
+            .clone()
+    }
+
+    /// Update spans from a raw message read from the CLI.
+    pub(crate) fn observe(&self, raw: &Value) {
+        if !ENABLED {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match raw.get("type").and_then(Value::as_str) {
+            Some("system") => {
+                if let Some(session_id) = raw.get("session_id").and_then(Value::as_str) {
+                    if let Some(turn) = &state.turn {
+                        turn.record("claude.session_id", session_id);
+                    }
+                    state.session_id = Some(session_id.to_string());
+                }
+            }
+            Some("assistant") => Self::observe_assistant(&mut state, raw),
+            Some("user") => {
+                for block in content_blocks(raw, "tool_result") {
+                    let id = block.get("tool_use_id").and_then(Value::as_str);
+                    if let Some(span) = id.and_then(|id| state.tools.remove(id)) {
+                        if block.get("is_error").and_then(Value::as_bool) == Some(true) {
+                            span.record("otel.status_code", "error");
+                        }
+                    }
+                }
+            }
+            Some("result") => {
+                if let Some(session_id) = raw.get("session_id").and_then(Value::as_str) {
+                    state.session_id = Some(session_id.to_string());
+                }
+                state.tools.clear();
+                if let Some(turn) = state.turn.take() {
+                    record_turn_result(&turn, raw, self.redactor.as_ref());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn observe_assistant(state: &mut TurnState, raw: &Value) {
+        let Some(turn) = state.turn.clone() else {
+            return;
+        };
+        if let Some(model) = raw.pointer("/message/model").and_then(Value::as_str) {
+            turn.record("gen_ai.response.model", model);
+        }
+        for block in content_blocks(raw, "tool_use") {
+            let (Some(id), Some(name)) = (
+                block.get("id").and_then(Value::as_str),
+                block.get("name").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let span = tracing::info_span!(
+                parent: &turn,
+                "claude.tool",
+                otel.kind = "internal",
+                otel.status_code = Empty,
+                gen_ai.operation.name = "execute_tool",
+                gen_ai.tool.name = name,
+                gen_ai.tool.call.id = id,
+            );
+            state.tools.insert(id.to_string(), span);
+        }
+    }
+
+    /// Close any open spans, e.g. when the session stops mid-turn.
+    pub(crate) fn finish(&self) {
+        if !ENABLED {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tools.clear();
+        state.turn = None;
+    }
+}
+
+/// Content blocks of `block_type` in a message's `message.content` array.
+fn content_blocks<'a>(raw: &'a Value, block_type: &'a str) -> impl Iterator<Item = &'a Value> {
+    raw.pointer("/message/content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(move |block| block.get("type").and_then(Value::as_str) == Some(block_type))
+}
+
+/// Record the attributes of a result message on its turn span.
+fn record_turn_result(turn: &Span, raw: &Value, redactor: Option<&Redactor>) {
+    if let Some(session_id) = raw.get("session_id").and_then(Value::as_str) {
+        turn.record("claude.session_id", session_id);
+    }
+    if let Some(cost) = raw.get("total_cost_usd").and_then(Value::as_f64) {
+        turn.record("claude.cost_usd", cost);
+    }
+    if let Some(num_turns) = raw.get("num_turns").and_then(Value::as_u64) {
+        turn.record("claude.num_turns", num_turns);
+    }
+    if let Some(duration) = raw.get("duration_ms").and_then(Value::as_u64) {
+        turn.record("claude.duration_ms", duration);
+    }
+    if let Some(subtype) = raw.get("subtype").and_then(Value::as_str) {
+        turn.record("claude.result_subtype", subtype);
+    }
+    if let Some(usage) = raw.get("usage") {
+        for (key, field) in [
+            ("input_tokens", "gen_ai.usage.input_tokens"),
+            ("output_tokens", "gen_ai.usage.output_tokens"),
+            (
+                "cache_read_input_tokens",
+                "claude.usage.cache_read_input_tokens",
+            ),
+            (
+                "cache_creation_input_tokens",
+                "claude.usage.cache_creation_input_tokens",
+            ),
+        ] {
+            if let Some(value) = usage.get(key).and_then(Value::as_u64) {
+                turn.record(field, value);
+            }
+        }
+    }
+    if raw.get("is_error").and_then(Value::as_bool) == Some(true) {
+        turn.record("otel.status_code", "error");
+        if let Some(result) = raw.get("result").and_then(Value::as_str) {
+            turn.record(
+                "otel.status_message",
+                crate::redact::redact(redactor, result).as_ref(),
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    /// A closed span: name, parent name, and recorded fields.
+    #[derive(Debug, Clone)]
+    struct Closed {
+        name: String,
+        parent: Option<String>,
+        fields: HashMap<String, String>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Capture {
+        closed: Arc<Mutex<Vec<Closed>>>,
+    }
+
+    impl<S> Layer<S> for Capture
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            let fields = extensions.get_mut::<HashMap<String, String>>().unwrap();
+            values.record(&mut FieldVisitor(fields));
+        }
+
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span
+                .extensions()
+                .get::<HashMap<String, String>>()
+                .cloned()
+                .unwrap_or_default();
+            self.closed
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(Closed {
+                    name: span.name().to_string(),
+                    parent: span.parent().map(|p| p.name().to_string()),
+                    fields,
+                });
+        }
+    }
+
+    fn capture(f: impl FnOnce()) -> Vec<Closed> {
+        let layer = Capture::default();
+        let closed = Arc::clone(&layer.closed);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, f);
+        let closed = closed.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        closed
+    }
+
+    #[test]
+    fn test_turn_with_tool_call() {
+        let closed = capture(|| {
+            let tracker = TurnTracker::new(
+                Some("claude-sonnet-4-5".to_string()),
+                None,
+                metadata_field(&HashMap::from([
+                    ("tenant".to_string(), "acme".to_string()),
+                    ("request_id".to_string(), "r-42".to_string()),
+                ])),
+            );
+            tracker.observe(&json!({"type": "system", "subtype": "init", "session_id": "s1"}));
+            tracker.begin_turn();
+            tracker.observe(&json!({
+                "type": "assistant",
+                "message": {
+                    "model": "claude-sonnet-4-5-20250929",
+                    "content": [{"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}]
+                }
+            }));
+            tracker.observe(&json!({
+                "type": "user",
+                "message": {
+                    "content": [{"type": "tool_result", "tool_use_id": "tu_1", "is_error": true}]
+                }
+            }));
+            tracker.observe(&json!({
+                "type": "result",
+                "subtype": "success",
+                "is_error": false,
+                "session_id": "s1",
+                "num_turns": 2,
+                "duration_ms": 1200,
+                "total_cost_usd": 0.25,
+                "usage": {"input_tokens": 100, "output_tokens": 20}
+            }));
+        });
+
+        assert_eq!(closed.len(), 2);
+        let tool = &closed[0];
+        assert_eq!(tool.name, "claude.tool");
+        assert_eq!(tool.parent.as_deref(), Some("claude.turn"));
+        assert_eq!(tool.fields["gen_ai.tool.name"], "Read");
+        assert_eq!(tool.fields["otel.status_code"], "error");
+
+        let turn = &closed[1];
+        assert_eq!(turn.name, "claude.turn");
+        assert_eq!(turn.fields["gen_ai.request.model"], "claude-sonnet-4-5");
+        assert_eq!(
+            turn.fields["gen_ai.response.model"],
+            "claude-sonnet-4-5-20250929"
+        );
+        assert_eq!(turn.fields["claude.session_id"], "s1");
+        assert_eq!(
+            turn.fields["claude.metadata"],
+            r#"{"request_id":"r-42","tenant":"acme"}"#
+        );
+        assert_eq!(turn.fields["claude.cost_usd"], "0.25");
+        assert_eq!(turn.fields["gen_ai.usage.input_tokens"], "100");
+        assert_eq!(turn.fields["gen_ai.usage.output_tokens"], "20");
+        assert!(!turn.fields.contains_key("otel.status_code"));
+    }
+
+    #[test]
+    fn test_finish_closes_open_spans() {
+        let closed = capture(|| {
+            let tracker = TurnTracker::new(None, None, None);
+            tracker.begin_turn();
+            assert!(tracker.current().is_some());
+            tracker.finish();
+            assert!(tracker.current().is_none());
+        });
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].name, "claude.turn");
+    }
+
+    #[test]
+    fn test_control_request_span_error() {
+        let closed = capture(|| {
+            let span = control_request_span(None, "interrupt", "req_1", "outgoing");
+            record_result::<(), _>(&span, &Err("boom"));
+        });
+        assert_eq!(closed[0].name, "claude.control_request");
+        assert_eq!(closed[0].fields["otel.kind"], "client");
+        assert_eq!(closed[0].fields["claude.control.subtype"], "interrupt");
+        assert_eq!(closed[0].fields["otel.status_message"], "boom");
+    }
+}
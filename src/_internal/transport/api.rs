@@ -0,0 +1,799 @@
+//! Running the agent loop against the Anthropic API.
+//!
+//! Where the CLI can't be installed, [`ApiTransport`] stands in for it. It
+//! sends each prompt to the Messages API, runs the tools the model calls, and
+//! reports the turn as the CLI would, so clients and queries work unchanged.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use super::{MessageStream, Transport};
+use crate::errors::{ClaudeSDKError, Result};
+use crate::mcp::SdkMcpServer;
+use crate::types::{ClaudeAgentOptions, PermissionMode, SystemPromptConfig};
+
+/// Model used when the options name none.
+pub const DEFAULT_API_MODEL: &str = "claude-sonnet-4-5";
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const API_VERSION: &str = "2023-06-01";
+const MAX_TOKENS: u32 = 8192;
+
+/// A transport running the agent loop itself, against the Messages API.
+///
+/// Each user message starts a turn: the conversation so far goes to the API,
+/// and while the model calls tools, each call is run on the SDK MCP server
+/// that provides it and the results are sent back. The turn ends with a
+/// `result` message when the model stops calling tools or
+/// [`max_turns`](ClaudeAgentOptions::max_turns) is reached.
+///
+/// This covers a subset of what the CLI does. The tools are those of the
+/// options' [SDK MCP servers](ClaudeAgentOptions::with_sdk_mcp_server),
+/// named `mcp__<server>__<tool>` as with the CLI; built-in tools, external
+/// MCP servers, hooks, partial messages, and resuming sessions are not
+/// available. Tools in
+/// [`disallowed_tools`](ClaudeAgentOptions::disallowed_tools) are refused,
+/// and [`can_use_tool`](ClaudeAgentOptions::can_use_tool) is asked about
+/// tools not in [`allowed_tools`](ClaudeAgentOptions::allowed_tools) unless
+/// permissions are bypassed; without it, those tools are refused. API errors end the turn with an error result,
+/// as the CLI reports them.
+///
+/// Requests go to `ANTHROPIC_BASE_URL` from the options'
+/// [`env`](ClaudeAgentOptions::env) or the environment, if set.
+///
+/// Usually selected with [`Backend::Api`](crate::Backend::Api) rather than
+/// built directly.
+pub struct ApiTransport {
+    shared: Arc<Shared>,
+    prompts: Mutex<Option<mpsc::UnboundedSender<Value>>>,
+    prompt_rx: Option<mpsc::UnboundedReceiver<Value>>,
+    output: Option<mpsc::UnboundedReceiver<Value>>,
+    task: Option<JoinHandle<()>>,
+    ready: bool,
+}
+
+/// State shared by the transport and its agent loop.
+struct Shared {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    system: Option<String>,
+    max_turns: Option<u32>,
+    allowed_tools: Vec<String>,
+    disallowed_tools: Vec<String>,
+    ask_permission: bool,
+    servers: Vec<Arc<SdkMcpServer>>,
+    session_id: String,
+    settings: Mutex<Settings>,
+    /// Output to the SDK; `None` once the session has ended.
+    out: Mutex<Option<mpsc::UnboundedSender<Value>>>,
+    /// Permission requests waiting for the SDK's answer, by request ID.
+    permissions: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    /// Cancelled to interrupt the current turn.
+    interrupt: Mutex<CancellationToken>,
+    next_id: AtomicU64,
+}
+
+/// Settings the SDK can change mid-session.
+struct Settings {
+    model: String,
+    permission_mode: PermissionMode,
+}
+
+/// How a turn is going, for its result message.
+#[derive(Default)]
+struct Turn {
+    num_turns: u32,
+    api_duration_ms: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    /// Text of the last assistant message.
+    text: String,
+}
+
+/// Why a turn ended.
+enum TurnEnd {
+    Done,
+    MaxTurns,
+    Failed(String),
+    Interrupted,
+}
+
+impl ApiTransport {
+    /// Run `options`' sessions against the API with `api_key`, or the
+    /// `ANTHROPIC_API_KEY` variable if the key is empty.
+    pub fn new(options: &ClaudeAgentOptions, api_key: impl Into<String>) -> Result<Self> {
+        let env = |name: &str| {
+            options
+                .env
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok())
+                .filter(|value| !value.is_empty())
+        };
+        let mut api_key = api_key.into();
+        if api_key.is_empty() {
+            api_key = env("ANTHROPIC_API_KEY").ok_or_else(|| {
+                ClaudeSDKError::configuration("No API key given and ANTHROPIC_API_KEY is not set")
+            })?;
+        }
+        let system = match &options.system_prompt {
+            Some(SystemPromptConfig::Text(text)) => Some(text.clone()),
+            // The CLI's own prompt isn't available; keep what was appended.
+            Some(SystemPromptConfig::Preset(preset)) => preset.append.clone(),
+            None => None,
+        };
+        let mut servers: Vec<_> = options.sdk_mcp_servers.values().cloned().collect();
+        servers.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                http: reqwest::Client::new(),
+                api_key,
+                base_url: env("ANTHROPIC_BASE_URL")
+                    .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                    .trim_end_matches('/')
+                    .to_string(),
+                system,
+                max_turns: options.max_turns,
+                allowed_tools: options.allowed_tools.clone(),
+                disallowed_tools: options.disallowed_tools.clone(),
+                ask_permission: options.can_use_tool.is_some(),
+                servers,
+                session_id: uuid::Uuid::new_v4().to_string(),
+                settings: Mutex::new(Settings {
+                    model: options
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_API_MODEL.to_string()),
+                    permission_mode: options.permission_mode.unwrap_or_default(),
+                }),
+                out: Mutex::new(None),
+                permissions: Mutex::default(),
+                interrupt: Mutex::default(),
+                next_id: AtomicU64::new(1),
+            }),
+            prompts: Mutex::new(None),
+            prompt_rx: None,
+            output: None,
+            task: None,
+            ready: false,
+        })
+    }
+
+    /// Answer the SDK's control requests, which need no conversation state.
+    fn on_control_request(&self, message: &Value) {
+        let request = &message["request"];
+        match request["subtype"].as_str() {
+            Some("interrupt") => self
+                .shared
+                .interrupt
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .cancel(),
+            Some("set_model") => {
+                self.shared
+                    .settings
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .model = request["model"]
+                    .as_str()
+                    .unwrap_or(DEFAULT_API_MODEL)
+                    .to_string();
+            }
+            Some("set_permission_mode") => {
+                if let Ok(mode) = serde_json::from_value(request["mode"].clone()) {
+                    self.shared
+                        .settings
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .permission_mode = mode;
+                }
+            }
+            _ => {}
+        }
+        self.shared.emit(json!({
+            "type": "control_response",
+            "response": {
+                "subtype": "success",
+                "request_id": message["request_id"],
+                "response": {}
+            }
+        }));
+    }
+
+    /// Hand the SDK's answer to a permission request to the turn waiting
+    /// for it.
+    fn on_control_response(&self, message: &Value) {
+        let response = &message["response"];
+        let request_id = response["request_id"].as_str().unwrap_or_default();
+        let Some(waiting) = self
+            .shared
+            .permissions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(request_id)
+        else {
+            debug!(
+                "Ignoring control response to unknown request {}",
+                request_id
+            );
+            return;
+        };
+        let answer = if response["subtype"] == "success" {
+            response["response"].clone()
+        } else {
+            json!({
+                "behavior": "deny",
+                "message": response["error"].as_str().unwrap_or("Permission request failed")
+            })
+        };
+        let _ = waiting.send(answer);
+    }
+}
+
+impl Shared {
+    fn emit(&self, message: Value) {
+        if let Some(out) = &*self.out.lock().unwrap_or_else(|e| e.into_inner()) {
+            let _ = out.send(message);
+        }
+    }
+
+    fn model(&self) -> String {
+        self.settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .model
+            .clone()
+    }
+
+    /// The tools offered to the model, by API name.
+    fn tools(&self) -> Vec<(String, Arc<SdkMcpServer>, crate::mcp::SdkMcpTool)> {
+        self.servers
+            .iter()
+            .flat_map(|server| {
+                server.tools().into_iter().map(move |tool| {
+                    (
+                        crate::mcp::tool_name(server.name(), &tool.name),
+                        Arc::clone(server),
+                        tool,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Send the conversation to the API, returning the response or why it
+    /// failed.
+    async fn create_message(&self, messages: &[Value]) -> std::result::Result<Value, String> {
+        let tools: Vec<Value> = self
+            .tools()
+            .into_iter()
+            .map(|(name, _, tool)| {
+                json!({
+                    "name": name,
+                    "description": tool.description,
+                    "input_schema": tool.input_schema,
+                })
+            })
+            .collect();
+        let mut body = json!({
+            "model": self.model(),
+            "max_tokens": MAX_TOKENS,
+            "messages": messages,
+        });
+        if let Some(system) = &self.system {
+            body["system"] = json!(system);
+        }
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+        }
+
+        let response = self
+            .http
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("API Error: {}", e))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("API Error: {}", e))?;
+        let value: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = value["error"]["message"].as_str().unwrap_or(&text);
+            return Err(format!("API Error: {} {}", status.as_u16(), message));
+        }
+        if value.is_null() {
+            return Err(format!("API Error: invalid response: {}", text));
+        }
+        Ok(value)
+    }
+
+    /// Whether a tool call may run, and with what input.
+    async fn permit(
+        &self,
+        name: &str,
+        input: &Value,
+        tool_use_id: &str,
+    ) -> std::result::Result<Value, String> {
+        if self.disallowed_tools.iter().any(|tool| tool == name) {
+            return Err(format!("Permission to use {} has been denied.", name));
+        }
+        let bypass = self
+            .settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .permission_mode
+            == PermissionMode::BypassPermissions;
+        if bypass || self.is_allowed(name) {
+            return Ok(input.clone());
+        }
+        // As with the CLI, a tool that isn't allowed needs the SDK's
+        // permission, and without a callback to ask it is denied.
+        if !self.ask_permission {
+            return Err(format!("Permission to use {} has been denied.", name));
+        }
+
+        let request_id = format!("api_req_{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (tx, rx) = oneshot::channel();
+        self.permissions
+            .lock()
+            .unwrap()
+            .insert(request_id.clone(), tx);
+        self.emit(json!({
+            "type": "control_request",
+            "request_id": request_id,
+            "request": {
+                "subtype": "can_use_tool",
+                "tool_name": name,
+                "input": input,
+                "tool_use_id": tool_use_id
+            }
+        }));
+        let answer = rx.await.unwrap_or(Value::Null);
+        if answer["behavior"] == "allow" {
+            return Ok(match &answer["updatedInput"] {
+                Value::Null => input.clone(),
+                updated => updated.clone(),
+            });
+        }
+        if answer["interrupt"] == true {
+            self.interrupt
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .cancel();
+        }
+        Err(answer["message"]
+            .as_str()
+            .filter(|message| !message.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Permission to use {} has been denied.", name)))
+    }
+
+    /// Whether `allowed_tools` names the tool or its server.
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowed_tools.iter().any(|allowed| {
+            allowed == name
+                || name
+                    .strip_prefix(allowed.as_str())
+                    .is_some_and(|rest| rest.starts_with("__"))
+        })
+    }
+
+    /// Run one tool call, returning its `tool_result` block.
+    async fn call_tool(&self, block: &Value) -> Value {
+        let id = block["id"].as_str().unwrap_or_default();
+        let name = block["name"].as_str().unwrap_or_default();
+        let result = |content: Value, is_error: bool| {
+            json!({
+                "type": "tool_result",
+                "tool_use_id": id,
+                "content": content,
+                "is_error": is_error
+            })
+        };
+
+        let input = match self.permit(name, &block["input"], id).await {
+            Ok(input) => input,
+            Err(message) => return result(json!(message), true),
+        };
+        let Some((_, server, tool)) = self.tools().into_iter().find(|(n, ..)| n == name) else {
+            return result(json!(format!("No such tool available: {}", name)), true);
+        };
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::SeqCst),
+            "method": "tools/call",
+            "params": {"name": tool.name, "arguments": input}
+        });
+        match server.handle_message(request).await {
+            Some(response) if response.get("result").is_some() => {
+                let outcome = &response["result"];
+                result(
+                    tool_result_content(outcome),
+                    outcome["isError"].as_bool().unwrap_or(false),
+                )
+            }
+            Some(response) => result(
+                json!(response["error"]["message"]
+                    .as_str()
+                    .unwrap_or("Tool call failed")),
+                true,
+            ),
+            None => result(json!("No response from tool"), true),
+        }
+    }
+
+    /// Run a turn until the model stops calling tools.
+    async fn run_turn(&self, messages: &mut Vec<Value>, turn: &mut Turn) -> TurnEnd {
+        loop {
+            if self.max_turns.is_some_and(|max| turn.num_turns >= max) {
+                return TurnEnd::MaxTurns;
+            }
+            let started = Instant::now();
+            let response = self.create_message(messages).await;
+            turn.api_duration_ms += started.elapsed().as_millis() as u64;
+            let response = match response {
+                Ok(response) => response,
+                Err(message) => return TurnEnd::Failed(message),
+            };
+            turn.num_turns += 1;
+            turn.input_tokens += response["usage"]["input_tokens"].as_u64().unwrap_or(0);
+            turn.output_tokens += response["usage"]["output_tokens"].as_u64().unwrap_or(0);
+
+            let content = response["content"].clone();
+            self.emit(json!({
+                "type": "assistant",
+                "message": {
+                    "id": response["id"],
+                    "model": response["model"],
+                    "role": "assistant",
+                    "content": content,
+                    "stop_reason": response["stop_reason"],
+                    "usage": response["usage"]
+                },
+                "parent_tool_use_id": null,
+                "session_id": self.session_id
+            }));
+            messages.push(json!({"role": "assistant", "content": content}));
+            turn.text = text_of(&content);
+
+            let calls: Vec<&Value> = content
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|block| block["type"] == "tool_use")
+                .collect();
+            if calls.is_empty() {
+                return TurnEnd::Done;
+            }
+            let mut results = Vec::new();
+            for call in calls {
+                results.push(self.call_tool(call).await);
+            }
+            self.emit(json!({
+                "type": "user",
+                "message": {"role": "user", "content": results},
+                "parent_tool_use_id": null,
+                "session_id": self.session_id
+            }));
+            messages.push(json!({"role": "user", "content": results}));
+        }
+    }
+
+    fn result(&self, turn: &Turn, end: TurnEnd, started: Instant) -> Value {
+        let (subtype, is_error, text) = match end {
+            TurnEnd::Done => ("success", false, Some(turn.text.clone())),
+            TurnEnd::MaxTurns => ("error_max_turns", true, None),
+            TurnEnd::Failed(message) => ("error_during_execution", true, Some(message)),
+            TurnEnd::Interrupted => ("error_during_execution", true, None),
+        };
+        let mut result = json!({
+            "type": "result",
+            "subtype": subtype,
+            "duration_ms": started.elapsed().as_millis() as u64,
+            "duration_api_ms": turn.api_duration_ms,
+            "is_error": is_error,
+            "num_turns": turn.num_turns,
+            "session_id": self.session_id,
+            "usage": {
+                "input_tokens": turn.input_tokens,
+                "output_tokens": turn.output_tokens
+            }
+        });
+        if let Some(text) = text {
+            result["result"] = json!(text);
+        }
+        result
+    }
+}
+
+/// The agent loop: one turn per prompt, until input ends.
+async fn run(shared: Arc<Shared>, mut prompts: mpsc::UnboundedReceiver<Value>) {
+    let mut messages = Vec::new();
+    while let Some(content) = prompts.recv().await {
+        let interrupt = CancellationToken::new();
+        *shared.interrupt.lock().unwrap_or_else(|e| e.into_inner()) = interrupt.clone();
+        let started = Instant::now();
+        shared.emit(json!({
+            "type": "system",
+            "subtype": "init",
+            "session_id": shared.session_id,
+            "model": shared.model(),
+            "tools": shared.tools().into_iter().map(|(name, ..)| name).collect::<Vec<_>>()
+        }));
+
+        messages.push(json!({"role": "user", "content": content}));
+        let mut turn = Turn::default();
+        let end = tokio::select! {
+            end = shared.run_turn(&mut messages, &mut turn) => end,
+            _ = interrupt.cancelled() => TurnEnd::Interrupted,
+        };
+        if matches!(end, TurnEnd::Interrupted) {
+            answer_pending_calls(&mut messages);
+        }
+        shared.emit(shared.result(&turn, end, started));
+    }
+    shared.out.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+/// Close tool calls cut off by an interrupt, so the conversation stays
+/// valid for the next prompt.
+fn answer_pending_calls(messages: &mut Vec<Value>) {
+    let Some(last) = messages.last() else { return };
+    if last["role"] != "assistant" {
+        return;
+    }
+    let results: Vec<Value> = last["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "tool_use")
+        .map(|block| {
+            json!({
+                "type": "tool_result",
+                "tool_use_id": block["id"],
+                "content": "Interrupted by user",
+                "is_error": true
+            })
+        })
+        .collect();
+    if !results.is_empty() {
+        messages.push(json!({"role": "user", "content": results}));
+    }
+}
+
+/// The text blocks of API content, joined.
+fn text_of(content: &Value) -> String {
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| block["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An MCP tool result's content as API `tool_result` content.
+fn tool_result_content(outcome: &Value) -> Value {
+    let mut blocks: Vec<Value> = outcome["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|item| match item["type"].as_str() {
+            Some("text") => json!({"type": "text", "text": item["text"]}),
+            Some("image") => json!({
+                "type": "image",
+                "source": {
+                    "type": "base64",
+                    "media_type": item["mimeType"],
+                    "data": item["data"]
+                }
+            }),
+            _ => json!({"type": "text", "text": item.to_string()}),
+        })
+        .collect();
+    if blocks.is_empty() {
+        if let Some(structured) = outcome.get("structuredContent") {
+            blocks.push(json!({"type": "text", "text": structured.to_string()}));
+        }
+    }
+    Value::Array(blocks)
+}
+
+#[async_trait]
+impl Transport for ApiTransport {
+    async fn connect(&mut self) -> Result<()> {
+        if self.ready {
+            return Ok(());
+        }
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        let (prompt_tx, prompt_rx) = mpsc::unbounded_channel();
+        *self.shared.out.lock().unwrap_or_else(|e| e.into_inner()) = Some(out_tx);
+        *self.prompts.get_mut().unwrap() = Some(prompt_tx);
+        self.output = Some(out_rx);
+        self.prompt_rx = Some(prompt_rx);
+        self.ready = true;
+        Ok(())
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        let message: Value = serde_json::from_str(data)
+            .map_err(|e| ClaudeSDKError::internal(format!("Invalid message to API: {}", e)))?;
+        match message["type"].as_str() {
+            Some("user") => {
+                let prompts = self.prompts.lock().unwrap_or_else(|e| e.into_inner());
+                let sent = prompts
+                    .as_ref()
+                    .map(|prompts| prompts.send(message["message"]["content"].clone()));
+                if !matches!(sent, Some(Ok(()))) {
+                    return Err(ClaudeSDKError::cli_connection("API session has ended"));
+                }
+            }
+            Some("control_request") => self.on_control_request(&message),
+            Some("control_response") => self.on_control_response(&message),
+            other => debug!("Ignoring {:?} message to API", other),
+        }
+        Ok(())
+    }
+
+    fn message_stream(&self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+        warn!("message_stream() called on ApiTransport - use take_messages() instead");
+        Box::pin(futures::stream::empty())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.ready = false;
+        self.shared
+            .interrupt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .cancel();
+        self.prompts.get_mut().unwrap().take();
+        self.shared
+            .out
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        self.prompts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    fn take_messages(&mut self) -> Option<MessageStream> {
+        let output = self.output.take()?;
+        if let Some(prompts) = self.prompt_rx.take() {
+            self.task = Some(tokio::spawn(run(Arc::clone(&self.shared), prompts)));
+        }
+        Some(Box::pin(UnboundedReceiverStream::new(output).map(Ok)))
+    }
+
+    async fn is_alive(&self) -> bool {
+        self.ready
+            && self
+                .shared
+                .out
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .is_some()
+    }
+}
+
+impl Drop for ApiTransport {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_result_content() {
+        let outcome = json!({
+            "content": [
+                {"type": "text", "text": "done"},
+                {"type": "image", "data": "aGk=", "mimeType": "image/png"}
+            ],
+            "isError": false
+        });
+        assert_eq!(
+            tool_result_content(&outcome),
+            json!([
+                {"type": "text", "text": "done"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "aGk="}}
+            ])
+        );
+        let structured = json!({"content": [], "structuredContent": {"n": 1}});
+        assert_eq!(
+            tool_result_content(&structured),
+            json!([{"type": "text", "text": "{\"n\":1}"}])
+        );
+    }
+
+    #[test]
+    fn test_interrupted_calls_are_answered() {
+        let mut messages = vec![
+            json!({"role": "user", "content": "go"}),
+            json!({"role": "assistant", "content": [
+                {"type": "text", "text": "Running"},
+                {"type": "tool_use", "id": "toolu_1", "name": "mcp__x__y", "input": {}}
+            ]}),
+        ];
+        answer_pending_calls(&mut messages);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+
+        answer_pending_calls(&mut messages);
+        assert_eq!(messages.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permit_without_callback_needs_allowed_tool_or_bypass() {
+        let input = json!({"n": 1});
+        let options = ClaudeAgentOptions::new().with_allowed_tools(["mcp__calc__add"]);
+        let transport = ApiTransport::new(&options, "key").unwrap();
+        let shared = &transport.shared;
+        assert_eq!(
+            shared.permit("mcp__calc__add", &input, "t1").await,
+            Ok(input.clone())
+        );
+        assert_eq!(
+            shared.permit("mcp__calc__sub", &input, "t2").await,
+            Err("Permission to use mcp__calc__sub has been denied.".to_string())
+        );
+
+        let mut options =
+            ClaudeAgentOptions::new().with_permission_mode(PermissionMode::BypassPermissions);
+        options.disallowed_tools = vec!["mcp__calc__div".to_string()];
+        let transport = ApiTransport::new(&options, "key").unwrap();
+        let shared = &transport.shared;
+        assert_eq!(
+            shared.permit("mcp__calc__sub", &input, "t3").await,
+            Ok(input.clone())
+        );
+        assert!(shared.permit("mcp__calc__div", &input, "t4").await.is_err());
+    }
+
+    #[test]
+    fn test_allowed_tools_match_servers() {
+        let options = ClaudeAgentOptions::new().with_allowed_tools(["mcp__calc"]);
+        let transport = ApiTransport::new(&options, "key").unwrap();
+        assert!(transport.shared.is_allowed("mcp__calc__add"));
+        assert!(!transport.shared.is_allowed("mcp__calculator__add"));
+    }
+}
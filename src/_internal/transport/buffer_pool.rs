@@ -0,0 +1,99 @@
+//! Reusable line buffers for the stdout reader.
+//!
+//! With partial messages enabled the CLI prints many small lines per second.
+//! Returning each line's buffer to a pool once it has been decoded lets the
+//! next line reuse the allocation.
+
+/// Most buffers kept for reuse.
+pub(crate) const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Largest buffer capacity kept in the pool. Buffers grown past it by a long
+/// line are freed, so one large tool result does not pin its memory.
+pub(crate) const DEFAULT_WATERMARK: usize = 64 * 1024;
+
+/// A small stack of cleared byte buffers.
+#[derive(Debug)]
+pub(crate) struct BufferPool {
+    buffers: Vec<Vec<u8>>,
+    max_buffers: usize,
+    watermark: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOL_SIZE, DEFAULT_WATERMARK)
+    }
+}
+
+impl BufferPool {
+    /// Create a pool holding at most `max_buffers` buffers of up to
+    /// `watermark` bytes of capacity each.
+    pub(crate) fn new(max_buffers: usize, watermark: usize) -> Self {
+        Self {
+            buffers: Vec::with_capacity(max_buffers),
+            max_buffers,
+            watermark,
+        }
+    }
+
+    /// Take an empty buffer, reusing a pooled one if there is any.
+    pub(crate) fn get(&mut self) -> Vec<u8> {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Return a buffer to the pool. Buffers over the watermark, or beyond
+    /// the pool's size, are dropped.
+    pub(crate) fn put(&mut self, mut buf: Vec<u8>) {
+        if buf.capacity() == 0
+            || buf.capacity() > self.watermark
+            || self.buffers.len() >= self.max_buffers
+        {
+            return;
+        }
+        buf.clear();
+        self.buffers.push(buf);
+    }
+
+    /// Number of buffers ready for reuse.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.buffers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuses_returned_buffer() {
+        let mut pool = BufferPool::new(2, 1024);
+        let mut buf = pool.get();
+        buf.extend_from_slice(b"hello");
+        let ptr = buf.as_ptr();
+        pool.put(buf);
+
+        let reused = pool.get();
+        assert!(reused.is_empty());
+        assert_eq!(reused.as_ptr(), ptr);
+        assert_eq!(pool.len(), 0);
+    }
+
+    #[test]
+    fn test_drops_buffers_over_watermark() {
+        let mut pool = BufferPool::new(2, 16);
+        pool.put(Vec::with_capacity(64));
+        assert_eq!(pool.len(), 0);
+        pool.put(Vec::with_capacity(8));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_holds_at_most_max_buffers() {
+        let mut pool = BufferPool::new(2, 16);
+        for _ in 0..3 {
+            pool.put(Vec::with_capacity(8));
+        }
+        assert_eq!(pool.len(), 2);
+    }
+}
@@ -1,17 +1,44 @@
 //! Transport layer for communicating with the Claude CLI.
 //!
 //! This module provides the [`Transport`] trait for abstracting communication
-//! with the CLI process, and [`SubprocessTransport`] as the concrete implementation.
+//! with the CLI process, `SubprocessTransport` running the CLI as a child
+//! process, [`RemoteTransport`] reaching a CLI through a bridge, and
+//! `ApiTransport` running the agent loop against the Anthropic API without
+//! the CLI.
 
+#[cfg(feature = "api")]
+mod api;
+#[cfg(feature = "subprocess")]
+pub(crate) mod buffer_pool;
+mod remote;
+#[cfg(feature = "subprocess")]
 mod subprocess;
 
+#[cfg(feature = "api")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api")))]
+pub use api::{ApiTransport, DEFAULT_API_MODEL};
+pub use remote::RemoteTransport;
+#[cfg(feature = "subprocess")]
+#[cfg_attr(docsrs, doc(cfg(feature = "subprocess")))]
 pub use subprocess::SubprocessTransport;
 
 use async_trait::async_trait;
 use std::pin::Pin;
+use std::sync::Arc;
 use tokio_stream::Stream;
 
 use crate::errors::Result;
+use crate::types::ClaudeAgentOptions;
+
+/// Largest line accepted from the CLI when none is configured, in bytes.
+pub(crate) const DEFAULT_MAX_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Makes a transport for a session's options.
+pub type TransportFactory =
+    Arc<dyn Fn(&ClaudeAgentOptions) -> Result<Box<dyn Transport>> + Send + Sync>;
+
+/// CLI output owned by its reader, for reading from another task.
+pub type MessageStream = Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send>>;
 
 /// Abstract transport trait for CLI communication.
 ///
@@ -37,4 +64,18 @@ pub trait Transport: Send + Sync {
 
     /// Check if the transport is ready for communication.
     fn is_ready(&self) -> bool;
+
+    /// Take the CLI's output for reading from another task.
+    ///
+    /// [`ClaudeClient`](crate::ClaudeClient) and [`query`](crate::query())
+    /// read through this, so a transport must return it once after
+    /// connecting to be used by them. Returns `None` by default.
+    fn take_messages(&mut self) -> Option<MessageStream> {
+        None
+    }
+
+    /// Check if the CLI on the other end is still running.
+    async fn is_alive(&self) -> bool {
+        self.is_ready()
+    }
 }
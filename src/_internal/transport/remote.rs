@@ -0,0 +1,236 @@
+//! Reaching the CLI through a bridge.
+//!
+//! Where the CLI can't run as a child process, such as in a browser or at
+//! the edge, a bridge service runs it and relays its stdin and stdout over a
+//! connection like a WebSocket. [`RemoteTransport`] speaks the CLI's
+//! newline-delimited JSON over any such connection.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::{Sink, SinkExt, StreamExt};
+use tokio::sync::Mutex;
+use tokio_stream::Stream;
+use tracing::warn;
+
+use super::{MessageStream, Transport, DEFAULT_MAX_BUFFER_SIZE};
+use crate::_internal::framing::JsonFramer;
+use crate::errors::{ClaudeSDKError, Result};
+
+type FrameSink = Pin<Box<dyn Sink<String, Error = ClaudeSDKError> + Send>>;
+type FrameStream = Pin<Box<dyn Stream<Item = Result<String>> + Send>>;
+
+/// A transport over a connection to a bridge running the CLI.
+///
+/// Built from the two halves of a connection: a sink taking outgoing text
+/// frames and a stream of incoming ones, as a split WebSocket provides. Each
+/// message goes out as one frame ending in a newline, and incoming frames
+/// may split or combine lines freely, so a bridge can copy bytes between the
+/// connection and the CLI's stdin and stdout unchanged.
+///
+/// Ending input closes the sink; bridges should close the CLI's stdin then
+/// and keep relaying its output.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use claude_agents_sdk::_internal::transport::RemoteTransport;
+/// use claude_agents_sdk::{ClaudeAgentOptions, ClaudeClient};
+/// use futures::{SinkExt, StreamExt, TryStreamExt};
+///
+/// let options = ClaudeAgentOptions::new().with_transport(|_| {
+///     // With gloo-net in a browser; any WebSocket library works alike.
+///     let socket = gloo_net::websocket::futures::WebSocket::open("wss://bridge.example/cli")?;
+///     let (sink, stream) = socket.split();
+///     Ok(RemoteTransport::new(
+///         sink.with(|text| async { Ok(Message::Text(text)) }),
+///         stream.try_filter_map(|message| async { Ok(text_of(message)) }),
+///     ))
+/// });
+/// let mut client = ClaudeClient::new(Some(options));
+/// ```
+pub struct RemoteTransport {
+    sink: Mutex<FrameSink>,
+    incoming: std::sync::Mutex<Option<FrameStream>>,
+    max_buffer_size: usize,
+    ready: bool,
+    /// Whether the bridge is still sending output.
+    open: Arc<AtomicBool>,
+}
+
+impl RemoteTransport {
+    /// Relay messages over `sink`, reading the CLI's output from `stream`.
+    pub fn new<S, R, E, F>(sink: S, stream: R) -> Self
+    where
+        S: Sink<String, Error = E> + Send + 'static,
+        R: Stream<Item = std::result::Result<String, F>> + Send + 'static,
+        E: Display,
+        F: Display,
+    {
+        let sink = sink.sink_map_err(|e| {
+            ClaudeSDKError::cli_connection(format!("Failed to send to CLI bridge: {}", e))
+        });
+        let stream = stream.map(|frame| {
+            frame.map_err(|e| {
+                ClaudeSDKError::cli_connection(format!("Failed to read from CLI bridge: {}", e))
+            })
+        });
+        Self {
+            sink: Mutex::new(Box::pin(sink)),
+            incoming: std::sync::Mutex::new(Some(Box::pin(stream))),
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+            ready: false,
+            open: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Set the largest message accepted from the bridge, in bytes.
+    pub fn with_max_buffer_size(mut self, bytes: usize) -> Self {
+        self.max_buffer_size = bytes;
+        self
+    }
+}
+
+/// Split incoming frames into JSON documents.
+fn documents(frames: FrameStream, max_len: usize, open: Arc<AtomicBool>) -> MessageStream {
+    let state = (frames, JsonFramer::new(max_len), VecDeque::new(), open);
+    Box::pin(futures::stream::unfold(
+        state,
+        |(mut frames, mut framer, mut ready, open)| async move {
+            loop {
+                if let Some(document) = ready.pop_front() {
+                    return Some((document, (frames, framer, ready, open)));
+                }
+                if !open.load(Ordering::SeqCst) {
+                    return None;
+                }
+                match frames.next().await {
+                    Some(Ok(frame)) => {
+                        for line in frame.split('\n') {
+                            ready.extend(framer.push_line(line.strip_suffix('\r').unwrap_or(line)));
+                        }
+                    }
+                    Some(Err(e)) => {
+                        open.store(false, Ordering::SeqCst);
+                        ready.push_back(Err(e));
+                    }
+                    None => {
+                        open.store(false, Ordering::SeqCst);
+                        ready.extend(framer.finish());
+                    }
+                }
+            }
+        },
+    ))
+}
+
+#[async_trait]
+impl Transport for RemoteTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.ready = true;
+        Ok(())
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        let mut frame = data.to_string();
+        if !frame.ends_with('\n') {
+            frame.push('\n');
+        }
+        self.sink.lock().await.send(frame).await
+    }
+
+    fn message_stream(&self) -> Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send + '_>> {
+        warn!("message_stream() called on RemoteTransport - use take_messages() instead");
+        Box::pin(futures::stream::empty())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.ready = false;
+        *self.incoming.get_mut().unwrap() = None;
+        self.sink.lock().await.close().await
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        self.sink.lock().await.close().await
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    fn take_messages(&mut self) -> Option<MessageStream> {
+        let frames = self.incoming.get_mut().unwrap().take()?;
+        Some(documents(
+            frames,
+            self.max_buffer_size,
+            Arc::clone(&self.open),
+        ))
+    }
+
+    async fn is_alive(&self) -> bool {
+        self.ready && self.open.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+
+    fn transport() -> (
+        RemoteTransport,
+        mpsc::UnboundedReceiver<String>,
+        mpsc::UnboundedSender<std::result::Result<String, String>>,
+    ) {
+        let (out_tx, out_rx) = mpsc::unbounded();
+        let (in_tx, in_rx) = mpsc::unbounded();
+        (RemoteTransport::new(out_tx, in_rx), out_rx, in_tx)
+    }
+
+    #[tokio::test]
+    async fn test_writes_one_line_per_frame() {
+        let (mut transport, mut out_rx, _in_tx) = transport();
+        transport.connect().await.unwrap();
+        transport.write(r#"{"type":"user"}"#).await.unwrap();
+        assert_eq!(out_rx.next().await.unwrap(), "{\"type\":\"user\"}\n");
+
+        transport.end_input().await.unwrap();
+        assert!(out_rx.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reads_documents_across_frames() {
+        let (mut transport, _out_rx, in_tx) = transport();
+        transport.connect().await.unwrap();
+        let mut messages = transport.take_messages().unwrap();
+        assert!(transport.take_messages().is_none());
+
+        for frame in ["{\"a\":1}\n{\"b\"", ":2}\n", "{\"c\":3}"] {
+            in_tx.unbounded_send(Ok(frame.to_string())).unwrap();
+        }
+        for key in ["a", "b", "c"] {
+            assert!(messages.next().await.unwrap().unwrap().get(key).is_some());
+        }
+
+        assert!(transport.is_alive().await);
+        drop(in_tx);
+        assert!(messages.next().await.is_none());
+        assert!(!transport.is_alive().await);
+    }
+
+    #[tokio::test]
+    async fn test_connection_error_ends_stream() {
+        let (mut transport, _out_rx, in_tx) = transport();
+        let mut messages = transport.take_messages().unwrap();
+        in_tx.unbounded_send(Err("reset".to_string())).unwrap();
+        assert!(matches!(
+            messages.next().await,
+            Some(Err(ClaudeSDKError::CLIConnection { .. }))
+        ));
+        assert!(messages.next().await.is_none());
+    }
+}
@@ -4,27 +4,152 @@
 //! that communicates with the Claude CLI via subprocess stdin/stdout.
 
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 use tokio_stream::Stream;
 use tracing::{debug, error, trace, warn};
 
-use super::Transport;
+use super::{MessageStream, Transport, DEFAULT_MAX_BUFFER_SIZE};
+use crate::_internal::framing::JsonFramer;
+use crate::_internal::outbox;
+use crate::_internal::spill::{self, Line, LineReader, SpillFiles};
 use crate::errors::{ClaudeSDKError, Result};
+use crate::redact::{self, Redactor};
 use crate::types::*;
-
-/// Default maximum buffer size (1MB).
-const DEFAULT_MAX_BUFFER_SIZE: usize = 1024 * 1024;
+use crate::wire_log::{WireDirection, WireLog, WireTap};
 
 /// Default CLI command name.
 const DEFAULT_CLI_PATH: &str = "claude";
 
+/// How much of the end of stderr to keep for error reports (8KB).
+const STDERR_TAIL_BYTES: usize = 8 * 1024;
+
+/// How long to wait for the process to exit after unparsable output or EOF
+/// before giving up on reporting its exit status.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Variables kept when the environment is not inherited, so the CLI can
+/// still find its runtime and a home and temp directory.
+const ESSENTIAL_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+];
+
+/// The end of the CLI's stderr, kept for error reports.
+#[derive(Default)]
+struct StderrTail {
+    text: std::sync::Mutex<String>,
+}
+
+impl StderrTail {
+    fn push_line(&self, line: &str) {
+        let mut text = self.text.lock().unwrap_or_else(|e| e.into_inner());
+        text.push_str(line);
+        text.push('\n');
+        if text.len() > STDERR_TAIL_BYTES {
+            let mut cut = text.len() - STDERR_TAIL_BYTES;
+            while !text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            text.drain(..cut);
+        }
+    }
+
+    fn get(&self) -> String {
+        self.text.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// The first 200 characters of a line, for trace output.
+fn preview(line: &str) -> &str {
+    match line.char_indices().nth(200) {
+        Some((end, _)) => &line[..end],
+        None => line,
+    }
+}
+
+/// What the transport needs to tell a clean exit from a failure and build
+/// [`ClaudeSDKError::ProcessFailed`] or [`ClaudeSDKError::ProcessExited`].
+#[derive(Clone)]
+struct ExitWatch {
+    process: Weak<Mutex<Child>>,
+    stderr_tail: Arc<StderrTail>,
+    redactor: Option<Redactor>,
+    /// Becomes true once stderr has been read to the end.
+    stderr_done: tokio::sync::watch::Receiver<bool>,
+    argv: Vec<String>,
+    /// Set once the CLI has printed a valid protocol line.
+    session_started: Arc<AtomicBool>,
+    /// Set once the SDK has closed stdin, after which a clean exit is expected.
+    input_closed: Arc<AtomicBool>,
+    /// Set once the SDK is shutting the process down, after which any exit is
+    /// expected.
+    closing: Arc<AtomicBool>,
+}
+
+impl ExitWatch {
+    /// If the process exits within the grace period and the exit was not
+    /// expected, build the error describing it.
+    ///
+    /// `unparsable_output` is set when the last thing the CLI printed was not
+    /// JSON; a failure then is reported as `ProcessFailed`, and a clean exit
+    /// leaves the decode error to speak for itself.
+    async fn failure(&self, unparsable_output: bool) -> Option<ClaudeSDKError> {
+        let process = self.process.upgrade()?;
+        let status = tokio::time::timeout(EXIT_GRACE_PERIOD, async {
+            process.lock().await.wait().await
+        })
+        .await
+        .ok()?
+        .ok()?;
+        if self.closing.load(Ordering::SeqCst) {
+            return None;
+        }
+        let expected = self.input_closed.load(Ordering::SeqCst);
+        if status.success() && (expected || unparsable_output) {
+            return None;
+        }
+
+        // Exit closes stderr, but the reader may still be draining it.
+        let mut stderr_done = self.stderr_done.clone();
+        let _ = tokio::time::timeout(EXIT_GRACE_PERIOD, stderr_done.wait_for(|done| *done)).await;
+        let stderr = redact::redact(self.redactor.as_ref(), &self.stderr_tail.get()).into_owned();
+        let argv = self
+            .argv
+            .iter()
+            .map(|arg| redact::redact(self.redactor.as_ref(), arg).into_owned())
+            .collect();
+
+        let started = self.session_started.load(Ordering::SeqCst);
+        if unparsable_output || (!started && !status.success()) {
+            return Some(ClaudeSDKError::process_failed(status.code(), stderr, argv));
+        }
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Some(ClaudeSDKError::process_exited(
+            status.code(),
+            signal,
+            argv,
+            stderr,
+        ))
+    }
+}
+
 /// Subprocess-based transport for communicating with the Claude CLI.
 ///
 /// This transport spawns the Claude CLI as a subprocess and communicates
@@ -42,8 +167,15 @@ pub struct SubprocessTransport {
     env: HashMap<String, String>,
     /// Maximum buffer size.
     max_buffer_size: usize,
-    /// Child process handle.
-    process: Option<Child>,
+    /// Where to write lines longer than `max_buffer_size`.
+    spill_dir: Option<PathBuf>,
+    /// Spill files of messages not parsed yet, deleted with the transport.
+    spilled: Arc<SpillFiles>,
+    /// Capacity of the stdout line channel.
+    channel_capacity: usize,
+    /// Child process handle, shared with the stdout reader so it can report
+    /// the exit status.
+    process: Option<Arc<Mutex<Child>>>,
     /// Stdin handle (wrapped in mutex for thread safety).
     /// Inner Option allows dropping stdin to send EOF to the child process.
     stdin: Option<Arc<Mutex<Option<tokio::process::ChildStdin>>>>,
@@ -55,6 +187,16 @@ pub struct SubprocessTransport {
     ready: bool,
     /// Working directory.
     cwd: Option<PathBuf>,
+    /// Where to capture raw protocol lines.
+    wire_log: Option<WireLog>,
+    /// The open wire log, once connected.
+    wire_tap: Option<WireTap>,
+    /// Exit reporting for the running process.
+    exit_watch: Option<ExitWatch>,
+    /// Redacts logged protocol lines, arguments, and stderr.
+    redactor: Option<Redactor>,
+    /// Session metadata, tagging wire log entries.
+    metadata: BTreeMap<String, String>,
 }
 
 impl SubprocessTransport {
@@ -85,17 +227,29 @@ impl SubprocessTransport {
             args,
             env,
             max_buffer_size,
+            spill_dir: options.spill_dir.clone(),
+            spilled: Arc::default(),
+            channel_capacity: outbox::channel_capacity(options),
             process: None,
             stdin: None,
             stdout_rx: None,
             stderr_callback: options.stderr.clone(),
             ready: false,
             cwd: options.cwd.clone(),
+            wire_log: options.wire_log.clone(),
+            wire_tap: None,
+            exit_watch: None,
+            redactor: options.redactor.clone(),
+            metadata: options
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         })
     }
 
     /// Build command-line arguments from options.
-    fn build_args(options: &ClaudeAgentOptions) -> Result<Vec<String>> {
+    pub(crate) fn build_args(options: &ClaudeAgentOptions) -> Result<Vec<String>> {
         let mut args = vec![
             "--output-format".to_string(),
             "stream-json".to_string(),
@@ -143,6 +297,16 @@ impl SubprocessTransport {
             );
         }
 
+        // Permission prompts: to the SDK's callback over the control
+        // protocol, or to an MCP tool
+        if options.can_use_tool.is_some() {
+            args.push("--permission-prompt-tool".to_string());
+            args.push("stdio".to_string());
+        } else if let Some(ref tool) = options.permission_prompt_tool_name {
+            args.push("--permission-prompt-tool".to_string());
+            args.push(tool.clone());
+        }
+
         // Model
         if let Some(ref model) = options.model {
             args.push("--model".to_string());
@@ -240,20 +404,25 @@ impl SubprocessTransport {
             }
         }
 
-        // MCP servers
+        // MCP servers (SDK servers are merged in as {"type": "sdk", "name": ...})
+        let mut mcp_servers = Self::sdk_mcp_server_entries(options);
         match &options.mcp_servers {
             McpServersConfig::Path(path) => {
                 args.push("--mcp-config".to_string());
                 args.push(path.to_string_lossy().to_string());
             }
-            McpServersConfig::Map(servers) if !servers.is_empty() => {
-                let json = serde_json::to_string(servers).map_err(|e| {
+            McpServersConfig::Map(servers) => {
+                let value = serde_json::to_value(servers).map_err(|e| {
                     ClaudeSDKError::configuration(format!("Failed to serialize MCP servers: {}", e))
                 })?;
-                args.push("--mcp-servers".to_string());
-                args.push(json);
+                if let serde_json::Value::Object(map) = value {
+                    mcp_servers.extend(map);
+                }
             }
-            _ => {}
+        }
+        if !mcp_servers.is_empty() {
+            args.push("--mcp-servers".to_string());
+            args.push(serde_json::Value::Object(mcp_servers).to_string());
         }
 
         // User
@@ -283,6 +452,19 @@ impl SubprocessTransport {
             }
         }
 
+        // Local plugins
+        for plugin in &options.plugins {
+            if plugin.plugin_type == "local" {
+                args.push("--plugin-dir".to_string());
+                args.push(plugin.path.clone());
+            } else {
+                warn!(
+                    "Ignoring plugin of unsupported type '{}'",
+                    plugin.plugin_type
+                );
+            }
+        }
+
         // Additional directories
         for dir in &options.add_dirs {
             args.push("--add-dir".to_string());
@@ -294,9 +476,11 @@ impl SubprocessTransport {
             args.push("--include-partial-messages".to_string());
         }
 
-        // File checkpointing
+        // File checkpointing. Echoed prompts carry the message IDs that
+        // checkpoints are keyed by.
         if options.enable_file_checkpointing {
             args.push("--enable-file-checkpointing".to_string());
+            args.push("--replay-user-messages".to_string());
         }
 
         // Sandbox settings
@@ -325,28 +509,64 @@ impl SubprocessTransport {
         // Beta features
         for beta in &options.betas {
             args.push("--beta".to_string());
-            args.push(
-                serde_json::to_string(beta)
-                    .unwrap_or_else(|_| format!("{:?}", beta))
-                    .trim_matches('"')
-                    .to_string(),
-            );
+            args.push(beta.as_str().to_string());
         }
 
         // Extra args
-        for (key, value) in &options.extra_args {
-            args.push(format!("--{}", key));
-            if let Some(v) = value {
-                args.push(v.clone());
-            }
+        for arg in CliArg::from_map(&options.extra_args)
+            .iter()
+            .chain(&options.cli_args)
+        {
+            args.extend(arg.to_args());
         }
 
         Ok(args)
     }
 
+    /// Build the CLI config entries for in-process SDK MCP servers.
+    #[cfg(feature = "mcp")]
+    fn sdk_mcp_server_entries(
+        options: &ClaudeAgentOptions,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        options
+            .sdk_mcp_servers
+            .iter()
+            .map(|(name, server)| {
+                let value = serde_json::to_value(server.config())
+                    .unwrap_or_else(|_| serde_json::json!({"type": "sdk", "name": name}));
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Build the CLI config entries for in-process SDK MCP servers.
+    #[cfg(not(feature = "mcp"))]
+    fn sdk_mcp_server_entries(
+        _options: &ClaudeAgentOptions,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        serde_json::Map::new()
+    }
+
     /// Build environment variables.
-    fn build_env(options: &ClaudeAgentOptions) -> HashMap<String, String> {
-        let mut env = std::env::vars().collect::<HashMap<_, _>>();
+    ///
+    /// Later sources win: the inherited environment, the proxy, feature
+    /// flags, then `options.env`.
+    pub(crate) fn build_env(options: &ClaudeAgentOptions) -> HashMap<String, String> {
+        let mut env = if options.clear_env {
+            std::env::vars()
+                .filter(|(key, _)| ESSENTIAL_ENV_VARS.contains(&key.as_str()))
+                .collect::<HashMap<_, _>>()
+        } else {
+            std::env::vars().collect::<HashMap<_, _>>()
+        };
+
+        if let Some(ref proxy) = options.proxy {
+            env.extend(proxy.env_vars());
+        }
+        for (name, enabled) in &options.feature_flags {
+            let value = if *enabled { "1" } else { "0" };
+            env.insert(name.clone(), value.to_string());
+        }
 
         // Override with user-specified env vars
         for (key, value) in &options.env {
@@ -363,45 +583,146 @@ impl SubprocessTransport {
     fn spawn_stdout_reader(
         stdout: tokio::process::ChildStdout,
         max_buffer_size: usize,
+        spill: Option<(PathBuf, Weak<SpillFiles>)>,
+        channel_capacity: usize,
+        wire_tap: Option<WireTap>,
+        exit_watch: ExitWatch,
+        redactor: Option<Redactor>,
     ) -> tokio::sync::mpsc::Receiver<Result<serde_json::Value>> {
-        let (tx, rx) = tokio::sync::mpsc::channel(256);
+        let (tx, rx) = tokio::sync::mpsc::channel(channel_capacity);
 
         tokio::spawn(async move {
             let reader = BufReader::with_capacity(max_buffer_size, stdout);
-            let mut lines = reader.lines();
-
-            loop {
-                match lines.next_line().await {
-                    Ok(Some(line)) => {
-                        let display_len = line.len().min(200);
-                        trace!("Received line from CLI: {}", &line[..display_len]);
-
-                        let result = match serde_json::from_str(&line) {
-                            Ok(value) => Ok(value),
-                            Err(e) => Err(ClaudeSDKError::json_decode_with_context(
-                                "Failed to parse JSON from CLI",
-                                Some(line),
-                                None,
-                                e,
-                            )),
+            let (spill_dir, spilled) = spill.unzip();
+            let mut lines = LineReader::new(reader, max_buffer_size, spill_dir);
+            let mut framer = JsonFramer::new(max_buffer_size);
+            // A decode error is held back briefly: if the CLI exits right after
+            // printing garbage, the process failure is the more useful error.
+            let mut pending: Option<ClaudeSDKError> = None;
+
+            'read: loop {
+                let next = match pending.take() {
+                    Some(error) => {
+                        match tokio::time::timeout(EXIT_GRACE_PERIOD, lines.next_line()).await {
+                            Ok(Ok(None)) => {
+                                let error = exit_watch.failure(true).await.unwrap_or(error);
+                                let _ = tx.send(Err(error)).await;
+                                break;
+                            }
+                            Ok(next) => {
+                                if tx.send(Err(error)).await.is_err() {
+                                    break;
+                                }
+                                next
+                            }
+                            Err(_) => match exit_watch.failure(true).await {
+                                Some(failure) => {
+                                    let _ = tx.send(Err(failure)).await;
+                                    break;
+                                }
+                                None => {
+                                    if tx.send(Err(error)).await.is_err() {
+                                        break;
+                                    }
+                                    continue;
+                                }
+                            },
+                        }
+                    }
+                    None => lines.next_line().await,
+                };
+
+                match next {
+                    Ok(Some(Line::Spilled { path, len })) => {
+                        debug!("Spilled {} byte line from CLI to {}", len, path.display());
+                        let loaded = tokio::task::spawn_blocking(move || {
+                            spill::load_spilled(path, max_buffer_size)
+                        })
+                        .await
+                        .unwrap_or_else(|e| {
+                            Err(ClaudeSDKError::internal(format!(
+                                "Loading spilled output failed: {}",
+                                e
+                            )))
+                        });
+                        let value = match loaded {
+                            Ok((value, file)) => {
+                                if let Some(file) = file {
+                                    // Once the transport is gone, its files go too.
+                                    match spilled.as_ref().and_then(Weak::upgrade) {
+                                        Some(spilled) => spilled.add(file),
+                                        None => spill::release(&[file]),
+                                    }
+                                }
+                                exit_watch.session_started.store(true, Ordering::SeqCst);
+                                if let Some(ref tap) = wire_tap {
+                                    tap.record(WireDirection::Received, &value.to_string());
+                                }
+                                value
+                            }
+                            Err(e) => {
+                                pending = Some(e);
+                                continue;
+                            }
                         };
 
-                        if tx.send(result).await.is_err() {
+                        if tx.send(Ok(value)).await.is_err() {
                             debug!("Stdout reader: receiver dropped");
                             break;
                         }
                     }
+                    Ok(Some(Line::Text(line))) => {
+                        trace!(
+                            "Received line from CLI: {}",
+                            preview(&redact::redact_line(redactor.as_ref(), &line))
+                        );
+                        if let Some(ref tap) = wire_tap {
+                            tap.record(WireDirection::Received, &line);
+                        }
+
+                        let documents = framer.push_line(&line);
+                        lines.recycle(line);
+                        for document in documents {
+                            let (error, value) = match document {
+                                Ok(value) => {
+                                    exit_watch.session_started.store(true, Ordering::SeqCst);
+                                    (pending.take(), Some(value))
+                                }
+                                // Only the last error of a line waits for the
+                                // grace period; earlier ones are sent as is.
+                                Err(e) => (pending.replace(e), None),
+                            };
+                            for item in error.map(Err).into_iter().chain(value.map(Ok)) {
+                                if tx.send(item).await.is_err() {
+                                    debug!("Stdout reader: receiver dropped");
+                                    break 'read;
+                                }
+                            }
+                        }
+                    }
+                    Ok(None) if framer.has_pending() => {
+                        // Output ended inside a document: report it like a
+                        // line that is not JSON.
+                        if let Some(Err(e)) = framer.finish() {
+                            pending = Some(e);
+                        }
+                    }
                     Ok(None) => {
                         debug!("Stdout reader: EOF received");
+                        if let Some(failure) = exit_watch.failure(false).await {
+                            let _ = tx.send(Err(failure)).await;
+                        }
                         break;
                     }
                     Err(e) => {
-                        let _ = tx
-                            .send(Err(ClaudeSDKError::cli_connection_with_source(
+                        let error = match exit_watch.failure(false).await {
+                            Some(failure) => failure,
+                            None => ClaudeSDKError::cli_connection_with_source(
                                 "Failed to read from CLI stdout",
                                 e,
-                            )))
-                            .await;
+                            ),
+                        };
+                        let _ = tx.send(Err(error)).await;
                         break;
                     }
                 }
@@ -417,6 +738,9 @@ impl SubprocessTransport {
     fn spawn_stderr_reader(
         stderr: tokio::process::ChildStderr,
         callback: Option<Arc<dyn Fn(String) + Send + Sync>>,
+        tail: Arc<StderrTail>,
+        redactor: Option<Redactor>,
+        done: tokio::sync::watch::Sender<bool>,
     ) {
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
@@ -425,7 +749,8 @@ impl SubprocessTransport {
             loop {
                 match lines.next_line().await {
                     Ok(Some(line)) => {
-                        trace!("CLI stderr: {}", line);
+                        trace!("CLI stderr: {}", redact::redact(redactor.as_ref(), &line));
+                        tail.push_line(&line);
                         if let Some(ref cb) = callback {
                             cb(line);
                         }
@@ -442,6 +767,7 @@ impl SubprocessTransport {
             }
 
             debug!("Stderr reader task finished");
+            let _ = done.send(true);
         });
     }
 }
@@ -452,11 +778,18 @@ impl Transport for SubprocessTransport {
         debug!(
             "Starting CLI process: {} {:?}",
             self.cli_path.display(),
-            self.args
+            redact::redact(self.redactor.as_ref(), &format!("{:?}", self.args))
         );
 
+        self.wire_tap = self
+            .wire_log
+            .as_ref()
+            .map(|log| log.open(self.redactor.clone(), self.metadata.clone()))
+            .transpose()?;
+
         let mut cmd = Command::new(&self.cli_path);
         cmd.args(&self.args)
+            .env_clear()
             .envs(&self.env)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -489,18 +822,50 @@ impl Transport for SubprocessTransport {
             .ok_or_else(|| ClaudeSDKError::cli_connection("Failed to open stdin to CLI process"))?;
         self.stdin = Some(Arc::new(Mutex::new(Some(stdin))));
 
-        // Take stdout and start reader task
-        let stdout = child.stdout.take().ok_or_else(|| {
-            ClaudeSDKError::cli_connection("Failed to open stdout from CLI process")
-        })?;
-        self.stdout_rx = Some(Self::spawn_stdout_reader(stdout, self.max_buffer_size));
-
         // Take stderr and start reader task
+        let stderr_tail = Arc::new(StderrTail::default());
+        let (stderr_done_tx, stderr_done) = tokio::sync::watch::channel(false);
         if let Some(stderr) = child.stderr.take() {
-            Self::spawn_stderr_reader(stderr, self.stderr_callback.clone());
+            Self::spawn_stderr_reader(
+                stderr,
+                self.stderr_callback.clone(),
+                stderr_tail.clone(),
+                self.redactor.clone(),
+                stderr_done_tx,
+            );
         }
 
-        self.process = Some(child);
+        // Take stdout and start reader task
+        let stdout = child.stdout.take().ok_or_else(|| {
+            ClaudeSDKError::cli_connection("Failed to open stdout from CLI process")
+        })?;
+        let process = Arc::new(Mutex::new(child));
+        let exit_watch = ExitWatch {
+            process: Arc::downgrade(&process),
+            stderr_tail,
+            redactor: self.redactor.clone(),
+            stderr_done,
+            argv: std::iter::once(self.cli_path.display().to_string())
+                .chain(self.args.iter().cloned())
+                .collect(),
+            session_started: Arc::default(),
+            input_closed: Arc::default(),
+            closing: Arc::default(),
+        };
+        self.stdout_rx = Some(Self::spawn_stdout_reader(
+            stdout,
+            self.max_buffer_size,
+            self.spill_dir
+                .clone()
+                .map(|dir| (dir, Arc::downgrade(&self.spilled))),
+            self.channel_capacity,
+            self.wire_tap.clone(),
+            exit_watch.clone(),
+            self.redactor.clone(),
+        ));
+        self.exit_watch = Some(exit_watch);
+
+        self.process = Some(process);
         self.ready = true;
 
         debug!("CLI process started successfully");
@@ -518,39 +883,59 @@ impl Transport for SubprocessTransport {
             .as_mut()
             .ok_or_else(|| ClaudeSDKError::cli_connection("Stdin already closed"))?;
 
-        trace!("Writing to CLI: {}", &data[..data.len().min(200)]);
-
-        stdin.write_all(data.as_bytes()).await.map_err(|e| {
-            ClaudeSDKError::cli_connection_with_source("Failed to write to CLI stdin", e)
-        })?;
-
-        stdin.write_all(b"\n").await.map_err(|e| {
-            ClaudeSDKError::cli_connection_with_source("Failed to write newline to CLI stdin", e)
-        })?;
-
-        stdin.flush().await.map_err(|e| {
-            ClaudeSDKError::cli_connection_with_source("Failed to flush CLI stdin", e)
-        })?;
+        trace!(
+            "Writing to CLI: {}",
+            preview(&redact::redact_line(self.redactor.as_ref(), data))
+        );
+        if let Some(ref tap) = self.wire_tap {
+            tap.record(WireDirection::Sent, data);
+        }
 
-        Ok(())
+        let written = async {
+            stdin
+                .write_all(data.as_bytes())
+                .await
+                .map_err(|e| ("Failed to write to CLI stdin", e))?;
+            stdin
+                .write_all(b"\n")
+                .await
+                .map_err(|e| ("Failed to write newline to CLI stdin", e))?;
+            stdin
+                .flush()
+                .await
+                .map_err(|e| ("Failed to flush CLI stdin", e))
+        };
+
+        match written.await {
+            Ok(()) => Ok(()),
+            Err((message, e)) => {
+                // A write usually fails because the process died; say why.
+                if let Some(ref exit_watch) = self.exit_watch {
+                    if let Some(failure) = exit_watch.failure(false).await {
+                        return Err(failure);
+                    }
+                }
+                Err(ClaudeSDKError::cli_connection_with_source(message, e))
+            }
+        }
     }
 
     fn message_stream(&self) -> Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send + '_>> {
         // The message_stream method from the Transport trait cannot be properly
         // implemented with &self because we need to take ownership of the channel.
-        // Users should use take_stdout_rx() instead which takes &mut self.
+        // Users should use take_messages() instead which takes &mut self.
         //
         // This returns an empty stream - the actual message stream is obtained
-        // via take_stdout_rx() on SubprocessTransport directly.
-        //
-        // Note: If custom transport support is re-added in the future, this trait
-        // method should be redesigned to use &mut self or an Arc-wrapped receiver.
-        warn!("message_stream() called on SubprocessTransport - use take_stdout_rx() instead");
+        // via take_messages(), or take_stdout_rx() on SubprocessTransport directly.
+        warn!("message_stream() called on SubprocessTransport - use take_messages() instead");
         Box::pin(futures::stream::empty())
     }
 
     async fn close(&mut self) -> Result<()> {
         self.ready = false;
+        if let Some(ref exit_watch) = self.exit_watch {
+            exit_watch.closing.store(true, Ordering::SeqCst);
+        }
 
         // Close stdin first
         if let Some(stdin) = self.stdin.take() {
@@ -558,7 +943,8 @@ impl Transport for SubprocessTransport {
         }
 
         // Wait for process to exit or kill it
-        if let Some(mut process) = self.process.take() {
+        if let Some(process) = self.process.take() {
+            let mut process = process.lock().await;
             // Give it a moment to exit gracefully
             match tokio::time::timeout(std::time::Duration::from_secs(2), process.wait()).await {
                 Ok(Ok(status)) => {
@@ -578,6 +964,9 @@ impl Transport for SubprocessTransport {
     }
 
     async fn end_input(&self) -> Result<()> {
+        if let Some(ref exit_watch) = self.exit_watch {
+            exit_watch.input_closed.store(true, Ordering::SeqCst);
+        }
         // Drop the ChildStdin handle to send EOF to the process
         if let Some(stdin_arc) = &self.stdin {
             let mut guard = stdin_arc.lock().await;
@@ -592,6 +981,20 @@ impl Transport for SubprocessTransport {
     fn is_ready(&self) -> bool {
         self.ready
     }
+
+    fn take_messages(&mut self) -> Option<MessageStream> {
+        let rx = self.take_stdout_rx()?;
+        Some(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
+    /// Whether the CLI process is running.
+    async fn is_alive(&self) -> bool {
+        let Some(process) = &self.process else {
+            return false;
+        };
+        let mut process = process.lock().await;
+        matches!(process.try_wait(), Ok(None))
+    }
 }
 
 impl SubprocessTransport {
@@ -627,6 +1030,26 @@ mod tests {
         assert!(args.contains(&"claude-3-sonnet".to_string()));
     }
 
+    #[test]
+    fn test_build_args_permission_prompt_tool() {
+        let args = SubprocessTransport::build_args(&ClaudeAgentOptions::new()).unwrap();
+        assert!(!args.contains(&"--permission-prompt-tool".to_string()));
+
+        let options = ClaudeAgentOptions::new()
+            .with_can_use_tool(|_, _, _| async { PermissionResult::allow() });
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        let pos = args
+            .iter()
+            .position(|a| a == "--permission-prompt-tool")
+            .unwrap();
+        assert_eq!(args[pos + 1], "stdio");
+
+        let mut options = ClaudeAgentOptions::new();
+        options.permission_prompt_tool_name = Some("mcp__auth__approve".to_string());
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        assert!(args.contains(&"mcp__auth__approve".to_string()));
+    }
+
     #[test]
     fn test_build_args_always_streaming() {
         let options = ClaudeAgentOptions::default();
@@ -650,6 +1073,131 @@ mod tests {
         assert_eq!(env.get("CUSTOM_VAR"), Some(&"value".to_string()));
     }
 
+    #[test]
+    fn test_build_env_without_inheritance() {
+        let inherited = ClaudeAgentOptions::new().with_env("PATH", "/sdk/bin");
+        let env = SubprocessTransport::build_env(&inherited);
+        assert!(std::env::vars().all(|(key, _)| env.contains_key(&key)));
+        assert_eq!(env["PATH"], "/sdk/bin");
+
+        let isolated = ClaudeAgentOptions::new()
+            .inherit_env(false)
+            .with_envs([("ANTHROPIC_API_KEY", "sk-test"), ("LOG_LEVEL", "debug")]);
+        let env = SubprocessTransport::build_env(&isolated);
+        for key in env.keys() {
+            assert!(
+                ESSENTIAL_ENV_VARS.contains(&key.as_str())
+                    || ["ANTHROPIC_API_KEY", "LOG_LEVEL", "CLAUDE_SDK"].contains(&key.as_str()),
+                "{} should not be inherited",
+                key
+            );
+        }
+        assert_eq!(env["ANTHROPIC_API_KEY"], "sk-test");
+        assert_eq!(env["CLAUDE_SDK"], "true");
+    }
+
+    #[test]
+    fn test_build_args_file_checkpointing_replays_prompts() {
+        let mut options = ClaudeAgentOptions::new();
+        options.enable_file_checkpointing = true;
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        assert!(args.contains(&"--enable-file-checkpointing".to_string()));
+        assert!(args.contains(&"--replay-user-messages".to_string()));
+
+        let args = SubprocessTransport::build_args(&ClaudeAgentOptions::new()).unwrap();
+        assert!(!args.contains(&"--replay-user-messages".to_string()));
+    }
+
+    #[test]
+    fn test_build_args_cli_args() {
+        let mut options = ClaudeAgentOptions::new().with_cli_args([
+            CliArg::option("add-dir", "a"),
+            CliArg::option("add-dir", "b"),
+            CliArg::flag("verbose"),
+            CliArg::positional("extra"),
+        ]);
+        options
+            .extra_args
+            .insert("debug-to-stderr".to_string(), None);
+
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        let start = args.iter().position(|a| a == "--debug-to-stderr").unwrap();
+        assert_eq!(
+            &args[start..],
+            [
+                "--debug-to-stderr",
+                "--add-dir",
+                "a",
+                "--add-dir",
+                "b",
+                "--verbose",
+                "extra"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_args_betas() {
+        let options = ClaudeAgentOptions::new()
+            .with_beta(SdkBeta::Context1m)
+            .with_beta("interleaved-thinking-2025-05-14");
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        let betas: Vec<&[String]> = args.windows(2).filter(|w| w[0] == "--beta").collect();
+        assert_eq!(betas.len(), 2);
+        assert_eq!(betas[0][1], "context-1m-2025-08-07");
+        assert_eq!(betas[1][1], "interleaved-thinking-2025-05-14");
+    }
+
+    #[test]
+    fn test_build_args_plugins() {
+        let options = ClaudeAgentOptions::new()
+            .with_plugin_dir("./plugins/formatter")
+            .with_plugin_dir("/opt/plugins/audit");
+        let args = SubprocessTransport::build_args(&options).unwrap();
+        let dirs: Vec<&str> = args
+            .windows(2)
+            .filter(|w| w[0] == "--plugin-dir")
+            .map(|w| w[1].as_str())
+            .collect();
+        assert_eq!(dirs, ["./plugins/formatter", "/opt/plugins/audit"]);
+    }
+
+    #[test]
+    fn test_build_env_feature_flags() {
+        let mut options = ClaudeAgentOptions::new()
+            .with_feature_flag("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC", true)
+            .with_feature_flag("DISABLE_AUTOUPDATER", false)
+            .with_feature_flag("CLAUDE_CODE_ENABLE_TELEMETRY", true);
+        options
+            .env
+            .insert("CLAUDE_CODE_ENABLE_TELEMETRY".to_string(), "0".to_string());
+
+        let env = SubprocessTransport::build_env(&options);
+        assert_eq!(env["CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC"], "1");
+        assert_eq!(env["DISABLE_AUTOUPDATER"], "0");
+        assert_eq!(env["CLAUDE_CODE_ENABLE_TELEMETRY"], "0");
+    }
+
+    #[test]
+    fn test_build_env_proxy() {
+        let mut options = ClaudeAgentOptions::new().with_proxy(
+            ProxyConfig::new("http://proxy.corp:3128")
+                .with_no_proxy("localhost")
+                .with_no_proxy(".internal"),
+        );
+        options
+            .env
+            .insert("NO_PROXY".to_string(), "override".to_string());
+
+        let env = SubprocessTransport::build_env(&options);
+
+        for name in ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"] {
+            assert_eq!(env[name], "http://proxy.corp:3128", "{}", name);
+        }
+        assert_eq!(env["no_proxy"], "localhost,.internal");
+        assert_eq!(env["NO_PROXY"], "override");
+    }
+
     #[test]
     fn test_build_args_system_prompt_none() {
         let options = ClaudeAgentOptions::default();
@@ -725,6 +1273,8 @@ mod tests {
                 prompt: "Do stuff".to_string(),
                 tools: None,
                 model: None,
+                mcp_servers: None,
+                permission_mode: None,
             },
         )]));
         let args = SubprocessTransport::build_args(&options).unwrap();
@@ -732,6 +1282,37 @@ mod tests {
         assert!(!args.contains(&"--agents".to_string()));
     }
 
+    #[cfg(feature = "mcp")]
+    #[test]
+    fn test_build_args_sdk_mcp_servers() {
+        use crate::mcp::SdkMcpServer;
+
+        let mut options = ClaudeAgentOptions::new().with_sdk_mcp_server(SdkMcpServer::new(
+            "calc",
+            "1.0.0",
+            vec![],
+        ));
+        options.mcp_servers = McpServersConfig::Map(HashMap::from([(
+            "fs".to_string(),
+            McpServerConfig::Stdio(McpStdioServerConfig {
+                server_type: "stdio".to_string(),
+                command: "mcp-fs".to_string(),
+                args: vec![],
+                env: HashMap::new(),
+            }),
+        )]));
+        let args = SubprocessTransport::build_args(&options).unwrap();
+
+        let idx = args
+            .iter()
+            .position(|a| a == "--mcp-servers")
+            .expect("Should have --mcp-servers");
+        let servers: serde_json::Value = serde_json::from_str(&args[idx + 1]).unwrap();
+        assert_eq!(servers["calc"]["type"], "sdk");
+        assert_eq!(servers["calc"]["name"], "calc");
+        assert_eq!(servers["fs"]["type"], "stdio");
+    }
+
     // ====================================================================
     // ThinkingConfig → CLI args tests
     // ====================================================================
@@ -0,0 +1,314 @@
+//! Subagents defined in markdown files.
+//!
+//! The CLI reads subagents from `.claude/agents/*.md`: YAML frontmatter with
+//! the agent's name, description, tools, model, MCP servers, and permission
+//! mode, followed by its prompt.
+//!
+//! ```markdown
+//! ---
+//! name: code-reviewer
+//! description: Reviews diffs for correctness and style
+//! tools: Read, Grep, Glob
+//! model: sonnet
+//! mcpServers: github
+//! permissionMode: plan
+//! ---
+//! You are a meticulous code reviewer...
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{AgentDefinition, ClaudeAgentOptions, ModelId, PermissionMode};
+
+/// Project-level agents directory.
+pub const PROJECT_AGENTS_DIR: &str = ".claude/agents";
+
+impl AgentDefinition {
+    /// Parse an agent file, returning its name and definition.
+    ///
+    /// `default_name` is used when the frontmatter has no `name`, as for a
+    /// file named after its agent.
+    pub fn from_markdown(contents: &str, default_name: &str) -> Result<(String, Self)> {
+        let file = Self::parse_agent_file(contents, default_name)?;
+        Ok((file.name, file.definition))
+    }
+
+    fn parse_agent_file(contents: &str, default_name: &str) -> Result<AgentFile> {
+        let (frontmatter, body) = split_frontmatter(contents)
+            .ok_or_else(|| ClaudeSDKError::configuration("Agent file has no frontmatter"))?;
+        let fields = parse_frontmatter(frontmatter)?;
+
+        let name = fields
+            .get("name")
+            .and_then(FieldValue::as_scalar)
+            .unwrap_or(default_name)
+            .to_string();
+        let description = fields
+            .get("description")
+            .and_then(FieldValue::as_scalar)
+            .ok_or_else(|| {
+                ClaudeSDKError::configuration(format!("Agent '{}' has no description", name))
+            })?
+            .to_string();
+        let tools = fields.get("tools").map(FieldValue::to_list);
+        let model = match fields.get("model").and_then(FieldValue::as_scalar) {
+            None => None,
+            Some(model) => match ModelId::from(model) {
+                ModelId::Custom(id) if !id.starts_with("claude-") => {
+                    return Err(ClaudeSDKError::configuration(format!(
+                        "Agent '{}' has unknown model '{}': expected sonnet, opus, haiku, inherit, or a claude-* model ID",
+                        name, model
+                    )))
+                }
+                model => Some(model),
+            },
+        };
+        let mcp_servers = fields.get("mcpServers").map(FieldValue::to_list);
+        let permission_mode = match fields.get("permissionMode").and_then(FieldValue::as_scalar) {
+            None => None,
+            Some(mode) => Some(
+                serde_json::from_value::<PermissionMode>(mode.into()).map_err(|_| {
+                    ClaudeSDKError::configuration(format!(
+                        "Agent '{}' has unknown permission mode '{}': expected default, acceptEdits, plan, or bypassPermissions",
+                        name, mode
+                    ))
+                })?,
+            ),
+        };
+
+        let version = fields
+            .get("version")
+            .and_then(FieldValue::as_scalar)
+            .map(str::to_string);
+
+        Ok(AgentFile {
+            name,
+            version,
+            definition: Self {
+                description,
+                prompt: body.trim().to_string(),
+                tools,
+                model,
+                mcp_servers,
+                permission_mode,
+            },
+        })
+    }
+
+    /// Load every `*.md` agent file in a directory, keyed by agent name.
+    ///
+    /// Errors name the file that failed to parse.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<HashMap<String, Self>> {
+        Ok(read_agent_files(dir)?
+            .into_iter()
+            .map(|file| (file.name, file.definition))
+            .collect())
+    }
+}
+
+/// A parsed agent file.
+pub(crate) struct AgentFile {
+    pub(crate) name: String,
+    /// The frontmatter's `version`, which the CLI ignores.
+    pub(crate) version: Option<String>,
+    pub(crate) definition: AgentDefinition,
+}
+
+/// Parse every `*.md` agent file in a directory, in file name order.
+///
+/// Errors name the file that failed to parse.
+pub(crate) fn read_agent_files(dir: impl AsRef<Path>) -> Result<Vec<AgentFile>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Failed to read agents directory {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+
+    let mut files = Vec::new();
+    for path in paths {
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            ClaudeSDKError::configuration(format!(
+                "Failed to read agent file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file = AgentDefinition::parse_agent_file(&contents, &stem).map_err(|e| match e {
+            ClaudeSDKError::Configuration { message } => {
+                ClaudeSDKError::configuration(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+impl ClaudeAgentOptions {
+    /// Add the agents defined in a directory of markdown files.
+    ///
+    /// Agents already in [`agents`](Self::agents) keep their definition, so
+    /// programmatic agents override files of the same name.
+    pub fn with_agents_dir(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        let loaded = AgentDefinition::load_dir(dir)?;
+        let agents = self.agents.get_or_insert_with(HashMap::new);
+        for (name, agent) in loaded {
+            agents.entry(name).or_insert(agent);
+        }
+        Ok(self)
+    }
+}
+
+/// A frontmatter value: a scalar, or a list in flow (`[a, b]`) or block
+/// (`- a`) style.
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl FieldValue {
+    fn as_scalar(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(value) => Some(value),
+            Self::List(_) => None,
+        }
+    }
+
+    /// Comma-separated scalars and lists both become lists.
+    fn to_list(&self) -> Vec<String> {
+        match self {
+            Self::Scalar(value) => split_list(value),
+            Self::List(items) => items.clone(),
+        }
+    }
+}
+
+/// Split `---` delimited frontmatter from the body.
+fn split_frontmatter(contents: &str) -> Option<(&str, &str)> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let rest = contents
+        .strip_prefix("---\r\n")
+        .or_else(|| contents.strip_prefix("---\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parse the `key: value` subset of YAML used by agent files.
+fn parse_frontmatter(frontmatter: &str) -> Result<HashMap<String, FieldValue>> {
+    let mut fields = HashMap::new();
+    let mut list_key: Option<String> = None;
+
+    for line in frontmatter.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            let key = list_key.as_ref().ok_or_else(|| {
+                ClaudeSDKError::configuration(format!("Unexpected list item: {}", trimmed))
+            })?;
+            if let Some(FieldValue::List(items)) = fields.get_mut(key) {
+                items.push(unquote(item.trim()).to_string());
+            }
+            continue;
+        }
+
+        let (key, value) = trimmed.split_once(':').ok_or_else(|| {
+            ClaudeSDKError::configuration(format!("Invalid frontmatter line: {}", trimmed))
+        })?;
+        let key = key.trim().to_string();
+        let value = value.trim();
+        let value = if value.is_empty() {
+            list_key = Some(key.clone());
+            FieldValue::List(Vec::new())
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            list_key = None;
+            FieldValue::List(split_list(inner))
+        } else {
+            list_key = None;
+            FieldValue::Scalar(unquote(value).to_string())
+        };
+        fields.insert(key, value);
+    }
+    Ok(fields)
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| unquote(item.trim()).to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\na: 1\n---\nbody\n"),
+            Some(("a: 1\n", "body\n"))
+        );
+        assert_eq!(
+            split_frontmatter("---\r\na: 1\r\n---\r\nbody"),
+            Some(("a: 1\r\n", "body"))
+        );
+        assert_eq!(split_frontmatter("no frontmatter"), None);
+        assert_eq!(split_frontmatter("---\nunterminated\n"), None);
+    }
+
+    #[test]
+    fn test_list_styles() {
+        let fields =
+            parse_frontmatter("a: Read, Grep\nb: [\"Read\", 'Grep']\nc:\n  - Read\n  - Grep\n")
+                .unwrap();
+        let expected = vec!["Read".to_string(), "Grep".to_string()];
+        for key in ["a", "b", "c"] {
+            assert_eq!(fields[key].to_list(), expected, "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_quoted_scalar_with_colon() {
+        let fields = parse_frontmatter("description: \"Use when: tests fail\"").unwrap();
+        assert_eq!(
+            fields["description"].as_scalar(),
+            Some("Use when: tests fail")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Shared, versioned subagent definitions.
+//!
+//! A [`Registry`] holds vetted [`AgentDefinition`]s by name and version, so
+//! services can attach the same subagents instead of each keeping a copy.
+//! Agents are referenced as `name` for the latest version, `name@1.2.0` for
+//! an exact one, or `name@^1` for the latest matching a requirement.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::agents::Registry;
+//! use claude_agents_sdk::{AgentDefinition, ClaudeAgentOptions};
+//!
+//! # fn main() -> claude_agents_sdk::Result<()> {
+//! let reviewer = AgentDefinition {
+//!     description: "Reviews Rust changes".to_string(),
+//!     prompt: "You review Rust code for soundness and style.".to_string(),
+//!     tools: Some(vec!["Read".to_string(), "Grep".to_string()]),
+//!     model: None,
+//!     mcp_servers: Some(Vec::new()),
+//!     permission_mode: None,
+//! };
+//! Registry::global().register("rust-reviewer", "1.0.0", reviewer)?;
+//!
+//! let options = ClaudeAgentOptions::new().with_agent_from_registry("rust-reviewer@^1")?;
+//! assert!(options.agents.unwrap().contains_key("rust-reviewer"));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+use semver::{Version, VersionReq};
+
+use crate::agent_files::read_agent_files;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{AgentDefinition, ClaudeAgentOptions};
+
+/// Version given to agent files without a `version` in their frontmatter.
+pub const DEFAULT_AGENT_VERSION: &str = "0.0.0";
+
+/// Agent definitions by name and version.
+#[derive(Debug, Default)]
+pub struct Registry {
+    agents: RwLock<HashMap<String, BTreeMap<Version, AgentDefinition>>>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry, used by
+    /// [`ClaudeAgentOptions::with_agent_from_registry`].
+    pub fn global() -> &'static Registry {
+        static GLOBAL: OnceLock<Registry> = OnceLock::new();
+        GLOBAL.get_or_init(Registry::new)
+    }
+
+    /// Register a version of an agent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `version` isn't a semantic version,
+    /// or if that version of the agent is already registered: a published
+    /// version can't change under the services using it.
+    pub fn register(
+        &self,
+        name: impl Into<String>,
+        version: &str,
+        definition: AgentDefinition,
+    ) -> Result<()> {
+        let name = name.into();
+        let version = Version::parse(version).map_err(|e| {
+            ClaudeSDKError::configuration(format!(
+                "Agent '{}' has invalid version '{}': {}",
+                name, version, e
+            ))
+        })?;
+        let mut agents = self.agents.write().unwrap_or_else(|e| e.into_inner());
+        let versions = agents.entry(name.clone()).or_default();
+        if versions.contains_key(&version) {
+            return Err(ClaudeSDKError::configuration(format!(
+                "Agent '{}' version {} is already registered",
+                name, version
+            )));
+        }
+        versions.insert(version, definition);
+        Ok(())
+    }
+
+    /// Register the agents in a directory of markdown files, returning how
+    /// many were added.
+    ///
+    /// Each file's frontmatter may give a `version`; files without one are
+    /// registered as [`DEFAULT_AGENT_VERSION`].
+    pub fn load_dir(&self, dir: impl AsRef<Path>) -> Result<usize> {
+        let files = read_agent_files(dir)?;
+        let count = files.len();
+        for file in files {
+            let version = file.version.as_deref().unwrap_or(DEFAULT_AGENT_VERSION);
+            self.register(file.name, version, file.definition)?;
+        }
+        Ok(count)
+    }
+
+    /// Look up an agent by reference: `name`, `name@<version>`, or
+    /// `name@<requirement>`. Returns the newest matching version.
+    pub fn get(&self, reference: &str) -> Option<(Version, AgentDefinition)> {
+        let (name, requirement) = split_reference(reference).ok()?;
+        let agents = self.agents.read().unwrap_or_else(|e| e.into_inner());
+        agents
+            .get(name)?
+            .iter()
+            .rev()
+            .find(|(version, _)| {
+                requirement
+                    .as_ref()
+                    .map_or(true, |req| req.matches(version))
+            })
+            .map(|(version, definition)| (version.clone(), definition.clone()))
+    }
+
+    /// Registered versions of an agent, oldest first.
+    pub fn versions(&self, name: &str) -> Vec<Version> {
+        self.agents
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|versions| versions.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Names of the registered agents, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .agents
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Split `name@requirement`. An exact version like `1.2.0` means `=1.2.0`,
+/// not `^1.2.0` as a bare requirement would.
+fn split_reference(reference: &str) -> Result<(&str, Option<VersionReq>)> {
+    let Some((name, requirement)) = reference.split_once('@') else {
+        return Ok((reference, None));
+    };
+    let requirement = match Version::parse(requirement) {
+        Ok(version) => VersionReq::parse(&format!("={}", version)),
+        Err(_) => VersionReq::parse(requirement),
+    }
+    .map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Agent reference '{}' has invalid version requirement: {}",
+            reference, e
+        ))
+    })?;
+    Ok((name, Some(requirement)))
+}
+
+impl ClaudeAgentOptions {
+    /// Add an agent from the [global registry](Registry::global), under its
+    /// name without the version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if the reference is malformed or no
+    /// registered version matches it.
+    pub fn with_agent_from_registry(self, reference: &str) -> Result<Self> {
+        self.with_agent_from(Registry::global(), reference)
+    }
+
+    /// Add an agent from `registry`, under its name without the version.
+    pub fn with_agent_from(mut self, registry: &Registry, reference: &str) -> Result<Self> {
+        let (name, _) = split_reference(reference)?;
+        let (_, definition) = registry.get(reference).ok_or_else(|| {
+            ClaudeSDKError::configuration(match registry.versions(name).as_slice() {
+                [] => format!("No agent '{}' in the registry", name),
+                versions => format!(
+                    "No version of agent '{}' matches '{}'; registered: {}",
+                    name,
+                    reference,
+                    versions
+                        .iter()
+                        .map(Version::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+        })?;
+        self.agents
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), definition);
+        Ok(self)
+    }
+}
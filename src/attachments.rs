@@ -0,0 +1,241 @@
+//! Local files attached to prompts.
+//!
+//! An [`Attachment`] turns a file into a content block of the prompt's user
+//! message: images and PDFs are base64-encoded, text files are inlined with
+//! their path. Send them with
+//! [`query_with_attachments`](crate::query_with_attachments) or
+//! [`ClaudeClient::query_with_attachments`](crate::ClaudeClient::query_with_attachments).
+
+use std::path::Path;
+
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::errors::{ClaudeSDKError, Result};
+
+/// Largest image the API accepts, in bytes.
+pub const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Largest PDF the API accepts, in bytes.
+pub const MAX_DOCUMENT_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Largest text file inlined into a prompt, in bytes.
+pub const MAX_TEXT_BYTES: u64 = 1024 * 1024;
+
+/// Media types by file extension, for files that aren't plain text.
+const BINARY_TYPES: [(&str, &str); 6] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+];
+
+/// Media types by leading bytes, for files with a missing or wrong
+/// extension.
+const SIGNATURES: [(&[u8], &str); 6] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"RIFF", "image/webp"),
+    (b"%PDF-", "application/pdf"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Body {
+    /// Base64-encoded image.
+    Image(String),
+    /// Base64-encoded PDF.
+    Document(String),
+    /// UTF-8 text.
+    Text(String),
+}
+
+/// A local file to send along with a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    name: String,
+    media_type: String,
+    body: Body,
+}
+
+impl Attachment {
+    /// Read a file, choosing its content block by media type.
+    ///
+    /// PNG, JPEG, GIF, and WebP images become image blocks and PDFs document
+    /// blocks, detected by extension or by their leading bytes. Any other
+    /// file must be UTF-8 text and is inlined as a text block.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if the file can't be read, is larger
+    /// than [`MAX_IMAGE_BYTES`], [`MAX_DOCUMENT_BYTES`], or
+    /// [`MAX_TEXT_BYTES`] for its kind, or is binary in a format the API
+    /// doesn't accept.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let error = |reason: String| {
+            ClaudeSDKError::configuration(format!("Cannot attach {}: {}", path.display(), reason))
+        };
+        let size = std::fs::metadata(path)
+            .map_err(|e| error(e.to_string()))?
+            .len();
+        let by_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| {
+                BINARY_TYPES
+                    .iter()
+                    .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+            })
+            .map(|(_, media_type)| *media_type);
+        // Check before reading what the file may be; the exact limit
+        // depends on its contents.
+        let limit = match by_extension {
+            Some("application/pdf") | None => MAX_DOCUMENT_BYTES,
+            Some(_) => MAX_IMAGE_BYTES,
+        };
+        if size > limit {
+            return Err(error(format!(
+                "{} bytes is over the limit of {}",
+                size, limit
+            )));
+        }
+        let bytes = std::fs::read(path).map_err(|e| error(e.to_string()))?;
+        Self::from_bytes(path.display().to_string(), bytes, by_extension).map_err(error)
+    }
+
+    fn from_bytes(
+        name: String,
+        bytes: Vec<u8>,
+        by_extension: Option<&str>,
+    ) -> std::result::Result<Self, String> {
+        let sniffed = SIGNATURES
+            .iter()
+            .find(|(signature, media_type)| {
+                bytes.starts_with(signature)
+                    && (*media_type != "image/webp" || bytes.get(8..12) == Some(b"WEBP"))
+            })
+            .map(|(_, media_type)| *media_type);
+        let Some(media_type) = sniffed.or(by_extension) else {
+            if bytes.len() as u64 > MAX_TEXT_BYTES {
+                return Err(format!(
+                    "{} bytes is over the limit of {}",
+                    bytes.len(),
+                    MAX_TEXT_BYTES
+                ));
+            }
+            let text = String::from_utf8(bytes)
+                .ok()
+                .filter(|text| !text.contains('\0'))
+                .ok_or("not a text file, image, or PDF")?;
+            return Ok(Self {
+                name,
+                media_type: "text/plain".to_string(),
+                body: Body::Text(text),
+            });
+        };
+        let (limit, encode): (u64, fn(String) -> Body) = if media_type == "application/pdf" {
+            (MAX_DOCUMENT_BYTES, Body::Document)
+        } else {
+            (MAX_IMAGE_BYTES, Body::Image)
+        };
+        if bytes.len() as u64 > limit {
+            return Err(format!(
+                "{} bytes is over the limit of {}",
+                bytes.len(),
+                limit
+            ));
+        }
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        Ok(Self {
+            name,
+            media_type: media_type.to_string(),
+            body: encode(data),
+        })
+    }
+
+    /// The file's path, as given.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The detected media type, `text/plain` for text files.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// The content block carrying the file in a user message.
+    pub fn to_content_block(&self) -> Value {
+        match &self.body {
+            Body::Image(data) => json!({
+                "type": "image",
+                "source": {"type": "base64", "media_type": self.media_type, "data": data}
+            }),
+            Body::Document(data) => json!({
+                "type": "document",
+                "source": {"type": "base64", "media_type": self.media_type, "data": data},
+                "title": self.name
+            }),
+            Body::Text(text) => json!({
+                "type": "text",
+                "text": format!("<file path=\"{}\">\n{}\n</file>", self.name, text)
+            }),
+        }
+    }
+}
+
+/// User message content holding `prompt` followed by `attachments`.
+pub(crate) fn prompt_content(prompt: &str, attachments: &[Attachment]) -> Value {
+    let mut blocks = vec![json!({"type": "text", "text": prompt})];
+    blocks.extend(attachments.iter().map(Attachment::to_content_block));
+    Value::Array(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_images_and_inlines_text() {
+        let dir = tempfile::tempdir().unwrap();
+        // A PNG named like a text file is still sent as an image.
+        let image = dir.path().join("shot.txt");
+        std::fs::write(&image, b"\x89PNG\r\n\x1a\nrest").unwrap();
+        let text = dir.path().join("notes.md");
+        std::fs::write(&text, "# Notes").unwrap();
+
+        let image = Attachment::from_path(&image).unwrap();
+        assert_eq!(image.media_type(), "image/png");
+        let block = image.to_content_block();
+        assert_eq!(block["type"], "image");
+        assert_eq!(block["source"]["data"], "iVBORw0KGgpyZXN0");
+
+        let text = Attachment::from_path(&text).unwrap();
+        assert_eq!(text.media_type(), "text/plain");
+        let content = prompt_content("Summarize", &[text]);
+        assert_eq!(content[0]["text"], "Summarize");
+        assert!(content[1]["text"]
+            .as_str()
+            .unwrap()
+            .ends_with("notes.md\">\n# Notes\n</file>"));
+    }
+
+    #[test]
+    fn test_rejects_binary_and_oversized_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("app.bin");
+        std::fs::write(&binary, [0u8, 159, 146, 150]).unwrap();
+        let error = Attachment::from_path(&binary).unwrap_err().to_string();
+        assert!(error.contains("not a text file"), "{}", error);
+
+        let large = dir.path().join("large.png");
+        std::fs::write(&large, vec![0u8; MAX_IMAGE_BYTES as usize + 1]).unwrap();
+        let error = Attachment::from_path(&large).unwrap_err().to_string();
+        assert!(error.contains("over the limit"), "{}", error);
+
+        assert!(Attachment::from_path(dir.path().join("missing.txt")).is_err());
+    }
+}
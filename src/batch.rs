@@ -0,0 +1,326 @@
+//! Running many independent prompts.
+//!
+//! Evals and bulk jobs send a list of prompts and want each one's answer
+//! and cost. A [`Batch`] runs each prompt in a session of its own, several
+//! at a time, and reports every prompt's outcome, so one failure doesn't
+//! lose the others' results. A budget shared by the whole batch stops it
+//! from starting prompts once spent; the prompts left are reported as
+//! skipped.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::batch::{Batch, Prompt};
+//! use claude_agents_sdk::ClaudeAgentOptions;
+//!
+//! # async fn example() {
+//! let prompts = vec![
+//!     Prompt::new("What is 2 + 2?").with_id("add"),
+//!     Prompt::new("What is 3 * 3?").with_id("multiply"),
+//! ];
+//! let report = Batch::new(ClaudeAgentOptions::new().with_max_turns(1))
+//!     .with_concurrency(4)
+//!     .with_budget_usd(1.0)
+//!     .on_progress(|progress| {
+//!         eprintln!("{}/{} done, ${:.4}", progress.finished, progress.total, progress.spent_usd);
+//!     })
+//!     .run(prompts)
+//!     .await;
+//!
+//! for item in &report.items {
+//!     match &item.result {
+//!         Ok((text, _)) => println!("{:?}: {} (${:.4})", item.prompt.id, text, item.cost_usd),
+//!         Err(e) => println!("{:?} failed: {}", item.prompt.id, e),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use futures::future::join_all;
+
+use crate::client::ClaudeClient;
+use crate::errors::Result;
+use crate::types::{ClaudeAgentOptions, ResultMessage};
+
+/// Default number of prompts a batch runs at once.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A prompt in a batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Prompt {
+    /// Caller's ID for the prompt, such as an eval case name.
+    pub id: Option<String>,
+    /// The prompt text.
+    pub text: String,
+}
+
+impl Prompt {
+    /// Create a prompt without an ID.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            text: text.into(),
+        }
+    }
+
+    /// Set the prompt's ID.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+}
+
+impl From<&str> for Prompt {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Prompt {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// A prompt that ran, with its outcome.
+#[derive(Debug)]
+pub struct BatchItem {
+    /// The prompt.
+    pub prompt: Prompt,
+    /// The response text and result, or why the prompt failed.
+    pub result: Result<(String, ResultMessage)>,
+    /// What the prompt cost, in USD.
+    pub cost_usd: f64,
+}
+
+impl BatchItem {
+    /// Whether the prompt ran and its result isn't an error.
+    pub fn is_success(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_ok_and(|(_, result)| !result.is_error)
+    }
+}
+
+/// A batch's progress, passed to its progress callback after each prompt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchProgress {
+    /// Prompts finished so far.
+    pub finished: usize,
+    /// Prompts finished without success so far.
+    pub failed: usize,
+    /// Prompts in the batch.
+    pub total: usize,
+    /// What the batch has spent so far, in USD.
+    pub spent_usd: f64,
+}
+
+/// The outcome of a batch.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// The prompts that ran, in the order given.
+    pub items: Vec<BatchItem>,
+    /// The prompts not started because the budget was spent, in the order
+    /// given.
+    pub skipped: Vec<Prompt>,
+    /// What the batch spent, in USD.
+    pub spent_usd: f64,
+}
+
+impl BatchReport {
+    /// Whether every prompt ran successfully.
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty() && self.items.iter().all(BatchItem::is_success)
+    }
+
+    /// The prompts that ran successfully.
+    pub fn succeeded(&self) -> impl Iterator<Item = &BatchItem> {
+        self.items.iter().filter(|item| item.is_success())
+    }
+
+    /// The prompts that ran without success.
+    pub fn failed(&self) -> impl Iterator<Item = &BatchItem> {
+        self.items.iter().filter(|item| !item.is_success())
+    }
+}
+
+type ProgressFn = Arc<dyn Fn(&BatchProgress) + Send + Sync>;
+
+/// A prompt's result and cost.
+type Outcome = (Result<(String, ResultMessage)>, f64);
+
+/// Runs prompts, each in a session of its own, several at a time.
+#[derive(Clone)]
+pub struct Batch {
+    options: ClaudeAgentOptions,
+    concurrency: usize,
+    budget_usd: Option<f64>,
+    progress: Option<ProgressFn>,
+}
+
+impl Batch {
+    /// Create a batch whose sessions start from `options`.
+    pub fn new(options: ClaudeAgentOptions) -> Self {
+        Self {
+            options,
+            concurrency: DEFAULT_CONCURRENCY,
+            budget_usd: None,
+            progress: None,
+        }
+    }
+
+    /// Run up to `concurrency` prompts at once. Defaults to
+    /// [`DEFAULT_CONCURRENCY`]; zero counts as one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Start no more prompts once the batch has spent `budget_usd`.
+    ///
+    /// Each session is given what's left of the budget as its
+    /// `max_budget_usd`, so prompts running at once can together spend a
+    /// little past it.
+    pub fn with_budget_usd(mut self, budget_usd: f64) -> Self {
+        self.budget_usd = Some(budget_usd);
+        self
+    }
+
+    /// Call `callback` each time a prompt finishes.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BatchProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Run the prompts, returning each one's outcome.
+    pub async fn run(&self, prompts: Vec<Prompt>) -> BatchReport {
+        let tally = Mutex::new(Tally {
+            outcomes: prompts.iter().map(|_| None).collect(),
+            ..Tally::default()
+        });
+        let workers = self.concurrency.max(1).min(prompts.len());
+        join_all((0..workers).map(|_| self.worker(&prompts, &tally))).await;
+
+        let tally = tally.into_inner().unwrap();
+        let mut report = BatchReport {
+            spent_usd: tally.spent_usd,
+            ..BatchReport::default()
+        };
+        for (prompt, outcome) in prompts.into_iter().zip(tally.outcomes) {
+            match outcome {
+                Some((result, cost_usd)) => report.items.push(BatchItem {
+                    prompt,
+                    result,
+                    cost_usd,
+                }),
+                None => report.skipped.push(prompt),
+            }
+        }
+        report
+    }
+
+    /// Take prompts off the list and run them until none are left or the
+    /// budget is spent.
+    async fn worker(&self, prompts: &[Prompt], tally: &Mutex<Tally>) {
+        loop {
+            let (index, remaining_usd) = {
+                let mut tally = tally.lock().unwrap_or_else(|e| e.into_inner());
+                let remaining_usd = self.budget_usd.map(|budget| budget - tally.spent_usd);
+                if tally.next == prompts.len() || remaining_usd.is_some_and(|r| r <= 0.0) {
+                    return;
+                }
+                tally.next += 1;
+                (tally.next - 1, remaining_usd)
+            };
+
+            let mut options = self.options.clone();
+            if let Some(remaining) = remaining_usd {
+                options.max_budget_usd = Some(match options.max_budget_usd {
+                    Some(own) => own.min(remaining),
+                    None => remaining,
+                });
+            }
+            let result = run_prompt(options, &prompts[index].text).await;
+            let cost_usd = result
+                .as_ref()
+                .ok()
+                .and_then(|(_, result)| result.total_cost_usd)
+                .unwrap_or(0.0);
+            let failed = !result.as_ref().is_ok_and(|(_, result)| !result.is_error);
+
+            let progress = {
+                let mut tally = tally.lock().unwrap_or_else(|e| e.into_inner());
+                tally.spent_usd += cost_usd;
+                tally.finished += 1;
+                tally.failed += usize::from(failed);
+                tally.outcomes[index] = Some((result, cost_usd));
+                tally.progress(prompts.len())
+            };
+            if let Some(callback) = &self.progress {
+                callback(&progress);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Batch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Batch")
+            .field("concurrency", &self.concurrency)
+            .field("budget_usd", &self.budget_usd)
+            .finish()
+    }
+}
+
+/// Run `prompts` with up to `concurrency` at once; see [`Batch`] for a
+/// budget and progress callback.
+pub async fn run(
+    prompts: Vec<Prompt>,
+    options: ClaudeAgentOptions,
+    concurrency: usize,
+) -> BatchReport {
+    Batch::new(options)
+        .with_concurrency(concurrency)
+        .run(prompts)
+        .await
+}
+
+/// What the workers share.
+#[derive(Default)]
+struct Tally {
+    /// The next prompt to start.
+    next: usize,
+    finished: usize,
+    failed: usize,
+    spent_usd: f64,
+    /// Each prompt's result and cost, once it has run.
+    outcomes: Vec<Option<Outcome>>,
+}
+
+impl Tally {
+    fn progress(&self, total: usize) -> BatchProgress {
+        BatchProgress {
+            finished: self.finished,
+            failed: self.failed,
+            total,
+            spent_usd: self.spent_usd,
+        }
+    }
+}
+
+/// Run one prompt in a new session.
+async fn run_prompt(options: ClaudeAgentOptions, prompt: &str) -> Result<(String, ResultMessage)> {
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await?;
+    let response = match client.query(prompt).await {
+        Ok(()) => client.receive_response().await,
+        Err(e) => Err(e),
+    };
+    let _ = client.disconnect().await;
+    response
+}
@@ -0,0 +1,223 @@
+//! Harness for measuring the SDK's hot paths.
+//!
+//! Each function runs one piece of the work the SDK does per session, with
+//! the CLI taken out: parsing lines of output into messages, assembling
+//! partial message streams into text, and turning options into the CLI's
+//! command line. Criterion benches in the repository's `benches/` directory
+//! call these, and so can benches of your own, fed with output recorded from
+//! your own sessions to catch regressions on the payloads you care about.
+//!
+//! ```rust
+//! use claude_agents_sdk::bench::{self, Corpus};
+//!
+//! let corpus = Corpus::builtin().repeat(100);
+//! assert_eq!(bench::parse_messages(&corpus).unwrap(), corpus.len());
+//! ```
+
+use serde_json::json;
+
+use crate::_internal::framing::JsonFramer;
+use crate::_internal::message_parser::{decode_line, parse_message};
+use crate::_internal::transport::SubprocessTransport;
+use crate::errors::Result;
+use crate::types::{ClaudeAgentOptions, Message};
+
+/// Recorded sessions shipped with the crate.
+const BUILTIN: [&str; 4] = [
+    include_str!("../fixtures/text_turn.jsonl"),
+    include_str!("../fixtures/tool_use_turn.jsonl"),
+    include_str!("../fixtures/partial_messages.jsonl"),
+    include_str!("../fixtures/error_max_turns.jsonl"),
+];
+
+/// Lines of CLI output to run the harness over.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    lines: Vec<String>,
+}
+
+impl Corpus {
+    /// The non-blank lines of JSONL text, such as a wire log or a recorded
+    /// session.
+    pub fn from_jsonl(text: &str) -> Self {
+        Self {
+            lines: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// The recorded sessions shipped with the crate: text and tool use
+    /// turns, a partial message stream, and an error result.
+    pub fn builtin() -> Self {
+        Self {
+            lines: BUILTIN
+                .iter()
+                .flat_map(|text| Self::from_jsonl(text).lines)
+                .collect(),
+        }
+    }
+
+    /// A stream of `n` text deltas of `chunk` bytes each, framed by the
+    /// events the CLI sends around them.
+    pub fn partial_stream(n: usize, chunk: usize) -> Self {
+        let event = |event: serde_json::Value| {
+            json!({
+                "type": "stream_event",
+                "uuid": "bench",
+                "session_id": "bench",
+                "parent_tool_use_id": null,
+                "event": event,
+            })
+            .to_string()
+        };
+        let text = "x".repeat(chunk);
+        let mut lines = vec![
+            event(json!({"type": "message_start", "message": {"role": "assistant"}})),
+            event(json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": {"type": "text", "text": ""}
+            })),
+        ];
+        let delta = event(json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": {"type": "text_delta", "text": text}
+        }));
+        lines.extend(std::iter::repeat(delta).take(n));
+        lines.push(event(json!({"type": "content_block_stop", "index": 0})));
+        lines.push(event(json!({"type": "message_stop"})));
+        Self { lines }
+    }
+
+    /// This corpus `times` times over.
+    pub fn repeat(&self, times: usize) -> Self {
+        Self {
+            lines: (0..times).flat_map(|_| self.lines.clone()).collect(),
+        }
+    }
+
+    /// The lines of output.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Number of lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Total size of the lines in bytes, for throughput measurements.
+    pub fn bytes(&self) -> usize {
+        self.lines.iter().map(String::len).sum()
+    }
+}
+
+/// Decode and parse every line, as the reader does for CLI output, and
+/// return the number of messages produced.
+pub fn parse_messages(corpus: &Corpus) -> Result<usize> {
+    let mut parsed = 0;
+    for line in corpus.lines() {
+        if parse_message(decode_line(line)?)?.is_some() {
+            parsed += 1;
+        }
+    }
+    Ok(parsed)
+}
+
+/// Frame output arriving with line breaks every `split` bytes, as from a
+/// wrapper that wraps long lines, and return the number of documents.
+pub fn frame_split_lines(corpus: &Corpus, split: usize) -> Result<usize> {
+    let split = split.max(1);
+    let max_len = corpus.lines().iter().map(String::len).max().unwrap_or(0);
+    let mut framer = JsonFramer::new(max_len);
+    let mut documents = 0;
+    for line in corpus.lines() {
+        let mut rest = line.as_str();
+        while !rest.is_empty() {
+            let mut end = split.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            for document in framer.push_line(&rest[..end]) {
+                document?;
+                documents += 1;
+            }
+            rest = &rest[end..];
+        }
+    }
+    if let Some(Err(e)) = framer.finish() {
+        return Err(e);
+    }
+    Ok(documents)
+}
+
+/// Parse a partial message stream and assemble the text of its deltas, as
+/// a consumer rendering output as it arrives would.
+pub fn assemble_partial_stream(corpus: &Corpus) -> Result<String> {
+    let mut text = String::new();
+    for line in corpus.lines() {
+        if let Some(Message::StreamEvent(event)) = parse_message(decode_line(line)?)? {
+            if event.event["delta"]["type"] == "text_delta" {
+                if let Some(delta) = event.event["delta"]["text"].as_str() {
+                    text.push_str(delta);
+                }
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Build the CLI command line and environment for `options`, as each
+/// connect does, and return the number of arguments and variables.
+pub fn serialize_options(options: &ClaudeAgentOptions) -> Result<usize> {
+    let args = SubprocessTransport::build_args(options)?;
+    let env = SubprocessTransport::build_env(options);
+    Ok(args.len() + env.len())
+}
+
+/// Options with most settings filled in, so option serialization has work
+/// to do.
+pub fn sample_options() -> ClaudeAgentOptions {
+    ClaudeAgentOptions::new()
+        .with_model("claude-sonnet-4-5")
+        .with_system_prompt("You are a careful assistant.")
+        .with_allowed_tools(["Read", "Grep", "Glob", "Bash(git status:*)"])
+        .allow_mcp_server("docs")
+        .with_max_turns(8)
+        .with_cwd("/tmp")
+        .with_env("BENCH", "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_corpus_parses() {
+        let corpus = Corpus::builtin();
+        assert_eq!(corpus.len(), 23);
+        assert_eq!(parse_messages(&corpus).unwrap(), corpus.len());
+        assert_eq!(frame_split_lines(&corpus, 7).unwrap(), corpus.len());
+    }
+
+    #[test]
+    fn test_assemble_partial_stream() {
+        let corpus = Corpus::partial_stream(3, 2);
+        assert_eq!(assemble_partial_stream(&corpus).unwrap(), "xxxxxx");
+    }
+
+    #[test]
+    fn test_serialize_options() {
+        assert!(serialize_options(&sample_options()).unwrap() > 10);
+    }
+}
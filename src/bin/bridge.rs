@@ -0,0 +1,31 @@
+//! Serve the SDK as JSON-RPC over stdio; see `claude_agents_sdk::bridge`.
+//!
+//! Usage: `claude-agents-bridge [--config <options file>]`
+
+use std::process::ExitCode;
+
+use claude_agents_sdk::{bridge, ClaudeAgentOptions};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let options = match (args.next().as_deref(), args.next()) {
+        (None, _) => Ok(ClaudeAgentOptions::new()),
+        (Some("--config"), Some(path)) => ClaudeAgentOptions::from_file(path),
+        _ => {
+            eprintln!("Usage: claude-agents-bridge [--config <options file>]");
+            return ExitCode::from(2);
+        }
+    };
+    let result = match options {
+        Ok(options) => bridge::serve_stdio(options).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("claude-agents-bridge: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! The SDK as a JSON-RPC 2.0 server, for hosts that aren't written in Rust.
+//!
+//! [`Bridge`] serves one agent session over a pair of byte streams, usually
+//! a subprocess's stdin and stdout, so editors and other programs can embed
+//! the SDK the way they embed a language server. The `claude-agents-bridge`
+//! binary runs it over stdio.
+//!
+//! # Framing
+//!
+//! Messages are framed as in the Language Server Protocol, with a
+//! `Content-Length` header and a blank line before each JSON body. A host
+//! may instead send one JSON message per line, and is then answered the
+//! same way; the first message decides.
+//!
+//! # Methods
+//!
+//! - `initialize` connects the CLI. Its optional [`InitializeParams`]
+//!   override the bridge's options, and the result holds the
+//!   [`PROTOCOL_VERSION`], the SDK version, and the session ID.
+//! - `query` sends `{"prompt": "..."}` and answers with the turn's result
+//!   message. Every message of the turn, the result included, is sent
+//!   first as a `stream` notification `{"message": {...}}`. One query runs
+//!   at a time.
+//! - `interrupt` interrupts the running query, whose result then arrives
+//!   as usual.
+//! - `shutdown` disconnects the CLI, and the `exit` notification ends
+//!   [`Bridge::serve`]. So does the end of the input.
+//!
+//! SDK errors are answered with code [`SDK_ERROR`] and the error's `kind`
+//! as data.
+//!
+//! # Examples
+//!
+//! ```text
+//! --> {"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"model": "claude-sonnet-4-5"}}
+//! <-- {"jsonrpc": "2.0", "id": 1, "result": {"protocolVersion": 1, "sdkVersion": "0.1.7", "sessionId": null}}
+//! --> {"jsonrpc": "2.0", "id": 2, "method": "query", "params": {"prompt": "What's 2 + 2?"}}
+//! <-- {"jsonrpc": "2.0", "method": "stream", "params": {"message": {"type": "assistant", ...}}}
+//! <-- {"jsonrpc": "2.0", "method": "stream", "params": {"message": {"type": "result", ...}}}
+//! <-- {"jsonrpc": "2.0", "id": 2, "result": {"type": "result", "subtype": "success", ...}}
+//! ```
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinSet;
+use tokio_stream::StreamExt;
+
+use crate::client::ClaudeClient;
+use crate::control::ControlHandle;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ClaudeAgentOptions, Message, PermissionMode};
+
+/// Version of the bridge protocol, reported by `initialize`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// JSON-RPC error code for a message that isn't JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for a message that isn't a request.
+pub const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code for an unknown method.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for malformed params.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Error code for a request before `initialize`, as in LSP.
+pub const NOT_INITIALIZED: i64 = -32002;
+/// Error code for an error from the SDK.
+pub const SDK_ERROR: i64 = -32000;
+
+/// Largest message accepted, in bytes.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Params of `initialize`, each overriding the bridge's options.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InitializeParams {
+    /// Model to use.
+    pub model: Option<String>,
+    /// Working directory of the CLI.
+    pub cwd: Option<PathBuf>,
+    /// Permission mode, e.g. `"acceptEdits"`.
+    pub permission_mode: Option<PermissionMode>,
+    /// Maximum conversation turns per query.
+    pub max_turns: Option<u32>,
+    /// Session to resume.
+    pub resume: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QueryParams {
+    prompt: String,
+}
+
+/// How messages are delimited, decided by the host's first message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Framing {
+    Headers,
+    Lines,
+}
+
+/// A connected session.
+struct Session {
+    client: Arc<Mutex<ClaudeClient>>,
+    control: ControlHandle,
+}
+
+/// An error answered to a request.
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<ClaudeSDKError> for RpcError {
+    fn from(e: ClaudeSDKError) -> Self {
+        Self {
+            code: SDK_ERROR,
+            message: e.to_string(),
+            data: Some(json!({"kind": e.kind()})),
+        }
+    }
+}
+
+/// Serves the SDK over JSON-RPC.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::bridge::Bridge;
+/// use claude_agents_sdk::ClaudeAgentOptions;
+///
+/// # async fn example() -> claude_agents_sdk::Result<()> {
+/// let bridge = Bridge::new(ClaudeAgentOptions::new().with_max_turns(8));
+/// bridge.serve(tokio::io::stdin(), tokio::io::stdout()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Bridge {
+    options: ClaudeAgentOptions,
+}
+
+impl Bridge {
+    /// Create a bridge connecting with `options`.
+    pub fn new(options: ClaudeAgentOptions) -> Self {
+        Self { options }
+    }
+
+    /// Answer requests from `input` on `output` until `exit` or the end of
+    /// the input.
+    ///
+    /// Returns an error only if reading or writing fails; errors in
+    /// requests are answered to the host.
+    pub async fn serve<R, W>(self, input: R, output: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let framing = Arc::new(OnceLock::new());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let writer = tokio::spawn(write_messages(output, Arc::clone(&framing), rx));
+
+        let mut input = BufReader::new(input);
+        let mut session: Option<Session> = None;
+        let mut queries = JoinSet::new();
+        let result = loop {
+            let (message, message_framing) = match read_message(&mut input).await {
+                Ok(Some(read)) => read,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+            framing.get_or_init(|| message_framing);
+
+            let message: Value = match serde_json::from_slice(&message) {
+                Ok(message) => message,
+                Err(e) => {
+                    let error = RpcError::new(PARSE_ERROR, format!("Invalid JSON: {}", e));
+                    let _ = tx.send(response(Value::Null, Err(error)));
+                    continue;
+                }
+            };
+            let id = message.get("id").cloned();
+            let Some(method) = message.get("method").and_then(Value::as_str) else {
+                // Responses from the host need no answer.
+                if message.get("result").is_none() && message.get("error").is_none() {
+                    let error = RpcError::new(INVALID_REQUEST, "Not a JSON-RPC request");
+                    let _ = tx.send(response(id.unwrap_or(Value::Null), Err(error)));
+                }
+                continue;
+            };
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            let outcome = match method {
+                "exit" => break Ok(()),
+                "initialize" => self.initialize(&mut session, params).await,
+                "query" => match start_query(session.as_ref(), params, &tx, id.clone()) {
+                    Ok(query) => {
+                        queries.spawn(query);
+                        continue;
+                    }
+                    Err(e) => Err(e),
+                },
+                "interrupt" => match &session {
+                    Some(session) => session
+                        .control
+                        .interrupt()
+                        .await
+                        .map(|()| Value::Null)
+                        .map_err(RpcError::from),
+                    None => Err(not_initialized()),
+                },
+                "shutdown" => {
+                    queries.abort_all();
+                    match session.take() {
+                        Some(session) => session
+                            .client
+                            .lock()
+                            .await
+                            .disconnect()
+                            .await
+                            .map(|()| Value::Null)
+                            .map_err(RpcError::from),
+                        None => Ok(Value::Null),
+                    }
+                }
+                other => Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("Unknown method {}", other),
+                )),
+            };
+            if let Some(id) = id {
+                let _ = tx.send(response(id, outcome));
+            }
+        };
+
+        queries.shutdown().await;
+        if let Some(session) = session {
+            let _ = session.client.lock().await.disconnect().await;
+        }
+        drop(tx);
+        let written = writer
+            .await
+            .map_err(|e| ClaudeSDKError::internal(format!("Bridge writer failed: {}", e)))?;
+        result.and(written)
+    }
+
+    async fn initialize(
+        &self,
+        session: &mut Option<Session>,
+        params: Value,
+    ) -> std::result::Result<Value, RpcError> {
+        if session.is_some() {
+            return Err(RpcError::new(INVALID_REQUEST, "Already initialized"));
+        }
+        let params: InitializeParams = parse_params(params)?;
+
+        let mut options = self.options.clone();
+        if let Some(model) = params.model {
+            options = options.with_model(model);
+        }
+        if let Some(cwd) = params.cwd {
+            options = options.with_cwd(cwd);
+        }
+        if let Some(mode) = params.permission_mode {
+            options = options.with_permission_mode(mode);
+        }
+        if let Some(turns) = params.max_turns {
+            options = options.with_max_turns(turns);
+        }
+        if let Some(resume) = params.resume {
+            options.resume = Some(resume);
+        }
+
+        let mut client = ClaudeClient::new(Some(options));
+        client.connect().await?;
+        let control = client.control_handle()?;
+        let session_id = client.session_id();
+        *session = Some(Session {
+            client: Arc::new(Mutex::new(client)),
+            control,
+        });
+        Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "sdkVersion": env!("CARGO_PKG_VERSION"),
+            "sessionId": session_id,
+        }))
+    }
+}
+
+impl std::fmt::Debug for Bridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bridge")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Serve the SDK over this process's stdin and stdout.
+#[cfg(feature = "subprocess")]
+pub async fn serve_stdio(options: ClaudeAgentOptions) -> Result<()> {
+    Bridge::new(options)
+        .serve(tokio::io::stdin(), tokio::io::stdout())
+        .await
+}
+
+/// A task running one query, or the error to answer it with.
+fn start_query(
+    session: Option<&Session>,
+    params: Value,
+    tx: &mpsc::UnboundedSender<Value>,
+    id: Option<Value>,
+) -> std::result::Result<impl std::future::Future<Output = ()>, RpcError> {
+    let session = session.ok_or_else(not_initialized)?;
+    let QueryParams { prompt } = parse_params(params)?;
+    let mut client = Arc::clone(&session.client)
+        .try_lock_owned()
+        .map_err(|_| RpcError::new(INVALID_REQUEST, "A query is already running"))?;
+    let tx = tx.clone();
+    Ok(async move {
+        let outcome = async {
+            client.query(&prompt).await?;
+            let mut messages = client.receive_messages();
+            while let Some(message) = messages.next().await {
+                let message = message?;
+                let value = serde_json::to_value(&message).map_err(|e| {
+                    ClaudeSDKError::internal(format!("Failed to serialize message: {}", e))
+                })?;
+                let _ = tx.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "stream",
+                    "params": {"message": value},
+                }));
+                if matches!(message, Message::Result(_)) {
+                    return Ok(value);
+                }
+            }
+            Err(ClaudeSDKError::cli_connection(
+                "CLI closed the stream before the result",
+            ))
+        }
+        .await
+        .map_err(RpcError::from);
+        if let Some(id) = id {
+            let _ = tx.send(response(id, outcome));
+        }
+    })
+}
+
+fn not_initialized() -> RpcError {
+    RpcError::new(NOT_INITIALIZED, "Call initialize first")
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn response(id: Value, outcome: std::result::Result<Value, RpcError>) -> Value {
+    match outcome {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => {
+            let mut body = json!({"code": error.code, "message": error.message});
+            if let Some(data) = error.data {
+                body["data"] = data;
+            }
+            json!({"jsonrpc": "2.0", "id": id, "error": body})
+        }
+    }
+}
+
+/// Read the next message body, or `None` at the end of the input.
+async fn read_message<R: AsyncRead + Unpin>(
+    input: &mut BufReader<R>,
+) -> Result<Option<(Vec<u8>, Framing)>> {
+    let io_error =
+        |e: std::io::Error| ClaudeSDKError::channel(format!("Failed to read from host: {}", e));
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line).await.map_err(io_error)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    if line.trim_start().starts_with(['{', '[']) {
+        return Ok(Some((line.into_bytes(), Framing::Lines)));
+    }
+
+    let mut length = None;
+    loop {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+        line.clear();
+        if input.read_line(&mut line).await.map_err(io_error)? == 0 {
+            return Ok(None);
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+    let length = length
+        .filter(|length| *length <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| ClaudeSDKError::channel("Message without a valid Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).await.map_err(io_error)?;
+    Ok(Some((body, Framing::Headers)))
+}
+
+async fn write_messages<W: AsyncWrite + Unpin>(
+    mut output: W,
+    framing: Arc<OnceLock<Framing>>,
+    mut rx: mpsc::UnboundedReceiver<Value>,
+) -> Result<()> {
+    let io_error =
+        |e: std::io::Error| ClaudeSDKError::channel(format!("Failed to write to host: {}", e));
+    while let Some(message) = rx.recv().await {
+        let body = message.to_string();
+        let framed = match framing.get().copied().unwrap_or(Framing::Headers) {
+            Framing::Headers => format!("Content-Length: {}\r\n\r\n{}", body.len(), body),
+            Framing::Lines => format!("{}\n", body),
+        };
+        output
+            .write_all(framed.as_bytes())
+            .await
+            .map_err(io_error)?;
+        output.flush().await.map_err(io_error)?;
+    }
+    Ok(())
+}
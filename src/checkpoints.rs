@@ -0,0 +1,192 @@
+//! File checkpoints for rewinding.
+//!
+//! With [`enable_file_checkpointing`](crate::ClaudeAgentOptions::enable_file_checkpointing),
+//! the CLI snapshots files at each user prompt, and
+//! [`ClaudeClient::rewind_files`](crate::ClaudeClient::rewind_files) restores
+//! them to the snapshot taken at a given prompt. The SDK records a
+//! [`Checkpoint`] for every prompt the CLI echoes back, along with the files
+//! the file-editing tools touched in response to it.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::types::{ContentBlock, Message, UserMessage, UserMessageContent};
+
+/// Tools whose `file_path` or `notebook_path` input is a file they change.
+const FILE_EDITING_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// A point the session's files can be rewound to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// ID of the user message the checkpoint was taken at.
+    pub message_id: String,
+    /// When the SDK received the message.
+    pub timestamp: SystemTime,
+    /// Files changed by tools after this checkpoint and before the next,
+    /// in the order first changed.
+    pub files_changed: Vec<PathBuf>,
+}
+
+/// Collects [`Checkpoint`]s from a session's messages.
+#[derive(Clone, Default)]
+pub(crate) struct CheckpointTracker {
+    checkpoints: Arc<Mutex<Vec<Checkpoint>>>,
+}
+
+impl CheckpointTracker {
+    pub(crate) fn snapshot(&self) -> Vec<Checkpoint> {
+        self.checkpoints
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn observe(&self, message: &Message) {
+        // Leave lazy assistant messages unparsed unless they edit a file.
+        if let Message::Lazy(lazy) = message {
+            let edits_file = lazy.raw()["message"]["content"]
+                .as_array()
+                .is_some_and(|blocks| {
+                    blocks.iter().any(|block| {
+                        block["name"]
+                            .as_str()
+                            .is_some_and(|name| FILE_EDITING_TOOLS.contains(&name))
+                    })
+                });
+            if lazy.msg_type() == "assistant" && !edits_file {
+                return;
+            }
+        }
+        match message.typed() {
+            Message::User(user) => {
+                if let Some(uuid) = prompt_id(user) {
+                    self.checkpoints
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(Checkpoint {
+                            message_id: uuid.to_string(),
+                            timestamp: SystemTime::now(),
+                            files_changed: Vec::new(),
+                        });
+                }
+            }
+            Message::Assistant(assistant) => {
+                let mut checkpoints = self.checkpoints.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(current) = checkpoints.last_mut() else {
+                    return;
+                };
+                for block in &assistant.content {
+                    let ContentBlock::ToolUse(tool_use) = block else {
+                        continue;
+                    };
+                    if !FILE_EDITING_TOOLS.contains(&tool_use.name.as_str()) {
+                        continue;
+                    }
+                    let path = tool_use
+                        .input
+                        .get("file_path")
+                        .or_else(|| tool_use.input.get("notebook_path"))
+                        .and_then(|v| v.as_str())
+                        .map(PathBuf::from);
+                    if let Some(path) = path {
+                        if !current.files_changed.contains(&path) {
+                            current.files_changed.push(path);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The ID of a user message that is a prompt rather than a tool result.
+fn prompt_id(user: &UserMessage) -> Option<&str> {
+    if user.parent_tool_use_id.is_some() {
+        return None;
+    }
+    let is_tool_result = match &user.content {
+        UserMessageContent::Text(_) => false,
+        UserMessageContent::Blocks(blocks) => blocks
+            .iter()
+            .any(|block| matches!(block, ContentBlock::ToolResult(_))),
+    };
+    if is_tool_result {
+        None
+    } else {
+        user.uuid.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+
+    fn message(value: serde_json::Value) -> Message {
+        parse_message(value).unwrap().unwrap()
+    }
+
+    fn tool_use(name: &str, input: serde_json::Value) -> Message {
+        message(serde_json::json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "t", "name": name, "input": input}
+            ]}
+        }))
+    }
+
+    #[test]
+    fn test_checkpoints_track_prompts_and_edited_files() {
+        let tracker = CheckpointTracker::default();
+        // Edits before the first prompt have no checkpoint to belong to.
+        tracker.observe(&tool_use(
+            "Write",
+            serde_json::json!({"file_path": "early.rs"}),
+        ));
+
+        tracker.observe(&message(serde_json::json!({
+            "type": "user", "uuid": "u1",
+            "message": {"role": "user", "content": "Fix the bug"}
+        })));
+        tracker.observe(&tool_use(
+            "Edit",
+            serde_json::json!({"file_path": "src/lib.rs"}),
+        ));
+        tracker.observe(&tool_use(
+            "Read",
+            serde_json::json!({"file_path": "src/main.rs"}),
+        ));
+        tracker.observe(&message(serde_json::json!({
+            "type": "user", "uuid": "tool-result",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t", "content": "ok"}
+            ]}
+        })));
+        tracker.observe(&tool_use(
+            "Edit",
+            serde_json::json!({"file_path": "src/lib.rs"}),
+        ));
+        tracker.observe(&tool_use(
+            "NotebookEdit",
+            serde_json::json!({"notebook_path": "analysis.ipynb"}),
+        ));
+
+        tracker.observe(&message(serde_json::json!({
+            "type": "user", "uuid": "u2",
+            "message": {"role": "user", "content": "Now add a test"}
+        })));
+
+        let checkpoints = tracker.snapshot();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].message_id, "u1");
+        assert_eq!(
+            checkpoints[0].files_changed,
+            [PathBuf::from("src/lib.rs"), PathBuf::from("analysis.ipynb")]
+        );
+        assert_eq!(checkpoints[1].message_id, "u2");
+        assert!(checkpoints[1].files_changed.is_empty());
+        assert!(checkpoints[0].timestamp <= checkpoints[1].timestamp);
+    }
+}
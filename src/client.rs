@@ -10,11 +10,25 @@
 //! - File checkpointing and rewinding
 
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio_stream::{Stream, StreamExt};
 
-use crate::_internal::client::InternalClient;
+use crate::_internal::client::{unwrap_shared, InternalClient};
+use crate::attachments::{prompt_content, Attachment};
+use crate::checkpoints::Checkpoint;
+use crate::control::ControlHandle;
 use crate::errors::{ClaudeSDKError, Result};
+use crate::health::HealthReport;
+use crate::keepalive::Pinger;
+use crate::mcp_status::{McpServerStatus, ToolDescriptor};
+use crate::options_delta::{AppliedDelta, OptionsDelta};
+use crate::plan::TodoItem;
+use crate::sessions::SessionInfo;
+use crate::stats::SessionStats;
+use crate::subagents::Subagent;
+use crate::turns::{AssistantTurn, TurnCollector};
 use crate::types::*;
 
 /// Bidirectional client for streaming Claude interactions.
@@ -76,7 +90,10 @@ pub struct ClaudeClient {
     /// Internal client implementation.
     internal: InternalClient,
     /// Message receiver from the internal client.
-    message_rx: Option<mpsc::Receiver<Result<Message>>>,
+    message_rx: Option<mpsc::Receiver<Result<Arc<Message>>>>,
+    /// Messages of the turn [`receive_turn`](Self::receive_turn) is
+    /// collecting.
+    turns: TurnCollector,
 }
 
 impl ClaudeClient {
@@ -104,6 +121,7 @@ impl ClaudeClient {
         Self {
             internal: InternalClient::new(options.unwrap_or_default()),
             message_rx: None,
+            turns: TurnCollector::default(),
         }
     }
 
@@ -172,6 +190,99 @@ impl ClaudeClient {
         self.internal.send_message(prompt).await
     }
 
+    /// Send a query with local files attached.
+    ///
+    /// The prompt is followed by a content block per attachment, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not connected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{Attachment, ClaudeClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     let screenshot = Attachment::from_path("screenshot.png")?;
+    ///     client
+    ///         .query_with_attachments("What's wrong with this layout?", vec![screenshot])
+    ///         .await?;
+    ///     let (response, _) = client.receive_response().await?;
+    ///     println!("{}", response);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn query_with_attachments(
+        &mut self,
+        prompt: &str,
+        attachments: Vec<Attachment>,
+    ) -> Result<()> {
+        self.internal
+            .send_content(prompt_content(prompt, &attachments))
+            .await
+    }
+
+    /// Send the result of a tool call the application executed itself.
+    ///
+    /// Writes a user message holding a single `tool_result` block for
+    /// `tool_use_id`. `content` is a string or an array of content blocks;
+    /// set `is_error` when the tool failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not connected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{ClaudeClient, ContentBlock, Message};
+    /// use tokio_stream::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("What's the weather in Oslo?").await?;
+    ///
+    ///     let mut tool_use_id = None;
+    ///     {
+    ///         let mut stream = client.receive_messages();
+    ///         while let Some(msg) = stream.next().await {
+    ///             match msg? {
+    ///                 Message::Assistant(asst) => {
+    ///                     for block in &asst.content {
+    ///                         if let ContentBlock::ToolUse(tool_use) = block {
+    ///                             tool_use_id = Some(tool_use.id.clone());
+    ///                         }
+    ///                     }
+    ///                 }
+    ///                 Message::Result(_) => break,
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     if let Some(id) = tool_use_id {
+    ///         client.send_tool_result(&id, "4°C, light rain", false).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn send_tool_result(
+        &mut self,
+        tool_use_id: &str,
+        content: impl Into<serde_json::Value>,
+        is_error: bool,
+    ) -> Result<()> {
+        self.internal
+            .send_tool_result(tool_use_id, content.into(), is_error)
+            .await
+    }
+
     /// Get a stream of messages from the current query.
     ///
     /// Returns a stream that yields messages as they are received from
@@ -201,6 +312,16 @@ impl ClaudeClient {
     /// }
     /// ```
     pub fn receive_messages(&mut self) -> impl Stream<Item = Result<Message>> + '_ {
+        self.receive_messages_shared()
+            .map(|message| message.map(unwrap_shared))
+    }
+
+    /// Get a stream of messages from the current query without copying them.
+    ///
+    /// Like [`receive_messages`](Self::receive_messages), but yields each
+    /// message in the [`Arc`] it was parsed into, so it can be passed to
+    /// other tasks or kept in several places without cloning its payload.
+    pub fn receive_messages_shared(&mut self) -> impl Stream<Item = Result<Arc<Message>>> + '_ {
         futures::stream::poll_fn(move |cx| {
             if let Some(ref mut rx) = self.message_rx {
                 Pin::new(rx).poll_recv(cx)
@@ -241,7 +362,7 @@ impl ClaudeClient {
         let mut response_parts: Vec<String> = Vec::new();
 
         while let Some(msg) = self.receive_messages().next().await {
-            match msg? {
+            match msg?.into_parsed()? {
                 Message::Assistant(asst) => {
                     let text = asst.text();
                     if !text.is_empty() {
@@ -258,6 +379,56 @@ impl ClaudeClient {
         Err(ClaudeSDKError::internal("Connection closed without result"))
     }
 
+    /// Receive the current query's response one assistant turn at a time.
+    ///
+    /// Returns after each response of the model once its tool calls have
+    /// results, so a UI can show the agent step by step or an application
+    /// can review each step before letting it continue. The turn that ends
+    /// the query carries its result; see [`AssistantTurn::is_final`].
+    /// Returns `None` if the connection has closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Fix the failing test").await?;
+    ///
+    ///     while let Some(turn) = client.receive_turn().await? {
+    ///         println!("{}", turn.text());
+    ///         for (call, result) in turn.tool_calls() {
+    ///             println!("  {} -> {:?}", call.name, result.map(|r| &r.content));
+    ///         }
+    ///         if turn.is_final() {
+    ///             break;
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn receive_turn(&mut self) -> Result<Option<AssistantTurn>> {
+        loop {
+            let Some(msg) = self.receive_messages().next().await else {
+                break;
+            };
+            if let Some(turn) = self.turns.push(msg?) {
+                return Ok(Some(turn));
+            }
+        }
+
+        if self.turns.is_empty() {
+            Ok(None)
+        } else {
+            self.turns = TurnCollector::default();
+            Err(ClaudeSDKError::internal("Connection closed mid-turn"))
+        }
+    }
+
     /// Interrupt the current operation.
     ///
     /// Sends an interrupt signal to Claude, stopping the current response.
@@ -337,6 +508,44 @@ impl ClaudeClient {
         self.internal.set_model(model).await
     }
 
+    /// Change options of the running session.
+    ///
+    /// Changes to the model, permission mode, and thinking budget are sent
+    /// to the CLI. With a [`can_use_tool`](ClaudeAgentOptions::can_use_tool)
+    /// callback, newly allowed tools are granted when the CLI asks for them;
+    /// without one the CLI never asks, so they wait for a reconnect. If any
+    /// of these fails, the ones already made are undone and the
+    /// error returned. Other changes are saved in the client's options and
+    /// take effect on the next [`connect`](Self::connect); the result lists
+    /// which fields are which.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{ClaudeClient, OptionsDelta, PermissionMode};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///
+    ///     let delta = OptionsDelta::new()
+    ///         .with_model("claude-opus-4-5")
+    ///         .with_permission_mode(PermissionMode::AcceptEdits)
+    ///         .with_system_prompt("Be terse.");
+    ///     let applied = client.apply_options_delta(delta).await?;
+    ///     if !applied.requires_reconnect.is_empty() {
+    ///         client.disconnect().await?;
+    ///         client.connect().await?;
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn apply_options_delta(&mut self, delta: OptionsDelta) -> Result<AppliedDelta> {
+        self.internal.apply_options_delta(delta).await
+    }
+
     /// Rewind files to a specific user message.
     ///
     /// This is only available when file checkpointing is enabled.
@@ -368,6 +577,124 @@ impl ClaudeClient {
         self.internal.rewind_files(user_message_id).await
     }
 
+    /// List the checkpoints [`rewind_files`](Self::rewind_files) can restore,
+    /// oldest first.
+    ///
+    /// Requires [`enable_file_checkpointing`](ClaudeAgentOptions::enable_file_checkpointing).
+    /// There is one checkpoint per prompt, listing the files the session's
+    /// file-editing tools changed in response to it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{ClaudeAgentOptions, ClaudeClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut options = ClaudeAgentOptions::new();
+    ///     options.enable_file_checkpointing = true;
+    ///
+    ///     let mut client = ClaudeClient::new(Some(options));
+    ///     client.connect().await?;
+    ///     client.query("Refactor the parser").await?;
+    ///     client.receive_response().await?;
+    ///
+    ///     // Undo the refactor
+    ///     if let Some(checkpoint) = client.checkpoints().last() {
+    ///         println!("Restoring {:?}", checkpoint.files_changed);
+    ///         client.rewind_to(checkpoint).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        self.internal.checkpoints()
+    }
+
+    /// Restore files to their state at a checkpoint from
+    /// [`checkpoints`](Self::checkpoints).
+    pub async fn rewind_to(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.rewind_files(checkpoint.message_id.as_str()).await
+    }
+
+    /// The agent's task list as last written with the `TodoWrite` tool,
+    /// empty until the agent makes one.
+    ///
+    /// The list is updated as messages are received, so reading it after
+    /// each message shows the plan as it changes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{ClaudeClient, TodoStatus};
+    /// use tokio_stream::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Migrate the config loader to TOML").await?;
+    ///     {
+    ///         let mut messages = client.receive_messages();
+    ///         while let Some(message) = messages.next().await {
+    ///             let message = message?;
+    ///             if let Some(todos) = message.todo_update() {
+    ///                 println!("{} tasks", todos.len());
+    ///             }
+    ///             if message.is_result() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     for todo in client.current_plan() {
+    ///         let mark = if todo.status == TodoStatus::Completed { "x" } else { " " };
+    ///         println!("[{}] {}", mark, todo.content);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn current_plan(&self) -> Vec<TodoItem> {
+        self.internal.plan()
+    }
+
+    /// Subagents started with the `Task` tool so far, in the order they
+    /// started, each with its own messages and token usage.
+    ///
+    /// A subagent's estimated cost is filled in once its turn's result
+    /// arrives. Subscribe to an [`EventBus`](crate::EventBus) for
+    /// [`SdkEvent::SubagentStarted`](crate::SdkEvent::SubagentStarted) and
+    /// [`SdkEvent::SubagentFinished`](crate::SdkEvent::SubagentFinished) to
+    /// follow them as they run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Review each crate in the workspace").await?;
+    ///     client.receive_response().await?;
+    ///
+    ///     for subagent in client.subagents() {
+    ///         println!(
+    ///             "{}: {:?}, {} messages, ${:.4}",
+    ///             subagent.description.as_deref().unwrap_or("subagent"),
+    ///             subagent.status,
+    ///             subagent.messages.len(),
+    ///             subagent.cost_usd.unwrap_or(0.0),
+    ///         );
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn subagents(&self) -> Vec<Subagent> {
+        self.internal.subagents()
+    }
+
     /// Get server initialization info.
     ///
     /// Returns the initialization response from the CLI, which includes
@@ -399,11 +726,131 @@ impl ClaudeClient {
         self.internal.get_server_info().await
     }
 
+    /// Get a handle for sending control requests from other tasks.
+    ///
+    /// The handle is independent of this client's borrow, so another task
+    /// can interrupt or switch models while this one consumes
+    /// [`receive_messages`](Self::receive_messages). Fails if the client is
+    /// not connected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    /// use tokio_stream::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Write a very long story").await?;
+    ///
+    ///     let control = client.control_handle()?;
+    ///     tokio::spawn(async move {
+    ///         tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    ///         let _ = control.interrupt().await;
+    ///     });
+    ///
+    ///     let mut messages = client.receive_messages();
+    ///     while let Some(message) = messages.next().await {
+    ///         println!("{:?}", message?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn control_handle(&self) -> Result<ControlHandle> {
+        self.internal.control_handle()
+    }
+
+    /// Time one control round trip to the CLI (streaming mode only).
+    ///
+    /// Uses the configured [`control_timeout`](crate::ClaudeAgentOptions::control_timeout).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///
+    ///     println!("CLI round trip: {:?}", client.ping().await?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn ping(&self) -> Result<Duration> {
+        self.control_handle()?.ping().await
+    }
+
+    /// Ping the CLI every `interval` in the background (streaming mode only).
+    ///
+    /// The returned [`Pinger`] keeps the last [`PING_HISTORY_LEN`](crate::PING_HISTORY_LEN)
+    /// samples and stops when dropped. A ping that takes longer than
+    /// `interval` is recorded as failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///
+    ///     let pinger = client.start_pinger(Duration::from_secs(10))?;
+    ///     client.query("Refactor the parser").await?;
+    ///     client.receive_response().await?;
+    ///
+    ///     println!("mean RTT {:?}, {} failed", pinger.mean_rtt(), pinger.failures());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn start_pinger(&self, interval: Duration) -> Result<Pinger> {
+        Ok(self.control_handle()?.start_pinger(interval))
+    }
+
     /// Get current MCP server connection status (streaming mode only).
     pub async fn get_mcp_status(&self) -> Result<serde_json::Value> {
         self.internal.get_mcp_status().await
     }
 
+    /// Get the status of each MCP server (streaming mode only).
+    ///
+    /// Includes servers from the options, settings, and SDK servers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::{ClaudeClient, McpServerState};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///
+    ///     for server in client.mcp_servers().await? {
+    ///         if server.state != McpServerState::Connected {
+    ///             println!("{}: {:?} {:?}", server.name, server.state, server.error);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn mcp_servers(&self) -> Result<Vec<McpServerStatus>> {
+        self.internal.mcp_servers().await
+    }
+
+    /// Get the tools an MCP server advertises (streaming mode only).
+    ///
+    /// Fails if no server has that name.
+    pub async fn mcp_tools(&self, server: &str) -> Result<Vec<ToolDescriptor>> {
+        self.internal.mcp_tools(server).await
+    }
+
     /// Disconnect from the Claude CLI.
     ///
     /// Gracefully closes the connection to the CLI process.
@@ -426,13 +873,157 @@ impl ClaudeClient {
     /// ```
     pub async fn disconnect(&mut self) -> Result<()> {
         self.message_rx = None;
+        self.turns = TurnCollector::default();
         self.internal.disconnect().await
     }
 
+    /// Get latency and throughput statistics for the current connection.
+    ///
+    /// Per-turn figures are also attached to each result as
+    /// [`ResultMessage::stats`](crate::ResultMessage::stats).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Hello!").await?;
+    ///     client.receive_response().await?;
+    ///
+    ///     if let Some(turn) = client.stats().last_turn {
+    ///         println!("TTFT: {:?}, tokens/s: {:?}", turn.time_to_first_token, turn.tokens_per_second);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn stats(&self) -> SessionStats {
+        self.internal.stats()
+    }
+
+    /// Check the health of the client and its CLI.
+    ///
+    /// Runs `claude --version`, sends one control request (waiting at most
+    /// [`HEALTH_CHECK_TIMEOUT`](crate::HEALTH_CHECK_TIMEOUT)) that also
+    /// returns MCP server status, and checks that the CLI process is
+    /// running. Never fails: problems show up in the report. A disconnected
+    /// client reports only the CLI version.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///
+    ///     let health = client.health().await;
+    ///     if !health.is_healthy() {
+    ///         eprintln!("not ready: {:?}", health);
+    ///     }
+    ///     for server in health.failed_mcp_servers() {
+    ///         eprintln!("MCP server {} failed: {:?}", server.name, server.error);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn health(&self) -> HealthReport {
+        self.internal.health().await
+    }
+
     /// Check if the client is connected.
     pub fn is_connected(&self) -> bool {
         self.internal.is_connected()
     }
+
+    /// Whether the client is connected and the CLI hasn't exited since.
+    pub(crate) fn is_alive(&self) -> bool {
+        self.internal.is_alive()
+    }
+
+    /// The current session ID, once the CLI has reported one.
+    ///
+    /// The CLI reports it with its first message, after the first query.
+    pub fn session_id(&self) -> Option<String> {
+        self.internal.session_id()
+    }
+
+    /// Connect, resuming the session last run in the working directory.
+    ///
+    /// This is what
+    /// [`continue_conversation`](ClaudeAgentOptions::continue_conversation)
+    /// asks the CLI to do, made explicit: the session is found with
+    /// [`Session::most_recent`](crate::Session::most_recent), under the
+    /// options' [`cwd`](ClaudeAgentOptions::cwd) or the current directory
+    /// and a `CLAUDE_CONFIG_DIR` set in [`env`](ClaudeAgentOptions::env),
+    /// and resumed by ID.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the client is already connected, no session was saved for
+    /// the directory, or connecting fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     let session = client.resume_most_recent().await?;
+    ///     println!("Resumed {}", session.session_id);
+    ///     client.query("Where were we?").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn resume_most_recent(&mut self) -> Result<SessionInfo> {
+        let session = self.internal.resume_most_recent()?;
+        self.connect().await?;
+        Ok(session)
+    }
+
+    /// Fork the session into a second, connected client.
+    ///
+    /// The new client resumes this session with
+    /// [`fork_session`](ClaudeAgentOptions::fork_session), so it starts from
+    /// the same conversation under a new session ID and the two diverge from
+    /// here. It uses this client's options. Fork between turns: a turn still
+    /// in progress may be only partly in the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the client is not connected, no query has been sent yet, or
+    /// the new client fails to connect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::ClaudeClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut client = ClaudeClient::new(None);
+    ///     client.connect().await?;
+    ///     client.query("Here is the failing test...").await?;
+    ///     client.receive_response().await?;
+    ///
+    ///     let mut alternative = client.fork().await?;
+    ///     client.query("Fix it by changing the parser").await?;
+    ///     alternative.query("Fix it by changing the lexer").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn fork(&self) -> Result<ClaudeClient> {
+        let mut client = ClaudeClient::new(Some(self.internal.fork_options()?));
+        client.connect().await?;
+        Ok(client)
+    }
 }
 
 /// Builder for creating a [`ClaudeClient`] with configuration.
@@ -478,9 +1069,9 @@ impl ClaudeClientBuilder {
         self
     }
 
-    /// Set the system prompt.
-    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
-        self.options.system_prompt = Some(SystemPromptConfig::Text(prompt.into()));
+    /// Set the system prompt: text, or a [`SystemPromptPreset`](crate::SystemPromptPreset).
+    pub fn system_prompt(mut self, prompt: impl Into<SystemPromptConfig>) -> Self {
+        self.options.system_prompt = Some(prompt.into());
         self
     }
 
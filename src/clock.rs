@@ -0,0 +1,191 @@
+//! Time source for timeouts, keepalive pings, and latency measurements.
+//!
+//! The client reads time through a [`Clock`] set with
+//! [`ClaudeAgentOptions::with_clock`](crate::ClaudeAgentOptions::with_clock).
+//! The default [`TokioClock`] uses `tokio::time`, so tests can already run
+//! timeouts instantly under `tokio::time::pause`. [`MockClock`] only moves
+//! when told to, for tests that need to step through time exactly.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::clock::{Clock, MockClock};
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let clock = MockClock::new();
+//! let start = clock.now();
+//!
+//! let sleep = tokio::spawn(clock.sleep(Duration::from_secs(30)));
+//! clock.advance(Duration::from_secs(30));
+//! sleep.await.unwrap();
+//!
+//! assert_eq!(clock.now() - start, Duration::from_secs(30));
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use futures::future::BoxFuture;
+use tokio::sync::watch;
+
+/// A source of time.
+pub trait Clock: Send + Sync {
+    /// The current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// The current wall-clock time.
+    fn system_time(&self) -> SystemTime;
+
+    /// Wait for `duration` to pass.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The default clock, backed by `tokio::time`.
+///
+/// [`now`](Clock::now) and [`sleep`](Clock::sleep) follow
+/// `tokio::time::pause` and `advance`; [`system_time`](Clock::system_time)
+/// is the real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when [`advance`](Self::advance) is called.
+///
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    start_system: SystemTime,
+    elapsed: Arc<watch::Sender<Duration>>,
+}
+
+impl MockClock {
+    /// A clock stopped at the current time.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_system: SystemTime::now(),
+            elapsed: Arc::new(watch::Sender::new(Duration::ZERO)),
+        }
+    }
+
+    /// Move the clock forward, waking sleeps that are now due.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+
+    /// Time advanced since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.start_system + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let deadline = self.elapsed() + duration;
+        let mut elapsed = self.elapsed.subscribe();
+        Box::pin(async move {
+            while *elapsed.borrow_and_update() < deadline {
+                // The sender lives as long as any clone of the clock.
+                if elapsed.changed().await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            }
+        })
+    }
+}
+
+/// The default clock.
+pub(crate) fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(TokioClock)
+}
+
+/// Run `future` with a deadline on `clock`; `None` if it passes first.
+pub(crate) async fn timeout<F: Future>(
+    clock: &dyn Clock,
+    duration: Duration,
+    future: F,
+) -> Option<F::Output> {
+    tokio::select! {
+        biased;
+        output = future => Some(output),
+        _ = clock.sleep(duration) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_clock_sleep_waits_for_advance() {
+        let clock = MockClock::new();
+        let mut sleep = tokio::spawn(clock.sleep(Duration::from_secs(10)));
+
+        clock.advance(Duration::from_secs(9));
+        tokio::task::yield_now().await;
+        assert!(futures::poll!(&mut sleep).is_pending());
+
+        clock.advance(Duration::from_secs(1));
+        sleep.await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_zero_sleep_is_ready() {
+        MockClock::new().sleep(Duration::ZERO).await;
+    }
+
+    #[tokio::test]
+    async fn test_timeout_on_mock_clock() {
+        let clock = MockClock::new();
+        let pending = timeout(&clock, Duration::from_secs(1), std::future::pending::<()>());
+        let advance = async {
+            tokio::task::yield_now().await;
+            clock.advance(Duration::from_secs(1));
+        };
+        let (result, ()) = tokio::join!(pending, advance);
+        assert_eq!(result, None);
+
+        assert_eq!(timeout(&clock, Duration::ZERO, async { 7 }).await, Some(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokio_clock_follows_paused_time() {
+        let clock = TokioClock;
+        let start = clock.now();
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(clock.now() - start, Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,220 @@
+//! Automatic context compaction.
+//!
+//! With [`ClaudeAgentOptions::auto_compact`](crate::ClaudeAgentOptions::auto_compact)
+//! set, the client watches the context size reported in assistant messages
+//! and `message_start` stream events. Once a turn ends above the policy's
+//! threshold, the next [`ClaudeClient::query`](crate::ClaudeClient::query)
+//! first sends the CLI's `/compact` command. The compaction's messages are
+//! kept out of the message stream; an [`SdkEvent::AutoCompacted`] on the
+//! event bus reports it instead.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EventTracker, SdkEvent};
+
+/// When to compact the conversation automatically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoCompactPolicy {
+    /// Compact once a turn ends with at least this many tokens in context.
+    pub trigger_tokens: u64,
+    /// Instructions for the summary, passed to `/compact`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+impl AutoCompactPolicy {
+    /// Compact once the context reaches `trigger_tokens`.
+    pub fn new(trigger_tokens: u64) -> Self {
+        Self {
+            trigger_tokens,
+            instructions: None,
+        }
+    }
+
+    /// Set instructions for the summary.
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// The prompt that asks the CLI to compact.
+    fn command(&self) -> String {
+        match &self.instructions {
+            Some(instructions) => format!("/compact {}", instructions),
+            None => "/compact".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Tokens in context as of the latest API call.
+    context_tokens: Option<u64>,
+    /// Context size that made a compaction due, until it is sent.
+    due: Option<u64>,
+    /// Context size before the compaction in progress.
+    running: Option<u64>,
+}
+
+/// Decides when to compact and absorbs the compaction's messages.
+#[derive(Clone, Default)]
+pub(crate) struct AutoCompactTracker {
+    policy: Option<AutoCompactPolicy>,
+    events: EventTracker,
+    state: Arc<Mutex<State>>,
+}
+
+impl AutoCompactTracker {
+    pub(crate) fn new(policy: Option<AutoCompactPolicy>, events: EventTracker) -> Self {
+        Self {
+            policy,
+            events,
+            state: Arc::default(),
+        }
+    }
+
+    /// Observe a raw message from the CLI.
+    ///
+    /// Returns true if the message belongs to an automatic compaction and
+    /// should not be delivered.
+    pub(crate) fn absorb(&self, raw: &serde_json::Value) -> bool {
+        let Some(policy) = &self.policy else {
+            return false;
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let msg_type = raw.get("type").and_then(|v| v.as_str());
+
+        if let Some(context_tokens) = state.running {
+            if msg_type == Some("result") {
+                state.running = None;
+                state.context_tokens = None;
+                self.events.emit(SdkEvent::AutoCompacted {
+                    context_tokens,
+                    trigger_tokens: policy.trigger_tokens,
+                    is_error: raw
+                        .get("is_error")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                });
+            }
+            return true;
+        }
+
+        let usage = match msg_type {
+            Some("assistant") => raw.pointer("/message/usage"),
+            Some("stream_event")
+                if raw.pointer("/event/type") == Some(&serde_json::json!("message_start")) =>
+            {
+                raw.pointer("/event/message/usage")
+            }
+            Some("result") => {
+                if let Some(tokens) = state.context_tokens {
+                    if tokens >= policy.trigger_tokens {
+                        state.due = Some(tokens);
+                    }
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some(tokens) = usage.and_then(context_tokens) {
+            state.context_tokens = Some(tokens);
+        }
+        false
+    }
+
+    /// If a compaction is due, mark it as running and return the prompt to
+    /// send before the next user message.
+    pub(crate) fn take_due(&self) -> Option<String> {
+        let policy = self.policy.as_ref()?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let tokens = state.due.take()?;
+        state.running = Some(tokens);
+        Some(policy.command())
+    }
+}
+
+/// Tokens in context for an API call: all input, cached or not.
+fn context_tokens(usage: &serde_json::Value) -> Option<u64> {
+    let field = |name: &str| usage.get(name).and_then(|v| v.as_u64());
+    let input = field("input_tokens")?;
+    Some(
+        input
+            + field("cache_creation_input_tokens").unwrap_or(0)
+            + field("cache_read_input_tokens").unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use serde_json::json;
+
+    fn assistant(input_tokens: u64, cache_read: u64) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [], "usage": {
+                "input_tokens": input_tokens,
+                "cache_read_input_tokens": cache_read,
+                "output_tokens": 10
+            }}
+        })
+    }
+
+    fn result() -> serde_json::Value {
+        json!({"type": "result", "subtype": "success", "is_error": false})
+    }
+
+    #[test]
+    fn test_compaction_due_after_turn_over_threshold() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+        let tracker = AutoCompactTracker::new(
+            Some(AutoCompactPolicy::new(1000).with_instructions("keep the plan")),
+            EventTracker::new(Some(bus)),
+        );
+
+        assert!(!tracker.absorb(&assistant(100, 200)));
+        assert!(!tracker.absorb(&result()));
+        assert_eq!(tracker.take_due(), None);
+
+        assert!(!tracker.absorb(&json!({
+            "type": "stream_event",
+            "event": {"type": "message_start", "message": {"usage": {"input_tokens": 50, "cache_read_input_tokens": 1000}}}
+        })));
+        assert!(!tracker.absorb(&result()));
+        assert_eq!(
+            tracker.take_due().as_deref(),
+            Some("/compact keep the plan")
+        );
+        assert_eq!(tracker.take_due(), None);
+
+        assert!(tracker.absorb(&json!({"type": "system", "subtype": "compact_boundary"})));
+        assert!(tracker.absorb(&result()));
+        assert!(!tracker.absorb(&assistant(10, 0)));
+
+        match rx.try_recv().unwrap() {
+            SdkEvent::AutoCompacted {
+                context_tokens,
+                trigger_tokens,
+                is_error,
+            } => {
+                assert_eq!(context_tokens, 1050);
+                assert_eq!(trigger_tokens, 1000);
+                assert!(!is_error);
+            }
+            other => panic!("Expected AutoCompacted, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_without_policy_nothing_is_absorbed() {
+        let tracker = AutoCompactTracker::default();
+        assert!(!tracker.absorb(&assistant(1_000_000, 0)));
+        assert!(!tracker.absorb(&result()));
+        assert_eq!(tracker.take_due(), None);
+    }
+}
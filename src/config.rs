@@ -0,0 +1,396 @@
+//! Loading options from configuration files.
+//!
+//! [`ClaudeAgentOptions::from_file`] reads options from TOML, JSON, or, with
+//! the `yaml` feature, YAML, chosen by file extension. Hooks are referenced by
+//! name and resolved against a [`HookRegistry`] of callbacks defined in code.
+//!
+//! ```toml
+//! model = "claude-sonnet-4-5"
+//! permission_mode = "acceptEdits"
+//! allowed_tools = ["Read", "Grep", "Bash"]
+//! max_turns = 20
+//!
+//! [sandbox]
+//! enabled = true
+//!
+//! [mcp_servers.docs]
+//! command = "docs-mcp"
+//! args = ["--stdio"]
+//!
+//! [[hooks.PreToolUse]]
+//! matcher = "Bash"
+//! hooks = ["audit_bash"]
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use claude_agents_sdk::{ClaudeAgentOptions, HookOutput, HookRegistry};
+//!
+//! # fn example() -> claude_agents_sdk::Result<()> {
+//! let hooks = HookRegistry::new().with_hook(
+//!     "audit_bash",
+//!     Arc::new(|input, _tool_use_id, _context| {
+//!         Box::pin(async move {
+//!             println!("{:?}", input);
+//!             HookOutput::default()
+//!         })
+//!     }),
+//! );
+//! let options = ClaudeAgentOptions::from_file_with_hooks("claude-agent.toml", &hooks)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::compaction::AutoCompactPolicy;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{
+    AgentDefinition, ClaudeAgentOptions, Effort, HookCallback, HookEvent, HookMatcher,
+    McpServerConfig, McpServersConfig, PermissionMode, ProxyConfig, SandboxSettings, SdkBeta,
+    SdkPluginConfig, SettingSource, SystemPromptConfig, ThinkingConfig, ToolsConfig,
+};
+
+/// Config file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML (`.toml`).
+    Toml,
+    /// JSON (`.json`).
+    Json,
+    /// YAML (`.yaml`, `.yml`). Requires the `yaml` feature.
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// The format for a file, by extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Named hook callbacks that config files refer to.
+#[derive(Clone, Default)]
+pub struct HookRegistry {
+    hooks: HashMap<String, HookCallback>,
+}
+
+impl HookRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback under `name`.
+    pub fn with_hook(mut self, name: impl Into<String>, callback: HookCallback) -> Self {
+        self.hooks.insert(name.into(), callback);
+        self
+    }
+
+    /// Look up a callback by name.
+    pub fn get(&self, name: &str) -> Option<&HookCallback> {
+        self.hooks.get(name)
+    }
+}
+
+impl fmt::Debug for HookRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.hooks.keys().collect();
+        names.sort();
+        f.debug_struct("HookRegistry")
+            .field("hooks", &names)
+            .finish()
+    }
+}
+
+/// A hook matcher in a config file, with callbacks referenced by name.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookMatcherConfig {
+    /// Pattern to match (e.g., tool name or regex).
+    #[serde(default)]
+    pub matcher: Option<String>,
+    /// Names of callbacks in the [`HookRegistry`].
+    pub hooks: Vec<String>,
+    /// Timeout in seconds.
+    #[serde(default)]
+    pub timeout: Option<f64>,
+}
+
+/// The contents of an options config file.
+///
+/// Relative `cwd` and `add_dirs` paths are resolved against the directory
+/// containing the file. Settings that take code, such as callbacks and
+/// in-process MCP servers, are set on the resulting options instead.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OptionsFile {
+    /// Model to use.
+    pub model: Option<String>,
+    /// Fallback model.
+    pub fallback_model: Option<String>,
+    /// Permission mode, e.g. `"acceptEdits"`.
+    pub permission_mode: Option<PermissionMode>,
+    /// Permission prompt tool name.
+    pub permission_prompt_tool_name: Option<String>,
+    /// Base set of tools.
+    pub tools: Option<ToolsConfig>,
+    /// Tools allowed without prompting.
+    pub allowed_tools: Vec<String>,
+    /// Tools that are not allowed.
+    pub disallowed_tools: Vec<String>,
+    /// System prompt.
+    pub system_prompt: Option<SystemPromptConfig>,
+    /// Maximum conversation turns.
+    pub max_turns: Option<u32>,
+    /// Maximum budget in USD.
+    pub max_budget_usd: Option<f64>,
+    /// Maximum thinking tokens.
+    pub max_thinking_tokens: Option<u32>,
+    /// Extended thinking configuration.
+    pub thinking: Option<ThinkingConfig>,
+    /// Effort level.
+    pub effort: Option<Effort>,
+    /// Working directory.
+    pub cwd: Option<PathBuf>,
+    /// Additional directories.
+    pub add_dirs: Vec<PathBuf>,
+    /// Environment variables.
+    pub env: HashMap<String, String>,
+    /// Start the CLI without this process's environment.
+    pub clear_env: bool,
+    /// HTTP proxy settings.
+    pub proxy: Option<ProxyConfig>,
+    /// Anthropic betas to enable.
+    pub betas: Vec<SdkBeta>,
+    /// CLI feature flags.
+    pub feature_flags: BTreeMap<String, bool>,
+    /// Tags attributing the session's activity.
+    pub metadata: HashMap<String, String>,
+    /// Settings file path or JSON.
+    pub settings: Option<String>,
+    /// Setting sources to load.
+    pub setting_sources: Option<Vec<SettingSource>>,
+    /// Sandbox settings.
+    pub sandbox: Option<SandboxSettings>,
+    /// MCP servers by name. Servers without a `type` are stdio servers.
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+    /// Agent definitions.
+    pub agents: Option<HashMap<String, AgentDefinition>>,
+    /// Plugins to load.
+    pub plugins: Vec<SdkPluginConfig>,
+    /// Hooks by event, with callbacks referenced by name.
+    pub hooks: HashMap<HookEvent, Vec<HookMatcherConfig>>,
+    /// Include partial messages in the stream.
+    pub include_partial_messages: bool,
+    /// Enable file checkpointing.
+    pub enable_file_checkpointing: bool,
+    /// Timeout for CLI operations in seconds.
+    pub timeout_secs: Option<u64>,
+    /// Automatic compaction policy.
+    pub auto_compact: Option<AutoCompactPolicy>,
+}
+
+impl OptionsFile {
+    /// Read a config file, choosing the format by extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            ClaudeSDKError::configuration(format!(
+                "Unknown config format for {}: expected .toml, .json, .yaml, or .yml",
+                path.display()
+            ))
+        })?;
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ClaudeSDKError::configuration(format!(
+                "Failed to read config {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let mut file = Self::parse(&contents, format).map_err(|e| match e {
+            ClaudeSDKError::Configuration { message } => {
+                ClaudeSDKError::configuration(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })?;
+        if let Some(dir) = path.parent() {
+            file.resolve_paths(dir);
+        }
+        Ok(file)
+    }
+
+    /// Parse config file contents.
+    pub fn parse(contents: &str, format: ConfigFormat) -> Result<Self> {
+        let mut value = match format {
+            ConfigFormat::Toml => toml::from_str::<Value>(contents)
+                .map_err(|e| ClaudeSDKError::configuration(format!("Invalid TOML: {}", e)))?,
+            ConfigFormat::Json => serde_json::from_str::<Value>(contents)
+                .map_err(|e| ClaudeSDKError::configuration(format!("Invalid JSON: {}", e)))?,
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::from_str::<Value>(contents)
+                .map_err(|e| ClaudeSDKError::configuration(format!("Invalid YAML: {}", e)))?,
+            #[cfg(not(feature = "yaml"))]
+            ConfigFormat::Yaml => {
+                return Err(ClaudeSDKError::configuration(
+                    "YAML config files require the `yaml` feature",
+                ))
+            }
+        };
+
+        // As in `.mcp.json`, entries without a "type" are stdio servers.
+        if let Some(servers) = value.get_mut("mcp_servers").and_then(Value::as_object_mut) {
+            for server in servers.values_mut().filter_map(Value::as_object_mut) {
+                if !server.contains_key("type") && server.contains_key("command") {
+                    server.insert("type".to_string(), Value::from("stdio"));
+                }
+            }
+        }
+
+        serde_json::from_value(value)
+            .map_err(|e| ClaudeSDKError::configuration(format!("Invalid options: {}", e)))
+    }
+
+    fn resolve_paths(&mut self, dir: &Path) {
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        };
+        if let Some(cwd) = self.cwd.as_mut() {
+            resolve(cwd);
+        }
+        self.add_dirs.iter_mut().for_each(resolve);
+    }
+
+    /// Build options, resolving hook names against `hooks`.
+    ///
+    /// Returns a configuration error naming any hook that is not registered.
+    pub fn into_options(self, hooks: &HookRegistry) -> Result<ClaudeAgentOptions> {
+        let mut resolved: HashMap<HookEvent, Vec<HookMatcher>> = HashMap::new();
+        for (event, matchers) in self.hooks {
+            for matcher in matchers {
+                let callbacks = matcher
+                    .hooks
+                    .iter()
+                    .map(|name| {
+                        hooks.get(name).cloned().ok_or_else(|| {
+                            ClaudeSDKError::configuration(format!(
+                                "Hook '{}' for {:?} is not registered",
+                                name, event
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                resolved.entry(event).or_default().push(HookMatcher {
+                    matcher: matcher.matcher,
+                    hooks: callbacks,
+                    timeout: matcher.timeout,
+                });
+            }
+        }
+
+        let mut options = ClaudeAgentOptions::new();
+        options.model = self.model;
+        options.fallback_model = self.fallback_model;
+        options.permission_mode = self.permission_mode;
+        options.permission_prompt_tool_name = self.permission_prompt_tool_name;
+        options.tools = self.tools;
+        options.allowed_tools = self.allowed_tools;
+        options.disallowed_tools = self.disallowed_tools;
+        options.system_prompt = self.system_prompt;
+        options.max_turns = self.max_turns;
+        options.max_budget_usd = self.max_budget_usd;
+        options.max_thinking_tokens = self.max_thinking_tokens;
+        options.thinking = self.thinking;
+        options.effort = self.effort;
+        options.cwd = self.cwd;
+        options.add_dirs = self.add_dirs;
+        options.env = self.env;
+        options.clear_env = self.clear_env;
+        options.proxy = self.proxy;
+        options.betas = self.betas;
+        options.feature_flags = self.feature_flags;
+        options.metadata = self.metadata;
+        options.settings = self.settings;
+        options.setting_sources = self.setting_sources;
+        options.sandbox = self.sandbox;
+        options.mcp_servers = McpServersConfig::Map(self.mcp_servers);
+        options.agents = self.agents;
+        options.plugins = self.plugins;
+        options.hooks = (!resolved.is_empty()).then_some(resolved);
+        options.include_partial_messages = self.include_partial_messages;
+        options.enable_file_checkpointing = self.enable_file_checkpointing;
+        options.timeout_secs = self.timeout_secs;
+        options.auto_compact = self.auto_compact;
+        Ok(options)
+    }
+}
+
+impl ClaudeAgentOptions {
+    /// Load options from a TOML, JSON, or YAML file.
+    ///
+    /// See the [`config`](crate::config) module for the file layout. Returns
+    /// a configuration error if the file refers to hooks; use
+    /// [`from_file_with_hooks`](Self::from_file_with_hooks) for those.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file_with_hooks(path, &HookRegistry::default())
+    }
+
+    /// Load options from a file, resolving hook names against `hooks`.
+    pub fn from_file_with_hooks(path: impl AsRef<Path>, hooks: &HookRegistry) -> Result<Self> {
+        OptionsFile::load(path)?.into_options(hooks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path("a/claude-agent.toml"),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(ConfigFormat::from_path("x.JSON"), Some(ConfigFormat::Json));
+        assert_eq!(ConfigFormat::from_path("x.yml"), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_path("x.ini"), None);
+        assert_eq!(ConfigFormat::from_path("toml"), None);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let err = OptionsFile::parse("modle = \"x\"", ConfigFormat::Toml).unwrap_err();
+        assert!(err.to_string().contains("unknown field `modle`"), "{}", err);
+    }
+
+    #[test]
+    fn test_relative_paths_resolve_against_file() {
+        let mut file = OptionsFile::parse(
+            r#"{"cwd": "work", "add_dirs": ["/abs", "rel"]}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        file.resolve_paths(Path::new("/etc/agent"));
+        assert_eq!(file.cwd, Some(PathBuf::from("/etc/agent/work")));
+        assert_eq!(
+            file.add_dirs,
+            vec![PathBuf::from("/abs"), PathBuf::from("/etc/agent/rel")]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Control protocol messages.
+//!
+//! Besides conversation messages, the SDK and CLI exchange control requests
+//! over stream-json, each answered by a control response with the same
+//! `request_id`:
+//!
+//! ```json
+//! {"type": "control_request", "request_id": "req_1", "request": {"subtype": "interrupt"}}
+//! {"type": "control_response", "response": {"subtype": "success", "request_id": "req_1"}}
+//! ```
+//!
+//! These types are what [`ClaudeClient`](crate::ClaudeClient) sends and
+//! receives, for custom transports and tools that speak the protocol
+//! directly. A [`ControlHandle`] sends them over a connected client's
+//! session.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::PermissionMode;
+
+pub use crate::_internal::query::ControlHandle;
+
+/// Control request subtypes.
+///
+/// Requests flow both ways: the SDK sends `initialize`, `interrupt`, and the
+/// setters; the CLI sends `can_use_tool`, `hook_callback`, and
+/// `mcp_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "subtype")]
+pub enum ControlRequestPayload {
+    /// Interrupt request.
+    #[serde(rename = "interrupt")]
+    Interrupt,
+    /// Tool permission request.
+    #[serde(rename = "can_use_tool")]
+    CanUseTool {
+        /// Tool name.
+        tool_name: String,
+        /// Tool input.
+        input: serde_json::Value,
+        /// Permission suggestions.
+        permission_suggestions: Option<Vec<serde_json::Value>>,
+        /// Blocked path.
+        blocked_path: Option<String>,
+    },
+    /// Initialize request.
+    #[serde(rename = "initialize")]
+    Initialize {
+        /// Hook configurations.
+        hooks: Option<serde_json::Value>,
+        /// Agent definitions.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        agents: Option<serde_json::Value>,
+    },
+    /// Set permission mode request.
+    #[serde(rename = "set_permission_mode")]
+    SetPermissionMode {
+        /// New mode.
+        mode: PermissionMode,
+    },
+    /// Set model request.
+    #[serde(rename = "set_model")]
+    SetModel {
+        /// New model, or `None` for the default.
+        model: Option<String>,
+    },
+    /// Set thinking budget request.
+    #[serde(rename = "set_max_thinking_tokens")]
+    SetMaxThinkingTokens {
+        /// New budget, or `None` for the default.
+        max_thinking_tokens: Option<u32>,
+    },
+    /// Hook callback request.
+    #[serde(rename = "hook_callback")]
+    HookCallback {
+        /// Callback ID.
+        callback_id: String,
+        /// Hook input.
+        input: serde_json::Value,
+        /// Tool use ID.
+        tool_use_id: Option<String>,
+    },
+    /// MCP message request.
+    #[serde(rename = "mcp_message")]
+    McpMessage {
+        /// Server name.
+        server_name: String,
+        /// JSONRPC message.
+        message: serde_json::Value,
+    },
+    /// MCP status request.
+    #[serde(rename = "mcp_status")]
+    McpStatus,
+    /// Rewind files request.
+    #[serde(rename = "rewind_files")]
+    RewindFiles {
+        /// User message ID to rewind to.
+        user_message_id: String,
+    },
+    /// A request with a subtype this SDK does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ControlRequestPayload {
+    /// The `subtype` tag of this request.
+    pub fn subtype(&self) -> &'static str {
+        match self {
+            Self::Interrupt => "interrupt",
+            Self::CanUseTool { .. } => "can_use_tool",
+            Self::Initialize { .. } => "initialize",
+            Self::SetPermissionMode { .. } => "set_permission_mode",
+            Self::SetModel { .. } => "set_model",
+            Self::SetMaxThinkingTokens { .. } => "set_max_thinking_tokens",
+            Self::HookCallback { .. } => "hook_callback",
+            Self::McpMessage { .. } => "mcp_message",
+            Self::McpStatus => "mcp_status",
+            Self::RewindFiles { .. } => "rewind_files",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Control request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlRequest {
+    /// Always "control_request".
+    #[serde(rename = "type")]
+    pub request_type: String,
+    /// Request ID.
+    pub request_id: String,
+    /// Request payload.
+    pub request: ControlRequestPayload,
+}
+
+impl ControlRequest {
+    /// Create a request.
+    pub fn new(request_id: impl Into<String>, request: ControlRequestPayload) -> Self {
+        Self {
+            request_type: "control_request".to_string(),
+            request_id: request_id.into(),
+            request,
+        }
+    }
+}
+
+/// Success response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlSuccessResponse {
+    /// Always "success".
+    pub subtype: String,
+    /// Request ID.
+    pub request_id: String,
+    /// Response data.
+    pub response: Option<serde_json::Value>,
+}
+
+/// Error response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlErrorResponse {
+    /// Always "error".
+    pub subtype: String,
+    /// Request ID.
+    pub request_id: String,
+    /// Error message.
+    pub error: String,
+}
+
+/// Control response payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "subtype")]
+pub enum ControlResponsePayload {
+    /// Success response.
+    #[serde(rename = "success")]
+    Success {
+        /// Request ID.
+        request_id: String,
+        /// Response data.
+        response: Option<serde_json::Value>,
+    },
+    /// Error response.
+    #[serde(rename = "error")]
+    Error {
+        /// Request ID.
+        request_id: String,
+        /// Error message.
+        error: String,
+    },
+}
+
+/// Control response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    /// Always "control_response".
+    #[serde(rename = "type")]
+    pub response_type: String,
+    /// Response payload.
+    pub response: ControlResponsePayload,
+}
+
+impl ControlResponse {
+    /// A success response to the request with `request_id`.
+    pub fn success(request_id: impl Into<String>, data: serde_json::Value) -> Self {
+        Self::from_payload(ControlResponsePayload::Success {
+            request_id: request_id.into(),
+            response: Some(data),
+        })
+    }
+
+    /// An error response to the request with `request_id`.
+    pub fn failure(request_id: impl Into<String>, error: impl Into<String>) -> Self {
+        Self::from_payload(ControlResponsePayload::Error {
+            request_id: request_id.into(),
+            error: error.into(),
+        })
+    }
+
+    fn from_payload(response: ControlResponsePayload) -> Self {
+        Self {
+            response_type: "control_response".to_string(),
+            response,
+        }
+    }
+
+    /// Get the request ID.
+    pub fn request_id(&self) -> &str {
+        match &self.response {
+            ControlResponsePayload::Success { request_id, .. } => request_id,
+            ControlResponsePayload::Error { request_id, .. } => request_id,
+        }
+    }
+
+    /// Check if this is a success response.
+    pub fn is_success(&self) -> bool {
+        matches!(&self.response, ControlResponsePayload::Success { .. })
+    }
+
+    /// Get the response data if successful.
+    pub fn data(&self) -> Option<&serde_json::Value> {
+        match &self.response {
+            ControlResponsePayload::Success { response, .. } => response.as_ref(),
+            ControlResponsePayload::Error { .. } => None,
+        }
+    }
+
+    /// Get the error message if failed.
+    pub fn error(&self) -> Option<&str> {
+        match &self.response {
+            ControlResponsePayload::Success { .. } => None,
+            ControlResponsePayload::Error { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = ControlRequest::new(
+            "req_1",
+            ControlRequestPayload::SetPermissionMode {
+                mode: PermissionMode::AcceptEdits,
+            },
+        );
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "type": "control_request",
+                "request_id": "req_1",
+                "request": {"subtype": "set_permission_mode", "mode": "acceptEdits"}
+            })
+        );
+
+        let parsed: ControlRequest = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.request.subtype(), "set_permission_mode");
+    }
+
+    #[test]
+    fn test_unknown_request_subtype() {
+        let parsed: ControlRequest = serde_json::from_value(serde_json::json!({
+            "type": "control_request",
+            "request_id": "req_2",
+            "request": {"subtype": "elicitation", "message": "Pick one"}
+        }))
+        .unwrap();
+        assert!(matches!(parsed.request, ControlRequestPayload::Unknown));
+    }
+
+    #[test]
+    fn test_response_constructors() {
+        let ok = ControlResponse::success("req_1", serde_json::json!({"ok": true}));
+        assert!(ok.is_success());
+        assert_eq!(ok.request_id(), "req_1");
+        assert_eq!(
+            serde_json::to_value(&ok).unwrap()["response"]["subtype"],
+            "success"
+        );
+
+        let err = ControlResponse::failure("req_2", "boom");
+        assert_eq!(err.error(), Some("boom"));
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "type": "control_response",
+                "response": {"subtype": "error", "request_id": "req_2", "error": "boom"}
+            })
+        );
+    }
+}
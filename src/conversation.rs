@@ -0,0 +1,407 @@
+//! Collected messages and queries over them.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    AssistantMessage, ContentBlock, Message, ParseWarning, ResultMessage, ToolResultBlock,
+    ToolUseBlock, UserMessageContent,
+};
+
+/// The messages of a run, with queries for analyzing them afterwards.
+///
+/// Dereferences to `Vec<Message>`, and converts from one or from an
+/// iterator of messages.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{query_all, Conversation};
+///
+/// # async fn example() -> claude_agents_sdk::Result<()> {
+/// let conversation = Conversation::from(query_all("Run the tests", None).await?);
+///
+/// for call in conversation.tool_uses_of("Bash") {
+///     println!("ran: {}", call.input["command"]);
+/// }
+/// for error in conversation.errors() {
+///     eprintln!("error: {}", error);
+/// }
+/// println!("{}", conversation.text_transcript());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Conversation {
+    messages: Vec<Message>,
+}
+
+impl Conversation {
+    /// Create an empty conversation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unwrap into the messages.
+    pub fn into_messages(self) -> Vec<Message> {
+        self.messages
+    }
+
+    /// Assistant messages, in order.
+    pub fn assistant_messages(&self) -> Vec<&AssistantMessage> {
+        self.messages
+            .iter()
+            .filter_map(|message| match message.typed() {
+                Message::Assistant(assistant) => Some(assistant),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Result messages, one per completed turn.
+    pub fn results(&self) -> Vec<&ResultMessage> {
+        self.messages
+            .iter()
+            .filter_map(Message::as_result)
+            .collect()
+    }
+
+    /// The last result message.
+    pub fn result(&self) -> Option<&ResultMessage> {
+        self.messages.iter().rev().find_map(Message::as_result)
+    }
+
+    /// Every tool call, in order.
+    pub fn tool_uses(&self) -> Vec<&ToolUseBlock> {
+        self.blocks()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse(tool_use) => Some(tool_use),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Calls of the tool `name`, in order.
+    pub fn tool_uses_of(&self, name: &str) -> Vec<&ToolUseBlock> {
+        self.tool_uses()
+            .into_iter()
+            .filter(|tool_use| tool_use.name == name)
+            .collect()
+    }
+
+    /// Every tool result, in order.
+    pub fn tool_results(&self) -> Vec<&ToolResultBlock> {
+        self.blocks()
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult(result) => Some(result),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The result of the tool call `tool_use_id`.
+    pub fn tool_result_for(&self, tool_use_id: &str) -> Option<&ToolResultBlock> {
+        self.tool_results()
+            .into_iter()
+            .find(|result| result.tool_use_id == tool_use_id)
+    }
+
+    /// Tool calls paired with their results; the result is `None` for a call
+    /// that never got one, such as one that was interrupted.
+    pub fn tool_calls(&self) -> Vec<(&ToolUseBlock, Option<&ToolResultBlock>)> {
+        let results = self.tool_results();
+        self.tool_uses()
+            .into_iter()
+            .map(|tool_use| {
+                let result = results
+                    .iter()
+                    .find(|result| result.tool_use_id == tool_use.id)
+                    .copied();
+                (tool_use, result)
+            })
+            .collect()
+    }
+
+    /// Everything that went wrong, in order: API errors on assistant
+    /// messages, failed tool calls, error results, and skipped output.
+    pub fn errors(&self) -> Vec<ConversationError<'_>> {
+        let mut errors = Vec::new();
+        for message in self.messages.iter().map(Message::typed) {
+            match message {
+                Message::Assistant(assistant) if assistant.error.is_some() => {
+                    errors.push(ConversationError::Assistant(assistant));
+                }
+                Message::Result(result) if result.is_error => {
+                    errors.push(ConversationError::Result(result));
+                }
+                Message::ParseWarning(warning) => {
+                    errors.push(ConversationError::ParseWarning(warning));
+                }
+                _ => {}
+            }
+            errors.extend(
+                message_blocks(message)
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::ToolResult(result) if result.is_error == Some(true) => {
+                            Some(ConversationError::Tool(result))
+                        }
+                        _ => None,
+                    }),
+            );
+        }
+        errors
+    }
+
+    /// A plain-text transcript: prompts, assistant text, tool calls, and
+    /// tool results, one entry per paragraph. Thinking is left out.
+    pub fn text_transcript(&self) -> String {
+        let mut entries = Vec::new();
+        for message in self.messages.iter().map(Message::typed) {
+            match message {
+                Message::User(user) => match &user.content {
+                    UserMessageContent::Text(text) => entries.push(format!("User: {}", text)),
+                    UserMessageContent::Blocks(blocks) => {
+                        entries.extend(blocks.iter().filter_map(transcript_entry))
+                    }
+                },
+                Message::Assistant(assistant) => {
+                    entries.extend(assistant.content.iter().filter_map(transcript_entry))
+                }
+                _ => {}
+            }
+        }
+        entries.join("\n\n")
+    }
+
+    fn blocks(&self) -> impl Iterator<Item = &ContentBlock> {
+        self.messages
+            .iter()
+            .flat_map(|message| message_blocks(message.typed()))
+    }
+}
+
+fn message_blocks(message: &Message) -> &[ContentBlock] {
+    match message {
+        Message::Assistant(assistant) => &assistant.content,
+        Message::User(user) => match &user.content {
+            UserMessageContent::Blocks(blocks) => blocks,
+            UserMessageContent::Text(_) => &[],
+        },
+        _ => &[],
+    }
+}
+
+fn transcript_entry(block: &ContentBlock) -> Option<String> {
+    match block {
+        ContentBlock::Text(text) => Some(format!("Assistant: {}", text.text)),
+        ContentBlock::Thinking(_) => None,
+        ContentBlock::ToolUse(_) => Some(format!("Tool call: {}", block)),
+        ContentBlock::ToolResult(result) if result.is_error == Some(true) => {
+            Some(format!("Tool error: {}", result.text()))
+        }
+        ContentBlock::ToolResult(result) => Some(format!("Tool result: {}", result.text())),
+        ContentBlock::LargePayload(_) => Some(block.to_string()),
+    }
+}
+
+/// Something that went wrong in a [`Conversation`].
+#[derive(Debug, Clone, Copy)]
+pub enum ConversationError<'a> {
+    /// An assistant message with an API error, such as a rate limit.
+    Assistant(&'a AssistantMessage),
+    /// A tool result marked as an error.
+    Tool(&'a ToolResultBlock),
+    /// A result reporting that the turn failed.
+    Result(&'a ResultMessage),
+    /// Output that could not be parsed.
+    ParseWarning(&'a ParseWarning),
+}
+
+impl fmt::Display for ConversationError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Assistant(assistant) => match assistant.error {
+                Some(error) => write!(f, "API error: {:?}", error),
+                None => f.write_str("API error"),
+            },
+            Self::Tool(result) => {
+                write!(f, "tool {} failed: {}", result.tool_use_id, result.text())
+            }
+            Self::Result(result) => match &result.result {
+                Some(text) => write!(f, "turn failed ({}): {}", result.subtype, text),
+                None => write!(f, "turn failed ({})", result.subtype),
+            },
+            Self::ParseWarning(warning) => write!(f, "unparsable output: {}", warning.message),
+        }
+    }
+}
+
+impl Deref for Conversation {
+    type Target = Vec<Message>;
+
+    fn deref(&self) -> &Vec<Message> {
+        &self.messages
+    }
+}
+
+impl DerefMut for Conversation {
+    fn deref_mut(&mut self) -> &mut Vec<Message> {
+        &mut self.messages
+    }
+}
+
+impl From<Vec<Message>> for Conversation {
+    fn from(messages: Vec<Message>) -> Self {
+        Self { messages }
+    }
+}
+
+impl From<Conversation> for Vec<Message> {
+    fn from(conversation: Conversation) -> Self {
+        conversation.messages
+    }
+}
+
+impl FromIterator<Message> for Conversation {
+    fn from_iter<I: IntoIterator<Item = Message>>(iter: I) -> Self {
+        Self {
+            messages: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Message> for Conversation {
+    fn extend<I: IntoIterator<Item = Message>>(&mut self, iter: I) {
+        self.messages.extend(iter);
+    }
+}
+
+impl IntoIterator for Conversation {
+    type Item = Message;
+    type IntoIter = std::vec::IntoIter<Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Conversation {
+    type Item = &'a Message;
+    type IntoIter = std::slice::Iter<'a, Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use serde_json::json;
+
+    fn conversation() -> Conversation {
+        [
+            json!({"type": "user", "message": {"content": "List and build"}}),
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "thinking", "thinking": "Two commands.", "signature": "s"},
+                {"type": "text", "text": "Running both."},
+                {"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "ls"}},
+                {"type": "tool_use", "id": "tu_2", "name": "Bash", "input": {"command": "make"}},
+                {"type": "tool_use", "id": "tu_3", "name": "Read", "input": {"file_path": "a"}}
+            ]}}),
+            json!({"type": "user", "message": {"content": [
+                {"type": "tool_result", "tool_use_id": "tu_1", "content": "Makefile"},
+                {"type": "tool_result", "tool_use_id": "tu_2", "content": "make: *** failed", "is_error": true}
+            ]}}),
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "text", "text": "The build failed."}
+            ]}, "error": "rate_limit"}),
+            json!({
+                "type": "result", "subtype": "error_during_execution", "duration_ms": 1,
+                "duration_api_ms": 1, "is_error": true, "num_turns": 2, "session_id": "s"
+            }),
+        ]
+        .into_iter()
+        .map(|raw| parse_message(raw).unwrap().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_tool_queries() {
+        let conversation = conversation();
+        assert_eq!(conversation.assistant_messages().len(), 2);
+        assert_eq!(conversation.tool_uses().len(), 3);
+
+        let bash: Vec<_> = conversation
+            .tool_uses_of("Bash")
+            .iter()
+            .map(|call| call.input["command"].as_str().unwrap())
+            .collect();
+        assert_eq!(bash, vec!["ls", "make"]);
+
+        assert_eq!(conversation.tool_results().len(), 2);
+        assert_eq!(
+            conversation.tool_result_for("tu_1").unwrap().text(),
+            "Makefile"
+        );
+        let pending: Vec<_> = conversation
+            .tool_calls()
+            .into_iter()
+            .filter(|(_, result)| result.is_none())
+            .map(|(call, _)| call.id.as_str())
+            .collect();
+        assert_eq!(pending, vec!["tu_3"]);
+        assert_eq!(conversation.result().unwrap().num_turns, 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let conversation = conversation();
+        let errors: Vec<String> = conversation
+            .errors()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "tool tu_2 failed: make: *** failed",
+                "API error: RateLimit",
+                "turn failed (error_during_execution)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_transcript() {
+        assert_eq!(
+            conversation().text_transcript(),
+            "User: List and build\n\n\
+             Assistant: Running both.\n\n\
+             Tool call: Bash({\"command\":\"ls\"})\n\n\
+             Tool call: Bash({\"command\":\"make\"})\n\n\
+             Tool call: Read({\"file_path\":\"a\"})\n\n\
+             Tool result: Makefile\n\n\
+             Tool error: make: *** failed\n\n\
+             Assistant: The build failed."
+        );
+    }
+
+    #[test]
+    fn test_vec_like() {
+        let mut conversation = Conversation::new();
+        conversation.extend(self::conversation());
+        assert_eq!(conversation.len(), 5);
+        conversation.truncate(1);
+        assert_eq!((&conversation).into_iter().count(), 1);
+        let json = serde_json::to_value(&conversation).unwrap();
+        assert!(json.is_array());
+        let messages: Vec<Message> = conversation.into();
+        assert_eq!(messages.len(), 1);
+    }
+}
@@ -0,0 +1,318 @@
+//! Diffs of the files the model edits.
+//!
+//! With [`file_diffs`](crate::ClaudeAgentOptions::file_diffs) set, the SDK
+//! registers a `PostToolUse` hook for the `Edit`, `MultiEdit`, and `Write`
+//! tools and turns the patch each reports into a [`FileDiff`] on the
+//! [event bus](crate::EventBus), so a review UI can show what changed
+//! without reading the files.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::{ClaudeAgentOptions, ClaudeClient, EventBus, SdkEvent};
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let bus = EventBus::default();
+//! let mut events = bus.subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         if let SdkEvent::FileDiff(diff) = event {
+//!             println!("{}", diff.unified_diff);
+//!         }
+//!     }
+//! });
+//!
+//! let options = ClaudeAgentOptions::new()
+//!     .with_event_bus(bus)
+//!     .with_file_diffs(true);
+//! let mut client = ClaudeClient::new(Some(options));
+//! client.connect().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::events::{EventTracker, SdkEvent};
+use crate::types::{HookInput, HookMatcher, HookOutput};
+
+/// Tools whose changes are reported as diffs.
+const DIFF_TOOLS: &str = "Edit|MultiEdit|Write";
+
+/// Unchanged lines shown around a change the SDK diffs itself.
+const CONTEXT_LINES: usize = 3;
+
+/// A change a tool made to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// ID of the tool call that made the change.
+    pub tool_use_id: Option<String>,
+    /// The tool that made the change, e.g. `Edit`.
+    pub tool_name: String,
+    /// The file changed.
+    pub path: PathBuf,
+    /// [`content_hash`] of the file before the change, or `None` if the
+    /// change created it.
+    pub before_hash: Option<String>,
+    /// The change as a unified diff.
+    pub unified_diff: String,
+}
+
+impl FileDiff {
+    /// Build the diff from a `PostToolUse` hook's tool input and response.
+    ///
+    /// Uses the patch the CLI reports, or else diffs the file's content
+    /// before and after. Returns `None` if the response doesn't say what
+    /// changed, or nothing did.
+    pub(crate) fn from_tool(
+        tool_name: &str,
+        tool_use_id: Option<String>,
+        input: &Value,
+        response: &Value,
+    ) -> Option<Self> {
+        let path = str_field(response, "filePath").or_else(|| str_field(input, "file_path"))?;
+        let created = str_field(response, "type") == Some("create");
+        let before = str_field(response, "originalFile")
+            .or_else(|| str_field(response, "originalFileContents"))
+            .filter(|_| !created);
+
+        let mut hunks = String::new();
+        for hunk in response
+            .get("structuredPatch")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let field = |key| hunk.get(key).and_then(Value::as_u64).unwrap_or(0);
+            let _ = writeln!(
+                hunks,
+                "@@ -{},{} +{},{} @@",
+                field("oldStart"),
+                field("oldLines"),
+                field("newStart"),
+                field("newLines")
+            );
+            for line in hunk
+                .get("lines")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                hunks.push_str(line.as_str().unwrap_or_default());
+                hunks.push('\n');
+            }
+        }
+        if hunks.is_empty() {
+            let after = match tool_name {
+                "Write" => str_field(response, "content")
+                    .or_else(|| str_field(input, "content"))?
+                    .to_string(),
+                "Edit" => {
+                    let old = str_field(input, "old_string")?;
+                    let new = str_field(input, "new_string")?;
+                    if input.get("replace_all").and_then(Value::as_bool) == Some(true) {
+                        before?.replace(old, new)
+                    } else {
+                        before?.replacen(old, new, 1)
+                    }
+                }
+                _ => return None,
+            };
+            // Without the content before, only a new file can be diffed.
+            if before.is_none() && !created {
+                return None;
+            }
+            hunks = diff_lines(before.unwrap_or_default(), &after)?;
+        }
+
+        let from = if created { "/dev/null" } else { path };
+        Some(Self {
+            tool_use_id,
+            tool_name: tool_name.to_string(),
+            path: PathBuf::from(path),
+            before_hash: before.map(content_hash),
+            unified_diff: format!("--- {}\n+++ {}\n{}", from, path, hunks),
+        })
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+/// Hash of a file's content, as in [`FileDiff::before_hash`]: the 64-bit
+/// FNV-1a hash of its bytes, as 16 hex digits.
+pub fn content_hash(content: &str) -> String {
+    let hash = content
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// One hunk covering the lines that differ between `before` and `after`,
+/// or `None` if they're the same.
+fn diff_lines(before: &str, after: &str) -> Option<String> {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    if prefix == old.len() && prefix == new.len() {
+        return None;
+    }
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_changed, new_changed) = (old.len() - suffix, new.len() - suffix);
+    let start = prefix.saturating_sub(CONTEXT_LINES);
+    let trailing = suffix.min(CONTEXT_LINES);
+    let range = |changed: usize| {
+        let count = changed + trailing - start;
+        // A range of no lines starts at the line before it.
+        let first = if count == 0 { start } else { start + 1 };
+        format!("{},{}", first, count)
+    };
+
+    let mut hunk = format!("@@ -{} +{} @@\n", range(old_changed), range(new_changed));
+    let mut push = |sign: char, lines: &[&str]| {
+        for line in lines {
+            hunk.push(sign);
+            hunk.push_str(line);
+            hunk.push('\n');
+        }
+    };
+    push(' ', &old[start..prefix]);
+    push('-', &old[prefix..old_changed]);
+    push('+', &new[prefix..new_changed]);
+    push(' ', &old[old_changed..old_changed + trailing]);
+    Some(hunk)
+}
+
+/// The hook emitting a [`SdkEvent::FileDiff`] after each file-editing tool
+/// call.
+pub(crate) fn hook(events: EventTracker) -> HookMatcher {
+    HookMatcher::new(DIFF_TOOLS).with_callback(move |input, tool_use_id, _context| {
+        if let HookInput::PostToolUse(input) = input {
+            let tool_use_id = tool_use_id
+                .or_else(|| (!input.tool_use_id.is_empty()).then(|| input.tool_use_id.clone()));
+            if let Some(diff) = FileDiff::from_tool(
+                &input.tool_name,
+                tool_use_id,
+                &input.tool_input,
+                &input.tool_response,
+            ) {
+                events.emit(SdkEvent::FileDiff(diff));
+            }
+        }
+        std::future::ready(HookOutput::default())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_edit_uses_reported_patch() {
+        let diff = FileDiff::from_tool(
+            "Edit",
+            Some("tu_1".to_string()),
+            &json!({"file_path": "/repo/src/lib.rs", "old_string": "b", "new_string": "c"}),
+            &json!({
+                "filePath": "/repo/src/lib.rs",
+                "originalFile": "a\nb\n",
+                "structuredPatch": [
+                    {"oldStart": 1, "oldLines": 2, "newStart": 1, "newLines": 2,
+                     "lines": [" a", "-b", "+c"]}
+                ]
+            }),
+        )
+        .unwrap();
+        assert_eq!(diff.path, PathBuf::from("/repo/src/lib.rs"));
+        assert_eq!(diff.before_hash, Some(content_hash("a\nb\n")));
+        assert_eq!(
+            diff.unified_diff,
+            "--- /repo/src/lib.rs\n+++ /repo/src/lib.rs\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+    }
+
+    #[test]
+    fn test_diffs_content_without_patch() {
+        let before: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+        let diff = FileDiff::from_tool(
+            "Edit",
+            None,
+            &json!({"file_path": "f.txt", "old_string": "line 5", "new_string": "five"}),
+            &json!({"filePath": "f.txt", "originalFile": before}),
+        )
+        .unwrap();
+        assert_eq!(
+            diff.unified_diff,
+            "--- f.txt\n+++ f.txt\n@@ -2,7 +2,7 @@\n line 2\n line 3\n line 4\n\
+             -line 5\n+five\n line 6\n line 7\n line 8\n"
+        );
+
+        let created = FileDiff::from_tool(
+            "Write",
+            None,
+            &json!({"file_path": "new.txt", "content": "hello\n"}),
+            &json!({"type": "create", "filePath": "new.txt", "content": "hello\n"}),
+        )
+        .unwrap();
+        assert!(created.before_hash.is_none());
+        assert_eq!(
+            created.unified_diff,
+            "--- /dev/null\n+++ new.txt\n@@ -0,0 +1,1 @@\n+hello\n"
+        );
+
+        // Rewriting a file with the same content changes nothing.
+        assert!(FileDiff::from_tool(
+            "Write",
+            None,
+            &json!({"file_path": "same.txt", "content": "x\n"}),
+            &json!({"type": "update", "filePath": "same.txt", "content": "x\n", "originalFile": "x\n"}),
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hook_emits_file_diff() {
+        let bus = crate::EventBus::default();
+        let mut rx = bus.subscribe();
+        let matcher = hook(EventTracker::new(Some(bus)));
+        assert_eq!(matcher.matcher.as_deref(), Some(DIFF_TOOLS));
+
+        let input: HookInput = serde_json::from_value(json!({
+            "hook_event_name": "PostToolUse",
+            "session_id": "s", "transcript_path": "/tmp/t", "cwd": "/repo",
+            "tool_name": "Write",
+            "tool_input": {"file_path": "/repo/notes.md", "content": "hi\n"},
+            "tool_response": {"type": "create", "filePath": "/repo/notes.md", "content": "hi\n"},
+            "tool_use_id": "tu_7"
+        }))
+        .unwrap();
+        (matcher.hooks[0])(input, None, Default::default()).await;
+
+        match rx.try_recv().unwrap() {
+            SdkEvent::FileDiff(diff) => {
+                assert_eq!(diff.tool_use_id.as_deref(), Some("tu_7"));
+                assert_eq!(diff.tool_name, "Write");
+                assert!(diff.unified_diff.ends_with("+hi\n"));
+            }
+            other => panic!("Expected FileDiff, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_content_hash_is_fnv1a() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
+}
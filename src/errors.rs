@@ -3,9 +3,272 @@
 //! This module provides a comprehensive error hierarchy for handling various
 //! failure modes when interacting with the Claude Code CLI.
 
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+/// Claude Code documentation, linked from diagnostics about the CLI itself.
+const CLAUDE_CODE_DOCS: &str = "https://docs.anthropic.com/en/docs/claude-code";
+
+/// Broad category of a [`ClaudeSDKError`], for retry decisions and metrics.
+///
+/// Discriminants are stable across releases and can be stored or exported as
+/// numbers; [`as_str`](Self::as_str) gives a stable label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+#[repr(u16)]
+pub enum ErrorKind {
+    /// The CLI is not installed or not at the configured path.
+    CliNotFound = 1,
+    /// The connection to the CLI broke.
+    ConnectionLost = 2,
+    /// An operation timed out.
+    Timeout = 3,
+    /// The API rejected a request because of rate limits or overload.
+    RateLimited = 4,
+    /// The session's budget was exhausted.
+    BudgetExceeded = 5,
+    /// The CLI sent something the SDK could not understand or rejected a
+    /// control request.
+    Protocol = 6,
+    /// The options were invalid.
+    Configuration = 7,
+    /// The CLI is older than the SDK supports.
+    VersionMismatch = 8,
+    /// The CLI failed before the session started.
+    ProcessFailed = 9,
+    /// The CLI terminated unexpectedly during a session.
+    ProcessExited = 10,
+    /// The operation was interrupted.
+    Interrupted = 11,
+    /// A local I/O operation failed.
+    Io = 12,
+    /// A bug in the SDK.
+    Internal = 13,
+}
+
+impl ErrorKind {
+    /// Stable numeric code.
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// Stable snake_case label, e.g. for metric labels.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CliNotFound => "cli_not_found",
+            Self::ConnectionLost => "connection_lost",
+            Self::Timeout => "timeout",
+            Self::RateLimited => "rate_limited",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::Protocol => "protocol",
+            Self::Configuration => "configuration",
+            Self::VersionMismatch => "version_mismatch",
+            Self::ProcessFailed => "process_failed",
+            Self::ProcessExited => "process_exited",
+            Self::Interrupted => "interrupted",
+            Self::Io => "io",
+            Self::Internal => "internal",
+        }
+    }
+
+    /// Whether an operation failing with this kind might succeed if retried.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::ConnectionLost | Self::Timeout | Self::RateLimited | Self::ProcessExited
+        )
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A structured description of an error for display in a user interface.
+///
+/// Produced by [`ClaudeSDKError::to_diagnostic`]. Serializes to JSON so it can
+/// be sent to web frontends as-is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// Stable machine-readable code, e.g. `cli_not_found`.
+    pub code: String,
+    /// Short summary suitable for a heading.
+    pub title: String,
+    /// The full error message.
+    pub detail: String,
+    /// What the user can do about it, if anything.
+    pub hint: Option<String>,
+    /// Whether retrying the operation might succeed.
+    pub retryable: bool,
+    /// Where to read more.
+    pub docs_url: Option<String>,
+}
+
+/// What the API said about a rate limit it applied.
+///
+/// Read from the CLI's rate limit errors, so a retry can wait as long as
+/// the API asked instead of guessing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// How long to wait before retrying, if the API said.
+    pub retry_after: Option<Duration>,
+    /// When the limit resets, if the API gave a time instead of a wait.
+    /// See [`retry_after_at`](Self::retry_after_at).
+    pub resets_at: Option<SystemTime>,
+    /// The limit that was hit, as the API names it, e.g.
+    /// `rate_limit_error`, `overloaded_error`, or for a subscription's
+    /// usage limit, `usage_limit` or the window such as `five_hour`.
+    pub limit_type: Option<String>,
+}
+
+impl RateLimitInfo {
+    /// How long to wait before retrying, as of `now`: the wait the API
+    /// gave, or else the time left until the limit resets.
+    pub fn retry_after_at(&self, now: SystemTime) -> Option<Duration> {
+        self.retry_after.or_else(|| {
+            self.resets_at
+                .map(|at| at.duration_since(now).unwrap_or_default())
+        })
+    }
+
+    /// Read rate limit details from a CLI payload and the error text it
+    /// carries. Fields of the payload win over what the text says.
+    pub(crate) fn parse(raw: &Value, text: &str) -> Self {
+        let mut info = Self::from_text(text);
+        let objects = [
+            Some(raw),
+            raw.get("rate_limit_info"),
+            raw.get("rateLimitInfo"),
+            raw.get("error"),
+        ];
+        for object in objects.into_iter().flatten().filter(|v| v.is_object()) {
+            if let Some(wait) = wait_field(object) {
+                info.set_wait(wait);
+            }
+            if let Some(limit_type) = ["rateLimitType", "rate_limit_type", "limit_type"]
+                .iter()
+                .find_map(|key| object.get(key).and_then(Value::as_str))
+            {
+                info.limit_type = Some(limit_type.to_string());
+            }
+        }
+        info
+    }
+
+    /// Read rate limit details from error text, such as the CLI's
+    /// `API Error: 429 {...}` or `Claude AI usage limit reached|<reset>`.
+    pub(crate) fn from_text(text: &str) -> Self {
+        let mut info = Self::default();
+        // The CLI reports API errors followed by the API's JSON body.
+        if let Some(body) = text.find('{').and_then(|start| {
+            serde_json::Deserializer::from_str(&text[start..])
+                .into_iter::<Value>()
+                .next()?
+                .ok()
+        }) {
+            info.limit_type = body
+                .pointer("/error/type")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if let Some(wait) = wait_field(&body).or_else(|| body.get("error").and_then(wait_field))
+            {
+                info.set_wait(wait);
+            }
+        }
+        if let Some((_, reset)) = text.split_once("usage limit reached|") {
+            let reset: String = reset.chars().take_while(char::is_ascii_digit).collect();
+            info.limit_type
+                .get_or_insert_with(|| "usage_limit".to_string());
+            if let Some(reset) = reset.parse().ok().and_then(epoch_secs) {
+                info.set_wait(Wait::Until(reset));
+            }
+        }
+        if info.retry_after.is_none() && info.resets_at.is_none() {
+            let lower = text.to_ascii_lowercase();
+            info.retry_after = ["retry-after:", "retry after", "try again in"]
+                .iter()
+                .find_map(|phrase| {
+                    let (_, rest) = lower.split_once(phrase)?;
+                    parse_wait(rest)
+                });
+        }
+        info
+    }
+
+    fn set_wait(&mut self, wait: Wait) {
+        (self.retry_after, self.resets_at) = match wait {
+            Wait::After(duration) => (Some(duration), None),
+            Wait::Until(at) => (None, Some(at)),
+        };
+    }
+}
+
+/// When a rate limit says to retry.
+enum Wait {
+    /// After a delay.
+    After(Duration),
+    /// Once the limit resets.
+    Until(SystemTime),
+}
+
+/// A retry delay given by a payload's fields: seconds to wait, or when the
+/// limit resets.
+fn wait_field(object: &Value) -> Option<Wait> {
+    let seconds = |value: &Value| {
+        value
+            .as_f64()
+            .or_else(|| value.as_str()?.trim().parse().ok())
+            .filter(|secs: &f64| secs.is_finite() && *secs >= 0.0)
+    };
+    if let Some(secs) = ["retry_after", "retryAfter", "retry_after_seconds"]
+        .iter()
+        .find_map(|key| object.get(key).and_then(seconds))
+    {
+        // A wait too long to represent is no hint.
+        return Duration::try_from_secs_f64(secs).ok().map(Wait::After);
+    }
+    ["resetsAt", "resets_at"]
+        .iter()
+        .find_map(|key| object.get(key)?.as_u64())
+        .and_then(epoch_secs)
+        .map(Wait::Until)
+}
+
+/// The time of a Unix timestamp, or `None` if it's too far off to
+/// represent.
+fn epoch_secs(secs: u64) -> Option<SystemTime> {
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
+
+/// A wait like `30`, `30s`, ` 30 seconds`, `5m`, or `2 hours` at the start
+/// of `text`. `None` for a unit it doesn't know.
+fn parse_wait(text: &str) -> Option<Duration> {
+    let text = text.trim_start();
+    let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+    let amount: u64 = digits.parse().ok()?;
+    let unit: String = text[digits.len()..]
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect();
+    let unit_secs = match unit.as_str() {
+        "ms" | "msec" | "msecs" | "millisecond" | "milliseconds" => {
+            return Some(Duration::from_millis(amount))
+        }
+        "" | "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(amount.saturating_mul(unit_secs)))
+}
+
 /// Main error type for the Claude Agents SDK.
 ///
 /// All errors in this SDK derive from this type, making it easy to handle
@@ -40,27 +303,66 @@ pub enum ClaudeSDKError {
         stderr: Option<String>,
     },
 
+    /// The CLI process exited unsuccessfully before the session started, or
+    /// emitted unparsable output before exiting.
+    ///
+    /// Carries the end of the CLI's stderr and the command line, so the failure
+    /// can be diagnosed from the error alone.
+    #[error("{}", process_failed_summary(*exit_code, stderr_tail))]
+    ProcessFailed {
+        /// Exit code, if the process exited normally.
+        exit_code: Option<i32>,
+        /// The last few kilobytes of stderr.
+        stderr_tail: String,
+        /// The CLI path followed by its arguments.
+        argv: Vec<String>,
+    },
+
+    /// The CLI process terminated unexpectedly during a session.
+    ///
+    /// Returned when the process exits while the SDK still has its stdin open,
+    /// or exits unsuccessfully after the session started. An exit after the
+    /// SDK closes stdin or disconnects is not an error.
+    #[error("{}", process_exited_summary(*code, *signal, stderr))]
+    ProcessExited {
+        /// Exit code, if the process exited normally.
+        code: Option<i32>,
+        /// Signal that terminated the process, on Unix.
+        signal: Option<i32>,
+        /// The CLI path followed by its arguments.
+        argv: Vec<String>,
+        /// The last few kilobytes of stderr.
+        stderr: String,
+    },
+
     /// Failed to decode JSON from the CLI.
     #[error("JSON decode error: {message}")]
     JSONDecode {
         /// Error message describing the parse failure
         message: String,
-        /// The raw data that failed to parse
+        /// The raw data that failed to parse, truncated around the error for
+        /// long lines
         raw_data: Option<String>,
         /// The buffer content at time of error
         buffer_content: Option<String>,
+        /// Byte offset of the error in the original line
+        offset: Option<usize>,
+        /// JSON pointer to the value being parsed when the error occurred
+        path: Option<String>,
         /// Underlying serde_json error
         #[source]
         source: Option<serde_json::Error>,
     },
 
     /// Failed to parse a message into the expected type.
-    #[error("Message parse error: {message}")]
+    #[error("Message parse error: {message}{}", path.as_deref().map(|p| format!(" at {}", p)).unwrap_or_default())]
     MessageParse {
         /// Error message
         message: String,
         /// The raw message data that failed to parse
         raw_message: Option<serde_json::Value>,
+        /// JSON pointer to the offending field
+        path: Option<String>,
     },
 
     /// An invalid configuration was provided.
@@ -90,6 +392,17 @@ pub enum ClaudeSDKError {
         duration_ms: u64,
     },
 
+    /// The CLI did not answer a control request in time.
+    #[error("Control request '{request_type}' ({request_id}) timed out after {duration_ms}ms")]
+    ControlRequestTimedOut {
+        /// The request's subtype, e.g. `initialize`.
+        request_type: String,
+        /// The request ID.
+        request_id: String,
+        /// How long the SDK waited, in milliseconds.
+        duration_ms: u64,
+    },
+
     /// The CLI version is too old.
     #[error("CLI version {found} is below minimum required version {required}")]
     VersionMismatch {
@@ -99,6 +412,15 @@ pub enum ClaudeSDKError {
         required: String,
     },
 
+    /// The API rate limited the session.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// Error message
+        message: String,
+        /// What the API said about the limit.
+        info: RateLimitInfo,
+    },
+
     /// An IO error occurred.
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
@@ -164,12 +486,42 @@ impl ClaudeSDKError {
         }
     }
 
+    /// Create a process failure error.
+    pub fn process_failed(
+        exit_code: Option<i32>,
+        stderr_tail: impl Into<String>,
+        argv: Vec<String>,
+    ) -> Self {
+        Self::ProcessFailed {
+            exit_code,
+            stderr_tail: stderr_tail.into(),
+            argv,
+        }
+    }
+
+    /// Create an unexpected process exit error.
+    pub fn process_exited(
+        code: Option<i32>,
+        signal: Option<i32>,
+        argv: Vec<String>,
+        stderr: impl Into<String>,
+    ) -> Self {
+        Self::ProcessExited {
+            code,
+            signal,
+            argv,
+            stderr: stderr.into(),
+        }
+    }
+
     /// Create a JSON decode error.
     pub fn json_decode(message: impl Into<String>) -> Self {
         Self::JSONDecode {
             message: message.into(),
             raw_data: None,
             buffer_content: None,
+            offset: None,
+            path: None,
             source: None,
         }
     }
@@ -185,6 +537,26 @@ impl ClaudeSDKError {
             message: message.into(),
             raw_data,
             buffer_content,
+            offset: None,
+            path: None,
+            source: Some(source),
+        }
+    }
+
+    /// Create a JSON decode error located at a byte offset and JSON pointer.
+    pub fn json_decode_at(
+        message: impl Into<String>,
+        raw_data: impl Into<String>,
+        offset: usize,
+        path: impl Into<String>,
+        source: serde_json::Error,
+    ) -> Self {
+        Self::JSONDecode {
+            message: message.into(),
+            raw_data: Some(raw_data.into()),
+            buffer_content: None,
+            offset: Some(offset),
+            path: Some(path.into()),
             source: Some(source),
         }
     }
@@ -194,6 +566,7 @@ impl ClaudeSDKError {
         Self::MessageParse {
             message: message.into(),
             raw_message: None,
+            path: None,
         }
     }
 
@@ -205,6 +578,20 @@ impl ClaudeSDKError {
         Self::MessageParse {
             message: message.into(),
             raw_message: Some(raw_message),
+            path: None,
+        }
+    }
+
+    /// Create a message parse error for the field at a JSON pointer.
+    pub fn message_parse_at(
+        message: impl Into<String>,
+        path: impl Into<String>,
+        raw_message: serde_json::Value,
+    ) -> Self {
+        Self::MessageParse {
+            message: message.into(),
+            raw_message: Some(raw_message),
+            path: Some(path.into()),
         }
     }
 
@@ -239,6 +626,19 @@ impl ClaudeSDKError {
         Self::Timeout { duration_ms }
     }
 
+    /// Create a control request timeout error.
+    pub fn control_request_timed_out(
+        request_type: impl Into<String>,
+        request_id: impl Into<String>,
+        duration_ms: u64,
+    ) -> Self {
+        Self::ControlRequestTimedOut {
+            request_type: request_type.into(),
+            request_id: request_id.into(),
+            duration_ms,
+        }
+    }
+
     /// Create a version mismatch error.
     pub fn version_mismatch(found: impl Into<String>, required: impl Into<String>) -> Self {
         Self::VersionMismatch {
@@ -261,6 +661,203 @@ impl ClaudeSDKError {
         }
     }
 
+    /// Create a rate limit error.
+    pub fn rate_limited(message: impl Into<String>, info: RateLimitInfo) -> Self {
+        Self::RateLimited {
+            message: message.into(),
+            info,
+        }
+    }
+
+    /// Describe this error for display in a user interface.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let (code, title, hint, docs_url): (&str, &str, Option<String>, Option<&str>) = match self {
+            Self::CLINotFound { .. } => (
+                "cli_not_found",
+                "Claude Code is not installed",
+                Some(
+                    "Install Claude Code with `npm install -g @anthropic-ai/claude-code`, \
+                     or set `cli_path` to its location."
+                        .to_string(),
+                ),
+                Some(CLAUDE_CODE_DOCS),
+            ),
+            Self::CLIConnection { .. } => (
+                "cli_connection",
+                "Lost connection to Claude Code",
+                Some("Check that the CLI runs on its own, then reconnect.".to_string()),
+                None,
+            ),
+            Self::Process { stderr, .. } => (
+                "process_error",
+                "Claude Code exited with an error",
+                stderr
+                    .as_deref()
+                    .and_then(last_line)
+                    .map(|line| format!("The CLI reported: {}", line)),
+                Some(CLAUDE_CODE_DOCS),
+            ),
+            Self::ProcessFailed { stderr_tail, .. } => (
+                "process_failed",
+                "Claude Code exited unexpectedly",
+                last_line(stderr_tail).map(|line| format!("The CLI reported: {}", line)),
+                Some(CLAUDE_CODE_DOCS),
+            ),
+            Self::ProcessExited { stderr, .. } => (
+                "process_exited",
+                "Claude Code stopped unexpectedly",
+                Some(match last_line(stderr) {
+                    Some(line) => format!("The CLI reported: {}", line),
+                    None => "Reconnect to start a new CLI process.".to_string(),
+                }),
+                Some(CLAUDE_CODE_DOCS),
+            ),
+            Self::JSONDecode { .. } => (
+                "json_decode",
+                "Unexpected output from Claude Code",
+                Some("The CLI may be newer or older than this SDK supports.".to_string()),
+                None,
+            ),
+            Self::MessageParse { .. } => (
+                "message_parse",
+                "Unrecognized message from Claude Code",
+                Some("The CLI may be newer or older than this SDK supports.".to_string()),
+                None,
+            ),
+            Self::Configuration { .. } => (
+                "configuration",
+                "Invalid configuration",
+                Some("Fix the options passed to the client.".to_string()),
+                None,
+            ),
+            Self::ControlProtocol { .. } => (
+                "control_protocol",
+                "Claude Code rejected a request",
+                None,
+                None,
+            ),
+            Self::Interrupted => ("interrupted", "Interrupted", None, None),
+            Self::Timeout { .. } => (
+                "timeout",
+                "Claude Code did not respond in time",
+                Some("Try again, or increase the timeout.".to_string()),
+                None,
+            ),
+            Self::ControlRequestTimedOut { .. } => (
+                "control_request_timed_out",
+                "Claude Code did not answer a request",
+                Some("Try again, or increase the control timeout.".to_string()),
+                None,
+            ),
+            Self::VersionMismatch { required, .. } => (
+                "version_mismatch",
+                "Claude Code is out of date",
+                Some(format!(
+                    "Update Claude Code to version {} or later with `claude update`.",
+                    required
+                )),
+                Some(CLAUDE_CODE_DOCS),
+            ),
+            Self::RateLimited { info, .. } => (
+                "rate_limited",
+                "Rate limit reached",
+                Some(match info.retry_after_at(SystemTime::now()) {
+                    Some(wait) => format!("Try again in {} seconds.", wait.as_secs().max(1)),
+                    None => "Wait a moment, then try again.".to_string(),
+                }),
+                None,
+            ),
+            Self::Io(_) => ("io", "I/O error", None, None),
+            Self::Channel { .. } => ("channel", "Internal communication failed", None, None),
+            Self::Internal { .. } => (
+                "internal",
+                "Internal SDK error",
+                Some("This is a bug in the SDK; please report it.".to_string()),
+                None,
+            ),
+        };
+
+        Diagnostic {
+            code: code.to_string(),
+            title: title.to_string(),
+            detail: self.to_string(),
+            hint,
+            retryable: self.is_retryable(),
+            docs_url: docs_url.map(str::to_string),
+        }
+    }
+
+    /// The error's category.
+    ///
+    /// Besides [`RateLimited`](Self::RateLimited), rate limit and budget
+    /// errors are recognized from the CLI's error text in control protocol
+    /// and process errors.
+    pub fn kind(&self) -> ErrorKind {
+        if let Some(kind) = self.reported().and_then(classify_reported_error) {
+            return kind;
+        }
+
+        match self {
+            Self::CLINotFound { .. } => ErrorKind::CliNotFound,
+            Self::CLIConnection { .. } | Self::Channel { .. } => ErrorKind::ConnectionLost,
+            Self::Process { .. } | Self::ProcessFailed { .. } => ErrorKind::ProcessFailed,
+            Self::ProcessExited { .. } => ErrorKind::ProcessExited,
+            Self::JSONDecode { .. } | Self::MessageParse { .. } | Self::ControlProtocol { .. } => {
+                ErrorKind::Protocol
+            }
+            Self::Configuration { .. } => ErrorKind::Configuration,
+            Self::Interrupted => ErrorKind::Interrupted,
+            Self::Timeout { .. } | Self::ControlRequestTimedOut { .. } => ErrorKind::Timeout,
+            Self::VersionMismatch { .. } => ErrorKind::VersionMismatch,
+            Self::RateLimited { .. } => ErrorKind::RateLimited,
+            Self::Io(_) => ErrorKind::Io,
+            Self::Internal { .. } => ErrorKind::Internal,
+        }
+    }
+
+    /// What the API said about the limit, for a rate limit error: the
+    /// details of a [`RateLimited`](Self::RateLimited) error, or those in
+    /// the CLI's error text.
+    pub fn rate_limit_info(&self) -> Option<RateLimitInfo> {
+        match self {
+            Self::RateLimited { info, .. } => Some(info.clone()),
+            _ if self.kind() == ErrorKind::RateLimited => {
+                self.reported().map(RateLimitInfo::from_text)
+            }
+            _ => None,
+        }
+    }
+
+    /// How long the API asked to wait before retrying, for a rate limit
+    /// error that said. A reset time is counted from the current time; see
+    /// [`RateLimitInfo::retry_after_at`] to count from another.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.rate_limit_info()?.retry_after_at(SystemTime::now())
+    }
+
+    /// Error text reported by the CLI.
+    fn reported(&self) -> Option<&str> {
+        match self {
+            Self::ControlProtocol { message, .. } => Some(message.as_str()),
+            Self::Process {
+                message, stderr, ..
+            } => Some(stderr.as_deref().unwrap_or(message)),
+            Self::ProcessFailed { stderr_tail, .. } => Some(stderr_tail.as_str()),
+            Self::ProcessExited { stderr, .. } => Some(stderr.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Check if retrying the failed operation might succeed.
+    ///
+    /// True for lost connections, timeouts, rate limits, and CLI crashes
+    /// mid-session. Unlike [`is_recoverable`](Self::is_recoverable), rate
+    /// limits reported by the CLI are retryable and internal channel errors
+    /// are classified by [`kind`](Self::kind).
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
     /// Check if this error indicates the CLI was not found.
     pub fn is_cli_not_found(&self) -> bool {
         matches!(self, Self::CLINotFound { .. })
@@ -270,11 +867,76 @@ impl ClaudeSDKError {
     pub fn is_recoverable(&self) -> bool {
         matches!(
             self,
-            Self::CLIConnection { .. } | Self::Timeout { .. } | Self::Channel { .. }
+            Self::CLIConnection { .. }
+                | Self::Timeout { .. }
+                | Self::ControlRequestTimedOut { .. }
+                | Self::Channel { .. }
         )
     }
 }
 
+/// Recognize rate limit and budget errors in text reported by the CLI.
+pub(crate) fn classify_reported_error(text: &str) -> Option<ErrorKind> {
+    let text = text.to_ascii_lowercase();
+    if [
+        "rate limit",
+        "rate_limit",
+        "too many requests",
+        "overloaded",
+        "usage limit reached",
+    ]
+    .iter()
+    .any(|needle| text.contains(needle))
+    {
+        Some(ErrorKind::RateLimited)
+    } else if ["budget", "error_max_budget_usd"]
+        .iter()
+        .any(|needle| text.contains(needle))
+    {
+        Some(ErrorKind::BudgetExceeded)
+    } else {
+        None
+    }
+}
+
+/// The last non-empty line of some output, trimmed.
+fn last_line(text: &str) -> Option<&str> {
+    text.lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+}
+
+/// Display text for [`ClaudeSDKError::ProcessFailed`]: the exit status and the
+/// last line of stderr, which is usually the CLI's own error message.
+fn process_failed_summary(exit_code: Option<i32>, stderr_tail: &str) -> String {
+    let status = match exit_code {
+        Some(code) => format!("exit code {}", code),
+        None => "terminated by signal".to_string(),
+    };
+    match stderr_tail
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+    {
+        Some(line) => format!("CLI process failed ({}): {}", status, line.trim()),
+        None => format!("CLI process failed ({}) with no stderr output", status),
+    }
+}
+
+/// Display text for [`ClaudeSDKError::ProcessExited`].
+fn process_exited_summary(code: Option<i32>, signal: Option<i32>, stderr: &str) -> String {
+    let status = match (code, signal) {
+        (Some(code), _) => format!("exit code {}", code),
+        (None, Some(signal)) => format!("signal {}", signal),
+        (None, None) => "unknown status".to_string(),
+    };
+    match last_line(stderr) {
+        Some(line) => format!("CLI process exited unexpectedly ({}): {}", status, line),
+        None => format!("CLI process exited unexpectedly ({})", status),
+    }
+}
+
 /// Result type alias for SDK operations.
 pub type Result<T> = std::result::Result<T, ClaudeSDKError>;
 
@@ -295,6 +957,193 @@ mod tests {
         assert!(err.to_string().contains("1"));
     }
 
+    #[test]
+    fn test_process_failed_display_uses_last_stderr_line() {
+        let err = ClaudeSDKError::process_failed(
+            Some(1),
+            "loading config\nError: invalid API key\n\n",
+            vec!["claude".to_string()],
+        );
+        assert_eq!(
+            err.to_string(),
+            "CLI process failed (exit code 1): Error: invalid API key"
+        );
+
+        let err = ClaudeSDKError::process_failed(None, "", Vec::new());
+        assert_eq!(
+            err.to_string(),
+            "CLI process failed (terminated by signal) with no stderr output"
+        );
+    }
+
+    #[test]
+    fn test_process_exited_display() {
+        let err = ClaudeSDKError::process_exited(None, Some(9), Vec::new(), "");
+        assert_eq!(
+            err.to_string(),
+            "CLI process exited unexpectedly (signal 9)"
+        );
+        let err = ClaudeSDKError::process_exited(Some(0), None, Vec::new(), "bye\n");
+        assert_eq!(
+            err.to_string(),
+            "CLI process exited unexpectedly (exit code 0): bye"
+        );
+    }
+
+    #[test]
+    fn test_to_diagnostic() {
+        let diagnostic = ClaudeSDKError::cli_not_found("claude not in PATH").to_diagnostic();
+        assert_eq!(diagnostic.code, "cli_not_found");
+        assert!(diagnostic.detail.contains("claude not in PATH"));
+        assert!(diagnostic.hint.unwrap().contains("cli_path"));
+        assert!(!diagnostic.retryable);
+        assert_eq!(diagnostic.docs_url.as_deref(), Some(CLAUDE_CODE_DOCS));
+
+        let diagnostic = ClaudeSDKError::timeout(1000).to_diagnostic();
+        assert_eq!(diagnostic.code, "timeout");
+        assert!(diagnostic.retryable);
+        assert!(diagnostic.docs_url.is_none());
+
+        let diagnostic =
+            ClaudeSDKError::process_failed(Some(1), "Error: invalid API key\n", Vec::new())
+                .to_diagnostic();
+        assert_eq!(
+            diagnostic.hint.as_deref(),
+            Some("The CLI reported: Error: invalid API key")
+        );
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            ClaudeSDKError::cli_not_found("x").kind(),
+            ErrorKind::CliNotFound
+        );
+        assert_eq!(
+            ClaudeSDKError::channel("x").kind(),
+            ErrorKind::ConnectionLost
+        );
+        assert_eq!(ClaudeSDKError::json_decode("x").kind(), ErrorKind::Protocol);
+        assert_eq!(
+            ClaudeSDKError::control_protocol("Rate limit exceeded").kind(),
+            ErrorKind::RateLimited
+        );
+        assert_eq!(
+            ClaudeSDKError::process_exited(Some(1), None, Vec::new(), "API Error: 529 overloaded")
+                .kind(),
+            ErrorKind::RateLimited
+        );
+        assert_eq!(
+            ClaudeSDKError::process_failed(Some(1), "Exceeded budget of $1.00", Vec::new()).kind(),
+            ErrorKind::BudgetExceeded
+        );
+        assert_eq!(
+            ClaudeSDKError::process_failed(Some(1), "bad flag", Vec::new()).kind(),
+            ErrorKind::ProcessFailed
+        );
+
+        let timed_out = ClaudeSDKError::control_request_timed_out("initialize", "req_1", 60_000);
+        assert_eq!(timed_out.kind(), ErrorKind::Timeout);
+        assert_eq!(
+            timed_out.to_string(),
+            "Control request 'initialize' (req_1) timed out after 60000ms"
+        );
+    }
+
+    #[test]
+    fn test_error_kind_codes_are_stable() {
+        assert_eq!(ErrorKind::CliNotFound.code(), 1);
+        assert_eq!(ErrorKind::RateLimited.code(), 4);
+        assert_eq!(ErrorKind::Internal.code(), 13);
+        assert_eq!(ErrorKind::ConnectionLost.as_str(), "connection_lost");
+        assert_eq!(
+            serde_json::to_string(&ErrorKind::BudgetExceeded).unwrap(),
+            r#""budget_exceeded""#
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(ClaudeSDKError::timeout(1000).is_retryable());
+        assert!(ClaudeSDKError::cli_connection("broken pipe").is_retryable());
+        assert!(ClaudeSDKError::control_protocol("rate_limit_error").is_retryable());
+        assert!(!ClaudeSDKError::control_protocol("unknown subtype").is_retryable());
+        assert!(!ClaudeSDKError::configuration("bad").is_retryable());
+        assert!(!ClaudeSDKError::internal("bug").is_retryable());
+    }
+
+    #[test]
+    fn test_rate_limit_info() {
+        let err = ClaudeSDKError::control_protocol("Rate limit exceeded. Retry-After: 12");
+        let info = err.rate_limit_info().unwrap();
+        assert_eq!(info.retry_after, Some(Duration::from_secs(12)));
+        assert!(info.limit_type.is_none());
+        assert!(ClaudeSDKError::control_protocol("bad request")
+            .rate_limit_info()
+            .is_none());
+
+        let info = RateLimitInfo::from_text("Claude AI usage limit reached|1760000000");
+        assert_eq!(info.limit_type.as_deref(), Some("usage_limit"));
+        assert!(info.retry_after.is_none());
+        let reset = UNIX_EPOCH + Duration::from_secs(1_760_000_000);
+        assert_eq!(info.resets_at, Some(reset));
+        assert_eq!(
+            info.retry_after_at(reset - Duration::from_secs(3600)),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            info.retry_after_at(reset + Duration::from_secs(1)),
+            Some(Duration::ZERO)
+        );
+
+        let wait = |text: &str| RateLimitInfo::from_text(text).retry_after;
+        assert_eq!(
+            wait("Overloaded, try again in 2 minutes"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            wait("Usage limit hit, try again in 2 hours."),
+            Some(Duration::from_secs(2 * 3600))
+        );
+        assert_eq!(wait("Retry-After: 5m"), Some(Duration::from_secs(300)));
+        assert_eq!(wait("Retry-After: 5h"), Some(Duration::from_secs(5 * 3600)));
+        assert_eq!(wait("retry after 30s"), Some(Duration::from_secs(30)));
+        assert_eq!(wait("retry after 250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(wait("Try again in 3 days"), None);
+
+        let err = ClaudeSDKError::rate_limited(
+            "Too many requests",
+            RateLimitInfo {
+                retry_after: Some(Duration::from_secs(45)),
+                limit_type: Some("rate_limit_error".to_string()),
+                ..RateLimitInfo::default()
+            },
+        );
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(45)));
+        let diagnostic = err.to_diagnostic();
+        assert_eq!(diagnostic.code, "rate_limited");
+        assert_eq!(diagnostic.hint.as_deref(), Some("Try again in 45 seconds."));
+    }
+
+    #[test]
+    fn test_rate_limit_info_ignores_unrepresentable_waits() {
+        let info = RateLimitInfo::from_text(
+            r#"429 {"type":"error","error":{"type":"rate_limit_error","retry_after":1e20}}"#,
+        );
+        assert_eq!(info.limit_type.as_deref(), Some("rate_limit_error"));
+        assert!(info.retry_after.is_none());
+
+        let info = RateLimitInfo::from_text(&format!(
+            r#"429 {{"error":{{"type":"rate_limit_error","resetsAt":{}}}}}"#,
+            u64::MAX
+        ));
+        assert!(info.resets_at.is_none());
+        let info = RateLimitInfo::from_text(&format!("Claude AI usage limit reached|{}", u64::MAX));
+        assert!(info.resets_at.is_none());
+    }
+
     #[test]
     fn test_is_recoverable() {
         assert!(ClaudeSDKError::timeout(1000).is_recoverable());
@@ -0,0 +1,412 @@
+//! Typed event bus for SDK activity.
+//!
+//! An [`EventBus`] broadcasts [`SdkEvent`]s — tool calls, hook invocations,
+//! permission decisions, cost updates, and partial stream events — to any
+//! number of subscribers, independently of the message stream the client
+//! consumes. Slow subscribers miss events rather than holding up the session.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::{ClaudeAgentOptions, ClaudeClient, EventBus, SdkEvent};
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let bus = EventBus::default();
+//! let mut events = bus.subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         if let SdkEvent::ToolCallStarted { tool_name, .. } = event {
+//!             println!("tool: {}", tool_name);
+//!         }
+//!     }
+//! });
+//!
+//! let mut client = ClaudeClient::new(Some(ClaudeAgentOptions::new().with_event_bus(bus)));
+//! client.connect().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+
+use crate::diffs::FileDiff;
+use crate::types::{ContentBlock, Message, PermissionResult, StreamEvent, UserMessageContent};
+
+/// Default number of events buffered per subscriber.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// An event emitted by the SDK.
+#[derive(Debug, Clone)]
+pub enum SdkEvent {
+    /// The model requested a tool call.
+    ToolCallStarted {
+        /// Tool use ID.
+        tool_use_id: String,
+        /// Tool name.
+        tool_name: String,
+        /// Tool input.
+        input: serde_json::Value,
+    },
+    /// A tool call's result was returned to the model.
+    ToolCallFinished {
+        /// Tool use ID.
+        tool_use_id: String,
+        /// Tool name, if the start of the call was seen.
+        tool_name: Option<String>,
+        /// Whether the tool reported an error.
+        is_error: bool,
+        /// Time since the call started, if the start was seen.
+        duration: Option<Duration>,
+    },
+    /// A hook callback ran.
+    HookInvoked {
+        /// Hook event name, e.g. `PreToolUse`.
+        event: String,
+        /// Callback ID assigned at registration.
+        callback_id: String,
+        /// Tool use ID for tool-related hooks.
+        tool_use_id: Option<String>,
+        /// Output returned to the CLI.
+        output: serde_json::Value,
+        /// How long the callback took.
+        duration: Duration,
+    },
+    /// The `can_use_tool` callback decided on a tool call.
+    PermissionDecision {
+        /// Tool name.
+        tool_name: String,
+        /// Tool input as requested.
+        input: serde_json::Value,
+        /// The decision.
+        result: PermissionResult,
+    },
+    /// A result message reported the session's cost.
+    CostUpdated {
+        /// Session ID.
+        session_id: String,
+        /// Total cost in USD, if reported.
+        total_cost_usd: Option<f64>,
+        /// Token usage, if reported.
+        usage: Option<serde_json::Value>,
+    },
+    /// A partial-message stream event arrived.
+    StreamEventReceived(StreamEvent),
+    /// The conversation was compacted under an
+    /// [`AutoCompactPolicy`](crate::AutoCompactPolicy).
+    AutoCompacted {
+        /// Tokens in context before compacting.
+        context_tokens: u64,
+        /// The policy's threshold.
+        trigger_tokens: u64,
+        /// Whether the CLI reported the compaction as failed.
+        is_error: bool,
+    },
+    /// The model started a [`Subagent`](crate::Subagent) with the `Task`
+    /// tool.
+    SubagentStarted {
+        /// ID of the `Task` tool use.
+        tool_use_id: String,
+        /// Subagent type, if given.
+        subagent_type: Option<String>,
+        /// Short description of the task, if given.
+        description: Option<String>,
+    },
+    /// A subagent returned its result.
+    SubagentFinished {
+        /// ID of the `Task` tool use.
+        tool_use_id: String,
+        /// Whether the subagent returned an error.
+        is_error: bool,
+        /// Input tokens of the subagent's API calls.
+        input_tokens: u64,
+        /// Output tokens of the subagent's API calls.
+        output_tokens: u64,
+        /// How long the subagent ran, if its start was seen.
+        duration: Option<Duration>,
+    },
+    /// A tool changed a file. Emitted with
+    /// [`file_diffs`](crate::ClaudeAgentOptions::file_diffs) set.
+    FileDiff(FileDiff),
+}
+
+/// Broadcasts [`SdkEvent`]s to subscribers.
+///
+/// Cloning shares the same bus.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<SdkEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self { tx }
+    }
+
+    /// Subscribe to events emitted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<SdkEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Number of active subscribers.
+    pub fn receiver_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Emit an event. Events are dropped when nobody is subscribed.
+    pub fn emit(&self, event: SdkEvent) {
+        let _ = self.tx.send(event);
+    }
+}
+
+/// Derives events from a session's messages and callbacks.
+#[derive(Clone, Default)]
+pub(crate) struct EventTracker {
+    bus: Option<EventBus>,
+    /// Tool calls in progress: tool use ID -> (name, start time).
+    tool_calls: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+}
+
+impl EventTracker {
+    pub(crate) fn new(bus: Option<EventBus>) -> Self {
+        Self {
+            bus,
+            tool_calls: Arc::default(),
+        }
+    }
+
+    /// The bus, if anyone is listening.
+    fn active(&self) -> Option<&EventBus> {
+        self.bus.as_ref().filter(|bus| bus.receiver_count() > 0)
+    }
+
+    /// Emit events for a message read from the CLI.
+    pub(crate) fn observe(&self, message: &Message) {
+        let Some(bus) = self.active() else {
+            return;
+        };
+        match message.typed() {
+            Message::Assistant(assistant) => {
+                for block in &assistant.content {
+                    if let ContentBlock::ToolUse(tool_use) = block {
+                        self.tool_calls
+                            .lock()
+                            .unwrap()
+                            .insert(tool_use.id.clone(), (tool_use.name.clone(), Instant::now()));
+                        bus.emit(SdkEvent::ToolCallStarted {
+                            tool_use_id: tool_use.id.clone(),
+                            tool_name: tool_use.name.clone(),
+                            input: tool_use.input.clone(),
+                        });
+                    }
+                }
+            }
+            Message::User(user) => {
+                let UserMessageContent::Blocks(blocks) = &user.content else {
+                    return;
+                };
+                for block in blocks {
+                    if let ContentBlock::ToolResult(result) = block {
+                        let started = self
+                            .tool_calls
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .remove(&result.tool_use_id);
+                        bus.emit(SdkEvent::ToolCallFinished {
+                            tool_use_id: result.tool_use_id.clone(),
+                            tool_name: started.as_ref().map(|(name, _)| name.clone()),
+                            is_error: result.is_error.unwrap_or(false),
+                            duration: started.map(|(_, at)| at.elapsed()),
+                        });
+                    }
+                }
+            }
+            Message::Result(result) => {
+                bus.emit(SdkEvent::CostUpdated {
+                    session_id: result.session_id.clone(),
+                    total_cost_usd: result.total_cost_usd,
+                    usage: result.usage.clone(),
+                });
+            }
+            Message::StreamEvent(event) => {
+                bus.emit(SdkEvent::StreamEventReceived(event.clone()));
+            }
+            Message::System(_) | Message::ParseWarning(_) | Message::Lazy(_) => {}
+        }
+    }
+
+    /// Emit an event, if anyone is listening.
+    pub(crate) fn emit(&self, event: SdkEvent) {
+        if let Some(bus) = self.active() {
+            bus.emit(event);
+        }
+    }
+
+    /// Emit [`SdkEvent::HookInvoked`].
+    pub(crate) fn hook_invoked(
+        &self,
+        event: &str,
+        callback_id: &str,
+        tool_use_id: Option<&str>,
+        output: &serde_json::Value,
+        duration: Duration,
+    ) {
+        if let Some(bus) = self.active() {
+            bus.emit(SdkEvent::HookInvoked {
+                event: event.to_string(),
+                callback_id: callback_id.to_string(),
+                tool_use_id: tool_use_id.map(str::to_string),
+                output: output.clone(),
+                duration,
+            });
+        }
+    }
+
+    /// Emit [`SdkEvent::PermissionDecision`].
+    pub(crate) fn permission_decision(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+        result: &PermissionResult,
+    ) {
+        if let Some(bus) = self.active() {
+            bus.emit(SdkEvent::PermissionDecision {
+                tool_name: tool_name.to_string(),
+                input: input.clone(),
+                result: result.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use serde_json::json;
+
+    fn message(raw: serde_json::Value) -> Message {
+        parse_message(raw).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_tool_call_lifecycle() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+        let tracker = EventTracker::new(Some(bus));
+
+        tracker.observe(&message(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "ls"}}
+            ]}
+        })));
+        tracker.observe(&message(json!({
+            "type": "user",
+            "message": {"content": [
+                {"type": "tool_result", "tool_use_id": "tu_1", "content": "ok", "is_error": false}
+            ]}
+        })));
+
+        match rx.try_recv().unwrap() {
+            SdkEvent::ToolCallStarted {
+                tool_use_id,
+                tool_name,
+                input,
+            } => {
+                assert_eq!(tool_use_id, "tu_1");
+                assert_eq!(tool_name, "Bash");
+                assert_eq!(input["command"], "ls");
+            }
+            other => panic!("Expected ToolCallStarted, got {:?}", other),
+        }
+        match rx.try_recv().unwrap() {
+            SdkEvent::ToolCallFinished {
+                tool_name,
+                is_error,
+                duration,
+                ..
+            } => {
+                assert_eq!(tool_name.as_deref(), Some("Bash"));
+                assert!(!is_error);
+                assert!(duration.is_some());
+            }
+            other => panic!("Expected ToolCallFinished, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cost_and_stream_events() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+        let tracker = EventTracker::new(Some(bus));
+
+        tracker.observe(&message(json!({
+            "type": "stream_event", "uuid": "u1", "session_id": "s1",
+            "event": {"type": "content_block_delta"}
+        })));
+        tracker.observe(&message(json!({
+            "type": "result", "subtype": "success", "duration_ms": 1, "duration_api_ms": 1,
+            "is_error": false, "num_turns": 1, "session_id": "s1", "total_cost_usd": 0.1
+        })));
+
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            SdkEvent::StreamEventReceived(_)
+        ));
+        match rx.try_recv().unwrap() {
+            SdkEvent::CostUpdated {
+                session_id,
+                total_cost_usd,
+                ..
+            } => {
+                assert_eq!(session_id, "s1");
+                assert_eq!(total_cost_usd, Some(0.1));
+            }
+            other => panic!("Expected CostUpdated, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_no_subscribers_is_noop() {
+        let bus = EventBus::default();
+        let tracker = EventTracker::new(Some(bus.clone()));
+        tracker.permission_decision("Read", &json!({}), &PermissionResult::allow());
+
+        let mut rx = bus.subscribe();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_multiple_subscribers() {
+        let bus = EventBus::new(4);
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+        EventTracker::new(Some(bus)).hook_invoked(
+            "PreToolUse",
+            "hook_0",
+            Some("tu_1"),
+            &json!({}),
+            Duration::from_millis(3),
+        );
+        assert!(matches!(
+            a.try_recv().unwrap(),
+            SdkEvent::HookInvoked { .. }
+        ));
+        assert!(matches!(
+            b.try_recv().unwrap(),
+            SdkEvent::HookInvoked { .. }
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+//! Aggregated health checks.
+//!
+//! [`ClaudeClient::health`](crate::ClaudeClient::health) combines the CLI
+//! version check, a control round trip, MCP server status, and the state of
+//! the CLI process into one [`HealthReport`], for readiness probes in
+//! services that embed the SDK.
+
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+use crate::mcp_status::{McpServerState, McpServerStatus};
+
+/// How long [`ClaudeClient::health`](crate::ClaudeClient::health) waits for
+/// the CLI to answer its control request.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The state of a client and its CLI at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// Version printed by `claude --version`, if it ran.
+    pub cli_version: Option<String>,
+    /// Whether the CLI answered a control request within
+    /// [`HEALTH_CHECK_TIMEOUT`].
+    pub protocol_ok: bool,
+    /// Round-trip time of that control request.
+    pub protocol_rtt: Option<Duration>,
+    /// Status of each MCP server; empty if the CLI did not answer.
+    pub mcp_servers: Vec<McpServerStatus>,
+    /// When the SDK last received a line from the CLI.
+    pub last_activity: Option<SystemTime>,
+    /// Whether the CLI process is running.
+    pub subprocess_alive: bool,
+}
+
+impl HealthReport {
+    /// A report for a client that is not connected.
+    pub(crate) fn disconnected(cli_version: Option<String>) -> Self {
+        Self {
+            cli_version,
+            protocol_ok: false,
+            protocol_rtt: None,
+            mcp_servers: Vec::new(),
+            last_activity: None,
+            subprocess_alive: false,
+        }
+    }
+
+    /// Whether the CLI is running and answering control requests.
+    pub fn is_healthy(&self) -> bool {
+        self.subprocess_alive && self.protocol_ok
+    }
+
+    /// MCP servers that failed to start or connect.
+    pub fn failed_mcp_servers(&self) -> impl Iterator<Item = &McpServerStatus> {
+        self.mcp_servers
+            .iter()
+            .filter(|server| server.state == McpServerState::Failed)
+    }
+}
@@ -0,0 +1,312 @@
+//! Message history with a bounded memory footprint.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use tempfile::TempPath;
+
+use crate::conversation::Conversation;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::Message;
+
+/// The messages of a long-running session, keeping only the most recent in
+/// memory.
+///
+/// Once more than the memory limit are held, the oldest are appended to a
+/// JSONL file, one message per line, and read back from it when accessed.
+/// Without a limit, a `History` keeps everything in memory like a
+/// [`Conversation`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{ClaudeClient, History};
+/// use tokio_stream::StreamExt;
+///
+/// # async fn example() -> claude_agents_sdk::Result<()> {
+/// let mut history = History::new().with_memory_limit(1000);
+/// let mut client = ClaudeClient::new(None);
+/// client.connect().await?;
+/// client.query("Watch the build and fix failures").await?;
+/// let mut messages = client.receive_messages();
+/// while let Some(message) = messages.next().await {
+///     history.push(message?)?;
+/// }
+///
+/// let first = history.get(0)?;
+/// let conversation = history.to_conversation()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct History {
+    /// The most recent messages.
+    recent: VecDeque<Message>,
+    max_in_memory: Option<usize>,
+    spill_path: Option<PathBuf>,
+    spill: Option<SpillFile>,
+}
+
+/// The file older messages are written to.
+#[derive(Debug)]
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    /// Deletes the file on drop when it is temporary.
+    _temp: Option<TempPath>,
+    /// Start of each message in the file, and the end of the last one.
+    offsets: Vec<u64>,
+}
+
+impl History {
+    /// Create an empty history that keeps everything in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max` messages in memory, spilling older ones to disk.
+    ///
+    /// At least one message is always kept in memory.
+    pub fn with_memory_limit(mut self, max: usize) -> Self {
+        self.max_in_memory = Some(max.max(1));
+        self
+    }
+
+    /// Spill to this file instead of a temporary one.
+    ///
+    /// The file is truncated when the first message is spilled and left in
+    /// place afterwards, so it holds the start of the session.
+    pub fn with_spill_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spill_path = Some(path.into());
+        self
+    }
+
+    /// Add a message, spilling the oldest in memory if over the limit.
+    pub fn push(&mut self, message: Message) -> Result<()> {
+        self.recent.push_back(message);
+        let Some(max) = self.max_in_memory else {
+            return Ok(());
+        };
+        while self.recent.len() > max {
+            let spill = match &mut self.spill {
+                Some(spill) => spill,
+                None => self
+                    .spill
+                    .insert(SpillFile::create(self.spill_path.as_deref())?),
+            };
+            // Only drop the message from memory once it is on disk.
+            spill.append(&self.recent[0])?;
+            self.recent.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Number of messages, in memory and on disk.
+    pub fn len(&self) -> usize {
+        self.spilled() + self.recent.len()
+    }
+
+    /// Whether there are no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of messages on disk.
+    pub fn spilled(&self) -> usize {
+        self.spill.as_ref().map_or(0, SpillFile::len)
+    }
+
+    /// The file older messages were spilled to, if any were.
+    pub fn spill_file(&self) -> Option<&Path> {
+        self.spill.as_ref().map(|spill| spill.path.as_path())
+    }
+
+    /// The messages in memory, oldest first.
+    pub fn recent(&self) -> impl Iterator<Item = &Message> {
+        self.recent.iter()
+    }
+
+    /// The most recent message.
+    pub fn last(&self) -> Option<&Message> {
+        self.recent.back()
+    }
+
+    /// The message at `index`, counting from the start of the session,
+    /// reading it back from disk if it was spilled.
+    pub fn get(&self, index: usize) -> Result<Option<Message>> {
+        let spilled = self.spilled();
+        match (index.checked_sub(spilled), &self.spill) {
+            (Some(recent), _) => Ok(self.recent.get(recent).cloned()),
+            (None, Some(spill)) => spill.read(index).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Every message, oldest first, reading spilled ones back from disk.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Message>> + '_ {
+        let spilled: Box<dyn Iterator<Item = Result<Message>>> = match &self.spill {
+            None => Box::new(std::iter::empty()),
+            Some(spill) => match spill.lines() {
+                Ok(lines) => Box::new(lines),
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            },
+        };
+        spilled.chain(self.recent.iter().cloned().map(Ok))
+    }
+
+    /// Load every message into a [`Conversation`] for analysis.
+    pub fn to_conversation(&self) -> Result<Conversation> {
+        self.iter().collect()
+    }
+}
+
+impl SpillFile {
+    fn create(path: Option<&Path>) -> Result<Self> {
+        let (file, path, temp) = match path {
+            Some(path) => {
+                let file = File::options()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(path)
+                    .map_err(|e| {
+                        ClaudeSDKError::configuration(format!(
+                            "Failed to create history file {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                (file, path.to_path_buf(), None)
+            }
+            None => {
+                let (file, temp) = tempfile::NamedTempFile::new()?.into_parts();
+                (file, temp.to_path_buf(), Some(temp))
+            }
+        };
+        Ok(Self {
+            file,
+            path,
+            _temp: temp,
+            offsets: vec![0],
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn append(&mut self, message: &Message) -> Result<()> {
+        let mut line = serde_json::to_vec(message)
+            .map_err(|e| ClaudeSDKError::internal(format!("Failed to serialize message: {}", e)))?;
+        line.push(b'\n');
+        let end = self.offsets[self.len()];
+        self.file.write_all(&line)?;
+        self.offsets.push(end + line.len() as u64);
+        Ok(())
+    }
+
+    fn read(&self, index: usize) -> Result<Message> {
+        let (start, end) = (self.offsets[index], self.offsets[index + 1]);
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut line = vec![0; (end - start) as usize];
+        file.read_exact(&mut line)?;
+        decode(&line)
+    }
+
+    fn lines(&self) -> Result<impl Iterator<Item = Result<Message>>> {
+        let file = File::open(&self.path)?;
+        Ok(BufReader::new(file)
+            .split(b'\n')
+            .take(self.len())
+            .map(|line| decode(&line?)))
+    }
+}
+
+fn decode(line: &[u8]) -> Result<Message> {
+    serde_json::from_slice(line)
+        .map_err(|e| ClaudeSDKError::json_decode(format!("Failed to read spilled message: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{UserMessage, UserMessageContent};
+
+    fn user(text: &str) -> Message {
+        Message::User(UserMessage {
+            content: UserMessageContent::Text(text.to_string()),
+            uuid: None,
+            parent_tool_use_id: None,
+        })
+    }
+
+    fn text(message: &Message) -> &str {
+        match message {
+            Message::User(UserMessage {
+                content: UserMessageContent::Text(text),
+                ..
+            }) => text,
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unbounded_keeps_everything_in_memory() {
+        let mut history = History::new();
+        for i in 0..10 {
+            history.push(user(&i.to_string())).unwrap();
+        }
+        assert_eq!(history.len(), 10);
+        assert_eq!(history.spilled(), 0);
+        assert!(history.spill_file().is_none());
+    }
+
+    #[test]
+    fn test_spills_oldest_and_reloads() {
+        let mut history = History::new().with_memory_limit(3);
+        for i in 0..10 {
+            history.push(user(&i.to_string())).unwrap();
+        }
+        assert_eq!(history.len(), 10);
+        assert_eq!(history.spilled(), 7);
+        assert_eq!(history.recent().count(), 3);
+        assert_eq!(text(history.last().unwrap()), "9");
+
+        assert_eq!(text(&history.get(0).unwrap().unwrap()), "0");
+        assert_eq!(text(&history.get(6).unwrap().unwrap()), "6");
+        assert_eq!(text(&history.get(7).unwrap().unwrap()), "7");
+        assert!(history.get(10).unwrap().is_none());
+
+        let all: Vec<String> = history
+            .iter()
+            .map(|message| text(&message.unwrap()).to_string())
+            .collect();
+        assert_eq!(all, (0..10).map(|i| i.to_string()).collect::<Vec<_>>());
+        assert_eq!(history.to_conversation().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_spill_path_and_temporary_cleanup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut history = History::new().with_memory_limit(1).with_spill_path(&path);
+        history.push(user("a\nb")).unwrap();
+        history.push(user("c")).unwrap();
+        assert_eq!(history.spill_file(), Some(path.as_path()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(text(&history.get(0).unwrap().unwrap()), "a\nb");
+        drop(history);
+        assert!(path.exists());
+
+        let mut history = History::new().with_memory_limit(1);
+        history.push(user("a")).unwrap();
+        history.push(user("b")).unwrap();
+        let temp = history.spill_file().unwrap().to_path_buf();
+        assert!(temp.exists());
+        drop(history);
+        assert!(!temp.exists());
+    }
+}
@@ -0,0 +1,111 @@
+//! Keepalive pings and round-trip time measurement.
+//!
+//! [`ClaudeClient::ping`](crate::ClaudeClient::ping) times one control
+//! round trip to the CLI. [`ClaudeClient::start_pinger`](crate::ClaudeClient::start_pinger)
+//! pings on an interval in the background and keeps the recent samples in a
+//! [`Pinger`], so slow or stalled transports show up as numbers rather than
+//! as vague slowness.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::clock::Clock;
+use crate::control::ControlHandle;
+
+/// Number of samples a [`Pinger`] keeps.
+pub const PING_HISTORY_LEN: usize = 256;
+
+/// One background ping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingSample {
+    /// When the ping was sent.
+    pub sent_at: SystemTime,
+    /// Round-trip time, or `None` if the ping failed or took longer than the
+    /// ping interval.
+    pub rtt: Option<Duration>,
+}
+
+/// A background task pinging the CLI on an interval.
+///
+/// Stops when dropped or when [`stop`](Self::stop) is called.
+pub struct Pinger {
+    history: Arc<Mutex<VecDeque<PingSample>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Pinger {
+    pub(crate) fn spawn(handle: ControlHandle, clock: Arc<dyn Clock>, interval: Duration) -> Self {
+        let history = Arc::new(Mutex::new(VecDeque::with_capacity(PING_HISTORY_LEN)));
+        let samples = Arc::clone(&history);
+        let task = tokio::spawn(async move {
+            loop {
+                let started = clock.now();
+                let sent_at = clock.system_time();
+                let rtt = handle.ping_with_timeout(Some(interval)).await.ok();
+                {
+                    let mut samples = samples.lock().unwrap_or_else(|e| e.into_inner());
+                    if samples.len() == PING_HISTORY_LEN {
+                        samples.pop_front();
+                    }
+                    samples.push_back(PingSample { sent_at, rtt });
+                }
+                let next = started + interval;
+                clock
+                    .sleep(next.saturating_duration_since(clock.now()))
+                    .await;
+            }
+        });
+        Self { history, task }
+    }
+
+    /// Samples so far, oldest first, up to [`PING_HISTORY_LEN`].
+    pub fn history(&self) -> Vec<PingSample> {
+        self.history
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// The most recent sample.
+    pub fn latest(&self) -> Option<PingSample> {
+        self.history
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .back()
+            .copied()
+    }
+
+    /// Mean round-trip time of the successful samples in the history.
+    pub fn mean_rtt(&self) -> Option<Duration> {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let rtts: Vec<Duration> = history.iter().filter_map(|s| s.rtt).collect();
+        if rtts.is_empty() {
+            return None;
+        }
+        Some(rtts.iter().sum::<Duration>() / rtts.len() as u32)
+    }
+
+    /// Number of failed samples in the history.
+    pub fn failures(&self) -> usize {
+        self.history
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.rtt.is_none())
+            .count()
+    }
+
+    /// Stop pinging.
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for Pinger {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
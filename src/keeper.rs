@@ -0,0 +1,258 @@
+//! A session kept warm between interactions.
+//!
+//! Interactive apps want the CLI running while the user is active, so
+//! replies start quickly, but not holding a subprocess while they're away.
+//! A [`SessionKeeper`] connects on first use, disconnects once the session
+//! has been idle for a while, and reconnects resuming the same session the
+//! next time it's used.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::client::ClaudeClient;
+use crate::clock::{self, Clock};
+use crate::errors::Result;
+use crate::types::{ClaudeAgentOptions, ResultMessage};
+
+/// Keeps a client connected while in use and disconnects it when idle.
+///
+/// Must be created within a Tokio runtime; the idle timer runs as a task
+/// until the keeper is dropped.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{ClaudeAgentOptions, SessionKeeper};
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let keeper = SessionKeeper::new(ClaudeAgentOptions::new(), Duration::from_secs(300));
+///
+///     let (answer, _) = keeper.query("What's in this repo?").await?;
+///     println!("{}", answer);
+///
+///     // Minutes later, after the CLI was shut down for being idle, this
+///     // reconnects and resumes the same conversation.
+///     let (answer, _) = keeper.query("And how is it tested?").await?;
+///     println!("{}", answer);
+///     Ok(())
+/// }
+/// ```
+pub struct SessionKeeper {
+    shared: Arc<Shared>,
+    watcher: tokio::task::JoinHandle<()>,
+}
+
+struct Shared {
+    options: ClaudeAgentOptions,
+    idle_timeout: Duration,
+    clock: Arc<dyn Clock>,
+    state: tokio::sync::Mutex<State>,
+    last_used: Mutex<Instant>,
+    /// Signaled on connect, to restart the idle timer.
+    connected: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    client: Option<ClaudeClient>,
+    /// The session to resume on reconnect.
+    session_id: Option<String>,
+}
+
+impl State {
+    /// Take the client, keeping its session to resume.
+    fn take_client(&mut self) -> Option<ClaudeClient> {
+        let client = self.client.take()?;
+        if let Some(session_id) = client.session_id() {
+            self.session_id = Some(session_id);
+        }
+        Some(client)
+    }
+}
+
+impl SessionKeeper {
+    /// Create a keeper that connects with `options` on first use and
+    /// disconnects after `idle_timeout` without use.
+    pub fn new(options: ClaudeAgentOptions, idle_timeout: Duration) -> Self {
+        let clock = options.clock.clone().unwrap_or_else(clock::default_clock);
+        let shared = Arc::new(Shared {
+            last_used: Mutex::new(clock.now()),
+            options,
+            idle_timeout,
+            clock,
+            state: tokio::sync::Mutex::default(),
+            connected: Notify::new(),
+        });
+        let watcher = tokio::spawn(Self::watch_idle(Arc::clone(&shared)));
+        Self { shared, watcher }
+    }
+
+    /// Disconnect the client once it has gone unused for the idle timeout.
+    async fn watch_idle(shared: Arc<Shared>) {
+        loop {
+            let deadline =
+                *shared.last_used.lock().unwrap_or_else(|e| e.into_inner()) + shared.idle_timeout;
+            let now = shared.clock.now();
+            if now < deadline {
+                shared.clock.sleep(deadline - now).await;
+                continue;
+            }
+            // Waits for a caller holding the client, who then resets the
+            // timer.
+            let mut state = shared.state.lock().await;
+            if *shared.last_used.lock().unwrap_or_else(|e| e.into_inner()) + shared.idle_timeout
+                > shared.clock.now()
+            {
+                continue;
+            }
+            match state.take_client() {
+                Some(mut client) => {
+                    if let Err(e) = client.disconnect().await {
+                        debug!("Failed to disconnect idle session: {}", e);
+                    }
+                    debug!("Disconnected session idle for {:?}", shared.idle_timeout);
+                }
+                None => {
+                    drop(state);
+                    shared.connected.notified().await;
+                }
+            }
+        }
+    }
+
+    /// The connected client, connecting first if needed.
+    ///
+    /// After an idle disconnect, or if the CLI has exited, the new
+    /// connection resumes the previous session. The idle timer restarts
+    /// when the returned guard is dropped, and the client isn't
+    /// disconnected while it's held.
+    pub async fn client(&self) -> Result<KeptClient<'_>> {
+        let mut state = self.shared.state.lock().await;
+        if state
+            .client
+            .as_ref()
+            .is_some_and(|client| !client.is_alive())
+        {
+            if let Some(mut client) = state.take_client() {
+                debug!("Kept session's CLI exited; reconnecting");
+                let _ = client.disconnect().await;
+            }
+        }
+        if state.client.is_none() {
+            let mut options = self.shared.options.clone();
+            if let Some(session_id) = state.session_id.clone() {
+                options.resume = Some(session_id);
+                options.continue_conversation = false;
+                options.fork_session = false;
+            }
+            let mut client = ClaudeClient::new(Some(options));
+            client.connect().await?;
+            state.client = Some(client);
+            self.shared.connected.notify_one();
+        }
+        Ok(KeptClient {
+            state,
+            shared: &self.shared,
+        })
+    }
+
+    /// Send a prompt and collect the response, connecting first if needed.
+    pub async fn query(&self, prompt: &str) -> Result<(String, ResultMessage)> {
+        let mut client = self.client().await?;
+        client.query(prompt).await?;
+        client.receive_response().await
+    }
+
+    /// Whether the client is connected and its CLI still running.
+    pub async fn is_connected(&self) -> bool {
+        self.shared
+            .state
+            .lock()
+            .await
+            .client
+            .as_ref()
+            .is_some_and(ClaudeClient::is_alive)
+    }
+
+    /// The session that is running, or that the next connection resumes.
+    pub async fn session_id(&self) -> Option<String> {
+        let state = self.shared.state.lock().await;
+        state
+            .client
+            .as_ref()
+            .and_then(ClaudeClient::session_id)
+            .or_else(|| state.session_id.clone())
+    }
+
+    /// How long the client may go unused before it's disconnected.
+    pub fn idle_timeout(&self) -> Duration {
+        self.shared.idle_timeout
+    }
+
+    /// Disconnect now, keeping the session to resume on next use.
+    pub async fn disconnect(&self) -> Result<()> {
+        let mut state = self.shared.state.lock().await;
+        let Some(mut client) = state.take_client() else {
+            return Ok(());
+        };
+        client.disconnect().await
+    }
+}
+
+impl Drop for SessionKeeper {
+    fn drop(&mut self) {
+        self.watcher.abort();
+    }
+}
+
+/// A [`SessionKeeper`]'s connected client, held for use.
+///
+/// Dereferences to [`ClaudeClient`]. Dropping it restarts the idle timer.
+pub struct KeptClient<'a> {
+    state: tokio::sync::MutexGuard<'a, State>,
+    shared: &'a Shared,
+}
+
+impl Deref for KeptClient<'_> {
+    type Target = ClaudeClient;
+
+    fn deref(&self) -> &ClaudeClient {
+        self.state
+            .client
+            .as_ref()
+            .expect("Kept client is connected")
+    }
+}
+
+impl DerefMut for KeptClient<'_> {
+    fn deref_mut(&mut self) -> &mut ClaudeClient {
+        self.state
+            .client
+            .as_mut()
+            .expect("Kept client is connected")
+    }
+}
+
+impl Drop for KeptClient<'_> {
+    fn drop(&mut self) {
+        if let Some(session_id) = self
+            .state
+            .client
+            .as_ref()
+            .and_then(ClaudeClient::session_id)
+        {
+            self.state.session_id = Some(session_id);
+        }
+        *self
+            .shared
+            .last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = self.shared.clock.now();
+    }
+}
@@ -129,37 +129,152 @@
 //! Configure queries with [`ClaudeAgentOptions`]:
 //!
 //! ```rust
-//! use claude_agents_sdk::{ClaudeAgentOptions, PermissionMode};
+//! use claude_agents_sdk::{BuiltinTool, ClaudeAgentOptions, PermissionMode};
 //!
 //! let options = ClaudeAgentOptions::new()
 //!     .with_model("claude-sonnet-4-20250514")
 //!     .with_system_prompt("You are a helpful coding assistant.")
 //!     .with_max_turns(10)
 //!     .with_permission_mode(PermissionMode::AcceptEdits)
-//!     .with_allowed_tools(vec!["Read".into(), "Write".into()])
+//!     .with_allowed_tools([BuiltinTool::Read, BuiltinTool::Write])
 //!     .with_timeout_secs(60);
 //! ```
 //!
 //! ## Feature Flags
 //!
-//! - **default**: Core SDK functionality
-//! - **mcp**: Enables MCP (Model Context Protocol) tool support for defining custom tools
+//! - **default**: Core SDK functionality, with `subprocess`
+//! - **subprocess**: Runs the CLI as a child process. Without it the crate builds for targets like
+//!   `wasm32`, and connects through a transport set with [`ClaudeAgentOptions::with_transport`],
+//!   such as a `RemoteTransport` to a CLI bridge
+//! - **api**: Runs the agent loop against the Anthropic API instead of the CLI, selected with
+//!   [`Backend::Api`], for environments where the CLI can't be installed
+//! - **bench**: Enables the `bench` module of harness functions for benchmarking parsing and option serialization
+//! - **bridge**: Enables the `bridge` module and `claude-agents-bridge` binary, serving the SDK as JSON-RPC over stdio for editors and other non-Rust hosts
+//! - **mcp**: Enables MCP (Model Context Protocol) tool support for defining custom tools, and the `tools::Tool` trait for wrapping tools from other frameworks
+//! - **mcp-client**: Enables the `mcp_client` module for calling external MCP servers directly
+//! - **metrics**: Forwards SDK metrics (see [`metrics`]) to the `metrics` crate facade by default
+//! - **otel**: Emits `tracing` spans for the query lifecycle, shaped for `tracing-opentelemetry`
+//! - **testing**: Enables the `testing` module with a `MockTransport` for unit tests without the CLI
+//! - **proptest**: Enables `testing::strategies`, proptest strategies for messages, content blocks, and options
+//! - **server**: Enables the `server` module, a tower service streaming queries over HTTP as server-sent events
+//! - **render-html**: Enables `render::html` and `render::markdown_to_html` for rendering messages as sanitized HTML
+//! - **yaml**: Reads YAML files in [`ClaudeAgentOptions::from_file`]
+//!
+//! ## OpenTelemetry
+//!
+//! With the `otel` feature the SDK opens `tracing` spans that an
+//! OpenTelemetry layer (e.g. `tracing-opentelemetry`) exports as a trace:
+//!
+//! | Span | Covers | Attributes |
+//! |------|--------|------------|
+//! | `claude.connect` | Spawning the CLI | `gen_ai.request.model` |
+//! | `claude.initialize` | The initialize handshake | |
+//! | `claude.turn` | A prompt through to its result | `gen_ai.request.model`, `gen_ai.response.model`, `claude.session_id`, `claude.cost_usd`, `gen_ai.usage.input_tokens`, `gen_ai.usage.output_tokens`, `claude.num_turns` |
+//! | `claude.tool` | A tool call within a turn | `gen_ai.tool.name`, `gen_ai.tool.call.id` |
+//! | `claude.control_request` | A control request in either direction | `claude.control.subtype`, `claude.control.request_id`, `claude.control.direction` |
+//!
+//! Failed operations set `otel.status_code = "error"`. Spans follow the
+//! caller's current span, so instrumenting the code that drives a
+//! [`ClaudeClient`] nests the whole run under it.
 
 #![warn(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod agent_files;
+mod attachments;
+mod checkpoints;
 mod client;
+mod compaction;
+mod conversation;
+mod diffs;
 mod errors;
+mod events;
+mod health;
+mod history;
+mod keepalive;
+mod keeper;
+mod mcp_config;
+mod mcp_status;
+mod options_delta;
+mod plan;
 mod query;
+mod redact;
+mod sessions;
+mod settings;
+mod stats;
+mod structured;
+mod subagents;
+mod turns;
 mod types;
+mod validate;
+mod wire_log;
 
 pub mod _internal;
+pub mod agents;
+pub mod batch;
+pub mod clock;
+pub mod config;
+pub mod control;
+pub mod metrics;
+pub mod orchestrator;
+pub mod python;
+pub mod recorder;
+pub mod render;
+pub mod repl;
+pub mod reporting;
 
 // Re-export public API
+pub use agent_files::PROJECT_AGENTS_DIR;
+pub use attachments::{Attachment, MAX_DOCUMENT_BYTES, MAX_IMAGE_BYTES, MAX_TEXT_BYTES};
+pub use checkpoints::Checkpoint;
 pub use client::{ClaudeClient, ClaudeClientBuilder, ClientGuard};
+pub use clock::Clock;
+pub use compaction::AutoCompactPolicy;
+pub use config::{HookRegistry, OptionsFile};
+pub use control::{
+    ControlErrorResponse, ControlHandle, ControlRequest, ControlRequestPayload, ControlResponse,
+    ControlResponsePayload, ControlSuccessResponse,
+};
+pub use conversation::{Conversation, ConversationError};
+pub use diffs::{content_hash, FileDiff};
 pub use errors::*;
-pub use query::{query, query_all, query_chunks, query_result};
+pub use events::{EventBus, SdkEvent};
+pub use health::{HealthReport, HEALTH_CHECK_TIMEOUT};
+pub use history::History;
+pub use keepalive::{PingSample, Pinger, PING_HISTORY_LEN};
+pub use keeper::{KeptClient, SessionKeeper};
+pub use mcp_config::{
+    McpConfigConflict, McpConfigLoader, McpConfigSource, MergedMcpConfig, PROJECT_MCP_CONFIG_FILE,
+};
+pub use mcp_status::{McpServerInfo, McpServerState, McpServerStatus, ToolDescriptor};
+pub use metrics::MetricsRecorder;
+pub use options_delta::{AppliedDelta, OptionsDelta};
+pub use plan::{TodoItem, TodoStatus};
+pub use query::{
+    query, query_all, query_chunks, query_in_dir, query_result, query_shared, query_structured,
+    query_with_attachments, query_with_model,
+};
+pub use redact::{Redactor, REDACTED};
+pub use sessions::{Session, SessionInfo, CLAUDE_CONFIG_DIR_ENV};
+pub use settings::{
+    CommandHook, Marketplace, MarketplaceSource, PermissionSettings, Settings, SettingsHookMatcher,
+    StatusLine,
+};
+pub use stats::{SessionStats, TurnStats};
+pub use structured::StructuredOutputPolicy;
+pub use subagents::{Subagent, SubagentStatus};
+pub use turns::AssistantTurn;
 pub use types::*;
+pub use validate::ValidationIssue;
+pub use wire_log::{WireDirection, WireEntry, WireLog};
+
+#[cfg(feature = "bench")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bench")))]
+pub mod bench;
+
+#[cfg(feature = "bridge")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub mod bridge;
 
 // Re-export MCP tools when feature enabled
 #[cfg(feature = "mcp")]
@@ -167,7 +282,27 @@ pub use types::*;
 pub mod mcp;
 
 #[cfg(feature = "mcp")]
-pub use mcp::{create_sdk_mcp_server, McpSdkServerConfig, SdkMcpTool, ToolAnnotations};
+pub use mcp::{
+    create_sdk_mcp_server, tool_name, EmbeddedResource, LogLevel, McpSdkServerConfig, Negotiation,
+    NegotiationError, SchemaViolation, SdkMcpServer, SdkMcpTool, ToolAnnotations, ToolArgSchema,
+    ToolCall, ToolContext, ToolError, ToolMiddleware, ToolOutcome,
+};
+
+#[cfg(feature = "mcp-client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mcp-client")))]
+pub mod mcp_client;
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub mod server;
+
+#[cfg(feature = "mcp")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mcp")))]
+pub mod tools;
+
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
 
 /// SDK version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -15,10 +15,14 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
+
+pub use tokio_util::sync::CancellationToken;
 
 /// Content type for tool responses.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +43,49 @@ pub enum ToolContent {
         #[serde(rename = "mimeType")]
         mime_type: String,
     },
+    /// Link to a resource the client can fetch.
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        /// Resource URI.
+        uri: String,
+        /// Resource name.
+        name: String,
+        /// MIME type of the resource.
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+    /// Resource contents embedded in the result.
+    #[serde(rename = "resource")]
+    Resource {
+        /// The embedded resource.
+        resource: EmbeddedResource,
+    },
+}
+
+/// Contents of a resource embedded in a tool result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddedResource {
+    /// Text resource contents.
+    Text {
+        /// Resource URI.
+        uri: String,
+        /// MIME type of the resource.
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        /// Text contents.
+        text: String,
+    },
+    /// Binary resource contents.
+    Blob {
+        /// Resource URI.
+        uri: String,
+        /// MIME type of the resource.
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        /// Base64-encoded contents.
+        blob: String,
+    },
 }
 
 impl ToolContent {
@@ -54,6 +101,90 @@ impl ToolContent {
             mime_type: mime_type.into(),
         }
     }
+
+    /// Create an image content item from raw bytes, base64-encoding them.
+    pub fn image_from_bytes(bytes: impl AsRef<[u8]>, mime_type: impl Into<String>) -> Self {
+        Self::image(encode_base64(bytes.as_ref()), mime_type)
+    }
+
+    /// Create an image content item from a file.
+    ///
+    /// The MIME type is inferred from the file extension (PNG, JPEG, GIF,
+    /// WebP, SVG, BMP). Other extensions yield an `InvalidInput` error.
+    pub fn image_from_path(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let mime_type = image_mime_type(path).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unrecognized image type: {}", path.display()),
+            )
+        })?;
+        let bytes = std::fs::read(path)?;
+        Ok(Self::image_from_bytes(bytes, mime_type))
+    }
+
+    /// Create a link to a resource.
+    ///
+    /// The link name defaults to the URI; set it on the variant directly to
+    /// use something friendlier.
+    pub fn resource(uri: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        let uri = uri.into();
+        Self::ResourceLink {
+            name: uri.clone(),
+            uri,
+            mime_type: Some(mime_type.into()),
+        }
+    }
+
+    /// Create an embedded text resource.
+    pub fn embedded_text(
+        uri: impl Into<String>,
+        mime_type: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Self {
+        Self::Resource {
+            resource: EmbeddedResource::Text {
+                uri: uri.into(),
+                mime_type: Some(mime_type.into()),
+                text: text.into(),
+            },
+        }
+    }
+
+    /// Create an embedded binary resource, base64-encoding the bytes.
+    pub fn embedded_blob(
+        uri: impl Into<String>,
+        mime_type: impl Into<String>,
+        bytes: impl AsRef<[u8]>,
+    ) -> Self {
+        Self::Resource {
+            resource: EmbeddedResource::Blob {
+                uri: uri.into(),
+                mime_type: Some(mime_type.into()),
+                blob: encode_base64(bytes.as_ref()),
+            },
+        }
+    }
+}
+
+/// Base64-encode bytes with the standard alphabet.
+fn encode_base64(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Infer an image MIME type from a file extension.
+fn image_mime_type(path: &std::path::Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        "bmp" => Some("image/bmp"),
+        _ => None,
+    }
 }
 
 /// Result from a tool execution.
@@ -64,6 +195,9 @@ pub struct ToolResult {
     /// Whether the result is an error.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Machine-readable result, sent as MCP `structuredContent`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
 }
 
 impl ToolResult {
@@ -72,14 +206,19 @@ impl ToolResult {
         Self {
             content: vec![ToolContent::text(text)],
             is_error: None,
+            structured_content: None,
         }
     }
 
     /// Create an error result.
+    ///
+    /// The message is returned to the model as tool output flagged with
+    /// `isError`. For failures of the call itself, return a [`ToolError`].
     pub fn error(message: impl Into<String>) -> Self {
         Self {
             content: vec![ToolContent::text(message)],
             is_error: Some(true),
+            structured_content: None,
         }
     }
 
@@ -88,10 +227,85 @@ impl ToolResult {
         Self {
             content,
             is_error: None,
+            structured_content: None,
+        }
+    }
+
+    /// Create a structured result.
+    ///
+    /// The value is sent as `structuredContent`, with its JSON serialization
+    /// also included as text content for clients that only read `content`.
+    pub fn structured(value: Value) -> Self {
+        Self {
+            content: vec![ToolContent::text(value.to_string())],
+            is_error: None,
+            structured_content: Some(value),
         }
     }
 }
 
+/// Outcome of a tool handler invocation.
+///
+/// `Ok` results (including [`ToolResult::error`]) are returned to the model as
+/// tool output; `Err` is reported to the CLI as a JSON-RPC error response.
+pub type ToolOutcome = Result<ToolResult, ToolError>;
+
+/// A protocol-level failure raised by a tool handler.
+///
+/// Use [`ToolResult::error`] for domain errors the model should see and react
+/// to (e.g. "file not found"). Use `ToolError` when the call itself could not
+/// be carried out; it becomes a JSON-RPC error with the given code, message,
+/// and optional data.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{message} (code {code})")]
+pub struct ToolError {
+    /// JSON-RPC error code.
+    pub code: i64,
+    /// Error message.
+    pub message: String,
+    /// Additional structured error data.
+    pub data: Option<Value>,
+}
+
+impl ToolError {
+    /// Create an error with an explicit JSON-RPC code.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Create an invalid-params error (`-32602`).
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    /// Create an internal error (`-32603`).
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+
+    /// Attach structured data to the error.
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl From<ToolResult> for ToolOutcome {
+    fn from(result: ToolResult) -> Self {
+        Ok(result)
+    }
+}
+
+impl From<serde_json::Error> for ToolError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::invalid_params(err.to_string())
+    }
+}
+
 /// Hints describing tool behavior for clients.
 ///
 /// All properties are hints and not guaranteed to faithfully describe tool behavior.
@@ -121,6 +335,43 @@ pub struct ToolAnnotations {
     pub open_world_hint: Option<bool>,
 }
 
+impl ToolAnnotations {
+    /// Create empty annotations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the human-readable title.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the read-only hint.
+    pub fn with_read_only_hint(mut self, read_only: bool) -> Self {
+        self.read_only_hint = Some(read_only);
+        self
+    }
+
+    /// Set the destructive hint.
+    pub fn with_destructive_hint(mut self, destructive: bool) -> Self {
+        self.destructive_hint = Some(destructive);
+        self
+    }
+
+    /// Set the idempotent hint.
+    pub fn with_idempotent_hint(mut self, idempotent: bool) -> Self {
+        self.idempotent_hint = Some(idempotent);
+        self
+    }
+
+    /// Set the open-world hint.
+    pub fn with_open_world_hint(mut self, open_world: bool) -> Self {
+        self.open_world_hint = Some(open_world);
+        self
+    }
+}
+
 /// Input schema for a tool.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolInputSchema {
@@ -198,15 +449,426 @@ impl ToolInputSchema {
         self.required.push(name.into());
         self
     }
+
+    /// Validate tool arguments against this schema.
+    ///
+    /// Checks that required properties are present and that declared property
+    /// types (`"type"` as a string or array of strings) match. Properties
+    /// without a declared type, and properties not in the schema, are accepted.
+    /// Returns every violation found rather than stopping at the first one.
+    pub fn validate(&self, input: &Value) -> Result<(), Vec<SchemaViolation>> {
+        if !json_type_matches(&self.schema_type, input) {
+            return Err(vec![SchemaViolation::new(
+                "",
+                format!(
+                    "expected {}, got {}",
+                    self.schema_type,
+                    json_type_name(input)
+                ),
+            )]);
+        }
+        let Some(object) = input.as_object() else {
+            return Ok(());
+        };
+
+        let mut violations = Vec::new();
+        for name in &self.required {
+            if !object.contains_key(name) {
+                violations.push(SchemaViolation::new(name, "missing required property"));
+            }
+        }
+
+        let mut names: Vec<&String> = self.properties.keys().collect();
+        names.sort();
+        for name in names {
+            let Some(value) = object.get(name) else {
+                continue;
+            };
+            let expected: Vec<&str> = match self.properties[name].get("type") {
+                Some(Value::String(t)) => vec![t.as_str()],
+                Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).collect(),
+                _ => continue,
+            };
+            if !expected.iter().any(|t| json_type_matches(t, value)) {
+                violations.push(SchemaViolation::new(
+                    name,
+                    format!(
+                        "expected {}, got {}",
+                        expected.join(" or "),
+                        json_type_name(value)
+                    ),
+                ));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// A single mismatch between tool arguments and a [`ToolInputSchema`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// Property name the violation refers to (empty for the arguments object itself).
+    pub path: String,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl SchemaViolation {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// JSON Schema of a [`tool!`](crate::tool) argument type, used for the
+/// argument's property in the generated [`ToolInputSchema`].
+///
+/// Arguments of types without an implementation, such as your own structs,
+/// get an empty schema, which accepts any value; their values are still
+/// checked when the argument is deserialized. Implement this trait for such
+/// a type to declare its schema.
+pub trait ToolArgSchema {
+    /// The type's schema, e.g. `{"type": "string"}`.
+    fn json_schema() -> Value;
+}
+
+macro_rules! tool_arg_schema {
+    ($schema_type:literal: $($type:ty),*) => {
+        $(
+            impl ToolArgSchema for $type {
+                fn json_schema() -> Value {
+                    serde_json::json!({"type": $schema_type})
+                }
+            }
+        )*
+    };
+}
+
+tool_arg_schema!("string": String, char, std::path::PathBuf);
+tool_arg_schema!("boolean": bool);
+tool_arg_schema!("integer": i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+tool_arg_schema!("number": f32, f64);
+
+impl<T: ToolArgSchema> ToolArgSchema for Vec<T> {
+    fn json_schema() -> Value {
+        serde_json::json!({"type": "array", "items": T::json_schema()})
+    }
+}
+
+impl<T: ToolArgSchema> ToolArgSchema for HashMap<String, T> {
+    fn json_schema() -> Value {
+        serde_json::json!({"type": "object", "additionalProperties": T::json_schema()})
+    }
+}
+
+impl<T: ToolArgSchema> ToolArgSchema for Option<T> {
+    fn json_schema() -> Value {
+        let mut schema = T::json_schema();
+        if let Some(Value::String(t)) = schema.get("type").cloned() {
+            schema["type"] = serde_json::json!([t, "null"]);
+        }
+        schema
+    }
+}
+
+impl ToolArgSchema for Value {
+    fn json_schema() -> Value {
+        serde_json::json!({})
+    }
+}
+
+/// Picks an argument type's [`ToolArgSchema`] in [`tool!`](crate::tool),
+/// falling back to an empty schema for types without one.
+#[doc(hidden)]
+pub struct ArgSchema<T>(pub std::marker::PhantomData<T>);
+
+#[doc(hidden)]
+pub trait TypedArgSchema {
+    fn json_schema(&self) -> Value;
+}
+
+impl<T: ToolArgSchema> TypedArgSchema for ArgSchema<T> {
+    fn json_schema(&self) -> Value {
+        T::json_schema()
+    }
+}
+
+#[doc(hidden)]
+pub trait AnyArgSchema {
+    fn json_schema(&self) -> Value;
+}
+
+// Implemented on the reference, so method lookup on `&ArgSchema<T>` prefers
+// `TypedArgSchema` when `T` has a schema.
+impl<T> AnyArgSchema for &ArgSchema<T> {
+    fn json_schema(&self) -> Value {
+        serde_json::json!({})
+    }
+}
+
+#[doc(hidden)]
+pub trait OptionalArg {
+    fn is_required(&self) -> bool;
+}
+
+impl<T> OptionalArg for ArgSchema<Option<T>> {
+    fn is_required(&self) -> bool {
+        false
+    }
+}
+
+#[doc(hidden)]
+pub trait RequiredArg {
+    fn is_required(&self) -> bool;
+}
+
+// As with `AnyArgSchema`, only used when `T` isn't an `Option`.
+impl<T> RequiredArg for &ArgSchema<T> {
+    fn is_required(&self) -> bool {
+        true
+    }
+}
+
+/// Check whether a JSON value matches a JSON Schema primitive type name.
+///
+/// Unknown type names are treated as matching so newer schema keywords don't
+/// cause spurious rejections.
+fn json_type_matches(schema_type: &str, value: &Value) -> bool {
+    match schema_type {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// JSON Schema type name of a value, for error messages.
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Severity of an MCP log message, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Detailed debugging information.
+    Debug,
+    /// General informational messages.
+    Info,
+    /// Normal but significant events.
+    Notice,
+    /// Warning conditions.
+    Warning,
+    /// Error conditions.
+    Error,
+    /// Critical conditions.
+    Critical,
+    /// Action must be taken immediately.
+    Alert,
+    /// System is unusable.
+    Emergency,
+}
+
+/// Sends a tool's log messages as MCP `notifications/message`.
+#[derive(Debug, Clone)]
+struct ToolLogger {
+    logger: String,
+    notifications: broadcast::Sender<Value>,
+    min_level: Arc<RwLock<LogLevel>>,
+    mirror_to_tracing: bool,
+}
+
+impl ToolLogger {
+    fn log(&self, level: LogLevel, data: Value) {
+        if self.mirror_to_tracing {
+            match level {
+                LogLevel::Debug => debug!(logger = %self.logger, "{}", data),
+                LogLevel::Info | LogLevel::Notice => info!(logger = %self.logger, "{}", data),
+                LogLevel::Warning => warn!(logger = %self.logger, "{}", data),
+                _ => error!(logger = %self.logger, "{}", data),
+            }
+        }
+        if level < *self.min_level.read().unwrap_or_else(|e| e.into_inner()) {
+            return;
+        }
+        let _ = self.notifications.send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {"level": level, "logger": self.logger, "data": data}
+        }));
+    }
+}
+
+/// Per-invocation context passed to tool handlers.
+///
+/// The context carries a [`CancellationToken`] that is cancelled when the CLI
+/// sends an MCP `notifications/cancelled` for the call, when the client is
+/// interrupted, or when the session ends. Long-running handlers should check
+/// it (or `select!` on [`ToolContext::cancelled`]) to stop work early.
+///
+/// When the tool runs on an [`SdkMcpServer`], the context can also emit MCP
+/// log messages (e.g. [`ToolContext::log_info`]), which are sent back to the
+/// CLI so they show up alongside the agent transcript.
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    cancellation: CancellationToken,
+    logger: Option<ToolLogger>,
+}
+
+impl ToolContext {
+    /// Create a context bound to the given cancellation token.
+    pub fn new(cancellation: CancellationToken) -> Self {
+        Self {
+            cancellation,
+            logger: None,
+        }
+    }
+
+    /// Attach a logger that emits MCP log notifications.
+    fn with_logger(mut self, logger: ToolLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Emit an MCP log message at the given level.
+    ///
+    /// Messages below the level requested by the client via
+    /// `logging/setLevel` are dropped. Outside an [`SdkMcpServer`] this is a
+    /// no-op.
+    pub fn log(&self, level: LogLevel, data: impl Into<Value>) {
+        if let Some(logger) = &self.logger {
+            logger.log(level, data.into());
+        }
+    }
+
+    /// Emit a debug-level log message.
+    pub fn log_debug(&self, message: impl Into<String>) {
+        self.log(LogLevel::Debug, message.into());
+    }
+
+    /// Emit an info-level log message.
+    pub fn log_info(&self, message: impl Into<String>) {
+        self.log(LogLevel::Info, message.into());
+    }
+
+    /// Emit a warning-level log message.
+    pub fn log_warning(&self, message: impl Into<String>) {
+        self.log(LogLevel::Warning, message.into());
+    }
+
+    /// Emit an error-level log message.
+    pub fn log_error(&self, message: impl Into<String>) {
+        self.log(LogLevel::Error, message.into());
+    }
+
+    /// Get the cancellation token for this invocation.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Check whether this invocation has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Wait until this invocation is cancelled.
+    pub async fn cancelled(&self) {
+        self.cancellation.cancelled().await
+    }
 }
 
 /// Type alias for tool handler functions.
-pub type ToolHandler =
-    Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = ToolResult> + Send>> + Send + Sync>;
+pub type ToolHandler = Arc<
+    dyn Fn(Value, ToolContext) -> Pin<Box<dyn Future<Output = ToolOutcome> + Send>> + Send + Sync,
+>;
+
+/// A tool invocation as seen by [`ToolMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// Name of the SDK MCP server handling the call.
+    pub server_name: String,
+    /// Name of the tool being called.
+    pub tool_name: String,
+    /// Tool arguments (already validated against the input schema).
+    pub arguments: Value,
+    /// Per-invocation context.
+    pub context: ToolContext,
+}
+
+/// Middleware wrapping every tool invocation on an [`SdkMcpServer`].
+///
+/// A middleware receives the [`ToolCall`] and the [`Next`] step in the chain.
+/// It can inspect or rewrite the call, short-circuit by returning without
+/// calling `next.run(call)`, and inspect or rewrite the outcome.
+pub type ToolMiddleware =
+    Arc<dyn Fn(ToolCall, Next) -> Pin<Box<dyn Future<Output = ToolOutcome> + Send>> + Send + Sync>;
+
+/// The remainder of a middleware chain, ending in the tool handler.
+#[derive(Clone)]
+pub struct Next {
+    middleware: Arc<Vec<ToolMiddleware>>,
+    index: usize,
+    handler: ToolHandler,
+}
+
+impl Next {
+    /// Invoke the next middleware, or the tool handler if none remain.
+    pub fn run(self, call: ToolCall) -> Pin<Box<dyn Future<Output = ToolOutcome> + Send>> {
+        match self.middleware.get(self.index).cloned() {
+            Some(middleware) => {
+                let next = Next {
+                    index: self.index + 1,
+                    ..self
+                };
+                middleware(call, next)
+            }
+            None => (self.handler)(call.arguments, call.context),
+        }
+    }
+}
+
+impl std::fmt::Debug for Next {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &(self.middleware.len() - self.index))
+            .finish()
+    }
+}
 
 /// SDK MCP tool definition.
 ///
 /// Represents a tool that can be registered with the SDK for in-process execution.
+#[derive(Clone)]
 pub struct SdkMcpTool {
     /// Tool name.
     pub name: String,
@@ -218,6 +880,8 @@ pub struct SdkMcpTool {
     pub handler: ToolHandler,
     /// Optional annotations describing tool behavior.
     pub annotations: Option<ToolAnnotations>,
+    /// Optional schema for the tool's structured output.
+    pub output_schema: Option<ToolInputSchema>,
 }
 
 impl SdkMcpTool {
@@ -230,14 +894,58 @@ impl SdkMcpTool {
     ) -> Self
     where
         F: Fn(Value) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = ToolResult> + Send + 'static,
+        Fut: Future<Output = ToolOutcome> + Send + 'static,
     {
         Self {
             name: name.into(),
             description: description.into(),
             input_schema,
-            handler: Arc::new(move |input| Box::pin(handler(input))),
+            handler: Arc::new(move |input, _ctx| Box::pin(handler(input))),
             annotations: None,
+            output_schema: None,
+        }
+    }
+
+    /// Create a new tool whose handler receives a [`ToolContext`].
+    ///
+    /// Use this when the handler needs to observe cancellation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use claude_agents_sdk::mcp::{SdkMcpTool, ToolInputSchema, ToolResult};
+    ///
+    /// let tool = SdkMcpTool::new_with_context(
+    ///     "slow",
+    ///     "A slow tool",
+    ///     ToolInputSchema::object(),
+    ///     |_input, ctx| async move {
+    ///         tokio::select! {
+    ///             _ = ctx.cancelled() => Ok(ToolResult::error("cancelled")),
+    ///             _ = tokio::time::sleep(std::time::Duration::from_secs(5)) => {
+    ///                 Ok(ToolResult::text("done"))
+    ///             }
+    ///         }
+    ///     },
+    /// );
+    /// ```
+    pub fn new_with_context<F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: ToolInputSchema,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value, ToolContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolOutcome> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+            handler: Arc::new(move |input, ctx| Box::pin(handler(input, ctx))),
+            annotations: None,
+            output_schema: None,
         }
     }
 
@@ -246,6 +954,39 @@ impl SdkMcpTool {
         self.annotations = Some(annotations);
         self
     }
+
+    /// Declare the schema of this tool's structured output.
+    ///
+    /// Results with `structured_content` are validated against it; a mismatch
+    /// is reported to the CLI as an internal error.
+    pub fn with_output_schema(mut self, schema: ToolInputSchema) -> Self {
+        self.output_schema = Some(schema);
+        self
+    }
+
+    /// Mark the tool as read-only (it does not modify its environment).
+    pub fn read_only(mut self) -> Self {
+        self.annotations
+            .get_or_insert_with(Default::default)
+            .read_only_hint = Some(true);
+        self
+    }
+
+    /// Mark the tool as performing destructive updates.
+    pub fn destructive(mut self) -> Self {
+        let annotations = self.annotations.get_or_insert_with(Default::default);
+        annotations.read_only_hint = Some(false);
+        annotations.destructive_hint = Some(true);
+        self
+    }
+
+    /// Mark whether the tool interacts with external entities.
+    pub fn open_world(mut self, open_world: bool) -> Self {
+        self.annotations
+            .get_or_insert_with(Default::default)
+            .open_world_hint = Some(open_world);
+        self
+    }
 }
 
 impl std::fmt::Debug for SdkMcpTool {
@@ -254,6 +995,8 @@ impl std::fmt::Debug for SdkMcpTool {
             .field("name", &self.name)
             .field("description", &self.description)
             .field("input_schema", &self.input_schema)
+            .field("annotations", &self.annotations)
+            .field("output_schema", &self.output_schema)
             .finish()
     }
 }
@@ -299,7 +1042,7 @@ pub struct McpSdkServerConfig {
 ///     |input| async move {
 ///         let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
 ///         let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
-///         ToolResult::text(format!("{}", a + b))
+///         Ok(ToolResult::text(format!("{}", a + b)))
 ///     },
 /// );
 ///
@@ -319,8 +1062,727 @@ pub fn create_sdk_mcp_server(
     (config, tools)
 }
 
+/// Build the namespaced name the CLI uses for an MCP tool.
+///
+/// Use this for `allowed_tools`/`disallowed_tools` entries and when matching
+/// tool names in permission callbacks or hooks. To allow every tool on a
+/// server, see [`ClaudeAgentOptions::allow_mcp_server`](crate::ClaudeAgentOptions::allow_mcp_server).
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::mcp::tool_name;
+///
+/// assert_eq!(tool_name("calc", "add"), "mcp__calc__add");
+/// ```
+pub fn tool_name(server: &str, tool: &str) -> String {
+    format!("mcp__{}__{}", server, tool)
+}
+
+/// Latest MCP protocol version implemented by [`SdkMcpServer`].
+pub const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// MCP protocol versions [`SdkMcpServer`] can speak, newest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// The outcome of an MCP `initialize` exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negotiation {
+    /// Protocol version the server answered with.
+    pub protocol_version: String,
+    /// Capabilities the client advertised.
+    pub client_capabilities: Value,
+    /// The client's `clientInfo` (name and version), if sent.
+    pub client_info: Option<Value>,
+}
+
+/// Why an MCP `initialize` exchange failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NegotiationError {
+    /// The server answered with a protocol version the client does not speak.
+    #[error("unsupported MCP protocol version {requested} (supported: {})", supported.join(", "))]
+    UnsupportedVersion {
+        /// Version the server answered with.
+        requested: String,
+        /// Versions the client supports.
+        supported: Vec<String>,
+    },
+}
+
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for internal errors.
+const INTERNAL_ERROR: i64 = -32603;
+/// JSON-RPC error code for cancelled requests.
+const REQUEST_CANCELLED: i64 = -32800;
+/// JSON-RPC error code for unparseable messages.
+const PARSE_ERROR: i64 = -32700;
+
+/// An in-process MCP server that dispatches JSON-RPC messages to SDK tools.
+///
+/// Register the server on [`ClaudeAgentOptions`](crate::ClaudeAgentOptions) with
+/// `with_sdk_mcp_server()`; the CLI then routes `mcp_message` control requests
+/// for this server name to [`SdkMcpServer::handle_message`].
+///
+/// In-flight tool calls are tracked so they can be cancelled, either by an MCP
+/// `notifications/cancelled` from the CLI or by [`SdkMcpServer::cancel_all`].
+///
+/// Tools can be added and removed while a session is running. Keep an
+/// `Arc<SdkMcpServer>` handle and call [`SdkMcpServer::add_tool`] or
+/// [`SdkMcpServer::remove_tool`]; the server emits
+/// `notifications/tools/list_changed` so the CLI refreshes its tool list.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::ClaudeAgentOptions;
+/// use claude_agents_sdk::mcp::{SdkMcpServer, SdkMcpTool, ToolInputSchema, ToolResult};
+///
+/// let echo = SdkMcpTool::new(
+///     "echo",
+///     "Echo the input",
+///     ToolInputSchema::object().string_property("text", "Text to echo"),
+///     |input| async move {
+///         Ok(ToolResult::text(input["text"].as_str().unwrap_or_default()))
+///     },
+/// );
+///
+/// let server = SdkMcpServer::new("utils", "1.0.0", vec![echo]);
+/// let options = ClaudeAgentOptions::new().with_sdk_mcp_server(server);
+/// ```
+pub struct SdkMcpServer {
+    name: String,
+    version: String,
+    tools: RwLock<Vec<SdkMcpTool>>,
+    in_flight: Mutex<HashMap<String, CancellationToken>>,
+    notifications: broadcast::Sender<Value>,
+    middleware: Arc<Vec<ToolMiddleware>>,
+    log_level: Arc<RwLock<LogLevel>>,
+    mirror_logs_to_tracing: bool,
+    protocol_versions: Vec<String>,
+    negotiation: RwLock<Option<Negotiation>>,
+}
+
+/// Capacity of the server-initiated notification channel.
+const NOTIFICATION_CAPACITY: usize = 16;
+
+impl SdkMcpServer {
+    /// Create a new server with the given tools.
+    pub fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        tools: Vec<SdkMcpTool>,
+    ) -> Self {
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        Self {
+            name: name.into(),
+            version: version.into(),
+            tools: RwLock::new(tools),
+            in_flight: Mutex::new(HashMap::new()),
+            notifications,
+            middleware: Arc::new(Vec::new()),
+            log_level: Arc::new(RwLock::new(LogLevel::Debug)),
+            mirror_logs_to_tracing: false,
+            protocol_versions: SUPPORTED_PROTOCOL_VERSIONS
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            negotiation: RwLock::new(None),
+        }
+    }
+
+    /// Restrict the protocol versions this server speaks, in order of
+    /// preference.
+    ///
+    /// A client requesting any other version is answered with the first,
+    /// as the MCP spec requires; the client decides whether to go on.
+    pub fn with_protocol_versions<I, S>(mut self, versions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.protocol_versions = versions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The result of the most recent `initialize` exchange.
+    ///
+    /// `None` until the client has initialized.
+    pub fn negotiation(&self) -> Option<Negotiation> {
+        self.negotiation
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Also emit tool log messages through `tracing`.
+    ///
+    /// Mirrored messages are not subject to the client's `logging/setLevel`.
+    pub fn with_tracing_logs(mut self, enabled: bool) -> Self {
+        self.mirror_logs_to_tracing = enabled;
+        self
+    }
+
+    /// Wrap every tool invocation on this server with a middleware.
+    ///
+    /// Middleware registered first runs outermost. Input validation happens
+    /// before the chain runs; output schema validation happens after.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use claude_agents_sdk::mcp::SdkMcpServer;
+    ///
+    /// let server = SdkMcpServer::new("utils", "1.0.0", vec![]).with_middleware(
+    ///     |call, next| async move {
+    ///         let tool = call.tool_name.clone();
+    ///         let started = std::time::Instant::now();
+    ///         let outcome = next.run(call).await;
+    ///         eprintln!("{} took {:?}", tool, started.elapsed());
+    ///         outcome
+    ///     },
+    /// );
+    /// ```
+    pub fn with_middleware<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(ToolCall, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolOutcome> + Send + 'static,
+    {
+        Arc::make_mut(&mut self.middleware)
+            .push(Arc::new(move |call, next| Box::pin(middleware(call, next))));
+        self
+    }
+
+    /// Get the server name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the server version.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Get a snapshot of the registered tools.
+    pub fn tools(&self) -> Vec<SdkMcpTool> {
+        self.tools.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Check whether a tool with the given name is registered.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|t| t.name == name)
+    }
+
+    /// Register a tool, replacing any existing tool with the same name.
+    ///
+    /// Emits `notifications/tools/list_changed`.
+    pub fn add_tool(&self, tool: SdkMcpTool) {
+        {
+            let mut tools = self.tools.write().unwrap_or_else(|e| e.into_inner());
+            match tools.iter_mut().find(|t| t.name == tool.name) {
+                Some(existing) => *existing = tool,
+                None => tools.push(tool),
+            }
+        }
+        self.notify_tools_changed();
+    }
+
+    /// Unregister a tool by name, returning it if it was registered.
+    ///
+    /// Emits `notifications/tools/list_changed` when a tool was removed.
+    /// Calls to the tool that are already running are not affected.
+    pub fn remove_tool(&self, name: &str) -> Option<SdkMcpTool> {
+        let removed = {
+            let mut tools = self.tools.write().unwrap_or_else(|e| e.into_inner());
+            let index = tools.iter().position(|t| t.name == name)?;
+            tools.remove(index)
+        };
+        self.notify_tools_changed();
+        Some(removed)
+    }
+
+    /// Subscribe to JSON-RPC notifications initiated by this server.
+    ///
+    /// The client forwards these to the CLI; most callers don't need this.
+    pub fn subscribe(&self) -> broadcast::Receiver<Value> {
+        self.notifications.subscribe()
+    }
+
+    /// Emit `notifications/tools/list_changed` to subscribers.
+    fn notify_tools_changed(&self) {
+        debug!("Tool list changed on SDK MCP server '{}'", self.name);
+        // No subscribers just means no session is running yet.
+        let _ = self.notifications.send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed"
+        }));
+    }
+
+    /// Get the configuration entry the CLI expects for this server.
+    pub fn config(&self) -> McpSdkServerConfig {
+        McpSdkServerConfig {
+            server_type: "sdk".to_string(),
+            name: self.name.clone(),
+            version: self.version.clone(),
+        }
+    }
+
+    /// Number of tool calls currently executing.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// Cancel every in-flight tool call.
+    ///
+    /// Called when the client is interrupted or the session ends.
+    pub fn cancel_all(&self) {
+        let in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        for token in in_flight.values() {
+            token.cancel();
+        }
+    }
+
+    /// Handle a JSON-RPC message addressed to this server.
+    ///
+    /// Returns the JSON-RPC response, or `None` for notifications.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let method = message.get("method").and_then(|v| v.as_str()).unwrap_or("");
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        debug!("SDK MCP server '{}' received {}", self.name, method);
+
+        match method {
+            "initialize" => {
+                let id = id?;
+                Some(self.initialize(id, &params))
+            }
+            "tools/list" => id.map(|id| {
+                let tools: Vec<Value> = self
+                    .tools()
+                    .iter()
+                    .map(|tool| {
+                        let mut entry = serde_json::json!({
+                            "name": tool.name,
+                            "description": tool.description,
+                            "inputSchema": tool.input_schema,
+                        });
+                        if let Some(output_schema) = &tool.output_schema {
+                            entry["outputSchema"] =
+                                serde_json::to_value(output_schema).unwrap_or_default();
+                        }
+                        if let Some(annotations) = &tool.annotations {
+                            entry["annotations"] =
+                                serde_json::to_value(annotations).unwrap_or_default();
+                        }
+                        entry
+                    })
+                    .collect();
+                jsonrpc_result(id, serde_json::json!({ "tools": tools }))
+            }),
+            "tools/call" => {
+                let id = id?;
+                Some(self.call_tool(id, params).await)
+            }
+            "logging/setLevel" => {
+                let id = id?;
+                let level = params
+                    .get("level")
+                    .cloned()
+                    .and_then(|v| serde_json::from_value::<LogLevel>(v).ok());
+                Some(match level {
+                    Some(level) => {
+                        *self.log_level.write().unwrap_or_else(|e| e.into_inner()) = level;
+                        jsonrpc_result(id, serde_json::json!({}))
+                    }
+                    None => jsonrpc_error(id, INVALID_PARAMS, "Invalid log level"),
+                })
+            }
+            "notifications/cancelled" => {
+                if let Some(request_id) = params.get("requestId") {
+                    self.cancel_request(request_id);
+                }
+                None
+            }
+            _ if id.is_none() => None,
+            other => Some(jsonrpc_error(
+                id.unwrap_or(Value::Null),
+                METHOD_NOT_FOUND,
+                format!("Method '{}' not found", other),
+            )),
+        }
+    }
+
+    /// Handle `initialize`: agree on a protocol version and advertise
+    /// capabilities.
+    ///
+    /// A requested version the server speaks is echoed back. Any other,
+    /// or none, is answered with the server's preferred version, leaving
+    /// the client to disconnect if it can't speak it.
+    fn initialize(&self, id: Value, params: &Value) -> Value {
+        let preferred = self
+            .protocol_versions
+            .first()
+            .map(String::as_str)
+            .unwrap_or(MCP_PROTOCOL_VERSION);
+        let version = match params.get("protocolVersion").and_then(|v| v.as_str()) {
+            Some(requested) if self.protocol_versions.iter().any(|v| v == requested) => requested,
+            Some(requested) => {
+                debug!(
+                    "SDK MCP server '{}' does not speak protocol {}; offering {}",
+                    self.name, requested, preferred
+                );
+                preferred
+            }
+            None => preferred,
+        }
+        .to_string();
+
+        debug!(
+            "SDK MCP server '{}' negotiated protocol {}",
+            self.name, version
+        );
+        *self.negotiation.write().unwrap_or_else(|e| e.into_inner()) = Some(Negotiation {
+            protocol_version: version.clone(),
+            client_capabilities: params
+                .get("capabilities")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+            client_info: params.get("clientInfo").cloned(),
+        });
+
+        jsonrpc_result(
+            id,
+            serde_json::json!({
+                "protocolVersion": version,
+                "capabilities": {"tools": {"listChanged": true}, "logging": {}},
+                "serverInfo": {"name": self.name, "version": self.version}
+            }),
+        )
+    }
+
+    /// Execute a `tools/call` request.
+    async fn call_tool(&self, id: Value, params: Value) -> Value {
+        let Some(name) = params.get("name").and_then(|v| v.as_str()) else {
+            return jsonrpc_error(id, INVALID_PARAMS, "Missing tool name");
+        };
+        let tool = self
+            .tools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|t| t.name == name)
+            .cloned();
+        let Some(tool) = tool else {
+            return jsonrpc_error(id, INVALID_PARAMS, format!("Tool '{}' not found", name));
+        };
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        if let Err(violations) = tool.input_schema.validate(&arguments) {
+            let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            debug!(
+                "Rejected call to '{}' on '{}': {}",
+                name,
+                self.name,
+                details.join("; ")
+            );
+            return jsonrpc_error_with_data(
+                id,
+                INVALID_PARAMS,
+                format!(
+                    "Invalid arguments for tool '{}': {}",
+                    name,
+                    details.join("; ")
+                ),
+                serde_json::json!({ "violations": violations }),
+            );
+        }
+
+        let token = CancellationToken::new();
+        let _guard = InFlightGuard::register(self, id.to_string(), token.clone());
+
+        let next = Next {
+            middleware: Arc::clone(&self.middleware),
+            index: 0,
+            handler: Arc::clone(&tool.handler),
+        };
+        let future = next.run(ToolCall {
+            server_name: self.name.clone(),
+            tool_name: name.to_string(),
+            arguments,
+            context: ToolContext::new(token.clone()).with_logger(ToolLogger {
+                logger: name.to_string(),
+                notifications: self.notifications.clone(),
+                min_level: Arc::clone(&self.log_level),
+                mirror_to_tracing: self.mirror_logs_to_tracing,
+            }),
+        });
+        let outcome = tokio::select! {
+            biased;
+            _ = token.cancelled() => None,
+            result = future => Some(result),
+        };
+
+        match outcome {
+            Some(Ok(result)) => {
+                if let (Some(schema), Some(structured)) =
+                    (&tool.output_schema, &result.structured_content)
+                {
+                    if let Err(violations) = schema.validate(structured) {
+                        let details: Vec<String> =
+                            violations.iter().map(ToString::to_string).collect();
+                        warn!(
+                            "Tool '{}' on '{}' returned output not matching its schema: {}",
+                            name,
+                            self.name,
+                            details.join("; ")
+                        );
+                        return jsonrpc_error_with_data(
+                            id,
+                            INTERNAL_ERROR,
+                            format!(
+                                "Tool '{}' returned invalid structured output: {}",
+                                name,
+                                details.join("; ")
+                            ),
+                            serde_json::json!({ "violations": violations }),
+                        );
+                    }
+                }
+
+                let mut body = serde_json::json!({ "content": result.content });
+                if let Some(is_error) = result.is_error {
+                    body["isError"] = Value::Bool(is_error);
+                }
+                if let Some(structured) = result.structured_content {
+                    body["structuredContent"] = structured;
+                }
+                jsonrpc_result(id, body)
+            }
+            Some(Err(err)) => {
+                debug!("Tool '{}' on '{}' failed: {}", name, self.name, err);
+                match err.data {
+                    Some(data) => jsonrpc_error_with_data(id, err.code, err.message, data),
+                    None => jsonrpc_error(id, err.code, err.message),
+                }
+            }
+            None => {
+                debug!("Tool call '{}' on '{}' was cancelled", name, self.name);
+                jsonrpc_error(id, REQUEST_CANCELLED, "Request cancelled")
+            }
+        }
+    }
+
+    /// Serve this server as a standalone MCP server over stdin/stdout.
+    ///
+    /// Reads newline-delimited JSON-RPC messages from stdin and writes
+    /// responses and notifications to stdout, so the same tools can be used
+    /// from an external MCP client (e.g. a `stdio` entry in the CLI's
+    /// `mcp_servers`). Returns when stdin is closed.
+    ///
+    /// Logging must not go to stdout while serving, since stdout carries the
+    /// protocol.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use claude_agents_sdk::mcp::{SdkMcpServer, SdkMcpTool, ToolInputSchema, ToolResult};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let ping = SdkMcpTool::new("ping", "Reply with pong", ToolInputSchema::object(), |_| async {
+    ///         Ok(ToolResult::text("pong"))
+    ///     });
+    ///     SdkMcpServer::new("utils", "1.0.0", vec![ping]).serve_stdio().await
+    /// }
+    /// ```
+    #[cfg(feature = "subprocess")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "subprocess")))]
+    pub async fn serve_stdio(&self) -> std::io::Result<()> {
+        self.serve(
+            tokio::io::BufReader::new(tokio::io::stdin()),
+            tokio::io::stdout(),
+        )
+        .await
+    }
+
+    /// Serve this server over an arbitrary newline-delimited JSON-RPC stream.
+    ///
+    /// Requests are handled concurrently so that a later
+    /// `notifications/cancelled` can interrupt a running tool call. When the
+    /// reader reaches EOF, in-flight calls are cancelled and their responses
+    /// flushed before returning.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> std::io::Result<()>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use futures::stream::{FuturesUnordered, StreamExt};
+        use tokio::io::AsyncBufReadExt;
+
+        let mut lines = reader.lines();
+        let mut notifications = self.subscribe();
+        let mut in_flight = FuturesUnordered::new();
+        let mut reading = true;
+        let shutdown = CancellationToken::new();
+
+        // Requests that are still pending when input closes are answered
+        // with a cancellation error instead of being left running.
+        let dispatch = |message: Value| {
+            let shutdown = shutdown.clone();
+            async move {
+                let id = message.get("id").cloned();
+                tokio::select! {
+                    biased;
+                    response = self.handle_message(message) => response,
+                    _ = shutdown.cancelled() => {
+                        id.map(|id| jsonrpc_error(id, REQUEST_CANCELLED, "Request cancelled"))
+                    }
+                }
+            }
+        };
+
+        while reading || !in_flight.is_empty() {
+            tokio::select! {
+                line = lines.next_line(), if reading => match line? {
+                    Some(line) if line.trim().is_empty() => {}
+                    Some(line) => match serde_json::from_str::<Value>(&line) {
+                        Ok(message) => in_flight.push(dispatch(message)),
+                        Err(e) => {
+                            let response = jsonrpc_error(
+                                Value::Null,
+                                PARSE_ERROR,
+                                format!("Parse error: {}", e),
+                            );
+                            write_json_line(&mut writer, &response).await?;
+                        }
+                    },
+                    None => {
+                        debug!("SDK MCP server '{}' input closed", self.name);
+                        reading = false;
+                        shutdown.cancel();
+                    }
+                },
+                Some(response) = in_flight.next(), if !in_flight.is_empty() => {
+                    if let Some(response) = response {
+                        write_json_line(&mut writer, &response).await?;
+                    }
+                }
+                Ok(notification) = notifications.recv() => {
+                    write_json_line(&mut writer, &notification).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a single in-flight request by its JSON-RPC ID.
+    fn cancel_request(&self, request_id: &Value) {
+        let in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        match in_flight.get(&request_id.to_string()) {
+            Some(token) => token.cancel(),
+            None => warn!(
+                "Cancellation for unknown request {} on '{}'",
+                request_id, self.name
+            ),
+        }
+    }
+}
+
+/// Tracks a tool call in the server's in-flight map for as long as it lives,
+/// so calls abandoned mid-await (e.g. an aborted task) are still removed.
+struct InFlightGuard<'a> {
+    server: &'a SdkMcpServer,
+    key: String,
+}
+
+impl<'a> InFlightGuard<'a> {
+    fn register(server: &'a SdkMcpServer, key: String, token: CancellationToken) -> Self {
+        server
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.clone(), token);
+        Self { server, key }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.server
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+    }
+}
+
+impl std::fmt::Debug for SdkMcpServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SdkMcpServer")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("tools", &self.tools)
+            .field("middleware", &self.middleware.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Write a JSON value followed by a newline and flush.
+async fn write_json_line<W>(writer: &mut W, value: &Value) -> std::io::Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let mut line = value.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Build a JSON-RPC success response.
+fn jsonrpc_result(id: Value, result: Value) -> Value {
+    serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+/// Build a JSON-RPC error response.
+fn jsonrpc_error(id: Value, code: i64, message: impl Into<String>) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message.into()}
+    })
+}
+
+/// Build a JSON-RPC error response carrying additional `data`.
+fn jsonrpc_error_with_data(id: Value, code: i64, message: impl Into<String>, data: Value) -> Value {
+    let mut response = jsonrpc_error(id, code, message);
+    response["error"]["data"] = data;
+    response
+}
+
 /// Macro for defining tools with a simpler syntax.
 ///
+/// Each argument is a property of the tool's input schema, typed by its
+/// [`ToolArgSchema`](crate::mcp::ToolArgSchema) and required unless it is an
+/// `Option`. An argument that doesn't deserialize as its type fails the call
+/// with [`ToolError::invalid_params`](crate::mcp::ToolError::invalid_params)
+/// naming it.
+///
 /// # Examples
 ///
 /// ```rust,ignore
@@ -340,12 +1802,21 @@ macro_rules! tool {
         fn $name:ident($($arg:ident: $type:ty),*) -> $ret:ty $body:block
     ) => {
         {
-            use $crate::mcp::{SdkMcpTool, ToolInputSchema, ToolResult};
+            #[allow(unused_imports)]
+            use $crate::mcp::{
+                AnyArgSchema, ArgSchema, OptionalArg, RequiredArg, SdkMcpTool, ToolError,
+                ToolInputSchema, ToolResult, TypedArgSchema,
+            };
 
             let mut schema = ToolInputSchema::object();
             $(
-                schema = schema.string_property(stringify!($arg), "");
-                schema = schema.required_property(stringify!($arg));
+                let arg = ArgSchema::<$type>(std::marker::PhantomData);
+                schema
+                    .properties
+                    .insert(stringify!($arg).to_string(), (&arg).json_schema());
+                if (&arg).is_required() {
+                    schema = schema.required_property(stringify!($arg));
+                }
             )*
 
             SdkMcpTool::new(
@@ -354,11 +1825,23 @@ macro_rules! tool {
                 schema,
                 |input: serde_json::Value| async move {
                     $(
-                        let $arg: $type = serde_json::from_value(
+                        let $arg: $type = match serde_json::from_value(
                             input.get(stringify!($arg)).cloned().unwrap_or_default()
-                        ).unwrap_or_default();
+                        ) {
+                            Ok(value) => value,
+                            Err(e) => {
+                                return Err(ToolError::invalid_params(format!(
+                                    "Invalid argument '{}': {}",
+                                    stringify!($arg),
+                                    e
+                                )))
+                            }
+                        };
                     )*
-                    $body
+                    // The block is its own async block, so `return` in the
+                    // body returns the tool's result.
+                    let output: $ret = async move { $body }.await;
+                    $crate::mcp::ToolOutcome::from(output)
                 },
             )
         }
@@ -404,10 +1887,212 @@ mod tests {
         assert!(schema.required.contains(&"name".to_string()));
     }
 
+    #[test]
+    fn test_tool_name() {
+        assert_eq!(tool_name("calc", "add"), "mcp__calc__add");
+        assert_eq!(tool_name("my-server", "do_it"), "mcp__my-server__do_it");
+    }
+
+    #[test]
+    fn test_tool_result_structured() {
+        let result = ToolResult::structured(serde_json::json!({"temp": 21.5}));
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({"temp": 21.5}))
+        );
+        assert!(
+            matches!(&result.content[0], ToolContent::Text { text } if text == r#"{"temp":21.5}"#)
+        );
+        assert!(result.is_error.is_none());
+    }
+
+    #[test]
+    fn test_tool_error_constructors() {
+        let err = ToolError::invalid_params("bad input");
+        assert_eq!(err.code, -32602);
+        assert_eq!(err.to_string(), "bad input (code -32602)");
+
+        let err = ToolError::internal("boom").with_data(serde_json::json!({"k": 1}));
+        assert_eq!(err.code, -32603);
+        assert_eq!(err.data, Some(serde_json::json!({"k": 1})));
+    }
+
+    #[test]
+    fn test_tool_error_from_serde_error() {
+        let parse_err = serde_json::from_str::<u32>("\"x\"").unwrap_err();
+        let err: ToolError = parse_err.into();
+        assert_eq!(err.code, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_tool_macro_wraps_result() {
+        let tool = crate::tool! {
+            fn shout(text: String) -> ToolResult {
+                ToolResult::text(text.to_uppercase())
+            }
+        };
+
+        let result = (tool.handler)(serde_json::json!({"text": "hi"}), ToolContext::default())
+            .await
+            .unwrap();
+        assert!(matches!(&result.content[0], ToolContent::Text { text } if text == "HI"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_macro_rejects_arguments_of_the_wrong_type() {
+        let tool = crate::tool! {
+            fn repeat(text: String, times: u8, sep: Option<String>) -> ToolResult {
+                ToolResult::text(vec![text; times as usize].join(sep.as_deref().unwrap_or("")))
+            }
+        };
+        assert_eq!(tool.input_schema.required, ["text", "times"]);
+        assert_eq!(
+            tool.input_schema.properties["sep"],
+            serde_json::json!({"type": ["string", "null"]})
+        );
+
+        let call = |input| (tool.handler)(input, ToolContext::default());
+        let result = call(serde_json::json!({"text": "a", "times": 3}))
+            .await
+            .unwrap();
+        assert!(matches!(&result.content[0], ToolContent::Text { text } if text == "aaa"));
+
+        for times in [
+            serde_json::json!("x"),
+            serde_json::json!(300),
+            serde_json::json!(-1),
+        ] {
+            let err = call(serde_json::json!({"text": "a", "times": times}))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, INVALID_PARAMS);
+            assert!(err.message.contains("'times'"), "{}", err.message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_macro_body_can_return_early() {
+        let tool = crate::tool! {
+            fn divide(a: f64, b: f64) -> ToolResult {
+                if b == 0.0 {
+                    return ToolResult::error("division by zero");
+                }
+                ToolResult::text(format!("{}", a / b))
+            }
+        };
+
+        let call = |input| (tool.handler)(input, ToolContext::default());
+        let result = call(serde_json::json!({"a": 1.0, "b": 0.0})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let result = call(serde_json::json!({"a": 6.0, "b": 3.0})).await.unwrap();
+        assert!(matches!(&result.content[0], ToolContent::Text { text } if text == "2"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_macro_schema_follows_argument_types() {
+        #[derive(serde::Deserialize)]
+        struct Point {
+            x: f64,
+        }
+
+        let tool = crate::tool! {
+            fn plot(label: String, size: u32, scale: f64, tags: Vec<String>, at: Point) -> ToolResult {
+                ToolResult::text(format!("{} {} {} {:?} {}", label, size, scale, tags, at.x))
+            }
+        };
+
+        let properties = &tool.input_schema.properties;
+        assert_eq!(properties["label"], serde_json::json!({"type": "string"}));
+        assert_eq!(properties["size"], serde_json::json!({"type": "integer"}));
+        assert_eq!(properties["scale"], serde_json::json!({"type": "number"}));
+        assert_eq!(
+            properties["tags"],
+            serde_json::json!({"type": "array", "items": {"type": "string"}})
+        );
+        assert_eq!(properties["at"], serde_json::json!({}));
+        assert_eq!(tool.input_schema.required.len(), 5);
+
+        let violations = tool
+            .input_schema
+            .validate(&serde_json::json!({
+                "label": 1, "size": 2, "scale": 0.5, "tags": [], "at": {"x": 1.0}
+            }))
+            .unwrap_err();
+        assert_eq!(
+            violations[0].to_string(),
+            "label: expected string, got number"
+        );
+    }
+
+    #[test]
+    fn test_schema_validate_accepts_valid_input() {
+        let schema = ToolInputSchema::object()
+            .string_property("name", "Name")
+            .number_property("count", "Count")
+            .required_property("name");
+
+        assert!(schema
+            .validate(&serde_json::json!({"name": "x", "count": 2.5}))
+            .is_ok());
+        assert!(schema.validate(&serde_json::json!({"name": "x"})).is_ok());
+    }
+
+    #[test]
+    fn test_schema_validate_reports_all_violations() {
+        let schema = ToolInputSchema::object()
+            .string_property("name", "Name")
+            .boolean_property("flag", "Flag")
+            .required_property("name")
+            .required_property("id");
+
+        let violations = schema
+            .validate(&serde_json::json!({"name": 42, "flag": "yes"}))
+            .unwrap_err();
+        assert_eq!(violations.len(), 3);
+        assert!(violations.contains(&SchemaViolation::new("id", "missing required property")));
+        assert!(violations.contains(&SchemaViolation::new("name", "expected string, got number")));
+        assert!(violations.contains(&SchemaViolation::new(
+            "flag",
+            "expected boolean, got string"
+        )));
+    }
+
+    #[test]
+    fn test_schema_validate_rejects_non_object() {
+        let violations = ToolInputSchema::object()
+            .validate(&serde_json::json!([1, 2]))
+            .unwrap_err();
+        assert_eq!(violations[0].to_string(), "expected object, got array");
+    }
+
+    #[test]
+    fn test_schema_validate_integer_and_union_types() {
+        let mut schema = ToolInputSchema::object();
+        schema
+            .properties
+            .insert("n".to_string(), serde_json::json!({"type": "integer"}));
+        schema.properties.insert(
+            "v".to_string(),
+            serde_json::json!({"type": ["string", "null"]}),
+        );
+
+        assert!(schema
+            .validate(&serde_json::json!({"n": 3, "v": null}))
+            .is_ok());
+        let violations = schema
+            .validate(&serde_json::json!({"n": 1.5, "v": 1}))
+            .unwrap_err();
+        assert_eq!(violations[0].to_string(), "n: expected integer, got number");
+        assert_eq!(
+            violations[1].to_string(),
+            "v: expected string or null, got number"
+        );
+    }
+
     #[test]
     fn test_create_sdk_server() {
         let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
-            ToolResult::text("ok")
+            Ok(ToolResult::text("ok"))
         });
 
         let (config, tools) = create_sdk_mcp_server("test-server", "1.0.0", vec![tool]);
@@ -462,7 +2147,7 @@ mod tests {
     #[test]
     fn test_sdk_mcp_tool_with_annotations() {
         let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
-            ToolResult::text("ok")
+            Ok(ToolResult::text("ok"))
         })
         .with_annotations(ToolAnnotations {
             read_only_hint: Some(true),
@@ -473,12 +2158,63 @@ mod tests {
         assert_eq!(tool.annotations.unwrap().read_only_hint, Some(true));
     }
 
+    #[test]
+    fn test_tool_annotations_builder() {
+        let annotations = ToolAnnotations::new()
+            .with_title("Delete file")
+            .with_read_only_hint(false)
+            .with_destructive_hint(true)
+            .with_idempotent_hint(true)
+            .with_open_world_hint(false);
+
+        assert_eq!(annotations.title.as_deref(), Some("Delete file"));
+        assert_eq!(annotations.read_only_hint, Some(false));
+        assert_eq!(annotations.destructive_hint, Some(true));
+        assert_eq!(annotations.idempotent_hint, Some(true));
+        assert_eq!(annotations.open_world_hint, Some(false));
+    }
+
+    #[test]
+    fn test_sdk_mcp_tool_annotation_shorthands() {
+        let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
+            Ok(ToolResult::text("ok"))
+        })
+        .read_only()
+        .open_world(false);
+
+        let annotations = tool.annotations.unwrap();
+        assert_eq!(annotations.read_only_hint, Some(true));
+        assert_eq!(annotations.open_world_hint, Some(false));
+
+        let tool = SdkMcpTool::new("rm", "Remove", ToolInputSchema::object(), |_| async {
+            Ok(ToolResult::text("ok"))
+        })
+        .destructive();
+        let annotations = tool.annotations.unwrap();
+        assert_eq!(annotations.read_only_hint, Some(false));
+        assert_eq!(annotations.destructive_hint, Some(true));
+    }
+
     #[test]
     fn test_sdk_mcp_tool_no_annotations_by_default() {
         let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
-            ToolResult::text("ok")
+            Ok(ToolResult::text("ok"))
         });
 
         assert!(tool.annotations.is_none());
     }
+
+    #[test]
+    fn test_sdk_mcp_tool_debug_shows_annotations_and_output_schema() {
+        let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
+            Ok(ToolResult::text("ok"))
+        })
+        .read_only()
+        .with_output_schema(ToolInputSchema::object().number_property("total", "Sum"));
+
+        let debug = format!("{:?}", tool);
+        assert!(debug.contains("read_only_hint: Some(true)"), "{}", debug);
+        assert!(debug.contains("output_schema: Some("), "{}", debug);
+        assert!(debug.contains("total"), "{}", debug);
+    }
 }
@@ -0,0 +1,635 @@
+//! Client for external MCP servers.
+//!
+//! This module lets Rust code talk to the same MCP servers that are handed to
+//! the CLI via `mcp_servers`: connect, list tools, and call them directly.
+//! Useful for smoke-testing server configurations before starting a session,
+//! or for reusing a server's tools outside an agent.
+//!
+//! Stdio and HTTP (streamable HTTP) servers are supported. SSE-only servers
+//! are not.
+//!
+//! # Feature Flag
+//!
+//! This module requires the `mcp-client` feature to be enabled:
+//!
+//! ```toml
+//! [dependencies]
+//! claude-agents-sdk = { version = "0.1", features = ["mcp-client"] }
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::mcp_client::McpClient;
+//! use claude_agents_sdk::{McpServerConfig, McpStdioServerConfig};
+//! use std::collections::HashMap;
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let config = McpServerConfig::Stdio(McpStdioServerConfig {
+//!     server_type: "stdio".to_string(),
+//!     command: "npx".to_string(),
+//!     args: vec!["-y".to_string(), "@modelcontextprotocol/server-everything".to_string()],
+//!     env: HashMap::new(),
+//! });
+//!
+//! let client = McpClient::connect(&config).await?;
+//! for tool in client.list_tools().await? {
+//!     println!("{}", tool.name);
+//! }
+//! let result = client.call_tool("echo", serde_json::json!({"message": "hi"})).await?;
+//! println!("{:?}", result.content);
+//! client.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex};
+use tracing::{debug, trace, warn};
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::mcp::{
+    NegotiationError, ToolAnnotations, ToolResult, MCP_PROTOCOL_VERSION,
+    SUPPORTED_PROTOCOL_VERSIONS,
+};
+use crate::types::{McpHttpServerConfig, McpServerConfig, McpStdioServerConfig};
+
+/// Default timeout for a single MCP request.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A tool advertised by an MCP server's `tools/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    /// Tool name.
+    pub name: String,
+    /// Tool description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema for the tool's arguments.
+    #[serde(default)]
+    pub input_schema: Value,
+    /// JSON Schema for the tool's structured output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Behavior hints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+type PendingResponses = Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+/// Removes a pending request when its caller stops waiting, whether it timed
+/// out, failed to write, or was cancelled.
+struct PendingGuard<'a> {
+    pending: &'a PendingResponses,
+    id: Option<u64>,
+}
+
+impl<'a> PendingGuard<'a> {
+    fn new(pending: &'a PendingResponses, id: u64) -> Self {
+        Self {
+            pending,
+            id: Some(id),
+        }
+    }
+
+    /// The response arrived, so the reader already removed the entry.
+    fn disarm(&mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        if let Ok(mut pending) = self.pending.try_lock() {
+            pending.remove(&id);
+        } else if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let pending = Arc::clone(self.pending);
+            handle.spawn(async move {
+                pending.lock().await.remove(&id);
+            });
+        }
+    }
+}
+
+/// Connection to the server.
+enum Connection {
+    Stdio {
+        child: Child,
+        stdin: Arc<Mutex<ChildStdin>>,
+        pending: PendingResponses,
+        reader: tokio::task::JoinHandle<()>,
+    },
+    Http {
+        http: reqwest::Client,
+        url: String,
+        headers: HashMap<String, String>,
+        session_id: Mutex<Option<String>>,
+    },
+}
+
+/// A connected, initialized MCP client.
+pub struct McpClient {
+    connection: Connection,
+    next_id: AtomicU64,
+    server_info: Value,
+    request_timeout: Duration,
+}
+
+impl McpClient {
+    /// Connect to a server described by a CLI MCP server config.
+    ///
+    /// Performs the MCP `initialize` handshake before returning.
+    pub async fn connect(config: &McpServerConfig) -> Result<Self> {
+        match config {
+            McpServerConfig::Stdio(config) => Self::connect_stdio(config).await,
+            McpServerConfig::Http(config) => Self::connect_http(config).await,
+            McpServerConfig::SSE(_) => Err(ClaudeSDKError::configuration(
+                "SSE MCP servers are not supported by McpClient",
+            )),
+        }
+    }
+
+    /// Spawn a stdio MCP server and connect to it.
+    pub async fn connect_stdio(config: &McpStdioServerConfig) -> Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                ClaudeSDKError::cli_connection_with_source(
+                    format!("Failed to start MCP server '{}'", config.command),
+                    e,
+                )
+            })?;
+
+        let stdin =
+            Arc::new(Mutex::new(child.stdin.take().ok_or_else(|| {
+                ClaudeSDKError::internal("MCP server stdin not captured")
+            })?));
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| ClaudeSDKError::internal("MCP server stdout not captured"))?;
+        let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
+
+        let reader = tokio::spawn(Self::read_stdio(
+            BufReader::new(stdout),
+            Arc::clone(&stdin),
+            Arc::clone(&pending),
+        ));
+
+        let connection = Connection::Stdio {
+            child,
+            stdin,
+            pending,
+            reader,
+        };
+        Self::initialize(connection).await
+    }
+
+    /// Connect to a streamable-HTTP MCP server.
+    pub async fn connect_http(config: &McpHttpServerConfig) -> Result<Self> {
+        let connection = Connection::Http {
+            http: reqwest::Client::new(),
+            url: config.url.clone(),
+            headers: config.headers.clone(),
+            session_id: Mutex::new(None),
+        };
+        Self::initialize(connection).await
+    }
+
+    /// Run the `initialize` handshake on a fresh connection.
+    async fn initialize(connection: Connection) -> Result<Self> {
+        let mut client = Self {
+            connection,
+            next_id: AtomicU64::new(1),
+            server_info: Value::Null,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        };
+
+        client.server_info = client
+            .request(
+                "initialize",
+                serde_json::json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "claude-agents-sdk", "version": crate::VERSION}
+                }),
+            )
+            .await?;
+
+        let version = client.server_info["protocolVersion"]
+            .as_str()
+            .unwrap_or_default();
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+            let error = NegotiationError::UnsupportedVersion {
+                requested: version.to_string(),
+                supported: SUPPORTED_PROTOCOL_VERSIONS
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect(),
+            };
+            let _ = client.close().await;
+            return Err(ClaudeSDKError::configuration(format!(
+                "MCP server chose {}",
+                error
+            )));
+        }
+
+        client
+            .notify("notifications/initialized", Value::Null)
+            .await?;
+
+        debug!(
+            "Connected to MCP server {}",
+            client.server_info["serverInfo"]["name"]
+        );
+        Ok(client)
+    }
+
+    /// Set the timeout for individual requests (default 60 seconds).
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// The server's `initialize` result (protocol version, capabilities,
+    /// server info).
+    pub fn server_info(&self) -> &Value {
+        &self.server_info
+    }
+
+    /// List the tools the server provides.
+    ///
+    /// Follows `nextCursor` pagination until all tools are collected.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<Value> = None;
+        loop {
+            let params = match cursor.take() {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let mut result = self.request("tools/list", params).await?;
+            let page: Vec<McpToolInfo> =
+                serde_json::from_value(result["tools"].take()).map_err(|e| {
+                    ClaudeSDKError::json_decode(format!("Invalid tools/list result: {}", e))
+                })?;
+            tools.extend(page);
+            match result.get("nextCursor") {
+                Some(next) if !next.is_null() => cursor = Some(next.clone()),
+                _ => return Ok(tools),
+            }
+        }
+    }
+
+    /// Call a tool by name.
+    ///
+    /// A tool-level failure (`isError: true`) is returned as an `Ok` result;
+    /// protocol errors are returned as `Err`.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<ToolResult> {
+        let mut result = self
+            .request(
+                "tools/call",
+                serde_json::json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+
+        Ok(ToolResult {
+            content: serde_json::from_value(result["content"].take()).map_err(|e| {
+                ClaudeSDKError::json_decode(format!("Invalid tools/call result: {}", e))
+            })?,
+            is_error: result.get("isError").and_then(|v| v.as_bool()),
+            structured_content: result
+                .get_mut("structuredContent")
+                .map(Value::take)
+                .filter(|v| !v.is_null()),
+        })
+    }
+
+    /// Send a raw JSON-RPC request and return its `result`.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut message = serde_json::json!({"jsonrpc": "2.0", "id": id, "method": method});
+        if !params.is_null() {
+            message["params"] = params;
+        }
+        trace!("MCP request {}: {}", id, method);
+
+        let response = tokio::time::timeout(self.request_timeout, self.exchange(id, message))
+            .await
+            .map_err(|_| ClaudeSDKError::timeout(self.request_timeout.as_millis() as u64))??;
+
+        if let Some(error) = response.get("error") {
+            return Err(ClaudeSDKError::control_protocol(format!(
+                "MCP error {} from {}: {}",
+                error["code"],
+                method,
+                error["message"].as_str().unwrap_or("unknown error")
+            )));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Send a JSON-RPC notification.
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let mut message = serde_json::json!({"jsonrpc": "2.0", "method": method});
+        if !params.is_null() {
+            message["params"] = params;
+        }
+        match &self.connection {
+            Connection::Stdio { stdin, .. } => write_line(stdin, &message).await,
+            Connection::Http { .. } => self.post(&message).await.map(|_| ()),
+        }
+    }
+
+    /// Shut down the connection, terminating a stdio server process.
+    pub async fn close(self) -> Result<()> {
+        match self.connection {
+            Connection::Stdio {
+                mut child,
+                stdin,
+                reader,
+                ..
+            } => {
+                drop(stdin);
+                reader.abort();
+                let _ = child.kill().await;
+                Ok(())
+            }
+            Connection::Http {
+                http,
+                url,
+                headers,
+                session_id,
+            } => {
+                // Tell the server the session is over; failures are harmless.
+                if let Some(session_id) = session_id.into_inner() {
+                    let mut request = http.delete(&url).header("Mcp-Session-Id", session_id);
+                    for (name, value) in &headers {
+                        request = request.header(name, value);
+                    }
+                    let _ = request.send().await;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Send a request and wait for the response with the matching ID.
+    async fn exchange(&self, id: u64, message: Value) -> Result<Value> {
+        match &self.connection {
+            Connection::Stdio { stdin, pending, .. } => {
+                let (tx, rx) = oneshot::channel();
+                pending.lock().await.insert(id, tx);
+                let mut guard = PendingGuard::new(pending, id);
+                write_line(stdin, &message).await?;
+                let response = rx.await.map_err(|_| {
+                    ClaudeSDKError::cli_connection("MCP server closed the connection")
+                })?;
+                guard.disarm();
+                Ok(response)
+            }
+            Connection::Http { .. } => {
+                let body = self.post(&message).await?;
+                find_response(&body, id).ok_or_else(|| {
+                    ClaudeSDKError::control_protocol(format!(
+                        "No response for MCP request {} in HTTP reply",
+                        id
+                    ))
+                })
+            }
+        }
+    }
+
+    /// POST a message to an HTTP server and return the response body.
+    async fn post(&self, message: &Value) -> Result<String> {
+        let Connection::Http {
+            http,
+            url,
+            headers,
+            session_id,
+        } = &self.connection
+        else {
+            return Err(ClaudeSDKError::internal("Not an HTTP connection"));
+        };
+
+        let mut request = http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .body(message.to_string());
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(session_id) = session_id.lock().await.as_deref() {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            ClaudeSDKError::cli_connection(format!("MCP HTTP request to {} failed: {}", url, e))
+        })?;
+        let status = response.status();
+        if let Some(id) = response
+            .headers()
+            .get("Mcp-Session-Id")
+            .and_then(|v| v.to_str().ok())
+        {
+            *session_id.lock().await = Some(id.to_string());
+        }
+        let body = response.text().await.map_err(|e| {
+            ClaudeSDKError::cli_connection(format!("Failed to read MCP HTTP response: {}", e))
+        })?;
+
+        if !status.is_success() {
+            return Err(ClaudeSDKError::cli_connection(format!(
+                "MCP server at {} returned HTTP {}: {}",
+                url, status, body
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Read responses from a stdio server and route them to waiting requests.
+    async fn read_stdio(
+        stdout: BufReader<tokio::process::ChildStdout>,
+        stdin: Arc<Mutex<ChildStdin>>,
+        pending: PendingResponses,
+    ) {
+        let mut lines = stdout.lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Ignoring invalid line from MCP server: {}", e);
+                    continue;
+                }
+            };
+
+            let id = message.get("id").cloned();
+            match (id, message.get("method").and_then(|m| m.as_str())) {
+                // Request from the server. Answer pings; decline the rest.
+                (Some(id), Some(method)) => {
+                    let response = if method == "ping" {
+                        serde_json::json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                    } else {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": {"code": -32601, "message": format!("Method '{}' not found", method)}
+                        })
+                    };
+                    let _ = write_line(&stdin, &response).await;
+                }
+                (Some(id), None) => {
+                    let sender = match id.as_u64() {
+                        Some(id) => pending.lock().await.remove(&id),
+                        None => None,
+                    };
+                    match sender {
+                        Some(sender) => {
+                            let _ = sender.send(message);
+                        }
+                        None => warn!("Response for unknown MCP request {}", id),
+                    }
+                }
+                (None, method) => {
+                    debug!("MCP server notification: {}", method.unwrap_or("unknown"));
+                }
+            }
+        }
+        debug!("MCP server stdout closed");
+        // Dropping the senders fails any requests still waiting.
+        pending.lock().await.clear();
+    }
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let transport = match &self.connection {
+            Connection::Stdio { .. } => "stdio",
+            Connection::Http { .. } => "http",
+        };
+        f.debug_struct("McpClient")
+            .field("transport", &transport)
+            .field("server_info", &self.server_info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Write a JSON message as one line to a stdio server.
+async fn write_line(stdin: &Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// Find the JSON-RPC response with the given ID in an HTTP reply body, which
+/// is either a JSON document or a `text/event-stream`.
+fn find_response(body: &str, id: u64) -> Option<Value> {
+    let matches = |v: &Value| v.get("id").and_then(Value::as_u64) == Some(id);
+
+    if let Ok(value) = serde_json::from_str::<Value>(body) {
+        return match value {
+            Value::Array(batch) => batch.into_iter().find(matches),
+            value if matches(&value) => Some(value),
+            _ => None,
+        };
+    }
+
+    // Server-sent events: each event's `data:` lines form one JSON message.
+    body.split("\n\n")
+        .filter_map(|event| {
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(str::trim_start)
+                .collect();
+            serde_json::from_str::<Value>(&data.join("\n")).ok()
+        })
+        .find(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_response_json() {
+        let body = r#"{"jsonrpc":"2.0","id":3,"result":{"ok":true}}"#;
+        assert_eq!(find_response(body, 3).unwrap()["result"]["ok"], true);
+        assert!(find_response(body, 4).is_none());
+    }
+
+    #[test]
+    fn test_find_response_event_stream() {
+        let body =
+            "event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/message\"}\n\n\
+                    event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{}}\n\n";
+        assert!(find_response(body, 7).unwrap()["result"].is_object());
+    }
+
+    #[test]
+    fn test_tool_info_deserialization() {
+        let info: McpToolInfo = serde_json::from_value(serde_json::json!({
+            "name": "echo",
+            "inputSchema": {"type": "object"},
+            "annotations": {"readOnlyHint": true}
+        }))
+        .unwrap();
+        assert_eq!(info.name, "echo");
+        assert!(info.description.is_none());
+        assert_eq!(info.annotations.unwrap().read_only_hint, Some(true));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_request_is_no_longer_pending() {
+        let script = r#"
+read init
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{},"serverInfo":{"name":"slow","version":"0.1"}}}'
+read initialized
+read list
+read rest
+"#;
+        let config = McpStdioServerConfig {
+            server_type: "stdio".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+        };
+        let client = McpClient::connect_stdio(&config)
+            .await
+            .unwrap()
+            .with_request_timeout(Duration::from_millis(50));
+
+        let err = client.list_tools().await.unwrap_err();
+        assert!(matches!(err, ClaudeSDKError::Timeout { .. }), "{:?}", err);
+        let Connection::Stdio { pending, .. } = &client.connection else {
+            unreachable!()
+        };
+        assert!(pending.lock().await.is_empty());
+        client.close().await.unwrap();
+    }
+}
@@ -0,0 +1,275 @@
+//! Merging MCP server configuration from multiple sources.
+//!
+//! The CLI reads MCP servers from user-level config (`~/.claude.json`),
+//! project files (`.mcp.json`), and whatever the SDK passes in
+//! `mcp_servers`. [`McpConfigLoader`] combines these layers in Rust with the
+//! same precedence, reporting servers defined differently in more than one
+//! place.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::{ClaudeAgentOptions, McpConfigLoader, McpServersConfig};
+//!
+//! # fn example() -> claude_agents_sdk::Result<()> {
+//! let merged = McpConfigLoader::new()
+//!     .user()?
+//!     .project(".")?
+//!     .options(&McpServersConfig::default())?
+//!     .load();
+//!
+//! for conflict in &merged.conflicts {
+//!     eprintln!("warning: {}", conflict);
+//! }
+//!
+//! let mut options = ClaudeAgentOptions::new();
+//! options.mcp_servers = McpServersConfig::Map(merged.servers);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{McpServerConfig, McpServersConfig};
+
+/// Project-level MCP config file name.
+pub const PROJECT_MCP_CONFIG_FILE: &str = ".mcp.json";
+
+/// Where an MCP server definition came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpConfigSource {
+    /// User-level config (`~/.claude.json`).
+    User,
+    /// A project's `.mcp.json`.
+    Project(PathBuf),
+    /// An explicitly added config file.
+    File(PathBuf),
+    /// `ClaudeAgentOptions::mcp_servers`.
+    Options,
+}
+
+impl fmt::Display for McpConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User => write!(f, "user config"),
+            Self::Project(path) => write!(f, "project config {}", path.display()),
+            Self::File(path) => write!(f, "config file {}", path.display()),
+            Self::Options => write!(f, "options"),
+        }
+    }
+}
+
+/// A server defined differently by more than one source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpConfigConflict {
+    /// Server name.
+    pub name: String,
+    /// Source whose definition was used.
+    pub winner: McpConfigSource,
+    /// Sources whose definitions were overridden, lowest precedence first.
+    pub overridden: Vec<McpConfigSource>,
+}
+
+impl fmt::Display for McpConfigConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let overridden: Vec<String> = self.overridden.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "MCP server '{}' from {} overrides {}",
+            self.name,
+            self.winner,
+            overridden.join(", ")
+        )
+    }
+}
+
+/// The merged result of an [`McpConfigLoader`].
+#[derive(Debug, Clone, Default)]
+pub struct MergedMcpConfig {
+    /// Final server definitions.
+    pub servers: HashMap<String, McpServerConfig>,
+    /// Which source each server was taken from.
+    pub sources: HashMap<String, McpConfigSource>,
+    /// Servers defined differently by more than one source.
+    pub conflicts: Vec<McpConfigConflict>,
+}
+
+/// Builder that layers MCP server configuration from several sources.
+///
+/// Layers added later take precedence, so add them from lowest to highest:
+/// user, project, explicit files, then options. A server defined identically
+/// in several layers is not reported as a conflict.
+#[derive(Debug, Clone, Default)]
+pub struct McpConfigLoader {
+    layers: Vec<(McpConfigSource, HashMap<String, McpServerConfig>)>,
+}
+
+impl McpConfigLoader {
+    /// Create an empty loader.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the user-level config (`~/.claude.json`), if present.
+    pub fn user(self) -> Result<Self> {
+        let Some(home) = home_dir() else {
+            return Ok(self);
+        };
+        self.user_from(home.join(".claude.json"))
+    }
+
+    /// Add a user-level config from an explicit path, if present.
+    pub fn user_from(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            self.layers
+                .push((McpConfigSource::User, read_mcp_config_file(path)?));
+        }
+        Ok(self)
+    }
+
+    /// Add `.mcp.json` from a project directory, if present.
+    pub fn project(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(PROJECT_MCP_CONFIG_FILE);
+        if path.exists() {
+            let servers = read_mcp_config_file(&path)?;
+            self.layers.push((McpConfigSource::Project(path), servers));
+        }
+        Ok(self)
+    }
+
+    /// Add a config file that must exist.
+    pub fn file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let servers = read_mcp_config_file(path)?;
+        self.layers
+            .push((McpConfigSource::File(path.to_path_buf()), servers));
+        Ok(self)
+    }
+
+    /// Add servers from `ClaudeAgentOptions::mcp_servers`.
+    pub fn options(mut self, config: &McpServersConfig) -> Result<Self> {
+        self.layers
+            .push((McpConfigSource::Options, config.resolve()?));
+        Ok(self)
+    }
+
+    /// Merge all layers.
+    pub fn load(self) -> MergedMcpConfig {
+        let mut merged = MergedMcpConfig::default();
+        // Sources that defined each server, with their serialized definition.
+        let mut history: HashMap<String, Vec<(McpConfigSource, Value)>> = HashMap::new();
+
+        for (source, servers) in self.layers {
+            for (name, config) in servers {
+                let value = serde_json::to_value(&config).unwrap_or(Value::Null);
+                history
+                    .entry(name.clone())
+                    .or_default()
+                    .push((source.clone(), value));
+                merged.sources.insert(name.clone(), source.clone());
+                merged.servers.insert(name, config);
+            }
+        }
+
+        let mut names: Vec<&String> = history.keys().collect();
+        names.sort();
+        for name in names {
+            let definitions = &history[name];
+            let (winner, winning_value) = definitions.last().expect("non-empty history");
+            let overridden: Vec<McpConfigSource> = definitions[..definitions.len() - 1]
+                .iter()
+                .filter(|(_, value)| value != winning_value)
+                .map(|(source, _)| source.clone())
+                .collect();
+            if !overridden.is_empty() {
+                merged.conflicts.push(McpConfigConflict {
+                    name: name.clone(),
+                    winner: winner.clone(),
+                    overridden,
+                });
+            }
+        }
+
+        merged
+    }
+}
+
+impl McpServersConfig {
+    /// Resolve to a map of servers, reading the file for [`McpServersConfig::Path`].
+    pub fn resolve(&self) -> Result<HashMap<String, McpServerConfig>> {
+        match self {
+            Self::Map(servers) => Ok(servers.clone()),
+            Self::Path(path) => read_mcp_config_file(path),
+        }
+    }
+
+    /// Merge another configuration into this one.
+    ///
+    /// Servers from `other` take precedence. Returns the merged map together
+    /// with the names of servers that were defined differently in both.
+    pub fn merge(&self, other: &McpServersConfig) -> Result<(McpServersConfig, Vec<String>)> {
+        let merged = McpConfigLoader {
+            layers: vec![
+                (McpConfigSource::Options, self.resolve()?),
+                (McpConfigSource::Options, other.resolve()?),
+            ],
+        }
+        .load();
+        let conflicts = merged.conflicts.into_iter().map(|c| c.name).collect();
+        Ok((McpServersConfig::Map(merged.servers), conflicts))
+    }
+}
+
+/// Read MCP servers from a JSON file.
+///
+/// Accepts both the `{"mcpServers": {...}}` layout used by `.mcp.json` and
+/// `~/.claude.json`, and a bare map of server name to config.
+fn read_mcp_config_file(path: &Path) -> Result<HashMap<String, McpServerConfig>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Failed to read MCP config {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let mut value: Value = serde_json::from_str(&contents).map_err(|e| {
+        ClaudeSDKError::configuration(format!("Invalid JSON in {}: {}", path.display(), e))
+    })?;
+
+    let mut servers = match value.get_mut("mcpServers") {
+        Some(servers) => servers.take(),
+        None if path.file_name() == Some(".claude.json".as_ref()) => return Ok(HashMap::new()),
+        None => value,
+    };
+    // The CLI treats entries without a "type" as stdio servers.
+    if let Some(entries) = servers.as_object_mut() {
+        for entry in entries.values_mut() {
+            if let Some(entry) = entry.as_object_mut() {
+                if !entry.contains_key("type") && entry.contains_key("command") {
+                    entry.insert("type".to_string(), Value::from("stdio"));
+                }
+            }
+        }
+    }
+    serde_json::from_value(servers).map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Invalid MCP server config in {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// The current user's home directory, from `HOME` or `USERPROFILE`.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
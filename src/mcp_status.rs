@@ -0,0 +1,186 @@
+//! Typed MCP server status from the CLI.
+//!
+//! The `mcp_status` control request returns the CLI's view of every MCP
+//! server it manages, including SDK servers: connection state, server info,
+//! and the tools each server advertises. [`ClaudeClient::mcp_servers`] and
+//! [`ClaudeClient::mcp_tools`] parse it into the types here.
+//!
+//! [`ClaudeClient::mcp_servers`]: crate::ClaudeClient::mcp_servers
+//! [`ClaudeClient::mcp_tools`]: crate::ClaudeClient::mcp_tools
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ClaudeSDKError, Result};
+
+/// Connection state of an MCP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum McpServerState {
+    /// Connected and serving tools.
+    Connected,
+    /// Still connecting.
+    Pending,
+    /// Failed to start or connect.
+    Failed,
+    /// Waiting for the user to authenticate.
+    NeedsAuth,
+    /// Disabled in settings.
+    Disabled,
+    /// A state this SDK does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Name and version an MCP server reported when it connected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerInfo {
+    /// Server name.
+    pub name: String,
+    /// Server version.
+    pub version: String,
+}
+
+/// Status of one MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    /// Server name, as configured.
+    pub name: String,
+    /// Connection state.
+    pub state: McpServerState,
+    /// Number of tools the server advertises.
+    pub tool_count: usize,
+    /// Server name and version, once connected.
+    pub server_info: Option<McpServerInfo>,
+    /// Why the server failed, if it did.
+    pub error: Option<String>,
+}
+
+/// A tool advertised by an MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDescriptor {
+    /// Tool name.
+    pub name: String,
+    /// Tool description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema for the tool's arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// Behavior hints such as `readOnlyHint`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<serde_json::Value>,
+}
+
+/// A server entry as the CLI reports it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawServerStatus {
+    name: String,
+    status: McpServerState,
+    #[serde(default)]
+    server_info: Option<McpServerInfo>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    tools: Vec<ToolDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawStatus {
+    #[serde(default)]
+    mcp_servers: Vec<RawServerStatus>,
+}
+
+fn parse(response: serde_json::Value) -> Result<Vec<RawServerStatus>> {
+    serde_json::from_value::<RawStatus>(response.clone())
+        .map(|status| status.mcp_servers)
+        .map_err(|e| {
+            ClaudeSDKError::message_parse_with_raw(
+                format!("Failed to parse MCP status: {}", e),
+                response,
+            )
+        })
+}
+
+/// Parse an `mcp_status` response into per-server statuses.
+pub(crate) fn parse_servers(response: serde_json::Value) -> Result<Vec<McpServerStatus>> {
+    Ok(parse(response)?
+        .into_iter()
+        .map(|raw| McpServerStatus {
+            name: raw.name,
+            state: raw.status,
+            tool_count: raw.tools.len(),
+            server_info: raw.server_info,
+            error: raw.error,
+        })
+        .collect())
+}
+
+/// Parse an `mcp_status` response into the tools of one server.
+pub(crate) fn parse_tools(
+    response: serde_json::Value,
+    server: &str,
+) -> Result<Vec<ToolDescriptor>> {
+    parse(response)?
+        .into_iter()
+        .find(|raw| raw.name == server)
+        .map(|raw| raw.tools)
+        .ok_or_else(|| ClaudeSDKError::configuration(format!("No MCP server named '{}'", server)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> serde_json::Value {
+        serde_json::json!({
+            "mcpServers": [
+                {
+                    "name": "docs",
+                    "status": "connected",
+                    "serverInfo": {"name": "docs-mcp", "version": "1.2.0"},
+                    "tools": [
+                        {"name": "search", "description": "Search the docs",
+                         "annotations": {"readOnlyHint": true}},
+                        {"name": "fetch"}
+                    ]
+                },
+                {"name": "github", "status": "needs-auth"},
+                {"name": "broken", "status": "failed", "error": "spawn ENOENT"},
+                {"name": "future", "status": "hibernating"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_parse_servers() {
+        let servers = parse_servers(status()).unwrap();
+        assert_eq!(servers.len(), 4);
+        assert_eq!(servers[0].state, McpServerState::Connected);
+        assert_eq!(servers[0].tool_count, 2);
+        assert_eq!(servers[0].server_info.as_ref().unwrap().version, "1.2.0");
+        assert_eq!(servers[1].state, McpServerState::NeedsAuth);
+        assert_eq!(servers[2].error.as_deref(), Some("spawn ENOENT"));
+        assert_eq!(servers[3].state, McpServerState::Unknown);
+    }
+
+    #[test]
+    fn test_parse_tools() {
+        let tools = parse_tools(status(), "docs").unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].description.as_deref(), Some("Search the docs"));
+        assert_eq!(tools[0].annotations.as_ref().unwrap()["readOnlyHint"], true);
+
+        assert!(parse_tools(status(), "github").unwrap().is_empty());
+        let err = parse_tools(status(), "missing").unwrap_err();
+        assert!(err.to_string().contains("No MCP server named 'missing'"));
+    }
+
+    #[test]
+    fn test_empty_status() {
+        assert!(parse_servers(serde_json::json!({})).unwrap().is_empty());
+    }
+}
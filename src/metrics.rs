@@ -0,0 +1,351 @@
+//! Metrics for agent activity.
+//!
+//! The SDK reports what agents do — queries, token usage, cost, tool calls,
+//! hook denials, and subprocess restarts — through a [`MetricsRecorder`]. Set
+//! one with [`ClaudeAgentOptions::with_metrics`](crate::ClaudeAgentOptions::with_metrics),
+//! or enable the `metrics` feature to forward everything to the
+//! [`metrics`](https://docs.rs/metrics) crate facade by default, where any
+//! exporter (Prometheus, StatsD, ...) can pick it up.
+//!
+//! | Metric | Kind | Labels |
+//! |--------|------|--------|
+//! | [`QUERIES_STARTED`] | counter | |
+//! | [`QUERIES_COMPLETED`] | counter | `status`, `subtype` |
+//! | [`INPUT_TOKENS`] | counter | |
+//! | [`OUTPUT_TOKENS`] | counter | |
+//! | [`QUERY_COST_USD`] | histogram | |
+//! | [`TOOL_CALLS`] | counter | `tool` |
+//! | [`HOOK_DENIALS`] | counter | `event` |
+//! | [`SUBPROCESS_RESTARTS`] | counter | |
+//! | [`ACTIVE_SESSIONS`] | gauge | |
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::metrics::MetricsRecorder;
+//! use claude_agents_sdk::ClaudeAgentOptions;
+//!
+//! struct StdoutRecorder;
+//!
+//! impl MetricsRecorder for StdoutRecorder {
+//!     fn increment_counter(&self, name: &'static str, value: u64, labels: &[(&'static str, &str)]) {
+//!         println!("{} += {} {:?}", name, value, labels);
+//!     }
+//!
+//!     fn record_gauge(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]) {
+//!         println!("{} = {} {:?}", name, value, labels);
+//!     }
+//! }
+//!
+//! let options = ClaudeAgentOptions::new().with_metrics(StdoutRecorder);
+//! ```
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+/// Prompts sent to the CLI.
+pub const QUERIES_STARTED: &str = "claude_queries_started_total";
+/// Result messages received, labelled by `status` (`success`/`error`) and `subtype`.
+pub const QUERIES_COMPLETED: &str = "claude_queries_completed_total";
+/// Input tokens reported in result messages.
+pub const INPUT_TOKENS: &str = "claude_input_tokens_total";
+/// Output tokens reported in result messages.
+pub const OUTPUT_TOKENS: &str = "claude_output_tokens_total";
+/// Cost of each query in USD.
+pub const QUERY_COST_USD: &str = "claude_query_cost_usd";
+/// Tool calls requested by the model, labelled by `tool`.
+pub const TOOL_CALLS: &str = "claude_tool_calls_total";
+/// Hook callbacks that blocked or denied an action, labelled by `event`.
+pub const HOOK_DENIALS: &str = "claude_hook_denials_total";
+/// CLI processes spawned by a client that had already connected once.
+pub const SUBPROCESS_RESTARTS: &str = "claude_subprocess_restarts_total";
+/// Sessions currently connected in this process.
+pub const ACTIVE_SESSIONS: &str = "claude_active_sessions";
+
+/// Receives metrics from the SDK.
+///
+/// Metric names are the constants in this module. Implementations must be
+/// cheap; they are called from the message reader task.
+pub trait MetricsRecorder: Send + Sync {
+    /// Add `value` to a counter.
+    fn increment_counter(&self, name: &'static str, value: u64, labels: &[(&'static str, &str)]);
+
+    /// Set a gauge to `value`.
+    fn record_gauge(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]);
+
+    /// Record a histogram sample. Ignored unless overridden.
+    fn record_histogram(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]) {
+        let _ = (name, value, labels);
+    }
+}
+
+/// A recorder that discards everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopRecorder;
+
+impl MetricsRecorder for NoopRecorder {
+    fn increment_counter(&self, _: &'static str, _: u64, _: &[(&'static str, &str)]) {}
+
+    fn record_gauge(&self, _: &'static str, _: f64, _: &[(&'static str, &str)]) {}
+}
+
+/// A recorder that forwards to the global [`metrics`](https://docs.rs/metrics) recorder.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsCrateRecorder;
+
+#[cfg(feature = "metrics")]
+impl MetricsCrateRecorder {
+    fn labels(labels: &[(&'static str, &str)]) -> Vec<::metrics::Label> {
+        labels
+            .iter()
+            .map(|(key, value)| ::metrics::Label::new(*key, value.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsRecorder for MetricsCrateRecorder {
+    fn increment_counter(&self, name: &'static str, value: u64, labels: &[(&'static str, &str)]) {
+        ::metrics::counter!(name, Self::labels(labels)).increment(value);
+    }
+
+    fn record_gauge(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]) {
+        ::metrics::gauge!(name, Self::labels(labels)).set(value);
+    }
+
+    fn record_histogram(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]) {
+        ::metrics::histogram!(name, Self::labels(labels)).record(value);
+    }
+}
+
+/// The recorder used when none is configured: [`MetricsCrateRecorder`] with
+/// the `metrics` feature, [`NoopRecorder`] otherwise.
+pub fn default_recorder() -> Arc<dyn MetricsRecorder> {
+    #[cfg(feature = "metrics")]
+    {
+        Arc::new(MetricsCrateRecorder)
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        Arc::new(NoopRecorder)
+    }
+}
+
+/// Sessions connected in this process, for [`ACTIVE_SESSIONS`].
+static ACTIVE: AtomicI64 = AtomicI64::new(0);
+
+/// Derives SDK metrics from session events and CLI messages.
+#[derive(Clone)]
+pub(crate) struct SessionMetrics {
+    recorder: Arc<dyn MetricsRecorder>,
+}
+
+impl SessionMetrics {
+    pub(crate) fn new(recorder: Option<Arc<dyn MetricsRecorder>>) -> Self {
+        Self {
+            recorder: recorder.unwrap_or_else(default_recorder),
+        }
+    }
+
+    pub(crate) fn session_started(&self) {
+        let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+        self.recorder
+            .record_gauge(ACTIVE_SESSIONS, active as f64, &[]);
+    }
+
+    pub(crate) fn session_ended(&self) {
+        let active = ACTIVE.fetch_sub(1, Ordering::SeqCst) - 1;
+        self.recorder
+            .record_gauge(ACTIVE_SESSIONS, active.max(0) as f64, &[]);
+    }
+
+    pub(crate) fn query_started(&self) {
+        self.recorder.increment_counter(QUERIES_STARTED, 1, &[]);
+    }
+
+    pub(crate) fn subprocess_restarted(&self) {
+        self.recorder.increment_counter(SUBPROCESS_RESTARTS, 1, &[]);
+    }
+
+    /// Record a hook callback's output, counting blocks and denials.
+    pub(crate) fn hook_output(&self, event: &str, output: &Value) {
+        let blocked = output.get("decision").and_then(Value::as_str) == Some("block");
+        let denied = output
+            .pointer("/hookSpecificOutput/permissionDecision")
+            .and_then(Value::as_str)
+            == Some("deny");
+        if blocked || denied {
+            self.recorder
+                .increment_counter(HOOK_DENIALS, 1, &[("event", event)]);
+        }
+    }
+
+    /// Record metrics carried by a raw message read from the CLI.
+    pub(crate) fn observe(&self, raw: &Value) {
+        match raw.get("type").and_then(Value::as_str) {
+            Some("assistant") => {
+                let blocks = raw
+                    .pointer("/message/content")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten();
+                for block in blocks {
+                    if block.get("type").and_then(Value::as_str) != Some("tool_use") {
+                        continue;
+                    }
+                    if let Some(name) = block.get("name").and_then(Value::as_str) {
+                        self.recorder
+                            .increment_counter(TOOL_CALLS, 1, &[("tool", name)]);
+                    }
+                }
+            }
+            Some("result") => self.observe_result(raw),
+            _ => {}
+        }
+    }
+
+    fn observe_result(&self, raw: &Value) {
+        let status = if raw.get("is_error").and_then(Value::as_bool) == Some(true) {
+            "error"
+        } else {
+            "success"
+        };
+        let subtype = raw
+            .get("subtype")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        self.recorder.increment_counter(
+            QUERIES_COMPLETED,
+            1,
+            &[("status", status), ("subtype", subtype)],
+        );
+
+        if let Some(usage) = raw.get("usage") {
+            for (key, name) in [
+                ("input_tokens", INPUT_TOKENS),
+                ("output_tokens", OUTPUT_TOKENS),
+            ] {
+                if let Some(tokens) = usage.get(key).and_then(Value::as_u64) {
+                    self.recorder.increment_counter(name, tokens, &[]);
+                }
+            }
+        }
+        if let Some(cost) = raw.get("total_cost_usd").and_then(Value::as_f64) {
+            self.recorder.record_histogram(QUERY_COST_USD, cost, &[]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    type Labels = Vec<(String, String)>;
+
+    #[derive(Default)]
+    struct Recorded {
+        counters: Mutex<Vec<(String, u64, Labels)>>,
+        histograms: Mutex<Vec<(String, f64)>>,
+    }
+
+    fn owned(labels: &[(&'static str, &str)]) -> Labels {
+        labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    impl MetricsRecorder for Recorded {
+        fn increment_counter(
+            &self,
+            name: &'static str,
+            value: u64,
+            labels: &[(&'static str, &str)],
+        ) {
+            self.counters
+                .lock()
+                .unwrap()
+                .push((name.to_string(), value, owned(labels)));
+        }
+
+        fn record_gauge(&self, _: &'static str, _: f64, _: &[(&'static str, &str)]) {}
+
+        fn record_histogram(&self, name: &'static str, value: f64, _: &[(&'static str, &str)]) {
+            self.histograms
+                .lock()
+                .unwrap()
+                .push((name.to_string(), value));
+        }
+    }
+
+    fn session() -> (SessionMetrics, Arc<Recorded>) {
+        let recorded = Arc::new(Recorded::default());
+        let metrics = SessionMetrics::new(Some(recorded.clone() as Arc<dyn MetricsRecorder>));
+        (metrics, recorded)
+    }
+
+    #[test]
+    fn test_tool_calls_and_result() {
+        let (metrics, recorded) = session();
+        metrics.query_started();
+        metrics.observe(&json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "text", "text": "Reading"},
+                {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}
+            ]}
+        }));
+        metrics.observe(&json!({
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "total_cost_usd": 0.5,
+            "usage": {"input_tokens": 10, "output_tokens": 4}
+        }));
+
+        let counters = recorded.counters.lock().unwrap();
+        let names: Vec<&str> = counters.iter().map(|(n, _, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                QUERIES_STARTED,
+                TOOL_CALLS,
+                QUERIES_COMPLETED,
+                INPUT_TOKENS,
+                OUTPUT_TOKENS
+            ]
+        );
+        assert_eq!(counters[1].2, owned(&[("tool", "Read")]));
+        assert_eq!(
+            counters[2].2,
+            owned(&[("status", "success"), ("subtype", "success")])
+        );
+        assert_eq!(counters[3].1, 10);
+        assert_eq!(
+            *recorded.histograms.lock().unwrap(),
+            vec![(QUERY_COST_USD.to_string(), 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_hook_denials() {
+        let (metrics, recorded) = session();
+        metrics.hook_output("PreToolUse", &json!({"continue": true}));
+        metrics.hook_output("Stop", &json!({"decision": "block"}));
+        metrics.hook_output(
+            "PreToolUse",
+            &json!({"hookSpecificOutput": {"permissionDecision": "deny"}}),
+        );
+
+        let counters = recorded.counters.lock().unwrap();
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[0].2, owned(&[("event", "Stop")]));
+        assert_eq!(counters[1].2, owned(&[("event", "PreToolUse")]));
+    }
+}
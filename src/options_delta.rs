@@ -0,0 +1,368 @@
+//! Changing a connected client's options.
+//!
+//! Some options can change while the CLI runs: the model, the permission
+//! mode, and the thinking budget by control request, and, when permission
+//! prompts go to [`can_use_tool`](crate::ClaudeAgentOptions::can_use_tool),
+//! tools allowed on top of those the CLI started with by answering its
+//! permission requests.
+//! Everything else is read when the CLI starts. An [`OptionsDelta`] names
+//! the options to change, and
+//! [`ClaudeClient::apply_options_delta`](crate::ClaudeClient::apply_options_delta)
+//! applies what it can live and keeps the rest for the next connect.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::types::{ClaudeAgentOptions, PermissionMode, SystemPromptConfig};
+
+/// Options to change on a connected client. Unset fields are left as they
+/// are.
+#[derive(Debug, Clone, Default)]
+pub struct OptionsDelta {
+    /// Model. Applied live.
+    pub model: Option<String>,
+    /// Permission mode. Applied live.
+    pub permission_mode: Option<PermissionMode>,
+    /// Allowed tools. Applied live if permission prompts go to
+    /// [`can_use_tool`](crate::ClaudeAgentOptions::can_use_tool) and the
+    /// list keeps every tool allowed at connect, since the CLI runs those
+    /// without asking.
+    pub allowed_tools: Option<Vec<String>>,
+    /// Thinking budget, `Some(None)` for the default. Applied live.
+    pub max_thinking_tokens: Option<Option<u32>>,
+    /// Disallowed tools. Needs a reconnect.
+    pub disallowed_tools: Option<Vec<String>>,
+    /// System prompt. Needs a reconnect.
+    pub system_prompt: Option<SystemPromptConfig>,
+    /// Turn limit. Needs a reconnect.
+    pub max_turns: Option<Option<u32>>,
+    /// Budget. Needs a reconnect.
+    pub max_budget_usd: Option<Option<f64>>,
+    /// Working directory. Needs a reconnect.
+    pub cwd: Option<PathBuf>,
+    /// CLI environment. Needs a reconnect.
+    pub env: Option<HashMap<String, String>>,
+}
+
+impl OptionsDelta {
+    /// Create an empty delta.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change the model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Change the permission mode.
+    pub fn with_permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.permission_mode = Some(mode);
+        self
+    }
+
+    /// Change the allowed tools.
+    pub fn with_allowed_tools<T: Into<String>>(
+        mut self,
+        tools: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.allowed_tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Change the thinking budget.
+    pub fn with_max_thinking_tokens(mut self, tokens: Option<u32>) -> Self {
+        self.max_thinking_tokens = Some(tokens);
+        self
+    }
+
+    /// Change the disallowed tools.
+    pub fn with_disallowed_tools<T: Into<String>>(
+        mut self,
+        tools: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.disallowed_tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Change the system prompt.
+    pub fn with_system_prompt(mut self, prompt: impl Into<SystemPromptConfig>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Change the turn limit.
+    pub fn with_max_turns(mut self, turns: Option<u32>) -> Self {
+        self.max_turns = Some(turns);
+        self
+    }
+
+    /// Change the budget.
+    pub fn with_max_budget_usd(mut self, budget: Option<f64>) -> Self {
+        self.max_budget_usd = Some(budget);
+        self
+    }
+
+    /// Change the working directory.
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Change the CLI environment.
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Sort the fields that differ from `current` into changes to apply live
+    /// and names of fields that need a reconnect. `launch_allowed_tools` are
+    /// the tools the CLI was started with; other tools can only be allowed
+    /// live if the CLI asks the SDK for permission to use them.
+    pub(crate) fn plan(
+        &self,
+        current: &ClaudeAgentOptions,
+        launch_allowed_tools: &[String],
+    ) -> DeltaPlan {
+        let prompts_routed = current.can_use_tool.is_some();
+        let mut plan = DeltaPlan::default();
+        if let Some(model) = self
+            .model
+            .as_ref()
+            .filter(|m| current.model.as_ref() != Some(m))
+        {
+            plan.live.push(LiveChange::Model {
+                to: model.clone(),
+                from: current.model.clone(),
+            });
+        }
+        if let Some(mode) = self
+            .permission_mode
+            .filter(|mode| current.permission_mode.unwrap_or_default() != *mode)
+        {
+            plan.live.push(LiveChange::PermissionMode {
+                to: mode,
+                from: current.permission_mode.unwrap_or_default(),
+            });
+        }
+        if let Some(tokens) = self
+            .max_thinking_tokens
+            .filter(|tokens| current.max_thinking_tokens != *tokens)
+        {
+            plan.live.push(LiveChange::MaxThinkingTokens {
+                to: tokens,
+                from: current.max_thinking_tokens,
+            });
+        }
+        if let Some(tools) = self
+            .allowed_tools
+            .as_ref()
+            .filter(|tools| **tools != current.allowed_tools)
+        {
+            if prompts_routed && launch_allowed_tools.iter().all(|tool| tools.contains(tool)) {
+                let granted = |tools: &[String]| {
+                    tools
+                        .iter()
+                        .filter(|tool| !launch_allowed_tools.contains(tool))
+                        .cloned()
+                        .collect()
+                };
+                plan.live.push(LiveChange::AllowedTools {
+                    to: granted(tools),
+                    from: granted(&current.allowed_tools),
+                });
+            } else {
+                plan.reconnect.push("allowed_tools");
+            }
+        }
+
+        let mut reconnect = |field: &'static str, changed: bool| {
+            if changed {
+                plan.reconnect.push(field);
+            }
+        };
+        reconnect(
+            "disallowed_tools",
+            self.disallowed_tools
+                .as_ref()
+                .is_some_and(|tools| *tools != current.disallowed_tools),
+        );
+        reconnect(
+            "system_prompt",
+            self.system_prompt.as_ref().is_some_and(|prompt| {
+                serde_json::to_value(prompt).ok()
+                    != current
+                        .system_prompt
+                        .as_ref()
+                        .and_then(|current| serde_json::to_value(current).ok())
+            }),
+        );
+        reconnect(
+            "max_turns",
+            self.max_turns
+                .is_some_and(|turns| turns != current.max_turns),
+        );
+        reconnect(
+            "max_budget_usd",
+            self.max_budget_usd
+                .is_some_and(|budget| budget != current.max_budget_usd),
+        );
+        reconnect(
+            "cwd",
+            self.cwd
+                .as_ref()
+                .is_some_and(|cwd| current.cwd.as_ref() != Some(cwd)),
+        );
+        reconnect(
+            "env",
+            self.env.as_ref().is_some_and(|env| *env != current.env),
+        );
+        plan
+    }
+
+    /// Write the delta's fields into `options`.
+    pub(crate) fn write_to(self, options: &mut ClaudeAgentOptions) {
+        if let Some(model) = self.model {
+            options.model = Some(model);
+        }
+        if let Some(mode) = self.permission_mode {
+            options.permission_mode = Some(mode);
+        }
+        if let Some(tools) = self.allowed_tools {
+            options.allowed_tools = tools;
+        }
+        if let Some(tokens) = self.max_thinking_tokens {
+            options.max_thinking_tokens = tokens;
+        }
+        if let Some(tools) = self.disallowed_tools {
+            options.disallowed_tools = tools;
+        }
+        if let Some(prompt) = self.system_prompt {
+            options.system_prompt = Some(prompt);
+        }
+        if let Some(turns) = self.max_turns {
+            options.max_turns = turns;
+        }
+        if let Some(budget) = self.max_budget_usd {
+            options.max_budget_usd = budget;
+        }
+        if let Some(cwd) = self.cwd {
+            options.cwd = Some(cwd);
+        }
+        if let Some(env) = self.env {
+            options.env = env;
+        }
+    }
+}
+
+/// What [`ClaudeClient::apply_options_delta`](crate::ClaudeClient::apply_options_delta)
+/// did with each changed field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppliedDelta {
+    /// Fields now in effect.
+    pub applied: Vec<&'static str>,
+    /// Fields saved in the client's options that take effect on the next
+    /// connect.
+    pub requires_reconnect: Vec<&'static str>,
+}
+
+/// A delta sorted by how it can be applied.
+#[derive(Debug, Default)]
+pub(crate) struct DeltaPlan {
+    pub(crate) live: Vec<LiveChange>,
+    pub(crate) reconnect: Vec<&'static str>,
+}
+
+/// A change applied while the CLI runs, with the value to restore if a
+/// later change fails.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LiveChange {
+    Model {
+        to: String,
+        from: Option<String>,
+    },
+    PermissionMode {
+        to: PermissionMode,
+        from: PermissionMode,
+    },
+    MaxThinkingTokens {
+        to: Option<u32>,
+        from: Option<u32>,
+    },
+    /// Tools granted beyond those the CLI started with.
+    AllowedTools {
+        to: Vec<String>,
+        from: Vec<String>,
+    },
+}
+
+impl LiveChange {
+    /// The option the change applies to.
+    pub(crate) fn field(&self) -> &'static str {
+        match self {
+            Self::Model { .. } => "model",
+            Self::PermissionMode { .. } => "permission_mode",
+            Self::MaxThinkingTokens { .. } => "max_thinking_tokens",
+            Self::AllowedTools { .. } => "allowed_tools",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PermissionResult;
+
+    #[test]
+    fn test_plan_sorts_live_and_reconnect_fields() {
+        let current = ClaudeAgentOptions::new()
+            .with_model("claude-sonnet-4-5")
+            .with_allowed_tools(["Read"])
+            .with_can_use_tool(|_, _, _| async { PermissionResult::allow() });
+        let delta = OptionsDelta::new()
+            .with_model("claude-opus-4-5")
+            .with_permission_mode(PermissionMode::Default)
+            .with_allowed_tools(["Read", "Grep"])
+            .with_max_thinking_tokens(Some(8000))
+            .with_max_turns(Some(5))
+            .with_system_prompt("Be terse.");
+        let plan = delta.plan(&current, &current.allowed_tools);
+
+        // The permission mode is already the default, so it doesn't change.
+        let fields: Vec<&str> = plan.live.iter().map(LiveChange::field).collect();
+        assert_eq!(fields, ["model", "max_thinking_tokens", "allowed_tools"]);
+        assert_eq!(
+            plan.live[2],
+            LiveChange::AllowedTools {
+                to: vec!["Grep".to_string()],
+                from: Vec::new()
+            }
+        );
+        assert_eq!(plan.reconnect, ["system_prompt", "max_turns"]);
+    }
+
+    #[test]
+    fn test_removing_launch_tool_needs_reconnect() {
+        let current = ClaudeAgentOptions::new()
+            .with_allowed_tools(["Read", "Bash"])
+            .with_can_use_tool(|_, _, _| async { PermissionResult::allow() });
+        let delta = OptionsDelta::new().with_allowed_tools(["Read"]);
+        let plan = delta.plan(&current, &current.allowed_tools);
+        assert!(plan.live.is_empty());
+        assert_eq!(plan.reconnect, ["allowed_tools"]);
+
+        let mut options = current.clone();
+        delta.write_to(&mut options);
+        assert_eq!(options.allowed_tools, ["Read"]);
+    }
+
+    #[test]
+    fn test_allowed_tools_need_reconnect_without_permission_callback() {
+        let current = ClaudeAgentOptions::new().with_allowed_tools(["Read"]);
+        let delta = OptionsDelta::new().with_allowed_tools(["Read", "Grep"]);
+        let plan = delta.plan(&current, &current.allowed_tools);
+        assert!(plan.live.is_empty());
+        assert_eq!(plan.reconnect, ["allowed_tools"]);
+    }
+}
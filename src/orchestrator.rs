@@ -0,0 +1,558 @@
+//! Several agents working together, each in a session of its own.
+//!
+//! An [`Orchestrator`] runs named agents, each an [`AgentDefinition`] turned
+//! into a CLI session with the agent's prompt as system prompt and its
+//! model, tools, permission mode, and MCP servers. A run starts with a
+//! prompt to one agent; when an agent finishes a turn, its routes decide
+//! which agents get a prompt next, so a planner can hand its plan to an
+//! executor, and the executor its work to a reviewer. Agents answer one prompt at a time and keep their conversation
+//! for the whole run, and different agents work concurrently.
+//!
+//! All sessions draw on one budget. Sessions start with the budget that is
+//! left as their `max_budget_usd`, and the run stops once the agents
+//! together have spent it.
+//!
+//! A run is a stream of [`OrchestratorEvent`]s from all agents, ending with
+//! [`OrchestratorEvent::Finished`]. Dropping it stops the agents.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::orchestrator::{Handoff, Orchestrator, OrchestratorEvent};
+//! use claude_agents_sdk::{AgentDefinition, ClaudeAgentOptions};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let agent = |prompt: &str| AgentDefinition {
+//!     description: String::new(),
+//!     prompt: prompt.to_string(),
+//!     tools: None,
+//!     model: None,
+//!     mcp_servers: None,
+//!     permission_mode: None,
+//! };
+//! let orchestrator = Orchestrator::new(ClaudeAgentOptions::new())
+//!     .with_agent("planner", agent("Break the task into steps."))
+//!     .with_agent("executor", agent("Carry out the plan you are given."))
+//!     .with_agent("reviewer", agent("Review the work. Reply APPROVED or list fixes."))
+//!     .with_route("planner", "executor")
+//!     .with_route("executor", "reviewer")
+//!     .with_router("reviewer", |turn| {
+//!         if turn.text.contains("APPROVED") {
+//!             vec![]
+//!         } else {
+//!             vec![Handoff::new("executor", turn.text.clone())]
+//!         }
+//!     })
+//!     .with_budget_usd(2.0);
+//!
+//! let mut run = orchestrator.run("planner", "Add a --verbose flag to the CLI")?;
+//! while let Some(event) = run.next().await {
+//!     if let OrchestratorEvent::TurnFinished { agent, text, .. } = event {
+//!         println!("[{}] {}", agent, text);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_stream::StreamExt;
+
+use crate::client::ClaudeClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{AgentDefinition, ClaudeAgentOptions, McpServersConfig, Message, ResultMessage};
+
+/// Default limit on handoffs in one run.
+pub const DEFAULT_MAX_HANDOFFS: usize = 32;
+
+/// A finished turn of one agent, as routes see it.
+#[derive(Debug, Clone)]
+pub struct AgentTurn {
+    /// The agent's name.
+    pub agent: String,
+    /// The agent's final text for the turn.
+    pub text: String,
+    /// The turn's result message.
+    pub result: ResultMessage,
+}
+
+/// A prompt for an agent, decided by a route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handoff {
+    /// The agent to prompt.
+    pub to: String,
+    /// The prompt.
+    pub prompt: String,
+}
+
+impl Handoff {
+    /// Prompt agent `to` with `prompt`.
+    pub fn new(to: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            to: to.into(),
+            prompt: prompt.into(),
+        }
+    }
+}
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// No agent had a prompt left.
+    Completed,
+    /// The agents spent the budget.
+    BudgetExceeded,
+    /// The run reached its handoff limit.
+    HandoffLimit,
+}
+
+/// Something that happened in a run.
+#[derive(Debug)]
+pub enum OrchestratorEvent {
+    /// An agent's session connected.
+    Started {
+        /// The agent's name.
+        agent: String,
+    },
+    /// A message from an agent's session.
+    Message {
+        /// The agent's name.
+        agent: String,
+        /// The message.
+        message: Message,
+    },
+    /// An agent finished a turn.
+    TurnFinished {
+        /// The agent's name.
+        agent: String,
+        /// The agent's final text for the turn.
+        text: String,
+        /// The turn's result message.
+        result: ResultMessage,
+    },
+    /// A route passed a prompt from one agent to another.
+    Handoff {
+        /// The agent whose turn was routed.
+        from: String,
+        /// The agent prompted.
+        to: String,
+        /// The prompt.
+        prompt: String,
+    },
+    /// An agent's turn failed. The run goes on without its routes.
+    Failed {
+        /// The agent's name.
+        agent: String,
+        /// What went wrong.
+        error: ClaudeSDKError,
+    },
+    /// The run stopped. Always the last event.
+    Finished {
+        /// Why the run stopped.
+        reason: StopReason,
+        /// What all agents spent, in USD.
+        total_cost_usd: f64,
+        /// What each agent spent, in USD.
+        cost_by_agent: HashMap<String, f64>,
+    },
+}
+
+type Router = Arc<dyn Fn(&AgentTurn) -> Vec<Handoff> + Send + Sync>;
+
+/// Runs named agents that hand work to each other.
+#[derive(Clone)]
+pub struct Orchestrator {
+    options: ClaudeAgentOptions,
+    agents: HashMap<String, AgentDefinition>,
+    routes: HashMap<String, Vec<Router>>,
+    budget_usd: Option<f64>,
+    max_handoffs: usize,
+}
+
+impl Orchestrator {
+    /// Create an orchestrator whose agents' sessions start from `options`.
+    pub fn new(options: ClaudeAgentOptions) -> Self {
+        Self {
+            options,
+            agents: HashMap::new(),
+            routes: HashMap::new(),
+            budget_usd: None,
+            max_handoffs: DEFAULT_MAX_HANDOFFS,
+        }
+    }
+
+    /// Add an agent, replacing any of the same name.
+    pub fn with_agent(mut self, name: impl Into<String>, definition: AgentDefinition) -> Self {
+        self.agents.insert(name.into(), definition);
+        self
+    }
+
+    /// Pass each successful turn of `from` to `to`, its final text as the
+    /// prompt.
+    pub fn with_route(self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        let to = to.into();
+        self.with_router(from, move |turn| {
+            if turn.result.is_error {
+                Vec::new()
+            } else {
+                vec![Handoff::new(to.clone(), turn.text.clone())]
+            }
+        })
+    }
+
+    /// Decide with `router` which agents to prompt after each turn of
+    /// `from`. An agent's routers all apply, in the order added.
+    pub fn with_router<F>(mut self, from: impl Into<String>, router: F) -> Self
+    where
+        F: Fn(&AgentTurn) -> Vec<Handoff> + Send + Sync + 'static,
+    {
+        self.routes
+            .entry(from.into())
+            .or_default()
+            .push(Arc::new(router));
+        self
+    }
+
+    /// Stop the run once the agents together have spent `budget_usd`.
+    pub fn with_budget_usd(mut self, budget_usd: f64) -> Self {
+        self.budget_usd = Some(budget_usd);
+        self
+    }
+
+    /// Stop the run after `max_handoffs` handoffs, so agents routing to
+    /// each other can't go on forever. Defaults to
+    /// [`DEFAULT_MAX_HANDOFFS`].
+    pub fn with_max_handoffs(mut self, max_handoffs: usize) -> Self {
+        self.max_handoffs = max_handoffs;
+        self
+    }
+
+    /// Names of the agents.
+    pub fn agents(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.agents.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Start a run by prompting `agent`.
+    ///
+    /// Returns a configuration error if there is no such agent.
+    pub fn run(&self, agent: &str, prompt: impl Into<String>) -> Result<OrchestratorRun> {
+        if !self.agents.contains_key(agent) {
+            return Err(ClaudeSDKError::configuration(format!(
+                "Unknown agent '{}'",
+                agent
+            )));
+        }
+        let (events, rx) = mpsc::unbounded_channel();
+        let task =
+            tokio::spawn(Coordinator::new(self.clone(), events).run(Handoff::new(agent, prompt)));
+        Ok(OrchestratorRun { events: rx, task })
+    }
+
+    /// Options for an agent's session, given what's left of the budget.
+    fn agent_options(
+        &self,
+        definition: &AgentDefinition,
+        remaining_usd: Option<f64>,
+    ) -> ClaudeAgentOptions {
+        let mut options = self
+            .options
+            .clone()
+            .with_system_prompt(definition.prompt.clone());
+        if let Some(model) = &definition.model {
+            options = options.with_model(model.to_string());
+        }
+        if let Some(tools) = &definition.tools {
+            options = options.with_tools(tools.clone());
+        }
+        if let Some(mode) = definition.permission_mode {
+            options = options.with_permission_mode(mode);
+        }
+        // Servers configured in a file are passed on whole.
+        if let (Some(names), McpServersConfig::Map(servers)) =
+            (&definition.mcp_servers, &mut options.mcp_servers)
+        {
+            servers.retain(|name, _| names.contains(name));
+        }
+        if let Some(remaining) = remaining_usd {
+            options.max_budget_usd = Some(match options.max_budget_usd {
+                Some(own) => own.min(remaining),
+                None => remaining,
+            });
+        }
+        options
+    }
+}
+
+impl std::fmt::Debug for Orchestrator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Orchestrator")
+            .field("agents", &self.agents())
+            .field("budget_usd", &self.budget_usd)
+            .field("max_handoffs", &self.max_handoffs)
+            .finish()
+    }
+}
+
+/// A running orchestration: the merged events of all its agents.
+///
+/// Dropping it stops the agents.
+pub struct OrchestratorRun {
+    events: mpsc::UnboundedReceiver<OrchestratorEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for OrchestratorRun {
+    type Item = OrchestratorEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for OrchestratorRun {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl std::fmt::Debug for OrchestratorRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrchestratorRun")
+            .field("finished", &self.task.is_finished())
+            .finish()
+    }
+}
+
+/// What an agent's session reports back after each prompt.
+enum Report {
+    Turn(AgentTurn),
+    Failed {
+        agent: String,
+        error: ClaudeSDKError,
+    },
+}
+
+/// Delivers prompts to agents, routes their turns, and keeps the budget.
+struct Coordinator {
+    orchestrator: Orchestrator,
+    events: mpsc::UnboundedSender<OrchestratorEvent>,
+    reports: (
+        mpsc::UnboundedSender<Report>,
+        mpsc::UnboundedReceiver<Report>,
+    ),
+    inboxes: HashMap<String, mpsc::UnboundedSender<String>>,
+    sessions: JoinSet<()>,
+    cost_by_agent: HashMap<String, f64>,
+    pending: usize,
+}
+
+impl Coordinator {
+    fn new(orchestrator: Orchestrator, events: mpsc::UnboundedSender<OrchestratorEvent>) -> Self {
+        Self {
+            orchestrator,
+            events,
+            reports: mpsc::unbounded_channel(),
+            inboxes: HashMap::new(),
+            sessions: JoinSet::new(),
+            cost_by_agent: HashMap::new(),
+            pending: 0,
+        }
+    }
+
+    async fn run(mut self, first: Handoff) {
+        self.deliver(first);
+        let mut handoffs = 0;
+        let mut reason = StopReason::Completed;
+        while self.pending > 0 {
+            let Some(report) = self.reports.1.recv().await else {
+                break;
+            };
+            self.pending -= 1;
+            let turn = match report {
+                Report::Turn(turn) => turn,
+                Report::Failed { agent, error } => {
+                    self.emit(OrchestratorEvent::Failed { agent, error });
+                    continue;
+                }
+            };
+
+            // Sessions report their cost so far, so the latest is the total.
+            let cost = turn.result.total_cost_usd.unwrap_or(0.0);
+            self.cost_by_agent.insert(turn.agent.clone(), cost);
+            let next: Vec<Handoff> = self
+                .orchestrator
+                .routes
+                .get(&turn.agent)
+                .into_iter()
+                .flatten()
+                .flat_map(|router| router(&turn))
+                .collect();
+            let from = turn.agent.clone();
+            self.emit(OrchestratorEvent::TurnFinished {
+                agent: turn.agent,
+                text: turn.text,
+                result: turn.result,
+            });
+            if self
+                .remaining_usd()
+                .is_some_and(|remaining| remaining <= 0.0)
+            {
+                reason = StopReason::BudgetExceeded;
+                break;
+            }
+
+            for handoff in next {
+                if handoffs == self.orchestrator.max_handoffs {
+                    reason = StopReason::HandoffLimit;
+                    break;
+                }
+                handoffs += 1;
+                self.emit(OrchestratorEvent::Handoff {
+                    from: from.clone(),
+                    to: handoff.to.clone(),
+                    prompt: handoff.prompt.clone(),
+                });
+                self.deliver(handoff);
+            }
+            if reason != StopReason::Completed {
+                break;
+            }
+        }
+
+        // Idle sessions disconnect once their inbox closes; busy ones are
+        // stopped.
+        self.inboxes.clear();
+        if reason == StopReason::Completed {
+            while self.sessions.join_next().await.is_some() {}
+        } else {
+            self.sessions.shutdown().await;
+        }
+        let total_cost_usd = self.cost_by_agent.values().sum();
+        let cost_by_agent = std::mem::take(&mut self.cost_by_agent);
+        self.emit(OrchestratorEvent::Finished {
+            reason,
+            total_cost_usd,
+            cost_by_agent,
+        });
+    }
+
+    /// Queue a prompt for an agent, starting its session if needed.
+    fn deliver(&mut self, handoff: Handoff) {
+        let Some(definition) = self.orchestrator.agents.get(&handoff.to) else {
+            self.emit(OrchestratorEvent::Failed {
+                error: ClaudeSDKError::configuration(format!("Unknown agent '{}'", handoff.to)),
+                agent: handoff.to,
+            });
+            return;
+        };
+        if !self.inboxes.contains_key(&handoff.to) {
+            let options = self
+                .orchestrator
+                .agent_options(definition, self.remaining_usd());
+            let (inbox, prompts) = mpsc::unbounded_channel();
+            self.sessions.spawn(run_agent(
+                handoff.to.clone(),
+                options,
+                prompts,
+                self.events.clone(),
+                self.reports.0.clone(),
+            ));
+            self.inboxes.insert(handoff.to.clone(), inbox);
+        }
+        if self.inboxes[&handoff.to].send(handoff.prompt).is_ok() {
+            self.pending += 1;
+        }
+    }
+
+    fn remaining_usd(&self) -> Option<f64> {
+        self.orchestrator
+            .budget_usd
+            .map(|budget| budget - self.cost_by_agent.values().sum::<f64>())
+    }
+
+    fn emit(&self, event: OrchestratorEvent) {
+        let _ = self.events.send(event);
+    }
+}
+
+/// One agent's session, answering the prompts in its inbox in order.
+async fn run_agent(
+    agent: String,
+    options: ClaudeAgentOptions,
+    mut prompts: mpsc::UnboundedReceiver<String>,
+    events: mpsc::UnboundedSender<OrchestratorEvent>,
+    reports: mpsc::UnboundedSender<Report>,
+) {
+    let mut client = ClaudeClient::new(Some(options));
+    while let Some(prompt) = prompts.recv().await {
+        let report = match turn(&agent, &mut client, &prompt, &events).await {
+            Ok((text, result)) => Report::Turn(AgentTurn {
+                agent: agent.clone(),
+                text,
+                result,
+            }),
+            Err(error) => Report::Failed {
+                agent: agent.clone(),
+                error,
+            },
+        };
+        let _ = reports.send(report);
+    }
+    if client.is_connected() {
+        let _ = client.disconnect().await;
+    }
+}
+
+/// Run one turn, returning its final text and result. The final text is
+/// the result's, or else the text of the turn's last assistant message.
+async fn turn(
+    agent: &str,
+    client: &mut ClaudeClient,
+    prompt: &str,
+    events: &mpsc::UnboundedSender<OrchestratorEvent>,
+) -> Result<(String, ResultMessage)> {
+    if !client.is_connected() {
+        client.connect().await?;
+        let _ = events.send(OrchestratorEvent::Started {
+            agent: agent.to_string(),
+        });
+    }
+    client.query(prompt).await?;
+    let mut messages = client.receive_messages();
+    let mut last_text = String::new();
+    while let Some(message) = messages.next().await {
+        let message = message?.into_parsed()?;
+        let result = match &message {
+            Message::Assistant(assistant) if assistant.parent_tool_use_id.is_none() => {
+                let text = assistant.text();
+                if !text.is_empty() {
+                    last_text = text;
+                }
+                None
+            }
+            Message::Result(result) => Some(result.clone()),
+            _ => None,
+        };
+        let _ = events.send(OrchestratorEvent::Message {
+            agent: agent.to_string(),
+            message,
+        });
+        if let Some(result) = result {
+            let text = result.result.clone().unwrap_or(last_text);
+            return Ok((text, result));
+        }
+    }
+    Err(ClaudeSDKError::cli_connection(
+        "CLI closed the stream before the result",
+    ))
+}
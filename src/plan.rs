@@ -0,0 +1,152 @@
+//! The agent's task list.
+//!
+//! The CLI keeps its plan for a task as a todo list, which the model
+//! rewrites in full with the `TodoWrite` tool as work progresses.
+//! [`Message::todo_update`] reads the list from such a call, and
+//! [`ClaudeClient::current_plan`](crate::ClaudeClient::current_plan) holds
+//! the latest one, so a UI can show the agent's progress as it goes.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ContentBlock, Message};
+
+/// Status of a [`TodoItem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    /// Not started.
+    Pending,
+    /// Being worked on.
+    InProgress,
+    /// Done.
+    Completed,
+    /// A status this SDK doesn't know.
+    #[serde(other)]
+    Unknown,
+}
+
+/// One task of the agent's plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    /// What the task is, e.g. "Run the tests".
+    pub content: String,
+    /// Where the task stands.
+    pub status: TodoStatus,
+    /// The task in progressive form, e.g. "Running the tests", shown while
+    /// it is in progress.
+    #[serde(
+        rename = "activeForm",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_form: Option<String>,
+}
+
+impl Message {
+    /// The agent's new task list, if this message updates it.
+    ///
+    /// An assistant message updates the list with a `TodoWrite` tool call,
+    /// whose input holds the whole list. Calls made by subagents are not
+    /// counted, as they plan for their own task.
+    pub fn todo_update(&self) -> Option<Vec<TodoItem>> {
+        let assistant = self.as_assistant()?;
+        if assistant.parent_tool_use_id.is_some() {
+            return None;
+        }
+        assistant
+            .content
+            .iter()
+            .rev()
+            .find_map(|block| match block {
+                ContentBlock::ToolUse(tool_use) if tool_use.name == "TodoWrite" => {
+                    serde_json::from_value(tool_use.input.get("todos")?.clone()).ok()
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Keeps the latest task list from a session's messages.
+#[derive(Clone, Default)]
+pub(crate) struct PlanTracker {
+    todos: Arc<Mutex<Vec<TodoItem>>>,
+}
+
+impl PlanTracker {
+    pub(crate) fn snapshot(&self) -> Vec<TodoItem> {
+        self.todos.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn observe(&self, message: &Message) {
+        // Leave lazy messages unparsed unless they call the tool.
+        if let Message::Lazy(lazy) = message {
+            let calls_todo_write = lazy.raw()["message"]["content"]
+                .as_array()
+                .is_some_and(|blocks| blocks.iter().any(|block| block["name"] == "TodoWrite"));
+            if !calls_todo_write {
+                return;
+            }
+        }
+        if let Some(todos) = message.todo_update() {
+            *self.todos.lock().unwrap_or_else(|e| e.into_inner()) = todos;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use serde_json::json;
+
+    fn todo_write(todos: serde_json::Value, parent: Option<&str>) -> Message {
+        parse_message(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "text", "text": "Let me plan this."},
+                {"type": "tool_use", "id": "t", "name": "TodoWrite", "input": {"todos": todos}}
+            ]},
+            "parent_tool_use_id": parent
+        }))
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_todo_update_from_todo_write() {
+        let message = todo_write(
+            json!([
+                {"content": "Run the tests", "status": "in_progress", "activeForm": "Running the tests"},
+                {"content": "Fix failures", "status": "pending", "activeForm": "Fixing failures"},
+                {"content": "Ship", "status": "blocked"}
+            ]),
+            None,
+        );
+        let todos = message.todo_update().unwrap();
+        assert_eq!(todos.len(), 3);
+        assert_eq!(todos[0].status, TodoStatus::InProgress);
+        assert_eq!(todos[0].active_form.as_deref(), Some("Running the tests"));
+        assert_eq!(todos[2].status, TodoStatus::Unknown);
+        assert_eq!(todos[2].active_form, None);
+    }
+
+    #[test]
+    fn test_tracker_keeps_latest_top_level_list() {
+        let tracker = PlanTracker::default();
+        tracker.observe(&todo_write(
+            json!([{"content": "Plan", "status": "completed", "activeForm": "Planning"}]),
+            None,
+        ));
+        tracker.observe(&todo_write(
+            json!([{"content": "Subtask", "status": "pending", "activeForm": "Subtasking"}]),
+            Some("toolu_task"),
+        ));
+        tracker.observe(&todo_write(json!("not a list"), None));
+
+        let todos = tracker.snapshot();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].content, "Plan");
+    }
+}
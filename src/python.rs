@@ -0,0 +1,441 @@
+//! Messages in the shapes of the Python SDK.
+//!
+//! The Python `claude-agent-sdk` represents messages as dataclasses:
+//! `AssistantMessage`, `ResultMessage`, `TextBlock`, and so on. This module
+//! converts between [`Message`] and those dataclasses as JSON objects, one
+//! key per field, with the class name under `_type`, so recorded sessions
+//! and test fixtures can be shared between the two SDKs. Transcripts are
+//! JSONL files of such objects, one message per line.
+//!
+//! Objects without `_type`, as from plain `dataclasses.asdict()`, are read
+//! too, with the class told apart by its fields.
+//!
+//! On the Python side, these helpers write and read the same format:
+//!
+//! ```python
+//! import dataclasses
+//! import claude_agent_sdk.types as types
+//!
+//! def to_dict(obj):
+//!     if dataclasses.is_dataclass(obj):
+//!         fields = {f.name: to_dict(getattr(obj, f.name)) for f in dataclasses.fields(obj)}
+//!         return {"_type": type(obj).__name__, **fields}
+//!     if isinstance(obj, list):
+//!         return [to_dict(item) for item in obj]
+//!     return obj
+//!
+//! def from_dict(obj):
+//!     if isinstance(obj, list):
+//!         return [from_dict(item) for item in obj]
+//!     if isinstance(obj, dict) and "_type" in obj:
+//!         fields = {k: from_dict(v) for k, v in obj.items() if k != "_type"}
+//!         return getattr(types, obj["_type"])(**fields)
+//!     return obj
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::{python, query_all};
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let messages = query_all("Summarize the README", None).await?;
+//! python::write_transcript("session.jsonl", &messages)?;
+//!
+//! let conversation = python::read_transcript("recorded_in_python.jsonl")?;
+//! println!("{}", conversation.text_transcript());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::conversation::Conversation;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ContentBlock, Message, UserMessageContent};
+
+/// Python class names of messages, their tags in this SDK, and their fields.
+const MESSAGES: [(&str, &str, &[&str]); 5] = [
+    (
+        "UserMessage",
+        "user",
+        &["content", "uuid", "parent_tool_use_id"],
+    ),
+    (
+        "AssistantMessage",
+        "assistant",
+        &["content", "model", "parent_tool_use_id", "error"],
+    ),
+    ("SystemMessage", "system", &["subtype", "data"]),
+    (
+        "ResultMessage",
+        "result",
+        &[
+            "subtype",
+            "duration_ms",
+            "duration_api_ms",
+            "is_error",
+            "num_turns",
+            "session_id",
+            "total_cost_usd",
+            "usage",
+            "result",
+            "structured_output",
+        ],
+    ),
+    (
+        "StreamEvent",
+        "stream_event",
+        &["uuid", "session_id", "event", "parent_tool_use_id"],
+    ),
+];
+
+/// Python class names of content blocks, their tags in this SDK, and their
+/// fields.
+const BLOCKS: [(&str, &str, &[&str]); 4] = [
+    ("TextBlock", "text", &["text"]),
+    ("ThinkingBlock", "thinking", &["thinking", "signature"]),
+    ("ToolUseBlock", "tool_use", &["id", "name", "input"]),
+    (
+        "ToolResultBlock",
+        "tool_result",
+        &["tool_use_id", "content", "is_error"],
+    ),
+];
+
+/// A message as the Python SDK's dataclass, as JSON.
+///
+/// Every field of the dataclass is present, `null` where unset, as
+/// `dataclasses.asdict()` gives, and fields the dataclass lacks, such as
+/// an assistant message's usage, are dropped. Lazy messages are parsed and large
+/// payloads read back from disk. A [`Message::ParseWarning`], which the
+/// Python SDK has no class for, becomes a `SystemMessage` with subtype
+/// `parse_warning`.
+pub fn to_python(message: &Message) -> Result<Value> {
+    let message = message.clone().into_parsed()?;
+    let message = match message {
+        Message::ParseWarning(warning) => {
+            return Ok(serde_json::json!({
+                "_type": "SystemMessage",
+                "subtype": "parse_warning",
+                "data": to_value(&warning)?,
+            }))
+        }
+        Message::Assistant(mut assistant) => {
+            assistant.content = load_payloads(assistant.content)?;
+            Message::Assistant(assistant)
+        }
+        Message::User(mut user) => {
+            if let UserMessageContent::Blocks(blocks) = user.content {
+                user.content = UserMessageContent::Blocks(load_payloads(blocks)?);
+            }
+            Message::User(user)
+        }
+        other => other,
+    };
+
+    let value = to_value(&message)?;
+    let (class, _, fields) = MESSAGES
+        .iter()
+        .find(|(_, tag, _)| value["type"] == *tag)
+        .ok_or_else(|| {
+            ClaudeSDKError::internal(format!("No Python class for {}", value["type"]))
+        })?;
+    let mut out = dataclass(class, fields, &value);
+    if let Some(Value::Array(blocks)) = out.get_mut("content") {
+        for block in blocks {
+            let (class, _, fields) = BLOCKS
+                .iter()
+                .find(|(_, tag, _)| block["type"] == *tag)
+                .ok_or_else(|| {
+                    ClaudeSDKError::internal(format!("No Python class for {}", block["type"]))
+                })?;
+            *block = Value::Object(dataclass(class, fields, block));
+        }
+    }
+    Ok(Value::Object(out))
+}
+
+/// A message from the Python SDK's dataclass, as JSON.
+///
+/// The class is taken from `_type` if present, and otherwise told apart by
+/// its fields.
+pub fn from_python(value: &Value) -> Result<Message> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| ClaudeSDKError::message_parse("Python message is not an object"))?;
+    let class = match object.get("_type").and_then(Value::as_str) {
+        Some(class) => class,
+        None => infer_message_class(object)?,
+    };
+    let (_, tag, fields) = MESSAGES
+        .iter()
+        .find(|(name, _, _)| *name == class)
+        .ok_or_else(|| {
+            ClaudeSDKError::message_parse(format!("Unknown Python message class {}", class))
+        })?;
+
+    let mut out = fields_of(object, fields);
+    out.insert("type".to_string(), Value::String(tag.to_string()));
+    if let Some(Value::Array(blocks)) = out.get_mut("content") {
+        for block in blocks {
+            *block = block_from_python(block)?;
+        }
+    }
+    serde_json::from_value(Value::Object(out))
+        .map_err(|e| ClaudeSDKError::message_parse(format!("Invalid Python {}: {}", class, e)))
+}
+
+/// Read a transcript of Python SDK messages: JSONL, one message per line,
+/// or a JSON array of messages.
+pub fn parse_transcript(text: &str) -> Result<Conversation> {
+    if text.trim_start().starts_with('[') {
+        let values: Vec<Value> = serde_json::from_str(text).map_err(|e| {
+            ClaudeSDKError::json_decode(format!("Invalid Python transcript: {}", e))
+        })?;
+        return values.iter().map(from_python).collect();
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let value: Value = serde_json::from_str(line).map_err(|e| {
+                ClaudeSDKError::json_decode(format!(
+                    "Invalid Python transcript at line {}: {}",
+                    i + 1,
+                    e
+                ))
+            })?;
+            from_python(&value)
+        })
+        .collect()
+}
+
+/// Read a transcript file written by the Python SDK or by
+/// [`write_transcript`].
+pub fn read_transcript(path: impl AsRef<Path>) -> Result<Conversation> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Failed to open transcript {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    parse_transcript(&text)
+}
+
+/// Write messages as a JSONL transcript of Python SDK messages, replacing
+/// the file.
+pub fn write_transcript(path: impl AsRef<Path>, messages: &[Message]) -> Result<()> {
+    let path = path.as_ref();
+    let values = messages.iter().map(to_python).collect::<Result<Vec<_>>>()?;
+    let write = || -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for value in &values {
+            serde_json::to_writer(&mut file, value)?;
+            file.write_all(b"\n")?;
+        }
+        file.flush()
+    };
+    write().map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Failed to write transcript {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn to_value(value: &impl serde::Serialize) -> Result<Value> {
+    serde_json::to_value(value)
+        .map_err(|e| ClaudeSDKError::internal(format!("Failed to serialize message: {}", e)))
+}
+
+/// Replace large payload blocks with their content, which Python has no
+/// class for.
+fn load_payloads(blocks: Vec<ContentBlock>) -> Result<Vec<ContentBlock>> {
+    blocks
+        .into_iter()
+        .map(|block| match block {
+            ContentBlock::LargePayload(payload) => match payload.load_block()? {
+                Some(block) => Ok(block),
+                None => Ok(ContentBlock::Text(crate::types::TextBlock {
+                    text: payload.load()?.to_string(),
+                })),
+            },
+            other => Ok(other),
+        })
+        .collect()
+}
+
+/// The `fields` of `value` as a dataclass object named `class`.
+fn dataclass(class: &str, fields: &[&str], value: &Value) -> Map<String, Value> {
+    let mut out = Map::new();
+    out.insert("_type".to_string(), Value::String(class.to_string()));
+    for field in fields {
+        out.insert(
+            field.to_string(),
+            value.get(*field).cloned().unwrap_or(Value::Null),
+        );
+    }
+    out
+}
+
+/// The `fields` of `object` that it has.
+fn fields_of(object: &Map<String, Value>, fields: &[&str]) -> Map<String, Value> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            object
+                .get(*field)
+                .map(|value| (field.to_string(), value.clone()))
+        })
+        .collect()
+}
+
+fn block_from_python(value: &Value) -> Result<Value> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| ClaudeSDKError::message_parse("Python content block is not an object"))?;
+    let class = match object.get("_type").and_then(Value::as_str) {
+        Some(class) => class,
+        None => infer_block_class(object)?,
+    };
+    let (_, tag, fields) = BLOCKS
+        .iter()
+        .find(|(name, _, _)| *name == class)
+        .ok_or_else(|| {
+            ClaudeSDKError::message_parse(format!("Unknown Python content block class {}", class))
+        })?;
+    let mut out = fields_of(object, fields);
+    out.insert("type".to_string(), Value::String(tag.to_string()));
+    Ok(Value::Object(out))
+}
+
+fn infer_message_class(object: &Map<String, Value>) -> Result<&'static str> {
+    let has = |field: &str| object.contains_key(field);
+    Ok(if has("duration_ms") && has("num_turns") {
+        "ResultMessage"
+    } else if has("subtype") && has("data") {
+        "SystemMessage"
+    } else if has("event") && has("uuid") {
+        "StreamEvent"
+    } else if has("model") && has("content") {
+        "AssistantMessage"
+    } else if has("content") {
+        "UserMessage"
+    } else {
+        return Err(ClaudeSDKError::message_parse(
+            "Cannot tell the class of a Python message without _type",
+        ));
+    })
+}
+
+fn infer_block_class(object: &Map<String, Value>) -> Result<&'static str> {
+    let has = |field: &str| object.contains_key(field);
+    Ok(if has("tool_use_id") {
+        "ToolResultBlock"
+    } else if has("id") && has("name") && has("input") {
+        "ToolUseBlock"
+    } else if has("thinking") {
+        "ThinkingBlock"
+    } else if has("text") {
+        "TextBlock"
+    } else {
+        return Err(ClaudeSDKError::message_parse(
+            "Cannot tell the class of a Python content block without _type",
+        ));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> Vec<Message> {
+        crate::_internal::framing::frame_all(
+            include_str!("../fixtures/tool_use_turn.jsonl"),
+            1 << 20,
+        )
+        .into_iter()
+        .filter_map(|value| {
+            crate::_internal::message_parser::parse_message(value.unwrap()).unwrap()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let messages = fixture();
+        for message in &messages {
+            let python = to_python(message).unwrap();
+            let back = from_python(&python).unwrap();
+            assert_eq!(to_python(&back).unwrap(), python);
+        }
+    }
+
+    #[test]
+    fn test_writes_every_dataclass_field() {
+        let message = fixture().into_iter().find(Message::is_result).unwrap();
+        let python = to_python(&message).unwrap();
+        assert_eq!(python["_type"], "ResultMessage");
+        assert!(python.get("type").is_none());
+        assert_eq!(python.as_object().unwrap().len(), 11);
+        assert!(python.get("structured_output").unwrap().is_null());
+    }
+
+    #[test]
+    fn test_reads_untagged_asdict_output() {
+        let transcript = [
+            json!({"content": "What's in src?", "uuid": null, "parent_tool_use_id": null}),
+            json!({
+                "content": [
+                    {"text": "Let me look."},
+                    {"id": "toolu_1", "name": "Bash", "input": {"command": "ls src"}}
+                ],
+                "model": "claude-sonnet-4-5",
+                "parent_tool_use_id": null,
+                "error": null
+            }),
+            json!({"content": [{"tool_use_id": "toolu_1", "content": "lib.rs", "is_error": false}]}),
+            json!({
+                "subtype": "success", "duration_ms": 10, "duration_api_ms": 8, "is_error": false,
+                "num_turns": 2, "session_id": "s", "total_cost_usd": 0.01, "usage": null,
+                "result": "lib.rs", "structured_output": null
+            }),
+        ]
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        let conversation = parse_transcript(&transcript).unwrap();
+        assert_eq!(conversation.len(), 4);
+        assert_eq!(conversation.tool_uses_of("Bash").len(), 1);
+        assert!(conversation.tool_result_for("toolu_1").is_some());
+        assert_eq!(
+            conversation.result().unwrap().result.as_deref(),
+            Some("lib.rs")
+        );
+    }
+
+    #[test]
+    fn test_transcript_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let messages = fixture();
+        write_transcript(&path, &messages).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), messages.len());
+        assert!(text.contains("\"_type\":\"ToolUseBlock\""));
+        assert_eq!(read_transcript(&path).unwrap().len(), messages.len());
+
+        let array = format!("[{}]", text.trim().lines().collect::<Vec<_>>().join(","));
+        assert_eq!(parse_transcript(&array).unwrap().len(), messages.len());
+    }
+}
@@ -4,11 +4,19 @@
 //! to Claude. For more complex interactions requiring bidirectional
 //! communication, use [`ClaudeClient`](crate::ClaudeClient).
 
+use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
 use tokio_stream::Stream;
+use tracing::debug;
 
-use crate::_internal::client::InternalClient;
-use crate::errors::Result;
+use crate::_internal::client::{unwrap_shared, InternalClient};
+use crate::attachments::{prompt_content, Attachment};
+use crate::client::ClaudeClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::structured;
 use crate::types::{ClaudeAgentOptions, Message};
 
 /// Execute a one-shot query to Claude.
@@ -69,6 +77,239 @@ pub async fn query(
     InternalClient::process_query(options, prompt).await
 }
 
+/// Execute a one-shot query, yielding each message in an [`Arc`].
+///
+/// Like [`query`], but messages are handed over in the allocation they were
+/// parsed into. Fanning them out to several consumers then costs a reference
+/// count instead of a copy, which matters when tool results run to megabytes.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use claude_agents_sdk::{query_shared, Message};
+/// use tokio_stream::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let (tx, mut rx) = tokio::sync::mpsc::channel::<Arc<Message>>(16);
+///     tokio::spawn(async move {
+///         while let Some(message) = rx.recv().await {
+///             println!("logged: {}", message);
+///         }
+///     });
+///
+///     let mut stream = query_shared("Read src/main.rs", None).await?;
+///     while let Some(message) = stream.next().await {
+///         let message = message?;
+///         tx.send(Arc::clone(&message)).await?;
+///         if message.is_result() {
+///             break;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn query_shared(
+    prompt: &str,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Arc<Message>>> + Send>>> {
+    let options = options.unwrap_or_default();
+    InternalClient::process_query_shared(options, prompt).await
+}
+
+/// Execute a one-shot query with a specific model and otherwise default options.
+///
+/// Shorthand for [`query`] with `ClaudeAgentOptions::new().with_model(model)`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{query_with_model, ModelId};
+/// use tokio_stream::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut stream = query_with_model("Hello, Claude!", ModelId::Haiku45).await?;
+///     while let Some(message) = stream.next().await {
+///         println!("{}", message?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn query_with_model(
+    prompt: &str,
+    model: impl Into<String>,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Message>> + Send>>> {
+    let options = ClaudeAgentOptions::new().with_model(model);
+    InternalClient::process_query(options, prompt).await
+}
+
+/// Execute a one-shot query in a working directory with otherwise default options.
+///
+/// Shorthand for [`query`] with `ClaudeAgentOptions::new().with_cwd(cwd)`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::query_in_dir;
+/// use tokio_stream::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut stream = query_in_dir("Summarize the README", "./my-project").await?;
+///     while let Some(message) = stream.next().await {
+///         println!("{}", message?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn query_in_dir(
+    prompt: &str,
+    cwd: impl Into<PathBuf>,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Message>> + Send>>> {
+    let options = ClaudeAgentOptions::new().with_cwd(cwd);
+    InternalClient::process_query(options, prompt).await
+}
+
+/// Execute a one-shot query with local files attached.
+///
+/// Like [`query`], with the prompt followed by a content block per
+/// attachment: images and PDFs base64-encoded, text files inlined.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{query_with_attachments, Attachment};
+/// use tokio_stream::StreamExt;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let attachments = vec![
+///         Attachment::from_path("diagram.png")?,
+///         Attachment::from_path("src/lib.rs")?,
+///     ];
+///     let mut stream =
+///         query_with_attachments("Does the code match the diagram?", attachments, None).await?;
+///     while let Some(message) = stream.next().await {
+///         println!("{}", message?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn query_with_attachments(
+    prompt: &str,
+    attachments: Vec<Attachment>,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Message>> + Send>>> {
+    use tokio_stream::StreamExt;
+
+    let options = options.unwrap_or_default();
+    let content = prompt_content(prompt, &attachments);
+    let stream = InternalClient::process_query_content(options, content).await?;
+    Ok(Box::pin(stream.map(|message| message.map(unwrap_shared))))
+}
+
+/// Execute a query for output matching a JSON schema, deserialized as `T`.
+///
+/// The schema is passed to the CLI as the
+/// [`output_format`](ClaudeAgentOptions::output_format). The output is
+/// taken from the result's structured output, or else parsed from its
+/// text. When it doesn't deserialize into `T`, the options'
+/// [`StructuredOutputPolicy`](crate::StructuredOutputPolicy) decides
+/// whether to tell the model why and ask again, in the same session.
+///
+/// # Errors
+///
+/// Returns a message parse error, with the rejected value when there is
+/// one, if the output is still invalid after the allowed repairs, or if the
+/// query ends in an error result. A result failing on a rate limit is
+/// returned as [`ClaudeSDKError::RateLimited`] instead.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use claude_agents_sdk::{query_structured, ClaudeAgentOptions, StructuredOutputPolicy};
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize)]
+/// struct Release {
+///     version: String,
+///     breaking: bool,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let schema = json!({
+///         "type": "object",
+///         "properties": {"version": {"type": "string"}, "breaking": {"type": "boolean"}},
+///         "required": ["version", "breaking"]
+///     });
+///     let options = ClaudeAgentOptions::new()
+///         .with_structured_output_policy(StructuredOutputPolicy::repair(2));
+///     let release: Release =
+///         query_structured("Describe the latest release in CHANGELOG.md", schema, Some(options))
+///             .await?;
+///     println!("{} (breaking: {})", release.version, release.breaking);
+///     Ok(())
+/// }
+/// ```
+pub async fn query_structured<T: DeserializeOwned>(
+    prompt: &str,
+    schema: serde_json::Value,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<T> {
+    let mut options = options.unwrap_or_default();
+    options.output_format = Some(serde_json::json!({"type": "json_schema", "schema": schema}));
+    let policy = options.structured_output_policy.clone();
+
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await?;
+    let outcome = async {
+        client.query(prompt).await?;
+        let mut repairs = 0;
+        loop {
+            let (text, result) = client.receive_response().await?;
+            if let Some(info) = result.rate_limit {
+                return Err(ClaudeSDKError::rate_limited(
+                    result.result.unwrap_or_default(),
+                    *info,
+                ));
+            }
+            if result.is_error {
+                return Err(ClaudeSDKError::message_parse(format!(
+                    "Structured query ended with {}: {}",
+                    result.subtype,
+                    result.result.as_deref().unwrap_or_default()
+                )));
+            }
+            match structured::extract(&text, &result) {
+                Ok(value) => return Ok(value),
+                Err((reason, _)) if repairs < policy.max_repairs => {
+                    repairs += 1;
+                    debug!("Structured output invalid, asking for a repair: {}", reason);
+                    client.query(&policy.repair_prompt(&reason)).await?;
+                }
+                Err((reason, value)) => {
+                    let message = format!(
+                        "Invalid structured output after {} attempts: {}",
+                        repairs + 1,
+                        reason
+                    );
+                    return Err(match value {
+                        Some(value) => ClaudeSDKError::message_parse_with_raw(message, value),
+                        None => ClaudeSDKError::message_parse(message),
+                    });
+                }
+            }
+        }
+    }
+    .await;
+    let _ = client.disconnect().await;
+    outcome
+}
+
 /// Execute a query and collect all messages.
 ///
 /// This is a convenience function that collects all messages from a query
@@ -212,7 +453,7 @@ pub async fn query_result(
     let mut result_message = None;
 
     while let Some(result) = stream.next().await {
-        match result? {
+        match result?.into_parsed()? {
             Message::Assistant(msg) => {
                 let text = msg.text();
                 if !text.is_empty() {
@@ -0,0 +1,290 @@
+//! Session recording and offline replay.
+//!
+//! A recording is a portable JSONL file: a [`RecordingHeader`] line followed by
+//! one [`WireEntry`] per line sent to or received from the CLI. [`Recorder`]
+//! produces one from a live session through the [wire log](crate::WireLog),
+//! and [`Replayer`] feeds it back through the SDK's message parser so a bug
+//! seen in someone else's environment can be reproduced from their file.
+//!
+//! # Examples
+//!
+//! Record a session:
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::recorder::Recorder;
+//! use claude_agents_sdk::{query, ClaudeAgentOptions};
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let options = ClaudeAgentOptions::new().with_wire_log(Recorder::start("session.jsonl")?);
+//! let stream = query("Hello", Some(options)).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Replay it one step at a time:
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::recorder::{ReplayEvent, Replayer};
+//!
+//! # fn example() -> claude_agents_sdk::Result<()> {
+//! let mut replayer = Replayer::from_file("session.jsonl")?;
+//! while let Some(step) = replayer.step() {
+//!     if let ReplayEvent::Error(e) = &step.event {
+//!         eprintln!("line {}: {}\n  {}", step.index, e, step.entry.line);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::_internal::message_parser::{
+    decode_line, is_control_request, is_control_response, parse_control_request,
+    parse_control_response, parse_message,
+};
+use crate::control::{ControlRequest, ControlResponse};
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::Message;
+use crate::wire_log::{WireDirection, WireEntry, WireLog};
+
+/// Current recording format version.
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// First line of a recording file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    /// Recording format version.
+    pub recording: u32,
+    /// SDK version that made the recording.
+    pub sdk_version: String,
+    /// Milliseconds since the Unix epoch when recording started.
+    pub created_ms: u64,
+}
+
+impl Default for RecordingHeader {
+    fn default() -> Self {
+        Self {
+            recording: RECORDING_FORMAT_VERSION,
+            sdk_version: crate::VERSION.to_string(),
+            created_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// A recorded session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Recording metadata.
+    pub header: RecordingHeader,
+    /// Captured lines, in order.
+    pub entries: Vec<WireEntry>,
+}
+
+impl Recording {
+    /// Load a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).map_err(|e| {
+            ClaudeSDKError::configuration(format!(
+                "Failed to open recording {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let mut lines = BufReader::new(file).lines();
+        let invalid = |line: usize, detail: String| {
+            ClaudeSDKError::configuration(format!(
+                "Invalid recording {} at line {}: {}",
+                path.display(),
+                line,
+                detail
+            ))
+        };
+
+        let header = match lines.next() {
+            Some(line) => line.map_err(|e| invalid(1, e.to_string()))?,
+            None => return Err(invalid(1, "empty file".to_string())),
+        };
+        let header: RecordingHeader =
+            serde_json::from_str(&header).map_err(|e| invalid(1, e.to_string()))?;
+        if header.recording > RECORDING_FORMAT_VERSION {
+            return Err(invalid(
+                1,
+                format!("unsupported format version {}", header.recording),
+            ));
+        }
+
+        let mut entries = Vec::new();
+        for (i, line) in lines.enumerate() {
+            let line = line.map_err(|e| invalid(i + 2, e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| invalid(i + 2, e.to_string()))?);
+        }
+
+        Ok(Self { header, entries })
+    }
+
+    /// Write the recording to a file, replacing it.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let write = || -> std::io::Result<()> {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            serde_json::to_writer(&mut file, &self.header)?;
+            file.write_all(b"\n")?;
+            for entry in &self.entries {
+                serde_json::to_writer(&mut file, entry)?;
+                file.write_all(b"\n")?;
+            }
+            file.flush()
+        };
+        write().map_err(|e| {
+            ClaudeSDKError::configuration(format!(
+                "Failed to write recording {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Starts recordings of live sessions.
+pub struct Recorder;
+
+impl Recorder {
+    /// Create a recording file at `path` and return a [`WireLog`] that appends
+    /// the session's traffic to it.
+    ///
+    /// Recordings contain everything exchanged with the CLI, including prompts
+    /// and tool output; review them before sharing.
+    pub fn start(path: impl AsRef<Path>) -> Result<WireLog> {
+        let path = path.as_ref();
+        Recording::default().save(path)?;
+        Ok(WireLog::file(path))
+    }
+}
+
+/// What a recorded line parsed into.
+#[derive(Debug)]
+pub enum ReplayEvent {
+    /// A message the CLI sent, as the client would have yielded it.
+    Message(Message),
+    /// A control request, from either side.
+    ControlRequest(ControlRequest),
+    /// A control response, from either side.
+    ControlResponse(ControlResponse),
+    /// Another line the SDK sent, such as a user message.
+    Sent(serde_json::Value),
+    /// A received line of a type the parser skips.
+    Skipped,
+    /// The line failed to parse.
+    Error(ClaudeSDKError),
+}
+
+/// One replayed line.
+#[derive(Debug)]
+pub struct ReplayStep {
+    /// Position of the entry in the recording.
+    pub index: usize,
+    /// The recorded line.
+    pub entry: WireEntry,
+    /// What it parsed into.
+    pub event: ReplayEvent,
+}
+
+/// Replays a [`Recording`] through the SDK's parsing pipeline.
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    recording: Recording,
+    position: usize,
+}
+
+impl Replayer {
+    /// Create a replayer for a recording.
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// Load a recording file and create a replayer for it.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Recording::load(path).map(Self::new)
+    }
+
+    /// The recording's header.
+    pub fn header(&self) -> &RecordingHeader {
+        &self.recording.header
+    }
+
+    /// Number of entries not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.recording.entries.len() - self.position
+    }
+
+    /// Replay the next entry.
+    pub fn step(&mut self) -> Option<ReplayStep> {
+        let entry = self.recording.entries.get(self.position)?.clone();
+        let index = self.position;
+        self.position += 1;
+        let event = Self::parse(&entry);
+        Some(ReplayStep {
+            index,
+            entry,
+            event,
+        })
+    }
+
+    /// Start again from the first entry.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Replay the remaining entries, yielding what the client's message
+    /// stream would have yielded: parsed messages and parse errors.
+    pub fn messages(self) -> impl Iterator<Item = Result<Message>> {
+        self.filter_map(|step| match step.event {
+            ReplayEvent::Message(message) => Some(Ok(message)),
+            ReplayEvent::Error(e) if step.entry.direction == WireDirection::Received => {
+                Some(Err(e))
+            }
+            _ => None,
+        })
+    }
+
+    fn parse(entry: &WireEntry) -> ReplayEvent {
+        let raw = match decode_line(&entry.line) {
+            Ok(raw) => raw,
+            Err(e) => return ReplayEvent::Error(e),
+        };
+
+        let result = if is_control_request(&raw) {
+            parse_control_request(raw).map(ReplayEvent::ControlRequest)
+        } else if is_control_response(&raw) {
+            parse_control_response(raw).map(ReplayEvent::ControlResponse)
+        } else if entry.direction == WireDirection::Sent {
+            Ok(ReplayEvent::Sent(raw))
+        } else {
+            parse_message(raw).map(|m| m.map_or(ReplayEvent::Skipped, ReplayEvent::Message))
+        };
+        result.unwrap_or_else(ReplayEvent::Error)
+    }
+}
+
+impl Iterator for Replayer {
+    type Item = ReplayStep;
+
+    fn next(&mut self) -> Option<ReplayStep> {
+        self.step()
+    }
+}
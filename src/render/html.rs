@@ -0,0 +1,734 @@
+//! HTML rendering of messages.
+//!
+//! Assistant text is usually markdown. [`markdown_to_html`] converts the
+//! parts of markdown Claude writes: headings, paragraphs, emphasis, code
+//! spans and fenced blocks, lists, block quotes, tables, rules, and links.
+//! [`html`] renders a whole message, adding collapsible summaries of tool
+//! calls, tool results, and thinking.
+//!
+//! The output is safe to insert into a page. Raw HTML in the input is
+//! escaped rather than passed through, and links with a scheme other than
+//! `http`, `https`, or `mailto` are rendered as plain text.
+//!
+//! Elements carry classes (`prompt`, `tool-call`, `tool-result`, `error`,
+//! `thinking`, `result`) for styling. When streaming partial messages,
+//! re-render the accumulated text on each delta; unclosed constructs render
+//! as literal text until they are closed.
+
+use serde_json::Value;
+
+use super::{result_summary, truncate};
+use crate::types::{ContentBlock, Message, ToolResultBlock, ToolUseBlock, UserMessageContent};
+
+/// Longest tool call summary, in characters.
+const SUMMARY_LEN: usize = 80;
+
+/// Render `message` as an HTML fragment.
+///
+/// Prompts, assistant content, tool results, and results are rendered;
+/// system messages, stream events, parse warnings, and lazy messages that
+/// fail to parse render as nothing.
+pub fn html(message: &Message) -> String {
+    let mut out = String::new();
+    match message {
+        Message::User(user) => match &user.content {
+            UserMessageContent::Text(text) => {
+                out.push_str(&format!("<div class=\"prompt\">{}</div>\n", escape(text)))
+            }
+            UserMessageContent::Blocks(blocks) => blocks.iter().for_each(|b| block(&mut out, b)),
+        },
+        Message::Assistant(assistant) => assistant.content.iter().for_each(|b| block(&mut out, b)),
+        Message::Result(result) => {
+            let class = if result.is_error {
+                "result error"
+            } else {
+                "result"
+            };
+            out.push_str(&format!(
+                "<div class=\"{}\">{}</div>\n",
+                class,
+                escape(&result_summary(result))
+            ));
+        }
+        Message::Lazy(lazy) => {
+            if let Ok(parsed) = lazy.get() {
+                return html(parsed);
+            }
+        }
+        Message::System(_) | Message::StreamEvent(_) | Message::ParseWarning(_) => {}
+    }
+    out
+}
+
+fn block(out: &mut String, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text(text) => out.push_str(&markdown_to_html(&text.text)),
+        ContentBlock::Thinking(thinking) => out.push_str(&format!(
+            "<details class=\"thinking\"><summary>Thinking</summary>\n{}</details>\n",
+            markdown_to_html(&thinking.thinking)
+        )),
+        ContentBlock::ToolUse(tool_use) => tool_call(out, tool_use),
+        ContentBlock::ToolResult(result) => tool_result(out, result),
+        ContentBlock::LargePayload(_) => out.push_str(&format!(
+            "<div class=\"large-payload\">{}</div>\n",
+            escape(&block.to_string())
+        )),
+    }
+}
+
+fn tool_call(out: &mut String, tool_use: &ToolUseBlock) {
+    let summary = match tool_summary(&tool_use.input) {
+        Some(summary) => format!(" <code>{}</code>", escape(&summary)),
+        None => String::new(),
+    };
+    let input = serde_json::to_string_pretty(&tool_use.input).unwrap_or_default();
+    out.push_str(&format!(
+        "<details class=\"tool-call\"><summary>{}{}</summary><pre>{}</pre></details>\n",
+        escape(&tool_use.name),
+        summary,
+        escape(&input)
+    ));
+}
+
+fn tool_result(out: &mut String, result: &ToolResultBlock) {
+    let (class, label) = if result.is_error == Some(true) {
+        ("tool-result error", "Error")
+    } else {
+        ("tool-result", "Result")
+    };
+    out.push_str(&format!(
+        "<details class=\"{}\"><summary>{}</summary><pre>{}</pre></details>\n",
+        class,
+        label,
+        escape(&result.text())
+    ));
+}
+
+/// The first line of the input's first string field, such as a Bash
+/// command or a file path.
+fn tool_summary(input: &Value) -> Option<String> {
+    let first = input.as_object()?.values().find_map(Value::as_str)?;
+    let line = first.lines().next().unwrap_or("");
+    Some(truncate(line, SUMMARY_LEN))
+}
+
+/// Escape text for HTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Convert markdown to an HTML fragment.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::render::markdown_to_html;
+///
+/// assert_eq!(
+///     markdown_to_html("Run `cargo test` **first**."),
+///     "<p>Run <code>cargo test</code> <strong>first</strong>.</p>\n"
+/// );
+/// assert_eq!(
+///     markdown_to_html("<script>alert(1)</script>"),
+///     "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>\n"
+/// );
+/// ```
+pub fn markdown_to_html(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out = String::new();
+    blocks(&mut out, &lines);
+    out
+}
+
+fn blocks(out: &mut String, lines: &[&str]) {
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() {
+            i += 1;
+        } else if let Some(fence) = fence(trimmed) {
+            let info = trimmed[fence.len()..].trim();
+            let end = lines[i + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with(fence))
+                .map_or(lines.len(), |n| i + 1 + n);
+            let code: String = lines[i + 1..end]
+                .iter()
+                .map(|l| format!("{}\n", l))
+                .collect();
+            match info.split_whitespace().next() {
+                Some(lang) => out.push_str(&format!(
+                    "<pre><code class=\"language-{}\">{}</code></pre>\n",
+                    escape(lang),
+                    escape(&code)
+                )),
+                None => out.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&code))),
+            }
+            i = end + 1;
+        } else if let Some((level, text)) = heading(trimmed) {
+            out.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline(text)));
+            i += 1;
+        } else if is_rule(trimmed) {
+            out.push_str("<hr>\n");
+            i += 1;
+        } else if trimmed.starts_with('>') {
+            let quoted: Vec<&str> = lines[i..]
+                .iter()
+                .take_while(|l| l.trim_start().starts_with('>'))
+                .map(|l| {
+                    let l = &l.trim_start()[1..];
+                    l.strip_prefix(' ').unwrap_or(l)
+                })
+                .collect();
+            i += quoted.len();
+            out.push_str("<blockquote>\n");
+            blocks(out, &quoted);
+            out.push_str("</blockquote>\n");
+        } else if list_item(line).is_some() {
+            i = list(out, lines, i);
+        } else if i + 1 < lines.len() && line.contains('|') && is_table_separator(lines[i + 1]) {
+            i = table(out, lines, i);
+        } else {
+            let start = i;
+            i += 1;
+            while i < lines.len() && !interrupts_paragraph(lines[i]) {
+                i += 1;
+            }
+            let text = lines[start..i]
+                .iter()
+                .map(|l| l.trim())
+                .collect::<Vec<_>>()
+                .join("\n");
+            out.push_str(&format!("<p>{}</p>\n", inline(&text)));
+        }
+    }
+}
+
+fn interrupts_paragraph(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty()
+        || fence(trimmed).is_some()
+        || heading(trimmed).is_some()
+        || trimmed.starts_with('>')
+        || is_rule(trimmed)
+        || list_item(line).is_some()
+}
+
+fn fence(line: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|fence| line.starts_with(fence))
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level, rest.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&m| chars.iter().all(|&c| c == m))
+}
+
+struct ListItem<'a> {
+    indent: usize,
+    ordered: Option<u64>,
+    /// Width of the indent and marker, where continuation lines start.
+    content_indent: usize,
+    text: &'a str,
+}
+
+fn list_item(line: &str) -> Option<ListItem<'_>> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    let (ordered, marker_len) = if rest.starts_with(['-', '*', '+']) {
+        (None, 1)
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if !(1..=9).contains(&digits) || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        (rest[..digits].parse().ok(), digits + 1)
+    };
+    let after = &rest[marker_len..];
+    if !(after.is_empty() || after.starts_with(' ')) {
+        return None;
+    }
+    Some(ListItem {
+        indent,
+        ordered,
+        content_indent: indent + marker_len + 1,
+        text: after.trim_start(),
+    })
+}
+
+/// Render the list starting at `lines[start]`; returns the index after it.
+fn list(out: &mut String, lines: &[&str], start: usize) -> usize {
+    let first = list_item(lines[start]).expect("list starts with an item");
+    let (open, close) = match first.ordered {
+        Some(1) => ("<ol>".to_string(), "</ol>"),
+        Some(n) => (format!("<ol start=\"{}\">", n), "</ol>"),
+        None => ("<ul>".to_string(), "</ul>"),
+    };
+    out.push_str(&open);
+    out.push('\n');
+
+    let mut i = start;
+    while i < lines.len() {
+        let item = match list_item(lines[i]) {
+            Some(item)
+                if item.indent == first.indent
+                    && item.ordered.is_some() == first.ordered.is_some() =>
+            {
+                item
+            }
+            _ => break,
+        };
+        let mut body = vec![item.text];
+        i += 1;
+        // Continuation lines are indented past the marker or nested items;
+        // a blank line only continues the item if indented content follows.
+        while i < lines.len() {
+            let line = lines[i];
+            let indent = line.len() - line.trim_start().len();
+            if line.trim().is_empty() {
+                let next_indented = lines[i + 1..]
+                    .iter()
+                    .find(|l| !l.trim().is_empty())
+                    .is_some_and(|l| l.len() - l.trim_start().len() > first.indent);
+                if !next_indented {
+                    break;
+                }
+                body.push("");
+            } else if indent > first.indent {
+                body.push(&line[indent.min(item.content_indent)..]);
+            } else if !interrupts_paragraph(line) && !body.last().is_some_and(|l| l.is_empty()) {
+                // Lazy continuation of the item's paragraph.
+                body.push(line.trim());
+            } else {
+                break;
+            }
+            i += 1;
+        }
+
+        let mut content = String::new();
+        blocks(&mut content, &body);
+        // Items without blank lines are tight: their leading paragraph is
+        // rendered without `<p>` tags.
+        let tight = !body.iter().any(|l| l.is_empty());
+        let content = match content.strip_prefix("<p>") {
+            Some(rest) if tight => rest.replacen("</p>\n", "\n", 1),
+            _ => content,
+        };
+        out.push_str(&format!("<li>{}</li>\n", content.trim_end()));
+        while i < lines.len() && lines[i].trim().is_empty() {
+            i += 1;
+        }
+    }
+    out.push_str(close);
+    out.push('\n');
+    i
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let cells = table_cells(line);
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let cell = cell.trim();
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+}
+
+fn table_cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(str::trim).collect()
+}
+
+/// Render the table starting at `lines[start]`; returns the index after it.
+fn table(out: &mut String, lines: &[&str], start: usize) -> usize {
+    let aligns: Vec<&str> = table_cells(lines[start + 1])
+        .iter()
+        .map(|cell| match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => " style=\"text-align:center\"",
+            (false, true) => " style=\"text-align:right\"",
+            (true, false) => " style=\"text-align:left\"",
+            (false, false) => "",
+        })
+        .collect();
+    let row = |out: &mut String, line: &str, tag: &str| {
+        out.push_str("<tr>");
+        for (i, align) in aligns.iter().enumerate() {
+            let cell = table_cells(line).get(i).copied().unwrap_or("");
+            out.push_str(&format!("<{0}{1}>{2}</{0}>", tag, align, inline(cell)));
+        }
+        out.push_str("</tr>\n");
+    };
+
+    out.push_str("<table>\n<thead>\n");
+    row(out, lines[start], "th");
+    out.push_str("</thead>\n");
+    let mut i = start + 2;
+    let body_start = i;
+    while i < lines.len() && lines[i].contains('|') && !lines[i].trim().is_empty() {
+        if i == body_start {
+            out.push_str("<tbody>\n");
+        }
+        row(out, lines[i], "td");
+        i += 1;
+    }
+    if i > body_start {
+        out.push_str("</tbody>\n");
+    }
+    out.push_str("</table>\n");
+    i
+}
+
+/// Render inline markdown.
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    inline_into(&mut out, &chars);
+    out
+}
+
+fn inline_into(out: &mut String, chars: &[char]) {
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i..];
+        if c == '\\' && chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) {
+            out.push_str(&escape(&chars[i + 1].to_string()));
+            i += 2;
+        } else if c == '`' {
+            let run = rest.iter().take_while(|&&c| c == '`').count();
+            match find_run(chars, i + run, '`', run) {
+                Some(end) => {
+                    let code: String = chars[i + run..end].iter().collect();
+                    let code = if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') {
+                        &code[1..code.len() - 1]
+                    } else {
+                        &code
+                    };
+                    out.push_str(&format!("<code>{}</code>", escape(code)));
+                    i = end + run;
+                }
+                None => {
+                    out.push_str(&"`".repeat(run));
+                    i += run;
+                }
+            }
+        } else if let Some(end) = emphasis(chars, i, 2) {
+            out.push_str(if c == '~' { "<del>" } else { "<strong>" });
+            inline_into(out, &chars[i + 2..end]);
+            out.push_str(if c == '~' { "</del>" } else { "</strong>" });
+            i = end + 2;
+        } else if let Some(end) = emphasis(chars, i, 1) {
+            out.push_str("<em>");
+            inline_into(out, &chars[i + 1..end]);
+            out.push_str("</em>");
+            i = end + 1;
+        } else if let Some((label, url, end)) = link(chars, i) {
+            match safe_url(&url) {
+                Some(url) => {
+                    out.push_str(&format!("<a href=\"{}\">", escape(url)));
+                    inline_into(out, label);
+                    out.push_str("</a>");
+                }
+                None => inline_into(out, label),
+            }
+            i = end;
+        } else if let Some((url, end)) = autolink(chars, i) {
+            match safe_url(&url) {
+                Some(safe) => out.push_str(&format!("<a href=\"{0}\">{0}</a>", escape(safe))),
+                None => out.push_str(&escape(&format!("<{}>", url))),
+            }
+            i = end;
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            i += 1;
+        }
+    }
+}
+
+/// Index of the next run of exactly `len` `marker`s at or after `from`.
+fn find_run(chars: &[char], from: usize, marker: char, len: usize) -> Option<usize> {
+    let mut i = from;
+    while i < chars.len() {
+        if chars[i] == marker {
+            let run = chars[i..].iter().take_while(|&&c| c == marker).count();
+            if run == len {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// If an emphasis span with a `len`-character delimiter opens at `start`,
+/// the index of its closing delimiter.
+///
+/// `*` and `_` delimit emphasis (`len` 1) and strong emphasis (`len` 2),
+/// and `~~` strikethrough. The span must not start or end with whitespace,
+/// and `_` only counts outside words, so `snake_case_names` stay intact.
+fn emphasis(chars: &[char], start: usize, len: usize) -> Option<usize> {
+    let marker = chars[start];
+    let valid = matches!((marker, len), ('*' | '_', _) | ('~', 2));
+    if !valid || chars[start..].iter().take_while(|&&c| c == marker).count() != len {
+        return None;
+    }
+    let in_word = |i: usize| chars.get(i).is_some_and(|c| c.is_alphanumeric());
+    if marker == '_' && start > 0 && in_word(start - 1) {
+        return None;
+    }
+    let open = start + len;
+    if chars.get(open).map_or(true, |c| c.is_whitespace()) {
+        return None;
+    }
+    let mut i = open;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let run = chars[i..].iter().take_while(|&&c| c == '`').count();
+            i = find_run(chars, i + run, '`', run).map_or(i + run, |end| end + run);
+            continue;
+        }
+        if chars[i] == marker {
+            let run = chars[i..].iter().take_while(|&&c| c == marker).count();
+            let closes =
+                run == len && !chars[i - 1].is_whitespace() && !(marker == '_' && in_word(i + len));
+            if closes && i > open {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// A `[label](url)` link at `start`: the label, URL, and index after it.
+fn link(chars: &[char], start: usize) -> Option<(&[char], String, usize)> {
+    if chars[start] != '[' {
+        return None;
+    }
+    let mut depth = 0;
+    let mut close = None;
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    // Parentheses in the URL must be balanced, as in wiki links.
+    let mut depth = 0;
+    let len = chars[close + 2..].iter().position(|&c| {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return true,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        false
+    })?;
+    let end = close + 2 + len;
+    let target: String = chars[close + 2..end].iter().collect();
+    // Drop an optional title: [label](url "title").
+    let url = target.split_whitespace().next().unwrap_or("").to_string();
+    Some((&chars[start + 1..close], url, end + 1))
+}
+
+/// A `<scheme:...>` autolink at `start`: the URL and index after it.
+fn autolink(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if chars[start] != '<' {
+        return None;
+    }
+    let end = chars[start + 1..].iter().position(|&c| c == '>')? + start + 1;
+    let url: String = chars[start + 1..end].iter().collect();
+    let scheme = url.split(':').next()?;
+    let is_url = url.contains(':')
+        && !scheme.is_empty()
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+        && !url.contains(char::is_whitespace);
+    is_url.then_some((url, end + 1))
+}
+
+/// `url` if it is safe to link to: `http`, `https`, and `mailto` URLs and
+/// relative references.
+fn safe_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let scheme_end = url.find(':');
+    let path_start = url.find(['/', '?', '#']);
+    let has_scheme = match (scheme_end, path_start) {
+        (Some(colon), Some(path)) => colon < path,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    if !has_scheme {
+        return Some(url);
+    }
+    let scheme = url[..scheme_end?].to_ascii_lowercase();
+    matches!(scheme.as_str(), "http" | "https" | "mailto").then_some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AssistantMessage, TextBlock, UserMessage};
+    use serde_json::json;
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            inline("**bold** *em* _em_ ~~gone~~ `a < b`"),
+            "<strong>bold</strong> <em>em</em> <em>em</em> <del>gone</del> <code>a &lt; b</code>"
+        );
+        assert_eq!(inline("snake_case_name"), "snake_case_name");
+        assert_eq!(inline("2 * 3 * 4"), "2 * 3 * 4");
+        assert_eq!(inline("**unclosed"), "**unclosed");
+        assert_eq!(inline("`*not em*`"), "<code>*not em*</code>");
+        assert_eq!(inline("\\*literal\\*"), "*literal*");
+        assert_eq!(inline("``a ` b``"), "<code>a ` b</code>");
+    }
+
+    #[test]
+    fn test_links_are_sanitized() {
+        assert_eq!(
+            inline("[docs](https://example.com/a?b=1&c=\"2\")"),
+            "<a href=\"https://example.com/a?b=1&amp;c=&quot;2&quot;\">docs</a>"
+        );
+        assert_eq!(inline("[x](javascript:alert(1))"), "x");
+        assert_eq!(inline("[x](JavaScript:void)"), "x");
+        assert_eq!(
+            inline("[rel](src/lib.rs#L1)"),
+            "<a href=\"src/lib.rs#L1\">rel</a>"
+        );
+        assert_eq!(
+            inline("<https://example.com>"),
+            "<a href=\"https://example.com\">https://example.com</a>"
+        );
+        assert_eq!(
+            inline("<img src=x onerror=alert(1)>"),
+            "&lt;img src=x onerror=alert(1)&gt;"
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let markdown =
+            "# Title\n\nSome *text*\nwrapped.\n\n```rust\nfn main() {}\n```\n\n> quoted\n\n---";
+        assert_eq!(
+            markdown_to_html(markdown),
+            "<h1>Title</h1>\n\
+             <p>Some <em>text</em>\nwrapped.</p>\n\
+             <pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n\
+             <blockquote>\n<p>quoted</p>\n</blockquote>\n\
+             <hr>\n"
+        );
+        assert_eq!(
+            markdown_to_html("```\n<b>\n"),
+            "<pre><code>&lt;b&gt;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            markdown_to_html("- one\n- two\n  - nested\n\n3. three\n4. four"),
+            "<ul>\n<li>one</li>\n<li>two\n<ul>\n<li>nested</li>\n</ul></li>\n</ul>\n\
+             <ol start=\"3\">\n<li>three</li>\n<li>four</li>\n</ol>\n"
+        );
+        assert_eq!(
+            markdown_to_html("Steps:\n1. a\n2. b"),
+            "<p>Steps:</p>\n<ol>\n<li>a</li>\n<li>b</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(
+            markdown_to_html("| a | b |\n|---|--:|\n| `x` | 1 |"),
+            "<table>\n<thead>\n<tr><th>a</th><th style=\"text-align:right\">b</th></tr>\n</thead>\n\
+             <tbody>\n<tr><td><code>x</code></td><td style=\"text-align:right\">1</td></tr>\n</tbody>\n\
+             </table>\n"
+        );
+    }
+
+    #[test]
+    fn test_message_html() {
+        let message = Message::Assistant(AssistantMessage {
+            content: vec![
+                ContentBlock::Text(TextBlock {
+                    text: "Checking **now**.".into(),
+                }),
+                ContentBlock::ToolUse(ToolUseBlock {
+                    id: "toolu_1".into(),
+                    name: "Bash".into(),
+                    input: json!({"command": "ls <dir>"}),
+                }),
+            ],
+            model: "claude-sonnet-4-5".into(),
+            parent_tool_use_id: None,
+            error: None,
+            usage: None,
+            rate_limit: None,
+        });
+        assert_eq!(
+            html(&message),
+            "<p>Checking <strong>now</strong>.</p>\n\
+             <details class=\"tool-call\"><summary>Bash <code>ls &lt;dir&gt;</code></summary>\
+             <pre>{\n  &quot;command&quot;: &quot;ls &lt;dir&gt;&quot;\n}</pre></details>\n"
+        );
+
+        let result = Message::User(UserMessage {
+            content: UserMessageContent::Blocks(vec![ContentBlock::ToolResult(ToolResultBlock {
+                tool_use_id: "toolu_1".into(),
+                content: Some(json!("denied")),
+                is_error: Some(true),
+            })]),
+            uuid: None,
+            parent_tool_use_id: None,
+        });
+        assert_eq!(
+            html(&result),
+            "<details class=\"tool-result error\"><summary>Error</summary><pre>denied</pre></details>\n"
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Terminal rendering of messages.
+//!
+//! [`terminal`] turns a message into text for a terminal: prompts and results
+//! in color, tool calls and their results in boxes, and thinking folded to a
+//! one-line summary. Stream events render as their text deltas, so printing
+//! every rendered message of a query with partial messages enabled shows the
+//! answer as it arrives.
+//!
+//! For plain text without escape codes, use the `Display` impl of
+//! [`Message`] or set [`RenderOptions::with_colors`] to `false`. With the
+//! `render-html` feature, `html` and `markdown_to_html` render messages as
+//! sanitized HTML for web frontends.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::render::{self, RenderOptions};
+//! use claude_agents_sdk::query;
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let options = RenderOptions::default().with_width(100);
+//! let mut stream = query("List the files here", None).await?;
+//! while let Some(message) = stream.next().await {
+//!     print!("{}", render::terminal(&message?, options));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "render-html")]
+mod html;
+
+#[cfg(feature = "render-html")]
+#[cfg_attr(docsrs, doc(cfg(feature = "render-html")))]
+pub use html::{html, markdown_to_html};
+
+use serde_json::Value;
+
+use crate::types::{
+    ContentBlock, Message, ResultMessage, ThinkingBlock, ToolResultBlock, ToolUseBlock,
+    UserMessageContent,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+
+/// Options for [`terminal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Emit ANSI color codes. Default: `true`.
+    pub colors: bool,
+    /// Show thinking blocks as a one-line summary. Default: `true`.
+    pub fold_thinking: bool,
+    /// Width of tool boxes, in characters; longer lines are cut. Default: 80.
+    pub width: usize,
+    /// Lines of tool output to show before eliding the rest. Default: 10.
+    pub max_tool_output_lines: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            colors: true,
+            fold_thinking: true,
+            width: 80,
+            max_tool_output_lines: 10,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Plain text options: no colors, everything else default.
+    pub fn plain() -> Self {
+        Self::default().with_colors(false)
+    }
+
+    /// Set whether to emit ANSI color codes.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Set whether to fold thinking blocks.
+    pub fn with_fold_thinking(mut self, fold: bool) -> Self {
+        self.fold_thinking = fold;
+        self
+    }
+
+    /// Set the width of tool boxes.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Set how many lines of tool output to show.
+    pub fn with_max_tool_output_lines(mut self, lines: usize) -> Self {
+        self.max_tool_output_lines = lines;
+        self
+    }
+}
+
+/// Render `message` for a terminal.
+///
+/// Every message but a stream event ends with a newline. Stream events other
+/// than text deltas, and system messages other than `init`, render as
+/// nothing.
+pub fn terminal(message: &Message, options: RenderOptions) -> String {
+    let mut out = Renderer {
+        options,
+        out: String::new(),
+    };
+    match message {
+        Message::User(user) => match &user.content {
+            UserMessageContent::Text(text) => out.prompt(text),
+            UserMessageContent::Blocks(blocks) => blocks.iter().for_each(|b| out.block(b)),
+        },
+        Message::Assistant(assistant) => {
+            assistant.content.iter().for_each(|b| out.block(b));
+            if let Some(error) = &assistant.error {
+                out.line(RED, &format!("✗ {:?}", error));
+            }
+        }
+        Message::System(system) if system.subtype == "init" => {
+            let model = system.data["model"].as_str().unwrap_or("unknown model");
+            out.line(DIM, &format!("· session started ({})", model));
+        }
+        Message::System(_) => {}
+        Message::Result(result) => out.result(result),
+        Message::StreamEvent(event) => {
+            if event.event["delta"]["type"] == "text_delta" {
+                if let Some(text) = event.event["delta"]["text"].as_str() {
+                    out.out.push_str(text);
+                }
+            }
+        }
+        Message::ParseWarning(warning) => {
+            out.line(YELLOW, &format!("⚠ skipped output: {}", warning.message))
+        }
+        Message::Lazy(lazy) => match lazy.get() {
+            Ok(parsed) => return terminal(parsed, options),
+            Err(e) => out.line(YELLOW, &format!("⚠ unparsable output: {}", e)),
+        },
+    }
+    out.out
+}
+
+struct Renderer {
+    options: RenderOptions,
+    out: String,
+}
+
+impl Renderer {
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.options.colors {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn line(&mut self, color: &str, text: &str) {
+        let line = self.paint(color, text);
+        self.out.push_str(&line);
+        self.out.push('\n');
+    }
+
+    fn prompt(&mut self, text: &str) {
+        for (i, line) in text.lines().enumerate() {
+            let prefix = if i == 0 { "> " } else { "  " };
+            self.line(CYAN, &format!("{}{}", prefix, line));
+        }
+    }
+
+    fn block(&mut self, block: &ContentBlock) {
+        match block {
+            ContentBlock::Text(text) => {
+                self.out.push_str(&text.text);
+                if !text.text.ends_with('\n') {
+                    self.out.push('\n');
+                }
+            }
+            ContentBlock::Thinking(thinking) => self.thinking(thinking),
+            ContentBlock::ToolUse(tool_use) => self.tool_use(tool_use),
+            ContentBlock::ToolResult(result) => self.tool_result(result),
+            ContentBlock::LargePayload(_) => self.line(DIM, &block.to_string()),
+        }
+    }
+
+    fn thinking(&mut self, thinking: &ThinkingBlock) {
+        let lines = thinking.thinking.lines().count();
+        if self.options.fold_thinking {
+            let plural = if lines == 1 { "" } else { "s" };
+            self.line(DIM, &format!("▸ thinking ({} line{})", lines, plural));
+        } else {
+            self.line(DIM, "▾ thinking");
+            for line in thinking.thinking.lines() {
+                self.line(DIM, &format!("  {}", line));
+            }
+        }
+    }
+
+    fn tool_use(&mut self, tool_use: &ToolUseBlock) {
+        let title = self.paint(BOLD, &tool_use.name);
+        let lines = input_lines(&tool_use.input);
+        self.boxed(BLUE, &title, tool_use.name.chars().count(), &lines);
+    }
+
+    fn tool_result(&mut self, result: &ToolResultBlock) {
+        let (color, title) = if result.is_error == Some(true) {
+            (RED, "✗ error")
+        } else {
+            (GREEN, "✓ result")
+        };
+        let text = result.text();
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let max = self.options.max_tool_output_lines;
+        if lines.len() > max {
+            let hidden = lines.len() - max;
+            lines.truncate(max);
+            lines.push(format!("… {} more lines", hidden));
+        }
+        let painted = self.paint(color, title);
+        self.boxed(color, &painted, title.chars().count(), &lines);
+    }
+
+    /// Draw a box with `title` in its top border. `title_len` is the title's
+    /// visible length, without escape codes.
+    fn boxed(&mut self, color: &str, title: &str, title_len: usize, lines: &[String]) {
+        let width = self.options.width.max(8);
+        let rule = |len: usize| "─".repeat(width.saturating_sub(len));
+        let top = format!(
+            "{} {} {}\n",
+            self.paint(color, "╭─"),
+            title,
+            self.paint(color, &rule(title_len + 4))
+        );
+        self.out.push_str(&top);
+        let side = self.paint(color, "│");
+        for line in lines {
+            let line = truncate(line, width - 2);
+            self.out.push_str(&format!("{} {}\n", side, line));
+        }
+        let bottom = format!("╰{}", rule(1));
+        self.line(color, &bottom);
+    }
+
+    fn result(&mut self, result: &ResultMessage) {
+        if result.is_error {
+            self.line(RED, &format!("✗ {}", result_summary(result)));
+            if let Some(text) = &result.result {
+                self.line(RED, text);
+            }
+        } else {
+            self.line(GREEN, &format!("✓ {}", result_summary(result)));
+        }
+    }
+}
+
+/// `subtype · N turns · duration · cost` for a result.
+fn result_summary(result: &ResultMessage) -> String {
+    let mut summary = format!(
+        "{} · {} turn{} · {:.1}s",
+        result.subtype,
+        result.num_turns,
+        if result.num_turns == 1 { "" } else { "s" },
+        result.duration_ms as f64 / 1000.0
+    );
+    if let Some(cost) = result.total_cost_usd {
+        summary.push_str(&format!(" · ${:.4}", cost));
+    }
+    summary
+}
+
+/// Tool input as lines: `key: value` for each field of an object, with
+/// strings unquoted, or the JSON otherwise.
+fn input_lines(input: &Value) -> Vec<String> {
+    match input {
+        Value::Object(fields) => fields
+            .iter()
+            .flat_map(|(key, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                let mut lines = value.lines();
+                let first = format!("{}: {}", key, lines.next().unwrap_or(""));
+                std::iter::once(first)
+                    .chain(lines.map(|line| format!("  {}", line)))
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        other => vec![other.to_string()],
+    }
+}
+
+/// Cut `line` to `max` characters, ending with `…` if anything was cut.
+fn truncate(line: &str, max: usize) -> String {
+    if line.chars().count() <= max {
+        line.to_string()
+    } else {
+        let mut cut: String = line.chars().take(max.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AssistantMessage, StreamEvent, TextBlock, UserMessage};
+    use serde_json::json;
+
+    fn assistant(content: Vec<ContentBlock>) -> Message {
+        Message::Assistant(AssistantMessage {
+            content,
+            model: "claude-sonnet-4-5".into(),
+            parent_tool_use_id: None,
+            error: None,
+            usage: None,
+            rate_limit: None,
+        })
+    }
+
+    fn tool_result(content: Value, is_error: bool) -> Message {
+        Message::User(UserMessage {
+            content: UserMessageContent::Blocks(vec![ContentBlock::ToolResult(ToolResultBlock {
+                tool_use_id: "toolu_1".into(),
+                content: Some(content),
+                is_error: Some(is_error),
+            })]),
+            uuid: None,
+            parent_tool_use_id: None,
+        })
+    }
+
+    #[test]
+    fn test_tool_call_box() {
+        let message = assistant(vec![
+            ContentBlock::Text(TextBlock {
+                text: "Listing files.".into(),
+            }),
+            ContentBlock::ToolUse(ToolUseBlock {
+                id: "toolu_1".into(),
+                name: "Bash".into(),
+                input: json!({"command": "ls -la", "timeout": 5}),
+            }),
+        ]);
+        let rendered = terminal(&message, RenderOptions::plain().with_width(20));
+        assert_eq!(
+            rendered,
+            format!(
+                "Listing files.\n╭─ Bash {}\n│ command: ls -la\n│ timeout: 5\n╰{}\n",
+                "─".repeat(12),
+                "─".repeat(19)
+            )
+        );
+    }
+
+    #[test]
+    fn test_tool_result_truncation() {
+        let output: Vec<String> = (1..=5).map(|i| format!("line {}", i)).collect();
+        let message = tool_result(json!(output.join("\n")), false);
+        let options = RenderOptions::plain()
+            .with_width(24)
+            .with_max_tool_output_lines(2);
+        assert_eq!(
+            terminal(&message, options),
+            format!(
+                "╭─ ✓ result {}\n│ line 1\n│ line 2\n│ … 3 more lines\n╰{}\n",
+                "─".repeat(12),
+                "─".repeat(23)
+            )
+        );
+
+        let error = terminal(&tool_result(json!("denied"), true), RenderOptions::plain());
+        assert!(error.starts_with("╭─ ✗ error ─"));
+    }
+
+    #[test]
+    fn test_thinking_folding() {
+        let message = assistant(vec![ContentBlock::Thinking(ThinkingBlock {
+            thinking: "First.\nSecond.".into(),
+            signature: String::new(),
+        })]);
+        assert_eq!(
+            terminal(&message, RenderOptions::plain()),
+            "▸ thinking (2 lines)\n"
+        );
+        assert_eq!(
+            terminal(&message, RenderOptions::plain().with_fold_thinking(false)),
+            "▾ thinking\n  First.\n  Second.\n"
+        );
+    }
+
+    #[test]
+    fn test_colors() {
+        let prompt = Message::User(UserMessage {
+            content: UserMessageContent::Text("Hi".into()),
+            uuid: None,
+            parent_tool_use_id: None,
+        });
+        assert_eq!(
+            terminal(&prompt, RenderOptions::default()),
+            "\x1b[36m> Hi\x1b[0m\n"
+        );
+        assert_eq!(terminal(&prompt, RenderOptions::plain()), "> Hi\n");
+    }
+
+    #[test]
+    fn test_stream_event_renders_text_delta() {
+        let event = |event: Value| {
+            Message::StreamEvent(StreamEvent {
+                uuid: "u".into(),
+                session_id: "s".into(),
+                event,
+                parent_tool_use_id: None,
+            })
+        };
+        let delta = event(json!({
+            "type": "content_block_delta",
+            "delta": {"type": "text_delta", "text": "Hel"}
+        }));
+        assert_eq!(terminal(&delta, RenderOptions::default()), "Hel");
+        let stop = event(json!({"type": "message_stop"}));
+        assert_eq!(terminal(&stop, RenderOptions::default()), "");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("héllo", 5), "héllo");
+        assert_eq!(truncate("héllo wörld", 5), "héll…");
+    }
+}
@@ -0,0 +1,354 @@
+//! A ready-made interactive chat loop.
+//!
+//! [`run`] connects a [`ClaudeClient`] and reads prompts from stdin,
+//! streaming each response to stdout with [`render::terminal`]. Lines
+//! starting with `/` are commands, and work while a response is streaming;
+//! prompts entered meanwhile are sent once it finishes.
+//!
+//! | Command | Effect |
+//! |---------|--------|
+//! | `/interrupt` | Stop the current response |
+//! | `/model [name]` | Show or switch the model |
+//! | `/mode [mode]` | Show or switch the permission mode (`default`, `acceptEdits`, `plan`, `bypassPermissions`) |
+//! | `/help` | List the commands |
+//! | `/exit` | Leave the loop; also on end of input |
+//!
+//! [`run_with`] takes the input, output, and rendering options, for embedding
+//! the loop in a larger program or testing it.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::{repl, ClaudeAgentOptions};
+//!
+//! # #[cfg(feature = "subprocess")]
+//! #[tokio::main]
+//! async fn main() -> claude_agents_sdk::Result<()> {
+//!     repl::run(ClaudeAgentOptions::new().with_model("claude-sonnet-4-5")).await
+//! }
+//! # #[cfg(not(feature = "subprocess"))]
+//! # fn main() {}
+//! ```
+
+use std::collections::VecDeque;
+#[cfg(feature = "subprocess")]
+use std::io::IsTerminal;
+
+#[cfg(feature = "subprocess")]
+use tokio::io::BufReader;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_stream::StreamExt;
+
+use crate::client::ClaudeClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::render::{self, RenderOptions};
+use crate::types::{ClaudeAgentOptions, ContentBlock, Message, PermissionMode, UserMessageContent};
+
+const HELP: &str = "\
+/interrupt      stop the current response
+/model [name]   show or switch the model
+/mode [mode]    show or switch the permission mode
+                (default, acceptEdits, plan, bypassPermissions)
+/help           show this help
+/exit           quit
+";
+
+/// Run a chat loop on stdin and stdout.
+///
+/// Colors are used when stdout is a terminal. Returns when input ends or on
+/// `/exit`, after the current response finishes.
+///
+/// # Errors
+///
+/// Returns an error if the client fails to connect, or if the CLI exits
+/// while a response is streaming.
+#[cfg(feature = "subprocess")]
+pub async fn run(options: ClaudeAgentOptions) -> Result<()> {
+    let render = RenderOptions::default().with_colors(std::io::stdout().is_terminal());
+    let stdin = BufReader::new(tokio::io::stdin());
+    run_with(options, render, stdin, tokio::io::stdout()).await
+}
+
+/// Run a chat loop on `input` and `output`.
+///
+/// Partial messages are enabled so responses stream as they are generated.
+pub async fn run_with<R, W>(
+    options: ClaudeAgentOptions,
+    render: RenderOptions,
+    input: R,
+    mut output: W,
+) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut state = State {
+        model: options.model.clone(),
+        mode: options.permission_mode.unwrap_or_default(),
+        streamed: false,
+    };
+    let mut client = ClaudeClient::new(Some(options.with_partial_messages()));
+    client.connect().await?;
+
+    let mut lines = input.lines();
+    let mut busy = false;
+    let mut input_done = false;
+    // Prompts entered while Claude is responding.
+    let mut queued = VecDeque::new();
+    write(&mut output, "> ").await?;
+
+    let result = loop {
+        if input_done && !busy {
+            break Ok(());
+        }
+        let event = tokio::select! {
+            line = lines.next_line(), if !input_done => Event::Line(line),
+            message = async { client.receive_messages().next().await }, if busy => {
+                Event::Message(message)
+            }
+        };
+        match event {
+            Event::Line(Ok(Some(line))) => {
+                let line = line.trim();
+                let reply = match Command::parse(line) {
+                    Some(Command::Exit) => {
+                        input_done = true;
+                        continue;
+                    }
+                    Some(command) => command.run(&client, &mut state, busy).await,
+                    None if line.is_empty() => String::new(),
+                    None if busy => {
+                        queued.push_back(line.to_string());
+                        String::new()
+                    }
+                    None => send(&mut client, line, &mut busy).await,
+                };
+                write(&mut output, &reply).await?;
+                if !busy {
+                    write(&mut output, "> ").await?;
+                }
+            }
+            Event::Line(Ok(None)) => input_done = true,
+            Event::Line(Err(e)) => break Err(io_error(e)),
+            Event::Message(Some(Ok(message))) => {
+                write(&mut output, &state.render(&message, render)).await?;
+                if message.is_result() {
+                    busy = false;
+                    while !busy {
+                        let Some(prompt) = queued.pop_front() else {
+                            break;
+                        };
+                        write(&mut output, &format!("> {}\n", prompt)).await?;
+                        let reply = send(&mut client, &prompt, &mut busy).await;
+                        write(&mut output, &reply).await?;
+                    }
+                    if !busy && !input_done {
+                        write(&mut output, "> ").await?;
+                    }
+                }
+            }
+            Event::Message(Some(Err(e))) => write(&mut output, &format!("error: {}\n", e)).await?,
+            Event::Message(None) => {
+                break Err(ClaudeSDKError::internal("Connection closed without result"))
+            }
+        }
+    };
+
+    write(&mut output, "\n").await?;
+    client.disconnect().await?;
+    result
+}
+
+/// Send a prompt, setting `busy` if it was sent; returns what to print.
+async fn send(client: &mut ClaudeClient, prompt: &str, busy: &mut bool) -> String {
+    match client.query(prompt).await {
+        Ok(()) => {
+            *busy = true;
+            String::new()
+        }
+        Err(e) => format!("error: {}\n", e),
+    }
+}
+
+/// Whichever of the input and the client was ready first.
+enum Event {
+    Line(std::io::Result<Option<String>>),
+    Message(Option<Result<Message>>),
+}
+
+/// What the loop tracks between messages.
+struct State {
+    model: Option<String>,
+    mode: PermissionMode,
+    /// Whether text deltas were printed since the last assistant message.
+    streamed: bool,
+}
+
+impl State {
+    fn render(&mut self, message: &Message, options: RenderOptions) -> String {
+        let message = message.typed();
+        match message {
+            Message::StreamEvent(_) => {
+                let text = render::terminal(message, options);
+                self.streamed |= !text.is_empty();
+                text
+            }
+            Message::Assistant(assistant) => {
+                self.model = Some(assistant.model.clone());
+                let streamed = std::mem::take(&mut self.streamed);
+                let mut shown = assistant.clone();
+                if streamed {
+                    // The text was printed as it streamed; end its line.
+                    shown
+                        .content
+                        .retain(|block| !matches!(block, ContentBlock::Text(_)));
+                }
+                let text = render::terminal(&Message::Assistant(shown), options);
+                if streamed {
+                    format!("\n{}", text)
+                } else {
+                    text
+                }
+            }
+            // The CLI echoes prompts; only show tool results.
+            Message::User(user) if matches!(user.content, UserMessageContent::Text(_)) => {
+                String::new()
+            }
+            _ => render::terminal(message, options),
+        }
+    }
+}
+
+/// A line starting with `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Interrupt,
+    Model(Option<String>),
+    Mode(Option<String>),
+    Help,
+    Exit,
+    Unknown(String),
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.strip_prefix('/')?;
+        let (name, arg) = match line.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, Some(arg.trim().to_string())),
+            None => (line, None),
+        };
+        let arg = arg.filter(|arg| !arg.is_empty());
+        Some(match name {
+            "interrupt" => Self::Interrupt,
+            "model" => Self::Model(arg),
+            "mode" => Self::Mode(arg),
+            "help" => Self::Help,
+            "exit" | "quit" => Self::Exit,
+            other => Self::Unknown(other.to_string()),
+        })
+    }
+
+    /// Run the command; returns what to print.
+    async fn run(self, client: &ClaudeClient, state: &mut State, busy: bool) -> String {
+        let result = match self {
+            Self::Interrupt if !busy => return "Nothing to interrupt.\n".to_string(),
+            Self::Interrupt => client.interrupt().await.map(|()| "Interrupted.\n".into()),
+            Self::Model(None) => {
+                let model = state.model.as_deref().unwrap_or("default");
+                return format!("Model: {}\n", model);
+            }
+            Self::Model(Some(model)) => client.set_model(model.as_str()).await.map(|()| {
+                let reply = format!("Model set to {}.\n", model);
+                state.model = Some(model);
+                reply
+            }),
+            Self::Mode(None) => return format!("Permission mode: {}\n", mode_name(state.mode)),
+            Self::Mode(Some(name)) => match parse_mode(&name) {
+                Some(mode) => client.set_permission_mode(mode).await.map(|()| {
+                    state.mode = mode;
+                    format!("Permission mode set to {}.\n", mode_name(mode))
+                }),
+                None => {
+                    return format!(
+                        "Unknown permission mode '{}'; expected default, acceptEdits, plan, or bypassPermissions.\n",
+                        name
+                    )
+                }
+            },
+            Self::Help => return HELP.to_string(),
+            Self::Exit => return String::new(),
+            Self::Unknown(name) => return format!("Unknown command /{}; try /help.\n", name),
+        };
+        result.unwrap_or_else(|e| format!("error: {}\n", e))
+    }
+}
+
+/// Parse a permission mode, ignoring case, `-`, and `_`.
+fn parse_mode(name: &str) -> Option<PermissionMode> {
+    let normalized: String = name
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    [
+        PermissionMode::Default,
+        PermissionMode::AcceptEdits,
+        PermissionMode::Plan,
+        PermissionMode::BypassPermissions,
+    ]
+    .into_iter()
+    .find(|mode| mode_name(*mode).to_ascii_lowercase() == normalized)
+}
+
+fn mode_name(mode: PermissionMode) -> &'static str {
+    match mode {
+        PermissionMode::Default => "default",
+        PermissionMode::AcceptEdits => "acceptEdits",
+        PermissionMode::Plan => "plan",
+        PermissionMode::BypassPermissions => "bypassPermissions",
+    }
+}
+
+async fn write<W: AsyncWrite + Unpin>(output: &mut W, text: &str) -> Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    output.write_all(text.as_bytes()).await.map_err(io_error)?;
+    output.flush().await.map_err(io_error)
+}
+
+fn io_error(e: std::io::Error) -> ClaudeSDKError {
+    ClaudeSDKError::internal(format!("REPL I/O failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(Command::parse("hello"), None);
+        assert_eq!(Command::parse("/interrupt"), Some(Command::Interrupt));
+        assert_eq!(Command::parse("/model"), Some(Command::Model(None)));
+        assert_eq!(
+            Command::parse("/model  claude-opus-4-1 "),
+            Some(Command::Model(Some("claude-opus-4-1".into())))
+        );
+        assert_eq!(Command::parse("/quit"), Some(Command::Exit));
+        assert_eq!(
+            Command::parse("/nope x"),
+            Some(Command::Unknown("nope".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("plan"), Some(PermissionMode::Plan));
+        assert_eq!(parse_mode("acceptEdits"), Some(PermissionMode::AcceptEdits));
+        assert_eq!(
+            parse_mode("bypass-permissions"),
+            Some(PermissionMode::BypassPermissions)
+        );
+        assert_eq!(parse_mode("yolo"), None);
+    }
+}
@@ -0,0 +1,484 @@
+//! Cost reports.
+//!
+//! [`CostReport::from_messages`] attributes cost and tokens from a session's
+//! messages to sessions, turns, and tools, and renders the result as JSON,
+//! CSV, or Markdown.
+//!
+//! Turn costs come from result messages. The CLI reports `total_cost_usd`
+//! cumulatively per session, so each turn is charged the increase since the
+//! previous result of the same session.
+//!
+//! Tool attribution is an estimate built from usage deltas between API calls:
+//! a tool call is charged the output tokens of the call that requested it and
+//! the input tokens its result added to the next call, and subagent activity
+//! is charged to the tool that spawned it. A turn's cost is split by those
+//! token shares, so tools are comparable with each other but the shares ignore
+//! per-token price differences such as cache reads.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use claude_agents_sdk::reporting::CostReport;
+//! use claude_agents_sdk::query_all;
+//!
+//! # async fn example() -> claude_agents_sdk::Result<()> {
+//! let messages = query_all("Summarize this repo", None).await?;
+//! let report = CostReport::from_messages(&messages);
+//! println!("{}", report.to_markdown());
+//! std::fs::write("costs.csv", report.to_csv()).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ContentBlock, Message, ResultMessage};
+
+/// Cost of one session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionCost {
+    /// Session ID.
+    pub session_id: String,
+    /// Completed turns.
+    pub turns: u32,
+    /// Cost in USD.
+    pub cost_usd: f64,
+    /// Input tokens, including cache reads and writes.
+    pub input_tokens: u64,
+    /// Output tokens.
+    pub output_tokens: u64,
+}
+
+/// Cost of one turn, from a prompt to its result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TurnCost {
+    /// Position of the turn in the report, starting at 1.
+    pub turn: u32,
+    /// Session ID.
+    pub session_id: String,
+    /// Cost in USD.
+    pub cost_usd: f64,
+    /// Input tokens, including cache reads and writes.
+    pub input_tokens: u64,
+    /// Output tokens.
+    pub output_tokens: u64,
+    /// Tool calls made during the turn.
+    pub tool_calls: u32,
+    /// Duration reported by the CLI, in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// Estimated cost of a tool across all its calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolCost {
+    /// Tool name.
+    pub tool_name: String,
+    /// Number of calls.
+    pub calls: u32,
+    /// Input tokens added by the tool's results and subagents.
+    pub input_tokens: u64,
+    /// Output tokens spent requesting the tool and in its subagents.
+    pub output_tokens: u64,
+    /// Estimated cost in USD.
+    pub cost_usd: f64,
+}
+
+/// Cost and token usage attributed to sessions, turns, and tools.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Total cost in USD.
+    pub total_cost_usd: f64,
+    /// Total input tokens, including cache reads and writes.
+    pub input_tokens: u64,
+    /// Total output tokens.
+    pub output_tokens: u64,
+    /// Per-session costs, in order of first appearance.
+    pub sessions: Vec<SessionCost>,
+    /// Per-turn costs, in order.
+    pub turns: Vec<TurnCost>,
+    /// Per-tool costs, most expensive first.
+    pub tools: Vec<ToolCost>,
+    /// Tags attributing the costs, such as the session's
+    /// [`metadata`](crate::ClaudeAgentOptions::metadata).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// One API call within a turn.
+#[derive(Default)]
+struct ApiCall {
+    usage: Option<serde_json::Value>,
+    input: u64,
+    output: u64,
+    /// Tool use IDs requested by this call.
+    tools: Vec<String>,
+}
+
+/// Tokens attributed to one tool call.
+#[derive(Default, Clone, Copy)]
+struct Attributed {
+    input: u64,
+    output: u64,
+}
+
+/// Messages of the turn in progress.
+#[derive(Default)]
+struct TurnBuilder {
+    calls: Vec<ApiCall>,
+    /// Tokens of subagent calls, by the tool use that spawned them.
+    subagents: HashMap<String, Attributed>,
+}
+
+fn input_tokens(usage: &serde_json::Value) -> u64 {
+    [
+        "input_tokens",
+        "cache_read_input_tokens",
+        "cache_creation_input_tokens",
+    ]
+    .iter()
+    .filter_map(|key| usage.get(key).and_then(|v| v.as_u64()))
+    .sum()
+}
+
+fn output_tokens(usage: &serde_json::Value) -> u64 {
+    usage
+        .get("output_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+impl CostReport {
+    /// Build a report from messages, e.g. collected with
+    /// [`query_all`](crate::query_all) or from a client's stream.
+    ///
+    /// Messages after the last result belong to an unfinished turn and are
+    /// not counted.
+    pub fn from_messages(messages: &[Message]) -> Self {
+        let mut report = CostReport::default();
+        let mut turn = TurnBuilder::default();
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut tools: HashMap<String, ToolCost> = HashMap::new();
+        let mut last_total: HashMap<String, f64> = HashMap::new();
+
+        for message in messages.iter().map(Message::typed) {
+            match message {
+                Message::Assistant(assistant) => {
+                    let tool_uses = assistant.content.iter().filter_map(|block| match block {
+                        ContentBlock::ToolUse(tool_use) => Some(tool_use),
+                        _ => None,
+                    });
+                    for tool_use in tool_uses.clone() {
+                        tool_names.insert(tool_use.id.clone(), tool_use.name.clone());
+                    }
+
+                    if let Some(parent) = &assistant.parent_tool_use_id {
+                        let attributed = turn.subagents.entry(parent.clone()).or_default();
+                        if let Some(usage) = &assistant.usage {
+                            attributed.input += input_tokens(usage);
+                            attributed.output += output_tokens(usage);
+                        }
+                        continue;
+                    }
+
+                    let same_call = matches!(
+                        (turn.calls.last(), &assistant.usage),
+                        (Some(last), Some(usage)) if last.usage.as_ref() == Some(usage)
+                    ) || (assistant.usage.is_none() && !turn.calls.is_empty());
+                    if !same_call {
+                        let usage = assistant.usage.clone();
+                        turn.calls.push(ApiCall {
+                            input: usage.as_ref().map(input_tokens).unwrap_or(0),
+                            output: usage.as_ref().map(output_tokens).unwrap_or(0),
+                            usage,
+                            tools: Vec::new(),
+                        });
+                    }
+                    let call = turn.calls.last_mut().expect("call pushed above");
+                    call.tools.extend(tool_uses.map(|t| t.id.clone()));
+                }
+                Message::Result(result) => {
+                    let builder = std::mem::take(&mut turn);
+                    report.finish_turn(builder, result, &tool_names, &mut tools, &mut last_total);
+                }
+                _ => {}
+            }
+        }
+
+        let mut tools: Vec<ToolCost> = tools.into_values().collect();
+        tools.sort_by(|a, b| {
+            b.cost_usd
+                .total_cmp(&a.cost_usd)
+                .then_with(|| a.tool_name.cmp(&b.tool_name))
+        });
+        report.tools = tools;
+        report
+    }
+
+    fn finish_turn(
+        &mut self,
+        builder: TurnBuilder,
+        result: &ResultMessage,
+        tool_names: &HashMap<String, String>,
+        tools: &mut HashMap<String, ToolCost>,
+        last_total: &mut HashMap<String, f64>,
+    ) {
+        let total = result.total_cost_usd.unwrap_or(0.0);
+        let previous = last_total.insert(result.session_id.clone(), total);
+        let cost = match previous {
+            Some(previous) if total >= previous => total - previous,
+            _ => total,
+        };
+
+        // Attribute tokens to tool calls from usage deltas between calls.
+        let mut attributed: HashMap<String, Attributed> = builder.subagents;
+        let mut call_tokens: u64 = attributed.values().map(|a| a.input + a.output).sum();
+        for (i, call) in builder.calls.iter().enumerate() {
+            call_tokens += call.input + call.output;
+            let count = call.tools.len() as u64;
+            if count == 0 {
+                continue;
+            }
+            let added = builder
+                .calls
+                .get(i + 1)
+                .map(|next| next.input.saturating_sub(call.input + call.output))
+                .unwrap_or(0);
+            for id in &call.tools {
+                let entry = attributed.entry(id.clone()).or_default();
+                entry.output += call.output / count;
+                entry.input += added / count;
+            }
+        }
+
+        let mut tool_calls = 0;
+        for call in &builder.calls {
+            tool_calls += call.tools.len() as u32;
+            for id in &call.tools {
+                let name = tool_names.get(id).cloned().unwrap_or_default();
+                tools
+                    .entry(name.clone())
+                    .or_insert_with(|| ToolCost {
+                        tool_name: name,
+                        ..ToolCost::default()
+                    })
+                    .calls += 1;
+            }
+        }
+        for (id, tokens) in attributed {
+            let name = tool_names.get(&id).cloned().unwrap_or_default();
+            let tool = tools.entry(name.clone()).or_insert_with(|| ToolCost {
+                tool_name: name,
+                ..ToolCost::default()
+            });
+            tool.input_tokens += tokens.input;
+            tool.output_tokens += tokens.output;
+            if call_tokens > 0 {
+                tool.cost_usd += cost * (tokens.input + tokens.output) as f64 / call_tokens as f64;
+            }
+        }
+
+        let (input, output) = match &result.usage {
+            Some(usage) => (input_tokens(usage), output_tokens(usage)),
+            None => builder
+                .calls
+                .iter()
+                .fold((0, 0), |(i, o), call| (i + call.input, o + call.output)),
+        };
+
+        self.total_cost_usd += cost;
+        self.input_tokens += input;
+        self.output_tokens += output;
+        self.turns.push(TurnCost {
+            turn: self.turns.len() as u32 + 1,
+            session_id: result.session_id.clone(),
+            cost_usd: cost,
+            input_tokens: input,
+            output_tokens: output,
+            tool_calls,
+            duration_ms: result.duration_ms,
+        });
+
+        let session = match self
+            .sessions
+            .iter_mut()
+            .position(|s| s.session_id == result.session_id)
+        {
+            Some(index) => &mut self.sessions[index],
+            None => {
+                self.sessions.push(SessionCost {
+                    session_id: result.session_id.clone(),
+                    ..SessionCost::default()
+                });
+                self.sessions.last_mut().expect("session pushed above")
+            }
+        };
+        session.turns += 1;
+        session.cost_usd += cost;
+        session.input_tokens += input;
+        session.output_tokens += output;
+    }
+
+    /// Tag the report, e.g. with the session's
+    /// [`metadata`](crate::ClaudeAgentOptions::metadata). Tags appear in the
+    /// JSON and Markdown renderings.
+    pub fn with_metadata<K, V>(mut self, metadata: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.metadata
+            .extend(metadata.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Render as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Render as CSV with one row per session, turn, and tool.
+    ///
+    /// Columns: `scope,name,calls,input_tokens,output_tokens,cost_usd`, where
+    /// `calls` is the turn count for sessions and the tool call count otherwise.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("scope,name,calls,input_tokens,output_tokens,cost_usd\n");
+        let mut row = |scope: &str, name: &str, calls: u32, input: u64, output: u64, cost: f64| {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{:.6}",
+                scope,
+                csv_field(name),
+                calls,
+                input,
+                output,
+                cost
+            );
+        };
+        row(
+            "total",
+            "",
+            self.turns.len() as u32,
+            self.input_tokens,
+            self.output_tokens,
+            self.total_cost_usd,
+        );
+        for s in &self.sessions {
+            row(
+                "session",
+                &s.session_id,
+                s.turns,
+                s.input_tokens,
+                s.output_tokens,
+                s.cost_usd,
+            );
+        }
+        for t in &self.turns {
+            row(
+                "turn",
+                &t.turn.to_string(),
+                t.tool_calls,
+                t.input_tokens,
+                t.output_tokens,
+                t.cost_usd,
+            );
+        }
+        for t in &self.tools {
+            row(
+                "tool",
+                &t.tool_name,
+                t.calls,
+                t.input_tokens,
+                t.output_tokens,
+                t.cost_usd,
+            );
+        }
+        out
+    }
+
+    /// Render as Markdown tables.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Cost report\n");
+        let _ = writeln!(
+            out,
+            "**Total:** ${:.4} — {} input tokens, {} output tokens, {} turns\n",
+            self.total_cost_usd,
+            self.input_tokens,
+            self.output_tokens,
+            self.turns.len()
+        );
+        if !self.metadata.is_empty() {
+            let tags: Vec<String> = self
+                .metadata
+                .iter()
+                .map(|(k, v)| format!("`{}={}`", k, v))
+                .collect();
+            let _ = writeln!(out, "**Metadata:** {}\n", tags.join(", "));
+        }
+
+        let _ = writeln!(out, "## Sessions\n");
+        let _ = writeln!(
+            out,
+            "| Session | Turns | Input tokens | Output tokens | Cost (USD) |"
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+        for s in &self.sessions {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {:.4} |",
+                s.session_id, s.turns, s.input_tokens, s.output_tokens, s.cost_usd
+            );
+        }
+
+        let _ = writeln!(out, "\n## Turns\n");
+        let _ = writeln!(
+            out,
+            "| Turn | Session | Tool calls | Input tokens | Output tokens | Duration (ms) | Cost (USD) |"
+        );
+        let _ = writeln!(out, "|---:|---|---:|---:|---:|---:|---:|");
+        for t in &self.turns {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} | {} | {} | {} | {:.4} |",
+                t.turn,
+                t.session_id,
+                t.tool_calls,
+                t.input_tokens,
+                t.output_tokens,
+                t.duration_ms,
+                t.cost_usd
+            );
+        }
+
+        let _ = writeln!(out, "\n## Tools (estimated)\n");
+        let _ = writeln!(
+            out,
+            "| Tool | Calls | Input tokens | Output tokens | Cost (USD) |"
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+        for t in &self.tools {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {:.4} |",
+                t.tool_name.replace('|', "\\|"),
+                t.calls,
+                t.input_tokens,
+                t.output_tokens,
+                t.cost_usd
+            );
+        }
+        out
+    }
+}
+
+/// Quote a CSV field if needed.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
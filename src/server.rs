@@ -0,0 +1,445 @@
+//! Serving the agent over HTTP.
+//!
+//! [`AgentService`] is a tower [`Service`] answering HTTP requests with the
+//! agent's messages, streamed as server-sent events. Mount it in an axum
+//! router with `nest_service`, or serve it with hyper directly.
+//!
+//! # Routes
+//!
+//! - `POST /query` takes a JSON [`QueryRequest`] and streams each message
+//!   as an event named after its type (`assistant`, `result`, ...) with the
+//!   message's JSON as data. Errors while streaming are sent as an `error`
+//!   event holding the error's `kind` and `message`.
+//! - `DELETE /sessions/{id}` disconnects a session's CLI.
+//!
+//! A query without a `session_id` runs in a CLI of its own that exits after
+//! the result. Queries with one are routed to the same connected CLI, which
+//! keeps the conversation and answers one query at a time, until the session
+//! is deleted. Sessions are told apart by ID and by where their options run
+//! the CLI (its path, working directory, extra directories, environment,
+//! settings, and user), so with a per-tenant options mapper one tenant can't
+//! reach another's session by using its ID.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use claude_agents_sdk::server::AgentService;
+//! use claude_agents_sdk::ClaudeAgentOptions;
+//!
+//! let agent = AgentService::new(ClaudeAgentOptions::new().with_max_turns(4))
+//!     .with_options_mapper(|options, _request, parts| {
+//!         let team = parts.headers.get("x-team").and_then(|v| v.to_str().ok());
+//!         Ok(options.with_cwd(format!("/srv/workspaces/{}", team.unwrap_or("default"))))
+//!     });
+//! let app = axum::Router::new().nest_service("/agent", agent);
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+//! axum::serve(listener, app).await?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::{header, Method, Request, Response, StatusCode};
+use http_body::{Body, Frame};
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full, Limited, StreamBody};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tower_service::Service;
+
+use crate::client::ClaudeClient;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::{ClaudeAgentOptions, Message};
+
+/// Largest request body accepted, in bytes.
+pub const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Header naming the session a response belongs to.
+pub const SESSION_HEADER: &str = "x-session-id";
+
+/// The body of a `POST /query` request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryRequest {
+    /// The prompt to send.
+    pub prompt: String,
+    /// Session to run the query in, keeping the conversation across
+    /// requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model to use instead of the service's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Turn limit to use instead of the service's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+}
+
+/// Builds the options for a request from the service's options, with the
+/// request's model and turn limit applied.
+pub type OptionsMapper = Arc<
+    dyn Fn(ClaudeAgentOptions, &QueryRequest, &http::request::Parts) -> Result<ClaudeAgentOptions>
+        + Send
+        + Sync,
+>;
+
+/// The body of every response.
+pub type ResponseBody = UnsyncBoxBody<Bytes, Infallible>;
+
+type Session = Arc<Mutex<ClaudeClient>>;
+
+/// Where a session's CLI runs, from the options of the request that
+/// started it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SessionScope {
+    cli_path: Option<PathBuf>,
+    cwd: Option<PathBuf>,
+    add_dirs: Vec<PathBuf>,
+    env: BTreeMap<String, String>,
+    settings: Option<String>,
+    user: Option<String>,
+}
+
+impl SessionScope {
+    fn of(options: &ClaudeAgentOptions) -> Self {
+        Self {
+            cli_path: options.cli_path.clone(),
+            cwd: options.cwd.clone(),
+            add_dirs: options.add_dirs.clone(),
+            env: options.env.clone().into_iter().collect(),
+            settings: options.settings.clone(),
+            user: options.user.clone(),
+        }
+    }
+}
+
+/// A tower service running queries for HTTP requests.
+///
+/// Cloning is cheap, and clones share sessions.
+#[derive(Clone)]
+pub struct AgentService {
+    options: Arc<ClaudeAgentOptions>,
+    map_options: Option<OptionsMapper>,
+    sessions: Arc<std::sync::Mutex<HashMap<(SessionScope, String), Session>>>,
+}
+
+impl AgentService {
+    /// Serve queries with `options`.
+    pub fn new(options: ClaudeAgentOptions) -> Self {
+        Self {
+            options: Arc::new(options),
+            map_options: None,
+            sessions: Arc::default(),
+        }
+    }
+
+    /// Build each request's options with `mapper`, such as to pick a
+    /// working directory from an authenticated header.
+    ///
+    /// Errors are answered with `400 Bad Request`. A session keeps the
+    /// options of the request that started it, and only requests whose
+    /// options run the CLI in the same place reach it. `DELETE` requests are
+    /// mapped too, with an empty prompt and the session's ID.
+    pub fn with_options_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(
+                ClaudeAgentOptions,
+                &QueryRequest,
+                &http::request::Parts,
+            ) -> Result<ClaudeAgentOptions>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.map_options = Some(Arc::new(mapper));
+        self
+    }
+
+    /// IDs of the sessions with a CLI running. Sessions of different
+    /// tenants can share an ID.
+    pub fn sessions(&self) -> Vec<String> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+
+    /// Disconnect every session with the ID, returning whether one existed.
+    pub async fn close_session(&self, id: &str) -> Result<bool> {
+        self.close_sessions(|(_, session_id)| session_id == id)
+            .await
+    }
+
+    /// Disconnect the sessions whose keys match, returning whether one did.
+    async fn close_sessions(
+        &self,
+        matches: impl Fn(&(SessionScope, String)) -> bool,
+    ) -> Result<bool> {
+        let closed: Vec<Session> = {
+            let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+            let keys: Vec<_> = sessions
+                .keys()
+                .filter(|key| matches(key))
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| sessions.remove(key)).collect()
+        };
+        for session in &closed {
+            session.lock().await.disconnect().await?;
+        }
+        Ok(!closed.is_empty())
+    }
+
+    /// Answer `DELETE /sessions/{id}`, closing the session only if the
+    /// request maps to its scope.
+    async fn delete_session<B>(self, request: Request<B>, id: &str) -> Response<ResponseBody> {
+        let (parts, _) = request.into_parts();
+        let query = QueryRequest {
+            prompt: String::new(),
+            session_id: Some(id.to_string()),
+            model: None,
+            max_turns: None,
+        };
+        let scope = match self.options_for(&query, &parts) {
+            Ok(options) => SessionScope::of(&options),
+            Err(e) => return sdk_error_response(StatusCode::BAD_REQUEST, &e),
+        };
+        let key = (scope, id.to_string());
+        match self.close_sessions(|k| *k == key).await {
+            Ok(true) => empty_response(StatusCode::NO_CONTENT),
+            Ok(false) => error_response(StatusCode::NOT_FOUND, "No such session"),
+            Err(e) => sdk_error_response(StatusCode::BAD_GATEWAY, &e),
+        }
+    }
+
+    async fn handle<B>(self, request: Request<B>) -> Response<ResponseBody>
+    where
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let path = request.uri().path().to_string();
+        match (request.method(), path.as_str()) {
+            (&Method::POST, "/query") => self.query(request).await,
+            (_, "/query") => {
+                let mut response = error_response(StatusCode::METHOD_NOT_ALLOWED, "Use POST");
+                response
+                    .headers_mut()
+                    .insert(header::ALLOW, header::HeaderValue::from_static("POST"));
+                response
+            }
+            (&Method::DELETE, path) if path.starts_with("/sessions/") => {
+                let id = path["/sessions/".len()..].to_string();
+                self.delete_session(request, &id).await
+            }
+            _ => error_response(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    async fn query<B>(self, request: Request<B>) -> Response<ResponseBody>
+    where
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (parts, body) = request.into_parts();
+        let bytes = match Limited::new(body, MAX_REQUEST_BYTES).collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(e) if e.is::<http_body_util::LengthLimitError>() => {
+                return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large")
+            }
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+        };
+        let query: QueryRequest = match serde_json::from_slice(&bytes) {
+            Ok(query) => query,
+            Err(e) => {
+                return error_response(StatusCode::BAD_REQUEST, &format!("Invalid request: {}", e))
+            }
+        };
+        let options = match self.options_for(&query, &parts) {
+            Ok(options) => options,
+            Err(e) => return sdk_error_response(StatusCode::BAD_REQUEST, &e),
+        };
+
+        let (tx, rx) = mpsc::channel(32);
+        let session_id = query.session_id.clone();
+        match session_id.as_deref() {
+            None => {
+                let mut messages = match crate::query::query(&query.prompt, Some(options)).await {
+                    Ok(messages) => messages,
+                    Err(e) => return sdk_error_response(StatusCode::BAD_GATEWAY, &e),
+                };
+                tokio::spawn(async move {
+                    while let Some(message) = messages.next().await {
+                        if tx.send(event(message)).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            Some(id) => {
+                let mut client = match self.session(id, options).await {
+                    Ok(client) => client,
+                    Err(e) => return sdk_error_response(StatusCode::BAD_GATEWAY, &e),
+                };
+                if let Err(e) = client.query(&query.prompt).await {
+                    return sdk_error_response(StatusCode::BAD_GATEWAY, &e);
+                }
+                tokio::spawn(async move {
+                    // Read to the result even if the caller went away, so the
+                    // session's next query starts on a clean turn.
+                    let mut messages = client.receive_messages();
+                    while let Some(message) = messages.next().await {
+                        let done = matches!(&message, Ok(message) if message.is_result());
+                        let _ = tx.send(event(message)).await;
+                        if done {
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+
+        let body = StreamBody::new(ReceiverStream::new(rx).map(|bytes| Ok(Frame::data(bytes))));
+        let mut response = Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache");
+        if let Some(id) = session_id {
+            response = response.header(SESSION_HEADER, id);
+        }
+        response
+            .body(body.boxed_unsync())
+            .unwrap_or_else(|e| error_response(StatusCode::BAD_REQUEST, &e.to_string()))
+    }
+
+    fn options_for(
+        &self,
+        query: &QueryRequest,
+        parts: &http::request::Parts,
+    ) -> Result<ClaudeAgentOptions> {
+        let mut options = (*self.options).clone();
+        if let Some(model) = &query.model {
+            options = options.with_model(model.clone());
+        }
+        if let Some(turns) = query.max_turns {
+            options = options.with_max_turns(turns);
+        }
+        match &self.map_options {
+            Some(mapper) => mapper(options, query, parts),
+            None => Ok(options),
+        }
+    }
+
+    /// The session's client, locked for a query, connecting it first if new.
+    async fn session(
+        &self,
+        id: &str,
+        options: ClaudeAgentOptions,
+    ) -> Result<tokio::sync::OwnedMutexGuard<ClaudeClient>> {
+        let key = (SessionScope::of(&options), id.to_string());
+        let session = Arc::clone(
+            self.sessions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(key.clone())
+                .or_insert_with(|| Arc::new(Mutex::new(ClaudeClient::new(Some(options))))),
+        );
+        let mut client = Arc::clone(&session).lock_owned().await;
+        if !client.is_connected() {
+            if let Err(e) = client.connect().await {
+                let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+                if sessions.get(&key).is_some_and(|s| Arc::ptr_eq(s, &session)) {
+                    sessions.remove(&key);
+                }
+                return Err(e);
+            }
+        }
+        Ok(client)
+    }
+}
+
+impl std::fmt::Debug for AgentService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentService")
+            .field("options", &self.options)
+            .field("sessions", &self.sessions())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B> Service<Request<B>> for AgentService
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Response = Response<ResponseBody>;
+    type Error = Infallible;
+    type Future =
+        Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move { Ok(this.handle(request).await) })
+    }
+}
+
+/// A message or error as a server-sent event.
+fn event(message: Result<Message>) -> Bytes {
+    let (name, data) = match message.and_then(Message::into_parsed) {
+        Ok(message) => match serde_json::to_value(&message) {
+            Ok(value) => (
+                value["type"].as_str().unwrap_or("message").to_string(),
+                value.to_string(),
+            ),
+            Err(e) => (
+                "error".to_string(),
+                error_json(&ClaudeSDKError::internal(e.to_string())),
+            ),
+        },
+        Err(e) => ("error".to_string(), error_json(&e)),
+    };
+    Bytes::from(format!("event: {}\ndata: {}\n\n", name, data))
+}
+
+fn error_json(e: &ClaudeSDKError) -> String {
+    json!({"kind": e.kind(), "message": e.to_string()}).to_string()
+}
+
+fn json_response(status: StatusCode, body: String) -> Response<ResponseBody> {
+    let mut response = Response::new(Full::new(Bytes::from(body)).boxed_unsync());
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<ResponseBody> {
+    json_response(status, json!({"error": {"message": message}}).to_string())
+}
+
+fn sdk_error_response(status: StatusCode, e: &ClaudeSDKError) -> Response<ResponseBody> {
+    json_response(status, format!("{{\"error\":{}}}", error_json(e)))
+}
+
+fn empty_response(status: StatusCode) -> Response<ResponseBody> {
+    let mut response = Response::new(Full::new(Bytes::new()).boxed_unsync());
+    *response.status_mut() = status;
+    response
+}
@@ -0,0 +1,167 @@
+//! Saved sessions.
+//!
+//! The CLI saves each session's transcript as
+//! `<config dir>/projects/<project>/<session ID>.jsonl`, where the config
+//! dir is `$CLAUDE_CONFIG_DIR` or `~/.claude` and the project is the working
+//! directory with every character other than an ASCII letter or digit
+//! replaced by `-`. [`Session`] finds them, so an application can pick the
+//! session to resume instead of relying on
+//! [`continue_conversation`](crate::ClaudeAgentOptions::continue_conversation).
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::errors::{ClaudeSDKError, Result};
+
+/// Environment variable overriding the CLI's config dir.
+pub const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// A session saved by the CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Session ID, to pass to
+    /// [`resume`](crate::ClaudeAgentOptions::resume).
+    pub session_id: String,
+    /// The transcript file.
+    pub path: PathBuf,
+    /// When the transcript was last written.
+    pub modified: SystemTime,
+}
+
+/// Finds the sessions the CLI saved for a working directory.
+pub struct Session;
+
+impl Session {
+    /// The CLI's config dir: `$CLAUDE_CONFIG_DIR`, or `~/.claude`.
+    pub fn config_dir() -> Option<PathBuf> {
+        std::env::var_os(CLAUDE_CONFIG_DIR_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| crate::mcp_config::home_dir().map(|home| home.join(".claude")))
+    }
+
+    /// The directory holding the transcripts of sessions run in `cwd`.
+    pub fn project_dir(config_dir: impl AsRef<Path>, cwd: impl AsRef<Path>) -> PathBuf {
+        let cwd = cwd.as_ref();
+        let cwd = std::fs::canonicalize(cwd).unwrap_or_else(|_| cwd.to_path_buf());
+        let project: String = cwd
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        config_dir.as_ref().join("projects").join(project)
+    }
+
+    /// Sessions run in `cwd`, most recent first.
+    pub fn list(cwd: impl AsRef<Path>) -> Result<Vec<SessionInfo>> {
+        match Self::config_dir() {
+            Some(config_dir) => Self::list_in(config_dir, cwd),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Sessions run in `cwd` under an explicit config dir, most recent
+    /// first.
+    pub fn list_in(
+        config_dir: impl AsRef<Path>,
+        cwd: impl AsRef<Path>,
+    ) -> Result<Vec<SessionInfo>> {
+        let dir = Self::project_dir(config_dir, cwd);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(ClaudeSDKError::configuration(format!(
+                    "Failed to read sessions in {}: {}",
+                    dir.display(),
+                    e
+                )))
+            }
+        };
+
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+            // Subagent transcripts sit alongside sessions but can't be resumed.
+            let Some(session_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if session_id.starts_with("agent-") {
+                continue;
+            }
+            let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+                continue;
+            };
+            sessions.push(SessionInfo {
+                session_id: session_id.to_string(),
+                path,
+                modified,
+            });
+        }
+        sessions.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+        Ok(sessions)
+    }
+
+    /// The session last written in `cwd`: the one
+    /// [`continue_conversation`](crate::ClaudeAgentOptions::continue_conversation)
+    /// would continue.
+    pub fn most_recent(cwd: impl AsRef<Path>) -> Option<SessionInfo> {
+        Self::list(cwd).ok()?.into_iter().next()
+    }
+
+    /// The session last written in `cwd` under an explicit config dir.
+    pub fn most_recent_in(
+        config_dir: impl AsRef<Path>,
+        cwd: impl AsRef<Path>,
+    ) -> Option<SessionInfo> {
+        Self::list_in(config_dir, cwd).ok()?.into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn touch(path: &Path, age_secs: u64) {
+        let file = std::fs::File::create(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_project_dir_replaces_punctuation() {
+        let dir = Session::project_dir("/home/u/.claude", "/nonexistent/my_app.v2");
+        assert_eq!(
+            dir,
+            PathBuf::from("/home/u/.claude/projects/-nonexistent-my-app-v2")
+        );
+    }
+
+    #[test]
+    fn test_most_recent_skips_subagents_and_other_files() {
+        let config = tempfile::tempdir().unwrap();
+        let cwd = tempfile::tempdir().unwrap();
+        assert!(Session::most_recent_in(config.path(), cwd.path()).is_none());
+
+        let project = Session::project_dir(config.path(), cwd.path());
+        std::fs::create_dir_all(&project).unwrap();
+        touch(&project.join("old.jsonl"), 60);
+        touch(&project.join("new.jsonl"), 10);
+        touch(&project.join("agent-1234.jsonl"), 0);
+        touch(&project.join("notes.txt"), 0);
+
+        let sessions = Session::list_in(config.path(), cwd.path()).unwrap();
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["new", "old"]);
+        let recent = Session::most_recent_in(config.path(), cwd.path()).unwrap();
+        assert_eq!(recent.path, project.join("new.jsonl"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Typed Claude Code settings.
+//!
+//! [`Settings`] builds the JSON passed to the CLI's `--settings` flag, as set
+//! by [`ClaudeAgentOptions::with_settings`](crate::ClaudeAgentOptions::with_settings).
+//! Keys without a typed field go through [`Settings::with_raw`], or set
+//! [`ClaudeAgentOptions::settings`](crate::ClaudeAgentOptions::settings) to a
+//! JSON string or file path directly.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::{ClaudeAgentOptions, HookEvent, PermissionMode, Settings};
+//!
+//! let settings = Settings::new()
+//!     .with_allow("Bash(git diff:*)")
+//!     .with_deny("WebFetch")
+//!     .with_default_mode(PermissionMode::AcceptEdits)
+//!     .with_env("RUST_LOG", "debug")
+//!     .with_command_hook(HookEvent::PostToolUse, Some("Edit|Write"), "cargo fmt")
+//!     .with_status_line("~/.claude/statusline.sh");
+//!
+//! let options = ClaudeAgentOptions::new().with_settings(settings);
+//! assert!(options.settings.unwrap().contains("\"deny\":[\"WebFetch\"]"));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{HookEvent, PermissionMode};
+
+/// Permission rules.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionSettings {
+    /// Rules for tool uses allowed without prompting, e.g. `Bash(npm test:*)`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Rules for tool uses that are refused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Rules for tool uses that always prompt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ask: Vec<String>,
+    /// Directories outside the working directory that tools may access.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_directories: Vec<PathBuf>,
+    /// Permission mode to start in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<PermissionMode>,
+}
+
+impl PermissionSettings {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// A shell command run by a settings hook.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandHook {
+    /// Hook type (always `"command"`).
+    #[serde(rename = "type")]
+    pub hook_type: String,
+    /// Shell command to run.
+    pub command: String,
+    /// Timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl CommandHook {
+    /// A hook running `command`.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            hook_type: "command".to_string(),
+            command: command.into(),
+            timeout: None,
+        }
+    }
+}
+
+/// Command hooks for tools matching a pattern.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsHookMatcher {
+    /// Tool name pattern; matches every tool when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    /// Commands to run.
+    pub hooks: Vec<CommandHook>,
+}
+
+/// Status line shown by the CLI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusLine {
+    /// Status line type (always `"command"`).
+    #[serde(rename = "type")]
+    pub status_type: String,
+    /// Command whose output is shown.
+    pub command: String,
+    /// Padding in columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub padding: Option<u32>,
+}
+
+/// Where a plugin marketplace is fetched from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum MarketplaceSource {
+    /// A GitHub repository, as `owner/repo`.
+    GitHub {
+        /// Repository in `owner/repo` form.
+        repo: String,
+    },
+    /// Any git repository.
+    Git {
+        /// Clone URL.
+        url: String,
+    },
+    /// A local directory.
+    Directory {
+        /// Directory path.
+        path: PathBuf,
+    },
+}
+
+/// A plugin marketplace known to the CLI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marketplace {
+    /// Where the marketplace is fetched from.
+    pub source: MarketplaceSource,
+}
+
+/// Claude Code settings, serialized for the `--settings` flag.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// Permission rules.
+    #[serde(default, skip_serializing_if = "PermissionSettings::is_empty")]
+    pub permissions: PermissionSettings,
+    /// Environment variables for every session.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Shell command hooks by event.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hooks: HashMap<HookEvent, Vec<SettingsHookMatcher>>,
+    /// Status line configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_line: Option<StatusLine>,
+    /// Default model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Output style name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_style: Option<String>,
+    /// Script that prints an API key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_helper: Option<String>,
+    /// Days to keep local chat transcripts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleanup_period_days: Option<u32>,
+    /// Whether to add a co-author trailer to git commits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_co_authored_by: Option<bool>,
+    /// Plugins by `name@marketplace`, and whether each is enabled.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub enabled_plugins: BTreeMap<String, bool>,
+    /// Marketplaces to make available, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_known_marketplaces: BTreeMap<String, Marketplace>,
+    /// Keys without a typed field.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Settings {
+    /// Empty settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow tool uses matching `rule` without prompting.
+    pub fn with_allow(mut self, rule: impl Into<String>) -> Self {
+        self.permissions.allow.push(rule.into());
+        self
+    }
+
+    /// Refuse tool uses matching `rule`.
+    pub fn with_deny(mut self, rule: impl Into<String>) -> Self {
+        self.permissions.deny.push(rule.into());
+        self
+    }
+
+    /// Always prompt for tool uses matching `rule`.
+    pub fn with_ask(mut self, rule: impl Into<String>) -> Self {
+        self.permissions.ask.push(rule.into());
+        self
+    }
+
+    /// Let tools access a directory outside the working directory.
+    pub fn with_additional_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.permissions.additional_directories.push(dir.into());
+        self
+    }
+
+    /// Set the permission mode to start in.
+    pub fn with_default_mode(mut self, mode: PermissionMode) -> Self {
+        self.permissions.default_mode = Some(mode);
+        self
+    }
+
+    /// Set an environment variable.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Run a shell command on `event` for tools matching `matcher`.
+    pub fn with_command_hook(
+        mut self,
+        event: HookEvent,
+        matcher: Option<&str>,
+        command: impl Into<String>,
+    ) -> Self {
+        self.hooks
+            .entry(event)
+            .or_default()
+            .push(SettingsHookMatcher {
+                matcher: matcher.map(str::to_string),
+                hooks: vec![CommandHook::new(command)],
+            });
+        self
+    }
+
+    /// Show the output of `command` as the status line.
+    pub fn with_status_line(mut self, command: impl Into<String>) -> Self {
+        self.status_line = Some(StatusLine {
+            status_type: "command".to_string(),
+            command: command.into(),
+            padding: None,
+        });
+        self
+    }
+
+    /// Set the default model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Enable or disable a plugin, named `plugin@marketplace`.
+    pub fn with_plugin(mut self, plugin: impl Into<String>, enabled: bool) -> Self {
+        self.enabled_plugins.insert(plugin.into(), enabled);
+        self
+    }
+
+    /// Make a plugin marketplace available under `name`.
+    pub fn with_marketplace(mut self, name: impl Into<String>, source: MarketplaceSource) -> Self {
+        self.extra_known_marketplaces
+            .insert(name.into(), Marketplace { source });
+        self
+    }
+
+    /// Set a key without a typed field.
+    ///
+    /// A raw key replaces the typed field of the same name.
+    pub fn with_raw(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    /// Serialize for the `--settings` flag.
+    pub fn to_json_string(&self) -> String {
+        // Through `Value` so a raw key replaces, rather than repeats, a typed one.
+        serde_json::to_value(self)
+            .expect("settings serialize to JSON")
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty_settings() {
+        assert_eq!(Settings::new().to_json_string(), "{}");
+    }
+
+    #[test]
+    fn test_settings_json_shape() {
+        let settings = Settings::new()
+            .with_allow("Read")
+            .with_default_mode(PermissionMode::Plan)
+            .with_command_hook(HookEvent::PreToolUse, Some("Bash"), "audit.sh")
+            .with_status_line("status.sh")
+            .with_raw("spinnerTipsEnabled", json!(false));
+
+        let value: Value = serde_json::from_str(&settings.to_json_string()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "permissions": {"allow": ["Read"], "defaultMode": "plan"},
+                "hooks": {"PreToolUse": [{"matcher": "Bash", "hooks": [{"type": "command", "command": "audit.sh"}]}]},
+                "statusLine": {"type": "command", "command": "status.sh"},
+                "spinnerTipsEnabled": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_plugin_settings() {
+        let settings = Settings::new()
+            .with_marketplace(
+                "team-tools",
+                MarketplaceSource::GitHub {
+                    repo: "acme/claude-plugins".to_string(),
+                },
+            )
+            .with_plugin("formatter@team-tools", true)
+            .with_plugin("telemetry@team-tools", false);
+
+        let value: Value = serde_json::from_str(&settings.to_json_string()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "enabledPlugins": {"formatter@team-tools": true, "telemetry@team-tools": false},
+                "extraKnownMarketplaces": {
+                    "team-tools": {"source": {"source": "github", "repo": "acme/claude-plugins"}}
+                }
+            })
+        );
+        let parsed: Settings = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn test_raw_key_replaces_typed_field() {
+        let settings = Settings::new()
+            .with_model("sonnet")
+            .with_raw("model", json!("opus"));
+        assert_eq!(settings.to_json_string(), r#"{"model":"opus"}"#);
+    }
+
+    #[test]
+    fn test_settings_round_trip_keeps_unknown_keys() {
+        let raw = r#"{"model":"opus","cleanupPeriodDays":7,"newSetting":{"a":1}}"#;
+        let settings: Settings = serde_json::from_str(raw).unwrap();
+        assert_eq!(settings.model.as_deref(), Some("opus"));
+        assert_eq!(settings.cleanup_period_days, Some(7));
+        assert_eq!(settings.extra["newSetting"], json!({"a": 1}));
+        assert_eq!(
+            serde_json::from_str::<Value>(&settings.to_json_string()).unwrap(),
+            serde_json::from_str::<Value>(raw).unwrap()
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Per-turn latency and throughput statistics.
+//!
+//! The CLI's result message only reports `duration_ms` and `duration_api_ms`.
+//! The SDK also timestamps the messages it receives, so each turn gets a
+//! [`TurnStats`] with time to first token, throughput, and tool wall time. It
+//! is attached to the turn's [`ResultMessage::stats`](crate::ResultMessage::stats)
+//! and accumulated into [`SessionStats`], available from
+//! [`ClaudeClient::stats`](crate::ClaudeClient::stats).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::clock::Clock;
+use crate::types::{ContentBlock, Message, ResultMessage, UserMessageContent};
+
+/// Timing and throughput for one turn.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TurnStats {
+    /// Time from sending the prompt to the first streamed token, or to the
+    /// first assistant message when partial messages are off.
+    pub time_to_first_token: Option<Duration>,
+    /// Time from sending the prompt to receiving the result, measured by the SDK.
+    pub wall_time: Duration,
+    /// Total duration reported by the CLI.
+    pub total_duration: Duration,
+    /// Time the CLI spent in API calls.
+    pub api_duration: Duration,
+    /// Summed wall time of tool calls, from `tool_use` to `tool_result`.
+    pub tool_time: Duration,
+    /// Number of tool calls.
+    pub tool_calls: u32,
+    /// Input tokens, if reported.
+    pub input_tokens: Option<u64>,
+    /// Output tokens, if reported.
+    pub output_tokens: Option<u64>,
+    /// Output tokens per second of generation.
+    ///
+    /// Measured from the first streamed token to the result when partial
+    /// messages are on, and over [`api_duration`](Self::api_duration) otherwise.
+    pub tokens_per_second: Option<f64>,
+}
+
+impl TurnStats {
+    /// Time the CLI spent outside API calls (tools, hooks, startup).
+    pub fn non_api_duration(&self) -> Duration {
+        self.total_duration.saturating_sub(self.api_duration)
+    }
+}
+
+/// Statistics accumulated over a session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    /// Completed turns.
+    pub turns: u64,
+    /// Statistics for the most recent turn.
+    pub last_turn: Option<TurnStats>,
+    /// Summed wall time of all turns.
+    pub total_wall_time: Duration,
+    /// Summed API time of all turns.
+    pub total_api_duration: Duration,
+    /// Summed tool wall time of all turns.
+    pub total_tool_time: Duration,
+    /// Summed tool calls of all turns.
+    pub total_tool_calls: u64,
+    /// Summed input tokens of all turns.
+    pub total_input_tokens: u64,
+    /// Summed output tokens of all turns.
+    pub total_output_tokens: u64,
+}
+
+/// In-progress turn.
+#[derive(Default)]
+struct TurnState {
+    started: Option<Instant>,
+    first_token: Option<Instant>,
+    streamed: bool,
+    tools: HashMap<String, Instant>,
+    tool_time: Duration,
+    tool_calls: u32,
+}
+
+#[derive(Default)]
+struct StatsState {
+    turn: TurnState,
+    session: SessionStats,
+}
+
+/// Collects [`TurnStats`] from a session's messages.
+#[derive(Clone)]
+pub(crate) struct StatsTracker {
+    state: Arc<Mutex<StatsState>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for StatsTracker {
+    fn default() -> Self {
+        Self::new(crate::clock::default_clock())
+    }
+}
+
+impl StatsTracker {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            state: Arc::default(),
+            clock,
+        }
+    }
+
+    /// Start timing a turn.
+    pub(crate) fn begin_turn(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).turn = TurnState {
+            started: Some(self.clock.now()),
+            ..TurnState::default()
+        };
+    }
+
+    /// Session statistics so far.
+    pub(crate) fn snapshot(&self) -> SessionStats {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .session
+            .clone()
+    }
+
+    /// Update from a message, attaching [`TurnStats`] to result messages.
+    pub(crate) fn observe(&self, message: &mut Message) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let turn = &mut state.turn;
+        turn.started.get_or_insert(now);
+
+        match message {
+            Message::StreamEvent(event) => {
+                let is_delta =
+                    event.event.get("type").and_then(|t| t.as_str()) == Some("content_block_delta");
+                if is_delta {
+                    turn.streamed = true;
+                    turn.first_token.get_or_insert(now);
+                }
+            }
+            Message::Assistant(assistant) => {
+                turn.first_token.get_or_insert(now);
+                for block in &assistant.content {
+                    if let ContentBlock::ToolUse(tool_use) = block {
+                        turn.tools.insert(tool_use.id.clone(), now);
+                        turn.tool_calls += 1;
+                    }
+                }
+            }
+            Message::User(user) => {
+                if let UserMessageContent::Blocks(blocks) = &user.content {
+                    for block in blocks {
+                        if let ContentBlock::ToolResult(result) = block {
+                            if let Some(started) = turn.tools.remove(&result.tool_use_id) {
+                                turn.tool_time += now.saturating_duration_since(started);
+                            }
+                        }
+                    }
+                }
+            }
+            Message::Result(result) => {
+                let stats = Self::finish_turn(std::mem::take(turn), result, now);
+                let session = &mut state.session;
+                session.turns += 1;
+                session.total_wall_time += stats.wall_time;
+                session.total_api_duration += stats.api_duration;
+                session.total_tool_time += stats.tool_time;
+                session.total_tool_calls += u64::from(stats.tool_calls);
+                session.total_input_tokens += stats.input_tokens.unwrap_or(0);
+                session.total_output_tokens += stats.output_tokens.unwrap_or(0);
+                session.last_turn = Some(stats.clone());
+                result.stats = Some(Box::new(stats));
+            }
+            // Read lazy messages' tool calls and results from the raw JSON,
+            // leaving them unparsed.
+            Message::Lazy(lazy) => {
+                let blocks = lazy.raw()["message"]["content"].as_array();
+                let blocks = blocks.into_iter().flatten();
+                match lazy.msg_type() {
+                    "assistant" => {
+                        turn.first_token.get_or_insert(now);
+                        for block in blocks.filter(|block| block["type"] == "tool_use") {
+                            if let Some(id) = block["id"].as_str() {
+                                turn.tools.insert(id.to_string(), now);
+                                turn.tool_calls += 1;
+                            }
+                        }
+                    }
+                    "user" => {
+                        for id in blocks.filter_map(|block| block["tool_use_id"].as_str()) {
+                            if let Some(started) = turn.tools.remove(id) {
+                                turn.tool_time += now.saturating_duration_since(started);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Message::System(_) | Message::ParseWarning(_) => {}
+        }
+    }
+
+    fn finish_turn(turn: TurnState, result: &ResultMessage, now: Instant) -> TurnStats {
+        let started = turn.started.unwrap_or(now);
+        let usage = |key: &str| {
+            result
+                .usage
+                .as_ref()
+                .and_then(|u| u.get(key))
+                .and_then(|v| v.as_u64())
+        };
+        let output_tokens = usage("output_tokens");
+        let api_duration = Duration::from_millis(result.duration_api_ms);
+
+        let generation = match turn.first_token {
+            Some(first) if turn.streamed => now.saturating_duration_since(first),
+            _ => api_duration,
+        };
+        let tokens_per_second = output_tokens
+            .filter(|_| !generation.is_zero())
+            .map(|tokens| tokens as f64 / generation.as_secs_f64());
+
+        TurnStats {
+            time_to_first_token: turn
+                .first_token
+                .map(|first| first.saturating_duration_since(started)),
+            wall_time: now.saturating_duration_since(started),
+            total_duration: Duration::from_millis(result.duration_ms),
+            api_duration,
+            tool_time: turn.tool_time,
+            tool_calls: turn.tool_calls,
+            input_tokens: usage("input_tokens"),
+            output_tokens,
+            tokens_per_second,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use crate::types::LazyMessage;
+    use serde_json::json;
+
+    fn message(raw: serde_json::Value) -> Message {
+        parse_message(raw).unwrap().unwrap()
+    }
+
+    fn result(output_tokens: u64) -> Message {
+        message(json!({
+            "type": "result", "subtype": "success", "duration_ms": 3000, "duration_api_ms": 2000,
+            "is_error": false, "num_turns": 1, "session_id": "s1",
+            "usage": {"input_tokens": 50, "output_tokens": output_tokens}
+        }))
+    }
+
+    #[test]
+    fn test_turn_stats_attached_to_result() {
+        let tracker = StatsTracker::default();
+        tracker.begin_turn();
+        tracker.observe(&mut message(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}
+            ]}
+        })));
+        std::thread::sleep(Duration::from_millis(5));
+        tracker.observe(&mut message(json!({
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "tu_1"}]}
+        })));
+        let mut msg = result(100);
+        tracker.observe(&mut msg);
+
+        let Message::Result(result) = msg else {
+            unreachable!()
+        };
+        let stats = result.stats.unwrap();
+        assert!(stats.time_to_first_token.is_some());
+        assert!(stats.tool_time >= Duration::from_millis(5));
+        assert_eq!(stats.tool_calls, 1);
+        assert_eq!(stats.api_duration, Duration::from_secs(2));
+        assert_eq!(stats.non_api_duration(), Duration::from_secs(1));
+        assert_eq!(stats.input_tokens, Some(50));
+        // Without streaming, throughput is measured over API time.
+        assert_eq!(stats.tokens_per_second, Some(50.0));
+        assert!(stats.wall_time >= stats.tool_time);
+    }
+
+    #[test]
+    fn test_streamed_throughput_uses_generation_window() {
+        let tracker = StatsTracker::default();
+        tracker.begin_turn();
+        tracker.observe(&mut message(json!({
+            "type": "stream_event", "uuid": "u", "session_id": "s1",
+            "event": {"type": "content_block_delta"}
+        })));
+        std::thread::sleep(Duration::from_millis(10));
+        let mut msg = result(10);
+        tracker.observe(&mut msg);
+
+        let stats = tracker.snapshot().last_turn.unwrap();
+        let rate = stats.tokens_per_second.unwrap();
+        // 10 tokens over at least 10ms, far faster than the 2s API window.
+        assert!(rate > 5.0 && rate <= 1000.0, "rate {}", rate);
+    }
+
+    #[test]
+    fn test_turn_timing_uses_clock() {
+        let clock = crate::clock::MockClock::new();
+        let tracker = StatsTracker::new(Arc::new(clock.clone()));
+        tracker.begin_turn();
+        clock.advance(Duration::from_millis(400));
+        tracker.observe(&mut message(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}
+            ]}
+        })));
+        clock.advance(Duration::from_millis(250));
+        tracker.observe(&mut message(json!({
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "tu_1"}]}
+        })));
+        clock.advance(Duration::from_millis(100));
+        tracker.observe(&mut result(10));
+
+        let stats = tracker.snapshot().last_turn.unwrap();
+        assert_eq!(stats.time_to_first_token, Some(Duration::from_millis(400)));
+        assert_eq!(stats.tool_time, Duration::from_millis(250));
+        assert_eq!(stats.wall_time, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn test_lazy_messages_counted_unparsed() {
+        let clock = crate::clock::MockClock::new();
+        let tracker = StatsTracker::new(Arc::new(clock.clone()));
+        tracker.begin_turn();
+        let mut assistant = Message::Lazy(LazyMessage::new(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}
+            ]}
+        })));
+        tracker.observe(&mut assistant);
+        clock.advance(Duration::from_millis(250));
+        tracker.observe(&mut Message::Lazy(LazyMessage::new(json!({
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "tu_1"}]}
+        }))));
+        tracker.observe(&mut result(10));
+
+        assert!(matches!(&assistant, Message::Lazy(lazy) if !lazy.is_parsed()));
+        let stats = tracker.snapshot().last_turn.unwrap();
+        assert_eq!(stats.tool_calls, 1);
+        assert_eq!(stats.tool_time, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_session_totals() {
+        let tracker = StatsTracker::default();
+        for _ in 0..2 {
+            tracker.begin_turn();
+            tracker.observe(&mut result(10));
+        }
+        let session = tracker.snapshot();
+        assert_eq!(session.turns, 2);
+        assert_eq!(session.total_output_tokens, 20);
+        assert_eq!(session.total_input_tokens, 100);
+        assert_eq!(session.total_api_duration, Duration::from_secs(4));
+    }
+}
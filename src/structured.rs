@@ -0,0 +1,118 @@
+//! Typed structured output.
+//!
+//! [`query_structured`](crate::query_structured) asks for output matching a
+//! JSON schema and deserializes it. Output that doesn't fit is either an
+//! error or, under a [`StructuredOutputPolicy`] allowing repairs, sent back
+//! to the model with the reason it was rejected.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::ResultMessage;
+
+/// What to do when structured output can't be used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructuredOutputPolicy {
+    /// Times to re-prompt with the reason the output was rejected before
+    /// failing. Zero fails on the first invalid output.
+    pub max_repairs: u32,
+}
+
+impl StructuredOutputPolicy {
+    /// Re-prompt up to `max_repairs` times.
+    pub fn repair(max_repairs: u32) -> Self {
+        Self { max_repairs }
+    }
+
+    /// The follow-up prompt asking the model to fix its output.
+    pub(crate) fn repair_prompt(&self, reason: &str) -> String {
+        format!(
+            "The JSON was invalid because {}. Reply again with only the corrected JSON, \
+             matching the schema.",
+            reason
+        )
+    }
+}
+
+/// Read a turn's structured output as `T`.
+///
+/// Prefers the output the CLI validated against the schema, falling back to
+/// JSON in the result or response text, optionally in a code fence. On
+/// failure, returns why along with the rejected value, if any.
+pub(crate) fn extract<T: DeserializeOwned>(
+    text: &str,
+    result: &ResultMessage,
+) -> Result<T, (String, Option<Value>)> {
+    let value = match &result.structured_output {
+        Some(value) => value.clone(),
+        None => {
+            let text = result.result.as_deref().unwrap_or(text);
+            parse_json_text(text).map_err(|e| (format!("it could not be parsed: {}", e), None))?
+        }
+    };
+    serde_json::from_value(value.clone()).map_err(|e| (e.to_string(), Some(value)))
+}
+
+/// Parse JSON from model text, unwrapping a Markdown code fence.
+fn parse_json_text(text: &str) -> serde_json::Result<Value> {
+    let text = text.trim();
+    let unfenced = text
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|body| body.strip_prefix("json").unwrap_or(body))
+        .unwrap_or(text);
+    serde_json::from_str(unfenced.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use crate::types::Message;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    fn result(extra: Value) -> ResultMessage {
+        let mut raw = json!({
+            "type": "result", "subtype": "success", "duration_ms": 1, "duration_api_ms": 1,
+            "is_error": false, "num_turns": 1, "session_id": "s"
+        });
+        raw.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        match parse_message(raw).unwrap().unwrap() {
+            Message::Result(result) => result,
+            other => panic!("Expected Result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_extract_prefers_structured_output() {
+        let structured = result(json!({"structured_output": {"name": "Ada", "age": 36}}));
+        let person: Person = extract("ignored", &structured).unwrap();
+        assert_eq!(person.name, "Ada");
+
+        let fenced = "```json\n{\"name\": \"Alan\", \"age\": 41}\n```";
+        let person: Person = extract(fenced, &result(json!({}))).unwrap();
+        assert_eq!(person.age, 41);
+    }
+
+    #[test]
+    fn test_extract_reports_reason() {
+        let (reason, value) =
+            extract::<Person>(r#"{"name": "Ada"}"#, &result(json!({}))).unwrap_err();
+        assert!(reason.contains("missing field `age`"), "{}", reason);
+        assert_eq!(value, Some(json!({"name": "Ada"})));
+
+        let (reason, value) =
+            extract::<Person>("Sure! Here it is.", &result(json!({}))).unwrap_err();
+        assert!(reason.starts_with("it could not be parsed"), "{}", reason);
+        assert_eq!(value, None);
+    }
+}
@@ -0,0 +1,402 @@
+//! Subagent activity.
+//!
+//! When the model delegates work with the `Task` tool, the CLI runs a
+//! subagent whose messages are interleaved with the main conversation and
+//! tagged with the `parent_tool_use_id` of the `Task` call. [`Subagent`]
+//! groups them per call, and
+//! [`ClaudeClient::subagents`](crate::ClaudeClient::subagents) keeps the
+//! groups for a session. [`SdkEvent::SubagentStarted`] and
+//! [`SdkEvent::SubagentFinished`] on the event bus report each one as it
+//! starts and finishes.
+//!
+//! The CLI reports cost per turn, not per subagent. Once a turn's result
+//! arrives, its cost is shared among the subagents that ran in it by their
+//! share of the turn's tokens.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::events::{EventTracker, SdkEvent};
+use crate::types::{ContentBlock, Message, UserMessageContent};
+
+/// Names of the tool that starts a subagent.
+const TASK_TOOLS: [&str; 2] = ["Task", "Agent"];
+
+/// Where a [`Subagent`] stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubagentStatus {
+    /// Still working.
+    Running,
+    /// Returned its result.
+    Completed,
+    /// Returned an error.
+    Failed,
+}
+
+/// One subagent run, started by a `Task` tool call.
+#[derive(Debug, Clone)]
+pub struct Subagent {
+    /// ID of the `Task` tool use that started the subagent.
+    pub tool_use_id: String,
+    /// Subagent type, e.g. `general-purpose`, if the call was seen.
+    pub subagent_type: Option<String>,
+    /// Short description of the task, if the call was seen.
+    pub description: Option<String>,
+    /// Where the subagent stands.
+    pub status: SubagentStatus,
+    /// The subagent's own messages, in order.
+    pub messages: Vec<Message>,
+    /// Input tokens of the subagent's API calls, including cache reads and
+    /// writes.
+    pub input_tokens: u64,
+    /// Output tokens of the subagent's API calls.
+    pub output_tokens: u64,
+    /// Estimated cost in USD, once the turn's result has reported its cost.
+    pub cost_usd: Option<f64>,
+    /// How long the subagent ran, once finished and if its start was seen.
+    pub duration: Option<Duration>,
+}
+
+impl Subagent {
+    fn new(tool_use_id: &str) -> Self {
+        Self {
+            tool_use_id: tool_use_id.to_string(),
+            subagent_type: None,
+            description: None,
+            status: SubagentStatus::Running,
+            messages: Vec::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: None,
+            duration: None,
+        }
+    }
+
+    /// Group the subagent runs in a list of messages, e.g. collected with
+    /// [`query_all`](crate::query_all), in the order they started.
+    pub fn from_messages(messages: &[Message]) -> Vec<Subagent> {
+        let tracker = SubagentTracker::default();
+        for message in messages {
+            tracker.observe(message);
+        }
+        tracker.snapshot()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    subagents: Vec<Subagent>,
+    /// Start times of running subagents, by tool use ID.
+    started: HashMap<String, Instant>,
+    /// Latest usage per caller (a subagent's tool use ID, or `None` for the
+    /// main conversation), as the CLI repeats it for each content block.
+    last_usage: HashMap<Option<String>, serde_json::Value>,
+    /// Tokens of the turn in progress.
+    turn_tokens: u64,
+    /// Tokens of each subagent in the turn in progress.
+    turn_subagents: HashMap<String, u64>,
+    /// Latest total cost per session.
+    last_total: HashMap<String, f64>,
+}
+
+impl State {
+    fn entry(&mut self, tool_use_id: &str) -> &mut Subagent {
+        let index = match self
+            .subagents
+            .iter()
+            .position(|s| s.tool_use_id == tool_use_id)
+        {
+            Some(index) => index,
+            None => {
+                self.subagents.push(Subagent::new(tool_use_id));
+                self.subagents.len() - 1
+            }
+        };
+        &mut self.subagents[index]
+    }
+
+    fn is_running(&self, tool_use_id: &str) -> bool {
+        self.subagents
+            .iter()
+            .any(|s| s.tool_use_id == tool_use_id && s.status == SubagentStatus::Running)
+    }
+
+    /// Count an API call's tokens, once per call.
+    fn count_usage(&mut self, caller: Option<&String>, usage: &serde_json::Value) {
+        if self.last_usage.get(&caller.cloned()) == Some(usage) {
+            return;
+        }
+        self.last_usage.insert(caller.cloned(), usage.clone());
+        let input: u64 = [
+            "input_tokens",
+            "cache_read_input_tokens",
+            "cache_creation_input_tokens",
+        ]
+        .iter()
+        .filter_map(|key| usage.get(key).and_then(|v| v.as_u64()))
+        .sum();
+        let output = usage
+            .get("output_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        self.turn_tokens += input + output;
+        if let Some(id) = caller {
+            *self.turn_subagents.entry(id.clone()).or_default() += input + output;
+            let subagent = self.entry(id);
+            subagent.input_tokens += input;
+            subagent.output_tokens += output;
+        }
+    }
+}
+
+/// Groups a session's messages per subagent.
+#[derive(Clone, Default)]
+pub(crate) struct SubagentTracker {
+    events: EventTracker,
+    state: Arc<Mutex<State>>,
+}
+
+impl SubagentTracker {
+    pub(crate) fn new(events: EventTracker) -> Self {
+        Self {
+            events,
+            state: Arc::default(),
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<Subagent> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .subagents
+            .clone()
+    }
+
+    /// Whether a lazy message may concern a subagent, judged without
+    /// parsing it.
+    fn concerns_subagents(&self, raw: &serde_json::Value) -> bool {
+        if raw["parent_tool_use_id"].is_string() {
+            return true;
+        }
+        let Some(blocks) = raw["message"]["content"].as_array() else {
+            return false;
+        };
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        blocks.iter().any(|block| {
+            block["name"]
+                .as_str()
+                .is_some_and(|name| TASK_TOOLS.contains(&name))
+                || block["tool_use_id"]
+                    .as_str()
+                    .is_some_and(|id| state.is_running(id))
+        })
+    }
+
+    pub(crate) fn observe(&self, message: &Message) {
+        let message = match message {
+            Message::Lazy(lazy) => {
+                // Leave lazy messages unparsed unless they concern a subagent,
+                // counting their tokens from the raw JSON.
+                if !self.concerns_subagents(lazy.raw()) {
+                    if let Some(usage) = lazy.raw()["message"].get("usage") {
+                        self.state
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .count_usage(None, usage);
+                    }
+                    return;
+                }
+                match lazy.get() {
+                    Ok(message) => message,
+                    Err(_) => return,
+                }
+            }
+            message => message,
+        };
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match message {
+            Message::Assistant(assistant) => {
+                if let Some(usage) = &assistant.usage {
+                    state.count_usage(assistant.parent_tool_use_id.as_ref(), usage);
+                }
+                if let Some(parent) = &assistant.parent_tool_use_id {
+                    state.entry(parent).messages.push(message.clone());
+                }
+                for block in &assistant.content {
+                    let ContentBlock::ToolUse(tool_use) = block else {
+                        continue;
+                    };
+                    if !TASK_TOOLS.contains(&tool_use.name.as_str()) {
+                        continue;
+                    }
+                    let field = |key: &str| {
+                        tool_use
+                            .input
+                            .get(key)
+                            .and_then(|v| v.as_str())
+                            .map(str::to_string)
+                    };
+                    let (subagent_type, description) =
+                        (field("subagent_type"), field("description"));
+                    let subagent = state.entry(&tool_use.id);
+                    subagent.subagent_type = subagent_type.clone();
+                    subagent.description = description.clone();
+                    state.started.insert(tool_use.id.clone(), Instant::now());
+                    self.events.emit(SdkEvent::SubagentStarted {
+                        tool_use_id: tool_use.id.clone(),
+                        subagent_type,
+                        description,
+                    });
+                }
+            }
+            Message::User(user) => {
+                if let Some(parent) = &user.parent_tool_use_id {
+                    state.entry(parent).messages.push(message.clone());
+                }
+                let UserMessageContent::Blocks(blocks) = &user.content else {
+                    return;
+                };
+                for block in blocks {
+                    let ContentBlock::ToolResult(result) = block else {
+                        continue;
+                    };
+                    if !state.is_running(&result.tool_use_id) {
+                        continue;
+                    }
+                    let is_error = result.is_error.unwrap_or(false);
+                    let duration = state
+                        .started
+                        .remove(&result.tool_use_id)
+                        .map(|at| at.elapsed());
+                    let subagent = state.entry(&result.tool_use_id);
+                    subagent.status = if is_error {
+                        SubagentStatus::Failed
+                    } else {
+                        SubagentStatus::Completed
+                    };
+                    subagent.duration = duration;
+                    self.events.emit(SdkEvent::SubagentFinished {
+                        tool_use_id: result.tool_use_id.clone(),
+                        is_error,
+                        input_tokens: subagent.input_tokens,
+                        output_tokens: subagent.output_tokens,
+                        duration,
+                    });
+                }
+            }
+            Message::Result(result) => {
+                let total = result.total_cost_usd.unwrap_or(0.0);
+                let previous = state.last_total.insert(result.session_id.clone(), total);
+                let cost = match previous {
+                    Some(previous) if total >= previous => total - previous,
+                    _ => total,
+                };
+                let turn_tokens = std::mem::take(&mut state.turn_tokens);
+                let turn_subagents = std::mem::take(&mut state.turn_subagents);
+                state.last_usage.clear();
+                if turn_tokens == 0 || result.total_cost_usd.is_none() {
+                    return;
+                }
+                for (id, tokens) in turn_subagents {
+                    let share = cost * tokens as f64 / turn_tokens as f64;
+                    let subagent = state.entry(&id);
+                    subagent.cost_usd = Some(subagent.cost_usd.unwrap_or(0.0) + share);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use crate::events::EventBus;
+    use serde_json::json;
+
+    fn message(raw: serde_json::Value) -> Message {
+        parse_message(raw).unwrap().unwrap()
+    }
+
+    fn session() -> Vec<Message> {
+        [
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "task_1", "name": "Task", "input": {
+                    "description": "Find tests", "prompt": "...", "subagent_type": "Explore"
+                }}
+            ], "usage": {"input_tokens": 100, "output_tokens": 50}}}),
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "text", "text": "Looking."}
+            ], "usage": {"input_tokens": 300, "output_tokens": 100}}, "parent_tool_use_id": "task_1"}),
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "g", "name": "Glob", "input": {"pattern": "tests/*"}}
+            ], "usage": {"input_tokens": 300, "output_tokens": 100}}, "parent_tool_use_id": "task_1"}),
+            json!({"type": "user", "message": {"content": [
+                {"type": "tool_result", "tool_use_id": "g", "content": "tests/a.rs"}
+            ]}, "parent_tool_use_id": "task_1"}),
+            json!({"type": "user", "message": {"content": [
+                {"type": "tool_result", "tool_use_id": "task_1", "content": "One test file."}
+            ]}}),
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "text", "text": "Found one."}
+            ], "usage": {"input_tokens": 400, "output_tokens": 50}}}),
+            json!({"type": "result", "subtype": "success", "duration_ms": 1, "duration_api_ms": 1,
+                "is_error": false, "num_turns": 1, "session_id": "s", "total_cost_usd": 1.0}),
+        ]
+        .into_iter()
+        .map(message)
+        .collect()
+    }
+
+    #[test]
+    fn test_groups_messages_and_shares_cost() {
+        let subagents = Subagent::from_messages(&session());
+        assert_eq!(subagents.len(), 1);
+        let subagent = &subagents[0];
+        assert_eq!(subagent.tool_use_id, "task_1");
+        assert_eq!(subagent.subagent_type.as_deref(), Some("Explore"));
+        assert_eq!(subagent.description.as_deref(), Some("Find tests"));
+        assert_eq!(subagent.status, SubagentStatus::Completed);
+        assert_eq!(subagent.messages.len(), 3);
+        // The repeated usage of one API call is counted once.
+        assert_eq!((subagent.input_tokens, subagent.output_tokens), (300, 100));
+        assert!((subagent.cost_usd.unwrap() - 0.4).abs() < 1e-9);
+        assert!(subagent.duration.is_some());
+    }
+
+    #[test]
+    fn test_emits_start_and_finish_events() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+        let tracker = SubagentTracker::new(EventTracker::new(Some(bus)));
+        for message in session() {
+            tracker.observe(&message);
+        }
+
+        match rx.try_recv().unwrap() {
+            SdkEvent::SubagentStarted {
+                tool_use_id,
+                subagent_type,
+                ..
+            } => {
+                assert_eq!(tool_use_id, "task_1");
+                assert_eq!(subagent_type.as_deref(), Some("Explore"));
+            }
+            other => panic!("Expected SubagentStarted, got {:?}", other),
+        }
+        match rx.try_recv().unwrap() {
+            SdkEvent::SubagentFinished {
+                is_error,
+                output_tokens,
+                ..
+            } => {
+                assert!(!is_error);
+                assert_eq!(output_tokens, 100);
+            }
+            other => panic!("Expected SubagentFinished, got {:?}", other),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+}
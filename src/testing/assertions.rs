@@ -0,0 +1,198 @@
+//! Assertions over collected messages.
+//!
+//! Integration tests usually collect a query's messages into a `Vec` and
+//! then dig through them for tool calls, denials, and the final answer.
+//! These functions do the digging and panic with what was actually there.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::testing::assertions::*;
+//! use claude_agents_sdk::testing::fixtures;
+//! use serde_json::json;
+//!
+//! let fixture = fixtures::builtin()
+//!     .into_iter()
+//!     .find(|f| f.name == "tool_use_turn")
+//!     .unwrap();
+//! let messages = fixture.parse().unwrap();
+//!
+//! assert_tool_called(&messages, "Bash", json!({"command": "ls"}));
+//! assert_no_denials(&messages);
+//! assert_final_text_contains(&messages, "Cargo.toml");
+//! ```
+
+use serde_json::Value;
+
+use crate::types::{ContentBlock, Message, ToolUseBlock};
+
+/// Panic unless the assistant called the tool `name` with input matching
+/// `input`.
+///
+/// `input` matches if every field it has is present in the call's input
+/// with a matching value, recursively; arrays and scalars must be equal.
+/// Pass `json!({})` to match any input.
+#[track_caller]
+pub fn assert_tool_called(messages: &[Message], name: &str, input: Value) {
+    let calls = tool_calls(messages);
+    assert!(
+        calls
+            .iter()
+            .any(|call| call.name == name && json_matches(&input, &call.input)),
+        "tool {:?} was not called with input matching {}; tool calls: {:?}",
+        name,
+        input,
+        calls
+            .iter()
+            .map(|call| format!("{}({})", call.name, call.input))
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Panic if the turn's result reports any permission denials.
+#[track_caller]
+pub fn assert_no_denials(messages: &[Message]) {
+    let denials: Vec<_> = messages
+        .iter()
+        .filter_map(|m| match m.typed() {
+            Message::Result(result) => Some(&result.permission_denials),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert!(
+        denials.is_empty(),
+        "expected no permission denials; denied: {:?}",
+        denials
+            .iter()
+            .map(|d| format!("{}({})", d.tool_name, d.tool_input))
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Panic unless the final assistant text contains `text`.
+///
+/// The final text is that of the last assistant message with any text, or
+/// the result's `result` if no assistant message has text.
+#[track_caller]
+pub fn assert_final_text_contains(messages: &[Message], text: &str) {
+    let final_text = final_text(messages);
+    assert!(
+        final_text.as_deref().is_some_and(|t| t.contains(text)),
+        "final text does not contain {:?}; final text: {:?}",
+        text,
+        final_text
+    );
+}
+
+/// Tool calls in assistant messages, in order.
+fn tool_calls(messages: &[Message]) -> Vec<&ToolUseBlock> {
+    messages
+        .iter()
+        .filter_map(|m| match m.typed() {
+            Message::Assistant(assistant) => Some(&assistant.content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse(tool_use) => Some(tool_use),
+            _ => None,
+        })
+        .collect()
+}
+
+fn final_text(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find_map(|m| match m.typed() {
+            Message::Assistant(assistant) => Some(assistant.text()).filter(|text| !text.is_empty()),
+            _ => None,
+        })
+        .or_else(|| {
+            messages.iter().rev().find_map(|m| match m.typed() {
+                Message::Result(result) => result.result.clone(),
+                _ => None,
+            })
+        })
+}
+
+/// Whether `actual` has every field of `expected`, recursively.
+fn json_matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|actual| json_matches(value, actual))
+        }),
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::builtin;
+    use crate::types::PermissionDenial;
+    use serde_json::json;
+
+    fn fixture(name: &str) -> Vec<Message> {
+        builtin()
+            .into_iter()
+            .find(|f| f.name == name)
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_json_matches() {
+        let input = json!({"command": "ls", "opts": {"all": true, "long": false}});
+        assert!(json_matches(&json!({}), &input));
+        assert!(json_matches(&json!({"opts": {"all": true}}), &input));
+        assert!(!json_matches(&json!({"command": "pwd"}), &input));
+        assert!(!json_matches(&json!({"missing": null}), &input));
+        assert!(!json_matches(&json!([1]), &json!([1, 2])));
+    }
+
+    #[test]
+    fn test_assertions_pass_on_fixture() {
+        let messages = fixture("tool_use_turn");
+        assert_tool_called(&messages, "Bash", json!({}));
+        assert_tool_called(&messages, "Bash", json!({"command": "ls"}));
+        assert_no_denials(&messages);
+        assert_final_text_contains(&messages, "`src`");
+    }
+
+    #[test]
+    #[should_panic(expected = "tool calls: [\"Bash(")]
+    fn test_assert_tool_called_lists_calls() {
+        assert_tool_called(&fixture("tool_use_turn"), "Bash", json!({"command": "rm"}));
+    }
+
+    #[test]
+    #[should_panic(expected = "denied: [\"Write(")]
+    fn test_assert_no_denials_lists_denials() {
+        let mut messages = fixture("text_turn");
+        if let Some(Message::Result(result)) = messages.last_mut() {
+            result.permission_denials.push(PermissionDenial {
+                tool_name: "Write".into(),
+                tool_use_id: "toolu_1".into(),
+                tool_input: json!({"file_path": "/etc/passwd"}),
+            });
+        }
+        assert_no_denials(&messages);
+    }
+
+    #[test]
+    fn test_final_text_falls_back_to_result() {
+        let messages = fixture("text_turn");
+        assert_final_text_contains(&messages, "4");
+        let result_only: Vec<_> = messages
+            .into_iter()
+            .filter(|m| matches!(m, Message::Result(_)))
+            .collect();
+        assert_eq!(final_text(&result_only).as_deref(), Some("2 + 2 = 4"));
+        assert_eq!(final_text(&[]), None);
+    }
+}
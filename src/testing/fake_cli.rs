@@ -0,0 +1,346 @@
+//! A fake `claude` executable for end-to-end tests.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::text_response;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::types::ClaudeAgentOptions;
+
+/// The fake CLI. `{dir}`, `{version}`, `{control}`, and `{exit}` are
+/// filled in by [`FakeCliBuilder::build()`].
+///
+/// It logs the arguments of each run and every line it reads, prints
+/// `start.jsonl`, answers control requests as configured, and prints
+/// `turn_<n>.jsonl` after the n-th user message. It exits when stdin closes.
+const SCRIPT: &str = r#"#!/bin/sh
+dir='{dir}'
+case " $* " in
+    *" -v "*|*" --version "*) echo "{version} (Claude Code)"; exit 0 ;;
+esac
+run=1
+while [ -e "$dir/args_$run" ]; do run=$((run + 1)); done
+printf '%s\n' "$@" > "$dir/args_$run"
+success() {
+    printf '{"type":"control_response","response":{"subtype":"success","request_id":"%s","response":%s}}\n' "$id" "$1"
+}
+error() {
+    printf '{"type":"control_response","response":{"subtype":"error","request_id":"%s","error":%s}}\n' "$id" "$1"
+}
+if [ -f "$dir/start.jsonl" ]; then cat "$dir/start.jsonl"; fi
+turn=0
+while IFS= read -r line; do
+    printf '%s\n' "$line" >> "$dir/stdin.jsonl"
+    case "$line" in
+        *'"type":"control_request"'*)
+            id=$(printf '%s\n' "$line" | sed 's/.*"request_id":"\([^"]*\)".*/\1/')
+            case "$line" in
+{control}                *) success '{}' ;;
+            esac
+            ;;
+        *'"type":"user"'*)
+            turn=$((turn + 1))
+            if [ -f "$dir/turn_$turn.jsonl" ]; then cat "$dir/turn_$turn.jsonl"; fi
+{exit}            ;;
+    esac
+done
+"#;
+
+/// Version the fake reports unless [`FakeCliBuilder::version()`] says
+/// otherwise.
+const DEFAULT_VERSION: &str = "2.0.14";
+
+/// A stand-in for the Claude CLI that prints canned stream-json.
+///
+/// Unlike [`MockTransport`](super::MockTransport), this is a real
+/// executable: point [`ClaudeAgentOptions::cli_path`] at it and the SDK
+/// spawns it, writes to its stdin, and reads its stdout exactly as it would
+/// the real CLI, without an installation or API key.
+///
+/// Each turn is the list of messages printed after one user message.
+/// Control requests get an empty success response unless
+/// [`FakeCli::builder()`] configures otherwise. The files backing the fake
+/// are removed when it is dropped.
+///
+/// Requires `/bin/sh`, so it is only available on Unix.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::testing::FakeCli;
+/// use claude_agents_sdk::query_all;
+///
+/// # #[tokio::main]
+/// # async fn main() -> claude_agents_sdk::Result<()> {
+/// let cli = FakeCli::with_text_response("Hello!")?;
+/// let messages = query_all("Hi", Some(cli.options())).await?;
+///
+/// assert_eq!(messages.len(), 3);
+/// assert_eq!(cli.prompts(), vec!["Hi"]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FakeCli {
+    dir: tempfile::TempDir,
+    path: PathBuf,
+}
+
+impl FakeCli {
+    /// Create a fake CLI that prints each of `turns` in response to one
+    /// user message.
+    pub fn new(turns: Vec<Vec<Value>>) -> Result<Self> {
+        turns
+            .into_iter()
+            .fold(Self::builder(), FakeCliBuilder::turn)
+            .build()
+    }
+
+    /// Create a fake CLI that answers one prompt with `text`.
+    pub fn with_text_response(text: &str) -> Result<Self> {
+        Self::new(vec![text_response(text)])
+    }
+
+    /// Start configuring a fake CLI.
+    pub fn builder() -> FakeCliBuilder {
+        FakeCliBuilder::default()
+    }
+
+    /// Create a fake CLI that runs `script` with `/bin/sh` instead of
+    /// speaking the protocol, for tests of how the SDK handles a CLI that
+    /// misbehaves at the process level. It logs nothing, so [`args()`],
+    /// [`runs()`], and [`received()`] are empty.
+    ///
+    /// [`args()`]: FakeCli::args
+    /// [`runs()`]: FakeCli::runs
+    /// [`received()`]: FakeCli::received
+    pub fn from_script(script: &str) -> Result<Self> {
+        let dir = tempfile::tempdir().map_err(io_error)?;
+        let path = write_executable(dir.path(), &format!("#!/bin/sh\n{}\n", script))?;
+        Ok(Self { dir, path })
+    }
+
+    /// Path to the executable.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Default options pointing at the fake.
+    pub fn options(&self) -> ClaudeAgentOptions {
+        self.apply(ClaudeAgentOptions::new())
+    }
+
+    /// Point `options` at the fake.
+    pub fn apply(&self, mut options: ClaudeAgentOptions) -> ClaudeAgentOptions {
+        options.cli_path = Some(self.path.clone());
+        options
+    }
+
+    /// Arguments of the most recent run.
+    pub fn args(&self) -> Vec<String> {
+        self.runs().pop().unwrap_or_default()
+    }
+
+    /// Arguments of each run, oldest first. Version checks are not runs.
+    pub fn runs(&self) -> Vec<Vec<String>> {
+        (1..)
+            .map(|run| std::fs::read_to_string(self.dir.path().join(format!("args_{}", run))))
+            .map_while(|args| args.ok())
+            .map(|args| args.lines().map(str::to_string).collect())
+            .collect()
+    }
+
+    /// Lines read from stdin that parse as JSON, across all runs.
+    pub fn received(&self) -> Vec<Value> {
+        std::fs::read_to_string(self.dir.path().join("stdin.jsonl"))
+            .map(|lines| {
+                lines
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Text of the user messages received.
+    pub fn prompts(&self) -> Vec<String> {
+        self.received()
+            .iter()
+            .filter(|msg| msg["type"] == "user")
+            .filter_map(|msg| msg["message"]["content"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Subtypes of the control requests received.
+    pub fn control_requests(&self) -> Vec<String> {
+        self.received()
+            .iter()
+            .filter(|msg| msg["type"] == "control_request")
+            .filter_map(|msg| msg["request"]["subtype"].as_str().map(str::to_string))
+            .collect()
+    }
+}
+
+/// How the fake answers control requests of one subtype.
+enum ControlRule {
+    Reply(Value),
+    Fail(String),
+    Ignore,
+}
+
+/// Builder for a [`FakeCli`], created with [`FakeCli::builder()`].
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::testing::FakeCli;
+/// use serde_json::json;
+///
+/// let cli = FakeCli::builder()
+///     .reply_to_control("mcp_status", json!({"mcpServers": []}))
+///     .fail_control("set_permission_mode", "not allowed")
+///     .ignore_control("interrupt")
+///     .raw_turn("not json\n")
+///     .exit_after_turn(1)
+///     .build()
+///     .unwrap();
+/// # let _ = cli;
+/// ```
+#[derive(Default)]
+pub struct FakeCliBuilder {
+    start: String,
+    turns: Vec<String>,
+    control: Vec<(String, ControlRule)>,
+    exit_after_turn: Option<usize>,
+    version: Option<String>,
+}
+
+impl FakeCliBuilder {
+    /// Print `messages` as soon as the fake starts, before it reads stdin.
+    pub fn on_start(mut self, messages: Vec<Value>) -> Self {
+        self.start.push_str(&lines(&messages));
+        self
+    }
+
+    /// Print `messages` after the next user message.
+    pub fn turn(mut self, messages: Vec<Value>) -> Self {
+        self.turns.push(lines(&messages));
+        self
+    }
+
+    /// Print `output` verbatim after the next user message, for output
+    /// that isn't valid JSON.
+    pub fn raw_turn(mut self, output: impl Into<String>) -> Self {
+        let mut output = output.into();
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        self.turns.push(output);
+        self
+    }
+
+    /// Answer control requests of `subtype` with a success carrying
+    /// `response`.
+    pub fn reply_to_control(mut self, subtype: impl Into<String>, response: Value) -> Self {
+        self.control
+            .push((subtype.into(), ControlRule::Reply(response)));
+        self
+    }
+
+    /// Answer control requests of `subtype` with an error response.
+    pub fn fail_control(mut self, subtype: impl Into<String>, error: impl Into<String>) -> Self {
+        self.control
+            .push((subtype.into(), ControlRule::Fail(error.into())));
+        self
+    }
+
+    /// Leave control requests of `subtype` unanswered.
+    pub fn ignore_control(mut self, subtype: impl Into<String>) -> Self {
+        self.control.push((subtype.into(), ControlRule::Ignore));
+        self
+    }
+
+    /// Exit once the output of turn `turn`, counting from 1, is printed.
+    pub fn exit_after_turn(mut self, turn: usize) -> Self {
+        self.exit_after_turn = Some(turn);
+        self
+    }
+
+    /// Version reported for `--version`.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Write the fake to a temporary directory.
+    pub fn build(self) -> Result<FakeCli> {
+        let dir = tempfile::tempdir().map_err(io_error)?;
+        let write = |name: String, contents: &str| {
+            std::fs::write(dir.path().join(name), contents).map_err(io_error)
+        };
+        if !self.start.is_empty() {
+            write("start.jsonl".to_string(), &self.start)?;
+        }
+        for (i, turn) in self.turns.iter().enumerate() {
+            write(format!("turn_{}.jsonl", i + 1), turn)?;
+        }
+
+        // The first rule for a subtype wins, as in a shell `case`.
+        let mut control = String::new();
+        for (i, (subtype, rule)) in self.control.iter().enumerate() {
+            let file = format!("control_{}.json", i);
+            let action = match rule {
+                ControlRule::Reply(response) => {
+                    write(file.clone(), &response.to_string())?;
+                    format!("success \"$(cat \"$dir/{}\")\"", file)
+                }
+                ControlRule::Fail(error) => {
+                    write(file.clone(), &Value::from(error.as_str()).to_string())?;
+                    format!("error \"$(cat \"$dir/{}\")\"", file)
+                }
+                ControlRule::Ignore => ":".to_string(),
+            };
+            control.push_str(&format!(
+                "                *'\"subtype\":\"{}\"'*) {} ;;\n",
+                subtype, action
+            ));
+        }
+        let exit = self
+            .exit_after_turn
+            .map(|turn| {
+                format!(
+                    "            if [ \"$turn\" -eq {} ]; then exit 0; fi\n",
+                    turn
+                )
+            })
+            .unwrap_or_default();
+
+        let script = SCRIPT
+            .replace("{dir}", &dir.path().display().to_string())
+            .replace(
+                "{version}",
+                self.version.as_deref().unwrap_or(DEFAULT_VERSION),
+            )
+            .replace("{control}", &control)
+            .replace("{exit}", &exit);
+        let path = write_executable(dir.path(), &script)?;
+        Ok(FakeCli { dir, path })
+    }
+}
+
+/// `messages` as JSON lines.
+fn lines(messages: &[Value]) -> String {
+    messages.iter().map(|msg| format!("{}\n", msg)).collect()
+}
+
+fn write_executable(dir: &Path, script: &str) -> Result<PathBuf> {
+    let path = dir.join("claude");
+    std::fs::write(&path, script).map_err(io_error)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).map_err(io_error)?;
+    Ok(path)
+}
+
+fn io_error(e: std::io::Error) -> ClaudeSDKError {
+    ClaudeSDKError::configuration(format!("Failed to create fake CLI: {}", e))
+}
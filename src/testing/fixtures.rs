@@ -0,0 +1,288 @@
+//! Golden fixtures of real CLI output.
+//!
+//! A fixture is a JSONL file of lines the CLI printed, either bare (one CLI
+//! message per line) or a [`Recording`] made with the
+//! [`Recorder`](crate::recorder::Recorder). [`load`] reads one;
+//! [`Fixture::parse`] runs it through the SDK's message parser and
+//! [`Fixture::transport`] replays it through a [`MockTransport`].
+//!
+//! The crate ships a small curated set, available through [`builtin`], so
+//! downstream tests catch parser regressions against real payload shapes
+//! without carrying their own copies.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::testing::fixtures;
+//!
+//! for fixture in fixtures::builtin() {
+//!     let messages = fixture.parse().expect(&fixture.name);
+//!     assert!(!messages.is_empty());
+//! }
+//! ```
+
+use std::path::Path;
+
+use crate::_internal::message_parser::decode_line;
+use crate::errors::{ClaudeSDKError, Result};
+use crate::recorder::{Recording, RecordingHeader, Replayer};
+use crate::types::Message;
+use crate::wire_log::{WireDirection, WireEntry};
+
+use super::MockTransport;
+
+/// The curated fixtures, by name.
+const BUILTIN: &[(&str, &str)] = &[
+    (
+        "error_max_turns",
+        include_str!("../../fixtures/error_max_turns.jsonl"),
+    ),
+    (
+        "partial_messages",
+        include_str!("../../fixtures/partial_messages.jsonl"),
+    ),
+    ("text_turn", include_str!("../../fixtures/text_turn.jsonl")),
+    (
+        "tool_use_turn",
+        include_str!("../../fixtures/tool_use_turn.jsonl"),
+    ),
+];
+
+/// Recorded CLI output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    /// File stem, or the builtin fixture's name.
+    pub name: String,
+    /// The recorded lines.
+    pub recording: Recording,
+}
+
+impl Fixture {
+    /// Build a fixture from JSONL text: bare CLI output or a recording.
+    pub fn from_jsonl(name: impl Into<String>, text: &str) -> Result<Self> {
+        let name = name.into();
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let first = lines.next().unwrap_or_default();
+
+        let recording = match serde_json::from_str::<RecordingHeader>(first) {
+            Ok(header) => {
+                let entries = lines
+                    .enumerate()
+                    .map(|(i, line)| {
+                        serde_json::from_str::<WireEntry>(line).map_err(|e| {
+                            ClaudeSDKError::configuration(format!(
+                                "Invalid fixture {} at entry {}: {}",
+                                name,
+                                i + 1,
+                                e
+                            ))
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Recording { header, entries }
+            }
+            Err(_) => Recording {
+                header: RecordingHeader {
+                    created_ms: 0,
+                    ..RecordingHeader::default()
+                },
+                entries: std::iter::once(first)
+                    .filter(|line| !line.is_empty())
+                    .chain(lines)
+                    .map(|line| WireEntry {
+                        timestamp_ms: 0,
+                        direction: WireDirection::Received,
+                        line: line.to_string(),
+                        metadata: Default::default(),
+                    })
+                    .collect(),
+            },
+        };
+        Ok(Self { name, recording })
+    }
+
+    /// Lines the CLI printed, in order.
+    pub fn received_lines(&self) -> impl Iterator<Item = &str> {
+        self.recording
+            .entries
+            .iter()
+            .filter(|entry| entry.direction == WireDirection::Received)
+            .map(|entry| entry.line.as_str())
+    }
+
+    /// Parse every message, as the client's message stream would yield them.
+    pub fn messages(&self) -> Vec<Result<Message>> {
+        Replayer::new(self.recording.clone()).messages().collect()
+    }
+
+    /// Parse every message, failing on the first error.
+    ///
+    /// The error names the fixture.
+    pub fn parse(&self) -> Result<Vec<Message>> {
+        self.messages()
+            .into_iter()
+            .enumerate()
+            .map(|(i, message)| {
+                message.map_err(|e| {
+                    ClaudeSDKError::message_parse(format!(
+                        "Fixture {} message {}: {}",
+                        self.name,
+                        i + 1,
+                        e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// A [`MockTransport`] that replays the CLI's side of the fixture.
+    ///
+    /// Lines that are not valid JSON come back as decode errors, as they
+    /// would from the CLI.
+    pub fn transport(&self) -> MockTransport {
+        let mut responses = Vec::new();
+        let mut malformed = Vec::new();
+        for line in self.received_lines() {
+            match decode_line(line) {
+                Ok(value) => responses.push(value),
+                Err(_) => malformed.push((responses.len(), line.to_string())),
+            }
+        }
+        malformed
+            .into_iter()
+            .fold(MockTransport::new(responses), |transport, (index, line)| {
+                transport.with_malformed_json_at(index, line)
+            })
+    }
+}
+
+/// Load a fixture file.
+pub fn load(path: impl AsRef<Path>) -> Result<Fixture> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        ClaudeSDKError::configuration(format!("Failed to read fixture {}: {}", path.display(), e))
+    })?;
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Fixture::from_jsonl(name, &text)
+}
+
+/// Load every `.jsonl` fixture in a directory, sorted by name.
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<Fixture>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        ClaudeSDKError::configuration(format!(
+            "Failed to read fixture directory {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    paths.sort();
+    paths.into_iter().map(load).collect()
+}
+
+/// The curated fixtures shipped with the crate.
+pub fn builtin() -> Vec<Fixture> {
+    BUILTIN
+        .iter()
+        .map(|(name, text)| {
+            Fixture::from_jsonl(*name, text).expect("builtin fixtures are valid JSONL")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::transport::Transport;
+    use crate::types::ContentBlock;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_builtin_fixtures_parse() {
+        let fixtures = builtin();
+        assert_eq!(fixtures.len(), BUILTIN.len());
+        for fixture in fixtures {
+            let messages = fixture.parse().unwrap();
+            assert!(
+                matches!(messages.last(), Some(Message::Result(_))),
+                "{} should end with a result",
+                fixture.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_builtin_fixture_shapes() {
+        let fixture = builtin()
+            .into_iter()
+            .find(|f| f.name == "tool_use_turn")
+            .unwrap();
+        let messages = fixture.parse().unwrap();
+        let tool_use = messages.iter().find_map(|m| match m {
+            Message::Assistant(a) => a.content.iter().find_map(|b| match b {
+                ContentBlock::ToolUse(t) => Some(t.name.clone()),
+                _ => None,
+            }),
+            _ => None,
+        });
+        assert_eq!(tool_use.as_deref(), Some("Bash"));
+    }
+
+    #[test]
+    fn test_fixture_directory_matches_builtin() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let loaded = load_dir(dir).unwrap();
+        assert_eq!(loaded, builtin());
+    }
+
+    #[test]
+    fn test_load_recording_fixture() {
+        let recording = Recording {
+            header: RecordingHeader::default(),
+            entries: vec![
+                WireEntry {
+                    timestamp_ms: 1,
+                    direction: WireDirection::Sent,
+                    line: r#"{"type":"user","message":{"role":"user","content":"hi"}}"#.into(),
+                    metadata: Default::default(),
+                },
+                WireEntry {
+                    timestamp_ms: 2,
+                    direction: WireDirection::Received,
+                    line: r#"{"type":"system","subtype":"init"}"#.into(),
+                    metadata: Default::default(),
+                },
+            ],
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        recording.save(&path).unwrap();
+
+        let fixture = load(&path).unwrap();
+        assert_eq!(fixture.name, "session");
+        assert_eq!(fixture.recording, recording);
+        assert_eq!(fixture.received_lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fixture_transport_replays_cli_output() {
+        let fixture = Fixture::from_jsonl(
+            "broken",
+            "{\"type\":\"system\",\"subtype\":\"init\"}\nnot json\n{\"type\":\"result\"}\n",
+        )
+        .unwrap();
+        let items: Vec<_> = fixture.transport().message_stream().collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap()["type"], "system");
+        assert!(items[1].is_err());
+        assert_eq!(items[2].as_ref().unwrap()["type"], "result");
+    }
+}
@@ -0,0 +1,587 @@
+//! Test doubles for code built on the SDK.
+//!
+//! [`MockTransport`] implements [`Transport`] with pre-recorded CLI output
+//! and records everything written to it, so agent code can be unit-tested
+//! without the Claude CLI. On Unix, `FakeCli` is an executable stand-in for
+//! the CLI itself, for exercising the real subprocess transport.
+//! [`Scenario`] scripts a multi-turn conversation and checks the
+//! application's side of it turn by turn. [`fixtures`] loads recorded CLI
+//! output, including a curated set shipped with the crate, and
+//! [`assertions`] checks the messages a query produced. With the `proptest`
+//! feature, `strategies` generates realistic messages and options for
+//! property tests.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::_internal::transport::Transport;
+//! use claude_agents_sdk::testing::MockTransport;
+//! use tokio_stream::StreamExt;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> claude_agents_sdk::Result<()> {
+//! let mut transport = MockTransport::with_text_response("Hello!");
+//! transport.connect().await?;
+//! transport
+//!     .write(r#"{"type":"user","message":{"role":"user","content":"Hi"}}"#)
+//!     .await?;
+//!
+//! let replies: Vec<_> = transport.message_stream().collect().await;
+//! assert_eq!(replies.len(), 3);
+//! transport.assert_prompt_sent("Hi");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream;
+use serde_json::{json, Value};
+use tokio_stream::Stream;
+
+use crate::_internal::message_parser::decode_line;
+use crate::_internal::transport::Transport;
+use crate::errors::{ClaudeSDKError, Result};
+
+pub mod assertions;
+#[cfg(all(unix, feature = "subprocess"))]
+mod fake_cli;
+pub mod fixtures;
+mod scenario;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod strategies;
+
+#[cfg(all(unix, feature = "subprocess"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "subprocess"))))]
+pub use fake_cli::{FakeCli, FakeCliBuilder};
+pub use scenario::{Scenario, ScenarioTransport};
+
+/// Faults and latency a [`MockTransport`] injects.
+#[derive(Debug, Clone, Default)]
+struct Faults {
+    /// Delay before every response without its own delay.
+    message_delay: Duration,
+    /// Delay before the response at an index.
+    delays: HashMap<usize, Duration>,
+    /// Malformed lines emitted before the response at an index.
+    malformed: HashMap<usize, String>,
+    /// Index at which the connection drops.
+    disconnect_at: Option<usize>,
+    /// Delay before each write is accepted.
+    write_delay: Duration,
+}
+
+/// A transport that returns pre-recorded responses.
+///
+/// Clones share the same responses and written messages, so a test can keep
+/// a clone to inspect after handing the transport to the code under test.
+///
+/// The `with_*_delay`, `with_malformed_json_at`, and `with_disconnect_at`
+/// builders inject latency and faults. Delays use `tokio::time`, so tests
+/// can run them instantly under `tokio::time::pause`. Indexes count
+/// responses from zero.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    /// Pre-recorded responses to return (in order).
+    responses: Arc<Mutex<Vec<Value>>>,
+    /// Index of the next response to return.
+    response_index: Arc<AtomicUsize>,
+    /// Whether the transport is connected.
+    connected: Arc<AtomicBool>,
+    /// Whether input has been ended.
+    input_ended: Arc<AtomicBool>,
+    /// Messages written to the transport.
+    written_messages: Arc<Mutex<Vec<String>>>,
+    /// Injected faults and latency.
+    faults: Faults,
+    /// Indexes whose malformed line has been emitted.
+    malformed_sent: Arc<Mutex<HashSet<usize>>>,
+    /// Whether an injected disconnect has happened.
+    dropped: Arc<AtomicBool>,
+}
+
+impl MockTransport {
+    /// Create a mock transport with the given responses.
+    pub fn new(responses: Vec<Value>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses)),
+            ..Self::default()
+        }
+    }
+
+    /// Create a mock transport with a simple text response.
+    pub fn with_text_response(text: &str) -> Self {
+        Self::new(text_response(text))
+    }
+
+    /// Create a mock transport that simulates an error.
+    pub fn with_error_response(error_message: &str) -> Self {
+        Self::new(vec![
+            init_message(),
+            json!({
+                "type": "result",
+                "subtype": "error",
+                "is_error": true,
+                "duration_ms": 50,
+                "duration_api_ms": 40,
+                "num_turns": 0,
+                "session_id": "mock-session",
+                "result": error_message
+            }),
+        ])
+    }
+
+    /// Create a mock transport that simulates tool use.
+    pub fn with_tool_use(tool_name: &str, tool_input: Value) -> Self {
+        Self::new(vec![
+            init_message(),
+            json!({
+                "type": "assistant",
+                "message": {
+                    "content": [
+                        {"type": "text", "text": "Let me use a tool."},
+                        {
+                            "type": "tool_use",
+                            "id": "mock-tool-id",
+                            "name": tool_name,
+                            "input": tool_input
+                        }
+                    ],
+                    "model": "mock-model"
+                }
+            }),
+            json!({
+                "type": "result",
+                "subtype": "success",
+                "is_error": false,
+                "duration_ms": 200,
+                "duration_api_ms": 150,
+                "num_turns": 1,
+                "session_id": "mock-session"
+            }),
+        ])
+    }
+
+    /// Delay every response by `delay`.
+    pub fn with_message_delay(mut self, delay: Duration) -> Self {
+        self.faults.message_delay = delay;
+        self
+    }
+
+    /// Delay the response at `index` by `delay`, instead of the
+    /// [message delay](Self::with_message_delay).
+    pub fn with_delay_at(mut self, index: usize, delay: Duration) -> Self {
+        self.faults.delays.insert(index, delay);
+        self
+    }
+
+    /// Emit `line` before the response at `index`.
+    ///
+    /// The line is decoded like CLI output, so invalid JSON surfaces as the
+    /// same [`ClaudeSDKError::JSONDecode`] the subprocess transport returns.
+    pub fn with_malformed_json_at(mut self, index: usize, line: impl Into<String>) -> Self {
+        self.faults.malformed.insert(index, line.into());
+        self
+    }
+
+    /// Drop the connection instead of returning the response at `index`.
+    ///
+    /// The stream yields a connection error and ends, and later writes fail.
+    pub fn with_disconnect_at(mut self, index: usize) -> Self {
+        self.faults.disconnect_at = Some(index);
+        self
+    }
+
+    /// Delay accepting each write by `delay`, like a CLI slow to read stdin.
+    pub fn with_write_delay(mut self, delay: Duration) -> Self {
+        self.faults.write_delay = delay;
+        self
+    }
+
+    /// Whether an injected disconnect has happened.
+    pub fn is_dropped(&self) -> bool {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    /// The next item of the message stream, after any injected delay.
+    async fn next_response(&self) -> Option<Result<Value>> {
+        if self.is_dropped() {
+            return None;
+        }
+        let index = self.response_index.load(Ordering::SeqCst);
+        if let Some(line) = self.faults.malformed.get(&index) {
+            if self.malformed_sent.lock().unwrap().insert(index) {
+                return Some(decode_line(line));
+            }
+        }
+        if self.faults.disconnect_at == Some(index) {
+            self.dropped.store(true, Ordering::SeqCst);
+            return Some(Err(ClaudeSDKError::cli_connection(format!(
+                "MockTransport dropped the connection at response {}",
+                index
+            ))));
+        }
+        let delay = self
+            .faults
+            .delays
+            .get(&index)
+            .copied()
+            .unwrap_or(self.faults.message_delay);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let index = self.response_index.fetch_add(1, Ordering::SeqCst);
+        let responses = self.responses.lock().unwrap();
+        responses.get(index).cloned().map(Ok)
+    }
+
+    /// Append a response after the recorded ones.
+    pub fn push_response(&self, response: Value) {
+        self.responses.lock().unwrap().push(response);
+    }
+
+    /// Messages written to the transport, as written.
+    pub fn written_messages(&self) -> Vec<String> {
+        self.written_messages.lock().unwrap().clone()
+    }
+
+    /// Messages written to the transport that parse as JSON.
+    pub fn written_json(&self) -> Vec<Value> {
+        self.written_messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    /// Text of the user messages written to the transport.
+    pub fn prompts(&self) -> Vec<String> {
+        self.written_json()
+            .iter()
+            .filter(|msg| msg["type"] == "user")
+            .filter_map(|msg| msg["message"]["content"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Subtypes of the control requests written to the transport.
+    pub fn control_requests(&self) -> Vec<String> {
+        self.written_json()
+            .iter()
+            .filter(|msg| msg["type"] == "control_request")
+            .filter_map(|msg| msg["request"]["subtype"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Whether [`end_input`](Transport::end_input) has been called.
+    pub fn input_ended(&self) -> bool {
+        self.input_ended.load(Ordering::SeqCst)
+    }
+
+    /// Panic unless a user message with exactly this text was written.
+    #[track_caller]
+    pub fn assert_prompt_sent(&self, text: &str) {
+        let prompts = self.prompts();
+        assert!(
+            prompts.iter().any(|prompt| prompt == text),
+            "prompt {:?} was not sent; prompts sent: {:?}",
+            text,
+            prompts
+        );
+    }
+
+    /// Panic unless a control request with this subtype was written.
+    #[track_caller]
+    pub fn assert_control_request_sent(&self, subtype: &str) {
+        let requests = self.control_requests();
+        assert!(
+            requests.iter().any(|request| request == subtype),
+            "control request {:?} was not sent; requests sent: {:?}",
+            subtype,
+            requests
+        );
+    }
+
+    /// Panic unless exactly `count` messages were written.
+    #[track_caller]
+    pub fn assert_written_count(&self, count: usize) {
+        let written = self.written_messages();
+        assert_eq!(
+            written.len(),
+            count,
+            "expected {} written messages, got {:?}",
+            count,
+            written
+        );
+    }
+}
+
+/// The `system` init message every canned conversation starts with.
+/// An init message, an assistant message with `text`, and a result.
+fn text_response(text: &str) -> Vec<Value> {
+    vec![
+        init_message(),
+        json!({
+            "type": "assistant",
+            "message": {
+                "content": [{"type": "text", "text": text}],
+                "model": "mock-model"
+            }
+        }),
+        json!({
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "duration_ms": 100,
+            "duration_api_ms": 80,
+            "num_turns": 1,
+            "session_id": "mock-session",
+            "total_cost_usd": 0.001
+        }),
+    ]
+}
+
+fn init_message() -> Value {
+    json!({
+        "type": "system",
+        "subtype": "init",
+        "data": {"session_id": "mock-session"}
+    })
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.connected.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        if !self.faults.write_delay.is_zero() {
+            tokio::time::sleep(self.faults.write_delay).await;
+        }
+        if self.is_dropped() {
+            return Err(ClaudeSDKError::cli_connection(
+                "MockTransport connection was dropped",
+            ));
+        }
+        self.written_messages.lock().unwrap().push(data.to_string());
+        Ok(())
+    }
+
+    fn message_stream(&self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+        Box::pin(stream::unfold(self, |transport| async move {
+            let next = transport.next_response().await;
+            next.map(|item| (item, transport))
+        }))
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.connected.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        self.input_ended.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_mock_transport_connect_and_close() {
+        let mut transport = MockTransport::new(vec![]);
+
+        assert!(!transport.is_ready(), "Should not be ready before connect");
+
+        transport.connect().await.unwrap();
+        assert!(transport.is_ready(), "Should be ready after connect");
+
+        transport.close().await.unwrap();
+        assert!(!transport.is_ready(), "Should not be ready after close");
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_write_captures_messages() {
+        let transport = MockTransport::new(vec![]);
+
+        transport.write("Hello").await.unwrap();
+        transport.write("World").await.unwrap();
+
+        let messages = transport.written_messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], "Hello");
+        assert_eq!(messages[1], "World");
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_returns_responses_in_order() {
+        let transport = MockTransport::new(vec![
+            json!({"index": 0}),
+            json!({"index": 1}),
+            json!({"index": 2}),
+        ]);
+
+        let mut stream = transport.message_stream();
+
+        let msg0 = stream.next().await.unwrap().unwrap();
+        assert_eq!(msg0["index"], 0);
+
+        let msg1 = stream.next().await.unwrap().unwrap();
+        assert_eq!(msg1["index"], 1);
+
+        let msg2 = stream.next().await.unwrap().unwrap();
+        assert_eq!(msg2["index"], 2);
+
+        // Should return None when exhausted
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_with_text_response() {
+        let transport = MockTransport::with_text_response("Hello, world!");
+        let mut stream = transport.message_stream();
+
+        // System init
+        let init = stream.next().await.unwrap().unwrap();
+        assert_eq!(init["type"], "system");
+
+        // Assistant response
+        let response = stream.next().await.unwrap().unwrap();
+        assert_eq!(response["type"], "assistant");
+        assert_eq!(response["message"]["content"][0]["text"], "Hello, world!");
+
+        // Result
+        let result = stream.next().await.unwrap().unwrap();
+        assert_eq!(result["type"], "result");
+        assert_eq!(result["is_error"], false);
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_with_error_response() {
+        let transport = MockTransport::with_error_response("Something went wrong");
+        let mut stream = transport.message_stream();
+
+        // System init
+        let _init = stream.next().await.unwrap().unwrap();
+
+        // Error result
+        let result = stream.next().await.unwrap().unwrap();
+        assert_eq!(result["type"], "result");
+        assert_eq!(result["is_error"], true);
+        assert_eq!(result["result"], "Something went wrong");
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_with_tool_use() {
+        let transport = MockTransport::with_tool_use("Bash", json!({"command": "ls"}));
+        let mut stream = transport.message_stream();
+
+        // System init
+        let _init = stream.next().await.unwrap().unwrap();
+
+        // Assistant with tool use
+        let response = stream.next().await.unwrap().unwrap();
+        assert_eq!(response["type"], "assistant");
+
+        let content = &response["message"]["content"];
+        assert_eq!(content[1]["type"], "tool_use");
+        assert_eq!(content[1]["name"], "Bash");
+        assert_eq!(content[1]["input"]["command"], "ls");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_injected_delays() {
+        let transport = MockTransport::new(vec![json!({"index": 0}), json!({"index": 1})])
+            .with_message_delay(Duration::from_millis(100))
+            .with_delay_at(1, Duration::from_secs(5))
+            .with_write_delay(Duration::from_millis(30));
+        let start = tokio::time::Instant::now();
+
+        transport.write("slow").await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
+
+        let mut stream = transport.message_stream();
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(130));
+
+        let second = tokio::time::timeout(Duration::from_secs(1), stream.next()).await;
+        assert!(second.is_err(), "Long delay should trip a shorter timeout");
+    }
+
+    #[tokio::test]
+    async fn test_injected_malformed_json() {
+        let transport = MockTransport::new(vec![json!({"index": 0}), json!({"index": 1})])
+            .with_malformed_json_at(1, "{\"type\": \"assistant\", oops}");
+        let items: Vec<_> = transport.message_stream().collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap()["index"], 0);
+        let err = items[1].as_ref().unwrap_err();
+        assert!(
+            matches!(err, ClaudeSDKError::JSONDecode { .. }),
+            "{:?}",
+            err
+        );
+        assert_eq!(items[2].as_ref().unwrap()["index"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_injected_disconnect() {
+        let transport = MockTransport::new(vec![json!({"index": 0}), json!({"index": 1})])
+            .with_disconnect_at(1);
+        let items: Vec<_> = transport.message_stream().collect().await;
+
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        assert!(items[1]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("dropped"));
+        assert!(transport.is_dropped());
+        assert!(transport.write("late").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_written_message_assertions() {
+        let transport = MockTransport::new(vec![]);
+        let observer = transport.clone();
+
+        transport
+            .write(r#"{"type":"control_request","request_id":"r1","request":{"subtype":"initialize"}}"#)
+            .await
+            .unwrap();
+        transport
+            .write(r#"{"type":"user","message":{"role":"user","content":"Hi"}}"#)
+            .await
+            .unwrap();
+        transport.end_input().await.unwrap();
+
+        observer.assert_written_count(2);
+        observer.assert_prompt_sent("Hi");
+        observer.assert_control_request_sent("initialize");
+        assert_eq!(observer.prompts(), ["Hi"]);
+        assert!(observer.input_ended());
+    }
+
+    #[test]
+    #[should_panic(expected = "prompt \"Bye\" was not sent")]
+    fn test_assert_prompt_sent_panics_with_prompts() {
+        MockTransport::new(vec![]).assert_prompt_sent("Bye");
+    }
+}
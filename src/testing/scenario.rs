@@ -0,0 +1,696 @@
+//! Scripted multi-turn conversations.
+//!
+//! A [`Scenario`] is a script of what the application should send and what
+//! the CLI replies. [`Scenario::build`] turns it into a [`ScenarioTransport`]
+//! that plays the CLI's side: it checks each message the application writes
+//! against the next expectation, emits the replies that follow, and records
+//! a readable description, with a diff where one helps, of the first
+//! message that does not match.
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+
+use crate::_internal::transport::{MessageStream, Transport};
+use crate::errors::{ClaudeSDKError, Result};
+
+/// Session ID used in every message a scenario emits.
+const SESSION_ID: &str = "scenario";
+
+#[derive(Debug, Clone)]
+enum Step {
+    ExpectUser { text: String, exact: bool },
+    ReplyText(String),
+    ToolCall { name: String, input: Value },
+    ReplyToolResult { content: Value, is_error: bool },
+    Charge(f64),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::ExpectUser { text, exact: true } => write!(f, "expect user message {:?}", text),
+            Step::ExpectUser { text, .. } => {
+                write!(f, "expect user message containing {:?}", text)
+            }
+            Step::ReplyText(text) => write!(f, "reply text {:?}", text),
+            Step::ToolCall { name, .. } => write!(f, "expect tool call {} to be allowed", name),
+            Step::ReplyToolResult { content, .. } => write!(f, "reply tool result {}", content),
+            Step::Charge(usd) => write!(f, "charge ${}", usd),
+        }
+    }
+}
+
+/// A scripted conversation between an application and the CLI.
+///
+/// Replies after an expectation form one turn; the scenario ends the turn
+/// with a `result` message before waiting for the next user message.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::testing::Scenario;
+///
+/// let transport = Scenario::new()
+///     .expect_user_contains("deploy")
+///     .reply_text("Deploying now.")
+///     .expect_tool_call("Bash")
+///     .reply_tool_result("deployed v2")
+///     .reply_text("Done.")
+///     .build();
+/// # let _ = transport;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    steps: Vec<Step>,
+}
+
+impl Scenario {
+    /// Create an empty scenario.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect the next user message to be exactly `text`.
+    pub fn expect_user(mut self, text: impl Into<String>) -> Self {
+        self.steps.push(Step::ExpectUser {
+            text: text.into(),
+            exact: true,
+        });
+        self
+    }
+
+    /// Expect the next user message to contain `text`.
+    pub fn expect_user_contains(mut self, text: impl Into<String>) -> Self {
+        self.steps.push(Step::ExpectUser {
+            text: text.into(),
+            exact: false,
+        });
+        self
+    }
+
+    /// Reply with an assistant text message.
+    pub fn reply_text(mut self, text: impl Into<String>) -> Self {
+        self.steps.push(Step::ReplyText(text.into()));
+        self
+    }
+
+    /// Call a tool with empty input and expect the application to allow it.
+    ///
+    /// Emits the assistant's `tool_use` block and a `can_use_tool` request,
+    /// and waits for a response with `"behavior": "allow"`.
+    pub fn expect_tool_call(self, name: impl Into<String>) -> Self {
+        self.expect_tool_call_with(name, json!({}))
+    }
+
+    /// Call a tool with `input` and expect the application to allow it.
+    pub fn expect_tool_call_with(mut self, name: impl Into<String>, input: Value) -> Self {
+        self.steps.push(Step::ToolCall {
+            name: name.into(),
+            input,
+        });
+        self
+    }
+
+    /// Reply with the result of the most recent tool call.
+    pub fn reply_tool_result(mut self, content: impl Into<Value>) -> Self {
+        self.steps.push(Step::ReplyToolResult {
+            content: content.into(),
+            is_error: false,
+        });
+        self
+    }
+
+    /// Reply with a failed result of the most recent tool call.
+    pub fn reply_tool_error(mut self, content: impl Into<Value>) -> Self {
+        self.steps.push(Step::ReplyToolResult {
+            content: content.into(),
+            is_error: true,
+        });
+        self
+    }
+
+    /// Add `usd` to the session's cost.
+    ///
+    /// Like the CLI, results report the session's cost so far as
+    /// `total_cost_usd`, once anything has been charged.
+    pub fn charge(mut self, usd: f64) -> Self {
+        self.steps.push(Step::Charge(usd));
+        self
+    }
+
+    /// Build the transport that plays this scenario.
+    pub fn build(self) -> ScenarioTransport {
+        let (tx, rx) = mpsc::unbounded_channel();
+        ScenarioTransport {
+            state: Arc::new(Mutex::new(State {
+                steps: self.steps,
+                cursor: 0,
+                turn_open: false,
+                tool_calls: 0,
+                cost_usd: None,
+                pending_tool: None,
+                failures: Vec::new(),
+                tx: Some(tx),
+                connected: false,
+            })),
+            rx: Arc::new(tokio::sync::Mutex::new(rx)),
+        }
+    }
+}
+
+/// A tool call waiting for the application's permission.
+struct PendingTool {
+    request_id: String,
+    name: String,
+}
+
+struct State {
+    steps: Vec<Step>,
+    cursor: usize,
+    /// Whether replies have been emitted since the last `result`.
+    turn_open: bool,
+    tool_calls: usize,
+    cost_usd: Option<f64>,
+    pending_tool: Option<PendingTool>,
+    failures: Vec<String>,
+    tx: Option<mpsc::UnboundedSender<Value>>,
+    connected: bool,
+}
+
+impl State {
+    fn emit(&self, message: Value) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(message);
+        }
+    }
+
+    fn tool_use_id(&self) -> String {
+        format!("toolu_scenario_{}", self.tool_calls)
+    }
+
+    fn end_turn(&mut self) {
+        if self.turn_open {
+            self.turn_open = false;
+            let mut result = json!({
+                "type": "result",
+                "subtype": "success",
+                "duration_ms": 0,
+                "duration_api_ms": 0,
+                "is_error": false,
+                "num_turns": 1,
+                "session_id": SESSION_ID
+            });
+            if let Some(cost) = self.cost_usd {
+                result["total_cost_usd"] = json!(cost);
+            }
+            self.emit(result);
+        }
+    }
+
+    /// Emit replies until the next expectation.
+    fn advance(&mut self) {
+        while let Some(step) = self.steps.get(self.cursor).cloned() {
+            match step {
+                Step::ExpectUser { .. } => {
+                    self.end_turn();
+                    return;
+                }
+                Step::ReplyText(text) => {
+                    self.turn_open = true;
+                    self.emit(json!({
+                        "type": "assistant",
+                        "message": {
+                            "model": SESSION_ID,
+                            "content": [{"type": "text", "text": text}]
+                        },
+                        "session_id": SESSION_ID
+                    }));
+                }
+                Step::ToolCall { name, input } => {
+                    if self.pending_tool.is_some() {
+                        return;
+                    }
+                    self.turn_open = true;
+                    self.tool_calls += 1;
+                    let tool_use_id = self.tool_use_id();
+                    let request_id = format!("scenario_req_{}", self.tool_calls);
+                    self.emit(json!({
+                        "type": "assistant",
+                        "message": {
+                            "model": SESSION_ID,
+                            "content": [{
+                                "type": "tool_use",
+                                "id": tool_use_id,
+                                "name": name,
+                                "input": input
+                            }]
+                        },
+                        "session_id": SESSION_ID
+                    }));
+                    self.emit(json!({
+                        "type": "control_request",
+                        "request_id": request_id,
+                        "request": {
+                            "subtype": "can_use_tool",
+                            "tool_name": name,
+                            "input": input,
+                            "tool_use_id": tool_use_id
+                        }
+                    }));
+                    self.pending_tool = Some(PendingTool { request_id, name });
+                    return;
+                }
+                Step::ReplyToolResult { content, is_error } => {
+                    self.turn_open = true;
+                    self.emit(json!({
+                        "type": "user",
+                        "message": {
+                            "role": "user",
+                            "content": [{
+                                "type": "tool_result",
+                                "tool_use_id": self.tool_use_id(),
+                                "content": content,
+                                "is_error": is_error
+                            }]
+                        },
+                        "parent_tool_use_id": null,
+                        "session_id": SESSION_ID
+                    }));
+                }
+                Step::Charge(usd) => {
+                    self.cost_usd = Some(self.cost_usd.unwrap_or(0.0) + usd);
+                }
+            }
+            self.cursor += 1;
+        }
+        self.end_turn();
+    }
+
+    /// Record a mismatch at the current step.
+    fn fail(&mut self, message: String) -> ClaudeSDKError {
+        let message = format!("scenario step {}: {}", self.cursor + 1, message);
+        self.failures.push(message.clone());
+        ClaudeSDKError::control_protocol(message)
+    }
+
+    fn expected(&self) -> String {
+        match self.steps.get(self.cursor) {
+            Some(step) => step.to_string(),
+            None => "nothing (scenario finished)".to_string(),
+        }
+    }
+
+    fn on_user_message(&mut self, message: &Value) -> Result<()> {
+        let text = user_text(message);
+        let Some(Step::ExpectUser { text: want, exact }) = self.steps.get(self.cursor).cloned()
+        else {
+            let expected = self.expected();
+            return Err(self.fail(format!(
+                "expected {}, got user message {:?}",
+                expected, text
+            )));
+        };
+        let matches = if exact {
+            text == want
+        } else {
+            text.contains(&want)
+        };
+        if !matches {
+            let detail = if exact {
+                format!("user message differs:\n{}", line_diff(&want, &text))
+            } else {
+                format!(
+                    "expected user message containing {:?}, got {:?}",
+                    want, text
+                )
+            };
+            return Err(self.fail(detail));
+        }
+        self.cursor += 1;
+        self.advance();
+        Ok(())
+    }
+
+    fn on_control_response(&mut self, message: &Value) -> Result<()> {
+        let response = &message["response"];
+        let request_id = response["request_id"].as_str().unwrap_or_default();
+        let pending = match self.pending_tool.take() {
+            Some(pending) if pending.request_id == request_id => pending,
+            other => {
+                self.pending_tool = other;
+                return Err(self.fail(format!("unexpected control response:\n{}", pretty(message))));
+            }
+        };
+        let behavior = response["response"]["behavior"].as_str();
+        if response["subtype"] != "success" || behavior != Some("allow") {
+            return Err(self.fail(format!(
+                "expected tool call {} to be allowed, got:\n{}",
+                pending.name,
+                pretty(response)
+            )));
+        }
+        self.cursor += 1;
+        self.advance();
+        Ok(())
+    }
+
+    /// Answer the application's own control requests, like `initialize`.
+    fn on_control_request(&mut self, message: &Value) {
+        self.emit(json!({
+            "type": "control_response",
+            "response": {
+                "subtype": "success",
+                "request_id": message["request_id"],
+                "response": {}
+            }
+        }));
+    }
+}
+
+/// The text of a user message: its string content or its text blocks.
+fn user_text(message: &Value) -> String {
+    let content = &message["message"]["content"];
+    if let Some(text) = content.as_str() {
+        return text.to_string();
+    }
+    content
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// A line diff of `expected` against `actual`, `-` for expected lines and
+/// `+` for actual ones.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    out.join("\n")
+}
+
+/// A [`Transport`] that plays a [`Scenario`].
+///
+/// Writes that do not match the scenario fail with a control protocol error
+/// and are recorded; call [`assert_complete`](Self::assert_complete) at the
+/// end of the test. Clones share the same state.
+#[derive(Clone)]
+pub struct ScenarioTransport {
+    state: Arc<Mutex<State>>,
+    rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<Value>>>,
+}
+
+impl ScenarioTransport {
+    /// The scenario's replies, as they are emitted.
+    fn replies(&self) -> MessageStream {
+        let rx = Arc::clone(&self.rx);
+        Box::pin(futures::stream::unfold(rx, |rx| async move {
+            let next = rx.lock().await.recv().await;
+            next.map(|message| (Ok(message), rx))
+        }))
+    }
+
+    /// Mismatches recorded so far.
+    pub fn failures(&self) -> Vec<String> {
+        self.state.lock().unwrap().failures.clone()
+    }
+
+    /// Whether every step ran and nothing mismatched.
+    pub fn is_complete(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.failures.is_empty() && state.cursor == state.steps.len()
+    }
+
+    /// Panic with the mismatches and unfinished steps, if any.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        let state = self.state.lock().unwrap();
+        let mut problems = state.failures.clone();
+        if state.cursor < state.steps.len() {
+            let remaining: Vec<String> = state.steps[state.cursor..]
+                .iter()
+                .enumerate()
+                .map(|(i, step)| format!("  {}. {}", state.cursor + i + 1, step))
+                .collect();
+            problems.push(format!("unfinished steps:\n{}", remaining.join("\n")));
+        }
+        assert!(
+            problems.is_empty(),
+            "scenario did not complete:\n{}",
+            problems.join("\n")
+        );
+    }
+}
+
+#[async_trait]
+impl Transport for ScenarioTransport {
+    async fn connect(&mut self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.connected {
+            state.connected = true;
+            state.advance();
+        }
+        Ok(())
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let message: Value = serde_json::from_str(data)
+            .map_err(|e| state.fail(format!("wrote invalid JSON ({}): {}", e, data)))?;
+        match message["type"].as_str() {
+            Some("user") => state.on_user_message(&message),
+            Some("control_response") => state.on_control_response(&message),
+            Some("control_request") => {
+                state.on_control_request(&message);
+                Ok(())
+            }
+            _ => Err(state.fail(format!("unexpected message:\n{}", pretty(&message)))),
+        }
+    }
+
+    fn message_stream(&self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+        self.replies()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.connected = false;
+        state.tx = None;
+        Ok(())
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        self.state.lock().unwrap().tx = None;
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.state.lock().unwrap().connected
+    }
+
+    fn take_messages(&mut self) -> Option<MessageStream> {
+        Some(self.replies())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    fn prompt(text: &str) -> String {
+        json!({"type": "user", "message": {"role": "user", "content": text}}).to_string()
+    }
+
+    fn allow(request_id: &Value) -> String {
+        json!({
+            "type": "control_response",
+            "response": {
+                "subtype": "success",
+                "request_id": request_id,
+                "response": {"behavior": "allow"}
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_scenario_plays_turns() {
+        let mut transport = Scenario::new()
+            .expect_user_contains("deploy")
+            .reply_text("Deploying now.")
+            .expect_tool_call_with("Bash", json!({"command": "make deploy"}))
+            .reply_tool_result("deployed v2")
+            .reply_text("Done.")
+            .expect_user("thanks")
+            .reply_text("Any time.")
+            .build();
+        transport.connect().await.unwrap();
+        let mut stream = transport.message_stream();
+
+        transport.write(&prompt("please deploy")).await.unwrap();
+        let text = stream.next().await.unwrap().unwrap();
+        assert_eq!(text["message"]["content"][0]["text"], "Deploying now.");
+        let tool_use = stream.next().await.unwrap().unwrap();
+        assert_eq!(tool_use["message"]["content"][0]["name"], "Bash");
+        let request = stream.next().await.unwrap().unwrap();
+        assert_eq!(request["request"]["subtype"], "can_use_tool");
+        assert_eq!(request["request"]["input"]["command"], "make deploy");
+
+        transport
+            .write(&allow(&request["request_id"]))
+            .await
+            .unwrap();
+        let result = stream.next().await.unwrap().unwrap();
+        assert_eq!(result["message"]["content"][0]["content"], "deployed v2");
+        let done = stream.next().await.unwrap().unwrap();
+        assert_eq!(done["message"]["content"][0]["text"], "Done.");
+        assert_eq!(stream.next().await.unwrap().unwrap()["type"], "result");
+
+        transport.write(&prompt("thanks")).await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap()["type"], "result");
+
+        transport.assert_complete();
+    }
+
+    #[tokio::test]
+    async fn test_scenario_drives_client() {
+        use crate::{ClaudeAgentOptions, ClaudeClient};
+
+        let transport = Scenario::new()
+            .expect_user("hello")
+            .reply_text("Hi there.")
+            .build();
+        let options = {
+            let transport = transport.clone();
+            ClaudeAgentOptions::new().with_transport(move |_| Ok(transport.clone()))
+        };
+        let mut client = ClaudeClient::new(Some(options));
+        client.connect().await.unwrap();
+        client.query("hello").await.unwrap();
+
+        let (text, result) = client.receive_response().await.unwrap();
+        assert_eq!(text, "Hi there.");
+        assert!(!result.is_error);
+        client.disconnect().await.unwrap();
+        transport.assert_complete();
+    }
+
+    #[tokio::test]
+    async fn test_scenario_answers_app_control_requests() {
+        let mut transport = Scenario::new().expect_user("hi").build();
+        transport.connect().await.unwrap();
+        let mut stream = transport.message_stream();
+
+        transport
+            .write(r#"{"type":"control_request","request_id":"init_1","request":{"subtype":"initialize"}}"#)
+            .await
+            .unwrap();
+        let response = stream.next().await.unwrap().unwrap();
+        assert_eq!(response["response"]["request_id"], "init_1");
+        assert!(!transport.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_scenario_reports_mismatches() {
+        let mut transport = Scenario::new()
+            .expect_user("line one\nline two")
+            .reply_text("ok")
+            .build();
+        transport.connect().await.unwrap();
+
+        let err = transport
+            .write(&prompt("line one\nline 2"))
+            .await
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("scenario step 1"), "{}", message);
+        assert!(message.contains("- line two"), "{}", message);
+        assert!(message.contains("+ line 2"), "{}", message);
+        assert_eq!(transport.failures().len(), 1);
+        assert!(!transport.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_scenario_reports_denied_tool_call() {
+        let mut transport = Scenario::new()
+            .expect_user_contains("go")
+            .expect_tool_call("Bash")
+            .build();
+        transport.connect().await.unwrap();
+        let mut stream = transport.message_stream();
+
+        transport.write(&prompt("go")).await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        let request = stream.next().await.unwrap().unwrap();
+        let deny = json!({
+            "type": "control_response",
+            "response": {
+                "subtype": "success",
+                "request_id": request["request_id"],
+                "response": {"behavior": "deny", "message": "no"}
+            }
+        });
+        let err = transport.write(&deny.to_string()).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected tool call Bash to be allowed"));
+    }
+
+    #[test]
+    #[should_panic(expected = "2. reply text \"never\"")]
+    fn test_assert_complete_lists_unfinished_steps() {
+        Scenario::new()
+            .expect_user("hi")
+            .reply_text("never")
+            .build()
+            .assert_complete();
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc", "a\nx\nc"), "  a\n- b\n+ x\n  c");
+    }
+}
@@ -0,0 +1,369 @@
+//! Proptest strategies for SDK types.
+//!
+//! The `cli_*` strategies generate JSON in the shapes the CLI prints, for
+//! fuzzing code that consumes the wire format. [`message`] and
+//! [`content_block`] generate typed values, and [`message`] is built by
+//! running [`cli_message`] through the SDK's parser, so it only produces
+//! messages the parser can actually yield.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::reporting::CostReport;
+//! use claude_agents_sdk::testing::strategies;
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//!
+//! let messages = prop::collection::vec(strategies::message(), 0..8);
+//! TestRunner::default()
+//!     .run(&messages, |messages| {
+//!         let report = CostReport::from_messages(&messages);
+//!         prop_assert!(report.total_cost_usd >= 0.0);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+use crate::_internal::message_parser::parse_message;
+use crate::types::{
+    BuiltinTool, ClaudeAgentOptions, ContentBlock, Message, PermissionMode, TextBlock,
+    ThinkingBlock, ToolResultBlock, ToolUseBlock,
+};
+
+/// Printable text, including non-ASCII.
+pub fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,100}"
+}
+
+/// A short ASCII identifier-like string.
+pub fn safe_string() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 _-]{0,100}"
+}
+
+/// A tool name such as `Bash` or `mcp__docs__search`.
+pub fn tool_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[A-Z][a-zA-Z0-9]{0,20}",
+        "mcp__[a-z][a-z0-9_-]{0,10}__[a-z][a-z0-9_]{0,15}",
+    ]
+}
+
+/// A tool use ID such as `toolu_01XYZ...`.
+pub fn tool_id() -> impl Strategy<Value = String> {
+    "toolu_[a-zA-Z0-9]{10,24}"
+}
+
+/// A session ID: a lowercase UUID.
+pub fn session_id() -> impl Strategy<Value = String> {
+    "[a-f0-9]{8}-[a-f0-9]{4}-[a-f0-9]{4}-[a-f0-9]{4}-[a-f0-9]{12}"
+}
+
+/// A model name.
+pub fn model() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("claude-sonnet-4-5-20250929".to_string()),
+        Just("claude-opus-4-1-20250805".to_string()),
+        Just("claude-haiku-4-5-20251001".to_string()),
+        "claude-[a-z]+-[0-9]-[0-9]{8}",
+    ]
+}
+
+/// A tool input object with up to four scalar fields.
+pub fn tool_input() -> impl Strategy<Value = Value> {
+    let scalar = prop_oneof![
+        text().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<bool>().prop_map(Value::from),
+        Just(Value::Null),
+    ];
+    prop::collection::btree_map("[a-z_]{1,12}", scalar, 0..4)
+        .prop_map(|fields| Value::Object(fields.into_iter().collect()))
+}
+
+/// Token usage as reported on assistant and result messages.
+pub fn cli_usage() -> impl Strategy<Value = Value> {
+    (0u64..200_000, 0u64..50_000, 0u64..200_000, 0u64..8_000).prop_map(
+        |(input, cache_creation, cache_read, output)| {
+            json!({
+                "input_tokens": input,
+                "cache_creation_input_tokens": cache_creation,
+                "cache_read_input_tokens": cache_read,
+                "output_tokens": output,
+                "service_tier": "standard"
+            })
+        },
+    )
+}
+
+/// A content block in the API's JSON shape.
+pub fn cli_content_block() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        text().prop_map(|text| json!({"type": "text", "text": text})),
+        (text(), safe_string()).prop_map(|(thinking, signature)| {
+            json!({"type": "thinking", "thinking": thinking, "signature": signature})
+        }),
+        (tool_id(), tool_name(), tool_input()).prop_map(|(id, name, input)| {
+            json!({"type": "tool_use", "id": id, "name": name, "input": input})
+        }),
+        cli_tool_result(),
+    ]
+}
+
+/// A `tool_result` block, with string or text-block content.
+pub fn cli_tool_result() -> impl Strategy<Value = Value> {
+    let content = prop_oneof![
+        text().prop_map(Value::from),
+        prop::collection::vec(text(), 1..3).prop_map(|texts| {
+            texts
+                .into_iter()
+                .map(|text| json!({"type": "text", "text": text}))
+                .collect()
+        }),
+    ];
+    (tool_id(), content, any::<bool>()).prop_map(|(tool_use_id, content, is_error)| {
+        json!({
+            "type": "tool_result",
+            "tool_use_id": tool_use_id,
+            "content": content,
+            "is_error": is_error
+        })
+    })
+}
+
+/// An `assistant` line.
+pub fn cli_assistant() -> impl Strategy<Value = Value> {
+    (
+        prop::collection::vec(cli_content_block(), 1..4),
+        model(),
+        session_id(),
+        cli_usage(),
+        prop::option::of(tool_id()),
+    )
+        .prop_map(|(content, model, session_id, usage, parent)| {
+            json!({
+                "type": "assistant",
+                "message": {
+                    "model": model,
+                    "role": "assistant",
+                    "type": "message",
+                    "content": content,
+                    "usage": usage
+                },
+                "parent_tool_use_id": parent,
+                "session_id": session_id
+            })
+        })
+}
+
+/// A `user` line: a prompt echo or tool results.
+pub fn cli_user() -> impl Strategy<Value = Value> {
+    let content = prop_oneof![
+        text().prop_map(Value::from),
+        prop::collection::vec(cli_tool_result(), 1..3).prop_map(Value::from),
+    ];
+    (content, session_id()).prop_map(|(content, session_id)| {
+        json!({
+            "type": "user",
+            "message": {"role": "user", "content": content},
+            "parent_tool_use_id": null,
+            "session_id": session_id
+        })
+    })
+}
+
+/// A `system` line; `init` or another subtype.
+pub fn cli_system() -> impl Strategy<Value = Value> {
+    let init = (
+        session_id(),
+        model(),
+        prop::collection::vec(tool_name(), 0..6),
+    )
+        .prop_map(|(session_id, model, tools)| {
+            json!({
+                "type": "system",
+                "subtype": "init",
+                "session_id": session_id,
+                "model": model,
+                "tools": tools,
+                "permissionMode": "default",
+                "cwd": "/home/user/project"
+            })
+        });
+    let other = ("[a-z_]{1,20}", session_id()).prop_map(|(subtype, session_id)| {
+        json!({"type": "system", "subtype": subtype, "session_id": session_id})
+    });
+    prop_oneof![init, other]
+}
+
+/// A `result` line.
+pub fn cli_result() -> impl Strategy<Value = Value> {
+    let subtype = prop_oneof![
+        3 => Just("success"),
+        1 => Just("error_max_turns"),
+        1 => Just("error_during_execution"),
+    ];
+    (
+        subtype,
+        session_id(),
+        0u64..600_000,
+        1u32..50,
+        0.0f64..5.0,
+        cli_usage(),
+        prop::option::of(text()),
+    )
+        .prop_map(
+            |(subtype, session_id, duration_ms, num_turns, cost, usage, result)| {
+                json!({
+                    "type": "result",
+                    "subtype": subtype,
+                    "is_error": subtype == "error_during_execution",
+                    "duration_ms": duration_ms,
+                    "duration_api_ms": duration_ms / 2,
+                    "num_turns": num_turns,
+                    "session_id": session_id,
+                    "total_cost_usd": cost,
+                    "usage": usage,
+                    "permission_denials": [],
+                    "result": result
+                })
+            },
+        )
+}
+
+/// A `stream_event` line carrying a text delta.
+pub fn cli_stream_event() -> impl Strategy<Value = Value> {
+    ("[a-f0-9-]{36}", session_id(), 0u32..4, text()).prop_map(|(uuid, session_id, index, text)| {
+        json!({
+            "type": "stream_event",
+            "uuid": uuid,
+            "session_id": session_id,
+            "event": {
+                "type": "content_block_delta",
+                "index": index,
+                "delta": {"type": "text_delta", "text": text}
+            },
+            "parent_tool_use_id": null
+        })
+    })
+}
+
+/// Any message line the CLI prints.
+pub fn cli_message() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        4 => cli_assistant(),
+        2 => cli_user(),
+        1 => cli_system(),
+        1 => cli_result(),
+        2 => cli_stream_event(),
+    ]
+}
+
+/// A parsed message.
+pub fn message() -> impl Strategy<Value = Message> {
+    cli_message().prop_map(|raw| {
+        parse_message(raw)
+            .expect("generated CLI messages parse")
+            .expect("generated CLI messages are not skipped")
+    })
+}
+
+/// A typed content block.
+pub fn content_block() -> impl Strategy<Value = ContentBlock> {
+    prop_oneof![
+        text().prop_map(|text| ContentBlock::Text(TextBlock { text })),
+        (text(), safe_string()).prop_map(|(thinking, signature)| {
+            ContentBlock::Thinking(ThinkingBlock {
+                thinking,
+                signature,
+            })
+        }),
+        (tool_id(), tool_name(), tool_input())
+            .prop_map(|(id, name, input)| ContentBlock::ToolUse(ToolUseBlock { id, name, input })),
+        (
+            tool_id(),
+            prop::option::of(text()),
+            prop::option::of(any::<bool>())
+        )
+            .prop_map(|(tool_use_id, content, is_error)| {
+                ContentBlock::ToolResult(ToolResultBlock {
+                    tool_use_id,
+                    content: content.map(Value::from),
+                    is_error,
+                })
+            }),
+    ]
+}
+
+/// A permission mode.
+pub fn permission_mode() -> impl Strategy<Value = PermissionMode> {
+    prop_oneof![
+        Just(PermissionMode::Default),
+        Just(PermissionMode::AcceptEdits),
+        Just(PermissionMode::Plan),
+        Just(PermissionMode::BypassPermissions),
+    ]
+}
+
+/// Options that pass [`ClaudeAgentOptions::validate`], with a model, turn
+/// limit, tool lists, permission mode, and budget, each set or not.
+pub fn options() -> impl Strategy<Value = ClaudeAgentOptions> {
+    (
+        prop::option::of(model()),
+        prop::option::of(1u32..100),
+        prop::collection::vec(0u8..3, BuiltinTool::ALL.len()),
+        prop::collection::vec("mcp__[a-z][a-z0-9_-]{0,10}__[a-z][a-z0-9_]{0,15}", 0..3),
+        permission_mode(),
+        prop::option::of(0.01f64..100.0),
+    )
+        .prop_map(|(model, max_turns, builtins, mcp_tools, mode, budget)| {
+            let tools = |list: u8| {
+                BuiltinTool::ALL
+                    .iter()
+                    .zip(&builtins)
+                    .filter(move |(_, choice)| **choice == list)
+                    .map(|(tool, _)| tool.as_str().to_string())
+            };
+            let mut options = ClaudeAgentOptions::new()
+                .with_permission_mode(mode)
+                .with_allowed_tools(tools(1).chain(mcp_tools));
+            options.model = model;
+            options.max_turns = max_turns;
+            options.disallowed_tools = tools(2).collect();
+            options.max_budget_usd = budget;
+            options
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn prop_cli_messages_parse(raw in cli_message()) {
+            let parsed = parse_message(raw.clone());
+            prop_assert!(matches!(parsed, Ok(Some(_))), "{} -> {:?}", raw, parsed);
+        }
+
+        #[test]
+        fn prop_message_types_match_wire(message in message()) {
+            let json = serde_json::to_value(&message).unwrap();
+            prop_assert!(json.get("type").is_some());
+        }
+
+        #[test]
+        fn prop_content_blocks_roundtrip(block in content_block()) {
+            let json = serde_json::to_string(&block).unwrap();
+            let back: ContentBlock = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        }
+
+        #[test]
+        fn prop_options_are_valid(options in options()) {
+            prop_assert_eq!(options.validate(), Ok(()));
+        }
+    }
+}
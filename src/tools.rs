@@ -0,0 +1,323 @@
+//! A tool trait for plugging existing tools into SDK MCP servers.
+//!
+//! [`Tool`] is the shape most Rust agent frameworks give their tools: a
+//! name, a description, a JSON Schema for the arguments, and an async call.
+//! Anything implementing it converts into an [`SdkMcpTool`] with
+//! `SdkMcpTool::from`, so a tool written for another framework is wrapped
+//! once rather than rewritten against [`SdkMcpTool::new`].
+//!
+//! Frameworks also describe tools as OpenAI-style function definitions,
+//! `{"name", "description", "parameters"}`, optionally wrapped in
+//! `{"type": "function", "function": ...}`. rig's `ToolDefinition`,
+//! async-openai's `FunctionObject`, and langchain-rust's tool specs all
+//! serialize to that shape, and [`FunctionTool`] pairs one with a handler.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use async_trait::async_trait;
+//! use claude_agents_sdk::mcp::{SdkMcpServer, SdkMcpTool, ToolOutcome, ToolResult};
+//! use claude_agents_sdk::tools::Tool;
+//! use serde_json::{json, Value};
+//!
+//! struct Echo;
+//!
+//! #[async_trait]
+//! impl Tool for Echo {
+//!     fn name(&self) -> String {
+//!         "echo".to_string()
+//!     }
+//!
+//!     fn description(&self) -> String {
+//!         "Repeat the input".to_string()
+//!     }
+//!
+//!     fn schema(&self) -> Value {
+//!         json!({"type": "object", "properties": {"text": {"type": "string"}}})
+//!     }
+//!
+//!     async fn call(&self, input: Value) -> ToolOutcome {
+//!         Ok(ToolResult::text(input["text"].as_str().unwrap_or_default()))
+//!     }
+//! }
+//!
+//! let server = SdkMcpServer::new("utils", "1.0", vec![SdkMcpTool::from(Echo)]);
+//! ```
+//!
+//! A rig tool wraps the same way, through its definition:
+//!
+//! ```rust,ignore
+//! let definition = weather.definition(String::new()).await;
+//! let tool = FunctionTool::new(&definition, move |input| {
+//!     let weather = weather.clone();
+//!     async move {
+//!         let args = serde_json::from_value(input).map_err(|e| e.to_string())?;
+//!         weather.call(args).await.map_err(|e| e.to_string())
+//!     }
+//! })?;
+//! ```
+
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::{ClaudeSDKError, Result};
+use crate::mcp::{SdkMcpTool, ToolInputSchema, ToolOutcome, ToolResult};
+
+/// A tool the agent can call.
+#[async_trait]
+pub trait Tool: Send + Sync + 'static {
+    /// Name the model calls the tool by.
+    fn name(&self) -> String;
+
+    /// What the tool does, for the model.
+    fn description(&self) -> String;
+
+    /// JSON Schema of the tool's arguments.
+    fn schema(&self) -> Value;
+
+    /// Run the tool.
+    async fn call(&self, input: Value) -> ToolOutcome;
+}
+
+#[async_trait]
+impl<T: Tool + ?Sized> Tool for Arc<T> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn description(&self) -> String {
+        (**self).description()
+    }
+
+    fn schema(&self) -> Value {
+        (**self).schema()
+    }
+
+    async fn call(&self, input: Value) -> ToolOutcome {
+        (**self).call(input).await
+    }
+}
+
+#[async_trait]
+impl<T: Tool + ?Sized> Tool for Box<T> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn description(&self) -> String {
+        (**self).description()
+    }
+
+    fn schema(&self) -> Value {
+        (**self).schema()
+    }
+
+    async fn call(&self, input: Value) -> ToolOutcome {
+        (**self).call(input).await
+    }
+}
+
+impl<T: Tool> From<T> for SdkMcpTool {
+    /// Wrap the tool. A schema that isn't an object schema becomes one
+    /// accepting any arguments.
+    fn from(tool: T) -> Self {
+        let input_schema =
+            serde_json::from_value(tool.schema()).unwrap_or_else(|_| ToolInputSchema::object());
+        let tool = Arc::new(tool);
+        SdkMcpTool::new(
+            tool.name(),
+            tool.description(),
+            input_schema,
+            move |input| {
+                let tool = Arc::clone(&tool);
+                async move { tool.call(input).await }
+            },
+        )
+    }
+}
+
+type Handler =
+    Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = ToolOutcome> + Send>> + Send + Sync>;
+
+/// A tool from an OpenAI-style function definition and a handler.
+///
+/// The handler returns any serializable value or displayable error, as
+/// framework tools do. Strings become the text of the result, other values
+/// their JSON, and errors a result with `is_error` set, so the model sees
+/// the failure rather than the call failing.
+#[derive(Clone)]
+pub struct FunctionTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    handler: Handler,
+}
+
+impl FunctionTool {
+    /// Create a tool from a definition serializing to `{"name",
+    /// "description", "parameters"}` or `{"type": "function", "function":
+    /// {...}}`.
+    pub fn new<D, F, Fut, T, E>(definition: &D, handler: F) -> Result<Self>
+    where
+        D: Serialize + ?Sized,
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Display,
+    {
+        let mut definition = serde_json::to_value(definition).map_err(|e| {
+            ClaudeSDKError::configuration(format!("Invalid function definition: {}", e))
+        })?;
+        if definition.get("type").and_then(Value::as_str) == Some("function") {
+            definition = definition["function"].take();
+        }
+        let name = definition["name"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| ClaudeSDKError::configuration("Function definition has no name"))?
+            .to_string();
+        let handler = Arc::new(handler);
+        Ok(Self {
+            name,
+            description: definition["description"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            parameters: definition["parameters"].take(),
+            handler: Arc::new(move |input| {
+                let future = handler(input);
+                Box::pin(async move { Ok(to_result(future.await)) })
+            }),
+        })
+    }
+}
+
+impl std::fmt::Debug for FunctionTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionTool")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("parameters", &self.parameters)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Tool for FunctionTool {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn schema(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    async fn call(&self, input: Value) -> ToolOutcome {
+        (self.handler)(input).await
+    }
+}
+
+fn to_result<T: Serialize, E: Display>(output: std::result::Result<T, E>) -> ToolResult {
+    match output.map(|value| serde_json::to_value(value)) {
+        Ok(Ok(Value::String(text))) => ToolResult::text(text),
+        Ok(Ok(value)) => ToolResult::text(value.to_string()),
+        Ok(Err(e)) => ToolResult::error(format!("Failed to serialize tool output: {}", e)),
+        Err(e) => ToolResult::error(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{SdkMcpServer, ToolContent};
+    use serde_json::json;
+
+    fn text(outcome: ToolOutcome) -> (String, bool) {
+        let result = outcome.unwrap();
+        let ToolContent::Text { text } = &result.content[0] else {
+            panic!("not text: {:?}", result.content);
+        };
+        (text.clone(), result.is_error.unwrap_or(false))
+    }
+
+    fn divide() -> FunctionTool {
+        FunctionTool::new(
+            &json!({
+                "type": "function",
+                "function": {
+                    "name": "divide",
+                    "description": "Divide a by b",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {"a": {"type": "number"}, "b": {"type": "number"}},
+                        "required": ["a", "b"]
+                    }
+                }
+            }),
+            |input| async move {
+                let b = input["b"].as_f64().unwrap_or(0.0);
+                if b == 0.0 {
+                    return Err("division by zero");
+                }
+                Ok(json!({"quotient": input["a"].as_f64().unwrap_or(0.0) / b}))
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_function_tool_converts_outputs() {
+        let tool = divide();
+        assert_eq!(tool.name(), "divide");
+        assert_eq!(
+            text(tool.call(json!({"a": 6, "b": 3})).await),
+            ("{\"quotient\":2.0}".to_string(), false)
+        );
+        assert_eq!(
+            text(tool.call(json!({"a": 1, "b": 0})).await),
+            ("division by zero".to_string(), true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_into_sdk_tool() {
+        let tool = SdkMcpTool::from(Arc::new(divide()));
+        assert_eq!(tool.name, "divide");
+        assert_eq!(tool.description, "Divide a by b");
+        assert_eq!(tool.input_schema.required, vec!["a", "b"]);
+
+        let server = SdkMcpServer::new("math", "1.0", vec![tool]);
+        let response = server
+            .handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": {"name": "divide", "arguments": {"a": 1, "b": 4}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "{\"quotient\":0.25}"
+        );
+    }
+
+    #[test]
+    fn test_definition_requires_name() {
+        let error = FunctionTool::new(&json!({"description": "nameless"}), |_| async {
+            Ok::<_, String>("")
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("no name"));
+    }
+}
@@ -0,0 +1,229 @@
+//! A query's response, one assistant turn at a time.
+//!
+//! Each time the model responds, the CLI streams its content blocks as
+//! assistant messages, runs the tools it called, and replies with their
+//! results before the model responds again. An [`AssistantTurn`] groups one
+//! such round, so
+//! [`ClaudeClient::receive_turn`](crate::ClaudeClient::receive_turn) can
+//! hand a UI or an approval step the response as it progresses instead of
+//! all at once.
+
+use std::collections::HashSet;
+
+use crate::types::{
+    AssistantMessage, ContentBlock, Message, ResultMessage, ToolResultBlock, ToolUseBlock,
+    UserMessageContent,
+};
+
+/// One response of the model, with the tool calls it made and their
+/// results.
+#[derive(Debug, Clone, Default)]
+pub struct AssistantTurn {
+    /// The messages of the turn, in order: the assistant's, the tool
+    /// results, and any from subagents or the CLI. The result is kept in
+    /// [`result`](Self::result).
+    pub messages: Vec<Message>,
+    /// The query's result, if the turn ended it.
+    pub result: Option<ResultMessage>,
+}
+
+impl AssistantTurn {
+    /// Whether the turn ended the query.
+    pub fn is_final(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// The assistant's messages, not counting subagents'.
+    pub fn assistant_messages(&self) -> Vec<&AssistantMessage> {
+        self.messages
+            .iter()
+            .filter_map(|message| match message.typed() {
+                Message::Assistant(assistant) if assistant.parent_tool_use_id.is_none() => {
+                    Some(assistant)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The assistant's text.
+    pub fn text(&self) -> String {
+        self.assistant_messages()
+            .iter()
+            .map(|assistant| assistant.text())
+            .collect()
+    }
+
+    /// The assistant's tool calls paired with their results; the result is
+    /// `None` for a call that never got one, such as one that was
+    /// interrupted.
+    pub fn tool_calls(&self) -> Vec<(&ToolUseBlock, Option<&ToolResultBlock>)> {
+        let results: Vec<&ToolResultBlock> = self
+            .messages
+            .iter()
+            .filter_map(|message| match message.typed() {
+                Message::User(user) if user.parent_tool_use_id.is_none() => Some(&user.content),
+                _ => None,
+            })
+            .flat_map(|content| match content {
+                UserMessageContent::Blocks(blocks) => blocks.as_slice(),
+                UserMessageContent::Text(_) => &[],
+            })
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult(result) => Some(result),
+                _ => None,
+            })
+            .collect();
+        self.assistant_messages()
+            .into_iter()
+            .flat_map(|assistant| assistant.tool_uses())
+            .map(|tool_use| {
+                let result = results
+                    .iter()
+                    .find(|result| result.tool_use_id == tool_use.id)
+                    .copied();
+                (tool_use, result)
+            })
+            .collect()
+    }
+}
+
+/// Groups a query's messages into turns.
+///
+/// A turn ends once every tool it called has a result, or at the query's
+/// result. Messages of subagents stay in the turn that started them.
+#[derive(Default)]
+pub(crate) struct TurnCollector {
+    turn: AssistantTurn,
+    pending: HashSet<String>,
+    called_tools: bool,
+}
+
+impl TurnCollector {
+    /// Add a message, returning the turn it completes.
+    pub(crate) fn push(&mut self, message: Message) -> Option<AssistantTurn> {
+        match message.typed() {
+            Message::Result(result) => {
+                self.turn.result = Some(result.clone());
+                return Some(self.take());
+            }
+            Message::Assistant(assistant) if assistant.parent_tool_use_id.is_none() => {
+                for tool_use in assistant.tool_uses() {
+                    self.called_tools = true;
+                    self.pending.insert(tool_use.id.clone());
+                }
+            }
+            Message::User(user) if user.parent_tool_use_id.is_none() => {
+                if let UserMessageContent::Blocks(blocks) = &user.content {
+                    for block in blocks {
+                        if let ContentBlock::ToolResult(result) = block {
+                            self.pending.remove(&result.tool_use_id);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        let tools_done = self.called_tools && self.pending.is_empty();
+        self.turn.messages.push(message);
+        tools_done.then(|| self.take())
+    }
+
+    /// Whether messages of an unfinished turn are held.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.turn.messages.is_empty()
+    }
+
+    fn take(&mut self) -> AssistantTurn {
+        self.pending.clear();
+        self.called_tools = false;
+        std::mem::take(&mut self.turn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_internal::message_parser::parse_message;
+    use serde_json::{json, Value};
+
+    fn message(raw: Value) -> Message {
+        parse_message(raw).unwrap().unwrap()
+    }
+
+    fn assistant(block: Value, parent: Option<&str>) -> Message {
+        message(json!({
+            "type": "assistant",
+            "message": {"model": "m", "content": [block]},
+            "parent_tool_use_id": parent
+        }))
+    }
+
+    fn tool_use(id: &str, name: &str) -> Value {
+        json!({"type": "tool_use", "id": id, "name": name, "input": {}})
+    }
+
+    fn tool_result(id: &str, parent: Option<&str>) -> Message {
+        message(json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": id, "content": "ok"}
+            ]},
+            "parent_tool_use_id": parent
+        }))
+    }
+
+    fn result() -> Message {
+        message(json!({
+            "type": "result", "subtype": "success", "duration_ms": 1, "duration_api_ms": 1,
+            "is_error": false, "num_turns": 2, "session_id": "s"
+        }))
+    }
+
+    #[test]
+    fn test_turn_ends_when_every_tool_has_a_result() {
+        let mut collector = TurnCollector::default();
+        let messages = [
+            assistant(json!({"type": "text", "text": "Checking."}), None),
+            assistant(tool_use("a", "Read"), None),
+            assistant(tool_use("b", "Task"), None),
+            tool_result("a", None),
+            // The subagent's own tool call doesn't hold the turn open.
+            assistant(tool_use("c", "Bash"), Some("b")),
+            tool_result("c", Some("b")),
+        ];
+        for message in messages {
+            assert!(collector.push(message).is_none());
+        }
+        let turn = collector.push(tool_result("b", None)).unwrap();
+        assert!(collector.is_empty());
+        assert!(!turn.is_final());
+        assert_eq!(turn.messages.len(), 7);
+        assert_eq!(turn.text(), "Checking.");
+        let calls: Vec<(&str, bool)> = turn
+            .tool_calls()
+            .iter()
+            .map(|(call, result)| (call.id.as_str(), result.is_some()))
+            .collect();
+        assert_eq!(calls, [("a", true), ("b", true)]);
+    }
+
+    #[test]
+    fn test_last_turn_carries_result() {
+        let mut collector = TurnCollector::default();
+        assert!(collector
+            .push(assistant(json!({"type": "text", "text": "Done."}), None))
+            .is_none());
+        assert!(!collector.is_empty());
+        let turn = collector.push(result()).unwrap();
+        assert!(turn.is_final());
+        assert_eq!(turn.text(), "Done.");
+        assert!(turn.tool_calls().is_empty());
+        assert_eq!(turn.result.unwrap().num_turns, 2);
+
+        // An interrupted tool call still ends with the result.
+        collector.push(assistant(tool_use("a", "Bash"), None));
+        let turn = collector.push(result()).unwrap();
+        assert!(turn.tool_calls()[0].1.is_none());
+    }
+}
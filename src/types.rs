@@ -4,16 +4,44 @@
 //! including messages, content blocks, options, hooks, and control protocol types.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 // ============================================================================
 // Permission Types
 // ============================================================================
 
+/// What to do when a line of CLI output cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseErrorPolicy {
+    /// Return the error. Output that is not valid JSON also ends the
+    /// message stream.
+    #[default]
+    Fail,
+    /// Yield a [`Message::ParseWarning`] and keep reading.
+    SkipAndReport,
+}
+
+/// What to do when the consumer falls behind and the message channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelOverflowPolicy {
+    /// Wait for the consumer. Control requests from the CLI, such as
+    /// permission checks and hooks, are not handled meanwhile.
+    #[default]
+    Block,
+    /// Hold partial message events back, dropping the oldest once
+    /// [`channel_capacity`](ClaudeAgentOptions::channel_capacity) of them are
+    /// waiting. Other messages still wait for the consumer.
+    DropOldestPartialEvents,
+    /// End the message stream with a [`Channel`](crate::ClaudeSDKError::Channel)
+    /// error.
+    Error,
+}
+
 /// Permission modes controlling how the CLI handles tool permissions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -521,6 +549,19 @@ pub enum HookInput {
     PermissionRequest(PermissionRequestHookInput),
 }
 
+impl HookInput {
+    /// Name of the tool the event is about, for tool events.
+    pub fn tool_name(&self) -> Option<&str> {
+        match self {
+            HookInput::PreToolUse(input) => Some(&input.tool_name),
+            HookInput::PostToolUse(input) => Some(&input.tool_name),
+            HookInput::PostToolUseFailure(input) => Some(&input.tool_name),
+            HookInput::PermissionRequest(input) => Some(&input.tool_name),
+            _ => None,
+        }
+    }
+}
+
 /// Hook-specific output for PreToolUse events.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -690,6 +731,141 @@ impl Default for HookOutput {
     }
 }
 
+impl HookOutput {
+    /// Combine the outputs of several callbacks for one event, taken in
+    /// order:
+    ///
+    /// - `continue: false` wins, with the first stop reason given.
+    /// - `suppressOutput: true` wins.
+    /// - A `"block"` decision wins, with its reason; otherwise the first
+    ///   decision and reason are kept.
+    /// - System messages and additional context are joined with newlines.
+    /// - The most restrictive permission decision wins (deny, then ask, then
+    ///   allow), with its reason.
+    /// - Other fields, such as an updated tool input, keep the first value.
+    ///
+    /// Async outputs are left out, unless every output is async, in which
+    /// case the first is returned.
+    pub fn merge(outputs: impl IntoIterator<Item = HookOutput>) -> HookOutput {
+        let mut first_async = None;
+        let mut merged: Option<SyncHookOutput> = None;
+        for output in outputs {
+            match output {
+                HookOutput::Async(output) => {
+                    first_async.get_or_insert(output);
+                }
+                HookOutput::Sync(output) => match merged.as_mut() {
+                    None => merged = Some(output),
+                    Some(merged) => merged.merge(output),
+                },
+            }
+        }
+        match (merged, first_async) {
+            (Some(merged), _) => HookOutput::Sync(merged),
+            (None, Some(output)) => HookOutput::Async(output),
+            (None, None) => HookOutput::default(),
+        }
+    }
+}
+
+impl SyncHookOutput {
+    fn merge(&mut self, other: SyncHookOutput) {
+        if other.continue_ == Some(false) && self.continue_ != Some(false) {
+            self.continue_ = Some(false);
+            self.stop_reason = other.stop_reason;
+        } else if self.continue_.is_none() {
+            self.continue_ = other.continue_;
+        }
+        self.suppress_output = match (self.suppress_output, other.suppress_output) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (current, other) => current.or(other),
+        };
+        let blocks = other.decision.as_deref() == Some("block");
+        if self.decision.is_none() || (blocks && self.decision.as_deref() != Some("block")) {
+            self.decision = other.decision;
+            self.reason = other.reason;
+        }
+        join_lines(&mut self.system_message, other.system_message);
+        match (&mut self.hook_specific_output, other.hook_specific_output) {
+            (Some(current), Some(other)) => current.merge(other),
+            (current @ None, other) => *current = other,
+            (Some(_), None) => {}
+        }
+    }
+}
+
+impl HookSpecificOutput {
+    /// Merge `other` into this output. Outputs for different events keep
+    /// this one.
+    fn merge(&mut self, other: HookSpecificOutput) {
+        fn rank(decision: Option<PermissionBehavior>) -> u8 {
+            match decision {
+                None => 0,
+                Some(PermissionBehavior::Allow) => 1,
+                Some(PermissionBehavior::Ask) => 2,
+                Some(PermissionBehavior::Deny) => 3,
+            }
+        }
+
+        match (self, other) {
+            (HookSpecificOutput::PreToolUse(current), HookSpecificOutput::PreToolUse(other)) => {
+                if rank(other.permission_decision) > rank(current.permission_decision) {
+                    current.permission_decision = other.permission_decision;
+                    current.permission_decision_reason = other.permission_decision_reason;
+                }
+                if current.updated_input.is_none() {
+                    current.updated_input = other.updated_input;
+                }
+                join_lines(&mut current.additional_context, other.additional_context);
+            }
+            (HookSpecificOutput::PostToolUse(current), HookSpecificOutput::PostToolUse(other)) => {
+                if current.updated_mcp_tool_output.is_none() {
+                    current.updated_mcp_tool_output = other.updated_mcp_tool_output;
+                }
+                join_lines(&mut current.additional_context, other.additional_context);
+            }
+            (
+                HookSpecificOutput::PostToolUseFailure(current),
+                HookSpecificOutput::PostToolUseFailure(other),
+            ) => join_lines(&mut current.additional_context, other.additional_context),
+            (
+                HookSpecificOutput::UserPromptSubmit(current),
+                HookSpecificOutput::UserPromptSubmit(other),
+            ) => join_lines(&mut current.additional_context, other.additional_context),
+            (
+                HookSpecificOutput::Notification(current),
+                HookSpecificOutput::Notification(other),
+            ) => join_lines(&mut current.additional_context, other.additional_context),
+            (
+                HookSpecificOutput::SubagentStart(current),
+                HookSpecificOutput::SubagentStart(other),
+            ) => join_lines(&mut current.additional_context, other.additional_context),
+            (
+                HookSpecificOutput::PermissionRequest(current),
+                HookSpecificOutput::PermissionRequest(other),
+            ) => {
+                let denies = |decision: &serde_json::Value| decision["behavior"] == "deny";
+                if denies(&other.decision) && !denies(&current.decision) {
+                    current.decision = other.decision;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Append `other` to `current` on a new line.
+fn join_lines(current: &mut Option<String>, other: Option<String>) {
+    match (current.as_mut(), other) {
+        (Some(current), Some(other)) => {
+            current.push('\n');
+            current.push_str(&other);
+        }
+        (None, other) => *current = other,
+        (Some(_), None) => {}
+    }
+}
+
 /// Context for hook callbacks.
 #[derive(Debug, Clone, Default)]
 pub struct HookContext {
@@ -745,6 +921,42 @@ impl std::fmt::Debug for HookMatcher {
     }
 }
 
+impl HookMatcher {
+    /// Match tools whose name matches `pattern`, such as `"Bash"` or
+    /// `"Write|Edit"`. An empty pattern matches everything.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self {
+            matcher: (!pattern.is_empty()).then_some(pattern),
+            ..Self::default()
+        }
+    }
+
+    /// Match every tool, and events that have no tool.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Add a callback.
+    pub fn with_callback<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(HookInput, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HookOutput> + Send + 'static,
+    {
+        self.hooks
+            .push(Arc::new(move |input, tool_use_id, context| {
+                Box::pin(callback(input, tool_use_id, context))
+            }));
+        self
+    }
+
+    /// Set the timeout in seconds.
+    pub fn with_timeout(mut self, secs: f64) -> Self {
+        self.timeout = Some(secs);
+        self
+    }
+}
+
 // ============================================================================
 // MCP Server Configuration
 // ============================================================================
@@ -831,6 +1043,16 @@ pub struct SdkPluginConfig {
     pub path: String,
 }
 
+impl SdkPluginConfig {
+    /// A plugin loaded from a local directory.
+    pub fn local(path: impl Into<String>) -> Self {
+        Self {
+            plugin_type: "local".to_string(),
+            path: path.into(),
+        }
+    }
+}
+
 // ============================================================================
 // Sandbox Configuration
 // ============================================================================
@@ -898,6 +1120,73 @@ fn default_true() -> bool {
     true
 }
 
+/// HTTP proxy settings.
+///
+/// Applied to the CLI as the `HTTPS_PROXY`, `HTTP_PROXY`, and `NO_PROXY`
+/// environment variables, in both upper and lower case since tools differ
+/// in which they read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy for HTTPS requests, e.g. `http://proxy.corp:3128`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_proxy: Option<String>,
+    /// Proxy for plain HTTP requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    /// Hosts, domains, or CIDR ranges that bypass the proxy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Use `url` for both HTTPS and HTTP requests.
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self {
+            https_proxy: Some(url.clone()),
+            http_proxy: Some(url),
+            no_proxy: Vec::new(),
+        }
+    }
+
+    /// Set the HTTPS proxy.
+    pub fn with_https_proxy(mut self, url: impl Into<String>) -> Self {
+        self.https_proxy = Some(url.into());
+        self
+    }
+
+    /// Set the HTTP proxy.
+    pub fn with_http_proxy(mut self, url: impl Into<String>) -> Self {
+        self.http_proxy = Some(url.into());
+        self
+    }
+
+    /// Bypass the proxy for a host, domain, or CIDR range.
+    pub fn with_no_proxy(mut self, host: impl Into<String>) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    /// The environment variables for these settings.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let no_proxy = (!self.no_proxy.is_empty()).then(|| self.no_proxy.join(","));
+        [
+            ("HTTPS_PROXY", &self.https_proxy),
+            ("HTTP_PROXY", &self.http_proxy),
+            ("NO_PROXY", &no_proxy),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| (name, value)))
+        .flat_map(|(name, value)| {
+            [
+                (name.to_string(), value.clone()),
+                (name.to_ascii_lowercase(), value.clone()),
+            ]
+        })
+        .collect()
+    }
+}
+
 // ============================================================================
 // Content Block Types
 // ============================================================================
@@ -942,6 +1231,69 @@ pub struct ToolResultBlock {
     pub is_error: Option<bool>,
 }
 
+impl ToolResultBlock {
+    /// The result content as text.
+    ///
+    /// String content is returned as is and the text of text blocks is
+    /// joined with newlines; other content is rendered as JSON.
+    pub fn text(&self) -> String {
+        match &self.content {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(serde_json::Value::Array(blocks))
+                if blocks
+                    .iter()
+                    .all(|b| b["type"] == "text" && b["text"].is_string()) =>
+            {
+                blocks
+                    .iter()
+                    .filter_map(|b| b["text"].as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Some(other) => other.to_string(),
+        }
+    }
+}
+
+/// Content too large to keep in memory, stored in a file.
+///
+/// Produced in place of a content block when
+/// [`ClaudeAgentOptions::spill_dir`] is set and a message from the CLI is
+/// longer than [`ClaudeAgentOptions::max_buffer_size`]. The file is deleted
+/// when the last block parsed from the message is dropped, or when the
+/// client disconnects if the message was never parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LargePayloadBlock {
+    /// File holding the complete message.
+    pub path: PathBuf,
+    /// JSON pointer to the content within the message.
+    pub pointer: String,
+    /// Size of the content in bytes.
+    pub len: u64,
+    /// Keeps the file while the block exists.
+    #[serde(skip)]
+    pub(crate) file: crate::_internal::spill::SpillHold,
+}
+
+impl LargePayloadBlock {
+    /// Read the content from disk.
+    pub fn load(&self) -> crate::errors::Result<serde_json::Value> {
+        crate::_internal::spill::load_payload(&self.path, &self.pointer)
+    }
+
+    /// Read the content from disk as a content block.
+    ///
+    /// Text content becomes a [`TextBlock`]. Returns `Ok(None)` for unknown
+    /// block types.
+    pub fn load_block(&self) -> crate::errors::Result<Option<ContentBlock>> {
+        match self.load()? {
+            serde_json::Value::String(text) => Ok(Some(ContentBlock::Text(TextBlock { text }))),
+            block => crate::_internal::message_parser::parse_content_block(&block),
+        }
+    }
+}
+
 /// Content block union type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -958,6 +1310,9 @@ pub enum ContentBlock {
     /// Tool result block.
     #[serde(rename = "tool_result")]
     ToolResult(ToolResultBlock),
+    /// Content spilled to disk.
+    #[serde(rename = "large_payload")]
+    LargePayload(LargePayloadBlock),
 }
 
 impl ContentBlock {
@@ -1049,6 +1404,16 @@ pub struct AssistantMessage {
     /// Error if the message failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<AssistantMessageError>,
+    /// Token usage of the API call that produced this message.
+    ///
+    /// The CLI splits one API response into a message per content block, each
+    /// carrying the same usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<serde_json::Value>,
+    /// What the API said about the limit, if the message is a
+    /// [`RateLimit`](AssistantMessageError::RateLimit) error.
+    #[serde(skip)]
+    pub rate_limit: Option<Box<crate::errors::RateLimitInfo>>,
 }
 
 impl AssistantMessage {
@@ -1109,6 +1474,125 @@ pub struct ResultMessage {
     /// Structured output if output_format was specified.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub structured_output: Option<serde_json::Value>,
+    /// Tool calls the permission system denied during the turn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permission_denials: Vec<PermissionDenial>,
+    /// Latency and throughput measured by the SDK for this turn.
+    #[serde(skip)]
+    pub stats: Option<Box<crate::stats::TurnStats>>,
+    /// What the API said about the limit, if the query failed on a rate
+    /// limit.
+    #[serde(skip)]
+    pub rate_limit: Option<Box<crate::errors::RateLimitInfo>>,
+}
+
+impl ResultMessage {
+    /// The typed [`subtype`](Self::subtype).
+    pub fn kind(&self) -> ResultSubtype {
+        ResultSubtype::from(self.subtype.as_str())
+    }
+
+    /// Whether the query completed without error.
+    pub fn is_success(&self) -> bool {
+        self.kind() == ResultSubtype::Success && !self.is_error
+    }
+
+    /// Whether the query stopped at
+    /// [`max_turns`](crate::ClaudeAgentOptions::max_turns).
+    pub fn hit_max_turns(&self) -> bool {
+        self.kind() == ResultSubtype::ErrorMaxTurns
+    }
+
+    /// Whether the query stopped at
+    /// [`max_budget_usd`](crate::ClaudeAgentOptions::max_budget_usd).
+    pub fn hit_max_budget(&self) -> bool {
+        self.kind() == ResultSubtype::ErrorMaxBudgetUsd
+    }
+
+    /// Whether the query failed with an error while running.
+    pub fn failed_during_execution(&self) -> bool {
+        self.kind() == ResultSubtype::ErrorDuringExecution
+    }
+}
+
+/// How a query ended, from a result's `subtype`.
+///
+/// Unknown subtypes are kept as [`Other`](Self::Other), so results from
+/// newer CLI versions still parse.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::ResultSubtype;
+///
+/// assert_eq!(ResultSubtype::from("error_max_turns"), ResultSubtype::ErrorMaxTurns);
+/// assert_eq!(ResultSubtype::ErrorMaxTurns.as_str(), "error_max_turns");
+/// assert_eq!(
+///     ResultSubtype::from("error_new_limit"),
+///     ResultSubtype::Other("error_new_limit".into())
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResultSubtype {
+    /// The query completed (`success`).
+    Success,
+    /// The query reached its turn limit (`error_max_turns`).
+    ErrorMaxTurns,
+    /// The query reached its budget (`error_max_budget_usd`).
+    ErrorMaxBudgetUsd,
+    /// The query failed while running (`error_during_execution`).
+    ErrorDuringExecution,
+    /// The model didn't produce valid structured output within the CLI's
+    /// retries (`error_max_structured_output_retries`).
+    ErrorMaxStructuredOutputRetries,
+    /// Any other subtype, as reported.
+    Other(String),
+}
+
+impl ResultSubtype {
+    /// The subtype as the CLI reports it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Success => "success",
+            Self::ErrorMaxTurns => "error_max_turns",
+            Self::ErrorMaxBudgetUsd => "error_max_budget_usd",
+            Self::ErrorDuringExecution => "error_during_execution",
+            Self::ErrorMaxStructuredOutputRetries => "error_max_structured_output_retries",
+            Self::Other(subtype) => subtype,
+        }
+    }
+}
+
+impl std::fmt::Display for ResultSubtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for ResultSubtype {
+    fn from(subtype: &str) -> Self {
+        match subtype {
+            "success" => Self::Success,
+            "error_max_turns" => Self::ErrorMaxTurns,
+            "error_max_budget_usd" => Self::ErrorMaxBudgetUsd,
+            "error_during_execution" => Self::ErrorDuringExecution,
+            "error_max_structured_output_retries" => Self::ErrorMaxStructuredOutputRetries,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// A tool call denied by the permission system, as reported in a result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionDenial {
+    /// Name of the denied tool.
+    pub tool_name: String,
+    /// ID of the denied tool use.
+    pub tool_use_id: String,
+    /// Input the tool was called with.
+    #[serde(default)]
+    pub tool_input: serde_json::Value,
 }
 
 /// Stream event for partial message updates.
@@ -1125,6 +1609,149 @@ pub struct StreamEvent {
     pub parent_tool_use_id: Option<String>,
 }
 
+/// A line of CLI output that could not be parsed and was skipped.
+///
+/// Only produced with [`ParseErrorPolicy::SkipAndReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// Why the line could not be parsed.
+    pub message: String,
+    /// The line, truncated around the error for long lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+    /// Byte offset of the error in the line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// JSON pointer to the offending value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl ParseWarning {
+    /// Build a warning from a JSON decode or message parse error.
+    pub(crate) fn from_error(error: &crate::errors::ClaudeSDKError) -> Self {
+        use crate::errors::ClaudeSDKError;
+        match error {
+            ClaudeSDKError::JSONDecode {
+                raw_data,
+                offset,
+                path,
+                ..
+            } => Self {
+                message: error.to_string(),
+                line: raw_data.clone(),
+                offset: *offset,
+                path: path.clone(),
+            },
+            ClaudeSDKError::MessageParse {
+                raw_message, path, ..
+            } => Self {
+                message: error.to_string(),
+                line: raw_message.as_ref().map(|raw| raw.to_string()),
+                offset: None,
+                path: path.clone(),
+            },
+            other => Self {
+                message: other.to_string(),
+                line: None,
+                offset: None,
+                path: None,
+            },
+        }
+    }
+}
+
+/// A message kept as raw JSON until it is first read.
+///
+/// Only produced with [`ClaudeAgentOptions::lazy_parse`].
+/// [`msg_type`](Self::msg_type) reads the `type` field without parsing;
+/// [`get`](Self::get) parses the message on first call and caches it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyMessage {
+    raw: serde_json::Value,
+    #[serde(skip)]
+    parsed: std::sync::OnceLock<std::result::Result<Box<Message>, LazyParseError>>,
+}
+
+/// A cached parse failure, rebuilt into an error on each access.
+#[derive(Debug, Clone)]
+struct LazyParseError {
+    message: String,
+    path: Option<String>,
+}
+
+impl LazyMessage {
+    /// Wrap a raw CLI message.
+    pub fn new(raw: serde_json::Value) -> Self {
+        Self {
+            raw,
+            parsed: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// The message's `type` field, such as `"assistant"`.
+    pub fn msg_type(&self) -> &str {
+        self.raw
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The message as received from the CLI.
+    pub fn raw(&self) -> &serde_json::Value {
+        &self.raw
+    }
+
+    /// Take the raw JSON, discarding any parsed message.
+    pub fn into_raw(self) -> serde_json::Value {
+        self.raw
+    }
+
+    /// Whether [`get`](Self::get) has run.
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    /// Parse the message, or return the message parsed earlier.
+    pub fn get(&self) -> crate::errors::Result<&Message> {
+        let parsed = self.parsed.get_or_init(|| {
+            Message::try_from(self.raw.clone())
+                .map(Box::new)
+                .map_err(|e| match e {
+                    crate::errors::ClaudeSDKError::MessageParse { message, path, .. } => {
+                        LazyParseError { message, path }
+                    }
+                    other => LazyParseError {
+                        message: other.to_string(),
+                        path: None,
+                    },
+                })
+        });
+        parsed.as_deref().map_err(|e| self.error(e))
+    }
+
+    /// Parse the message, consuming the wrapper.
+    pub fn parse(self) -> crate::errors::Result<Message> {
+        match self.parsed.into_inner() {
+            Some(Ok(message)) => Ok(*message),
+            Some(Err(e)) => Err(crate::errors::ClaudeSDKError::MessageParse {
+                message: e.message,
+                raw_message: Some(self.raw),
+                path: e.path,
+            }),
+            None => Message::try_from(self.raw),
+        }
+    }
+
+    fn error(&self, e: &LazyParseError) -> crate::errors::ClaudeSDKError {
+        crate::errors::ClaudeSDKError::MessageParse {
+            message: e.message.clone(),
+            raw_message: Some(self.raw.clone()),
+            path: e.path.clone(),
+        }
+    }
+}
+
 /// Message union type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -1144,84 +1771,759 @@ pub enum Message {
     /// Stream event.
     #[serde(rename = "stream_event")]
     StreamEvent(StreamEvent),
+    /// A skipped line of unparsable output.
+    #[serde(rename = "parse_warning")]
+    ParseWarning(ParseWarning),
+    /// A message not parsed yet. Only produced with
+    /// [`ClaudeAgentOptions::lazy_parse`].
+    #[serde(rename = "lazy")]
+    Lazy(LazyMessage),
 }
 
 impl Message {
     /// Check if this is a result message.
     pub fn is_result(&self) -> bool {
-        matches!(self, Message::Result(_))
+        match self {
+            Message::Lazy(lazy) => lazy.msg_type() == "result",
+            other => matches!(other, Message::Result(_)),
+        }
     }
 
     /// Check if this is an assistant message.
     pub fn is_assistant(&self) -> bool {
-        matches!(self, Message::Assistant(_))
+        match self {
+            Message::Lazy(lazy) => lazy.msg_type() == "assistant",
+            other => matches!(other, Message::Assistant(_)),
+        }
+    }
+
+    /// Check if this is a partial message event.
+    pub fn is_stream_event(&self) -> bool {
+        match self {
+            Message::Lazy(lazy) => lazy.msg_type() == "stream_event",
+            other => matches!(other, Message::StreamEvent(_)),
+        }
     }
 
     /// Get as assistant message if applicable.
+    ///
+    /// Parses a [`Message::Lazy`] message of type `assistant`.
     pub fn as_assistant(&self) -> Option<&AssistantMessage> {
         match self {
             Message::Assistant(msg) => Some(msg),
+            Message::Lazy(lazy) if lazy.msg_type() == "assistant" => {
+                lazy.get().ok().and_then(Message::as_assistant)
+            }
             _ => None,
         }
     }
 
     /// Get as result message if applicable.
+    ///
+    /// Parses a [`Message::Lazy`] message of type `result`.
     pub fn as_result(&self) -> Option<&ResultMessage> {
         match self {
             Message::Result(msg) => Some(msg),
+            Message::Lazy(lazy) if lazy.msg_type() == "result" => {
+                lazy.get().ok().and_then(Message::as_result)
+            }
             _ => None,
         }
     }
-}
 
-// ============================================================================
-// Agent Configuration
-// ============================================================================
+    /// This message, or the parsed form of a [`Message::Lazy`] one. A lazy
+    /// message that fails to parse is returned unchanged.
+    pub(crate) fn typed(&self) -> &Message {
+        match self {
+            Message::Lazy(lazy) => lazy.get().unwrap_or(self),
+            other => other,
+        }
+    }
 
-/// System prompt preset.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SystemPromptPreset {
-    /// Type (always "preset").
-    #[serde(rename = "type")]
-    pub preset_type: String,
-    /// Preset name.
-    pub preset: String,
-    /// Text to append.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub append: Option<String>,
-}
+    /// Parse a [`Message::Lazy`] message; other messages are returned as is.
+    pub fn into_parsed(self) -> crate::errors::Result<Message> {
+        match self {
+            Message::Lazy(lazy) => lazy.parse(),
+            other => Ok(other),
+        }
+    }
 
-/// Tools preset.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolsPreset {
-    /// Type (always "preset").
-    #[serde(rename = "type")]
-    pub preset_type: String,
-    /// Preset name.
-    pub preset: String,
-}
+    /// Serialize to canonical JSON, for golden and snapshot tests.
+    ///
+    /// Object keys are sorted at every level, optional fields holding
+    /// `null` are left out like absent ones, and the output is
+    /// pretty-printed, so equal messages always produce identical text.
+    /// [`ResultMessage::stats`] is not included.
+    pub fn to_canonical_json(&self) -> String {
+        let mut message = self.clone();
+        message.normalize_optional_fields();
+        let value = serde_json::to_value(&message).expect("messages serialize to JSON");
+        serde_json::to_string_pretty(&sort_keys(value)).expect("JSON values serialize")
+    }
 
-/// System prompt configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum SystemPromptConfig {
-    /// Plain text system prompt.
-    Text(String),
-    /// Preset configuration.
-    Preset(SystemPromptPreset),
-}
+    /// Parse a message written by [`to_canonical_json`](Self::to_canonical_json).
+    pub fn from_canonical_json(json: &str) -> crate::errors::Result<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            crate::errors::ClaudeSDKError::json_decode(format!(
+                "Invalid canonical message JSON: {}",
+                e
+            ))
+        })
+    }
 
-/// Tools configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum ToolsConfig {
-    /// List of tool names.
-    List(Vec<String>),
-    /// Preset configuration.
-    Preset(ToolsPreset),
+    /// Treat `Some(null)` optional values as absent.
+    fn normalize_optional_fields(&mut self) {
+        fn normalize(value: &mut Option<serde_json::Value>) {
+            if matches!(value, Some(serde_json::Value::Null)) {
+                *value = None;
+            }
+        }
+        fn normalize_blocks(blocks: &mut [ContentBlock]) {
+            for block in blocks {
+                if let ContentBlock::ToolResult(result) = block {
+                    normalize(&mut result.content);
+                }
+            }
+        }
+
+        match self {
+            Message::User(user) => {
+                if let UserMessageContent::Blocks(blocks) = &mut user.content {
+                    normalize_blocks(blocks);
+                }
+            }
+            Message::Assistant(assistant) => {
+                normalize(&mut assistant.usage);
+                normalize_blocks(&mut assistant.content);
+            }
+            Message::Result(result) => {
+                normalize(&mut result.usage);
+                normalize(&mut result.structured_output);
+            }
+            Message::System(_)
+            | Message::StreamEvent(_)
+            | Message::ParseWarning(_)
+            | Message::Lazy(_) => {}
+        }
+    }
+}
+
+/// Plain text, one block per line: text as is, tool calls as
+/// `name(input)`, and everything else as a bracketed summary.
+///
+/// For colored terminal output, see [`render::terminal`](crate::render::terminal).
+impl std::fmt::Display for ContentBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentBlock::Text(block) => f.write_str(&block.text),
+            ContentBlock::Thinking(block) => write!(f, "[thinking] {}", block.thinking),
+            ContentBlock::ToolUse(block) => write!(f, "{}({})", block.name, block.input),
+            ContentBlock::ToolResult(block) => {
+                let label = if block.is_error == Some(true) {
+                    "tool error"
+                } else {
+                    "tool result"
+                };
+                write!(f, "[{} {}] {}", label, block.tool_use_id, block.text())
+            }
+            ContentBlock::LargePayload(block) => write!(
+                f,
+                "[large payload: {} bytes in {}]",
+                block.len,
+                block.path.display()
+            ),
+        }
+    }
+}
+
+/// Plain text: user and assistant content via [`ContentBlock`]'s `Display`,
+/// and a one-line summary for other messages.
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn blocks(f: &mut std::fmt::Formatter<'_>, blocks: &[ContentBlock]) -> std::fmt::Result {
+            for (i, block) in blocks.iter().enumerate() {
+                if i > 0 {
+                    f.write_str("\n")?;
+                }
+                write!(f, "{}", block)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Message::User(user) => match &user.content {
+                UserMessageContent::Text(text) => f.write_str(text),
+                UserMessageContent::Blocks(content) => blocks(f, content),
+            },
+            Message::Assistant(assistant) => blocks(f, &assistant.content),
+            Message::System(system) => write!(f, "[system: {}]", system.subtype),
+            Message::Result(result) => {
+                write!(
+                    f,
+                    "[result: {}, {} turns, {} ms",
+                    result.subtype, result.num_turns, result.duration_ms
+                )?;
+                if let Some(cost) = result.total_cost_usd {
+                    write!(f, ", ${:.4}", cost)?;
+                }
+                f.write_str("]")?;
+                match &result.result {
+                    Some(text) => write!(f, " {}", text),
+                    None => Ok(()),
+                }
+            }
+            Message::StreamEvent(event) => write!(
+                f,
+                "[stream event: {}]",
+                event.event["type"].as_str().unwrap_or("unknown")
+            ),
+            Message::ParseWarning(warning) => write!(f, "[parse warning: {}]", warning.message),
+            Message::Lazy(lazy) => match lazy.get() {
+                Ok(message) => write!(f, "{}", message),
+                Err(e) => write!(f, "[unparsable {} message: {}]", lazy.msg_type(), e),
+            },
+        }
+    }
+}
+
+/// Parse a message in the CLI's stream-json format, as in a recorded session.
+///
+/// Unlike [`parse_message`](crate::_internal::message_parser::parse_message),
+/// unknown message types and control messages are errors, since there is no
+/// message to return. Messages serialized with serde use the SDK's own format;
+/// deserialize those with `serde_json` instead.
+impl TryFrom<serde_json::Value> for Message {
+    type Error = crate::errors::ClaudeSDKError;
+
+    fn try_from(value: serde_json::Value) -> crate::errors::Result<Self> {
+        let msg_type = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string();
+        crate::_internal::message_parser::parse_message(value)?.ok_or_else(|| {
+            crate::errors::ClaudeSDKError::message_parse(format!(
+                "Not a message: unknown type '{}'",
+                msg_type
+            ))
+        })
+    }
+}
+
+/// Parse one line of the CLI's stream-json output.
+///
+/// A trailing newline is ignored. Accepts the same messages as the
+/// `TryFrom<Value>` implementation.
+///
+/// # Examples
+///
+/// ```
+/// use claude_agents_sdk::Message;
+///
+/// let line = r#"{"type":"user","message":{"role":"user","content":"Hi"}}"#;
+/// let message: Message = line.parse().unwrap();
+/// assert_eq!(message.to_string(), "Hi");
+/// ```
+impl std::str::FromStr for Message {
+    type Err = crate::errors::ClaudeSDKError;
+
+    fn from_str(line: &str) -> crate::errors::Result<Self> {
+        let value =
+            crate::_internal::message_parser::decode_line(line.trim_end_matches(['\r', '\n']))?;
+        Message::try_from(value)
+    }
+}
+
+/// Sort object keys recursively.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+        }
+        other => other,
+    }
+}
+
+// ============================================================================
+// Agent Configuration
+// ============================================================================
+
+/// System prompt preset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemPromptPreset {
+    /// Type (always "preset").
+    #[serde(rename = "type")]
+    pub preset_type: String,
+    /// Preset name.
+    pub preset: String,
+    /// Text to append.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub append: Option<String>,
+}
+
+impl SystemPromptPreset {
+    /// The Claude Code system prompt.
+    pub fn claude_code() -> Self {
+        Self {
+            preset_type: "preset".to_string(),
+            preset: CLAUDE_CODE_PRESET.to_string(),
+            append: None,
+        }
+    }
+
+    /// Append text to the preset prompt.
+    pub fn with_append(mut self, text: impl Into<String>) -> Self {
+        self.append = Some(text.into());
+        self
+    }
+}
+
+/// Name of the Claude Code preset for system prompts and tools.
+pub const CLAUDE_CODE_PRESET: &str = "claude_code";
+
+/// Tools preset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolsPreset {
+    /// Type (always "preset").
+    #[serde(rename = "type")]
+    pub preset_type: String,
+    /// Preset name.
+    pub preset: String,
+}
+
+impl ToolsPreset {
+    /// The Claude Code tool set.
+    pub fn claude_code() -> Self {
+        Self {
+            preset_type: "preset".to_string(),
+            preset: CLAUDE_CODE_PRESET.to_string(),
+        }
+    }
+}
+
+/// System prompt configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SystemPromptConfig {
+    /// Plain text system prompt.
+    Text(String),
+    /// Preset configuration.
+    Preset(SystemPromptPreset),
+}
+
+impl SystemPromptConfig {
+    /// The Claude Code system prompt.
+    pub fn claude_code() -> Self {
+        Self::Preset(SystemPromptPreset::claude_code())
+    }
+
+    /// The Claude Code system prompt followed by `text`.
+    pub fn claude_code_with_append(text: impl Into<String>) -> Self {
+        Self::Preset(SystemPromptPreset::claude_code().with_append(text))
+    }
+
+    /// Add `text` to the end of the prompt, separated by a blank line.
+    ///
+    /// A text prompt gets `text` appended; a preset gets it added to its
+    /// `append` text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use claude_agents_sdk::SystemPromptConfig;
+    ///
+    /// let prompt = SystemPromptConfig::claude_code()
+    ///     .append("Prefer small commits.")
+    ///     .append("Never push.");
+    /// match prompt {
+    ///     SystemPromptConfig::Preset(preset) => assert_eq!(
+    ///         preset.append.as_deref(),
+    ///         Some("Prefer small commits.\n\nNever push.")
+    ///     ),
+    ///     SystemPromptConfig::Text(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn append(self, text: impl Into<String>) -> Self {
+        fn join(base: String, text: String) -> String {
+            if base.is_empty() {
+                text
+            } else {
+                format!("{}\n\n{}", base, text)
+            }
+        }
+
+        let text = text.into();
+        match self {
+            Self::Text(base) => Self::Text(join(base, text)),
+            Self::Preset(mut preset) => {
+                preset.append = Some(join(preset.append.unwrap_or_default(), text));
+                Self::Preset(preset)
+            }
+        }
+    }
+}
+
+impl From<String> for SystemPromptConfig {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for SystemPromptConfig {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<&String> for SystemPromptConfig {
+    fn from(text: &String) -> Self {
+        Self::Text(text.clone())
+    }
+}
+
+impl From<SystemPromptPreset> for SystemPromptConfig {
+    fn from(preset: SystemPromptPreset) -> Self {
+        Self::Preset(preset)
+    }
+}
+
+/// Tools configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolsConfig {
+    /// List of tool names.
+    List(Vec<String>),
+    /// Preset configuration.
+    Preset(ToolsPreset),
+}
+
+impl ToolsConfig {
+    /// The Claude Code tool set.
+    pub fn claude_code() -> Self {
+        Self::Preset(ToolsPreset::claude_code())
+    }
+}
+
+impl From<Vec<String>> for ToolsConfig {
+    fn from(tools: Vec<String>) -> Self {
+        Self::List(tools)
+    }
+}
+
+impl From<Vec<BuiltinTool>> for ToolsConfig {
+    fn from(tools: Vec<BuiltinTool>) -> Self {
+        Self::List(tools.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<ToolsPreset> for ToolsConfig {
+    fn from(preset: ToolsPreset) -> Self {
+        Self::Preset(preset)
+    }
+}
+
+/// A tool built into Claude Code.
+///
+/// Tool names are case-sensitive, so an allow rule for `"bash"` matches
+/// nothing. Use these variants, or parse names with [`str::parse`], which
+/// accepts any case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum BuiltinTool {
+    /// Launch a subagent.
+    Task,
+    /// Run a shell command.
+    Bash,
+    /// Read output from a background shell.
+    BashOutput,
+    /// Stop a background shell.
+    KillShell,
+    /// Read a file.
+    Read,
+    /// Write a file.
+    Write,
+    /// Edit part of a file.
+    Edit,
+    /// Edit a Jupyter notebook cell.
+    NotebookEdit,
+    /// Find files by glob pattern.
+    Glob,
+    /// Search file contents.
+    Grep,
+    /// Fetch a URL.
+    WebFetch,
+    /// Search the web.
+    WebSearch,
+    /// Update the todo list.
+    TodoWrite,
+    /// Leave plan mode.
+    ExitPlanMode,
+    /// List resources from MCP servers.
+    ListMcpResourcesTool,
+    /// Read an MCP resource.
+    ReadMcpResourceTool,
+    /// Run a slash command.
+    SlashCommand,
+    /// Use a skill.
+    Skill,
+    /// Ask the user a question.
+    AskUserQuestion,
+}
+
+impl BuiltinTool {
+    /// Every built-in tool.
+    pub const ALL: &'static [BuiltinTool] = &[
+        Self::Task,
+        Self::Bash,
+        Self::BashOutput,
+        Self::KillShell,
+        Self::Read,
+        Self::Write,
+        Self::Edit,
+        Self::NotebookEdit,
+        Self::Glob,
+        Self::Grep,
+        Self::WebFetch,
+        Self::WebSearch,
+        Self::TodoWrite,
+        Self::ExitPlanMode,
+        Self::ListMcpResourcesTool,
+        Self::ReadMcpResourceTool,
+        Self::SlashCommand,
+        Self::Skill,
+        Self::AskUserQuestion,
+    ];
+
+    /// The tool name as the CLI spells it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Task => "Task",
+            Self::Bash => "Bash",
+            Self::BashOutput => "BashOutput",
+            Self::KillShell => "KillShell",
+            Self::Read => "Read",
+            Self::Write => "Write",
+            Self::Edit => "Edit",
+            Self::NotebookEdit => "NotebookEdit",
+            Self::Glob => "Glob",
+            Self::Grep => "Grep",
+            Self::WebFetch => "WebFetch",
+            Self::WebSearch => "WebSearch",
+            Self::TodoWrite => "TodoWrite",
+            Self::ExitPlanMode => "ExitPlanMode",
+            Self::ListMcpResourcesTool => "ListMcpResourcesTool",
+            Self::ReadMcpResourceTool => "ReadMcpResourceTool",
+            Self::SlashCommand => "SlashCommand",
+            Self::Skill => "Skill",
+            Self::AskUserQuestion => "AskUserQuestion",
+        }
+    }
+}
+
+impl std::fmt::Display for BuiltinTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for BuiltinTool {
+    type Err = crate::errors::ClaudeSDKError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|tool| tool.as_str().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| {
+                crate::errors::ClaudeSDKError::configuration(format!(
+                    "Unknown built-in tool '{}'",
+                    name
+                ))
+            })
+    }
+}
+
+impl From<BuiltinTool> for String {
+    fn from(tool: BuiltinTool) -> Self {
+        tool.as_str().to_string()
+    }
+}
+
+/// A model, for [`ClaudeAgentOptions::with_model`],
+/// [`ClaudeClient::set_model`](crate::ClaudeClient::set_model), and
+/// [`AgentDefinition::model`].
+///
+/// Known models and aliases are variants, so a typo is a compile error and
+/// models can be matched on. Anything else, such as a dated snapshot or a
+/// Bedrock or Vertex ID, is [`Custom`](Self::Custom). Converts from and to
+/// strings, and serializes as the string the CLI expects.
+///
+/// # Examples
+///
+/// ```rust
+/// use claude_agents_sdk::{ClaudeAgentOptions, ModelAlias, ModelId};
+///
+/// let options = ClaudeAgentOptions::new()
+///     .with_model(ModelId::Sonnet45)
+///     .with_fallback_model(ModelId::Alias(ModelAlias::Haiku));
+/// assert_eq!(options.model.as_deref(), Some("claude-sonnet-4-5"));
+///
+/// assert_eq!(ModelId::from("opus"), ModelId::Alias(ModelAlias::Opus));
+/// assert_eq!(
+///     ModelId::from("claude-opus-4-5-20251101"),
+///     ModelId::Custom("claude-opus-4-5-20251101".into())
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ModelId {
+    /// Claude Opus 4.5 (`claude-opus-4-5`).
+    Opus45,
+    /// Claude Sonnet 4.5 (`claude-sonnet-4-5`).
+    Sonnet45,
+    /// Claude Haiku 4.5 (`claude-haiku-4-5`).
+    Haiku45,
+    /// A CLI alias that resolves to the current model of a family.
+    Alias(ModelAlias),
+    /// Any other model ID, passed to the CLI as is.
+    Custom(String),
+}
+
+impl ModelId {
+    /// The model ID as passed to the CLI.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Opus45 => "claude-opus-4-5",
+            Self::Sonnet45 => "claude-sonnet-4-5",
+            Self::Haiku45 => "claude-haiku-4-5",
+            Self::Alias(alias) => alias.as_str(),
+            Self::Custom(id) => id,
+        }
+    }
+}
+
+impl std::fmt::Display for ModelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for ModelId {
+    fn from(id: &str) -> Self {
+        match id {
+            "claude-opus-4-5" => Self::Opus45,
+            "claude-sonnet-4-5" => Self::Sonnet45,
+            "claude-haiku-4-5" => Self::Haiku45,
+            other => match ModelAlias::ALL.iter().find(|alias| alias.as_str() == other) {
+                Some(alias) => Self::Alias(*alias),
+                None => Self::Custom(other.to_string()),
+            },
+        }
+    }
+}
+
+impl From<String> for ModelId {
+    fn from(id: String) -> Self {
+        match Self::from(id.as_str()) {
+            Self::Custom(_) => Self::Custom(id),
+            known => known,
+        }
+    }
+}
+
+impl From<ModelAlias> for ModelId {
+    fn from(alias: ModelAlias) -> Self {
+        Self::Alias(alias)
+    }
+}
+
+impl From<AgentModel> for ModelId {
+    fn from(model: AgentModel) -> Self {
+        Self::Alias(match model {
+            AgentModel::Sonnet => ModelAlias::Sonnet,
+            AgentModel::Opus => ModelAlias::Opus,
+            AgentModel::Haiku => ModelAlias::Haiku,
+            AgentModel::Inherit => ModelAlias::Inherit,
+        })
+    }
+}
+
+impl From<ModelId> for String {
+    fn from(model: ModelId) -> Self {
+        match model {
+            ModelId::Custom(id) => id,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl Serialize for ModelId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// A model alias the CLI resolves to the current model of a family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ModelAlias {
+    /// The account's default model (`default`).
+    Default,
+    /// The latest Opus (`opus`).
+    Opus,
+    /// The latest Sonnet (`sonnet`).
+    Sonnet,
+    /// The latest Haiku (`haiku`).
+    Haiku,
+    /// Opus in plan mode and Sonnet otherwise (`opusplan`).
+    OpusPlan,
+    /// The parent's model, for subagents (`inherit`).
+    Inherit,
+}
+
+impl ModelAlias {
+    /// Every alias.
+    pub const ALL: &'static [ModelAlias] = &[
+        Self::Default,
+        Self::Opus,
+        Self::Sonnet,
+        Self::Haiku,
+        Self::OpusPlan,
+        Self::Inherit,
+    ];
+
+    /// The alias as passed to the CLI.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Opus => "opus",
+            Self::Sonnet => "sonnet",
+            Self::Haiku => "haiku",
+            Self::OpusPlan => "opusplan",
+            Self::Inherit => "inherit",
+        }
+    }
 }
 
 /// Agent model.
+///
+/// [`AgentDefinition::model`] takes a [`ModelId`]; these convert into it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AgentModel {
@@ -1247,7 +2549,15 @@ pub struct AgentDefinition {
     pub tools: Option<Vec<String>>,
     /// Model to use.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub model: Option<AgentModel>,
+    pub model: Option<ModelId>,
+    /// MCP servers the agent may use, by name. `None` gives it every server
+    /// of the session.
+    #[serde(rename = "mcpServers", skip_serializing_if = "Option::is_none")]
+    pub mcp_servers: Option<Vec<String>>,
+    /// Permission mode for the agent's tool calls. `None` uses the
+    /// session's.
+    #[serde(rename = "permissionMode", skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
 }
 
 /// Setting source.
@@ -1269,6 +2579,111 @@ pub enum SdkBeta {
     /// Extended context beta.
     #[serde(rename = "context-1m-2025-08-07")]
     Context1m,
+    /// A beta without a dedicated variant, by its header value.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl SdkBeta {
+    /// The beta header value passed to the CLI.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Context1m => "context-1m-2025-08-07",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for SdkBeta {
+    fn from(name: &str) -> Self {
+        match name {
+            "context-1m-2025-08-07" => Self::Context1m,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for SdkBeta {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+/// An extra argument passed to the CLI.
+///
+/// Unlike [`ClaudeAgentOptions::extra_args`], a list of these keeps its
+/// order and may repeat a flag. Names are given without the leading `--`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CliArg {
+    /// A flag without a value, as `--name`.
+    Flag {
+        /// Flag name.
+        name: String,
+    },
+    /// A flag with a value, as `--name value`.
+    Option {
+        /// Flag name.
+        name: String,
+        /// Flag value.
+        value: String,
+    },
+    /// A bare argument.
+    Positional {
+        /// The argument.
+        value: String,
+    },
+}
+
+impl CliArg {
+    /// A flag without a value.
+    pub fn flag(name: impl Into<String>) -> Self {
+        Self::Flag { name: name.into() }
+    }
+
+    /// A flag with a value.
+    pub fn option(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Option {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// A bare argument.
+    pub fn positional(value: impl Into<String>) -> Self {
+        Self::Positional {
+            value: value.into(),
+        }
+    }
+
+    /// Convert an [`extra_args`](ClaudeAgentOptions::extra_args) map,
+    /// sorted by name.
+    pub fn from_map(map: &HashMap<String, Option<String>>) -> Vec<Self> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by_key(|(name, _)| *name);
+        entries
+            .into_iter()
+            .map(|(name, value)| Self::from((name.clone(), value.clone())))
+            .collect()
+    }
+
+    /// The command-line words for this argument.
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            Self::Flag { name } => vec![format!("--{}", name)],
+            Self::Option { name, value } => vec![format!("--{}", name), value.clone()],
+            Self::Positional { value } => vec![value.clone()],
+        }
+    }
+}
+
+impl From<(String, Option<String>)> for CliArg {
+    fn from((name, value): (String, Option<String>)) -> Self {
+        match value {
+            Some(value) => Self::Option { name, value },
+            None => Self::Flag { name },
+        }
+    }
 }
 
 /// MCP servers configuration.
@@ -1326,6 +2741,29 @@ impl std::fmt::Display for Effort {
     }
 }
 
+/// What runs the agent loop.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// The CLI, as a subprocess or through a [`transport`](ClaudeAgentOptions::transport).
+    #[default]
+    Cli,
+    /// The SDK itself, calling the Anthropic API with this key, or with
+    /// `ANTHROPIC_API_KEY` if the key is empty. Requires the `api` feature.
+    ///
+    /// Only a subset of the CLI's features is available; see
+    /// `ApiTransport` in `_internal::transport`.
+    Api(String),
+}
+
+impl std::fmt::Debug for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cli => f.write_str("Cli"),
+            Self::Api(_) => write!(f, "Api({})", crate::redact::REDACTED),
+        }
+    }
+}
+
 /// Query options for Claude SDK.
 #[derive(Clone, Default)]
 pub struct ClaudeAgentOptions {
@@ -1355,30 +2793,59 @@ pub struct ClaudeAgentOptions {
     pub fallback_model: Option<String>,
     /// Beta features.
     pub betas: Vec<SdkBeta>,
+    /// CLI feature flags, passed as environment variables set to `1` or `0`.
+    /// Variables in [`env`](Self::env) take precedence.
+    pub feature_flags: BTreeMap<String, bool>,
     /// Permission prompt tool name.
     pub permission_prompt_tool_name: Option<String>,
     /// Working directory.
     pub cwd: Option<PathBuf>,
     /// Path to CLI executable.
     pub cli_path: Option<PathBuf>,
-    /// Settings string.
+    /// Settings as a JSON string or a path to a settings file.
     pub settings: Option<String>,
     /// Additional directories.
     pub add_dirs: Vec<PathBuf>,
-    /// Environment variables.
+    /// Environment variables. These override inherited variables.
     pub env: HashMap<String, String>,
-    /// Extra CLI arguments.
+    /// Start the CLI without this process's environment.
+    ///
+    /// Only `PATH`, `HOME`, and the platform's temp and system directory
+    /// variables are passed through, so credentials such as
+    /// `ANTHROPIC_API_KEY` must be set in [`env`](Self::env).
+    pub clear_env: bool,
+    /// HTTP proxy for the CLI. Variables in [`env`](Self::env) take precedence.
+    pub proxy: Option<ProxyConfig>,
+    /// Extra CLI arguments, as flag names mapped to optional values.
     pub extra_args: HashMap<String, Option<String>>,
-    /// Maximum buffer size for stdout.
+    /// Extra CLI arguments in order, passed after
+    /// [`extra_args`](Self::extra_args).
+    pub cli_args: Vec<CliArg>,
+    /// Maximum buffer size for stdout, and the longest line of output held
+    /// in memory when [`spill_dir`](Self::spill_dir) is set.
     pub max_buffer_size: Option<usize>,
+    /// Directory for CLI messages longer than `max_buffer_size`.
+    pub spill_dir: Option<PathBuf>,
+    /// Connect without running [`validate`](Self::validate).
+    pub skip_validation: bool,
     /// Callback for stderr output.
     pub stderr: Option<Arc<dyn Fn(String) + Send + Sync>>,
+    /// Makes the transport to the CLI. Defaults to running the CLI as a
+    /// subprocess.
+    pub transport: Option<crate::_internal::transport::TransportFactory>,
+    /// What runs the agent loop. Ignored when a
+    /// [`transport`](Self::transport) is set.
+    pub backend: Backend,
     /// Tool permission callback.
     pub can_use_tool: Option<CanUseTool>,
     /// Hook configurations.
     pub hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
     /// User identifier.
     pub user: Option<String>,
+    /// Tags attributing the session's activity, such as a request ID,
+    /// tenant, or feature name. Recorded on tracing spans and wire log
+    /// entries; not sent to the CLI.
+    pub metadata: HashMap<String, String>,
     /// Include partial messages in stream.
     pub include_partial_messages: bool,
     /// Fork session when resuming.
@@ -1399,11 +2866,58 @@ pub struct ClaudeAgentOptions {
     pub effort: Option<Effort>,
     /// Output format for structured outputs.
     pub output_format: Option<serde_json::Value>,
+    /// What [`query_structured`](crate::query_structured) does with output
+    /// that doesn't deserialize.
+    pub structured_output_policy: crate::structured::StructuredOutputPolicy,
     /// Enable file checkpointing.
     pub enable_file_checkpointing: bool,
     /// Timeout in seconds for CLI operations (default: 300 = 5 minutes).
     /// Set to 0 to disable timeout.
     pub timeout_secs: Option<u64>,
+    /// Timeout for each control request, such as `initialize` or
+    /// `set_model`. Defaults to [`timeout_secs`](Self::timeout_secs); zero
+    /// waits forever.
+    pub control_timeout: Option<Duration>,
+    /// Recorder for SDK metrics. Defaults to [`crate::metrics::default_recorder`].
+    pub metrics: Option<Arc<dyn crate::metrics::MetricsRecorder>>,
+    /// Capture every line sent to and received from the CLI.
+    pub wire_log: Option<crate::wire_log::WireLog>,
+    /// Compact the conversation automatically once the context grows past
+    /// a threshold. Applies to [`ClaudeClient`](crate::ClaudeClient) sessions.
+    pub auto_compact: Option<crate::compaction::AutoCompactPolicy>,
+    /// Bus that receives typed SDK events.
+    pub event_bus: Option<crate::events::EventBus>,
+    /// Emit a [`FileDiff`](crate::FileDiff) on the event bus for each file
+    /// changed by the `Edit`, `MultiEdit`, or `Write` tools, read from a
+    /// `PostToolUse` hook the SDK registers.
+    pub file_diffs: bool,
+    /// Redacts secrets and personal data from logs, wire logs, and telemetry.
+    pub redactor: Option<crate::redact::Redactor>,
+    /// Time source for timeouts, pings, and latency measurements. Defaults
+    /// to [`TokioClock`](crate::clock::TokioClock).
+    pub clock: Option<Arc<dyn crate::clock::Clock>>,
+    /// What to do with unparsable CLI output.
+    pub parse_error_policy: ParseErrorPolicy,
+    /// Run the callbacks of all matchers for a hook event concurrently, at
+    /// most this many at a time, and merge their outputs with
+    /// [`HookOutput::merge`]. By default the CLI calls each callback in turn.
+    pub hook_parallelism: Option<usize>,
+    /// Capacity of the channels between the CLI and the consumer, in
+    /// messages. Defaults to 256.
+    pub channel_capacity: Option<usize>,
+    /// What to do when the consumer falls behind by a full channel.
+    pub overflow_policy: ChannelOverflowPolicy,
+    /// Deliver messages as [`Message::Lazy`], parsed only when read.
+    ///
+    /// Result messages are still parsed up front so they carry
+    /// [`ResultMessage::stats`]. Tool timings in those stats, file
+    /// checkpoints, and tool call events need typed messages and are not
+    /// recorded for lazy ones.
+    pub lazy_parse: bool,
+    /// In-process SDK MCP servers, keyed by server name.
+    #[cfg(feature = "mcp")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mcp")))]
+    pub sdk_mcp_servers: HashMap<String, Arc<crate::mcp::SdkMcpServer>>,
 }
 
 impl std::fmt::Debug for ClaudeAgentOptions {
@@ -1428,6 +2942,28 @@ impl std::fmt::Debug for ClaudeAgentOptions {
                 &self.hooks.as_ref().map(|h| format!("{} events", h.len())),
             )
             .field("stderr", &self.stderr.as_ref().map(|_| "<callback>"))
+            .field("transport", &self.transport.as_ref().map(|_| "<factory>"))
+            .field("backend", &self.backend)
+            .field("metrics", &self.metrics.as_ref().map(|_| "<recorder>"))
+            .field("wire_log", &self.wire_log)
+            .field("auto_compact", &self.auto_compact)
+            .field("event_bus", &self.event_bus.as_ref().map(|_| "<bus>"))
+            .field("file_diffs", &self.file_diffs)
+            .field("redactor", &self.redactor.as_ref().map(|_| "<redactor>"))
+            .field("clock", &self.clock.as_ref().map(|_| "<clock>"))
+            .field("parse_error_policy", &self.parse_error_policy)
+            .field("structured_output_policy", &self.structured_output_policy)
+            .field("hook_parallelism", &self.hook_parallelism)
+            .field("channel_capacity", &self.channel_capacity)
+            .field("overflow_policy", &self.overflow_policy)
+            .field("lazy_parse", &self.lazy_parse)
+            .field("spill_dir", &self.spill_dir)
+            .field("clear_env", &self.clear_env)
+            .field("cli_args", &self.cli_args)
+            .field("skip_validation", &self.skip_validation)
+            .field("proxy", &self.proxy)
+            .field("feature_flags", &self.feature_flags)
+            .field("metadata", &self.metadata)
             .finish_non_exhaustive()
     }
 }
@@ -1438,9 +2974,33 @@ impl ClaudeAgentOptions {
         Self::default()
     }
 
-    /// Set the system prompt.
-    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
-        self.system_prompt = Some(SystemPromptConfig::Text(prompt.into()));
+    /// Options with Claude Code's own system prompt and tools.
+    ///
+    /// By default the SDK runs with a minimal system prompt; use this to get
+    /// the behavior of the interactive CLI.
+    pub fn claude_code() -> Self {
+        Self {
+            system_prompt: Some(SystemPromptConfig::claude_code()),
+            tools: Some(ToolsConfig::claude_code()),
+            ..Self::default()
+        }
+    }
+
+    /// Set the system prompt: text, or a [`SystemPromptPreset`].
+    pub fn with_system_prompt(mut self, prompt: impl Into<SystemPromptConfig>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Add `text` to the end of the system prompt.
+    ///
+    /// See [`SystemPromptConfig::append`]. Without a system prompt, `text`
+    /// becomes the prompt.
+    pub fn with_system_prompt_append(mut self, text: impl Into<String>) -> Self {
+        self.system_prompt = Some(match self.system_prompt.take() {
+            Some(prompt) => prompt.append(text),
+            None => SystemPromptConfig::Text(text.into()),
+        });
         self
     }
 
@@ -1450,6 +3010,22 @@ impl ClaudeAgentOptions {
         self
     }
 
+    /// Set what [`query_structured`](crate::query_structured) does with
+    /// invalid output.
+    pub fn with_structured_output_policy(
+        mut self,
+        policy: crate::structured::StructuredOutputPolicy,
+    ) -> Self {
+        self.structured_output_policy = policy;
+        self
+    }
+
+    /// Set the model to use when the primary model is overloaded.
+    pub fn with_fallback_model(mut self, model: impl Into<String>) -> Self {
+        self.fallback_model = Some(model.into());
+        self
+    }
+
     /// Set the permission mode.
     pub fn with_permission_mode(mut self, mode: PermissionMode) -> Self {
         self.permission_mode = Some(mode);
@@ -1468,9 +3044,30 @@ impl ClaudeAgentOptions {
         self
     }
 
-    /// Set allowed tools.
-    pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
-        self.allowed_tools = tools;
+    /// Set allowed tools, by name or as [`BuiltinTool`]s.
+    pub fn with_allowed_tools<T: Into<String>>(
+        mut self,
+        tools: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.allowed_tools = tools.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the base set of available tools.
+    pub fn with_tools(mut self, tools: impl Into<ToolsConfig>) -> Self {
+        self.tools = Some(tools.into());
+        self
+    }
+
+    /// Allow every tool provided by the named MCP server.
+    ///
+    /// Appends the `mcp__<server>__*` wildcard to `allowed_tools`, so the
+    /// namespaced pattern doesn't have to be written by hand.
+    pub fn allow_mcp_server(mut self, server: impl AsRef<str>) -> Self {
+        let pattern = format!("mcp__{}__*", server.as_ref());
+        if !self.allowed_tools.contains(&pattern) {
+            self.allowed_tools.push(pattern);
+        }
         self
     }
 
@@ -1504,6 +3101,18 @@ impl ClaudeAgentOptions {
         self
     }
 
+    /// Set the timeout for each control request.
+    pub fn with_control_timeout(mut self, timeout: Duration) -> Self {
+        self.control_timeout = Some(timeout);
+        self
+    }
+
+    /// Compact the conversation automatically under `policy`.
+    pub fn with_auto_compact(mut self, policy: crate::compaction::AutoCompactPolicy) -> Self {
+        self.auto_compact = Some(policy);
+        self
+    }
+
     /// Set the can_use_tool callback.
     pub fn with_can_use_tool<F, Fut>(mut self, callback: F) -> Self
     where
@@ -1515,175 +3124,275 @@ impl ClaudeAgentOptions {
         }));
         self
     }
-}
 
-// ============================================================================
-// Control Protocol Types
-// ============================================================================
+    /// Register `callback` for `event` on tools matching `matcher`.
+    ///
+    /// `matcher` is a tool name or pattern such as `"Bash"` or
+    /// `"Write|Edit"`; an empty string matches everything. Each call adds a
+    /// [`HookMatcher`] to [`hooks`](Self::hooks).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use claude_agents_sdk::{ClaudeAgentOptions, HookEvent, HookOutput};
+    ///
+    /// let options = ClaudeAgentOptions::new().with_hook(
+    ///     HookEvent::PreToolUse,
+    ///     "Bash",
+    ///     |input, _tool_use_id, _context| async move {
+    ///         println!("Bash: {:?}", input);
+    ///         HookOutput::default()
+    ///     },
+    /// );
+    /// assert_eq!(options.hooks.unwrap()[&HookEvent::PreToolUse].len(), 1);
+    /// ```
+    pub fn with_hook<F, Fut>(
+        self,
+        event: HookEvent,
+        matcher: impl Into<String>,
+        callback: F,
+    ) -> Self
+    where
+        F: Fn(HookInput, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HookOutput> + Send + 'static,
+    {
+        self.with_hook_matcher(event, HookMatcher::new(matcher).with_callback(callback))
+    }
 
-/// Control request subtypes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "subtype")]
-pub enum ControlRequestPayload {
-    /// Interrupt request.
-    #[serde(rename = "interrupt")]
-    Interrupt,
-    /// Tool permission request.
-    #[serde(rename = "can_use_tool")]
-    CanUseTool {
-        /// Tool name.
-        tool_name: String,
-        /// Tool input.
-        input: serde_json::Value,
-        /// Permission suggestions.
-        permission_suggestions: Option<Vec<serde_json::Value>>,
-        /// Blocked path.
-        blocked_path: Option<String>,
-    },
-    /// Initialize request.
-    #[serde(rename = "initialize")]
-    Initialize {
-        /// Hook configurations.
-        hooks: Option<serde_json::Value>,
-        /// Agent definitions.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        agents: Option<serde_json::Value>,
-    },
-    /// Set permission mode request.
-    #[serde(rename = "set_permission_mode")]
-    SetPermissionMode {
-        /// New mode.
-        mode: String,
-    },
-    /// Set model request.
-    #[serde(rename = "set_model")]
-    SetModel {
-        /// New model.
-        model: String,
-    },
-    /// Hook callback request.
-    #[serde(rename = "hook_callback")]
-    HookCallback {
-        /// Callback ID.
-        callback_id: String,
-        /// Hook input.
-        input: serde_json::Value,
-        /// Tool use ID.
-        tool_use_id: Option<String>,
-    },
-    /// MCP message request.
-    #[serde(rename = "mcp_message")]
-    McpMessage {
-        /// Server name.
-        server_name: String,
-        /// JSONRPC message.
-        message: serde_json::Value,
-    },
-    /// MCP status request.
-    #[serde(rename = "mcp_status")]
-    McpStatus,
-    /// Rewind files request.
-    #[serde(rename = "rewind_files")]
-    RewindFiles {
-        /// User message ID to rewind to.
-        user_message_id: String,
-    },
-}
+    /// Add `matcher` to the hooks for `event`.
+    pub fn with_hook_matcher(mut self, event: HookEvent, matcher: HookMatcher) -> Self {
+        self.hooks
+            .get_or_insert_with(HashMap::new)
+            .entry(event)
+            .or_default()
+            .push(matcher);
+        self
+    }
 
-/// Control request.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ControlRequest {
-    /// Always "control_request".
-    #[serde(rename = "type")]
-    pub request_type: String,
-    /// Request ID.
-    pub request_id: String,
-    /// Request payload.
-    pub request: ControlRequestPayload,
-}
+    /// Run matching hook callbacks concurrently, at most `parallelism` at a
+    /// time. See [`hook_parallelism`](Self::hook_parallelism).
+    ///
+    /// The SDK then matches tool names against [`HookMatcher::matcher`]
+    /// itself, and enforces each matcher's timeout; a callback that times
+    /// out contributes nothing to the merged output.
+    pub fn with_hook_parallelism(mut self, parallelism: usize) -> Self {
+        self.hook_parallelism = Some(parallelism);
+        self
+    }
 
-/// Success response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ControlSuccessResponse {
-    /// Always "success".
-    pub subtype: String,
-    /// Request ID.
-    pub request_id: String,
-    /// Response data.
-    pub response: Option<serde_json::Value>,
-}
+    /// Set the recorder for SDK metrics.
+    pub fn with_metrics(
+        mut self,
+        recorder: impl crate::metrics::MetricsRecorder + 'static,
+    ) -> Self {
+        self.metrics = Some(Arc::new(recorder));
+        self
+    }
 
-/// Error response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ControlErrorResponse {
-    /// Always "error".
-    pub subtype: String,
-    /// Request ID.
-    pub request_id: String,
-    /// Error message.
-    pub error: String,
-}
+    /// Capture raw protocol traffic to a file or channel.
+    pub fn with_wire_log(mut self, wire_log: crate::wire_log::WireLog) -> Self {
+        self.wire_log = Some(wire_log);
+        self
+    }
 
-/// Control response payload.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "subtype")]
-pub enum ControlResponsePayload {
-    /// Success response.
-    #[serde(rename = "success")]
-    Success {
-        /// Request ID.
-        request_id: String,
-        /// Response data.
-        response: Option<serde_json::Value>,
-    },
-    /// Error response.
-    #[serde(rename = "error")]
-    Error {
-        /// Request ID.
-        request_id: String,
-        /// Error message.
-        error: String,
-    },
-}
+    /// Publish typed SDK events to `bus`.
+    pub fn with_event_bus(mut self, bus: crate::events::EventBus) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
 
-/// Control response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ControlResponse {
-    /// Always "control_response".
-    #[serde(rename = "type")]
-    pub response_type: String,
-    /// Response payload.
-    pub response: ControlResponsePayload,
-}
+    /// Emit file diffs on the event bus. See
+    /// [`file_diffs`](Self::file_diffs).
+    pub fn with_file_diffs(mut self, enabled: bool) -> Self {
+        self.file_diffs = enabled;
+        self
+    }
 
-impl ControlResponse {
-    /// Get the request ID.
-    pub fn request_id(&self) -> &str {
-        match &self.response {
-            ControlResponsePayload::Success { request_id, .. } => request_id,
-            ControlResponsePayload::Error { request_id, .. } => request_id,
-        }
+    /// Redact secrets and personal data from everything the SDK logs.
+    pub fn with_redactor(mut self, redactor: crate::redact::Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
     }
 
-    /// Check if this is a success response.
-    pub fn is_success(&self) -> bool {
-        matches!(&self.response, ControlResponsePayload::Success { .. })
+    /// Read time from `clock` instead of `tokio::time`.
+    ///
+    /// Control request timeouts, keepalive pings, and turn statistics all use
+    /// it, so tests can drive them with a [`MockClock`](crate::clock::MockClock).
+    pub fn with_clock(mut self, clock: impl crate::clock::Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
     }
 
-    /// Get the response data if successful.
-    pub fn data(&self) -> Option<&serde_json::Value> {
-        match &self.response {
-            ControlResponsePayload::Success { response, .. } => response.as_ref(),
-            ControlResponsePayload::Error { .. } => None,
-        }
+    /// Reach the CLI through transports made by `factory` instead of
+    /// running it as a subprocess.
+    ///
+    /// The factory is called on each connect, and the transport must
+    /// support [`Transport::take_messages`]. [`RemoteTransport`] adapts a
+    /// WebSocket or other connection to a remote CLI bridge.
+    ///
+    /// [`Transport::take_messages`]: crate::_internal::transport::Transport::take_messages
+    /// [`RemoteTransport`]: crate::_internal::transport::RemoteTransport
+    pub fn with_transport<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn(&ClaudeAgentOptions) -> crate::errors::Result<T> + Send + Sync + 'static,
+        T: crate::_internal::transport::Transport + 'static,
+    {
+        self.transport = Some(Arc::new(move |options| {
+            Ok(Box::new(factory(options)?) as Box<dyn crate::_internal::transport::Transport>)
+        }));
+        self
     }
 
-    /// Get the error message if failed.
-    pub fn error(&self) -> Option<&str> {
-        match &self.response {
-            ControlResponsePayload::Success { .. } => None,
-            ControlResponsePayload::Error { error, .. } => Some(error),
-        }
+    /// Set what runs the agent loop.
+    ///
+    /// [`Backend::Api`] runs it in the SDK against the Anthropic API, for
+    /// environments where the CLI can't be installed.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Set what to do with unparsable CLI output.
+    ///
+    /// With [`ParseErrorPolicy::SkipAndReport`], a malformed line becomes a
+    /// [`Message::ParseWarning`] instead of ending the session.
+    pub fn with_parse_error_policy(mut self, policy: ParseErrorPolicy) -> Self {
+        self.parse_error_policy = policy;
+        self
+    }
+
+    /// Set the capacity of the channels between the CLI and the consumer.
+    ///
+    /// A capacity of zero is treated as one.
+    pub fn with_channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = Some(capacity);
+        self
+    }
+
+    /// Set what to do when the consumer falls behind by a full channel.
+    ///
+    /// With [`ChannelOverflowPolicy::DropOldestPartialEvents`], a consumer
+    /// that is slow to read partial message events no longer holds up
+    /// permission checks and hooks.
+    pub fn with_overflow_policy(mut self, policy: ChannelOverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Deliver messages unparsed until they are read. See
+    /// [`lazy_parse`](Self::lazy_parse).
+    ///
+    /// Useful when most messages are dropped after a look at their type,
+    /// such as when only result messages are kept.
+    pub fn with_lazy_parse(mut self, lazy: bool) -> Self {
+        self.lazy_parse = lazy;
+        self
+    }
+
+    /// Write CLI messages longer than `max_buffer_size` to files in `dir`.
+    ///
+    /// The largest content blocks of such a message are delivered as
+    /// [`ContentBlock::LargePayload`] and read back on demand with
+    /// [`LargePayloadBlock::load`]. Memory is bounded while such a line is
+    /// read and once its message is built, but building it parses the
+    /// whole file. Files are deleted when no longer referred to.
+    pub fn with_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
+
+    /// Pass typed settings to the CLI, replacing [`settings`](Self::settings).
+    pub fn with_settings(mut self, settings: crate::settings::Settings) -> Self {
+        self.settings = Some(settings.to_json_string());
+        self
+    }
+
+    /// Enable an Anthropic beta, such as `"context-1m-2025-08-07"`.
+    pub fn with_beta(mut self, beta: impl Into<SdkBeta>) -> Self {
+        self.betas.push(beta.into());
+        self
+    }
+
+    /// Set a CLI feature flag, such as `CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC`.
+    pub fn with_feature_flag(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.feature_flags.insert(name.into(), enabled);
+        self
+    }
+
+    /// Tag the session with a metadata entry, such as a tenant or request ID.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Load a plugin from a local directory.
+    pub fn with_plugin_dir(mut self, path: impl Into<String>) -> Self {
+        self.plugins.push(SdkPluginConfig::local(path));
+        self
+    }
+
+    /// Route the CLI's HTTP traffic through a proxy.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set an environment variable for the CLI.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set several environment variables for the CLI.
+    pub fn with_envs<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Whether the CLI inherits this process's environment (the default).
+    ///
+    /// Variables set with [`with_env`](Self::with_env) override inherited
+    /// ones either way. See [`clear_env`](Self::clear_env).
+    pub fn inherit_env(mut self, inherit: bool) -> Self {
+        self.clear_env = !inherit;
+        self
+    }
+
+    /// Append an extra CLI argument.
+    pub fn with_cli_arg(mut self, arg: CliArg) -> Self {
+        self.cli_args.push(arg);
+        self
+    }
+
+    /// Append extra CLI arguments, in order.
+    pub fn with_cli_args(mut self, args: impl IntoIterator<Item = CliArg>) -> Self {
+        self.cli_args.extend(args);
+        self
+    }
+
+    /// Skip [`validate`](Self::validate) when connecting.
+    pub fn with_skip_validation(mut self, skip: bool) -> Self {
+        self.skip_validation = skip;
+        self
+    }
+
+    /// Register an in-process SDK MCP server.
+    ///
+    /// Tool calls from the CLI for this server are dispatched to its tools
+    /// over the control protocol. Pass an `Arc<SdkMcpServer>` to keep a handle
+    /// for adding or removing tools while the session runs.
+    #[cfg(feature = "mcp")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mcp")))]
+    pub fn with_sdk_mcp_server(mut self, server: impl Into<Arc<crate::mcp::SdkMcpServer>>) -> Self {
+        let server = server.into();
+        self.sdk_mcp_servers
+            .insert(server.name().to_string(), server);
+        self
     }
 }
 
@@ -1705,6 +3414,31 @@ mod tests {
         assert!(msg.is_assistant());
     }
 
+    #[test]
+    fn test_lazy_message_parses_on_access() {
+        let lazy = LazyMessage::new(serde_json::json!({
+            "type": "assistant",
+            "message": {"model": "claude-3", "content": [{"type": "text", "text": "Hi"}]}
+        }));
+        assert_eq!(lazy.msg_type(), "assistant");
+        assert!(!lazy.is_parsed());
+
+        let message = Message::Lazy(lazy);
+        assert!(message.is_assistant());
+        assert_eq!(message.as_assistant().unwrap().text(), "Hi");
+        assert!(matches!(&message, Message::Lazy(lazy) if lazy.is_parsed()));
+        assert!(message.into_parsed().unwrap().as_assistant().is_some());
+    }
+
+    #[test]
+    fn test_lazy_message_parse_error() {
+        let lazy = LazyMessage::new(serde_json::json!({"type": "user"}));
+        let err = lazy.get().unwrap_err();
+        assert!(err.to_string().contains("message"), "{}", err);
+        assert!(lazy.get().is_err());
+        assert!(lazy.parse().is_err());
+    }
+
     #[test]
     fn test_content_block_text() {
         let block = ContentBlock::Text(TextBlock {
@@ -1713,6 +3447,15 @@ mod tests {
         assert_eq!(block.as_text(), Some("Hello"));
     }
 
+    #[test]
+    fn test_allow_mcp_server() {
+        let options = ClaudeAgentOptions::new()
+            .with_allowed_tools(vec!["Read".to_string()])
+            .allow_mcp_server("calc")
+            .allow_mcp_server("calc");
+        assert_eq!(options.allowed_tools, vec!["Read", "mcp__calc__*"]);
+    }
+
     #[test]
     fn test_options_builder() {
         let opts = ClaudeAgentOptions::new()
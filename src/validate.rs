@@ -0,0 +1,188 @@
+//! Checking options for mistakes before connecting.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::errors::ClaudeSDKError;
+use crate::types::{BuiltinTool, ClaudeAgentOptions};
+
+/// A problem found by [`ClaudeAgentOptions::validate`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// Both `can_use_tool` and `permission_prompt_tool_name` are set.
+    ConflictingPermissionHandlers,
+    /// A tool is in both `allowed_tools` and `disallowed_tools`.
+    ToolAllowedAndDisallowed {
+        /// The tool name.
+        tool: String,
+    },
+    /// An allowed or disallowed tool differs from a built-in tool only in
+    /// case, so the rule matches nothing.
+    MisspelledTool {
+        /// The configured name.
+        tool: String,
+        /// The built-in tool it resembles.
+        suggestion: BuiltinTool,
+    },
+    /// `max_budget_usd` is negative or not a number.
+    InvalidBudget {
+        /// The configured budget.
+        max_budget_usd: f64,
+    },
+    /// `max_turns` is zero while continuing a conversation.
+    ZeroMaxTurns,
+    /// Both `resume` and `continue_conversation` are set.
+    ResumeWithContinue {
+        /// The session being resumed.
+        session_id: String,
+    },
+    /// `cwd` does not exist or is not a directory.
+    CwdNotFound {
+        /// The configured directory.
+        path: PathBuf,
+    },
+    /// `cli_path` does not exist and is not on `PATH`.
+    CliNotFound {
+        /// The configured path.
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConflictingPermissionHandlers => write!(
+                f,
+                "Cannot specify both 'can_use_tool' and 'permission_prompt_tool_name'"
+            ),
+            Self::ToolAllowedAndDisallowed { tool } => write!(
+                f,
+                "Tool '{}' is in both 'allowed_tools' and 'disallowed_tools'",
+                tool
+            ),
+            Self::MisspelledTool { tool, suggestion } => write!(
+                f,
+                "Tool '{}' matches no tool; did you mean '{}'?",
+                tool, suggestion
+            ),
+            Self::InvalidBudget { max_budget_usd } => write!(
+                f,
+                "'max_budget_usd' must be a non-negative number, got {}",
+                max_budget_usd
+            ),
+            Self::ZeroMaxTurns => write!(
+                f,
+                "'max_turns' is 0, so the continued conversation cannot take a turn"
+            ),
+            Self::ResumeWithContinue { session_id } => write!(
+                f,
+                "Cannot both resume session '{}' and continue the most recent conversation",
+                session_id
+            ),
+            Self::CwdNotFound { path } => {
+                write!(f, "Working directory '{}' does not exist", path.display())
+            }
+            Self::CliNotFound { path } => write!(
+                f,
+                "Claude CLI not found at '{}'. Please ensure Claude Code is installed.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl ClaudeAgentOptions {
+    /// Check the options for contradictions and missing paths.
+    ///
+    /// Returns every issue found. Connecting runs this check and fails on
+    /// any issue unless [`skip_validation`](Self::skip_validation) is set.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        if self.can_use_tool.is_some() && self.permission_prompt_tool_name.is_some() {
+            issues.push(ValidationIssue::ConflictingPermissionHandlers);
+        }
+        for tool in &self.allowed_tools {
+            if self.disallowed_tools.contains(tool) {
+                issues.push(ValidationIssue::ToolAllowedAndDisallowed { tool: tool.clone() });
+            }
+        }
+        for tool in self.allowed_tools.iter().chain(&self.disallowed_tools) {
+            if let Ok(suggestion) = tool.parse::<BuiltinTool>() {
+                if suggestion.as_str() != tool {
+                    issues.push(ValidationIssue::MisspelledTool {
+                        tool: tool.clone(),
+                        suggestion,
+                    });
+                }
+            }
+        }
+        if let Some(max_budget_usd) = self.max_budget_usd {
+            if max_budget_usd.is_nan() || max_budget_usd < 0.0 {
+                issues.push(ValidationIssue::InvalidBudget { max_budget_usd });
+            }
+        }
+        if self.max_turns == Some(0) && self.continue_conversation {
+            issues.push(ValidationIssue::ZeroMaxTurns);
+        }
+        if let (Some(session_id), true) = (&self.resume, self.continue_conversation) {
+            issues.push(ValidationIssue::ResumeWithContinue {
+                session_id: session_id.clone(),
+            });
+        }
+        if let Some(cwd) = &self.cwd {
+            if !cwd.is_dir() {
+                issues.push(ValidationIssue::CwdNotFound { path: cwd.clone() });
+            }
+        }
+        #[cfg(feature = "subprocess")]
+        if let Some(cli_path) = &self.cli_path {
+            if !cli_path.exists() && which::which(cli_path).is_err() {
+                issues.push(ValidationIssue::CliNotFound {
+                    path: cli_path.clone(),
+                });
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Run [`validate`](Self::validate) as connecting does.
+    ///
+    /// Conflicting permission handlers are rejected even with
+    /// `skip_validation`, since the SDK cannot run with both.
+    pub(crate) fn check(&self) -> crate::errors::Result<()> {
+        let issues = match self.validate() {
+            Ok(()) => return Ok(()),
+            Err(issues) => issues,
+        };
+        let issues: Vec<ValidationIssue> = if self.skip_validation {
+            issues
+                .into_iter()
+                .filter(|issue| *issue == ValidationIssue::ConflictingPermissionHandlers)
+                .collect()
+        } else {
+            issues
+        };
+
+        match issues.as_slice() {
+            [] => Ok(()),
+            [issue @ ValidationIssue::CliNotFound { .. }] => {
+                Err(ClaudeSDKError::cli_not_found(issue.to_string()))
+            }
+            [issue] => Err(ClaudeSDKError::configuration(issue.to_string())),
+            issues => {
+                let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                Err(ClaudeSDKError::configuration(format!(
+                    "Invalid options: {}",
+                    messages.join("; ")
+                )))
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Raw protocol capture.
+//!
+//! A [`WireLog`] receives every line written to the CLI's stdin and read from
+//! its stdout, exactly as sent, with a timestamp and direction. This is the
+//! first thing to reach for when a new CLI version changes the protocol.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use claude_agents_sdk::{ClaudeAgentOptions, WireLog};
+//!
+//! // Append to a JSONL file, one entry per line.
+//! let options = ClaudeAgentOptions::new().with_wire_log(WireLog::file("claude-wire.jsonl"));
+//!
+//! // Or receive entries in-process.
+//! let (wire_log, mut entries) = WireLog::channel();
+//! let options = ClaudeAgentOptions::new().with_wire_log(wire_log);
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+#[cfg(feature = "subprocess")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "subprocess")]
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+#[cfg(feature = "subprocess")]
+use tracing::warn;
+
+#[cfg(feature = "subprocess")]
+use crate::errors::{ClaudeSDKError, Result};
+#[cfg(feature = "subprocess")]
+use crate::redact::Redactor;
+
+/// Direction of a captured line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireDirection {
+    /// Written to the CLI's stdin.
+    Sent,
+    /// Read from the CLI's stdout.
+    Received,
+}
+
+/// One captured line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireEntry {
+    /// Milliseconds since the Unix epoch when the line was sent or received.
+    pub timestamp_ms: u64,
+    /// Whether the line was sent or received.
+    pub direction: WireDirection,
+    /// The line, without its trailing newline.
+    pub line: String,
+    /// The session's [`metadata`](crate::ClaudeAgentOptions::metadata).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Where to send captured protocol lines.
+#[derive(Debug, Clone)]
+pub enum WireLog {
+    /// Append entries to a JSONL file, created if missing.
+    File(PathBuf),
+    /// Send entries to a channel.
+    Channel(mpsc::UnboundedSender<WireEntry>),
+}
+
+impl WireLog {
+    /// Log to a JSONL file.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::File(path.into())
+    }
+
+    /// Log to a new channel, returning the receiving end.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<WireEntry>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self::Channel(tx), rx)
+    }
+
+    /// Open the log for a session, redacting lines with `redactor` and
+    /// tagging entries with `metadata`.
+    #[cfg(feature = "subprocess")]
+    ///
+    /// File writes happen on a background task so the transport never blocks
+    /// on disk I/O.
+    pub(crate) fn open(
+        &self,
+        redactor: Option<Redactor>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<WireTap> {
+        match self {
+            Self::Channel(tx) => Ok(WireTap {
+                tx: tx.clone(),
+                redactor,
+                metadata,
+            }),
+            Self::File(path) => {
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| {
+                        ClaudeSDKError::configuration(format!(
+                            "Failed to open wire log {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                let (tx, mut rx) = mpsc::unbounded_channel::<WireEntry>();
+                let path = path.clone();
+                tokio::spawn(async move {
+                    let mut file = tokio::fs::File::from_std(file);
+                    while let Some(entry) = rx.recv().await {
+                        let mut line = match serde_json::to_string(&entry) {
+                            Ok(line) => line,
+                            Err(_) => continue,
+                        };
+                        line.push('\n');
+                        if let Err(e) = file.write_all(line.as_bytes()).await {
+                            warn!("Failed to write wire log {}: {}", path.display(), e);
+                            break;
+                        }
+                    }
+                    let _ = file.flush().await;
+                });
+                Ok(WireTap {
+                    tx,
+                    redactor,
+                    metadata,
+                })
+            }
+        }
+    }
+}
+
+/// An open [`WireLog`], shared by the transport's writer and reader.
+#[cfg(feature = "subprocess")]
+#[derive(Debug, Clone)]
+pub(crate) struct WireTap {
+    tx: mpsc::UnboundedSender<WireEntry>,
+    redactor: Option<Redactor>,
+    metadata: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "subprocess")]
+impl WireTap {
+    /// Record a line. Dropped silently if the receiver has gone away.
+    pub(crate) fn record(&self, direction: WireDirection, line: &str) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let _ = self.tx.send(WireEntry {
+            timestamp_ms,
+            direction,
+            line: crate::redact::redact_line(self.redactor.as_ref(), line).into_owned(),
+            metadata: self.metadata.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "subprocess")]
+    #[tokio::test]
+    async fn test_channel_tap() {
+        let (log, mut rx) = WireLog::channel();
+        let tap = log.open(None, BTreeMap::new()).unwrap();
+        tap.record(WireDirection::Sent, r#"{"type":"user"}"#);
+        tap.record(WireDirection::Received, "not json");
+
+        let sent = rx.recv().await.unwrap();
+        assert_eq!(sent.direction, WireDirection::Sent);
+        assert_eq!(sent.line, r#"{"type":"user"}"#);
+        assert!(sent.timestamp_ms > 0);
+        assert_eq!(rx.recv().await.unwrap().line, "not json");
+    }
+
+    #[cfg(feature = "subprocess")]
+    #[tokio::test]
+    async fn test_tap_tags_entries_with_metadata() {
+        let (log, mut rx) = WireLog::channel();
+        let metadata = BTreeMap::from([("tenant".to_string(), "acme".to_string())]);
+        let tap = log.open(None, metadata.clone()).unwrap();
+        tap.record(WireDirection::Sent, "{}");
+
+        let entry = rx.recv().await.unwrap();
+        assert_eq!(entry.metadata, metadata);
+        assert!(serde_json::to_string(&entry)
+            .unwrap()
+            .ends_with(r#","metadata":{"tenant":"acme"}}"#));
+    }
+
+    #[cfg(feature = "subprocess")]
+    #[tokio::test]
+    async fn test_file_tap_appends_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wire.jsonl");
+        std::fs::write(&path, "").unwrap();
+
+        let tap = WireLog::file(&path).open(None, BTreeMap::new()).unwrap();
+        tap.record(WireDirection::Sent, "first");
+        tap.record(WireDirection::Received, "second");
+        drop(tap);
+
+        let mut contents = String::new();
+        for _ in 0..50 {
+            contents = std::fs::read_to_string(&path).unwrap();
+            if contents.lines().count() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let entries: Vec<WireEntry> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].direction, WireDirection::Sent);
+        assert_eq!(entries[1].line, "second");
+    }
+
+    #[cfg(feature = "subprocess")]
+    #[test]
+    fn test_file_open_error() {
+        let err = WireLog::file("/nonexistent/dir/wire.jsonl")
+            .open(None, BTreeMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("wire log"));
+    }
+
+    #[cfg(feature = "subprocess")]
+    #[tokio::test]
+    async fn test_tap_redacts_lines() {
+        let (log, mut rx) = WireLog::channel();
+        let tap = log
+            .open(Some(Redactor::default()), BTreeMap::new())
+            .unwrap();
+        tap.record(
+            WireDirection::Sent,
+            r#"{"type":"user","message":{"content":"key sk-ant-REDACTED"}}"#,
+        );
+        let entry = rx.recv().await.unwrap();
+        assert!(!entry.line.contains("sk-ant"));
+        assert!(entry.line.contains(crate::REDACTED));
+    }
+
+    #[test]
+    fn test_entry_serialization() {
+        let entry = WireEntry {
+            timestamp_ms: 1,
+            direction: WireDirection::Received,
+            line: "{}".to_string(),
+            metadata: BTreeMap::new(),
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"timestamp_ms":1,"direction":"received","line":"{}"}"#
+        );
+    }
+}
@@ -122,6 +122,8 @@ async fn test_receive_messages_streaming() {
             Message::User(_) => "user",
             Message::Result(_) => "result",
             Message::StreamEvent(_) => "stream_event",
+            Message::ParseWarning(_) => "parse_warning",
+            Message::Lazy(_) => "lazy",
         });
         if matches!(msg, Message::Result(_)) {
             break;
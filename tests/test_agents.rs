@@ -0,0 +1,221 @@
+//! Tests for loading subagents from markdown files and sharing them through
+//! a registry.
+
+use claude_agents_sdk::agents::{Registry, DEFAULT_AGENT_VERSION};
+use claude_agents_sdk::{AgentDefinition, AgentModel, ClaudeAgentOptions, ModelId, PermissionMode};
+use std::collections::HashMap;
+
+const REVIEWER: &str = "---
+name: code-reviewer
+description: Reviews diffs for correctness
+tools: Read, Grep, Glob
+model: sonnet
+mcpServers: [github]
+permissionMode: plan
+---
+
+You are a meticulous code reviewer.
+
+Focus on correctness.
+";
+
+fn agents_dir(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        std::fs::write(dir.path().join(name), contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_from_markdown() {
+    let (name, agent) = AgentDefinition::from_markdown(REVIEWER, "ignored").unwrap();
+    assert_eq!(name, "code-reviewer");
+    assert_eq!(agent.description, "Reviews diffs for correctness");
+    assert_eq!(
+        agent.prompt,
+        "You are a meticulous code reviewer.\n\nFocus on correctness."
+    );
+    assert_eq!(
+        agent.tools,
+        Some(vec!["Read".into(), "Grep".into(), "Glob".into()])
+    );
+    assert_eq!(agent.model, Some(AgentModel::Sonnet.into()));
+    assert_eq!(agent.mcp_servers, Some(vec!["github".into()]));
+    assert_eq!(agent.permission_mode, Some(PermissionMode::Plan));
+
+    let json = serde_json::to_value(&agent).unwrap();
+    assert_eq!(json["mcpServers"], serde_json::json!(["github"]));
+    assert_eq!(json["permissionMode"], "plan");
+}
+
+#[test]
+fn test_minimal_agent_uses_file_name() {
+    let (name, agent) = AgentDefinition::from_markdown(
+        "---\ndescription: Writes tests\n---\nWrite tests.",
+        "tester",
+    )
+    .unwrap();
+    assert_eq!(name, "tester");
+    assert!(agent.tools.is_none());
+    assert!(agent.model.is_none());
+    assert!(agent.mcp_servers.is_none());
+    assert!(agent.permission_mode.is_none());
+}
+
+#[test]
+fn test_agent_model_id() {
+    let (_, agent) = AgentDefinition::from_markdown(
+        "---\ndescription: d\nmodel: claude-opus-4-5\n---\nbody",
+        "a",
+    )
+    .unwrap();
+    assert_eq!(agent.model, Some(ModelId::Opus45));
+}
+
+#[test]
+fn test_invalid_agents() {
+    for (contents, expected) in [
+        ("no frontmatter", "no frontmatter"),
+        ("---\nname: x\n---\nbody", "has no description"),
+        (
+            "---\ndescription: d\nmodel: gpt\n---\nbody",
+            "unknown model 'gpt'",
+        ),
+        (
+            "---\ndescription: d\npermissionMode: yolo\n---\nbody",
+            "unknown permission mode 'yolo'",
+        ),
+    ] {
+        let err = AgentDefinition::from_markdown(contents, "a").unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
+
+#[test]
+fn test_load_dir() {
+    let dir = agents_dir(&[
+        ("code-reviewer.md", REVIEWER),
+        (
+            "tester.md",
+            "---\ndescription: Writes tests\n---\nWrite tests.",
+        ),
+        ("notes.txt", "not an agent"),
+    ]);
+    let agents = AgentDefinition::load_dir(dir.path()).unwrap();
+
+    let mut names: Vec<&String> = agents.keys().collect();
+    names.sort();
+    assert_eq!(names, ["code-reviewer", "tester"]);
+}
+
+#[test]
+fn test_load_dir_error_names_file() {
+    let dir = agents_dir(&[("broken.md", "---\nname: x\n---\n")]);
+    let err = AgentDefinition::load_dir(dir.path()).unwrap_err();
+    assert!(err.to_string().contains("broken.md"), "{}", err);
+}
+
+#[test]
+fn test_programmatic_agents_take_precedence() {
+    let dir = agents_dir(&[
+        ("code-reviewer.md", REVIEWER),
+        (
+            "tester.md",
+            "---\ndescription: Writes tests\n---\nWrite tests.",
+        ),
+    ]);
+    let mut options = ClaudeAgentOptions::new();
+    options.agents = Some(HashMap::from([(
+        "code-reviewer".to_string(),
+        AgentDefinition {
+            description: "Programmatic".to_string(),
+            prompt: "Review.".to_string(),
+            tools: None,
+            model: None,
+            mcp_servers: None,
+            permission_mode: None,
+        },
+    )]));
+
+    let options = options.with_agents_dir(dir.path()).unwrap();
+    let agents = options.agents.unwrap();
+    assert_eq!(agents.len(), 2);
+    assert_eq!(agents["code-reviewer"].description, "Programmatic");
+    assert_eq!(agents["tester"].prompt, "Write tests.");
+}
+
+#[test]
+fn test_registry_resolves_versions() {
+    let dir = agents_dir(&[
+        (
+            "reviewer-v1.md",
+            "---\nname: rust-reviewer\ndescription: v1\nversion: 1.0.0\n---\nReview.",
+        ),
+        (
+            "reviewer-v2.md",
+            "---\nname: rust-reviewer\ndescription: v2\nversion: 2.1.0\n---\nReview.",
+        ),
+        (
+            "tester.md",
+            "---\ndescription: Writes tests\n---\nWrite tests.",
+        ),
+    ]);
+    let registry = Registry::new();
+    assert_eq!(registry.load_dir(dir.path()).unwrap(), 3);
+    assert_eq!(registry.names(), ["rust-reviewer", "tester"]);
+    assert_eq!(
+        registry.versions("tester"),
+        [DEFAULT_AGENT_VERSION.parse().unwrap()]
+    );
+
+    let (version, agent) = registry.get("rust-reviewer").unwrap();
+    assert_eq!(
+        (version.to_string(), agent.description.as_str()),
+        ("2.1.0".into(), "v2")
+    );
+    assert_eq!(
+        registry.get("rust-reviewer@^1").unwrap().1.description,
+        "v1"
+    );
+    assert_eq!(
+        registry.get("rust-reviewer@2.1.0").unwrap().1.description,
+        "v2"
+    );
+    assert!(registry.get("rust-reviewer@2.0.0").is_none());
+
+    let options = ClaudeAgentOptions::new()
+        .with_agent_from(&registry, "rust-reviewer@1")
+        .unwrap();
+    assert_eq!(options.agents.unwrap()["rust-reviewer"].description, "v1");
+
+    let err = ClaudeAgentOptions::new()
+        .with_agent_from(&registry, "rust-reviewer@^3")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("registered: 1.0.0, 2.1.0"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_registry_rejects_republished_versions() {
+    let agent = AgentDefinition::from_markdown("---\ndescription: d\n---\nbody", "a")
+        .unwrap()
+        .1;
+    let registry = Registry::new();
+    registry.register("a", "1.0.0", agent.clone()).unwrap();
+    let err = registry.register("a", "1.0.0", agent.clone()).unwrap_err();
+    assert!(err.to_string().contains("already registered"), "{}", err);
+    assert!(registry.register("a", "latest", agent).is_err());
+
+    let err = ClaudeAgentOptions::new()
+        .with_agent_from_registry("not-registered")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("No agent 'not-registered'"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,246 @@
+//! Tests for the API backend against a scripted Messages API server.
+
+#![cfg(feature = "api")]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use claude_agents_sdk::mcp::{SdkMcpServer, SdkMcpTool, ToolInputSchema, ToolResult};
+use claude_agents_sdk::{
+    query_all, Backend, ClaudeAgentOptions, ClaudeClient, ContentBlock, Message, PermissionResult,
+    UserMessageContent,
+};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+
+/// A request the server received: its headers and JSON body.
+type Seen = (HashMap<String, String>, Value);
+
+/// Serve `replies` in order, one per request, as `(status, body)`.
+async fn spawn_api(replies: Vec<(u16, Value)>) -> (String, JoinHandle<Vec<Seen>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut seen = Vec::new();
+        for (status, reply) in replies {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.split();
+            let mut reader = BufReader::new(read);
+
+            let mut headers = HashMap::new();
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            headers.insert(
+                ":path".to_string(),
+                line.split(' ').nth(1).unwrap().to_string(),
+            );
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let Some((name, value)) = line.trim_end().split_once(':') else {
+                    break;
+                };
+                headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+            }
+            let mut body = vec![0; headers["content-length"].parse().unwrap()];
+            reader.read_exact(&mut body).await.unwrap();
+            seen.push((headers, serde_json::from_slice(&body).unwrap()));
+
+            let reply = reply.to_string();
+            let response = format!(
+                "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                reply.len(),
+                reply
+            );
+            write.write_all(response.as_bytes()).await.unwrap();
+        }
+        seen
+    });
+    (url, server)
+}
+
+fn response(content: Value, stop_reason: &str) -> (u16, Value) {
+    (
+        200,
+        json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": content,
+            "stop_reason": stop_reason,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }),
+    )
+}
+
+fn calculator() -> SdkMcpServer {
+    let add = SdkMcpTool::new(
+        "add",
+        "Add two numbers",
+        ToolInputSchema::object()
+            .number_property("a", "First")
+            .number_property("b", "Second"),
+        |input| async move {
+            let sum = input["a"].as_f64().unwrap_or(0.0) + input["b"].as_f64().unwrap_or(0.0);
+            Ok(ToolResult::text(sum.to_string()))
+        },
+    );
+    SdkMcpServer::new("calc", "1.0", vec![add])
+}
+
+fn options(url: &str) -> ClaudeAgentOptions {
+    ClaudeAgentOptions::new()
+        .with_backend(Backend::Api("test-key".to_string()))
+        .with_env("ANTHROPIC_BASE_URL", url)
+        .with_sdk_mcp_server(calculator())
+}
+
+fn tool_results(messages: &[Message]) -> Vec<(String, bool)> {
+    messages
+        .iter()
+        .filter_map(|message| match message {
+            Message::User(user) => match &user.content {
+                UserMessageContent::Blocks(blocks) => Some(blocks.clone()),
+                UserMessageContent::Text(_) => None,
+            },
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            ContentBlock::ToolResult(result) => Some((
+                result.content.unwrap().to_string(),
+                result.is_error.unwrap_or(false),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_api_backend_runs_tools() {
+    let (url, server) = spawn_api(vec![
+        response(
+            json!([{"type": "tool_use", "id": "toolu_1", "name": "mcp__calc__add", "input": {"a": 1, "b": 2}}]),
+            "tool_use",
+        ),
+        response(json!([{"type": "text", "text": "It's 3."}]), "end_turn"),
+    ])
+    .await;
+
+    let options = options(&url).with_allowed_tools(["mcp__calc"]);
+    let messages = query_all("What is 1 + 2?", Some(options)).await.unwrap();
+    let seen = server.await.unwrap();
+
+    assert!(matches!(&messages[0], Message::System(system) if system.subtype == "init"));
+    assert_eq!(
+        tool_results(&messages),
+        vec![("[{\"text\":\"3\",\"type\":\"text\"}]".to_string(), false)]
+    );
+    let Some(Message::Result(result)) = messages.last() else {
+        panic!("no result: {:?}", messages.last());
+    };
+    assert!(!result.is_error);
+    assert_eq!(result.num_turns, 2);
+    assert_eq!(result.result.as_deref(), Some("It's 3."));
+
+    let (headers, first) = &seen[0];
+    assert_eq!(headers[":path"], "/v1/messages");
+    assert_eq!(headers["x-api-key"], "test-key");
+    assert_eq!(first["model"], "claude-sonnet-4-5");
+    assert_eq!(first["tools"][0]["name"], "mcp__calc__add");
+    assert_eq!(first["messages"][0]["content"], "What is 1 + 2?");
+    let (_, second) = &seen[1];
+    assert_eq!(
+        second["messages"][2]["content"][0]["tool_use_id"],
+        "toolu_1"
+    );
+}
+
+#[tokio::test]
+async fn test_api_backend_asks_permission() {
+    let (url, server) = spawn_api(vec![
+        response(
+            json!([{"type": "tool_use", "id": "toolu_1", "name": "mcp__calc__add", "input": {"a": 1, "b": 2}}]),
+            "tool_use",
+        ),
+        response(json!([{"type": "text", "text": "Denied."}]), "end_turn"),
+    ])
+    .await;
+    let asked = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let options = {
+        let asked = Arc::clone(&asked);
+        options(&url).with_can_use_tool(move |name, _, _| {
+            asked.lock().unwrap().push(name);
+            async { PermissionResult::deny_with_message("not today") }
+        })
+    };
+
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+    client.query("Add 1 and 2").await.unwrap();
+    let mut messages = Vec::new();
+    {
+        let mut stream = client.receive_messages();
+        while let Some(message) = stream.next().await {
+            let message = message.unwrap();
+            let done = message.is_result();
+            messages.push(message);
+            if done {
+                break;
+            }
+        }
+    }
+    client.disconnect().await.unwrap();
+    server.await.unwrap();
+
+    assert_eq!(*asked.lock().unwrap(), vec!["mcp__calc__add".to_string()]);
+    assert_eq!(
+        tool_results(&messages),
+        vec![("\"not today\"".to_string(), true)]
+    );
+}
+
+#[tokio::test]
+async fn test_api_error_ends_turn_with_error_result() {
+    let (url, server) = spawn_api(vec![(
+        401,
+        json!({"type": "error", "error": {"type": "authentication_error", "message": "invalid x-api-key"}}),
+    )])
+    .await;
+
+    let messages = query_all("Hello", Some(options(&url))).await.unwrap();
+    server.await.unwrap();
+    let Some(Message::Result(result)) = messages.last() else {
+        panic!("no result: {:?}", messages.last());
+    };
+    assert!(result.is_error);
+    assert_eq!(result.subtype, "error_during_execution");
+    assert_eq!(
+        result.result.as_deref(),
+        Some("API Error: 401 invalid x-api-key")
+    );
+}
+
+#[tokio::test]
+async fn test_api_backend_stops_at_max_turns() {
+    let (url, server) = spawn_api(vec![response(
+        json!([{"type": "tool_use", "id": "toolu_1", "name": "mcp__calc__add", "input": {"a": 1, "b": 1}}]),
+        "tool_use",
+    )])
+    .await;
+
+    let messages = query_all("Keep adding", Some(options(&url).with_max_turns(1)))
+        .await
+        .unwrap();
+    server.await.unwrap();
+    let Some(Message::Result(result)) = messages.last() else {
+        panic!("no result: {:?}", messages.last());
+    };
+    assert_eq!(result.subtype, "error_max_turns");
+    assert_eq!(result.num_turns, 1);
+}
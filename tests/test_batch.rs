@@ -0,0 +1,127 @@
+//! Tests for batch runs against mock CLI sessions.
+
+#![cfg(feature = "testing")]
+
+use std::sync::{Arc, Mutex};
+
+use claude_agents_sdk::batch::{self, Batch, BatchProgress, Prompt};
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::{ClaudeAgentOptions, ClaudeSDKError};
+
+/// A session answering `prompt` with `text` at a cost of `cost_usd`.
+fn session(prompt: &str, text: &str, cost_usd: f64) -> ScenarioTransport {
+    Scenario::new()
+        .expect_user(prompt)
+        .reply_text(text)
+        .charge(cost_usd)
+        .build()
+}
+
+/// Options connecting to each session in turn, recording the budget each
+/// was given. `None` sessions fail to connect.
+fn options(
+    sessions: Vec<Option<ScenarioTransport>>,
+) -> (ClaudeAgentOptions, Arc<Mutex<Vec<Option<f64>>>>) {
+    let sessions = Mutex::new(sessions.into_iter());
+    let budgets = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&budgets);
+    let options = ClaudeAgentOptions::new().with_transport(move |options| {
+        seen.lock().unwrap().push(options.max_budget_usd);
+        sessions
+            .lock()
+            .unwrap()
+            .next()
+            .expect("unexpected connect")
+            .ok_or_else(|| ClaudeSDKError::cli_connection("CLI not found"))
+    });
+    (options, budgets)
+}
+
+#[tokio::test]
+async fn test_results_in_prompt_order_with_failures_kept() {
+    let first = session("What is 2 + 2?", "4", 0.01);
+    let third = session("What is 3 * 3?", "9", 0.02);
+    let (options, _) = options(vec![Some(first.clone()), None, Some(third.clone())]);
+    let prompts = vec![
+        Prompt::new("What is 2 + 2?").with_id("add"),
+        Prompt::from("Unreachable"),
+        Prompt::new("What is 3 * 3?").with_id("multiply"),
+    ];
+
+    let report = batch::run(prompts, options, 1).await;
+    assert_eq!(report.items.len(), 3);
+    assert!(report.skipped.is_empty());
+    assert!(!report.is_complete());
+    assert_eq!(report.items[0].prompt.id.as_deref(), Some("add"));
+    assert_eq!(report.items[0].result.as_ref().unwrap().0, "4");
+    assert!(report.items[1].result.is_err());
+    assert_eq!(report.items[1].cost_usd, 0.0);
+    assert_eq!(report.items[2].result.as_ref().unwrap().0, "9");
+    assert_eq!(report.succeeded().count(), 2);
+    assert_eq!(report.failed().count(), 1);
+    assert!((report.spent_usd - 0.03).abs() < 1e-9);
+    first.assert_complete();
+    third.assert_complete();
+}
+
+#[tokio::test]
+async fn test_budget_skips_remaining_prompts() {
+    let (options, budgets) = options(vec![
+        Some(session("one", "1", 0.1)),
+        Some(session("two", "2", 0.1)),
+    ]);
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&progress);
+    let report = Batch::new(options)
+        .with_concurrency(1)
+        .with_budget_usd(0.15)
+        .on_progress(move |p| seen.lock().unwrap().push(*p))
+        .run(vec!["one".into(), "two".into(), "three".into()])
+        .await;
+
+    assert_eq!(report.items.len(), 2);
+    assert_eq!(report.skipped, [Prompt::new("three")]);
+    assert!(!report.is_complete());
+    // Each session may spend only what's left.
+    let budgets = budgets.lock().unwrap();
+    assert_eq!(budgets[0], Some(0.15));
+    assert!((budgets[1].unwrap() - 0.05).abs() < 1e-9);
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 2);
+    assert_eq!(
+        progress[1],
+        BatchProgress {
+            finished: 2,
+            failed: 0,
+            total: 3,
+            spent_usd: report.spent_usd,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_runs_prompts_concurrently() {
+    let sessions: Vec<ScenarioTransport> = (0..4)
+        .map(|_| {
+            Scenario::new()
+                .expect_user_contains("Summarize")
+                .reply_text("Summary.")
+                .build()
+        })
+        .collect();
+    let (options, _) = options(sessions.iter().cloned().map(Some).collect());
+    let prompts: Vec<Prompt> = (0..4)
+        .map(|i| Prompt::new(format!("Summarize file {}", i)).with_id(i.to_string()))
+        .collect();
+
+    let report = Batch::new(options)
+        .with_concurrency(3)
+        .run(prompts.clone())
+        .await;
+    assert!(report.is_complete());
+    let ran: Vec<&Prompt> = report.items.iter().map(|item| &item.prompt).collect();
+    assert_eq!(ran, prompts.iter().collect::<Vec<_>>());
+    for session in &sessions {
+        session.assert_complete();
+    }
+}
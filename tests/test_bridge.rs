@@ -0,0 +1,144 @@
+//! Tests for the JSON-RPC bridge against scripted CLI sessions.
+
+#![cfg(all(feature = "bridge", feature = "testing"))]
+
+use claude_agents_sdk::bridge::{Bridge, METHOD_NOT_FOUND, NOT_INITIALIZED, PROTOCOL_VERSION};
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::ClaudeAgentOptions;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::task::JoinHandle;
+
+/// A host talking to a bridge over in-memory pipes.
+struct Host {
+    input: DuplexStream,
+    output: BufReader<DuplexStream>,
+    bridge: JoinHandle<claude_agents_sdk::Result<()>>,
+    headers: bool,
+}
+
+impl Host {
+    fn start(transport: ScenarioTransport, headers: bool) -> Self {
+        let options = ClaudeAgentOptions::new().with_transport(move |_| Ok(transport.clone()));
+        let (input, bridge_input) = tokio::io::duplex(1 << 16);
+        let (bridge_output, output) = tokio::io::duplex(1 << 16);
+        let bridge = tokio::spawn(Bridge::new(options).serve(bridge_input, bridge_output));
+        Self {
+            input,
+            output: BufReader::new(output),
+            bridge,
+            headers,
+        }
+    }
+
+    async fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        let framed = if self.headers {
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+        } else {
+            format!("{}\n", body)
+        };
+        self.input.write_all(framed.as_bytes()).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut line = String::new();
+        self.output.read_line(&mut line).await.unwrap();
+        if !self.headers {
+            return serde_json::from_str(&line).unwrap();
+        }
+        let length: usize = line
+            .trim()
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        line.clear();
+        self.output.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "\r\n");
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .await;
+        self.receive().await
+    }
+
+    async fn exit(mut self) {
+        self.send(json!({"jsonrpc": "2.0", "method": "exit"})).await;
+        self.bridge.await.unwrap().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_query_streams_messages_then_result() {
+    let transport = Scenario::new()
+        .expect_user("hello")
+        .reply_text("Hi there.")
+        .build();
+    let mut host = Host::start(transport.clone(), true);
+
+    let init = host.request(1, "initialize", json!({})).await;
+    assert_eq!(init["id"], 1);
+    assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+    host.send(json!({"jsonrpc": "2.0", "id": 2, "method": "query", "params": {"prompt": "hello"}}))
+        .await;
+    let mut streamed = Vec::new();
+    let response = loop {
+        let message = host.receive().await;
+        if message["method"] == "stream" {
+            streamed.push(message["params"]["message"]["type"].clone());
+        } else {
+            break message;
+        }
+    };
+    assert_eq!(response["id"], 2);
+    assert_eq!(response["result"]["type"], "result");
+    assert!(streamed.contains(&json!("assistant")));
+    assert_eq!(streamed.last(), Some(&json!("result")));
+    transport.assert_complete();
+
+    let shutdown = host.request(3, "shutdown", Value::Null).await;
+    assert_eq!(shutdown["result"], Value::Null);
+    host.exit().await;
+}
+
+#[tokio::test]
+async fn test_answers_in_line_framing() {
+    let mut host = Host::start(Scenario::new().build(), false);
+
+    let response = host.request(1, "query", json!({"prompt": "hello"})).await;
+    assert_eq!(response["error"]["code"], NOT_INITIALIZED);
+
+    let response = host.request(2, "initialize", json!({"maxTurns": 3})).await;
+    assert!(response["result"].is_object());
+    let response = host.request(3, "initialize", json!({})).await;
+    assert_eq!(response["error"]["message"], "Already initialized");
+    let response = host.request(4, "interrupt", Value::Null).await;
+    assert_eq!(response["result"], Value::Null);
+    host.exit().await;
+}
+
+#[tokio::test]
+async fn test_rejects_bad_messages() {
+    let mut host = Host::start(Scenario::new().build(), false);
+
+    host.input.write_all(b"{not json\n").await.unwrap();
+    let response = host.receive().await;
+    assert_eq!(response["error"]["code"], -32700);
+    assert_eq!(response["id"], Value::Null);
+
+    let response = host.request(1, "frobnicate", json!({})).await;
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+    let response = host.request(2, "initialize", json!({"model": 4})).await;
+    assert_eq!(response["error"]["code"], -32602);
+
+    // The bridge also stops at the end of its input.
+    drop(host.input);
+    host.bridge.await.unwrap().unwrap();
+}
@@ -4,10 +4,10 @@
 //! and message type discrimination tests.
 
 use claude_agents_sdk::{
-    AgentDefinition, AssistantMessage, ClaudeAgentOptions, ContentBlock, Effort, McpServerConfig,
-    McpServersConfig, McpStdioServerConfig, Message, PermissionMode, ResultMessage,
-    SandboxNetworkConfig, SandboxSettings, SettingSource, SystemPromptConfig, SystemPromptPreset,
-    TextBlock, ThinkingConfig, ToolsConfig, ToolsPreset,
+    AgentDefinition, AssistantMessage, BuiltinTool, ClaudeAgentOptions, CliArg, ContentBlock,
+    Effort, McpServerConfig, McpServersConfig, McpStdioServerConfig, Message, PermissionMode,
+    ResultMessage, SandboxNetworkConfig, SandboxSettings, SettingSource, SystemPromptConfig,
+    SystemPromptPreset, TextBlock, ThinkingConfig, ToolsConfig, ToolsPreset, ValidationIssue,
 };
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -265,6 +265,92 @@ fn test_tools_preset_configuration() {
     }
 }
 
+#[test]
+fn test_claude_code_presets() {
+    let options = ClaudeAgentOptions::claude_code();
+    assert_eq!(
+        serde_json::to_value(&options.system_prompt).unwrap(),
+        serde_json::json!({"type": "preset", "preset": "claude_code"})
+    );
+    assert_eq!(
+        serde_json::to_value(&options.tools).unwrap(),
+        serde_json::json!({"type": "preset", "preset": "claude_code"})
+    );
+
+    let prompt = SystemPromptConfig::claude_code_with_append("Be brief.");
+    assert_eq!(
+        serde_json::to_value(&prompt).unwrap(),
+        serde_json::json!({"type": "preset", "preset": "claude_code", "append": "Be brief."})
+    );
+}
+
+#[test]
+fn test_system_prompt_conversions_and_append() {
+    let text = |options: ClaudeAgentOptions| serde_json::to_value(options.system_prompt).unwrap();
+
+    let owned = String::from("Be terse.");
+    assert_eq!(
+        text(ClaudeAgentOptions::new().with_system_prompt(&owned)),
+        "Be terse."
+    );
+    assert_eq!(
+        text(ClaudeAgentOptions::new().with_system_prompt(owned)),
+        "Be terse."
+    );
+    assert_eq!(
+        text(ClaudeAgentOptions::new().with_system_prompt(SystemPromptPreset::claude_code())),
+        serde_json::json!({"type": "preset", "preset": "claude_code"})
+    );
+
+    let options = ClaudeAgentOptions::new()
+        .with_system_prompt("You review code.")
+        .with_system_prompt_append("Be terse.");
+    assert_eq!(text(options), "You review code.\n\nBe terse.");
+
+    let options = ClaudeAgentOptions::claude_code()
+        .with_system_prompt_append("Be terse.")
+        .with_system_prompt_append("Never push.");
+    assert_eq!(
+        text(options),
+        serde_json::json!({
+            "type": "preset",
+            "preset": "claude_code",
+            "append": "Be terse.\n\nNever push."
+        })
+    );
+
+    let options = ClaudeAgentOptions::new().with_system_prompt_append("Be terse.");
+    assert_eq!(text(options), "Be terse.");
+    assert_eq!(
+        serde_json::to_value(SystemPromptConfig::Text(String::new()).append("Hi")).unwrap(),
+        "Hi"
+    );
+}
+
+#[test]
+fn test_builtin_tools() {
+    let options = ClaudeAgentOptions::new()
+        .with_allowed_tools([BuiltinTool::Read, BuiltinTool::WebFetch])
+        .with_tools(vec![BuiltinTool::Bash, BuiltinTool::Grep]);
+
+    assert_eq!(options.allowed_tools, vec!["Read", "WebFetch"]);
+    match &options.tools {
+        Some(ToolsConfig::List(tools)) => assert_eq!(tools, &["Bash", "Grep"]),
+        other => panic!("Expected ToolsConfig::List, got {:?}", other),
+    }
+
+    assert_eq!("bash".parse::<BuiltinTool>().unwrap(), BuiltinTool::Bash);
+    assert_eq!(
+        "NotebookEdit".parse::<BuiltinTool>().unwrap(),
+        BuiltinTool::NotebookEdit
+    );
+    assert!("mcp__docs__search".parse::<BuiltinTool>().is_err());
+    assert_eq!(
+        serde_json::to_value(BuiltinTool::TodoWrite).unwrap(),
+        "TodoWrite"
+    );
+}
+
 #[test]
 fn test_allowed_and_disallowed_tools_both_set() {
     let mut options = ClaudeAgentOptions::new();
@@ -395,6 +481,14 @@ fn test_extra_args_configuration() {
         Some(&Some("value".to_string()))
     );
     assert_eq!(options.extra_args.get("boolean-flag"), Some(&None));
+
+    assert_eq!(
+        CliArg::from_map(&options.extra_args),
+        vec![
+            CliArg::flag("boolean-flag"),
+            CliArg::option("new-flag", "value")
+        ]
+    );
 }
 
 // ============================================================================
@@ -511,6 +605,8 @@ fn test_agents_configuration() {
             prompt: "You are a test agent".to_string(),
             tools: Some(vec!["Read".to_string()]),
             model: None,
+            mcp_servers: None,
+            permission_mode: None,
         },
     );
 
@@ -562,6 +658,8 @@ fn test_message_is_assistant_returns_true_for_assistant_message() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     });
 
     assert!(
@@ -587,6 +685,9 @@ fn test_message_is_result_returns_true_for_result_message() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     assert!(
@@ -608,6 +709,8 @@ fn test_message_as_assistant_returns_some_for_assistant_message() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     });
 
     let asst = msg.as_assistant();
@@ -635,6 +738,9 @@ fn test_message_as_assistant_returns_none_for_non_assistant_message() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     assert!(
@@ -656,6 +762,9 @@ fn test_message_as_result_returns_some_for_result_message() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     let result = msg.as_result();
@@ -679,6 +788,8 @@ fn test_message_as_result_returns_none_for_non_result_message() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     });
 
     assert!(
@@ -852,3 +963,105 @@ fn test_thinking_and_effort_combined() {
     assert!(matches!(options.thinking, Some(ThinkingConfig::Adaptive)));
     assert_eq!(options.effort, Some(Effort::High));
 }
+
+// ============================================================================
+// Validation Tests
+// ============================================================================
+
+#[test]
+fn test_default_options_are_valid() {
+    assert_eq!(ClaudeAgentOptions::new().validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_reports_every_issue() {
+    let mut options = ClaudeAgentOptions::new()
+        .with_allowed_tools(vec!["Read".to_string(), "Bash".to_string()])
+        .with_max_turns(0)
+        .with_cwd("/nonexistent/dir/for/validation");
+    options.disallowed_tools = vec!["Bash".to_string()];
+    options.max_budget_usd = Some(-1.0);
+    options.continue_conversation = true;
+    options.resume = Some("session-1".to_string());
+    options.cli_path = Some(PathBuf::from("/nonexistent/claude"));
+
+    let issues = options.validate().unwrap_err();
+    #[allow(unused_mut)]
+    let mut expected = vec![
+        ValidationIssue::ToolAllowedAndDisallowed {
+            tool: "Bash".to_string(),
+        },
+        ValidationIssue::InvalidBudget {
+            max_budget_usd: -1.0,
+        },
+        ValidationIssue::ZeroMaxTurns,
+        ValidationIssue::ResumeWithContinue {
+            session_id: "session-1".to_string(),
+        },
+        ValidationIssue::CwdNotFound {
+            path: PathBuf::from("/nonexistent/dir/for/validation"),
+        },
+    ];
+    // Only the subprocess transport looks for the CLI.
+    #[cfg(feature = "subprocess")]
+    expected.push(ValidationIssue::CliNotFound {
+        path: PathBuf::from("/nonexistent/claude"),
+    });
+    assert_eq!(issues, expected);
+}
+
+#[test]
+fn test_validate_misspelled_tool() {
+    let mut options = ClaudeAgentOptions::new().with_allowed_tools(["bash", "mcp__docs__search"]);
+    options.disallowed_tools = vec!["webfetch".to_string()];
+
+    let issues = options.validate().unwrap_err();
+    assert_eq!(
+        issues,
+        vec![
+            ValidationIssue::MisspelledTool {
+                tool: "bash".to_string(),
+                suggestion: BuiltinTool::Bash,
+            },
+            ValidationIssue::MisspelledTool {
+                tool: "webfetch".to_string(),
+                suggestion: BuiltinTool::WebFetch,
+            },
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "Tool 'bash' matches no tool; did you mean 'Bash'?"
+    );
+}
+
+#[test]
+fn test_zero_max_turns_alone_is_valid() {
+    let options = ClaudeAgentOptions::new().with_max_turns(0);
+    assert_eq!(options.validate(), Ok(()));
+}
+
+#[tokio::test]
+async fn test_connect_runs_validation() {
+    let options = ClaudeAgentOptions::new().with_cwd("/nonexistent/dir/for/validation");
+    let mut client = claude_agents_sdk::ClaudeClient::new(Some(options));
+    let err = client.connect().await.unwrap_err();
+    assert_eq!(err.kind(), claude_agents_sdk::ErrorKind::Configuration);
+    assert!(err
+        .to_string()
+        .contains("Working directory '/nonexistent/dir/for/validation' does not exist"));
+}
+
+#[tokio::test]
+async fn test_skip_validation_still_rejects_conflicting_handlers() {
+    let mut options = ClaudeAgentOptions::new()
+        .with_skip_validation(true)
+        .with_can_use_tool(|_, _, _| async { claude_agents_sdk::PermissionResult::allow() });
+    options.permission_prompt_tool_name = Some("prompt".to_string());
+
+    let mut client = claude_agents_sdk::ClaudeClient::new(Some(options));
+    let err = client.connect().await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Cannot specify both 'can_use_tool' and 'permission_prompt_tool_name'"));
+}
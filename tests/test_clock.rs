@@ -0,0 +1,82 @@
+//! Tests for driving client timeouts and pings with an injected clock.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::clock::MockClock;
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{ClaudeClient, ClaudeSDKError};
+use std::time::Duration;
+
+/// A fake CLI that leaves pings unanswered.
+fn fake_cli() -> FakeCli {
+    FakeCli::builder()
+        .ignore_control("mcp_status")
+        .build()
+        .unwrap()
+}
+
+async fn connected_client(cli: &FakeCli, clock: &MockClock) -> ClaudeClient {
+    let mut options = cli.options().with_clock(clock.clone());
+    options.control_timeout = Some(Duration::from_secs(30));
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+    client
+}
+
+#[tokio::test]
+async fn test_control_timeout_follows_injected_clock() {
+    let cli = fake_cli();
+    let clock = MockClock::new();
+    let mut client = connected_client(&cli, &clock).await;
+
+    let handle = client.control_handle().unwrap();
+    let ping = tokio::spawn(async move { handle.ping().await });
+
+    // Real time passes, but the request only times out once the clock says so.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!ping.is_finished());
+
+    while !ping.is_finished() {
+        clock.advance(Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(clock.elapsed() < Duration::from_secs(60), "never timed out");
+    }
+    assert!(clock.elapsed() >= Duration::from_secs(30));
+    match ping.await.unwrap() {
+        Err(ClaudeSDKError::ControlRequestTimedOut { duration_ms, .. }) => {
+            assert_eq!(duration_ms, 30_000)
+        }
+        other => panic!("Expected ControlRequestTimedOut, got {:?}", other),
+    }
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pinger_waits_for_injected_clock() {
+    let cli = fake_cli();
+    let clock = MockClock::new();
+    let mut client = connected_client(&cli, &clock).await;
+
+    let pinger = client.start_pinger(Duration::from_secs(5)).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(pinger.history().is_empty());
+
+    for _ in 0..10 {
+        clock.advance(Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let history = pinger.history();
+    assert_eq!(history.len(), 2, "{:?}", history);
+    assert!(history.iter().all(|sample| sample.rtt.is_none()));
+    assert_eq!(
+        history[1]
+            .sent_at
+            .duration_since(history[0].sent_at)
+            .unwrap(),
+        Duration::from_secs(5)
+    );
+
+    pinger.stop();
+    client.disconnect().await.unwrap();
+}
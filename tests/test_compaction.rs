@@ -0,0 +1,123 @@
+//! Tests for automatic context compaction.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{AutoCompactPolicy, ClaudeClient, EventBus, Message, SdkEvent};
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+
+/// A turn with a 5000-token assistant message.
+fn large_turn() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "assistant",
+            "message": {
+                "model": "m",
+                "content": [{"type": "text", "text": "ok"}],
+                "usage": {"input_tokens": 200, "cache_read_input_tokens": 4800, "output_tokens": 5}
+            }
+        }),
+        result(),
+    ]
+}
+
+/// The turn answering `/compact`.
+fn compact_turn() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "system",
+            "subtype": "compact_boundary",
+            "session_id": "s1",
+            "compact_metadata": {"trigger": "manual", "pre_tokens": 5000}
+        }),
+        result(),
+    ]
+}
+
+fn result() -> Value {
+    json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 1,
+        "duration_api_ms": 1,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "s1"
+    })
+}
+
+/// Messages up to and including the next result.
+async fn turn(client: &mut ClaudeClient) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut stream = client.receive_messages();
+    while let Some(message) = stream.next().await {
+        let message = message.unwrap();
+        let done = matches!(message, Message::Result(_));
+        messages.push(message);
+        if done {
+            break;
+        }
+    }
+    messages
+}
+
+#[tokio::test]
+async fn test_compacts_before_next_prompt_over_threshold() {
+    let cli = FakeCli::new(vec![large_turn(), compact_turn(), large_turn()]).unwrap();
+    let bus = EventBus::default();
+    let mut events = bus.subscribe();
+    let options = cli
+        .options()
+        .with_event_bus(bus)
+        .with_auto_compact(AutoCompactPolicy::new(4000).with_instructions("keep the todo list"));
+
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+
+    client.query("first").await.unwrap();
+    assert_eq!(turn(&mut client).await.len(), 2);
+
+    client.query("second").await.unwrap();
+    let messages = turn(&mut client).await;
+    assert_eq!(messages.len(), 2, "{:?}", messages);
+    assert!(matches!(messages[0], Message::Assistant(_)));
+
+    assert_eq!(
+        cli.prompts(),
+        ["first", "/compact keep the todo list", "second"]
+    );
+
+    let compacted = loop {
+        match events.recv().await.unwrap() {
+            SdkEvent::AutoCompacted {
+                context_tokens,
+                trigger_tokens,
+                is_error,
+            } => break (context_tokens, trigger_tokens, is_error),
+            _ => continue,
+        }
+    };
+    assert_eq!(compacted, (5000, 4000, false));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_no_compaction_under_threshold() {
+    let cli = FakeCli::new(vec![large_turn(), large_turn()]).unwrap();
+    let options = cli
+        .options()
+        .with_auto_compact(AutoCompactPolicy::new(100_000));
+
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+    for prompt in ["first", "second"] {
+        client.query(prompt).await.unwrap();
+        turn(&mut client).await;
+    }
+
+    assert_eq!(cli.prompts(), ["first", "second"]);
+
+    client.disconnect().await.unwrap();
+}
@@ -346,6 +346,8 @@ async fn test_concurrent_message_creation() {
                     model: "claude-3".to_string(),
                     parent_tool_use_id: None,
                     error: None,
+                    usage: None,
+                    rate_limit: None,
                 });
 
                 if let Message::Assistant(asst) = msg {
@@ -371,6 +373,8 @@ async fn test_shared_message_reading() {
                 model: "claude-3".to_string(),
                 parent_tool_use_id: None,
                 error: None,
+                usage: None,
+                rate_limit: None,
             }))
         })
         .collect();
@@ -541,6 +545,9 @@ async fn test_concurrent_result_message_access() {
         usage: None,
         result: Some("test result".to_string()),
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     let handles: Vec<_> = (0..100)
@@ -581,6 +588,8 @@ async fn test_concurrent_message_channel() {
                         model: "claude-3".to_string(),
                         parent_tool_use_id: None,
                         error: None,
+                        usage: None,
+                        rate_limit: None,
                     });
                     tx.send(msg).await.unwrap();
                 }
@@ -676,6 +685,8 @@ async fn test_high_concurrency_message_creation() {
                     model: "claude-3".to_string(),
                     parent_tool_use_id: None,
                     error: None,
+                    usage: None,
+                    rate_limit: None,
                 });
 
                 if let Message::Assistant(asst) = &msg {
@@ -693,3 +704,105 @@ async fn test_high_concurrency_message_creation() {
 
     assert_eq!(messages.len(), 1000);
 }
+
+// ============================================================================
+// Pipelined Control Request Tests
+// ============================================================================
+
+/// A fake CLI that answers `initialize`, reads `count` control requests,
+/// then answers them in reverse order, echoing each request's model.
+#[cfg(all(unix, feature = "subprocess", feature = "testing"))]
+fn reversing_cli(count: usize) -> claude_agents_sdk::testing::FakeCli {
+    let script = format!(
+        r#"respond() {{
+    echo "{{\"type\":\"control_response\",\"response\":{{\"subtype\":\"success\",\"request_id\":\"$1\",\"response\":{{\"model\":\"$2\"}}}}}}"
+}}
+request_id() {{
+    echo "$1" | sed 's/.*"request_id":"\([^"]*\)".*/\1/'
+}}
+read line
+respond "$(request_id "$line")" none
+pending=""
+i=0
+while [ $i -lt {count} ]; do
+    read line
+    case "$line" in
+        *'"model"'*) model=$(echo "$line" | sed 's/.*"model":"\([^"]*\)".*/\1/') ;;
+        *) model=none ;;
+    esac
+    pending="$(request_id "$line"):$model $pending"
+    i=$((i + 1))
+done
+for entry in $pending; do
+    respond "${{entry%%:*}}" "${{entry#*:}}"
+done
+cat > /dev/null
+"#
+    );
+    claude_agents_sdk::testing::FakeCli::from_script(&script).unwrap()
+}
+
+#[cfg(all(unix, feature = "subprocess", feature = "testing"))]
+async fn connect_reversing(
+    cli: &claude_agents_sdk::testing::FakeCli,
+) -> claude_agents_sdk::ClaudeClient {
+    let options = cli
+        .options()
+        .with_control_timeout(std::time::Duration::from_secs(10));
+    let mut client = claude_agents_sdk::ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+    client
+}
+
+#[cfg(all(unix, feature = "subprocess", feature = "testing"))]
+#[tokio::test]
+async fn test_pipelined_control_requests_match_out_of_order_responses() {
+    use claude_agents_sdk::ControlRequestPayload;
+
+    const COUNT: usize = 50;
+    let cli = reversing_cli(COUNT);
+    let mut client = connect_reversing(&cli).await;
+    let control = client.control_handle().unwrap();
+
+    let tasks: Vec<_> = (0..COUNT)
+        .map(|i| {
+            let control = control.clone();
+            tokio::spawn(async move {
+                let model = format!("model-{}", i);
+                let response = control
+                    .request(ControlRequestPayload::SetModel {
+                        model: Some(model.clone()),
+                    })
+                    .await
+                    .unwrap();
+                (model, response)
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        let (model, response) = task.await.unwrap();
+        assert_eq!(response["model"], model.as_str());
+    }
+    client.disconnect().await.unwrap();
+}
+
+#[cfg(all(unix, feature = "subprocess", feature = "testing"))]
+#[tokio::test]
+async fn test_interrupt_while_set_model_pending() {
+    let cli = reversing_cli(2);
+    let mut client = connect_reversing(&cli).await;
+    let control = client.control_handle().unwrap();
+
+    // The CLI answers only once both requests arrive, so `set_model` cannot
+    // complete unless the interrupt is sent while it is pending.
+    let set_model = tokio::spawn({
+        let control = control.clone();
+        async move { control.set_model("claude-opus-4").await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    client.interrupt().await.unwrap();
+    set_model.await.unwrap().unwrap();
+
+    client.disconnect().await.unwrap();
+}
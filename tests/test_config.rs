@@ -0,0 +1,157 @@
+//! Tests for loading options from config files.
+
+use std::sync::Arc;
+
+use claude_agents_sdk::{
+    ClaudeAgentOptions, HookCallback, HookEvent, HookOutput, HookRegistry, McpServerConfig,
+    McpServersConfig, PermissionMode, SystemPromptConfig,
+};
+
+const TOML_CONFIG: &str = r#"
+model = "claude-sonnet-4-5"
+permission_mode = "acceptEdits"
+allowed_tools = ["Read", "Grep"]
+disallowed_tools = ["WebFetch"]
+system_prompt = "You are terse."
+max_turns = 12
+cwd = "project"
+
+[env]
+LOG_LEVEL = "debug"
+
+[proxy]
+https_proxy = "http://proxy.corp:3128"
+no_proxy = ["localhost"]
+
+[sandbox]
+enabled = true
+excludedCommands = ["docker"]
+
+[mcp_servers.docs]
+command = "docs-mcp"
+args = ["--stdio"]
+
+[mcp_servers.remote]
+type = "http"
+url = "https://mcp.example.com"
+
+[[hooks.PreToolUse]]
+matcher = "Bash"
+hooks = ["audit"]
+timeout = 5.0
+"#;
+
+fn noop_hook() -> HookCallback {
+    Arc::new(|_input, _tool_use_id, _context| Box::pin(async { HookOutput::default() }))
+}
+
+fn write_config(name: &str, contents: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn test_from_toml_file() {
+    let (dir, path) = write_config("claude-agent.toml", TOML_CONFIG);
+    let hooks = HookRegistry::new().with_hook("audit", noop_hook());
+    let options = ClaudeAgentOptions::from_file_with_hooks(&path, &hooks).unwrap();
+
+    assert_eq!(options.model.as_deref(), Some("claude-sonnet-4-5"));
+    assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
+    assert_eq!(options.allowed_tools, vec!["Read", "Grep"]);
+    assert_eq!(options.disallowed_tools, vec!["WebFetch"]);
+    assert!(matches!(
+        options.system_prompt,
+        Some(SystemPromptConfig::Text(ref text)) if text == "You are terse."
+    ));
+    assert_eq!(options.max_turns, Some(12));
+    assert_eq!(options.cwd, Some(dir.path().join("project")));
+    assert_eq!(options.env["LOG_LEVEL"], "debug");
+    let proxy = options.proxy.unwrap();
+    assert_eq!(proxy.https_proxy.as_deref(), Some("http://proxy.corp:3128"));
+    assert_eq!(proxy.no_proxy, vec!["localhost"]);
+
+    let sandbox = options.sandbox.unwrap();
+    assert!(sandbox.enabled);
+    assert_eq!(sandbox.excluded_commands, vec!["docker"]);
+
+    let McpServersConfig::Map(servers) = &options.mcp_servers else {
+        panic!("expected a server map");
+    };
+    assert!(matches!(servers["docs"], McpServerConfig::Stdio(ref s) if s.command == "docs-mcp"));
+    assert!(matches!(servers["remote"], McpServerConfig::Http(_)));
+
+    let pre_tool_use = &options.hooks.unwrap()[&HookEvent::PreToolUse];
+    assert_eq!(pre_tool_use.len(), 1);
+    assert_eq!(pre_tool_use[0].matcher.as_deref(), Some("Bash"));
+    assert_eq!(pre_tool_use[0].hooks.len(), 1);
+    assert_eq!(pre_tool_use[0].timeout, Some(5.0));
+}
+
+#[test]
+fn test_from_json_file() {
+    let (_dir, path) = write_config(
+        "agent.json",
+        r#"{"model": "m", "permission_mode": "bypassPermissions", "tools": ["Read"], "max_budget_usd": 1.5,
+            "betas": ["context-1m-2025-08-07"], "feature_flags": {"DISABLE_AUTOUPDATER": true}}"#,
+    );
+    let options = ClaudeAgentOptions::from_file(&path).unwrap();
+
+    assert_eq!(options.model.as_deref(), Some("m"));
+    assert_eq!(
+        options.permission_mode,
+        Some(PermissionMode::BypassPermissions)
+    );
+    assert_eq!(options.max_budget_usd, Some(1.5));
+    assert_eq!(options.betas, vec![claude_agents_sdk::SdkBeta::Context1m]);
+    assert!(options.feature_flags["DISABLE_AUTOUPDATER"]);
+    assert!(options.hooks.is_none());
+}
+
+#[test]
+fn test_unregistered_hook_is_an_error() {
+    let (_dir, path) = write_config("claude-agent.toml", TOML_CONFIG);
+    let err = ClaudeAgentOptions::from_file(&path).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Hook 'audit' for PreToolUse is not registered"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_invalid_config_names_the_file() {
+    let (_dir, path) = write_config("claude-agent.toml", "max_turns = \"many\"");
+    let err = ClaudeAgentOptions::from_file(&path).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("claude-agent.toml"), "{}", message);
+    assert!(message.contains("Invalid options"), "{}", message);
+
+    assert!(ClaudeAgentOptions::from_file("missing.toml").is_err());
+    assert!(ClaudeAgentOptions::from_file("settings.ini").is_err());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_from_yaml_file() {
+    let (_dir, path) = write_config(
+        "agent.yaml",
+        "model: m\nallowed_tools: [Read]\nhooks:\n  Stop:\n    - hooks: [audit]\n",
+    );
+    let hooks = HookRegistry::new().with_hook("audit", noop_hook());
+    let options = ClaudeAgentOptions::from_file_with_hooks(&path, &hooks).unwrap();
+
+    assert_eq!(options.allowed_tools, vec!["Read"]);
+    assert_eq!(options.hooks.unwrap()[&HookEvent::Stop].len(), 1);
+}
+
+#[cfg(not(feature = "yaml"))]
+#[test]
+fn test_yaml_requires_feature() {
+    let (_dir, path) = write_config("agent.yaml", "model: m\n");
+    let err = ClaudeAgentOptions::from_file(&path).unwrap_err();
+    assert!(err.to_string().contains("`yaml` feature"), "{}", err);
+}
@@ -0,0 +1,214 @@
+//! Tests for the fake CLI executable in `testing`.
+
+#![cfg(all(unix, feature = "testing", feature = "subprocess"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{query_all, query_result, ClaudeClient, Message};
+use serde_json::json;
+
+fn result(text: &str) -> serde_json::Value {
+    json!({
+        "type": "result",
+        "subtype": "success",
+        "is_error": false,
+        "duration_ms": 10,
+        "duration_api_ms": 5,
+        "num_turns": 1,
+        "session_id": "fake-session",
+        "result": text
+    })
+}
+
+fn text_turn(text: &str) -> Vec<serde_json::Value> {
+    vec![
+        json!({
+            "type": "assistant",
+            "message": {"model": "fake", "content": [{"type": "text", "text": text}]},
+            "session_id": "fake-session"
+        }),
+        result(text),
+    ]
+}
+
+#[tokio::test]
+async fn test_query_against_fake_cli() {
+    let cli = FakeCli::with_text_response("Hello from the fake").unwrap();
+    let messages = query_all("Hi", Some(cli.options())).await.unwrap();
+
+    match &messages[1] {
+        Message::Assistant(assistant) => assert_eq!(assistant.text(), "Hello from the fake"),
+        other => panic!("Expected Assistant, got {:?}", other),
+    }
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+    assert_eq!(cli.prompts(), vec!["Hi"]);
+    assert!(cli.control_requests().contains(&"initialize".to_string()));
+
+    let args = cli.args();
+    assert!(args.contains(&"--output-format".to_string()), "{:?}", args);
+    assert!(args.contains(&"stream-json".to_string()), "{:?}", args);
+}
+
+#[tokio::test]
+async fn test_client_multi_turn_against_fake_cli() {
+    let cli = FakeCli::new(vec![text_turn("first"), text_turn("second")]).unwrap();
+    let mut client = ClaudeClient::new(Some(cli.options().with_model("fake-model")));
+    client.connect().await.unwrap();
+
+    let mut replies = Vec::new();
+    for prompt in ["one", "two"] {
+        client.query(prompt).await.unwrap();
+        let (text, _) = client.receive_response().await.unwrap();
+        replies.push(text);
+    }
+    client.disconnect().await.unwrap();
+
+    assert_eq!(replies, vec!["first", "second"]);
+    assert_eq!(cli.prompts(), vec!["one", "two"]);
+    assert!(cli.args().contains(&"fake-model".to_string()));
+}
+
+#[tokio::test]
+async fn test_fake_cli_reports_version() {
+    let cli = FakeCli::new(Vec::new()).unwrap();
+    let output = tokio::process::Command::new(cli.path())
+        .arg("-v")
+        .output()
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "2.0.14 (Claude Code)"
+    );
+}
+
+#[tokio::test]
+async fn test_query_shared_against_fake_cli() {
+    use claude_agents_sdk::query_shared;
+    use tokio_stream::StreamExt;
+
+    let cli = FakeCli::with_text_response("shared").unwrap();
+    let mut stream = query_shared("Hi", Some(cli.options())).await.unwrap();
+    let mut kept = Vec::new();
+    while let Some(message) = stream.next().await {
+        let message = message.unwrap();
+        kept.push(std::sync::Arc::clone(&message));
+        if message.is_result() {
+            break;
+        }
+    }
+
+    assert!(kept.iter().any(|m| m.to_string() == "shared"));
+    assert!(kept.last().unwrap().is_result());
+}
+
+#[tokio::test]
+async fn test_lazy_parse_against_fake_cli() {
+    let cli = FakeCli::with_text_response("lazy").unwrap();
+    let options = cli.options().with_lazy_parse(true);
+    let messages = query_all("Hi", Some(options)).await.unwrap();
+
+    let assistant = messages.iter().find(|m| m.is_assistant()).unwrap();
+    assert!(matches!(assistant, Message::Lazy(lazy) if !lazy.is_parsed()));
+    assert_eq!(assistant.as_assistant().unwrap().text(), "lazy");
+
+    match messages.last() {
+        Some(Message::Result(result)) => assert!(result.stats.is_some()),
+        other => panic!("Expected Result, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_lazy_parse_response_text_against_fake_cli() {
+    let cli = FakeCli::new(vec![text_turn("first lazy"), text_turn("second lazy")]).unwrap();
+    let mut client = ClaudeClient::new(Some(cli.options().with_lazy_parse(true)));
+    client.connect().await.unwrap();
+    client.query("one").await.unwrap();
+    let (text, _) = client.receive_response().await.unwrap();
+    client.disconnect().await.unwrap();
+    assert_eq!(text, "first lazy");
+
+    let cli = FakeCli::with_text_response("one-shot lazy").unwrap();
+    let (text, _) = query_result("Hi", Some(cli.options().with_lazy_parse(true)))
+        .await
+        .unwrap();
+    assert_eq!(text, "one-shot lazy");
+}
+
+#[tokio::test]
+async fn test_fake_cli_answers_control_requests_as_configured() {
+    let cli = FakeCli::builder()
+        .reply_to_control("mcp_status", json!({"mcpServers": []}))
+        .fail_control("set_model", "no such model")
+        .ignore_control("interrupt")
+        .build()
+        .unwrap();
+    let options = cli
+        .options()
+        .with_control_timeout(std::time::Duration::from_millis(200));
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+
+    assert!(client.mcp_servers().await.unwrap().is_empty());
+    let error = client.set_model("fake-model").await.unwrap_err();
+    assert!(error.to_string().contains("no such model"), "{}", error);
+    assert!(client.interrupt().await.is_err());
+    client.disconnect().await.unwrap();
+
+    assert_eq!(
+        cli.control_requests(),
+        vec!["initialize", "mcp_status", "set_model", "interrupt"]
+    );
+}
+
+#[tokio::test]
+async fn test_fake_cli_exits_after_turn() {
+    use tokio::io::AsyncWriteExt;
+
+    let cli = FakeCli::builder()
+        .turn(text_turn("once"))
+        .exit_after_turn(1)
+        .build()
+        .unwrap();
+    let mut child = tokio::process::Command::new(cli.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(b"{\"type\":\"user\",\"message\":{\"content\":\"Hi\"}}\n")
+        .await
+        .unwrap();
+
+    // Stdin stays open; the fake exits on its own.
+    let status = tokio::time::timeout(std::time::Duration::from_secs(5), child.wait())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(status.success());
+    drop(stdin);
+}
+
+#[tokio::test]
+async fn test_fake_cli_logs_each_run() {
+    let cli = FakeCli::builder().version("2.1.0").build().unwrap();
+    for model in ["first", "second"] {
+        query_all("Hi", Some(cli.options().with_model(model)))
+            .await
+            .unwrap();
+    }
+
+    let runs = cli.runs();
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].contains(&"first".to_string()), "{:?}", runs);
+    assert_eq!(cli.args(), runs[1]);
+    let output = tokio::process::Command::new(cli.path())
+        .arg("--version")
+        .output()
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "2.1.0 (Claude Code)"
+    );
+}
@@ -0,0 +1,61 @@
+//! Tests for forking a session into a second client.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::ClaudeClient;
+use serde_json::json;
+
+/// A fake CLI that answers a prompt in session `s1`.
+fn fake_cli() -> FakeCli {
+    FakeCli::new(vec![vec![
+        json!({"type": "system", "subtype": "init", "session_id": "s1"}),
+        json!({
+            "type": "result",
+            "subtype": "success",
+            "duration_ms": 1,
+            "duration_api_ms": 1,
+            "is_error": false,
+            "num_turns": 1,
+            "session_id": "s1"
+        }),
+    ]])
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_fork_resumes_session_with_fork_flag() {
+    let cli = fake_cli();
+    let options = cli.options().with_model("test-model");
+
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+    assert_eq!(client.session_id(), None);
+    assert!(client.fork().await.is_err());
+
+    client.query("Hello").await.unwrap();
+    client.receive_response().await.unwrap();
+    assert_eq!(client.session_id().as_deref(), Some("s1"));
+
+    let mut fork = client.fork().await.unwrap();
+    assert!(fork.is_connected());
+
+    let runs: Vec<String> = cli.runs().iter().map(|args| args.join(" ")).collect();
+    assert_eq!(runs.len(), 2);
+    assert!(!runs[0].contains("--resume"));
+    assert!(runs[1].contains("--resume s1"), "{}", runs[1]);
+    assert!(runs[1].contains("--fork-session"), "{}", runs[1]);
+    assert!(runs[1].contains("--model test-model"), "{}", runs[1]);
+
+    fork.disconnect().await.unwrap();
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fork_requires_connection() {
+    let client = ClaudeClient::new(None);
+    let Err(err) = client.fork().await else {
+        panic!("fork of a disconnected client succeeded");
+    };
+    assert!(err.to_string().contains("not connected"), "{}", err);
+}
@@ -0,0 +1,56 @@
+//! Tests for the aggregated health check.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{ClaudeClient, McpServerState};
+use serde_json::json;
+
+/// A fake CLI reporting version 2.1.0 and one failed MCP server.
+fn fake_cli() -> FakeCli {
+    FakeCli::builder()
+        .version("2.1.0")
+        .reply_to_control(
+            "mcp_status",
+            json!({"mcpServers": [
+                {"name": "docs", "status": "connected"},
+                {"name": "broken", "status": "failed", "error": "spawn ENOENT"}
+            ]}),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_health_of_connected_client() {
+    let cli = fake_cli();
+    let mut client = ClaudeClient::new(Some(cli.options()));
+    client.connect().await.unwrap();
+
+    let health = client.health().await;
+    assert!(health.is_healthy(), "{:?}", health);
+    assert_eq!(health.cli_version.as_deref(), Some("2.1.0"));
+    assert!(health.protocol_rtt.is_some());
+    assert!(health.last_activity.is_some());
+    assert_eq!(health.mcp_servers.len(), 2);
+    assert_eq!(health.mcp_servers[0].state, McpServerState::Connected);
+    let failed: Vec<&str> = health
+        .failed_mcp_servers()
+        .map(|server| server.name.as_str())
+        .collect();
+    assert_eq!(failed, ["broken"]);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_health_of_disconnected_client() {
+    let cli = fake_cli();
+    let client = ClaudeClient::new(Some(cli.options()));
+    let health = client.health().await;
+    assert!(!health.is_healthy());
+    assert!(!health.protocol_ok);
+    assert!(!health.subprocess_alive);
+    assert!(health.mcp_servers.is_empty());
+    assert!(health.cli_version.is_some());
+}
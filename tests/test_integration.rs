@@ -16,6 +16,8 @@ fn create_assistant_message(text: &str) -> AssistantMessage {
         model: "claude-opus-4-1-20250805".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     }
 }
 
@@ -32,6 +34,9 @@ fn create_result_message(session_id: &str, cost: f64) -> ResultMessage {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     }
 }
 
@@ -73,6 +78,8 @@ fn test_query_with_tool_use() {
         model: "claude-opus-4-1-20250805".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     assert_eq!(assistant.content.len(), 2);
@@ -124,6 +131,9 @@ fn test_budget_exceeded_result() {
         })),
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     };
 
     assert_eq!(result.subtype, "error_max_budget_usd");
@@ -247,6 +257,8 @@ fn test_assistant_message_multiple_content_blocks() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     // text() should concatenate all text blocks
@@ -0,0 +1,60 @@
+//! Tests for keepalive pings and RTT measurement.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::ClaudeClient;
+use serde_json::json;
+use std::time::Duration;
+
+/// A fake CLI that answers pings with no MCP servers.
+fn fake_cli() -> FakeCli {
+    FakeCli::builder()
+        .reply_to_control("mcp_status", json!({"mcpServers": []}))
+        .build()
+        .unwrap()
+}
+
+async fn connected_client(cli: &FakeCli) -> ClaudeClient {
+    let mut client = ClaudeClient::new(Some(cli.options()));
+    client.connect().await.unwrap();
+    client
+}
+
+#[tokio::test]
+async fn test_ping_measures_round_trip() {
+    let cli = fake_cli();
+    let mut client = connected_client(&cli).await;
+
+    let rtt = client.ping().await.unwrap();
+    assert!(rtt < Duration::from_secs(5), "{:?}", rtt);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pinger_records_history() {
+    let cli = fake_cli();
+    let mut client = connected_client(&cli).await;
+
+    let pinger = client.start_pinger(Duration::from_millis(200)).unwrap();
+    tokio::time::sleep(Duration::from_millis(700)).await;
+    pinger.stop();
+
+    let history = pinger.history();
+    assert!(history.len() >= 2, "{:?}", history);
+    assert_eq!(pinger.latest(), history.last().copied());
+    assert!(pinger.mean_rtt().is_some());
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0].sent_at <= pair[1].sent_at));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ping_requires_connection() {
+    let client = ClaudeClient::new(None);
+    assert!(client.ping().await.is_err());
+    assert!(client.start_pinger(Duration::from_secs(1)).is_err());
+}
@@ -0,0 +1,151 @@
+//! Tests for keeping a session warm and reconnecting after idle timeouts.
+
+#![cfg(feature = "testing")]
+
+use claude_agents_sdk::clock::MockClock;
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::{ClaudeAgentOptions, SessionKeeper};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Options connecting to each scenario in turn, recording the session each
+/// connection resumed.
+fn options(
+    scenarios: Vec<ScenarioTransport>,
+    clock: &MockClock,
+) -> (ClaudeAgentOptions, Arc<Mutex<Vec<Option<String>>>>) {
+    let scenarios = Mutex::new(scenarios.into_iter());
+    let resumed = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&resumed);
+    let options = ClaudeAgentOptions::new()
+        .with_clock(clock.clone())
+        .with_transport(move |options| {
+            seen.lock().unwrap().push(options.resume.clone());
+            Ok(scenarios
+                .lock()
+                .unwrap()
+                .next()
+                .expect("unexpected connect"))
+        });
+    (options, resumed)
+}
+
+async fn wait_for_disconnect(keeper: &SessionKeeper) {
+    for _ in 0..100 {
+        if !keeper.is_connected().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("keeper never disconnected");
+}
+
+#[tokio::test]
+async fn test_disconnects_when_idle_and_resumes() {
+    let first = Scenario::new()
+        .expect_user("Hello")
+        .reply_text("Hi!")
+        .build();
+    let second = Scenario::new()
+        .expect_user("Still there?")
+        .reply_text("Yes.")
+        .build();
+    let clock = MockClock::new();
+    let (options, resumed) = options(vec![first.clone(), second.clone()], &clock);
+    let keeper = SessionKeeper::new(options, Duration::from_secs(60));
+    assert!(!keeper.is_connected().await);
+
+    let (text, _) = keeper.query("Hello").await.unwrap();
+    assert_eq!(text, "Hi!");
+    assert!(keeper.is_connected().await);
+    let session_id = keeper.session_id().await.unwrap();
+
+    // Not idle long enough yet.
+    clock.advance(Duration::from_secs(30));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(keeper.is_connected().await);
+
+    clock.advance(Duration::from_secs(31));
+    wait_for_disconnect(&keeper).await;
+    assert_eq!(keeper.session_id().await, Some(session_id.clone()));
+
+    let (text, _) = keeper.query("Still there?").await.unwrap();
+    assert_eq!(text, "Yes.");
+    assert_eq!(*resumed.lock().unwrap(), [None, Some(session_id)]);
+    first.assert_complete();
+    second.assert_complete();
+}
+
+#[tokio::test]
+async fn test_held_client_is_not_disconnected() {
+    let scenario = Scenario::new()
+        .expect_user("Slow task")
+        .reply_text("Done.")
+        .build();
+    let clock = MockClock::new();
+    let (options, resumed) = options(vec![scenario.clone()], &clock);
+    let keeper = SessionKeeper::new(options, Duration::from_secs(60));
+
+    let mut client = keeper.client().await.unwrap();
+    clock.advance(Duration::from_secs(120));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    client.query("Slow task").await.unwrap();
+    let (text, _) = client.receive_response().await.unwrap();
+    assert_eq!(text, "Done.");
+    drop(client);
+
+    // The timer restarted when the client was released.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(keeper.is_connected().await);
+    keeper.disconnect().await.unwrap();
+    assert!(!keeper.is_connected().await);
+    assert_eq!(resumed.lock().unwrap().len(), 1);
+    scenario.assert_complete();
+}
+
+/// A CLI that answers one prompt, then exits.
+#[cfg(all(unix, feature = "subprocess"))]
+fn one_shot_cli() -> claude_agents_sdk::testing::FakeCli {
+    use serde_json::json;
+
+    claude_agents_sdk::testing::FakeCli::builder()
+        .turn(vec![
+            json!({
+                "type": "assistant",
+                "message": {"model": "m", "content": [{"type": "text", "text": "Hi!"}]},
+                "session_id": "sess-1"
+            }),
+            json!({
+                "type": "result",
+                "subtype": "success",
+                "is_error": false,
+                "duration_ms": 1,
+                "duration_api_ms": 1,
+                "num_turns": 1,
+                "session_id": "sess-1"
+            }),
+        ])
+        .exit_after_turn(1)
+        .build()
+        .unwrap()
+}
+
+#[cfg(all(unix, feature = "subprocess"))]
+#[tokio::test]
+async fn test_reconnects_after_cli_exits() {
+    let cli = one_shot_cli();
+    let keeper = SessionKeeper::new(cli.options(), Duration::from_secs(300));
+
+    let (text, _) = keeper.query("Hello").await.unwrap();
+    assert_eq!(text, "Hi!");
+    // The CLI exited after answering; the keeper notices.
+    wait_for_disconnect(&keeper).await;
+    assert_eq!(keeper.session_id().await.as_deref(), Some("sess-1"));
+
+    let (text, _) = keeper.query("Again").await.unwrap();
+    assert_eq!(text, "Hi!");
+    let runs: Vec<String> = cli.runs().iter().map(|args| args.join(" ")).collect();
+    assert_eq!(runs.len(), 2, "{:?}", runs);
+    assert!(!runs[0].contains("--resume"));
+    assert!(runs[1].contains("--resume sess-1"), "{}", runs[1]);
+}
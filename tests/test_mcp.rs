@@ -5,9 +5,13 @@
 #![cfg(feature = "mcp")]
 
 use claude_agents_sdk::mcp::{
-    create_sdk_mcp_server, McpSdkServerConfig, SdkMcpTool, ToolContent, ToolInputSchema, ToolResult,
+    create_sdk_mcp_server, EmbeddedResource, LogLevel, McpSdkServerConfig, SdkMcpServer,
+    SdkMcpTool, ToolContent, ToolContext, ToolError, ToolInputSchema, ToolResult,
+    MCP_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
 };
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 // ============================================================================
 // ToolContent Tests
@@ -74,6 +78,75 @@ fn test_tool_content_deserialization() {
     }
 }
 
+#[test]
+fn test_tool_content_image_from_bytes() {
+    let content = ToolContent::image_from_bytes(b"hello", "image/png");
+    let serialized = serde_json::to_value(&content).unwrap();
+    assert_eq!(serialized["data"], "aGVsbG8=");
+    assert_eq!(serialized["mimeType"], "image/png");
+}
+
+#[test]
+fn test_tool_content_image_from_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pixel.JPG");
+    std::fs::write(&path, [0xff, 0xd8, 0xff]).unwrap();
+
+    match ToolContent::image_from_path(&path).unwrap() {
+        ToolContent::Image { data, mime_type } => {
+            assert_eq!(data, "/9j/");
+            assert_eq!(mime_type, "image/jpeg");
+        }
+        _ => panic!("Expected image content"),
+    }
+
+    let unknown = dir.path().join("notes.txt");
+    std::fs::write(&unknown, "text").unwrap();
+    let err = ToolContent::image_from_path(&unknown).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let missing = ToolContent::image_from_path(dir.path().join("missing.png")).unwrap_err();
+    assert_eq!(missing.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_tool_content_resource_link_serialization() {
+    let content = ToolContent::resource("file:///tmp/report.csv", "text/csv");
+    assert_eq!(
+        serde_json::to_value(&content).unwrap(),
+        json!({
+            "type": "resource_link",
+            "uri": "file:///tmp/report.csv",
+            "name": "file:///tmp/report.csv",
+            "mimeType": "text/csv"
+        })
+    );
+}
+
+#[test]
+fn test_tool_content_embedded_resource_serialization() {
+    let text = ToolContent::embedded_text("memo://1", "text/plain", "remember this");
+    assert_eq!(
+        serde_json::to_value(&text).unwrap(),
+        json!({
+            "type": "resource",
+            "resource": {"uri": "memo://1", "mimeType": "text/plain", "text": "remember this"}
+        })
+    );
+
+    let blob = ToolContent::embedded_blob("bin://1", "application/octet-stream", [1u8, 2, 3]);
+    let serialized = serde_json::to_value(&blob).unwrap();
+    assert_eq!(serialized["resource"]["blob"], "AQID");
+
+    let roundtrip: ToolContent = serde_json::from_value(serialized).unwrap();
+    match roundtrip {
+        ToolContent::Resource {
+            resource: EmbeddedResource::Blob { blob, .. },
+        } => assert_eq!(blob, "AQID"),
+        _ => panic!("Expected embedded blob"),
+    }
+}
+
 // ============================================================================
 // ToolResult Tests
 // ============================================================================
@@ -223,7 +296,7 @@ fn test_sdk_mcp_tool_creation() {
         ToolInputSchema::object()
             .string_property("name", "Name to greet")
             .required_property("name"),
-        |_input| async { ToolResult::text("Hello!") },
+        |_input| async { Ok(ToolResult::text("Hello!")) },
     );
 
     assert_eq!(tool.name, "greet");
@@ -234,7 +307,7 @@ fn test_sdk_mcp_tool_creation() {
 #[test]
 fn test_sdk_mcp_tool_debug() {
     let tool = SdkMcpTool::new("test", "Test tool", ToolInputSchema::object(), |_| async {
-        ToolResult::text("ok")
+        Ok(ToolResult::text("ok"))
     });
 
     let debug_str = format!("{:?}", tool);
@@ -253,12 +326,12 @@ async fn test_sdk_mcp_tool_handler_execution() {
         |input| async move {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            ToolResult::text(format!("{}", a + b))
+            Ok(ToolResult::text(format!("{}", a + b)))
         },
     );
 
     let input = json!({"a": 5.0, "b": 3.0});
-    let result = (tool.handler)(input).await;
+    let result = (tool.handler)(input, ToolContext::default()).await.unwrap();
 
     assert_eq!(result.content.len(), 1);
     if let ToolContent::Text { text } = &result.content[0] {
@@ -280,20 +353,20 @@ async fn test_sdk_mcp_tool_error_handling() {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
             if b == 0.0 {
-                return ToolResult::error("Division by zero");
+                return Ok(ToolResult::error("Division by zero"));
             }
-            ToolResult::text(format!("{}", a / b))
+            Ok(ToolResult::text(format!("{}", a / b)))
         },
     );
 
     // Test division by zero
     let input = json!({"a": 10.0, "b": 0.0});
-    let result = (tool.handler)(input).await;
+    let result = (tool.handler)(input, ToolContext::default()).await.unwrap();
     assert_eq!(result.is_error, Some(true));
 
     // Test normal division
     let input = json!({"a": 10.0, "b": 2.0});
-    let result = (tool.handler)(input).await;
+    let result = (tool.handler)(input, ToolContext::default()).await.unwrap();
     assert!(result.is_error.is_none());
 }
 
@@ -349,13 +422,13 @@ fn test_create_sdk_mcp_server_with_tools() {
         "tool1",
         "First tool",
         ToolInputSchema::object(),
-        |_| async { ToolResult::text("1") },
+        |_| async { Ok(ToolResult::text("1")) },
     );
     let tool2 = SdkMcpTool::new(
         "tool2",
         "Second tool",
         ToolInputSchema::object(),
-        |_| async { ToolResult::text("2") },
+        |_| async { Ok(ToolResult::text("2")) },
     );
 
     let (config, tools) = create_sdk_mcp_server("multi-tool", "2.0.0", vec![tool1, tool2]);
@@ -385,7 +458,7 @@ async fn test_calculator_tools_workflow() {
         |input| async move {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            ToolResult::text(format!("{}", a + b))
+            Ok(ToolResult::text(format!("{}", a + b)))
         },
     );
 
@@ -398,7 +471,7 @@ async fn test_calculator_tools_workflow() {
         |input| async move {
             let a = input.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
             let b = input.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            ToolResult::text(format!("{}", a * b))
+            Ok(ToolResult::text(format!("{}", a * b)))
         },
     );
 
@@ -408,13 +481,17 @@ async fn test_calculator_tools_workflow() {
     assert_eq!(tools.len(), 2);
 
     // Execute add
-    let add_result = (tools[0].handler)(json!({"a": 10, "b": 5})).await;
+    let add_result = (tools[0].handler)(json!({"a": 10, "b": 5}), ToolContext::default())
+        .await
+        .unwrap();
     if let ToolContent::Text { text } = &add_result.content[0] {
         assert_eq!(text, "15");
     }
 
     // Execute multiply
-    let mul_result = (tools[1].handler)(json!({"a": 4, "b": 7})).await;
+    let mul_result = (tools[1].handler)(json!({"a": 4, "b": 7}), ToolContext::default())
+        .await
+        .unwrap();
     if let ToolContent::Text { text } = &mul_result.content[0] {
         assert_eq!(text, "28");
     }
@@ -430,7 +507,7 @@ async fn test_string_processing_tools() {
             .required_property("text"),
         |input| async move {
             let text = input.get("text").and_then(|v| v.as_str()).unwrap_or("");
-            ToolResult::text(text.to_uppercase())
+            Ok(ToolResult::text(text.to_uppercase()))
         },
     );
 
@@ -442,20 +519,24 @@ async fn test_string_processing_tools() {
             .required_property("text"),
         |input| async move {
             let text = input.get("text").and_then(|v| v.as_str()).unwrap_or("");
-            ToolResult::text(text.chars().rev().collect::<String>())
+            Ok(ToolResult::text(text.chars().rev().collect::<String>()))
         },
     );
 
     let (_, tools) = create_sdk_mcp_server("string-utils", "1.0.0", vec![uppercase, reverse]);
 
     // Test uppercase
-    let result = (tools[0].handler)(json!({"text": "hello"})).await;
+    let result = (tools[0].handler)(json!({"text": "hello"}), ToolContext::default())
+        .await
+        .unwrap();
     if let ToolContent::Text { text } = &result.content[0] {
         assert_eq!(text, "HELLO");
     }
 
     // Test reverse
-    let result = (tools[1].handler)(json!({"text": "hello"})).await;
+    let result = (tools[1].handler)(json!({"text": "hello"}), ToolContext::default())
+        .await
+        .unwrap();
     if let ToolContent::Text { text } = &result.content[0] {
         assert_eq!(text, "olleh");
     }
@@ -474,12 +555,14 @@ async fn test_tool_with_missing_input() {
                 .get("name")
                 .and_then(|v| v.as_str())
                 .unwrap_or("stranger");
-            ToolResult::text(format!("Hello, {}!", name))
+            Ok(ToolResult::text(format!("Hello, {}!", name)))
         },
     );
 
     // Missing name should use default
-    let result = (tool.handler)(json!({})).await;
+    let result = (tool.handler)(json!({}), ToolContext::default())
+        .await
+        .unwrap();
     if let ToolContent::Text { text } = &result.content[0] {
         assert_eq!(text, "Hello, stranger!");
     }
@@ -492,14 +575,811 @@ async fn test_tool_with_complex_output() {
         "Calculate statistics",
         ToolInputSchema::object(),
         |_input| async move {
-            ToolResult::with_content(vec![
+            Ok(ToolResult::with_content(vec![
                 ToolContent::text("Count: 10"),
                 ToolContent::text("Sum: 55"),
                 ToolContent::text("Average: 5.5"),
-            ])
+            ]))
         },
     );
 
-    let result = (tool.handler)(json!({})).await;
+    let result = (tool.handler)(json!({}), ToolContext::default())
+        .await
+        .unwrap();
     assert_eq!(result.content.len(), 3);
 }
+
+// ============================================================================
+// SdkMcpServer Dispatch Tests
+// ============================================================================
+
+fn echo_server() -> SdkMcpServer {
+    let echo = SdkMcpTool::new(
+        "echo",
+        "Echo text",
+        ToolInputSchema::object().string_property("text", "Text to echo"),
+        |input| async move { Ok(ToolResult::text(input["text"].as_str().unwrap_or_default())) },
+    );
+    SdkMcpServer::new("utils", "1.0.0", vec![echo])
+}
+
+#[tokio::test]
+async fn test_sdk_server_initialize() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}))
+        .await
+        .unwrap();
+
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["serverInfo"]["name"], "utils");
+    assert!(response["result"]["capabilities"]["tools"].is_object());
+}
+
+#[tokio::test]
+async fn test_sdk_server_tools_list() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+        .await
+        .unwrap();
+
+    let tools = response["result"]["tools"].as_array().unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0]["name"], "echo");
+    assert_eq!(tools[0]["inputSchema"]["type"], "object");
+}
+
+#[tokio::test]
+async fn test_sdk_server_tools_call() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": {"name": "echo", "arguments": {"text": "hi"}}
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(response["result"]["content"][0]["text"], "hi");
+    assert!(response["result"].get("isError").is_none());
+}
+
+#[tokio::test]
+async fn test_sdk_server_unknown_tool() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": {"name": "missing", "arguments": {}}
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(response["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn test_sdk_server_notification_has_no_response() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+        .await;
+
+    assert!(response.is_none());
+}
+
+#[tokio::test]
+async fn test_sdk_server_tools_list_includes_annotations() {
+    let read = SdkMcpTool::new(
+        "read",
+        "Read a record",
+        ToolInputSchema::object(),
+        |_| async { Ok(ToolResult::text("ok")) },
+    )
+    .read_only();
+    let plain = SdkMcpTool::new("plain", "No hints", ToolInputSchema::object(), |_| async {
+        Ok(ToolResult::text("ok"))
+    });
+    let server = SdkMcpServer::new("db", "1.0.0", vec![read, plain]);
+
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+        .await
+        .unwrap();
+
+    let tools = response["result"]["tools"].as_array().unwrap();
+    assert_eq!(tools[0]["annotations"], json!({"readOnlyHint": true}));
+    assert!(tools[1].get("annotations").is_none());
+}
+
+fn weather_server() -> SdkMcpServer {
+    let weather = SdkMcpTool::new(
+        "weather",
+        "Current weather",
+        ToolInputSchema::object().string_property("city", "City"),
+        |input| async move {
+            if input["city"] == "Nowhere" {
+                return Ok(ToolResult::structured(json!({"temperature": "warm"})));
+            }
+            Ok(ToolResult::structured(json!({"temperature": 21.5})))
+        },
+    )
+    .with_output_schema(
+        ToolInputSchema::object()
+            .number_property("temperature", "Degrees Celsius")
+            .required_property("temperature"),
+    );
+    SdkMcpServer::new("weather", "1.0.0", vec![weather])
+}
+
+#[tokio::test]
+async fn test_sdk_server_structured_output() {
+    let server = weather_server();
+
+    let list = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+        .await
+        .unwrap();
+    let output_schema = &list["result"]["tools"][0]["outputSchema"];
+    assert_eq!(output_schema["properties"]["temperature"]["type"], "number");
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "weather", "arguments": {"city": "Oslo"}}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        response["result"]["structuredContent"],
+        json!({"temperature": 21.5})
+    );
+    assert_eq!(
+        response["result"]["content"][0]["text"],
+        r#"{"temperature":21.5}"#
+    );
+}
+
+#[tokio::test]
+async fn test_sdk_server_rejects_structured_output_not_matching_schema() {
+    let server = weather_server();
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": {"name": "weather", "arguments": {"city": "Nowhere"}}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32603);
+    assert_eq!(
+        response["error"]["data"]["violations"][0]["path"],
+        "temperature"
+    );
+}
+
+#[tokio::test]
+async fn test_sdk_server_rejects_invalid_arguments() {
+    let called = Arc::new(AtomicBool::new(false));
+    let called_clone = called.clone();
+    let tool = SdkMcpTool::new(
+        "greet",
+        "Greet someone",
+        ToolInputSchema::object()
+            .string_property("name", "Who to greet")
+            .required_property("name"),
+        move |_input| {
+            called_clone.store(true, Ordering::SeqCst);
+            async { Ok(ToolResult::text("hi")) }
+        },
+    );
+    let server = SdkMcpServer::new("utils", "1.0.0", vec![tool]);
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": "greet", "arguments": {"name": 5}}
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(response["error"]["code"], -32602);
+    assert_eq!(
+        response["error"]["message"],
+        "Invalid arguments for tool 'greet': name: expected string, got number"
+    );
+    assert_eq!(response["error"]["data"]["violations"][0]["path"], "name");
+    assert!(!called.load(Ordering::SeqCst));
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 8,
+            "method": "tools/call",
+            "params": {"name": "greet"}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        response["error"]["data"]["violations"][0]["message"],
+        "missing required property"
+    );
+    assert!(!called.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_sdk_server_maps_tool_error_to_jsonrpc_error() {
+    let tool = SdkMcpTool::new(
+        "lookup",
+        "Look up a record",
+        ToolInputSchema::object(),
+        |_input| async {
+            Err(ToolError::new(-32001, "Backend unavailable").with_data(json!({"retry_after": 5})))
+        },
+    );
+    let server = SdkMcpServer::new("db", "1.0.0", vec![tool]);
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": {"name": "lookup", "arguments": {}}
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(response["id"], 3);
+    assert!(response.get("result").is_none());
+    assert_eq!(response["error"]["code"], -32001);
+    assert_eq!(response["error"]["message"], "Backend unavailable");
+    assert_eq!(response["error"]["data"]["retry_after"], 5);
+}
+
+#[tokio::test]
+async fn test_sdk_server_domain_error_is_tool_result() {
+    let tool = SdkMcpTool::new(
+        "fail",
+        "Always fails",
+        ToolInputSchema::object(),
+        |_| async { Ok(ToolResult::error("File not found")) },
+    );
+    let server = SdkMcpServer::new("fs", "1.0.0", vec![tool]);
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": {"name": "fail"}
+        }))
+        .await
+        .unwrap();
+
+    assert!(response.get("error").is_none());
+    assert_eq!(response["result"]["isError"], true);
+    assert_eq!(response["result"]["content"][0]["text"], "File not found");
+}
+
+// ============================================================================
+// Protocol Negotiation Tests
+// ============================================================================
+
+async fn initialize_with(server: &SdkMcpServer, version: serde_json::Value) -> serde_json::Value {
+    server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": version,
+                "capabilities": {"roots": {}},
+                "clientInfo": {"name": "claude-code", "version": "2.0.0"}
+            }
+        }))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_initialize_negotiates_requested_version() {
+    let server = echo_server();
+    assert!(server.negotiation().is_none());
+
+    let response = initialize_with(&server, json!("2025-03-26")).await;
+    assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+
+    let negotiation = server.negotiation().unwrap();
+    assert_eq!(negotiation.protocol_version, "2025-03-26");
+    assert_eq!(negotiation.client_capabilities, json!({"roots": {}}));
+    assert_eq!(negotiation.client_info.unwrap()["name"], "claude-code");
+}
+
+#[tokio::test]
+async fn test_initialize_without_version_uses_latest() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}))
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["protocolVersion"], MCP_PROTOCOL_VERSION);
+}
+
+#[tokio::test]
+async fn test_initialize_answers_unsupported_version_with_latest() {
+    let server = echo_server();
+    let response = initialize_with(&server, json!("2099-01-01")).await;
+
+    assert!(response.get("error").is_none());
+    assert_eq!(
+        response["result"]["protocolVersion"],
+        SUPPORTED_PROTOCOL_VERSIONS[0]
+    );
+    let negotiation = server.negotiation().unwrap();
+    assert_eq!(negotiation.protocol_version, SUPPORTED_PROTOCOL_VERSIONS[0]);
+}
+
+#[tokio::test]
+async fn test_pinned_protocol_versions() {
+    let server = echo_server().with_protocol_versions(["2024-11-05"]);
+
+    let response = initialize_with(&server, json!("2025-06-18")).await;
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+
+    let response = initialize_with(&server, json!("2024-11-05")).await;
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(server.negotiation().unwrap().protocol_version, "2024-11-05");
+}
+
+// ============================================================================
+// Dynamic Tool Registration Tests
+// ============================================================================
+
+fn named_tool(name: &str) -> SdkMcpTool {
+    SdkMcpTool::new(name, "Dynamic tool", ToolInputSchema::object(), |_| async {
+        Ok(ToolResult::text("ok"))
+    })
+}
+
+async fn listed_tool_names(server: &SdkMcpServer) -> Vec<String> {
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+        .await
+        .unwrap();
+    response["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sdk_server_advertises_list_changed() {
+    let server = echo_server();
+    let response = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}))
+        .await
+        .unwrap();
+    assert_eq!(
+        response["result"]["capabilities"]["tools"]["listChanged"],
+        true
+    );
+}
+
+#[tokio::test]
+async fn test_add_tool_updates_list_and_notifies() {
+    let server = SdkMcpServer::new("dyn", "1.0.0", vec![]);
+    let mut notifications = server.subscribe();
+
+    server.add_tool(named_tool("account"));
+
+    assert!(server.has_tool("account"));
+    assert_eq!(listed_tool_names(&server).await, vec!["account"]);
+    let notification = notifications.try_recv().unwrap();
+    assert_eq!(notification["method"], "notifications/tools/list_changed");
+    assert!(notification.get("id").is_none());
+}
+
+#[tokio::test]
+async fn test_add_tool_replaces_same_name() {
+    let server = SdkMcpServer::new("dyn", "1.0.0", vec![named_tool("account")]);
+    server.add_tool(named_tool("account"));
+    assert_eq!(server.tools().len(), 1);
+}
+
+#[tokio::test]
+async fn test_remove_tool_updates_list_and_notifies() {
+    let server = SdkMcpServer::new("dyn", "1.0.0", vec![named_tool("a"), named_tool("b")]);
+    let mut notifications = server.subscribe();
+
+    let removed = server.remove_tool("a").unwrap();
+    assert_eq!(removed.name, "a");
+    assert_eq!(listed_tool_names(&server).await, vec!["b"]);
+    assert!(notifications.try_recv().is_ok());
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "a"}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn test_remove_unknown_tool_does_not_notify() {
+    let server = SdkMcpServer::new("dyn", "1.0.0", vec![]);
+    let mut notifications = server.subscribe();
+
+    assert!(server.remove_tool("missing").is_none());
+    assert!(notifications.try_recv().is_err());
+}
+
+#[test]
+fn test_options_keep_live_server_handle() {
+    let server = Arc::new(SdkMcpServer::new("dyn", "1.0.0", vec![]));
+    let options = claude_agents_sdk::ClaudeAgentOptions::new().with_sdk_mcp_server(server.clone());
+
+    server.add_tool(named_tool("late"));
+    assert!(options.sdk_mcp_servers["dyn"].has_tool("late"));
+}
+
+// ============================================================================
+// Middleware Tests
+// ============================================================================
+
+async fn call_echo(server: &SdkMcpServer, text: &str) -> serde_json::Value {
+    server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "echo", "arguments": {"text": text}}
+        }))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_middleware_runs_in_registration_order() {
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (outer, inner) = (order.clone(), order.clone());
+    let server = echo_server()
+        .with_middleware(move |call, next| {
+            let order = outer.clone();
+            async move {
+                order
+                    .lock()
+                    .unwrap()
+                    .push(format!("outer:{}", call.tool_name));
+                let outcome = next.run(call).await;
+                order.lock().unwrap().push("outer:done".to_string());
+                outcome
+            }
+        })
+        .with_middleware(move |call, next| {
+            let order = inner.clone();
+            async move {
+                order
+                    .lock()
+                    .unwrap()
+                    .push(format!("inner:{}", call.server_name));
+                next.run(call).await
+            }
+        });
+
+    let response = call_echo(&server, "hi").await;
+
+    assert_eq!(response["result"]["content"][0]["text"], "hi");
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec!["outer:echo", "inner:utils", "outer:done"]
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_can_short_circuit() {
+    let server = echo_server().with_middleware(|call, next| async move {
+        if call.arguments["text"] == "secret" {
+            return Err(ToolError::new(-32001, "Not authorized"));
+        }
+        next.run(call).await
+    });
+
+    let response = call_echo(&server, "secret").await;
+    assert_eq!(response["error"]["message"], "Not authorized");
+
+    let response = call_echo(&server, "public").await;
+    assert_eq!(response["result"]["content"][0]["text"], "public");
+}
+
+#[tokio::test]
+async fn test_middleware_can_rewrite_input_and_output() {
+    let server = echo_server().with_middleware(|mut call, next| async move {
+        call.arguments["text"] = json!("token=abc123");
+        let mut result = next.run(call).await?;
+        for content in &mut result.content {
+            if let ToolContent::Text { text } = content {
+                *text = text.replace("abc123", "[REDACTED]");
+            }
+        }
+        Ok(result)
+    });
+
+    let response = call_echo(&server, "ignored").await;
+    assert_eq!(response["result"]["content"][0]["text"], "token=[REDACTED]");
+}
+
+// ============================================================================
+// Logging Tests
+// ============================================================================
+
+fn logging_server() -> SdkMcpServer {
+    let tool = SdkMcpTool::new_with_context(
+        "chatty",
+        "Logs while working",
+        ToolInputSchema::object(),
+        |_input, ctx| async move {
+            ctx.log_debug("starting");
+            ctx.log_warning("disk almost full");
+            Ok(ToolResult::text("done"))
+        },
+    );
+    SdkMcpServer::new("logs", "1.0.0", vec![tool])
+}
+
+async fn call_chatty(server: &SdkMcpServer) {
+    server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "chatty"}
+        }))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_tool_log_messages_become_notifications() {
+    let server = logging_server();
+    let mut notifications = server.subscribe();
+
+    call_chatty(&server).await;
+
+    let first = notifications.try_recv().unwrap();
+    assert_eq!(first["method"], "notifications/message");
+    assert_eq!(first["params"]["level"], "debug");
+    assert_eq!(first["params"]["logger"], "chatty");
+    assert_eq!(first["params"]["data"], "starting");
+    let second = notifications.try_recv().unwrap();
+    assert_eq!(second["params"]["level"], "warning");
+}
+
+#[tokio::test]
+async fn test_set_level_filters_log_messages() {
+    let server = logging_server();
+    let mut notifications = server.subscribe();
+
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "logging/setLevel",
+            "params": {"level": "warning"}
+        }))
+        .await
+        .unwrap();
+    assert!(response["result"].is_object());
+
+    call_chatty(&server).await;
+
+    let only = notifications.try_recv().unwrap();
+    assert_eq!(only["params"]["data"], "disk almost full");
+    assert!(notifications.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_set_level_rejects_unknown_level() {
+    let server = logging_server();
+    let response = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 6,
+            "method": "logging/setLevel",
+            "params": {"level": "loud"}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response["error"]["code"], -32602);
+}
+
+#[test]
+fn test_log_without_server_is_noop() {
+    ToolContext::default().log(LogLevel::Error, json!({"ignored": true}));
+    assert!(LogLevel::Debug < LogLevel::Emergency);
+}
+
+// ============================================================================
+// Standalone Serving Tests
+// ============================================================================
+
+async fn serve_lines(server: &SdkMcpServer, input: &str) -> Vec<serde_json::Value> {
+    let mut output = Vec::new();
+    server
+        .serve(input.as_bytes(), &mut output)
+        .await
+        .expect("serve failed");
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_serve_handles_requests_until_eof() {
+    let server = echo_server();
+    let input = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "echo", "arguments": {"text": "hi"}}
+        }),
+    ]
+    .iter()
+    .map(|m| format!("{}\n", m))
+    .collect::<String>();
+
+    let mut responses = serve_lines(&server, &input).await;
+    responses.sort_by_key(|r| r["id"].as_i64());
+
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "utils");
+    assert_eq!(responses[1]["result"]["content"][0]["text"], "hi");
+}
+
+#[tokio::test]
+async fn test_serve_reports_parse_errors() {
+    let server = echo_server();
+    let responses = serve_lines(&server, "not json\n\n").await;
+
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0]["error"]["code"], -32700);
+    assert!(responses[0]["id"].is_null());
+}
+
+#[tokio::test]
+async fn test_serve_cancels_in_flight_calls_on_eof() {
+    let server = slow_server();
+    let input = format!(
+        "{}\n",
+        json!({"jsonrpc": "2.0", "id": 9, "method": "tools/call", "params": {"name": "slow"}})
+    );
+
+    let responses = serve_lines(&server, &input).await;
+
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0]["error"]["code"], -32800);
+}
+
+// ============================================================================
+// Cancellation Tests
+// ============================================================================
+
+fn slow_server() -> SdkMcpServer {
+    let slow = SdkMcpTool::new_with_context(
+        "slow",
+        "Never finishes on its own",
+        ToolInputSchema::object(),
+        |_input, ctx| async move {
+            ctx.cancelled().await;
+            Ok(ToolResult::error("cancelled"))
+        },
+    );
+    SdkMcpServer::new("slow-server", "1.0.0", vec![slow])
+}
+
+async fn wait_for_in_flight(server: &SdkMcpServer) {
+    for _ in 0..100 {
+        if server.in_flight_count() > 0 {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    panic!("tool call never started");
+}
+
+#[tokio::test]
+async fn test_cancelled_notification_cancels_tool_call() {
+    let server = Arc::new(slow_server());
+
+    let call = tokio::spawn({
+        let server = server.clone();
+        async move {
+            server
+                .handle_message(json!({
+                    "jsonrpc": "2.0",
+                    "id": "call-1",
+                    "method": "tools/call",
+                    "params": {"name": "slow", "arguments": {}}
+                }))
+                .await
+        }
+    });
+
+    wait_for_in_flight(&server).await;
+    let ack = server
+        .handle_message(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": {"requestId": "call-1", "reason": "user interrupt"}
+        }))
+        .await;
+    assert!(ack.is_none());
+
+    let response = call.await.unwrap().unwrap();
+    assert_eq!(response["id"], "call-1");
+    assert_eq!(response["error"]["code"], -32800);
+    assert_eq!(server.in_flight_count(), 0);
+}
+
+#[tokio::test]
+async fn test_cancel_all_cancels_in_flight_calls() {
+    let server = Arc::new(slow_server());
+
+    let call = tokio::spawn({
+        let server = server.clone();
+        async move {
+            server
+                .handle_message(json!({
+                    "jsonrpc": "2.0",
+                    "id": 7,
+                    "method": "tools/call",
+                    "params": {"name": "slow"}
+                }))
+                .await
+        }
+    });
+
+    wait_for_in_flight(&server).await;
+    server.cancel_all();
+
+    let response = call.await.unwrap().unwrap();
+    assert_eq!(response["error"]["code"], -32800);
+}
+
+#[tokio::test]
+async fn test_tool_context_cancellation_token() {
+    let ctx = ToolContext::default();
+    assert!(!ctx.is_cancelled());
+    ctx.cancellation_token().cancel();
+    assert!(ctx.is_cancelled());
+    ctx.cancelled().await;
+}
+
+#[test]
+fn test_options_with_sdk_mcp_server() {
+    let options = claude_agents_sdk::ClaudeAgentOptions::new().with_sdk_mcp_server(echo_server());
+
+    assert!(options.sdk_mcp_servers.contains_key("utils"));
+    assert_eq!(options.sdk_mcp_servers["utils"].tools().len(), 1);
+}
@@ -0,0 +1,234 @@
+//! Tests for the MCP client against scripted stdio and HTTP servers.
+
+#![cfg(feature = "mcp-client")]
+
+use claude_agents_sdk::mcp::ToolContent;
+use claude_agents_sdk::mcp_client::McpClient;
+use claude_agents_sdk::{
+    McpHttpServerConfig, McpSSEServerConfig, McpServerConfig, McpStdioServerConfig,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+// ============================================================================
+// Stdio Tests
+// ============================================================================
+
+/// A stdio server that answers requests by ID with canned responses.
+#[cfg(unix)]
+fn scripted_stdio_server() -> McpStdioServerConfig {
+    let script = r#"
+read init
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"scripted","version":"0.1"}}}'
+read initialized
+read list
+echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"listing"}}'
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","description":"Echo","inputSchema":{"type":"object"}}]}}'
+read call
+echo '{"jsonrpc":"2.0","id":3,"result":{"content":[{"type":"text","text":"hello"}],"structuredContent":{"n":1}}}'
+read bad
+echo '{"jsonrpc":"2.0","id":4,"error":{"code":-32602,"message":"Unknown tool"}}'
+read rest
+"#;
+    McpStdioServerConfig {
+        server_type: "stdio".to_string(),
+        command: "sh".to_string(),
+        args: vec!["-c".to_string(), script.to_string()],
+        env: HashMap::new(),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_client_lists_and_calls_tools() {
+    let config = McpServerConfig::Stdio(scripted_stdio_server());
+    let client = McpClient::connect(&config).await.unwrap();
+    assert_eq!(client.server_info()["serverInfo"]["name"], "scripted");
+
+    let tools = client.list_tools().await.unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "echo");
+    assert_eq!(tools[0].description.as_deref(), Some("Echo"));
+
+    let result = client
+        .call_tool("echo", json!({"text": "hello"}))
+        .await
+        .unwrap();
+    assert!(matches!(&result.content[0], ToolContent::Text { text } if text == "hello"));
+    assert_eq!(result.structured_content, Some(json!({"n": 1})));
+
+    let err = client.call_tool("missing", json!({})).await.unwrap_err();
+    assert!(err.to_string().contains("Unknown tool"));
+
+    client.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_client_rejects_unsupported_server_version() {
+    let script = r#"
+read init
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"1999-01-01","capabilities":{},"serverInfo":{"name":"old","version":"0.1"}}}'
+read rest
+"#;
+    let config = McpStdioServerConfig {
+        server_type: "stdio".to_string(),
+        command: "sh".to_string(),
+        args: vec!["-c".to_string(), script.to_string()],
+        env: HashMap::new(),
+    };
+
+    let err = McpClient::connect_stdio(&config).await.unwrap_err();
+    assert!(err.to_string().contains("1999-01-01"));
+}
+
+#[tokio::test]
+async fn test_stdio_client_reports_spawn_failure() {
+    let config = McpStdioServerConfig {
+        server_type: "stdio".to_string(),
+        command: "definitely-not-an-mcp-server-binary".to_string(),
+        args: vec![],
+        env: HashMap::new(),
+    };
+    let err = McpClient::connect_stdio(&config).await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("definitely-not-an-mcp-server-binary"));
+}
+
+#[tokio::test]
+async fn test_sse_config_is_rejected() {
+    let config = McpServerConfig::SSE(McpSSEServerConfig {
+        server_type: "sse".to_string(),
+        url: "http://localhost:1/sse".to_string(),
+        headers: HashMap::new(),
+    });
+    assert!(McpClient::connect(&config).await.is_err());
+}
+
+// ============================================================================
+// HTTP Tests
+// ============================================================================
+
+/// Read one HTTP request, returning its headers (lowercased names) and body.
+async fn read_http_request(
+    reader: &mut BufReader<tokio::net::tcp::ReadHalf<'_>>,
+) -> Option<(HashMap<String, String>, String)> {
+    let mut headers = HashMap::new();
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).await.ok()? == 0 {
+        return None;
+    }
+    headers.insert(
+        ":method".to_string(),
+        request_line.split(' ').next()?.to_string(),
+    );
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+    }
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.ok()?;
+    Some((headers, String::from_utf8(body).ok()?))
+}
+
+/// Serve a minimal streamable-HTTP MCP server on a local port.
+async fn spawn_http_server() -> (
+    String,
+    tokio::task::JoinHandle<Vec<HashMap<String, String>>>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+
+    let server = tokio::spawn(async move {
+        let mut seen = Vec::new();
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.split();
+        let mut reader = BufReader::new(read);
+
+        while let Some((headers, body)) = read_http_request(&mut reader).await {
+            let method = headers[":method"].clone();
+            seen.push(headers);
+            let (status, content_type, reply) = if method == "DELETE" {
+                ("200 OK", "application/json", String::new())
+            } else {
+                let message: Value = serde_json::from_str(&body).unwrap();
+                match message["method"].as_str().unwrap() {
+                    "initialize" => (
+                        "200 OK",
+                        "application/json",
+                        json!({"jsonrpc": "2.0", "id": message["id"], "result": {
+                            "protocolVersion": "2024-11-05",
+                            "capabilities": {"tools": {}},
+                            "serverInfo": {"name": "http-test", "version": "1.0"}
+                        }})
+                        .to_string(),
+                    ),
+                    "notifications/initialized" => {
+                        ("202 Accepted", "application/json", String::new())
+                    }
+                    "tools/list" => (
+                        "200 OK",
+                        "text/event-stream",
+                        format!(
+                            "event: message\ndata: {}\n\n",
+                            json!({"jsonrpc": "2.0", "id": message["id"], "result": {
+                                "tools": [{"name": "add", "inputSchema": {"type": "object"}}]
+                            }})
+                        ),
+                    ),
+                    other => panic!("unexpected method {}", other),
+                }
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nMcp-Session-Id: session-1\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                content_type,
+                reply.len(),
+                reply
+            );
+            write.write_all(response.as_bytes()).await.unwrap();
+        }
+        seen
+    });
+
+    (url, server)
+}
+
+#[tokio::test]
+async fn test_http_client_lists_tools_and_tracks_session() {
+    let (url, server) = spawn_http_server().await;
+    let mut headers = HashMap::new();
+    headers.insert("Authorization".to_string(), "Bearer token".to_string());
+    let config = McpHttpServerConfig {
+        server_type: "http".to_string(),
+        url,
+        headers,
+    };
+
+    let client = McpClient::connect_http(&config).await.unwrap();
+    assert_eq!(client.server_info()["serverInfo"]["name"], "http-test");
+
+    let tools = client.list_tools().await.unwrap();
+    assert_eq!(tools[0].name, "add");
+    client.close().await.unwrap();
+
+    let seen = server.await.unwrap();
+    assert_eq!(seen.len(), 4);
+    assert!(seen.iter().all(|h| h["authorization"] == "Bearer token"));
+    assert!(!seen[0].contains_key("mcp-session-id"));
+    assert_eq!(seen[2]["mcp-session-id"], "session-1");
+    assert_eq!(seen[3][":method"], "DELETE");
+}
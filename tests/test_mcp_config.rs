@@ -0,0 +1,163 @@
+//! Tests for merging MCP server configuration from multiple sources.
+
+use claude_agents_sdk::{
+    McpConfigLoader, McpConfigSource, McpServerConfig, McpServersConfig, McpStdioServerConfig,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+
+fn stdio(command: &str) -> McpServerConfig {
+    McpServerConfig::Stdio(McpStdioServerConfig {
+        server_type: "stdio".to_string(),
+        command: command.to_string(),
+        args: vec![],
+        env: HashMap::new(),
+    })
+}
+
+fn command_of(config: &McpServerConfig) -> &str {
+    match config {
+        McpServerConfig::Stdio(stdio) => &stdio.command,
+        other => panic!("Expected stdio config, got {:?}", other),
+    }
+}
+
+fn write_json(path: &Path, value: serde_json::Value) {
+    std::fs::write(path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+}
+
+// ============================================================================
+// McpServersConfig::merge Tests
+// ============================================================================
+
+#[test]
+fn test_merge_maps_prefers_other() {
+    let base = McpServersConfig::Map(HashMap::from([
+        ("fs".to_string(), stdio("fs-old")),
+        ("git".to_string(), stdio("git")),
+    ]));
+    let overlay = McpServersConfig::Map(HashMap::from([("fs".to_string(), stdio("fs-new"))]));
+
+    let (merged, conflicts) = base.merge(&overlay).unwrap();
+
+    let McpServersConfig::Map(servers) = merged else {
+        panic!("Expected map");
+    };
+    assert_eq!(servers.len(), 2);
+    assert_eq!(command_of(&servers["fs"]), "fs-new");
+    assert_eq!(conflicts, vec!["fs"]);
+}
+
+#[test]
+fn test_merge_identical_definitions_is_not_a_conflict() {
+    let a = McpServersConfig::Map(HashMap::from([("fs".to_string(), stdio("fs"))]));
+    let (_, conflicts) = a.merge(&a.clone()).unwrap();
+    assert!(conflicts.is_empty());
+}
+
+#[test]
+fn test_resolve_path_reads_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("servers.json");
+    write_json(
+        &path,
+        json!({"mcpServers": {"fs": {"type": "stdio", "command": "fs"}}}),
+    );
+
+    let servers = McpServersConfig::Path(path).resolve().unwrap();
+    assert_eq!(command_of(&servers["fs"]), "fs");
+}
+
+#[test]
+fn test_resolve_missing_file_is_configuration_error() {
+    let err = McpServersConfig::Path("/nonexistent/mcp.json".into())
+        .resolve()
+        .unwrap_err();
+    assert!(err.to_string().contains("/nonexistent/mcp.json"));
+}
+
+// ============================================================================
+// McpConfigLoader Tests
+// ============================================================================
+
+#[test]
+fn test_loader_precedence_and_conflicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join(".claude.json");
+    write_json(
+        &user,
+        json!({
+            "numStartups": 3,
+            "mcpServers": {
+                "fs": {"command": "fs-user"},
+                "search": {"type": "http", "url": "https://search.example/mcp"}
+            }
+        }),
+    );
+    write_json(
+        &dir.path().join(".mcp.json"),
+        json!({"mcpServers": {"fs": {"command": "fs-project"}, "db": {"command": "db"}}}),
+    );
+    let options = McpServersConfig::Map(HashMap::from([("db".to_string(), stdio("db"))]));
+
+    let merged = McpConfigLoader::new()
+        .user_from(&user)
+        .unwrap()
+        .project(dir.path())
+        .unwrap()
+        .options(&options)
+        .unwrap()
+        .load();
+
+    assert_eq!(merged.servers.len(), 3);
+    assert_eq!(command_of(&merged.servers["fs"]), "fs-project");
+    assert!(matches!(merged.servers["search"], McpServerConfig::Http(_)));
+    assert_eq!(merged.sources["db"], McpConfigSource::Options);
+    assert_eq!(merged.sources["search"], McpConfigSource::User);
+
+    // db is identical in project and options, so only fs conflicts.
+    assert_eq!(merged.conflicts.len(), 1);
+    let conflict = &merged.conflicts[0];
+    assert_eq!(conflict.name, "fs");
+    assert_eq!(conflict.overridden, vec![McpConfigSource::User]);
+    assert!(conflict.to_string().contains("overrides user config"));
+}
+
+#[test]
+fn test_loader_skips_missing_optional_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    let merged = McpConfigLoader::new()
+        .user_from(dir.path().join(".claude.json"))
+        .unwrap()
+        .project(dir.path())
+        .unwrap()
+        .load();
+    assert!(merged.servers.is_empty());
+    assert!(merged.conflicts.is_empty());
+}
+
+#[test]
+fn test_loader_file_accepts_bare_map_and_requires_existence() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("extra.json");
+    write_json(&path, json!({"fs": {"command": "fs"}}));
+
+    let merged = McpConfigLoader::new().file(&path).unwrap().load();
+    assert_eq!(merged.sources["fs"], McpConfigSource::File(path));
+
+    assert!(McpConfigLoader::new()
+        .file(dir.path().join("missing.json"))
+        .is_err());
+}
+
+#[test]
+fn test_loader_reports_invalid_config() {
+    let dir = tempfile::tempdir().unwrap();
+    write_json(
+        &dir.path().join(".mcp.json"),
+        json!({"mcpServers": {"bad": {"type": "carrier-pigeon"}}}),
+    );
+    let err = McpConfigLoader::new().project(dir.path()).unwrap_err();
+    assert!(err.to_string().contains(".mcp.json"));
+}
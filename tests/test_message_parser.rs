@@ -227,6 +227,32 @@ fn test_parse_result_message() {
     }
 }
 
+#[test]
+fn test_parse_result_message_permission_denials() {
+    let raw = json!({
+        "type": "result",
+        "subtype": "success",
+        "session_id": "sess_abc123",
+        "permission_denials": [{
+            "tool_name": "Bash",
+            "tool_use_id": "toolu_01",
+            "tool_input": {"command": "rm -rf /"}
+        }]
+    });
+
+    let msg = parse_message(raw).unwrap().unwrap();
+    match msg {
+        Message::Result(result) => {
+            assert_eq!(result.permission_denials.len(), 1);
+            let denial = &result.permission_denials[0];
+            assert_eq!(denial.tool_name, "Bash");
+            assert_eq!(denial.tool_use_id, "toolu_01");
+            assert_eq!(denial.tool_input["command"], "rm -rf /");
+        }
+        _ => panic!("Expected result message"),
+    }
+}
+
 #[test]
 fn test_parse_result_message_camel_case() {
     // Test that camelCase field names also work
@@ -495,3 +521,102 @@ fn test_parse_mixed_known_and_unknown_content_blocks() {
         _ => panic!("Expected assistant message"),
     }
 }
+
+#[test]
+fn test_decode_line_reports_offset_and_path() {
+    let line = r#"{"type":"assistant","message":{"content":[{"type":"text"},{"type":"text","text":oops}]}}"#;
+    let err = decode_line(line).unwrap_err();
+    match &err {
+        ClaudeSDKError::JSONDecode {
+            raw_data,
+            offset,
+            path,
+            ..
+        } => {
+            assert_eq!(*offset, line.find("oops"));
+            assert_eq!(path.as_deref(), Some("/message/content/1/text"));
+            assert_eq!(raw_data.as_deref(), Some(line));
+        }
+        other => panic!("Expected JSONDecode, got {:?}", other),
+    }
+    assert!(err.to_string().contains("in /message/content/1/text"));
+}
+
+#[test]
+fn test_decode_line_truncates_long_lines_around_error() {
+    let line = format!(r#"{{"type":"user","pad":"{}","bad":}}"#, "x".repeat(5000));
+    let err = decode_line(&line).unwrap_err();
+    match err {
+        ClaudeSDKError::JSONDecode {
+            raw_data, offset, ..
+        } => {
+            let raw_data = raw_data.unwrap();
+            assert!(raw_data.len() <= 1024 + 6);
+            assert!(raw_data.starts_with("..."));
+            assert!(raw_data.contains(r#""bad":}"#));
+            assert_eq!(offset, Some(line.len() - 1));
+        }
+        other => panic!("Expected JSONDecode, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_line_non_json() {
+    let err = decode_line("Error: not logged in").unwrap_err();
+    match err {
+        ClaudeSDKError::JSONDecode { offset, path, .. } => {
+            assert_eq!(offset, Some(0));
+            assert_eq!(path.as_deref(), Some(""));
+        }
+        other => panic!("Expected JSONDecode, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_error_reports_content_block_path() {
+    let raw = json!({
+        "type": "assistant",
+        "message": {"model": "m", "content": [{"type": "text", "text": "ok"}, {"text": "no type"}]}
+    });
+    let err = parse_message(raw).unwrap_err();
+    match &err {
+        ClaudeSDKError::MessageParse {
+            path, raw_message, ..
+        } => {
+            assert_eq!(path.as_deref(), Some("/message/content/1/type"));
+            assert_eq!(raw_message.as_ref().unwrap()["type"], "assistant");
+        }
+        other => panic!("Expected MessageParse, got {:?}", other),
+    }
+    assert!(err.to_string().ends_with("at /message/content/1/type"));
+}
+
+#[test]
+fn test_message_try_from_value() {
+    let raw = json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 10,
+        "duration_api_ms": 8,
+        "is_error": false,
+        "num_turns": 1,
+        "session_id": "s1"
+    });
+    let msg = Message::try_from(raw).unwrap();
+    assert!(msg.is_result());
+
+    let err =
+        Message::try_from(json!({"type": "control_request", "request_id": "r1"})).unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::MessageParse { .. }));
+    assert!(err.to_string().contains("control_request"));
+}
+
+#[test]
+fn test_message_from_str() {
+    let line = "{\"type\":\"assistant\",\"message\":{\"model\":\"m\",\"content\":[{\"type\":\"text\",\"text\":\"Hi\"}]}}\r\n";
+    let msg: Message = line.parse().unwrap();
+    assert_eq!(msg.to_string(), "Hi");
+
+    let err = "not json".parse::<Message>().unwrap_err();
+    assert!(matches!(err, ClaudeSDKError::JSONDecode { .. }));
+}
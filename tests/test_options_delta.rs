@@ -0,0 +1,122 @@
+//! Tests for changing a connected client's options against scripted CLI
+//! sessions.
+
+#![cfg(feature = "testing")]
+
+use claude_agents_sdk::testing::Scenario;
+use claude_agents_sdk::{
+    ClaudeAgentOptions, ClaudeClient, OptionsDelta, PermissionMode, PermissionResult,
+};
+
+#[tokio::test]
+async fn test_applies_live_changes_and_grants_tools() {
+    let transport = Scenario::new()
+        .expect_user("Run the tests")
+        .expect_tool_call("Bash")
+        .reply_tool_result("ok")
+        .reply_text("All green.")
+        .build();
+    let options = {
+        let transport = transport.clone();
+        ClaudeAgentOptions::new()
+            .with_transport(move |_| Ok(transport.clone()))
+            .with_allowed_tools(["Read"])
+            .with_can_use_tool(|tool, _, _| async move {
+                PermissionResult::deny_with_message(format!("{} not allowed", tool))
+            })
+    };
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+
+    let delta = OptionsDelta::new()
+        .with_model("claude-opus-4-5")
+        .with_permission_mode(PermissionMode::AcceptEdits)
+        .with_allowed_tools(["Read", "Bash"])
+        .with_max_turns(Some(3));
+    let applied = client.apply_options_delta(delta).await.unwrap();
+    assert_eq!(
+        applied.applied,
+        ["model", "permission_mode", "allowed_tools"]
+    );
+    assert_eq!(applied.requires_reconnect, ["max_turns"]);
+
+    // The CLI asks about Bash, which is now granted without the callback.
+    client.query("Run the tests").await.unwrap();
+    let (text, _) = client.receive_response().await.unwrap();
+    assert_eq!(text, "All green.");
+    transport.assert_complete();
+
+    // Applying the same values again changes nothing.
+    let applied = client
+        .apply_options_delta(OptionsDelta::new().with_model("claude-opus-4-5"))
+        .await
+        .unwrap();
+    assert!(applied.applied.is_empty());
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_requires_connection() {
+    let mut client = ClaudeClient::new(None);
+    let error = client
+        .apply_options_delta(OptionsDelta::new().with_model("claude-opus-4-5"))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("not connected"), "{}", error);
+}
+
+/// A CLI that refuses permission mode changes.
+#[cfg(all(unix, feature = "subprocess"))]
+fn mode_refusing_cli() -> claude_agents_sdk::testing::FakeCli {
+    claude_agents_sdk::testing::FakeCli::builder()
+        .fail_control("set_permission_mode", "no")
+        .build()
+        .unwrap()
+}
+
+#[cfg(all(unix, feature = "subprocess"))]
+#[tokio::test]
+async fn test_failed_change_restores_default_model() {
+    let cli = mode_refusing_cli();
+    let mut client = ClaudeClient::new(Some(cli.options()));
+    client.connect().await.unwrap();
+
+    let delta = OptionsDelta::new()
+        .with_model("claude-opus-4-5")
+        .with_permission_mode(PermissionMode::AcceptEdits);
+    assert!(client.apply_options_delta(delta).await.is_err());
+    client.disconnect().await.unwrap();
+
+    let models: Vec<serde_json::Value> = cli
+        .received()
+        .into_iter()
+        .filter(|request| request["request"]["subtype"] == "set_model")
+        .map(|request| request["request"]["model"].clone())
+        .collect();
+    // The model started unset, so the undo asks for the default model.
+    assert_eq!(
+        models,
+        [
+            serde_json::json!("claude-opus-4-5"),
+            serde_json::Value::Null
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_allowed_tools_need_reconnect_without_permission_callback() {
+    let transport = Scenario::new().build();
+    let options = ClaudeAgentOptions::new()
+        .with_transport(move |_| Ok(transport.clone()))
+        .with_allowed_tools(["Read"]);
+    let mut client = ClaudeClient::new(Some(options));
+    client.connect().await.unwrap();
+
+    let applied = client
+        .apply_options_delta(OptionsDelta::new().with_allowed_tools(["Read", "Bash"]))
+        .await
+        .unwrap();
+    assert!(applied.applied.is_empty());
+    assert_eq!(applied.requires_reconnect, ["allowed_tools"]);
+    client.disconnect().await.unwrap();
+}
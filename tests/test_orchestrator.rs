@@ -0,0 +1,211 @@
+//! Tests for multi-agent orchestration against mock CLI sessions.
+
+#![cfg(feature = "testing")]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use claude_agents_sdk::orchestrator::{Orchestrator, OrchestratorEvent, StopReason};
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::{
+    AgentDefinition, ClaudeAgentOptions, McpServerConfig, McpServersConfig, McpStdioServerConfig,
+    PermissionMode, SystemPromptConfig,
+};
+use tokio_stream::StreamExt;
+
+fn agent(prompt: &str) -> AgentDefinition {
+    AgentDefinition {
+        description: String::new(),
+        prompt: prompt.to_string(),
+        tools: None,
+        model: None,
+        mcp_servers: None,
+        permission_mode: None,
+    }
+}
+
+/// A session answering `prompt` with `text` at a cost of `cost_usd`.
+fn session(prompt: &str, text: &str, cost_usd: f64) -> ScenarioTransport {
+    Scenario::new()
+        .expect_user(prompt)
+        .reply_text(text)
+        .charge(cost_usd)
+        .build()
+}
+
+/// Options connecting each agent, told apart by system prompt, to its
+/// session.
+fn options(sessions: HashMap<&'static str, ScenarioTransport>) -> ClaudeAgentOptions {
+    ClaudeAgentOptions::new().with_transport(move |options| {
+        let Some(SystemPromptConfig::Text(prompt)) = &options.system_prompt else {
+            panic!("no system prompt");
+        };
+        Ok(sessions[prompt.as_str()].clone())
+    })
+}
+
+async fn events(orchestrator: &Orchestrator, prompt: &str) -> Vec<OrchestratorEvent> {
+    orchestrator.run("planner", prompt).unwrap().collect().await
+}
+
+fn handoffs(events: &[OrchestratorEvent]) -> Vec<(String, String, String)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            OrchestratorEvent::Handoff { from, to, prompt } => {
+                Some((from.clone(), to.clone(), prompt.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_routes_through_agents() {
+    let executor = session("1. Add a --verbose flag.", "Done: added the flag.", 0.2);
+    let reviewer = session("Done: added the flag.", "APPROVED", 0.05);
+    let orchestrator = Orchestrator::new(options(HashMap::from([
+        (
+            "plan",
+            session("Add a verbose flag", "1. Add a --verbose flag.", 0.1),
+        ),
+        ("execute", executor.clone()),
+        ("review", reviewer.clone()),
+    ])))
+    .with_agent("planner", agent("plan"))
+    .with_agent("executor", agent("execute"))
+    .with_agent("reviewer", agent("review"))
+    .with_route("planner", "executor")
+    .with_route("executor", "reviewer");
+
+    let events = events(&orchestrator, "Add a verbose flag").await;
+    assert_eq!(
+        handoffs(&events),
+        vec![
+            (
+                "planner".to_string(),
+                "executor".to_string(),
+                "1. Add a --verbose flag.".to_string()
+            ),
+            (
+                "executor".to_string(),
+                "reviewer".to_string(),
+                "Done: added the flag.".to_string()
+            ),
+        ]
+    );
+    executor.assert_complete();
+    reviewer.assert_complete();
+
+    let Some(OrchestratorEvent::Finished {
+        reason,
+        total_cost_usd,
+        cost_by_agent,
+    }) = events.last()
+    else {
+        panic!("not finished: {:?}", events.last());
+    };
+    assert_eq!(*reason, StopReason::Completed);
+    assert!((total_cost_usd - 0.35).abs() < 1e-9);
+    assert_eq!(cost_by_agent["reviewer"], 0.05);
+    let started = events
+        .iter()
+        .filter(|event| matches!(event, OrchestratorEvent::Started { .. }))
+        .count();
+    assert_eq!(started, 3);
+}
+
+#[tokio::test]
+async fn test_stops_at_shared_budget() {
+    let executor = session("A long plan.", "Done.", 0.2);
+    let orchestrator = Orchestrator::new(options(HashMap::from([
+        ("plan", session("Plan something big", "A long plan.", 1.5)),
+        ("execute", executor.clone()),
+    ])))
+    .with_agent("planner", agent("plan"))
+    .with_agent("executor", agent("execute"))
+    .with_route("planner", "executor")
+    .with_budget_usd(1.0);
+
+    let events = events(&orchestrator, "Plan something big").await;
+    assert!(handoffs(&events).is_empty());
+    assert!(!executor.is_complete());
+    assert!(matches!(
+        events.last(),
+        Some(OrchestratorEvent::Finished {
+            reason: StopReason::BudgetExceeded,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_stops_at_handoff_limit() {
+    let orchestrator = Orchestrator::new(options(HashMap::from([
+        ("plan", session("Ping", "Over to you.", 0.0)),
+        ("execute", session("Over to you.", "Back to you.", 0.0)),
+    ])))
+    .with_agent("planner", agent("plan"))
+    .with_agent("executor", agent("execute"))
+    .with_route("planner", "executor")
+    .with_route("executor", "planner")
+    .with_max_handoffs(1);
+
+    let events = events(&orchestrator, "Ping").await;
+    assert_eq!(handoffs(&events).len(), 1);
+    assert!(matches!(
+        events.last(),
+        Some(OrchestratorEvent::Finished {
+            reason: StopReason::HandoffLimit,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_agent_options_follow_definition() {
+    let server = |command: &str| {
+        McpServerConfig::Stdio(McpStdioServerConfig {
+            server_type: "stdio".to_string(),
+            command: command.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+        })
+    };
+    let seen = Arc::new(Mutex::new(None));
+    let transport = session("Look it up", "Found it.", 0.0);
+    let mut options = {
+        let seen = Arc::clone(&seen);
+        ClaudeAgentOptions::new()
+            .with_permission_mode(PermissionMode::AcceptEdits)
+            .with_transport(move |options| {
+                *seen.lock().unwrap() = Some(options.clone());
+                Ok(transport.clone())
+            })
+    };
+    options.mcp_servers = McpServersConfig::Map(HashMap::from([
+        ("docs".to_string(), server("docs-server")),
+        ("shell".to_string(), server("shell-server")),
+    ]));
+    let mut researcher = agent("research");
+    researcher.permission_mode = Some(PermissionMode::Plan);
+    researcher.mcp_servers = Some(vec!["docs".to_string()]);
+    let orchestrator = Orchestrator::new(options).with_agent("planner", researcher);
+
+    events(&orchestrator, "Look it up").await;
+    let seen = seen.lock().unwrap().take().unwrap();
+    assert_eq!(seen.permission_mode, Some(PermissionMode::Plan));
+    let McpServersConfig::Map(servers) = &seen.mcp_servers else {
+        panic!("Expected a server map");
+    };
+    assert_eq!(servers.keys().collect::<Vec<_>>(), vec!["docs"]);
+}
+
+#[tokio::test]
+async fn test_rejects_unknown_agent() {
+    let orchestrator =
+        Orchestrator::new(ClaudeAgentOptions::new()).with_agent("planner", agent("plan"));
+    let error = orchestrator.run("reviewer", "Hello").unwrap_err();
+    assert!(error.to_string().contains("Unknown agent 'reviewer'"));
+    assert_eq!(orchestrator.agents(), vec!["planner".to_string()]);
+}
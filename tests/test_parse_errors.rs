@@ -0,0 +1,72 @@
+//! Tests for recovering from malformed CLI output.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{query, ClaudeAgentOptions, ClaudeSDKError, Message, ParseErrorPolicy};
+use tokio_stream::StreamExt;
+
+/// A fake CLI that answers the prompt with `output` and a result.
+fn fake_cli(output: &str) -> FakeCli {
+    let result = r#"{"type":"result","subtype":"success","duration_ms":1,"duration_api_ms":1,"is_error":false,"num_turns":1,"session_id":"s1"}"#;
+    FakeCli::builder()
+        .raw_turn(format!("{}\n{}", output, result))
+        .build()
+        .unwrap()
+}
+
+const GARBAGE_THEN_TEXT: &str = r#"{"type":"assistant","message":{"model":"m","content":[{"type":"text","text":"trunc
+{"type":"assistant","message":{"model":"m"}}
+{"type":"assistant","message":{"model":"m","content":[{"type":"text","text":"Hello"}]}}"#;
+
+async fn collect(policy: ParseErrorPolicy) -> Vec<Result<Message, ClaudeSDKError>> {
+    let cli = fake_cli(GARBAGE_THEN_TEXT);
+    let options = cli.options().with_parse_error_policy(policy);
+
+    let stream = query("Hi", Some(options)).await.unwrap();
+    stream.collect().await
+}
+
+#[tokio::test]
+async fn test_skip_and_report_continues_after_malformed_lines() {
+    let items = collect(ParseErrorPolicy::SkipAndReport).await;
+    let messages: Vec<Message> = items.into_iter().map(|item| item.unwrap()).collect();
+
+    assert_eq!(messages.len(), 4, "{:?}", messages);
+    match &messages[0] {
+        Message::ParseWarning(warning) => {
+            assert!(warning.message.contains("Failed to parse JSON"));
+            assert!(warning.line.as_deref().unwrap().contains("trunc"));
+            assert!(warning.offset.is_some());
+        }
+        other => panic!("Expected ParseWarning, got {:?}", other),
+    }
+    match &messages[1] {
+        Message::ParseWarning(warning) => {
+            assert_eq!(warning.path.as_deref(), Some("/message/content"));
+            assert!(warning.line.is_some());
+        }
+        other => panic!("Expected ParseWarning, got {:?}", other),
+    }
+    match &messages[2] {
+        Message::Assistant(assistant) => assert_eq!(assistant.text(), "Hello"),
+        other => panic!("Expected Assistant, got {:?}", other),
+    }
+    assert!(matches!(messages[3], Message::Result(_)));
+}
+
+#[tokio::test]
+async fn test_fail_policy_ends_stream_on_malformed_json() {
+    let items = collect(ParseErrorPolicy::Fail).await;
+
+    assert_eq!(items.len(), 1, "{:?}", items);
+    assert!(matches!(items[0], Err(ClaudeSDKError::JSONDecode { .. })));
+}
+
+#[test]
+fn test_default_policy_is_fail() {
+    assert_eq!(
+        ClaudeAgentOptions::new().parse_error_policy,
+        ParseErrorPolicy::Fail
+    );
+}
@@ -0,0 +1,233 @@
+//! Tests for reporting CLI process failures and unexpected exits.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::_internal::transport::{SubprocessTransport, Transport};
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{ClaudeClient, ClaudeSDKError, Redactor};
+use std::path::PathBuf;
+
+async fn first_error(script: &str) -> (PathBuf, Option<ClaudeSDKError>) {
+    let cli = FakeCli::from_script(script).unwrap();
+    let options = cli.options().with_model("test-model");
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+    let mut error = None;
+    while let Some(item) = stdout.recv().await {
+        if let Err(e) = item {
+            error = Some(e);
+            break;
+        }
+    }
+    transport.close().await.unwrap();
+    (cli.path().to_path_buf(), error)
+}
+
+#[tokio::test]
+async fn test_nonzero_exit_reports_stderr_and_argv() {
+    let (cli, error) =
+        first_error("echo 'starting' >&2\necho 'Error: invalid API key' >&2\nexit 3").await;
+
+    match error.expect("expected an error") {
+        ClaudeSDKError::ProcessFailed {
+            exit_code,
+            stderr_tail,
+            argv,
+        } => {
+            assert_eq!(exit_code, Some(3));
+            assert_eq!(stderr_tail, "starting\nError: invalid API key\n");
+            assert_eq!(argv[0], cli.display().to_string());
+            assert!(argv.windows(2).any(|w| w == ["--model", "test-model"]));
+        }
+        other => panic!("Expected ProcessFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_unparsable_output_then_exit_reports_process_failure() {
+    let (_, error) = first_error("echo 'panic: boom'\necho 'stack trace' >&2\nexit 1").await;
+
+    match error.expect("expected an error") {
+        ClaudeSDKError::ProcessFailed {
+            exit_code,
+            stderr_tail,
+            ..
+        } => {
+            assert_eq!(exit_code, Some(1));
+            assert!(stderr_tail.contains("stack trace"));
+        }
+        other => panic!("Expected ProcessFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_exit_after_end_input_is_not_an_error() {
+    let cli = FakeCli::from_script(
+        "echo '{\"type\":\"system\"}'\ncat >/dev/null\necho 'note' >&2\nexit 0",
+    )
+    .unwrap();
+    let options = cli.options();
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+    assert!(stdout.recv().await.unwrap().is_ok());
+    transport.end_input().await.unwrap();
+    assert!(stdout.recv().await.is_none());
+    transport.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_close_is_not_an_error() {
+    let cli = FakeCli::from_script("exec cat").unwrap();
+    let options = cli.options();
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+    transport.close().await.unwrap();
+    assert!(stdout.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_exit_with_stdin_open_is_unexpected() {
+    let (cli, error) = first_error("echo '{\"type\":\"system\"}'\nexit 0").await;
+
+    match error.expect("expected an error") {
+        ClaudeSDKError::ProcessExited {
+            code, signal, argv, ..
+        } => {
+            assert_eq!(code, Some(0));
+            assert_eq!(signal, None);
+            assert_eq!(argv[0], cli.display().to_string());
+        }
+        other => panic!("Expected ProcessExited, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_crash_mid_session_is_process_exited() {
+    let (_, error) =
+        first_error("echo '{\"type\":\"system\"}'\necho 'fatal: out of memory' >&2\nexit 134")
+            .await;
+
+    let error = error.expect("expected an error");
+    assert!(
+        error
+            .to_string()
+            .ends_with("(exit code 134): fatal: out of memory"),
+        "{}",
+        error
+    );
+    match error {
+        ClaudeSDKError::ProcessExited { code, stderr, .. } => {
+            assert_eq!(code, Some(134));
+            assert_eq!(stderr, "fatal: out of memory\n");
+        }
+        other => panic!("Expected ProcessExited, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_killed_by_signal() {
+    let (_, error) = first_error("echo '{\"type\":\"system\"}'\nkill -9 $$").await;
+
+    match error.expect("expected an error") {
+        ClaudeSDKError::ProcessExited { code, signal, .. } => {
+            assert_eq!(code, None);
+            assert_eq!(signal, Some(9));
+        }
+        other => panic!("Expected ProcessExited, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_stderr_tail_is_bounded() {
+    let (_, error) = first_error(
+        "i=0\nwhile [ $i -lt 2000 ]; do echo \"line $i\" >&2; i=$((i+1)); done\nexit 1",
+    )
+    .await;
+
+    match error.expect("expected an error") {
+        ClaudeSDKError::ProcessFailed { stderr_tail, .. } => {
+            assert!(stderr_tail.len() <= 8 * 1024);
+            assert!(stderr_tail.ends_with("line 1999\n"));
+            assert!(!stderr_tail.contains("line 0\n"));
+        }
+        other => panic!("Expected ProcessFailed, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_connect_fails_with_process_failure() {
+    let cli = FakeCli::from_script("echo 'Error: not logged in' >&2\nexit 2").unwrap();
+    let options = cli.options();
+
+    let mut client = ClaudeClient::new(Some(options));
+    let error = client.connect().await.unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ClaudeSDKError::ProcessFailed {
+                exit_code: Some(2),
+                ..
+            }
+        ),
+        "got {:?}",
+        error
+    );
+    assert!(error.to_string().contains("Error: not logged in"));
+}
+
+#[tokio::test]
+async fn test_unanswered_initialize_times_out() {
+    let cli = FakeCli::builder()
+        .ignore_control("initialize")
+        .build()
+        .unwrap();
+    let options = cli
+        .options()
+        .with_control_timeout(std::time::Duration::from_millis(200));
+
+    let mut client = ClaudeClient::new(Some(options));
+    let error = client.connect().await.unwrap_err();
+    match error {
+        ClaudeSDKError::ControlRequestTimedOut {
+            ref request_type,
+            duration_ms,
+            ..
+        } => {
+            assert_eq!(request_type, "initialize");
+            assert_eq!(duration_ms, 200);
+        }
+        other => panic!("Expected ControlRequestTimedOut, got {:?}", other),
+    }
+    assert_eq!(error.kind(), claude_agents_sdk::ErrorKind::Timeout);
+}
+
+#[tokio::test]
+async fn test_process_failure_is_redacted() {
+    let cli = FakeCli::from_script(
+        "echo 'invalid key sk-ant-REDACTED' >&2\nexit 1",
+    )
+    .unwrap();
+    let options = cli
+        .options()
+        .with_system_prompt("Contact ops@example.com")
+        .with_redactor(Redactor::default());
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+    match stdout.recv().await.unwrap().unwrap_err() {
+        ClaudeSDKError::ProcessFailed {
+            stderr_tail, argv, ..
+        } => {
+            assert_eq!(stderr_tail, "invalid key [REDACTED]\n");
+            assert!(argv.iter().any(|arg| arg == "Contact [REDACTED]"));
+        }
+        other => panic!("Expected ProcessFailed, got {:?}", other),
+    }
+}
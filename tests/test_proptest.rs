@@ -346,4 +346,93 @@ proptest! {
         prop_assert_eq!(opts1.max_turns, opts2.max_turns);
         prop_assert_eq!(opts1.timeout_secs, opts2.timeout_secs);
     }
+
+    /// Canonical JSON should survive a round trip unchanged.
+    #[test]
+    fn prop_canonical_json_roundtrip(
+        text in arbitrary_safe_string(),
+        tool_name in arbitrary_tool_name(),
+        tool_id in arbitrary_tool_id(),
+        session_id in arbitrary_session_id()
+    ) {
+        let raw = json!({
+            "type": "assistant",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "content": [
+                    {"type": "text", "text": text},
+                    {"type": "tool_use", "id": tool_id, "name": tool_name, "input": {"b": 1, "a": [text]}}
+                ]
+            },
+            "session_id": session_id
+        });
+        let msg = parse_message(raw).unwrap().unwrap();
+
+        let canonical = msg.to_canonical_json();
+        let back = Message::from_canonical_json(&canonical).unwrap();
+        prop_assert_eq!(back.to_canonical_json(), canonical);
+    }
+}
+
+// ============================================================================
+// Framing Properties
+// ============================================================================
+
+fn arbitrary_documents() -> impl Strategy<Value = Vec<serde_json::Value>> {
+    prop::collection::vec(
+        (arbitrary_safe_string(), any::<i32>(), any::<bool>()).prop_map(|(text, n, flag)| {
+            json!({"type": "assistant", "text": text, "n": n, "nested": {"flag": flag, "list": [n, text]}})
+        }),
+        1..8,
+    )
+}
+
+proptest! {
+    /// Documents come out whole wherever the output is cut into lines.
+    #[test]
+    fn prop_framing_survives_random_line_breaks(
+        documents in arbitrary_documents(),
+        cuts in prop::collection::vec(any::<prop::sample::Index>(), 0..12),
+        separator in prop::sample::select(vec!["", " ", "\n"])
+    ) {
+        let text = documents
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(separator);
+        // The text is ASCII, so any byte offset is a char boundary.
+        let mut offsets: Vec<usize> = cuts.iter().map(|cut| cut.index(text.len() + 1)).collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        let mut output = String::new();
+        let mut last = 0;
+        for offset in offsets {
+            output.push_str(&text[last..offset]);
+            output.push('\n');
+            last = offset;
+        }
+        output.push_str(&text[last..]);
+
+        let framed: Vec<_> = claude_agents_sdk::_internal::framing::frame_all(&output, 1 << 20)
+            .into_iter()
+            .collect::<Result<_>>()
+            .unwrap();
+        prop_assert_eq!(framed, documents);
+    }
+
+    /// One document per line frames the same as decoding each line.
+    #[test]
+    fn prop_framing_matches_line_decoding(documents in arbitrary_documents()) {
+        let output = documents
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let framed: Vec<_> = claude_agents_sdk::_internal::framing::frame_all(&output, 1 << 20)
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect();
+        let decoded: Vec<_> = output.lines().map(|line| decode_line(line).unwrap()).collect();
+        prop_assert_eq!(framed, decoded);
+    }
 }
@@ -0,0 +1,181 @@
+//! Tests for session recording and replay.
+
+use claude_agents_sdk::recorder::{
+    Recording, RecordingHeader, ReplayEvent, Replayer, RECORDING_FORMAT_VERSION,
+};
+use claude_agents_sdk::{ControlRequestPayload, Message, WireDirection, WireEntry};
+use serde_json::json;
+
+fn entry(direction: WireDirection, line: serde_json::Value) -> WireEntry {
+    WireEntry {
+        timestamp_ms: 1,
+        direction,
+        line: line.to_string(),
+        metadata: Default::default(),
+    }
+}
+
+fn sample_recording() -> Recording {
+    Recording {
+        header: RecordingHeader::default(),
+        entries: vec![
+            entry(
+                WireDirection::Sent,
+                json!({"type": "control_request", "request_id": "req_1", "request": {"subtype": "interrupt"}}),
+            ),
+            entry(
+                WireDirection::Received,
+                json!({"type": "control_response", "response": {"subtype": "success", "request_id": "req_1"}}),
+            ),
+            entry(
+                WireDirection::Sent,
+                json!({"type": "user", "message": {"role": "user", "content": "Hi"}}),
+            ),
+            entry(
+                WireDirection::Received,
+                json!({"type": "assistant", "message": {"model": "m", "content": [{"type": "text", "text": "Hello"}]}}),
+            ),
+            WireEntry {
+                timestamp_ms: 2,
+                direction: WireDirection::Received,
+                line: "{truncated".to_string(),
+                metadata: Default::default(),
+            },
+            entry(WireDirection::Received, json!({"type": "future_type"})),
+            entry(
+                WireDirection::Received,
+                json!({
+                    "type": "result", "subtype": "success", "duration_ms": 1, "duration_api_ms": 1,
+                    "is_error": false, "num_turns": 1, "session_id": "s1"
+                }),
+            ),
+        ],
+    }
+}
+
+#[test]
+fn test_step_through_recording() {
+    let mut replayer = Replayer::new(sample_recording());
+    assert_eq!(replayer.remaining(), 7);
+
+    let step = replayer.step().unwrap();
+    assert_eq!(step.index, 0);
+    match step.event {
+        ReplayEvent::ControlRequest(request) => {
+            assert!(matches!(request.request, ControlRequestPayload::Interrupt))
+        }
+        other => panic!("Expected control request, got {:?}", other),
+    }
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::ControlResponse(_)
+    ));
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::Sent(_)
+    ));
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::Message(Message::Assistant(_))
+    ));
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::Error(_)
+    ));
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::Skipped
+    ));
+    assert!(matches!(
+        replayer.step().unwrap().event,
+        ReplayEvent::Message(Message::Result(_))
+    ));
+    assert!(replayer.step().is_none());
+
+    replayer.rewind();
+    assert_eq!(replayer.remaining(), 7);
+}
+
+#[test]
+fn test_messages_matches_client_stream() {
+    let results: Vec<_> = Replayer::new(sample_recording()).messages().collect();
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[0], Ok(Message::Assistant(_))));
+    assert!(results[1].is_err());
+    assert!(matches!(results[2], Ok(Message::Result(_))));
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let recording = sample_recording();
+    recording.save(&path).unwrap();
+
+    let loaded = Recording::load(&path).unwrap();
+    assert_eq!(loaded, recording);
+    assert_eq!(loaded.header.recording, RECORDING_FORMAT_VERSION);
+}
+
+#[test]
+fn test_load_rejects_invalid_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.jsonl");
+
+    std::fs::write(&path, "").unwrap();
+    assert!(Recording::load(&path).is_err());
+
+    std::fs::write(
+        &path,
+        r#"{"recording":99,"sdk_version":"9.9.9","created_ms":0}"#,
+    )
+    .unwrap();
+    let err = Recording::load(&path).unwrap_err();
+    assert!(err.to_string().contains("unsupported format version 99"));
+
+    let header = serde_json::to_string(&RecordingHeader::default()).unwrap();
+    std::fs::write(&path, format!("{}\nnot an entry\n", header)).unwrap();
+    let err = Recording::load(&path).unwrap_err();
+    assert!(err.to_string().contains("line 2"));
+}
+
+#[cfg(all(unix, feature = "subprocess"))]
+#[tokio::test]
+async fn test_recorder_captures_live_session() {
+    use claude_agents_sdk::_internal::transport::{SubprocessTransport, Transport};
+    use claude_agents_sdk::recorder::Recorder;
+    use claude_agents_sdk::ClaudeAgentOptions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cli = dir.path().join("claude");
+    std::fs::write(&cli, "#!/bin/sh\nexec cat\n").unwrap();
+    std::fs::set_permissions(&cli, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = dir.path().join("session.jsonl");
+
+    let mut options = ClaudeAgentOptions::new().with_wire_log(Recorder::start(&path).unwrap());
+    options.cli_path = Some(cli);
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+
+    let line = json!({"type": "assistant", "message": {"model": "m", "content": []}});
+    transport.write(&line.to_string()).await.unwrap();
+    stdout.recv().await.unwrap().unwrap();
+    transport.close().await.unwrap();
+
+    let mut recording = Recording::load(&path).unwrap();
+    for _ in 0..50 {
+        if recording.entries.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        recording = Recording::load(&path).unwrap();
+    }
+    assert_eq!(recording.entries.len(), 2);
+    assert_eq!(recording.entries[0].direction, WireDirection::Sent);
+
+    let messages: Vec<_> = Replayer::new(recording).messages().collect();
+    assert_eq!(messages.len(), 1);
+    assert!(matches!(messages[0], Ok(Message::Assistant(_))));
+}
@@ -0,0 +1,100 @@
+//! Tests for the chat loop in `repl`, against the fake CLI.
+
+#![cfg(all(unix, feature = "testing", feature = "subprocess"))]
+
+use claude_agents_sdk::render::RenderOptions;
+use claude_agents_sdk::repl;
+use claude_agents_sdk::testing::FakeCli;
+use serde_json::json;
+
+fn turn(text: &str) -> Vec<serde_json::Value> {
+    vec![
+        json!({
+            "type": "assistant",
+            "message": {"model": "fake-model", "content": [{"type": "text", "text": text}]},
+            "session_id": "fake-session"
+        }),
+        json!({
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "duration_ms": 1500,
+            "duration_api_ms": 5,
+            "num_turns": 1,
+            "session_id": "fake-session",
+            "result": text
+        }),
+    ]
+}
+
+async fn run(cli: &FakeCli, input: &str) -> String {
+    let mut output = Vec::new();
+    repl::run_with(
+        cli.options(),
+        RenderOptions::plain(),
+        input.as_bytes(),
+        &mut output,
+    )
+    .await
+    .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn test_repl_sends_prompts_in_order() {
+    let cli = FakeCli::new(vec![turn("First answer"), turn("Second answer")]).unwrap();
+    let output = run(&cli, "One\nTwo\n").await;
+
+    assert_eq!(cli.prompts(), vec!["One", "Two"]);
+    let first = output.find("First answer").expect(&output);
+    let second = output.find("Second answer").expect(&output);
+    assert!(first < second, "{}", output);
+    assert!(output.contains("✓ success · 1 turn · 1.5s"), "{}", output);
+    assert!(cli
+        .args()
+        .contains(&"--include-partial-messages".to_string()));
+}
+
+#[tokio::test]
+async fn test_repl_commands() {
+    let cli = FakeCli::new(vec![turn("Planned")]).unwrap();
+    let output = run(
+        &cli,
+        "/model\n/mode plan\n/mode\n/model claude-opus-4-1\n/interrupt\n/mode yolo\n/nope\nGo\n/exit\nIgnored\n",
+    )
+    .await;
+
+    assert!(
+        output.contains("Permission mode set to plan."),
+        "{}",
+        output
+    );
+    assert!(output.contains("Permission mode: plan"), "{}", output);
+    assert!(
+        output.contains("Model set to claude-opus-4-1."),
+        "{}",
+        output
+    );
+    assert!(output.contains("Nothing to interrupt."), "{}", output);
+    assert!(
+        output.contains("Unknown permission mode 'yolo'"),
+        "{}",
+        output
+    );
+    assert!(output.contains("Unknown command /nope"), "{}", output);
+    assert!(output.contains("Model: default"), "{}", output);
+    assert!(output.contains("Planned"), "{}", output);
+    assert_eq!(cli.prompts(), vec!["Go"]);
+
+    let controls = cli.control_requests();
+    assert!(
+        controls.contains(&"set_permission_mode".to_string()),
+        "{:?}",
+        controls
+    );
+    assert!(
+        controls.contains(&"set_model".to_string()),
+        "{:?}",
+        controls
+    );
+}
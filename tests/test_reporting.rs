@@ -0,0 +1,177 @@
+//! Tests for cost reports.
+
+use claude_agents_sdk::_internal::message_parser::parse_message;
+use claude_agents_sdk::reporting::CostReport;
+use claude_agents_sdk::Message;
+use serde_json::{json, Value};
+
+fn message(raw: Value) -> Message {
+    parse_message(raw).unwrap().unwrap()
+}
+
+fn assistant(content: Value, usage: Value, parent: Option<&str>) -> Message {
+    message(json!({
+        "type": "assistant",
+        "parent_tool_use_id": parent,
+        "message": {"model": "m", "content": content, "usage": usage}
+    }))
+}
+
+fn tool_use(id: &str, name: &str) -> Value {
+    json!({"type": "tool_use", "id": id, "name": name, "input": {}})
+}
+
+fn tool_result(id: &str) -> Message {
+    message(json!({
+        "type": "user",
+        "message": {"content": [{"type": "tool_result", "tool_use_id": id, "content": "ok"}]}
+    }))
+}
+
+fn result(session_id: &str, total_cost_usd: f64, input: u64, output: u64) -> Message {
+    message(json!({
+        "type": "result", "subtype": "success", "duration_ms": 1500, "duration_api_ms": 1000,
+        "is_error": false, "num_turns": 1, "session_id": session_id,
+        "total_cost_usd": total_cost_usd,
+        "usage": {"input_tokens": input, "output_tokens": output}
+    }))
+}
+
+/// A turn where the model reads a file, then answers.
+fn read_turn(session_id: &str, total_cost_usd: f64) -> Vec<Message> {
+    let first = json!({"input_tokens": 100, "output_tokens": 20});
+    vec![
+        assistant(
+            json!([{"type": "text", "text": "Reading"}]),
+            first.clone(),
+            None,
+        ),
+        assistant(json!([tool_use("tu_1", "Read")]), first, None),
+        tool_result("tu_1"),
+        assistant(
+            json!([{"type": "text", "text": "Done"}]),
+            json!({"input_tokens": 10, "cache_read_input_tokens": 170, "output_tokens": 10}),
+            None,
+        ),
+        result(session_id, total_cost_usd, 280, 30),
+    ]
+}
+
+#[test]
+fn test_turn_costs_are_deltas_of_cumulative_cost() {
+    let mut messages = read_turn("s1", 0.01);
+    messages.extend(read_turn("s1", 0.03));
+    let report = CostReport::from_messages(&messages);
+
+    assert_eq!(report.turns.len(), 2);
+    assert!((report.turns[0].cost_usd - 0.01).abs() < 1e-9);
+    assert!((report.turns[1].cost_usd - 0.02).abs() < 1e-9);
+    assert!((report.total_cost_usd - 0.03).abs() < 1e-9);
+    assert_eq!(report.input_tokens, 560);
+    assert_eq!(report.output_tokens, 60);
+
+    assert_eq!(report.sessions.len(), 1);
+    assert_eq!(report.sessions[0].turns, 2);
+    assert!((report.sessions[0].cost_usd - 0.03).abs() < 1e-9);
+}
+
+#[test]
+fn test_tool_attribution_from_usage_deltas() {
+    let report = CostReport::from_messages(&read_turn("s1", 0.01));
+
+    assert_eq!(report.turns[0].tool_calls, 1);
+    assert_eq!(report.tools.len(), 1);
+    let read = &report.tools[0];
+    assert_eq!(read.tool_name, "Read");
+    assert_eq!(read.calls, 1);
+    // The requesting call's output tokens, deduplicated across its two messages.
+    assert_eq!(read.output_tokens, 20);
+    // The next call's context grew by 180 - (100 + 20) tokens.
+    assert_eq!(read.input_tokens, 60);
+    // 80 of the turn's 310 call tokens.
+    assert!((read.cost_usd - 0.01 * 80.0 / 310.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_subagent_usage_charged_to_spawning_tool() {
+    let messages = vec![
+        assistant(
+            json!([tool_use("tu_task", "Task")]),
+            json!({"input_tokens": 100, "output_tokens": 10}),
+            None,
+        ),
+        assistant(
+            json!([{"type": "text", "text": "sub"}]),
+            json!({"input_tokens": 50, "output_tokens": 40}),
+            Some("tu_task"),
+        ),
+        tool_result("tu_task"),
+        result("s1", 0.02, 150, 50),
+    ];
+    let report = CostReport::from_messages(&messages);
+
+    let task = &report.tools[0];
+    assert_eq!(task.tool_name, "Task");
+    assert_eq!(task.input_tokens, 50);
+    assert_eq!(task.output_tokens, 50);
+    assert!((task.cost_usd - 0.02 * 100.0 / 200.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_multiple_sessions_and_unfinished_turn() {
+    let mut messages = read_turn("s1", 0.01);
+    messages.extend(read_turn("s2", 0.05));
+    messages.push(assistant(
+        json!([tool_use("tu_9", "Bash")]),
+        json!({"input_tokens": 5, "output_tokens": 5}),
+        None,
+    ));
+    let report = CostReport::from_messages(&messages);
+
+    assert_eq!(report.sessions.len(), 2);
+    assert_eq!(report.sessions[1].session_id, "s2");
+    assert!((report.sessions[1].cost_usd - 0.05).abs() < 1e-9);
+    assert!(report.tools.iter().all(|t| t.tool_name != "Bash"));
+}
+
+#[test]
+fn test_renderers() {
+    let report = CostReport::from_messages(&read_turn("s,1", 0.01));
+
+    let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["turns"][0]["tool_calls"], 1);
+    assert_eq!(json["tools"][0]["tool_name"], "Read");
+
+    let csv = report.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "scope,name,calls,input_tokens,output_tokens,cost_usd"
+    );
+    assert!(lines.contains(&"session,\"s,1\",1,280,30,0.010000"));
+    assert!(lines.iter().any(|l| l.starts_with("tool,Read,1,60,20,")));
+
+    let markdown = report.to_markdown();
+    assert!(markdown.contains("# Cost report"));
+    assert!(markdown.contains("| Read | 1 | 60 | 20 |"));
+}
+
+#[test]
+fn test_metadata_in_renderings() {
+    let report = CostReport::from_messages(&read_turn("s1", 0.01))
+        .with_metadata([("tenant", "acme"), ("request_id", "r-42")]);
+
+    let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["metadata"]["tenant"], "acme");
+    assert!(report
+        .to_markdown()
+        .contains("**Metadata:** `request_id=r-42`, `tenant=acme`"));
+    assert!(!CostReport::default().to_json().contains("metadata"));
+}
+
+#[test]
+fn test_empty_report() {
+    let report = CostReport::from_messages(&[]);
+    assert_eq!(report, CostReport::default());
+    assert_eq!(report.to_csv().lines().count(), 2);
+}
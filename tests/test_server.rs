@@ -0,0 +1,206 @@
+//! Tests for the HTTP service against scripted CLI sessions.
+
+#![cfg(all(feature = "server", feature = "testing"))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use claude_agents_sdk::server::{AgentService, ResponseBody, SESSION_HEADER};
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::{ClaudeAgentOptions, ClaudeSDKError};
+use http::{Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use serde_json::json;
+use tower_service::Service;
+
+/// A service whose queries all run against `transport`, counting connects.
+fn service(transport: ScenarioTransport) -> (AgentService, Arc<AtomicUsize>) {
+    let connects = Arc::new(AtomicUsize::new(0));
+    let options = {
+        let connects = Arc::clone(&connects);
+        ClaudeAgentOptions::new().with_transport(move |_| {
+            connects.fetch_add(1, Ordering::SeqCst);
+            Ok(transport.clone())
+        })
+    };
+    (AgentService::new(options), connects)
+}
+
+async fn call(
+    service: &mut AgentService,
+    method: Method,
+    path: &str,
+    body: &str,
+) -> Response<ResponseBody> {
+    let request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap();
+    service.call(request).await.unwrap()
+}
+
+async fn text(response: Response<ResponseBody>) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+/// The names of the events in a server-sent event stream.
+fn event_names(body: &str) -> Vec<&str> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("event: "))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_query_streams_events() {
+    let transport = Scenario::new()
+        .expect_user("hello")
+        .reply_text("Hi there.")
+        .build();
+    let (mut service, _) = service(transport.clone());
+
+    let response = call(
+        &mut service,
+        Method::POST,
+        "/query",
+        &json!({"prompt": "hello"}).to_string(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    assert!(response.headers().get(SESSION_HEADER).is_none());
+
+    let body = text(response).await;
+    assert_eq!(event_names(&body).last(), Some(&"result"));
+    assert!(event_names(&body).contains(&"assistant"));
+    assert!(body.contains("Hi there."));
+    transport.assert_complete();
+}
+
+#[tokio::test]
+async fn test_session_reuses_one_cli() {
+    let transport = Scenario::new()
+        .expect_user("first")
+        .reply_text("One.")
+        .expect_user("second")
+        .reply_text("Two.")
+        .build();
+    let (mut service, connects) = service(transport.clone());
+
+    for (prompt, reply) in [("first", "One."), ("second", "Two.")] {
+        let body = json!({"prompt": prompt, "session_id": "s1"}).to_string();
+        let response = call(&mut service, Method::POST, "/query", &body).await;
+        assert_eq!(response.headers()[SESSION_HEADER], "s1");
+        let body = text(response).await;
+        assert!(body.contains(reply));
+        assert_eq!(event_names(&body).last(), Some(&"result"));
+    }
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert_eq!(service.sessions(), vec!["s1".to_string()]);
+    transport.assert_complete();
+
+    let response = call(&mut service, Method::DELETE, "/sessions/s1", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = call(&mut service, Method::DELETE, "/sessions/s1", "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(service.sessions().is_empty());
+}
+
+#[tokio::test]
+async fn test_options_mapper_sees_request() {
+    let (service, connects) = service(Scenario::new().build());
+    let mut service = service.with_options_mapper(|options, request, parts| {
+        if parts.headers.contains_key("authorization") {
+            Ok(options)
+        } else {
+            Err(ClaudeSDKError::configuration(format!(
+                "Not authorized to run {:?}",
+                request.prompt
+            )))
+        }
+    });
+
+    let body = json!({"prompt": "hello", "model": "claude-haiku-4-5"}).to_string();
+    let response = call(&mut service, Method::POST, "/query", &body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: serde_json::Value = serde_json::from_str(&text(response).await).unwrap();
+    assert_eq!(error["error"]["kind"], "configuration");
+    assert_eq!(connects.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_rejects_bad_requests() {
+    let (mut service, _) = service(Scenario::new().build());
+
+    let response = call(&mut service, Method::POST, "/query", "not json").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = call(&mut service, Method::GET, "/query", "").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()["allow"], "POST");
+
+    let response = call(&mut service, Method::POST, "/elsewhere", "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let huge = json!({"prompt": "x".repeat(2 * 1024 * 1024)}).to_string();
+    let response = call(&mut service, Method::POST, "/query", &huge).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_sessions_are_kept_apart_per_tenant() {
+    let transport_a = Scenario::new()
+        .expect_user("from a")
+        .reply_text("A.")
+        .build();
+    let transport_b = Scenario::new()
+        .expect_user("from b")
+        .reply_text("B.")
+        .build();
+    let options = {
+        let (a, b) = (transport_a.clone(), transport_b.clone());
+        // The tenants' directories don't exist here.
+        ClaudeAgentOptions::new()
+            .with_skip_validation(true)
+            .with_transport(move |options| {
+                match options.cwd.as_deref().and_then(|cwd| cwd.to_str()) {
+                    Some("/srv/a") => Ok(a.clone()),
+                    _ => Ok(b.clone()),
+                }
+            })
+    };
+    let mut service = AgentService::new(options).with_options_mapper(|options, _, parts| {
+        let team = parts.headers["x-team"].to_str().unwrap();
+        Ok(options.with_cwd(format!("/srv/{}", team)))
+    });
+    let request = |method: Method, path: &str, team: &str, body: String| {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .header("x-team", team)
+            .body(Full::new(Bytes::from(body)))
+            .unwrap()
+    };
+
+    for (team, prompt, reply) in [("a", "from a", "A."), ("b", "from b", "B.")] {
+        let body = json!({"prompt": prompt, "session_id": "shared"}).to_string();
+        let response = service
+            .call(request(Method::POST, "/query", team, body))
+            .await
+            .unwrap();
+        assert!(text(response).await.contains(reply));
+    }
+    transport_a.assert_complete();
+    transport_b.assert_complete();
+    assert_eq!(service.sessions().len(), 2);
+
+    // Each tenant deletes only its own session.
+    let delete = |team| request(Method::DELETE, "/sessions/shared", team, String::new());
+    let response = service.call(delete("a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = service.call(delete("a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(service.sessions(), vec!["shared".to_string()]);
+}
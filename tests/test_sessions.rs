@@ -0,0 +1,41 @@
+//! Tests for finding and resuming saved sessions.
+
+#![cfg(feature = "testing")]
+
+use std::sync::{Arc, Mutex};
+
+use claude_agents_sdk::testing::Scenario;
+use claude_agents_sdk::{ClaudeAgentOptions, ClaudeClient, Session, CLAUDE_CONFIG_DIR_ENV};
+
+#[tokio::test]
+async fn test_resume_most_recent_resumes_by_id() {
+    let config = tempfile::tempdir().unwrap();
+    let cwd = tempfile::tempdir().unwrap();
+    let resumed = Arc::new(Mutex::new(None));
+    let options = {
+        let resumed = Arc::clone(&resumed);
+        let transport = Scenario::new().build();
+        ClaudeAgentOptions::new()
+            .with_cwd(cwd.path())
+            .with_env(CLAUDE_CONFIG_DIR_ENV, config.path().to_string_lossy())
+            .with_transport(move |options| {
+                *resumed.lock().unwrap() = options.resume.clone();
+                Ok(transport.clone())
+            })
+    };
+
+    let mut client = ClaudeClient::new(Some(options.clone()));
+    let error = client.resume_most_recent().await.unwrap_err();
+    assert!(error.to_string().contains("No saved session"), "{}", error);
+    assert!(!client.is_connected());
+
+    let project = Session::project_dir(config.path(), cwd.path());
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join("5f1c.jsonl"), "").unwrap();
+
+    let mut client = ClaudeClient::new(Some(options));
+    let session = client.resume_most_recent().await.unwrap();
+    assert_eq!(session.session_id, "5f1c");
+    assert!(client.is_connected());
+    assert_eq!(resumed.lock().unwrap().as_deref(), Some("5f1c"));
+}
@@ -0,0 +1,98 @@
+//! Tests for spilling oversized CLI messages to disk.
+
+#![cfg(all(unix, feature = "subprocess", feature = "testing"))]
+
+use claude_agents_sdk::_internal::message_parser::parse_message;
+use claude_agents_sdk::_internal::transport::{SubprocessTransport, Transport};
+use claude_agents_sdk::testing::FakeCli;
+use claude_agents_sdk::{
+    ClaudeAgentOptions, ContentBlock, LargePayloadBlock, Message, UserMessageContent,
+};
+use serde_json::json;
+
+/// A fake CLI that prints `output` as soon as it starts.
+fn fake_cli(output: Vec<serde_json::Value>) -> FakeCli {
+    FakeCli::builder().on_start(output).build().unwrap()
+}
+
+async fn read_messages(options: &ClaudeAgentOptions, count: usize) -> Vec<Message> {
+    let mut transport = SubprocessTransport::new(options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+    let mut messages = Vec::new();
+    while messages.len() < count {
+        let raw = stdout.recv().await.unwrap().unwrap();
+        messages.push(parse_message(raw).unwrap().unwrap());
+    }
+    transport.close().await.unwrap();
+    messages
+}
+
+fn large_tool_result(size: usize) -> serde_json::Value {
+    json!({
+        "type": "user",
+        "message": {"content": [
+            {"type": "text", "text": "see below"},
+            {"type": "tool_result", "tool_use_id": "tu_1", "content": "x".repeat(size)}
+        ]}
+    })
+}
+
+fn blocks(message: &Message) -> &[ContentBlock] {
+    match message {
+        Message::User(user) => match &user.content {
+            UserMessageContent::Blocks(blocks) => blocks,
+            other => panic!("Expected blocks, got {:?}", other),
+        },
+        other => panic!("Expected User, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_message_is_spilled() {
+    let spill = tempfile::tempdir().unwrap();
+    let after = json!({"type": "assistant", "message": {"model": "m", "content": []}});
+    let cli = fake_cli(vec![large_tool_result(64 * 1024), after]);
+    let mut options = cli.options().with_spill_dir(spill.path());
+    options.max_buffer_size = Some(4096);
+
+    let messages = read_messages(&options, 2).await;
+
+    let blocks = blocks(&messages[0]);
+    assert_eq!(blocks[0].as_text(), Some("see below"));
+    let payload: &LargePayloadBlock = match &blocks[1] {
+        ContentBlock::LargePayload(payload) => payload,
+        other => panic!("Expected LargePayload, got {:?}", other),
+    };
+    assert!(payload.path.starts_with(spill.path()));
+    assert_eq!(payload.pointer, "/message/content/1");
+    assert!(payload.len > 64 * 1024);
+
+    match payload.load_block().unwrap() {
+        Some(ContentBlock::ToolResult(result)) => {
+            assert_eq!(result.tool_use_id, "tu_1");
+            assert_eq!(result.content.unwrap().as_str().unwrap().len(), 64 * 1024);
+        }
+        other => panic!("Expected ToolResult, got {:?}", other),
+    }
+    assert!(matches!(messages[1], Message::Assistant(_)));
+
+    // The file goes with the last block pointing into it.
+    let path = payload.path.clone();
+    drop(messages);
+    assert!(!path.exists());
+    assert_eq!(std::fs::read_dir(spill.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_without_spill_dir_long_lines_stay_in_memory() {
+    let cli = fake_cli(vec![large_tool_result(64 * 1024)]);
+    let mut options = cli.options();
+    options.max_buffer_size = Some(4096);
+
+    let messages = read_messages(&options, 1).await;
+    assert!(matches!(
+        blocks(&messages[0])[1],
+        ContentBlock::ToolResult(_)
+    ));
+}
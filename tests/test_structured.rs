@@ -0,0 +1,87 @@
+//! Tests for structured output queries against scripted CLI sessions.
+
+#![cfg(feature = "testing")]
+
+use claude_agents_sdk::testing::{Scenario, ScenarioTransport};
+use claude_agents_sdk::{
+    query_structured, ClaudeAgentOptions, ClaudeSDKError, StructuredOutputPolicy,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Release {
+    version: String,
+    breaking: bool,
+}
+
+fn options(transport: &ScenarioTransport, max_repairs: u32) -> ClaudeAgentOptions {
+    let transport = transport.clone();
+    ClaudeAgentOptions::new()
+        .with_transport(move |_| Ok(transport.clone()))
+        .with_structured_output_policy(StructuredOutputPolicy::repair(max_repairs))
+}
+
+fn schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {"version": {"type": "string"}, "breaking": {"type": "boolean"}},
+        "required": ["version", "breaking"]
+    })
+}
+
+#[tokio::test]
+async fn test_repairs_invalid_output() {
+    let transport = Scenario::new()
+        .expect_user("Describe the release")
+        .reply_text(r#"{"version": "1.2.0"}"#)
+        .expect_user_contains("missing field `breaking`")
+        .reply_text(r#"{"version": "1.2.0", "breaking": false}"#)
+        .build();
+
+    let release: Release = query_structured(
+        "Describe the release",
+        schema(),
+        Some(options(&transport, 1)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        release,
+        Release {
+            version: "1.2.0".to_string(),
+            breaking: false
+        }
+    );
+    transport.assert_complete();
+}
+
+#[tokio::test]
+async fn test_fails_once_repairs_run_out() {
+    let transport = Scenario::new()
+        .expect_user("Describe the release")
+        .reply_text("It's version 1.2.0.")
+        .expect_user_contains("could not be parsed")
+        .reply_text(r#"{"version": 1, "breaking": false}"#)
+        .build();
+
+    let error = query_structured::<Release>(
+        "Describe the release",
+        schema(),
+        Some(options(&transport, 1)),
+    )
+    .await
+    .unwrap_err();
+    match error {
+        ClaudeSDKError::MessageParse {
+            message,
+            raw_message,
+            ..
+        } => {
+            assert!(message.contains("after 2 attempts"), "{}", message);
+            assert_eq!(raw_message, Some(json!({"version": 1, "breaking": false})));
+        }
+        other => panic!("Expected MessageParse, got {:?}", other),
+    }
+    transport.assert_complete();
+}
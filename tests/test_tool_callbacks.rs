@@ -494,3 +494,134 @@ fn test_control_request_payload_mcp_status_serialization() {
     // Should serialize as {"subtype":"mcp_status"}
     assert!(json.contains("\"subtype\":\"mcp_status\""));
 }
+
+#[tokio::test]
+async fn test_with_hook_builds_hooks_map() {
+    let options = ClaudeAgentOptions::new()
+        .with_hook(
+            HookEvent::PreToolUse,
+            "Bash",
+            |input, tool_use_id, _| async move {
+                match input {
+                    HookInput::PreToolUse(pre) => assert_eq!(pre.tool_name, "Bash"),
+                    _ => panic!("Expected PreToolUse input"),
+                }
+                assert_eq!(tool_use_id.as_deref(), Some("tu_1"));
+                HookOutput::Sync(SyncHookOutput {
+                    reason: Some("checked".to_string()),
+                    ..Default::default()
+                })
+            },
+        )
+        .with_hook(HookEvent::PreToolUse, "Write|Edit", |_, _, _| async {
+            HookOutput::default()
+        })
+        .with_hook(HookEvent::Stop, "", |_, _, _| async {
+            HookOutput::default()
+        })
+        .with_hook_matcher(
+            HookEvent::PostToolUse,
+            HookMatcher::all()
+                .with_callback(|_, _, _| async { HookOutput::default() })
+                .with_timeout(5.0),
+        );
+
+    let hooks = options.hooks.unwrap();
+    let pre = &hooks[&HookEvent::PreToolUse];
+    assert_eq!(pre.len(), 2);
+    assert_eq!(pre[0].matcher.as_deref(), Some("Bash"));
+    assert_eq!(pre[1].matcher.as_deref(), Some("Write|Edit"));
+    assert_eq!(hooks[&HookEvent::Stop][0].matcher, None);
+    assert_eq!(hooks[&HookEvent::PostToolUse][0].timeout, Some(5.0));
+    assert_eq!(hooks[&HookEvent::PostToolUse][0].hooks.len(), 1);
+
+    let input = HookInput::PreToolUse(PreToolUseHookInput {
+        base: claude_agents_sdk::BaseHookInput {
+            session_id: "test-session".to_string(),
+            transcript_path: "/tmp/transcript".to_string(),
+            cwd: "/test".to_string(),
+            permission_mode: None,
+        },
+        hook_event_name: "PreToolUse".to_string(),
+        tool_name: "Bash".to_string(),
+        tool_input: serde_json::json!({"command": "ls"}),
+        tool_use_id: "tu_1".to_string(),
+    });
+    let output = (pre[0].hooks[0])(input, Some("tu_1".to_string()), HookContext::default()).await;
+    match output {
+        HookOutput::Sync(sync) => assert_eq!(sync.reason.as_deref(), Some("checked")),
+        _ => panic!("Expected sync output"),
+    }
+}
+
+#[test]
+fn test_hook_output_merge_precedence() {
+    use claude_agents_sdk::{HookSpecificOutput, PermissionBehavior, PreToolUseHookSpecificOutput};
+
+    let pre_tool_use = |decision, reason: &str, context: &str| {
+        HookOutput::Sync(SyncHookOutput {
+            hook_specific_output: Some(HookSpecificOutput::PreToolUse(
+                PreToolUseHookSpecificOutput {
+                    hook_event_name: "PreToolUse".to_string(),
+                    permission_decision: decision,
+                    permission_decision_reason: Some(reason.to_string()),
+                    additional_context: Some(context.to_string()),
+                    ..Default::default()
+                },
+            )),
+            system_message: Some(context.to_string()),
+            ..Default::default()
+        })
+    };
+    let stop = HookOutput::Sync(SyncHookOutput {
+        continue_: Some(false),
+        stop_reason: Some("policy".to_string()),
+        ..Default::default()
+    });
+
+    let merged = HookOutput::merge([
+        pre_tool_use(Some(PermissionBehavior::Allow), "fine", "audit"),
+        pre_tool_use(Some(PermissionBehavior::Deny), "not allowed", "policy"),
+        stop,
+        pre_tool_use(Some(PermissionBehavior::Ask), "check", "log"),
+    ]);
+
+    let HookOutput::Sync(merged) = merged else {
+        panic!("expected sync output");
+    };
+    assert_eq!(merged.continue_, Some(false));
+    assert_eq!(merged.stop_reason.as_deref(), Some("policy"));
+    assert_eq!(merged.system_message.as_deref(), Some("audit\npolicy\nlog"));
+    match merged.hook_specific_output {
+        Some(HookSpecificOutput::PreToolUse(output)) => {
+            assert_eq!(output.permission_decision, Some(PermissionBehavior::Deny));
+            assert_eq!(
+                output.permission_decision_reason.as_deref(),
+                Some("not allowed")
+            );
+            assert_eq!(
+                output.additional_context.as_deref(),
+                Some("audit\npolicy\nlog")
+            );
+        }
+        other => panic!("unexpected hook output {:?}", other),
+    }
+}
+
+#[test]
+fn test_hook_output_merge_block_wins() {
+    let decide = |decision: &str, reason: &str| {
+        HookOutput::Sync(SyncHookOutput {
+            decision: Some(decision.to_string()),
+            reason: Some(reason.to_string()),
+            ..Default::default()
+        })
+    };
+
+    let merged = HookOutput::merge([decide("approve", "ok"), decide("block", "unsafe")]);
+    let HookOutput::Sync(merged) = merged else {
+        panic!("expected sync output");
+    };
+    assert_eq!(merged.decision.as_deref(), Some("block"));
+    assert_eq!(merged.reason.as_deref(), Some("unsafe"));
+}
@@ -250,6 +250,8 @@ fn test_assistant_message_text_concatenates_all_text_blocks() {
         model: "claude-3-sonnet".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     assert_eq!(
@@ -278,6 +280,8 @@ fn test_assistant_message_text_skips_non_text_blocks() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     assert_eq!(
@@ -308,6 +312,8 @@ fn test_assistant_message_tool_uses_extracts_all_tool_uses() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     let tool_uses = msg.tool_uses();
@@ -325,6 +331,8 @@ fn test_assistant_message_tool_uses_returns_empty_when_no_tools() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     assert!(
@@ -353,9 +361,14 @@ fn test_result_message_success() {
         })),
         result: Some("Task completed successfully".to_string()),
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     };
 
     assert_eq!(result.subtype, "success");
+    assert_eq!(result.kind(), ResultSubtype::Success);
+    assert!(result.is_success());
     assert!(!result.is_error);
     assert_eq!(result.num_turns, 3);
     assert_eq!(result.total_cost_usd, Some(0.0042));
@@ -374,10 +387,39 @@ fn test_result_message_error() {
         usage: None,
         result: Some("API rate limit exceeded".to_string()),
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     };
 
     assert!(result.is_error);
     assert_eq!(result.subtype, "error");
+    assert_eq!(result.kind(), ResultSubtype::Other("error".to_string()));
+    assert!(!result.is_success());
+    assert!(!result.hit_max_turns());
+}
+
+#[test]
+fn test_result_subtype_helpers() {
+    let result: ResultMessage = serde_json::from_value(json!({
+        "subtype": "error_max_turns", "duration_ms": 1, "duration_api_ms": 1,
+        "is_error": true, "num_turns": 3, "session_id": "s"
+    }))
+    .unwrap();
+    assert!(result.hit_max_turns());
+    assert!(!result.hit_max_budget());
+    assert!(!result.failed_during_execution());
+
+    for subtype in [
+        "success",
+        "error_max_turns",
+        "error_max_budget_usd",
+        "error_during_execution",
+        "error_max_structured_output_retries",
+        "error_new_limit",
+    ] {
+        assert_eq!(ResultSubtype::from(subtype).to_string(), subtype);
+    }
 }
 
 // ============================================================================
@@ -393,6 +435,8 @@ fn test_message_is_assistant_discriminates_correctly() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     });
 
     assert!(assistant.is_assistant());
@@ -412,6 +456,9 @@ fn test_message_is_result_discriminates_correctly() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     assert!(result.is_result());
@@ -427,6 +474,8 @@ fn test_message_as_assistant_returns_reference() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     });
 
     let asst = msg
@@ -448,6 +497,9 @@ fn test_message_as_result_returns_reference() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     });
 
     let result = msg.as_result().expect("Should return Some for Result");
@@ -455,6 +507,105 @@ fn test_message_as_result_returns_reference() {
     assert_eq!(result.total_cost_usd, Some(0.005));
 }
 
+#[test]
+fn test_message_canonical_json_sorts_keys_and_drops_null_options() {
+    let msg = Message::Assistant(AssistantMessage {
+        content: vec![
+            ContentBlock::ToolUse(ToolUseBlock {
+                id: "tu_1".to_string(),
+                name: "Bash".to_string(),
+                input: json!({"timeout": 5, "command": "ls"}),
+            }),
+            ContentBlock::ToolResult(ToolResultBlock {
+                tool_use_id: "tu_1".to_string(),
+                content: Some(serde_json::Value::Null),
+                is_error: None,
+            }),
+        ],
+        model: "claude-sonnet-4-5".to_string(),
+        parent_tool_use_id: None,
+        error: None,
+        usage: Some(serde_json::Value::Null),
+        rate_limit: None,
+    });
+
+    let canonical = msg.to_canonical_json();
+    assert_eq!(
+        canonical,
+        r#"{
+  "content": [
+    {
+      "id": "tu_1",
+      "input": {
+        "command": "ls",
+        "timeout": 5
+      },
+      "name": "Bash",
+      "type": "tool_use"
+    },
+    {
+      "tool_use_id": "tu_1",
+      "type": "tool_result"
+    }
+  ],
+  "model": "claude-sonnet-4-5",
+  "type": "assistant"
+}"#
+    );
+}
+
+#[test]
+fn test_message_canonical_json_roundtrip() {
+    let messages = [
+        r#"{"type":"system","subtype":"init","session_id":"s1","tools":["Bash"]}"#,
+        r#"{"type":"user","message":{"content":"Hi"},"uuid":"u1"}"#,
+        r#"{"type":"assistant","message":{"model":"m","content":[{"type":"thinking","thinking":"hm","signature":"sig"},{"type":"text","text":"Hello"}]}}"#,
+        r#"{"type":"result","subtype":"success","duration_ms":10,"duration_api_ms":5,"is_error":false,"num_turns":1,"session_id":"s1","total_cost_usd":0.01,"result":"Hello"}"#,
+        r#"{"type":"stream_event","uuid":"e1","session_id":"s1","event":{"type":"message_stop"}}"#,
+    ];
+    for line in messages {
+        let raw: serde_json::Value = serde_json::from_str(line).unwrap();
+        let msg = claude_agents_sdk::_internal::message_parser::parse_message(raw)
+            .unwrap()
+            .unwrap();
+        let canonical = msg.to_canonical_json();
+        let back = Message::from_canonical_json(&canonical).unwrap();
+        assert_eq!(back.to_canonical_json(), canonical, "{}", line);
+    }
+}
+
+#[test]
+fn test_message_canonical_json_excludes_stats() {
+    let mut result = ResultMessage {
+        subtype: "success".to_string(),
+        duration_ms: 100,
+        duration_api_ms: 80,
+        is_error: false,
+        num_turns: 1,
+        session_id: "test-session".to_string(),
+        total_cost_usd: None,
+        usage: None,
+        result: None,
+        structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
+    };
+    let without = Message::Result(result.clone()).to_canonical_json();
+    result.stats = Some(Box::default());
+    assert_eq!(Message::Result(result).to_canonical_json(), without);
+}
+
+#[test]
+fn test_message_from_canonical_json_rejects_invalid() {
+    let err = Message::from_canonical_json(r#"{"type":"bogus"}"#).unwrap_err();
+    assert!(
+        matches!(err, ClaudeSDKError::JSONDecode { .. }),
+        "{:?}",
+        err
+    );
+}
+
 // ============================================================================
 // Hook Event Tests
 // ============================================================================
@@ -690,7 +841,9 @@ fn test_agent_definition_serialization() {
             "Read".to_string(),
             "Write".to_string(),
         ]),
-        model: Some(AgentModel::Sonnet),
+        model: Some(AgentModel::Sonnet.into()),
+        mcp_servers: None,
+        permission_mode: None,
     };
 
     let json = serde_json::to_value(&agent).unwrap();
@@ -747,6 +900,8 @@ fn test_empty_assistant_message_returns_empty_string() {
         model: "claude-3".to_string(),
         parent_tool_use_id: None,
         error: None,
+        usage: None,
+        rate_limit: None,
     };
 
     assert_eq!(msg.text(), "", "Empty content should return empty string");
@@ -766,6 +921,9 @@ fn test_result_message_with_zero_duration() {
         usage: None,
         result: None,
         structured_output: None,
+        permission_denials: Vec::new(),
+        stats: None,
+        rate_limit: None,
     };
 
     assert_eq!(result.duration_ms, 0);
@@ -987,3 +1145,114 @@ fn test_effort_display() {
     assert_eq!(Effort::High.to_string(), "high");
     assert_eq!(Effort::Max.to_string(), "max");
 }
+
+#[test]
+fn test_sdk_beta_serde() {
+    let betas: Vec<SdkBeta> =
+        serde_json::from_value(json!(["context-1m-2025-08-07", "new-beta-2026-01-01"])).unwrap();
+    assert_eq!(
+        betas,
+        vec![
+            SdkBeta::Context1m,
+            SdkBeta::Other("new-beta-2026-01-01".to_string())
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&betas).unwrap(),
+        json!(["context-1m-2025-08-07", "new-beta-2026-01-01"])
+    );
+    assert_eq!(SdkBeta::from("context-1m-2025-08-07"), SdkBeta::Context1m);
+}
+
+#[test]
+fn test_content_block_display() {
+    let tool_use = ContentBlock::ToolUse(ToolUseBlock {
+        id: "toolu_1".to_string(),
+        name: "Bash".to_string(),
+        input: json!({"command": "ls"}),
+    });
+    assert_eq!(tool_use.to_string(), r#"Bash({"command":"ls"})"#);
+
+    let result = ContentBlock::ToolResult(ToolResultBlock {
+        tool_use_id: "toolu_1".to_string(),
+        content: Some(json!([{"type": "text", "text": "a"}, {"type": "text", "text": "b"}])),
+        is_error: Some(true),
+    });
+    assert_eq!(result.to_string(), "[tool error toolu_1] a\nb");
+}
+
+#[test]
+fn test_message_display() {
+    let assistant = Message::Assistant(AssistantMessage {
+        content: vec![
+            ContentBlock::Text(TextBlock {
+                text: "Hello".to_string(),
+            }),
+            ContentBlock::Thinking(ThinkingBlock {
+                thinking: "hmm".to_string(),
+                signature: String::new(),
+            }),
+        ],
+        model: "claude-sonnet-4-5".to_string(),
+        parent_tool_use_id: None,
+        error: None,
+        usage: None,
+        rate_limit: None,
+    });
+    assert_eq!(assistant.to_string(), "Hello\n[thinking] hmm");
+
+    let result: ResultMessage = serde_json::from_value(json!({
+        "subtype": "success",
+        "duration_ms": 1200,
+        "duration_api_ms": 1000,
+        "is_error": false,
+        "num_turns": 2,
+        "session_id": "s",
+        "total_cost_usd": 0.5,
+        "result": "Done"
+    }))
+    .unwrap();
+    assert_eq!(
+        Message::Result(result).to_string(),
+        "[result: success, 2 turns, 1200 ms, $0.5000] Done"
+    );
+}
+
+#[test]
+fn test_model_id_conversions() {
+    assert_eq!(ModelId::from("claude-sonnet-4-5"), ModelId::Sonnet45);
+    assert_eq!(ModelId::from("haiku"), ModelId::Alias(ModelAlias::Haiku));
+    assert_eq!(
+        ModelId::from("claude-sonnet-4-5-20250929".to_string()),
+        ModelId::Custom("claude-sonnet-4-5-20250929".into())
+    );
+    for model in [
+        ModelId::Opus45,
+        ModelId::Sonnet45,
+        ModelId::Haiku45,
+        ModelId::Custom("us.anthropic.claude-opus-4-5".into()),
+    ]
+    .into_iter()
+    .chain(ModelAlias::ALL.iter().map(|alias| ModelId::Alias(*alias)))
+    {
+        assert_eq!(ModelId::from(model.to_string()), model);
+        let json = serde_json::to_value(&model).unwrap();
+        assert_eq!(json, model.as_str());
+        assert_eq!(serde_json::from_value::<ModelId>(json).unwrap(), model);
+    }
+
+    let options = ClaudeAgentOptions::new()
+        .with_model(ModelId::Opus45)
+        .with_fallback_model(ModelAlias::Sonnet.as_str());
+    assert_eq!(options.model.as_deref(), Some("claude-opus-4-5"));
+    assert_eq!(options.fallback_model.as_deref(), Some("sonnet"));
+
+    assert_eq!(
+        ModelId::from(AgentModel::Inherit),
+        ModelId::Alias(ModelAlias::Inherit)
+    );
+    assert!(matches!(
+        ModelId::from("opus"),
+        ModelId::Alias(ModelAlias::Opus)
+    ));
+}
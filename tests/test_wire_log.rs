@@ -0,0 +1,107 @@
+//! Tests for capturing raw protocol traffic with `WireLog`.
+
+#![cfg(all(unix, feature = "subprocess"))]
+
+use claude_agents_sdk::_internal::transport::{SubprocessTransport, Transport};
+use claude_agents_sdk::{ClaudeAgentOptions, Redactor, WireDirection, WireLog, REDACTED};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Write an executable script standing in for the CLI that echoes stdin.
+fn echo_cli(dir: &Path) -> PathBuf {
+    let path = dir.join("claude");
+    std::fs::write(&path, "#!/bin/sh\nexec cat\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_wire_log_captures_both_directions() {
+    let dir = tempfile::tempdir().unwrap();
+    let (wire_log, mut entries) = WireLog::channel();
+    let mut options = ClaudeAgentOptions::new().with_wire_log(wire_log);
+    options.cli_path = Some(echo_cli(dir.path()));
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+
+    transport.write(r#"{"type":"ping"}"#).await.unwrap();
+    let echoed = stdout.recv().await.unwrap().unwrap();
+    assert_eq!(echoed["type"], "ping");
+
+    let sent = entries.recv().await.unwrap();
+    let received = entries.recv().await.unwrap();
+    assert_eq!(sent.direction, WireDirection::Sent);
+    assert_eq!(received.direction, WireDirection::Received);
+    assert_eq!(sent.line, r#"{"type":"ping"}"#);
+    assert_eq!(received.line, sent.line);
+    assert!(received.timestamp_ms >= sent.timestamp_ms);
+
+    transport.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wire_log_records_unparseable_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let (wire_log, mut entries) = WireLog::channel();
+    let mut options = ClaudeAgentOptions::new().with_wire_log(wire_log);
+    options.cli_path = Some(echo_cli(dir.path()));
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+
+    transport.write("not json").await.unwrap();
+    assert!(stdout.recv().await.unwrap().is_err());
+
+    entries.recv().await.unwrap();
+    let received = entries.recv().await.unwrap();
+    assert_eq!(received.direction, WireDirection::Received);
+    assert_eq!(received.line, "not json");
+
+    transport.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wire_log_file_open_error_fails_connect() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut options = ClaudeAgentOptions::new()
+        .with_wire_log(WireLog::file(dir.path().join("missing/wire.jsonl")));
+    options.cli_path = Some(echo_cli(dir.path()));
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    assert!(transport.connect().await.is_err());
+}
+
+#[tokio::test]
+async fn test_wire_log_applies_redactor() {
+    let dir = tempfile::tempdir().unwrap();
+    let (wire_log, mut entries) = WireLog::channel();
+    let mut options = ClaudeAgentOptions::new()
+        .with_wire_log(wire_log)
+        .with_redactor(Redactor::default().with_keyword("Project Nightingale"));
+    options.cli_path = Some(echo_cli(dir.path()));
+
+    let mut transport = SubprocessTransport::new(&options).unwrap();
+    transport.connect().await.unwrap();
+    let mut stdout = transport.take_stdout_rx().unwrap();
+
+    let line = r#"{"type":"user","message":{"content":"Project Nightingale for ann@example.com"},"api_key":"k"}"#;
+    transport.write(line).await.unwrap();
+    // The application still sees the original line.
+    let echoed = stdout.recv().await.unwrap().unwrap();
+    assert_eq!(echoed["api_key"], "k");
+
+    for _ in 0..2 {
+        let entry: serde_json::Value =
+            serde_json::from_str(&entries.recv().await.unwrap().line).unwrap();
+        assert_eq!(
+            entry["message"]["content"],
+            format!("{} for {}", REDACTED, REDACTED)
+        );
+        assert_eq!(entry["api_key"], REDACTED);
+    }
+
+    transport.close().await.unwrap();
+}
@@ -0,0 +1,9 @@
+//! Generates Swift and Kotlin sources from the built library.
+//!
+//! ```bash
+//! cargo run --bin uniffi-bindgen -- generate --library target/release/libclaude_agents_sdk_uniffi.so --language swift --out-dir bindings/swift
+//! ```
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
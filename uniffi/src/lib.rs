@@ -0,0 +1,325 @@
+//! Swift and Kotlin bindings for the Claude Agents SDK.
+//!
+//! Exposes one-shot queries, a [`Client`] for interactive sessions, and the
+//! message types as plain records and enums, through
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/). JSON payloads the SDK
+//! keeps as [`serde_json::Value`], such as tool inputs and stream events,
+//! cross the boundary as JSON strings.
+//!
+//! Generate bindings from a build of this crate:
+//!
+//! ```text
+//! cargo build --release
+//! cargo run --bin uniffi-bindgen generate \
+//!     --library target/release/libclaude_agents_sdk_uniffi.so \
+//!     --language swift --out-dir bindings/swift
+//! ```
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use claude_agents_sdk::{
+    ClaudeAgentOptions, ClaudeClient, ClaudeSDKError, ContentBlock, Message as SdkMessage,
+    PermissionMode as SdkPermissionMode, UserMessageContent,
+};
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+
+uniffi::setup_scaffolding!();
+
+/// An error from the SDK.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ClaudeError {
+    /// `kind` is the error's category, such as `cli_not_found` or
+    /// `rate_limit`.
+    #[error("{message}")]
+    Sdk { kind: String, message: String },
+}
+
+impl From<ClaudeSDKError> for ClaudeError {
+    fn from(e: ClaudeSDKError) -> Self {
+        Self::Sdk {
+            kind: e.kind().as_str().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, ClaudeError>;
+
+/// How tool use is approved.
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum PermissionMode {
+    Default,
+    AcceptEdits,
+    Plan,
+    BypassPermissions,
+}
+
+impl From<PermissionMode> for SdkPermissionMode {
+    fn from(mode: PermissionMode) -> Self {
+        match mode {
+            PermissionMode::Default => Self::Default,
+            PermissionMode::AcceptEdits => Self::AcceptEdits,
+            PermissionMode::Plan => Self::Plan,
+            PermissionMode::BypassPermissions => Self::BypassPermissions,
+        }
+    }
+}
+
+/// The commonly used subset of the SDK's options.
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct Options {
+    #[uniffi(default = None)]
+    pub model: Option<String>,
+    #[uniffi(default = None)]
+    pub system_prompt: Option<String>,
+    #[uniffi(default = None)]
+    pub cwd: Option<String>,
+    #[uniffi(default = [])]
+    pub allowed_tools: Vec<String>,
+    #[uniffi(default = [])]
+    pub disallowed_tools: Vec<String>,
+    #[uniffi(default = None)]
+    pub permission_mode: Option<PermissionMode>,
+    #[uniffi(default = None)]
+    pub max_turns: Option<u32>,
+    /// Path to the CLI, when it isn't on the `PATH`.
+    #[uniffi(default = None)]
+    pub cli_path: Option<String>,
+    /// Whether to receive stream events as the response is generated.
+    #[uniffi(default = false)]
+    pub include_partial_messages: bool,
+}
+
+impl From<Options> for ClaudeAgentOptions {
+    fn from(options: Options) -> Self {
+        let mut out = ClaudeAgentOptions::new().with_allowed_tools(options.allowed_tools);
+        if let Some(model) = options.model {
+            out = out.with_model(model);
+        }
+        if let Some(prompt) = options.system_prompt {
+            out = out.with_system_prompt(prompt);
+        }
+        if let Some(cwd) = options.cwd {
+            out = out.with_cwd(cwd);
+        }
+        if let Some(mode) = options.permission_mode {
+            out = out.with_permission_mode(mode.into());
+        }
+        if let Some(turns) = options.max_turns {
+            out = out.with_max_turns(turns);
+        }
+        if options.include_partial_messages {
+            out = out.with_partial_messages();
+        }
+        out.disallowed_tools = options.disallowed_tools;
+        out.cli_path = options.cli_path.map(PathBuf::from);
+        out
+    }
+}
+
+/// A block of message content.
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum Block {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content_json: Option<String>,
+        is_error: bool,
+    },
+    /// A block these bindings have no type for, as JSON.
+    Other {
+        json: String,
+    },
+}
+
+impl From<ContentBlock> for Block {
+    fn from(block: ContentBlock) -> Self {
+        match block {
+            ContentBlock::Text(block) => Self::Text { text: block.text },
+            ContentBlock::Thinking(block) => Self::Thinking {
+                thinking: block.thinking,
+                signature: block.signature,
+            },
+            ContentBlock::ToolUse(block) => Self::ToolUse {
+                id: block.id,
+                name: block.name,
+                input_json: block.input.to_string(),
+            },
+            ContentBlock::ToolResult(block) => Self::ToolResult {
+                tool_use_id: block.tool_use_id,
+                content_json: block.content.map(|content| content.to_string()),
+                is_error: block.is_error.unwrap_or(false),
+            },
+            other => Self::Other {
+                json: to_json(&other),
+            },
+        }
+    }
+}
+
+/// A message from the CLI.
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum Message {
+    User {
+        content: Vec<Block>,
+        parent_tool_use_id: Option<String>,
+    },
+    Assistant {
+        model: String,
+        content: Vec<Block>,
+        parent_tool_use_id: Option<String>,
+    },
+    System {
+        subtype: String,
+        data_json: String,
+    },
+    Result {
+        subtype: String,
+        is_error: bool,
+        num_turns: u32,
+        session_id: String,
+        duration_ms: u64,
+        total_cost_usd: Option<f64>,
+        result: Option<String>,
+    },
+    StreamEvent {
+        session_id: String,
+        event_json: String,
+        parent_tool_use_id: Option<String>,
+    },
+    /// A message these bindings have no type for, as JSON.
+    Other {
+        json: String,
+    },
+}
+
+impl Message {
+    fn convert(message: SdkMessage) -> Result<Self> {
+        Ok(match message.into_parsed()? {
+            SdkMessage::User(user) => Self::User {
+                content: match user.content {
+                    UserMessageContent::Text(text) => vec![Block::Text { text }],
+                    UserMessageContent::Blocks(blocks) => {
+                        blocks.into_iter().map(Block::from).collect()
+                    }
+                },
+                parent_tool_use_id: user.parent_tool_use_id,
+            },
+            SdkMessage::Assistant(assistant) => Self::Assistant {
+                model: assistant.model,
+                content: assistant.content.into_iter().map(Block::from).collect(),
+                parent_tool_use_id: assistant.parent_tool_use_id,
+            },
+            SdkMessage::System(system) => Self::System {
+                subtype: system.subtype,
+                data_json: system.data.to_string(),
+            },
+            SdkMessage::Result(result) => Self::Result {
+                subtype: result.subtype,
+                is_error: result.is_error,
+                num_turns: result.num_turns,
+                session_id: result.session_id,
+                duration_ms: result.duration_ms,
+                total_cost_usd: result.total_cost_usd,
+                result: result.result,
+            },
+            SdkMessage::StreamEvent(event) => Self::StreamEvent {
+                session_id: event.session_id,
+                event_json: event.event.to_string(),
+                parent_tool_use_id: event.parent_tool_use_id,
+            },
+            other => Self::Other {
+                json: to_json(&other),
+            },
+        })
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Run a one-shot query and return every message of the response.
+#[uniffi::export(async_runtime = "tokio")]
+pub async fn query(prompt: String, options: Option<Options>) -> Result<Vec<Message>> {
+    claude_agents_sdk::query_all(&prompt, options.map(Into::into))
+        .await?
+        .into_iter()
+        .map(Message::convert)
+        .collect()
+}
+
+/// An interactive session with the CLI.
+#[derive(uniffi::Object)]
+pub struct Client {
+    inner: Mutex<ClaudeClient>,
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl Client {
+    #[uniffi::constructor(default(options = None))]
+    pub fn new(options: Option<Options>) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(ClaudeClient::new(options.map(Into::into))),
+        })
+    }
+
+    /// Start the CLI.
+    pub async fn connect(&self) -> Result<()> {
+        Ok(self.inner.lock().await.connect().await?)
+    }
+
+    /// Send a prompt.
+    pub async fn query(&self, prompt: String) -> Result<()> {
+        Ok(self.inner.lock().await.query(&prompt).await?)
+    }
+
+    /// The next message, or nothing once the session has ended.
+    pub async fn next_message(&self) -> Result<Option<Message>> {
+        let mut client = self.inner.lock().await;
+        let next = client.receive_messages().next().await;
+        next.map(|message| Message::convert(message?)).transpose()
+    }
+
+    /// Every message up to and including the result of the current query.
+    pub async fn receive_response(&self) -> Result<Vec<Message>> {
+        let mut client = self.inner.lock().await;
+        let mut messages = client.receive_messages();
+        let mut out = Vec::new();
+        while let Some(message) = messages.next().await {
+            let message = Message::convert(message?)?;
+            let done = matches!(message, Message::Result { .. });
+            out.push(message);
+            if done {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Interrupt the current query.
+    ///
+    /// Waits for a pending `next_message` or `receive_response` call to
+    /// return, so read responses you may interrupt with `next_message`.
+    pub async fn interrupt(&self) -> Result<()> {
+        Ok(self.inner.lock().await.interrupt().await?)
+    }
+
+    /// Stop the CLI.
+    pub async fn disconnect(&self) -> Result<()> {
+        Ok(self.inner.lock().await.disconnect().await?)
+    }
+}